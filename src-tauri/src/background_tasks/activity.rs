@@ -0,0 +1,237 @@
+//! Activity-based polling of background worktrees
+//!
+//! The active worktree is watched and refreshes as soon as it changes. Other
+//! worktrees are polled on an interval that depends on how active they are:
+//! a running session or a recent commit or change keeps a worktree "busy",
+//! while worktrees nobody touched in a day are only checked occasionally.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::command_audit::AuditedCommand;
+use crate::projects::git_status::{ActiveWorktreeInfo, GitBranchStatus};
+use crate::projects::storage::load_projects_data;
+
+/// Seconds between checks for due background worktrees
+const SWEEP_INTERVAL: u64 = 15;
+
+/// Most background worktrees polled per sweep, so a burst of due worktrees
+/// doesn't stall polling of the active one
+const MAX_POLLS_PER_SWEEP: usize = 4;
+
+/// Activity within this many seconds makes a worktree busy
+const BUSY_WINDOW: u64 = 15 * 60;
+
+/// Activity within this many seconds makes a worktree recently active
+const RECENT_WINDOW: u64 = 24 * 60 * 60;
+
+/// How active a background worktree is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityLevel {
+    /// Running session, or commits/changes in the last 15 minutes
+    Busy,
+    /// Activity in the last day
+    Recent,
+    Idle,
+}
+
+impl ActivityLevel {
+    /// Seconds between polls at this level
+    pub fn poll_interval(self) -> u64 {
+        match self {
+            ActivityLevel::Busy => 60,
+            ActivityLevel::Recent => 5 * 60,
+            ActivityLevel::Idle => 30 * 60,
+        }
+    }
+}
+
+/// Classify a worktree from its activity signals
+pub fn classify(now: u64, session_running: bool, last_activity_at: Option<u64>) -> ActivityLevel {
+    let age = last_activity_at.map(|at| now.saturating_sub(at));
+    if session_running || age.is_some_and(|a| a < BUSY_WINDOW) {
+        ActivityLevel::Busy
+    } else if age.is_some_and(|a| a < RECENT_WINDOW) {
+        ActivityLevel::Recent
+    } else {
+        ActivityLevel::Idle
+    }
+}
+
+/// What we know about a background worktree from previous polls
+#[derive(Debug, Default)]
+struct Tracked {
+    last_poll_at: u64,
+    last_commit_at: Option<u64>,
+    /// When the polled status last differed from the previous poll
+    last_change_at: Option<u64>,
+    fingerprint: Option<String>,
+}
+
+/// A background worktree due for a poll
+pub struct DuePoll {
+    pub info: ActiveWorktreeInfo,
+    /// Whether its project allows fetching from the remote
+    pub remote_enabled: bool,
+}
+
+/// Tracks activity of background worktrees and decides which are due
+#[derive(Default)]
+pub struct ActivityTracker {
+    worktrees: Mutex<HashMap<String, Tracked>>,
+    last_sweep_at: Mutex<u64>,
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Background worktrees due for a poll (most overdue first), or nothing if
+    /// the last sweep was too recent. `active_worktree_id` is excluded since
+    /// it is polled separately.
+    pub fn due(&self, app: &AppHandle, now: u64, active_worktree_id: Option<&str>) -> Vec<DuePoll> {
+        {
+            let mut last_sweep_at = self.last_sweep_at.lock().unwrap();
+            if now.saturating_sub(*last_sweep_at) < SWEEP_INTERVAL {
+                return vec![];
+            }
+            *last_sweep_at = now;
+        }
+
+        let data = match load_projects_data(app) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to load projects for background polling: {e}");
+                return vec![];
+            }
+        };
+        let running = running_worktree_ids(app);
+
+        let mut worktrees = self.worktrees.lock().unwrap();
+        let mut due = Vec::new();
+        for worktree in &data.worktrees {
+            if worktree.archived_at.is_some()
+                || active_worktree_id == Some(worktree.id.as_str())
+                || !std::path::Path::new(&worktree.path).exists()
+            {
+                continue;
+            }
+            let Some(project) = data.find_project(&worktree.project_id) else {
+                continue;
+            };
+            if project.polling.as_ref().is_some_and(super::in_quiet_hours) {
+                continue;
+            }
+
+            let tracked = worktrees.entry(worktree.id.clone()).or_default();
+            let last_activity_at = tracked.last_commit_at.max(tracked.last_change_at);
+            let level = classify(now, running.contains(&worktree.id), last_activity_at);
+            let overdue =
+                now.saturating_sub(tracked.last_poll_at) as i64 - level.poll_interval() as i64;
+            if overdue < 0 {
+                continue;
+            }
+
+            due.push((
+                overdue,
+                DuePoll {
+                    info: ActiveWorktreeInfo {
+                        worktree_id: worktree.id.clone(),
+                        worktree_path: worktree.path.clone(),
+                        base_branch: project.default_branch.clone(),
+                        pr_number: worktree.pr_number,
+                        pr_url: worktree.pr_url.clone(),
+                    },
+                    remote_enabled: project.polling.as_ref().is_none_or(|p| p.remote_enabled),
+                },
+            ));
+        }
+
+        // Forget worktrees that no longer exist
+        let known: HashSet<&str> = data.worktrees.iter().map(|w| w.id.as_str()).collect();
+        worktrees.retain(|id, _| known.contains(id.as_str()));
+
+        due.sort_by_key(|(overdue, _)| std::cmp::Reverse(*overdue));
+        due.into_iter()
+            .take(MAX_POLLS_PER_SWEEP)
+            .map(|(_, poll)| poll)
+            .collect()
+    }
+
+    /// Record the result of polling a background worktree
+    pub fn record(&self, info: &ActiveWorktreeInfo, now: u64, status: Option<&GitBranchStatus>) {
+        let last_commit_at = last_commit_time(&info.worktree_path);
+        let mut worktrees = self.worktrees.lock().unwrap();
+        let tracked = worktrees.entry(info.worktree_id.clone()).or_default();
+        tracked.last_poll_at = now;
+        tracked.last_commit_at = last_commit_at;
+
+        if let Some(status) = status {
+            let fingerprint = format!(
+                "{}:{}:{}:{}:{}",
+                status.current_branch,
+                status.ahead_count,
+                status.behind_count,
+                status.uncommitted_added,
+                status.uncommitted_removed
+            );
+            // The first poll only establishes a baseline
+            if tracked
+                .fingerprint
+                .as_ref()
+                .is_some_and(|previous| *previous != fingerprint)
+            {
+                tracked.last_change_at = Some(now);
+            }
+            tracked.fingerprint = Some(fingerprint);
+        }
+    }
+}
+
+/// Worktrees with a running Claude session
+pub(super) fn running_worktree_ids(app: &AppHandle) -> HashSet<String> {
+    let sessions = crate::chat::registry::get_running_sessions();
+    if sessions.is_empty() {
+        return HashSet::new();
+    }
+    sessions
+        .iter()
+        .filter_map(|id| crate::chat::storage::load_metadata(app, id).ok().flatten())
+        .map(|metadata| metadata.worktree_id)
+        .collect()
+}
+
+/// Unix timestamp of the HEAD commit
+fn last_commit_time(repo_path: &str) -> Option<u64> {
+    let output = crate::platform::silent_command("git")
+        .args(["log", "-1", "--format=%ct"])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_activity() {
+        let now = 1_000_000;
+        assert_eq!(classify(now, true, None), ActivityLevel::Busy);
+        assert_eq!(classify(now, false, Some(now - 60)), ActivityLevel::Busy);
+        assert_eq!(
+            classify(now, false, Some(now - 3600)),
+            ActivityLevel::Recent
+        );
+        assert_eq!(
+            classify(now, false, Some(now - 3 * 24 * 3600)),
+            ActivityLevel::Idle
+        );
+        assert_eq!(classify(now, false, None), ActivityLevel::Idle);
+    }
+}
@@ -0,0 +1,300 @@
+//! Opt-in automatic updates from the base branch
+//!
+//! When a poll finds that origin's base branch moved ahead of a worktree, the
+//! worktree's branch can be rebased onto it (or have it merged in) without
+//! user action, then pushed (with lease, for rebases) if the branch has an
+//! upstream. Updates only run on clean worktrees with no running session, and
+//! only when `git merge-tree` predicts no conflicts.
+//!
+//! Outcomes are emitted as `git:auto-update`. Conflicts carry the conflicting
+//! files so the UI can hand them to the conflict resolution flow; each base
+//! commit is only attempted once per worktree, so a conflict isn't retried
+//! (or reported) on every poll.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+use crate::projects::git_status::{ActiveWorktreeInfo, GitBranchStatus};
+
+/// How a worktree is updated when its base branch moves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoUpdateMode {
+    #[default]
+    Off,
+    /// Rebase onto origin/{base} and push with lease
+    Rebase,
+    /// Merge origin/{base} into the branch and push
+    Merge,
+}
+
+impl AutoUpdateMode {
+    /// Parse the `auto_update_from_base` preference (unknown values mean off)
+    pub fn from_preference(value: &str) -> Self {
+        match value {
+            "rebase" => AutoUpdateMode::Rebase,
+            "merge" => AutoUpdateMode::Merge,
+            _ => AutoUpdateMode::Off,
+        }
+    }
+}
+
+/// Result of an automatic update
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AutoUpdateOutcome {
+    Updated {
+        pushed: bool,
+    },
+    /// The update would conflict (or did, and was aborted)
+    Conflict {
+        conflicting_files: Vec<String>,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// Payload of the `git:auto-update` event
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoUpdateEvent {
+    pub worktree_id: String,
+    pub branch: String,
+    pub base_branch: String,
+    pub mode: AutoUpdateMode,
+    #[serde(flatten)]
+    pub outcome: AutoUpdateOutcome,
+}
+
+/// Current mode (mirrors the `auto_update_from_base` preference)
+static MODE: Lazy<Mutex<AutoUpdateMode>> = Lazy::new(|| Mutex::new(AutoUpdateMode::Off));
+
+/// Base commit last attempted per worktree
+static ATTEMPTED: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Update the mode (called when preferences load or change)
+pub fn set_mode(mode: AutoUpdateMode) {
+    *MODE.lock().unwrap() = mode;
+}
+
+fn mode() -> AutoUpdateMode {
+    *MODE.lock().unwrap()
+}
+
+/// Update the worktree from its base branch if enabled and safe, after a
+/// poll produced `status`. `push` is false while remote access is disabled
+/// or paused. Returns true if the branch changed, so the caller can re-poll.
+pub fn maybe_update(
+    app: &AppHandle,
+    info: &ActiveWorktreeInfo,
+    status: &GitBranchStatus,
+    push: bool,
+) -> bool {
+    let mode = mode();
+    if mode == AutoUpdateMode::Off
+        || status.behind_count == 0
+        || status.current_branch == status.base_branch
+        || crate::projects::git::has_uncommitted_changes(&info.worktree_path)
+        || super::activity::running_worktree_ids(app).contains(&info.worktree_id)
+    {
+        return false;
+    }
+
+    let repo_path = info.worktree_path.as_str();
+    let origin_ref = format!("origin/{}", info.base_branch);
+    let Some(base_commit) = rev_parse(repo_path, &origin_ref) else {
+        return false;
+    };
+    {
+        let mut attempted = ATTEMPTED.lock().unwrap();
+        if attempted.get(&info.worktree_id) == Some(&base_commit) {
+            return false;
+        }
+        attempted.insert(info.worktree_id.clone(), base_commit);
+    }
+
+    log::info!(
+        "Auto-updating {} from {origin_ref} ({mode:?})",
+        status.current_branch
+    );
+    let outcome = match predict_conflicts(repo_path, &origin_ref) {
+        Some(conflicting_files) if !conflicting_files.is_empty() => {
+            AutoUpdateOutcome::Conflict { conflicting_files }
+        }
+        _ => update(repo_path, &origin_ref, &status.current_branch, mode, push),
+    };
+
+    let changed = matches!(outcome, AutoUpdateOutcome::Updated { .. });
+    if let AutoUpdateOutcome::Conflict { conflicting_files } = &outcome {
+        notify_conflict(app, &status.current_branch, &origin_ref, conflicting_files);
+    }
+    let event = AutoUpdateEvent {
+        worktree_id: info.worktree_id.clone(),
+        branch: status.current_branch.clone(),
+        base_branch: info.base_branch.clone(),
+        mode,
+        outcome,
+    };
+    if let Err(e) = app.emit_all("git:auto-update", &event) {
+        log::error!("Failed to emit git:auto-update event: {e}");
+    }
+    changed
+}
+
+fn update(
+    repo_path: &str,
+    origin_ref: &str,
+    branch: &str,
+    mode: AutoUpdateMode,
+    push: bool,
+) -> AutoUpdateOutcome {
+    let _queued = crate::projects::git_queue::lock(repo_path);
+    let (args, abort): (Vec<&str>, [&str; 2]) = match mode {
+        AutoUpdateMode::Rebase => (vec!["rebase", origin_ref], ["rebase", "--abort"]),
+        _ => (vec!["merge", "--no-edit", origin_ref], ["merge", "--abort"]),
+    };
+    let output = match git(repo_path, &args) {
+        Ok(output) => output,
+        Err(message) => return AutoUpdateOutcome::Failed { message },
+    };
+    if !output.status.success() {
+        let conflicting_files = git(repo_path, &["diff", "--name-only", "--diff-filter=U"])
+            .map(|o| lines(&o.stdout))
+            .unwrap_or_default();
+        let _ = git(repo_path, &abort);
+        if conflicting_files.is_empty() {
+            return AutoUpdateOutcome::Failed {
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            };
+        }
+        return AutoUpdateOutcome::Conflict { conflicting_files };
+    }
+
+    // Only push branches that are already on the remote
+    let upstream = format!("origin/{branch}");
+    if !push || rev_parse(repo_path, &upstream).is_none() {
+        return AutoUpdateOutcome::Updated { pushed: false };
+    }
+    let push_args: &[&str] = match mode {
+        AutoUpdateMode::Rebase => &["push", "--force-with-lease"],
+        _ => &["push"],
+    };
+    match git(repo_path, push_args) {
+        Ok(output) if output.status.success() => AutoUpdateOutcome::Updated { pushed: true },
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log::warn!("Auto-update push failed for {branch}: {stderr}");
+            AutoUpdateOutcome::Updated { pushed: false }
+        }
+        Err(e) => {
+            log::warn!("Auto-update push failed for {branch}: {e}");
+            AutoUpdateOutcome::Updated { pushed: false }
+        }
+    }
+}
+
+/// Files that would conflict when combining HEAD with `origin_ref`, or None
+/// if this git is too old for `merge-tree --write-tree` (2.38+)
+fn predict_conflicts(repo_path: &str, origin_ref: &str) -> Option<Vec<String>> {
+    let output = git(
+        repo_path,
+        &[
+            "merge-tree",
+            "--write-tree",
+            "--name-only",
+            "--no-messages",
+            "HEAD",
+            origin_ref,
+        ],
+    )
+    .ok()?;
+    // Exit code 1 means conflicts; anything else is an error
+    match output.status.code() {
+        Some(0) => Some(vec![]),
+        Some(1) => Some(parse_merge_tree_conflicts(&output.stdout)),
+        _ => None,
+    }
+}
+
+/// Conflicting files from `merge-tree --name-only` output (the first line is
+/// the resulting tree)
+fn parse_merge_tree_conflicts(stdout: &[u8]) -> Vec<String> {
+    lines(stdout).into_iter().skip(1).collect()
+}
+
+fn lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn rev_parse(repo_path: &str, rev: &str) -> Option<String> {
+    git(repo_path, &["rev-parse", "--verify", "--quiet", rev])
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+fn git(repo_path: &str, args: &[&str]) -> Result<std::process::Output, String> {
+    silent_command("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))
+}
+
+fn notify_conflict(app: &AppHandle, branch: &str, origin_ref: &str, files: &[String]) {
+    #[cfg(not(mobile))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("Couldn't update {branch} from {origin_ref}"))
+            .body(format!(
+                "Conflicts in {} file(s): {}",
+                files.len(),
+                files.join(", ")
+            ))
+            .show()
+        {
+            log::error!("Failed to show auto-update notification: {e}");
+        }
+    }
+    #[cfg(mobile)]
+    let _ = (app, branch, origin_ref, files);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_merge_tree_conflicts() {
+        let stdout = b"4b825dc642cb6eb9a060e54bf8d69288fbee4904\nsrc/main.rs\nREADME.md\n";
+        assert_eq!(
+            parse_merge_tree_conflicts(stdout),
+            vec!["src/main.rs".to_string(), "README.md".to_string()]
+        );
+        assert!(parse_merge_tree_conflicts(b"4b825dc642cb\n").is_empty());
+        assert_eq!(
+            AutoUpdateMode::from_preference("rebase"),
+            AutoUpdateMode::Rebase
+        );
+        assert_eq!(
+            AutoUpdateMode::from_preference("bogus"),
+            AutoUpdateMode::Off
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! CI failure watcher
+//!
+//! When a worktree's cached check status flips to failing, the failing checks
+//! and their GitHub Actions logs are gathered and turned into a fix-CI prompt
+//! (the `fix_ci` magic prompt, followed by the logs). Depending on the
+//! `ci_failure_action` preference this is offered with a notification, or a
+//! "Fix CI" session is created for it right away.
+//!
+//! Either way the result is emitted as `ci:failure-detected`; sending the
+//! prompt is left to the frontend, which owns model and mode selection.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+
+/// Most log bytes kept per failing job (the end of the log is kept)
+const MAX_LOG_BYTES_PER_JOB: usize = 12_000;
+
+/// Most failing jobs whose logs are included
+const MAX_JOB_LOGS: usize = 3;
+
+/// What happens when a PR's checks start failing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiFailureAction {
+    Off,
+    /// Notify and let the user start the session
+    Offer,
+    /// Create the session immediately
+    Auto,
+}
+
+impl CiFailureAction {
+    /// Parse the `ci_failure_action` preference (unknown values mean offer)
+    pub fn from_preference(value: &str) -> Self {
+        match value {
+            "off" => CiFailureAction::Off,
+            "auto" => CiFailureAction::Auto,
+            _ => CiFailureAction::Offer,
+        }
+    }
+}
+
+/// Whether a cached check status counts as failing
+pub fn is_failing(check_status: Option<&str>) -> bool {
+    matches!(check_status, Some("failure") | Some("error"))
+}
+
+/// A failing check of the PR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailingCheck {
+    pub name: String,
+    #[serde(default)]
+    pub link: String,
+}
+
+/// Payload of `ci:failure-detected`
+#[derive(Debug, Clone, Serialize)]
+pub struct CiFailureEvent {
+    pub worktree_id: String,
+    pub pr_number: u32,
+    pub failing_checks: Vec<FailingCheck>,
+    /// Fix-CI prompt, including the failing logs
+    pub prompt: String,
+    /// Model configured for the fix-CI prompt
+    pub model: String,
+    /// Session created for the fix (auto mode only)
+    pub session_id: Option<String>,
+}
+
+/// React to a worktree's checks starting to fail (called when the cached
+/// check status flips to failing)
+pub async fn on_checks_failed(app: AppHandle, worktree_id: String) -> Result<(), String> {
+    let preferences = crate::load_preferences(app.clone()).await?;
+    let action = CiFailureAction::from_preference(&preferences.ci_failure_action);
+    if action == CiFailureAction::Off {
+        return Ok(());
+    }
+
+    let data = crate::projects::storage::load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?
+        .clone();
+    let Some(pr_number) = worktree.pr_number else {
+        return Ok(());
+    };
+
+    let gh = crate::gh_cli::config::resolve_gh_binary(&app);
+    let failing_checks = failing_checks(&gh, &worktree.path, pr_number)?;
+    if failing_checks.is_empty() {
+        return Ok(());
+    }
+    log::info!(
+        "CI failing for PR #{pr_number} ({} check(s)) in {}",
+        failing_checks.len(),
+        worktree.name
+    );
+
+    let logs = failing_logs(&gh, &worktree.path, &failing_checks);
+    let prompt = build_prompt(
+        &preferences.magic_prompts.fix_ci,
+        pr_number,
+        &failing_checks,
+        &logs,
+    );
+
+    let session_id = if action == CiFailureAction::Auto {
+        let session = crate::chat::create_session(
+            app.clone(),
+            worktree.id.clone(),
+            worktree.path.clone(),
+            Some("Fix CI".to_string()),
+        )
+        .await?;
+        Some(session.id)
+    } else {
+        None
+    };
+
+    notify(
+        &app,
+        &worktree.name,
+        pr_number,
+        &failing_checks,
+        session_id.is_some(),
+    );
+    let event = CiFailureEvent {
+        worktree_id,
+        pr_number,
+        failing_checks,
+        prompt,
+        model: preferences.magic_prompt_models.fix_ci_model.clone(),
+        session_id,
+    };
+    app.emit_all("ci:failure-detected", &event)
+        .map_err(|e| format!("Failed to emit ci:failure-detected event: {e}"))
+}
+
+#[derive(Deserialize)]
+struct CheckEntry {
+    name: String,
+    #[serde(default)]
+    bucket: String,
+    #[serde(default)]
+    link: String,
+}
+
+/// Checks of a PR (`gh pr checks --json <fields>`), parsed as `T`
+pub(crate) fn pr_checks<T: DeserializeOwned>(
+    gh: &std::path::Path,
+    repo_path: &str,
+    pr_number: u32,
+    fields: &str,
+) -> Result<Vec<T>, String> {
+    // `gh pr checks` exits non-zero when checks fail or are pending, so only
+    // stdout matters
+    let output = silent_command(gh)
+        .args(["pr", "checks", &pr_number.to_string(), "--json", fields])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run gh pr checks: {e}"))?;
+    serde_json::from_slice(&output.stdout).map_err(|e| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("Failed to parse gh pr checks output: {e} {}", stderr.trim())
+    })
+}
+
+pub(crate) fn failing_checks(
+    gh: &std::path::Path,
+    repo_path: &str,
+    pr_number: u32,
+) -> Result<Vec<FailingCheck>, String> {
+    let checks: Vec<CheckEntry> = pr_checks(gh, repo_path, pr_number, "name,bucket,link")?;
+    Ok(checks
+        .into_iter()
+        .filter(|c| c.bucket == "fail")
+        .map(|c| FailingCheck {
+            name: c.name,
+            link: c.link,
+        })
+        .collect())
+}
+
+/// GitHub Actions job ID from a check link (`.../actions/runs/<run>/job/<job>`)
+pub(crate) fn job_id(link: &str) -> Option<&str> {
+    let (_, rest) = link.split_once("/actions/runs/")?;
+    let (_, job) = rest.split_once("/job/")?;
+    let job = job.split(['/', '?', '#']).next()?;
+    (!job.is_empty() && job.bytes().all(|b| b.is_ascii_digit())).then_some(job)
+}
+
+/// Logs of the failed steps of failing Actions jobs, as (check name, log).
+/// Shared with `investigate_ci_failure`.
+pub(crate) fn failing_logs(
+    gh: &std::path::Path,
+    repo_path: &str,
+    checks: &[FailingCheck],
+) -> Vec<(String, String)> {
+    checks
+        .iter()
+        .filter_map(|check| Some((check, job_id(&check.link)?)))
+        .take(MAX_JOB_LOGS)
+        .filter_map(|(check, job)| {
+            let output = silent_command(gh)
+                .args(["run", "view", "--job", job, "--log-failed"])
+                .current_dir(repo_path)
+                .output_audited()
+                .ok()
+                .filter(|o| o.status.success())?;
+            let log = String::from_utf8_lossy(&output.stdout).to_string();
+            Some((check.name.clone(), tail(&log, MAX_LOG_BYTES_PER_JOB)))
+        })
+        .collect()
+}
+
+/// The last `max_bytes` of `text`, cut at a line boundary
+pub(crate) fn tail(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let rest = &text[start..];
+    let rest = rest.split_once('\n').map_or(rest, |(_, after)| after);
+    format!("[... earlier output truncated ...]\n{rest}")
+}
+
+/// Fill in a CI prompt template (`{prNumber}`, `{failingChecks}`) and append
+/// the failing logs. Shared with `investigate_ci_failure`.
+pub(crate) fn build_prompt(
+    template: &str,
+    pr_number: u32,
+    checks: &[FailingCheck],
+    logs: &[(String, String)],
+) -> String {
+    let names = checks
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut prompt = template
+        .replace("{prNumber}", &pr_number.to_string())
+        .replace("{failingChecks}", &names);
+    if logs.is_empty() {
+        prompt.push_str(
+            "\n\nNo logs could be retrieved. Use `gh pr checks` and `gh run view --log-failed` to inspect the failures.",
+        );
+    }
+    for (name, log) in logs {
+        prompt.push_str(&format!(
+            "\n\n<ci-log check=\"{name}\">\n{}\n</ci-log>",
+            log.trim_end()
+        ));
+    }
+    prompt
+}
+
+fn notify(
+    app: &AppHandle,
+    worktree_name: &str,
+    pr_number: u32,
+    checks: &[FailingCheck],
+    session_started: bool,
+) {
+    #[cfg(not(mobile))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        let names = checks
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let body = if session_started {
+            format!("{names}. Started a Fix CI session in {worktree_name}.")
+        } else {
+            format!("{names}. Open {worktree_name} to investigate.")
+        };
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("CI failing on PR #{pr_number}"))
+            .body(body)
+            .show()
+        {
+            log::error!("Failed to show CI failure notification: {e}");
+        }
+    }
+    #[cfg(mobile)]
+    let _ = (app, worktree_name, pr_number, checks, session_started);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_id_from_link() {
+        assert_eq!(
+            job_id("https://github.com/o/r/actions/runs/123/job/456"),
+            Some("456")
+        );
+        assert_eq!(
+            job_id("https://github.com/o/r/actions/runs/123/job/456?pr=7"),
+            Some("456")
+        );
+        assert_eq!(job_id("https://circleci.com/gh/o/r/789"), None);
+    }
+
+    #[test]
+    fn test_build_prompt() {
+        let checks = vec![
+            FailingCheck {
+                name: "test".to_string(),
+                link: String::new(),
+            },
+            FailingCheck {
+                name: "lint".to_string(),
+                link: String::new(),
+            },
+        ];
+        let logs = vec![("test".to_string(), "error: boom\n".to_string())];
+        let prompt = build_prompt("PR #{prNumber}: {failingChecks}", 7, &checks, &logs);
+        assert!(prompt.starts_with("PR #7: test, lint"));
+        assert!(prompt.ends_with("<ci-log check=\"test\">\nerror: boom\n</ci-log>"));
+
+        let prompt = build_prompt("PR #{prNumber}", 7, &checks, &[]);
+        assert!(prompt.contains("No logs could be retrieved"));
+    }
+
+    #[test]
+    fn test_tail_keeps_end_of_log() {
+        let log = "first line\nsecond line\nerror: boom\n";
+        assert_eq!(tail(log, 100), log);
+        let tailed = tail(log, 18);
+        assert!(tailed.ends_with("error: boom\n"));
+        assert!(!tailed.contains("first"));
+    }
+}
@@ -1,23 +1,33 @@
 //! Tauri commands for controlling background tasks
 
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use super::power::PollingStatus;
+use super::scheduled_review::{self, ScheduledReviews};
+use super::scheduler::{self, JobRun, ScheduledJob, ScheduledJobInput};
+use super::time_tracking::{self, TimeRange, TimeReport};
 use super::{
     BackgroundTaskManager, MAX_POLL_INTERVAL, MAX_REMOTE_POLL_INTERVAL, MIN_POLL_INTERVAL,
     MIN_REMOTE_POLL_INTERVAL,
 };
 use crate::projects::git_status::ActiveWorktreeInfo;
+use crate::projects::storage::with_projects_data_mut;
+use crate::projects::types::{PollingOverrides, Project};
 
 /// Set the application focus state
 ///
 /// This controls whether background polling is active.
 /// Polling only occurs when the application is focused.
+/// Losing focus also flushes any pending UI state write.
 #[tauri::command]
 pub fn set_app_focus_state(
     state: State<'_, BackgroundTaskManager>,
     focused: bool,
 ) -> Result<(), String> {
     state.set_focused(focused);
+    if !focused {
+        crate::ui_state::flush();
+    }
     Ok(())
 }
 
@@ -52,17 +62,40 @@ pub fn set_active_worktree_for_polling(
 ///
 /// The interval must be between 10 and 600 seconds (10 seconds to 10 minutes).
 /// Values outside this range will be clamped.
+/// With `project_id`, only that project's interval is overridden.
 #[tauri::command]
 pub fn set_git_poll_interval(
+    app: AppHandle,
     state: State<'_, BackgroundTaskManager>,
     seconds: u64,
+    project_id: Option<String>,
 ) -> Result<(), String> {
     if !(MIN_POLL_INTERVAL..=MAX_POLL_INTERVAL).contains(&seconds) {
         log::warn!(
             "Git poll interval {seconds} out of range, will be clamped to {MIN_POLL_INTERVAL}-{MAX_POLL_INTERVAL}"
         );
     }
-    state.set_poll_interval(seconds);
+    let Some(project_id) = project_id else {
+        state.set_poll_interval(seconds);
+        return Ok(());
+    };
+
+    with_projects_data_mut(&app, |data| {
+        let project = data
+            .find_project_mut(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
+        let mut overrides = project.polling.clone().unwrap_or_default();
+        overrides.local_interval = Some(seconds.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL));
+        log::trace!(
+            "Setting git poll interval for project {} to {:?}",
+            project.name,
+            overrides.local_interval
+        );
+        project.polling = Some(overrides);
+        Ok(())
+    })?;
+
+    state.refresh_project_overrides();
     Ok(())
 }
 
@@ -118,3 +151,123 @@ pub fn trigger_immediate_remote_poll(
     state.trigger_immediate_remote_poll();
     Ok(())
 }
+
+/// Get the battery/network state affecting remote polling
+///
+/// Changes are also emitted as `polling:status-changed` events.
+#[tauri::command]
+pub fn get_polling_status(
+    app: AppHandle,
+    state: State<'_, BackgroundTaskManager>,
+) -> Result<PollingStatus, String> {
+    Ok(state.polling_status(&app))
+}
+
+/// Pause or resume all background polling (also toggled from the tray)
+///
+/// Changes are emitted as `polling:paused-changed` events.
+#[tauri::command]
+pub fn set_polling_paused(
+    state: State<'_, BackgroundTaskManager>,
+    paused: bool,
+) -> Result<(), String> {
+    state.set_paused(paused);
+    Ok(())
+}
+
+/// Override the polling intervals for a project
+///
+/// Intervals are clamped to the same ranges as the global ones; `None`
+/// intervals fall back to the global setting. Set `remote_enabled` to false
+/// to skip fetching and PR status checks (e.g. repos without a remote), and
+/// `quiet_hours` to stop scheduled polling during part of the day.
+/// Pass `None` to clear the overrides.
+#[tauri::command]
+pub fn set_project_poll_overrides(
+    app: AppHandle,
+    state: State<'_, BackgroundTaskManager>,
+    project_id: String,
+    overrides: Option<PollingOverrides>,
+) -> Result<Project, String> {
+    if let Some(quiet_hours) = overrides.as_ref().and_then(|o| o.quiet_hours.as_ref()) {
+        quiet_hours.validate()?;
+    }
+    let overrides = overrides
+        .map(|o| PollingOverrides {
+            local_interval: o
+                .local_interval
+                .map(|s| s.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL)),
+            remote_interval: o
+                .remote_interval
+                .map(|s| s.clamp(MIN_REMOTE_POLL_INTERVAL, MAX_REMOTE_POLL_INTERVAL)),
+            remote_enabled: o.remote_enabled,
+            quiet_hours: o.quiet_hours,
+        })
+        .filter(|o| *o != PollingOverrides::default());
+
+    let updated_project = with_projects_data_mut(&app, |data| {
+        let project = data
+            .find_project_mut(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
+        log::trace!(
+            "Setting polling overrides for project {}: {overrides:?}",
+            project.name
+        );
+        project.polling = overrides;
+        Ok(project.clone())
+    })?;
+
+    state.refresh_project_overrides();
+    Ok(updated_project)
+}
+
+/// List scheduled jobs with their next run time
+#[tauri::command]
+pub fn list_scheduled_jobs(app: AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    scheduler::list_jobs(&app)
+}
+
+/// Create a scheduled job, or update it when `job.id` is set
+///
+/// The schedule is a 5-field cron expression in local time.
+#[tauri::command]
+pub fn save_scheduled_job(app: AppHandle, job: ScheduledJobInput) -> Result<ScheduledJob, String> {
+    scheduler::upsert_job(&app, job)
+}
+
+/// Delete a scheduled job and its run history
+#[tauri::command]
+pub fn delete_scheduled_job(app: AppHandle, job_id: String) -> Result<(), String> {
+    scheduler::delete_job(&app, &job_id)
+}
+
+/// Run a scheduled job now, outside its schedule
+///
+/// Returns false if the job is already running.
+#[tauri::command]
+pub fn run_scheduled_job_now(app: AppHandle, job_id: String) -> Result<bool, String> {
+    let job = scheduler::get_job(&app, &job_id)?;
+    Ok(scheduler::spawn_run(&app, job, true))
+}
+
+/// Get the run history of a scheduled job, newest first
+#[tauri::command]
+pub fn get_scheduled_job_runs(
+    app: AppHandle,
+    job_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<JobRun>, String> {
+    scheduler::list_runs(&app, &job_id, limit.unwrap_or(20))
+}
+
+/// Get the active time per project, worktree and session in a range
+#[tauri::command]
+pub fn get_time_report(app: AppHandle, range: TimeRange) -> Result<TimeReport, String> {
+    time_tracking::report(&app, range)
+}
+
+/// Get the cached results of scheduled AI reviews, per worktree
+#[tauri::command]
+pub fn get_scheduled_reviews(app: AppHandle) -> Result<ScheduledReviews, String> {
+    scheduled_review::load(&app)
+}
@@ -0,0 +1,316 @@
+//! Daily activity digest
+//!
+//! A scheduled job action that sums up the last day: session runs and their
+//! cost, commits by the user, and PRs opened and merged. The digest is shown
+//! as a native notification and/or written as markdown to
+//! `<app data>/digests/<date>.md` (or a directory of the user's choosing).
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use crate::chat::storage::{list_all_session_ids, load_metadata};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+use crate::projects::storage::load_projects_data;
+use crate::projects::types::{Project, ProjectsData};
+
+/// Period a digest covers
+const DIGEST_PERIOD_SECS: u64 = 24 * 60 * 60;
+
+/// Activity of one project in a digest
+#[derive(Debug, Default)]
+pub struct ProjectActivity {
+    pub sessions: HashSet<String>,
+    pub runs: usize,
+    pub cost_usd: f64,
+    pub commits: Vec<String>,
+    pub prs_opened: Vec<String>,
+    pub prs_merged: Vec<String>,
+}
+
+/// Activity per project name
+#[derive(Debug, Default)]
+pub struct Digest {
+    pub since: u64,
+    pub until: u64,
+    pub projects: BTreeMap<String, ProjectActivity>,
+}
+
+impl Digest {
+    fn total<T: std::iter::Sum<T>>(&self, f: impl Fn(&ProjectActivity) -> T) -> T {
+        self.projects.values().map(f).sum()
+    }
+
+    /// One-line summary for the notification
+    pub fn summary(&self) -> String {
+        let runs: usize = self.total(|p| p.runs);
+        let sessions: usize = self.total(|p| p.sessions.len());
+        let commits: usize = self.total(|p| p.commits.len());
+        let opened: usize = self.total(|p| p.prs_opened.len());
+        let merged: usize = self.total(|p| p.prs_merged.len());
+        let cost: f64 = self.total(|p| p.cost_usd);
+        format!(
+            "{runs} runs in {sessions} sessions, {commits} commits, {opened} PRs opened, {merged} merged, ${cost:.2}"
+        )
+    }
+
+    /// The digest as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let day = format_time(self.until, "%A %Y-%m-%d");
+        let mut content = format!("# Jean digest: {day}\n\n");
+        content.push_str(&format!(
+            "{} to {}\n\n**{}**\n",
+            format_time(self.since, "%Y-%m-%d %H:%M"),
+            format_time(self.until, "%Y-%m-%d %H:%M"),
+            self.summary()
+        ));
+        if self.projects.is_empty() {
+            content.push_str("\nNo activity.\n");
+        }
+        for (name, activity) in &self.projects {
+            content.push_str(&format!("\n## {name}\n\n"));
+            content.push_str(&format!(
+                "- Sessions: {} ({} runs, ${:.2})\n",
+                activity.sessions.len(),
+                activity.runs,
+                activity.cost_usd
+            ));
+            let sections = [
+                ("Commits", &activity.commits),
+                ("PRs opened", &activity.prs_opened),
+                ("PRs merged", &activity.prs_merged),
+            ];
+            for (title, items) in sections {
+                if items.is_empty() {
+                    continue;
+                }
+                content.push_str(&format!("\n### {title}\n\n"));
+                for item in items {
+                    content.push_str(&format!("- {item}\n"));
+                }
+            }
+        }
+        content
+    }
+}
+
+fn format_time(secs: u64, format: &str) -> String {
+    Local
+        .timestamp_opt(secs as i64, 0)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// Session runs (and their cost) per project
+fn collect_sessions(
+    app: &AppHandle,
+    digest: &mut Digest,
+    data: &ProjectsData,
+    projects: &[&Project],
+) -> Result<(), String> {
+    for session_id in list_all_session_ids(app)? {
+        let Ok(Some(metadata)) = load_metadata(app, &session_id) else {
+            continue;
+        };
+        let Some(project) = data
+            .find_worktree(&metadata.worktree_id)
+            .and_then(|w| projects.iter().find(|p| p.id == w.project_id))
+        else {
+            continue;
+        };
+        for run in metadata
+            .runs
+            .iter()
+            .filter(|r| r.started_at >= digest.since && r.started_at < digest.until)
+        {
+            let activity = digest.projects.entry(project.name.clone()).or_default();
+            activity.sessions.insert(metadata.id.clone());
+            activity.runs += 1;
+            activity.cost_usd += run.usage.as_ref().and_then(|u| u.cost_usd).unwrap_or(0.0);
+        }
+    }
+    Ok(())
+}
+
+/// Commits by the repository's git user in the period, on any branch
+fn commits(project: &Project, since: u64, until: u64) -> Vec<String> {
+    let git = |args: &[&str]| {
+        silent_command("git")
+            .args(args)
+            .current_dir(&project.path)
+            .output_audited()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let Some(email) = git(&["config", "user.email"]).filter(|e| !e.is_empty()) else {
+        return Vec::new();
+    };
+    let since = format!("--since=@{since}");
+    let until = format!("--until=@{until}");
+    let author = format!("--author={email}");
+    git(&[
+        "log",
+        "--all",
+        "--no-merges",
+        &since,
+        &until,
+        &author,
+        "--format=%h %s",
+    ])
+    .map(|out| out.lines().map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrEntry {
+    number: u32,
+    title: String,
+    created_at: Option<String>,
+    merged_at: Option<String>,
+}
+
+/// PRs by the user opened and merged in the period
+fn prs(app: &AppHandle, project: &Project, since: u64, until: u64) -> (Vec<String>, Vec<String>) {
+    let gh = crate::gh_cli::config::resolve_gh_binary(app);
+    let output = silent_command(&gh)
+        .args([
+            "pr",
+            "list",
+            "--author",
+            "@me",
+            "--state",
+            "all",
+            "--limit",
+            "50",
+            "--json",
+            "number,title,createdAt,mergedAt",
+        ])
+        .current_dir(&project.path)
+        .output_audited();
+    let prs: Vec<PrEntry> = match output {
+        Ok(o) if o.status.success() => serde_json::from_slice(&o.stdout).unwrap_or_default(),
+        _ => return (Vec::new(), Vec::new()),
+    };
+    let in_period = |t: &Option<String>| {
+        t.as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|t| (since as i64..until as i64).contains(&t.timestamp()))
+    };
+    let label = |pr: &PrEntry| format!("#{} {}", pr.number, pr.title);
+    (
+        prs.iter()
+            .filter(|pr| in_period(&pr.created_at))
+            .map(label)
+            .collect(),
+        prs.iter()
+            .filter(|pr| in_period(&pr.merged_at))
+            .map(label)
+            .collect(),
+    )
+}
+
+/// Compile the digest of the day before `until`, for one project or all
+pub fn collect(app: &AppHandle, until: u64, project_id: Option<&str>) -> Result<Digest, String> {
+    let data = load_projects_data(app)?;
+    let projects: Vec<&Project> = data
+        .projects
+        .iter()
+        .filter(|p| {
+            !p.is_folder
+                && match project_id {
+                    Some(id) => p.id == id,
+                    None => true,
+                }
+        })
+        .collect();
+
+    let mut digest = Digest {
+        since: until.saturating_sub(DIGEST_PERIOD_SECS),
+        until,
+        ..Default::default()
+    };
+    collect_sessions(app, &mut digest, &data, &projects)?;
+
+    for project in projects {
+        let commits = commits(project, digest.since, digest.until);
+        let (opened, merged) = prs(app, project, digest.since, digest.until);
+        if commits.is_empty() && opened.is_empty() && merged.is_empty() {
+            continue;
+        }
+        let activity = digest.projects.entry(project.name.clone()).or_default();
+        activity.commits = commits;
+        activity.prs_opened = opened;
+        activity.prs_merged = merged;
+    }
+    Ok(digest)
+}
+
+/// Write the digest as markdown, returning the file path
+pub fn export(app: &AppHandle, digest: &Digest, dir: Option<&str>) -> Result<PathBuf, String> {
+    let dir = match dir.filter(|d| !d.trim().is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::locations::app_data_dir(app)?.join("digests"),
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create digest directory: {e}"))?;
+    let path = dir.join(format!("{}.md", format_time(digest.until, "%Y-%m-%d")));
+    std::fs::write(&path, digest.to_markdown())
+        .map_err(|e| format!("Failed to write digest: {e}"))?;
+    Ok(path)
+}
+
+/// Show the digest summary as a native notification
+pub fn notify(app: &AppHandle, digest: &Digest) {
+    #[cfg(not(mobile))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title("Jean daily digest")
+            .body(digest.summary())
+            .show()
+        {
+            log::error!("Failed to show digest notification: {e}");
+        }
+    }
+    #[cfg(mobile)]
+    let _ = (app, digest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_markdown() {
+        let mut digest = Digest {
+            since: 1_700_000_000,
+            until: 1_700_086_400,
+            ..Default::default()
+        };
+        let activity = digest.projects.entry("api".to_string()).or_default();
+        activity.sessions.insert("s1".to_string());
+        activity.runs = 3;
+        activity.cost_usd = 1.5;
+        activity.commits = vec!["abc1234 Fix login".to_string()];
+        activity.prs_merged = vec!["#42 Fix login".to_string()];
+
+        assert_eq!(
+            digest.summary(),
+            "3 runs in 1 sessions, 1 commits, 0 PRs opened, 1 merged, $1.50"
+        );
+        let markdown = digest.to_markdown();
+        assert!(markdown.contains("## api\n\n- Sessions: 1 (3 runs, $1.50)"));
+        assert!(markdown.contains("### Commits\n\n- abc1234 Fix login\n"));
+        assert!(markdown.contains("### PRs merged\n\n- #42 Fix login\n"));
+        assert!(!markdown.contains("PRs opened\n\n"));
+    }
+}
@@ -6,6 +6,34 @@
 //! Polling is split into two categories:
 //! - **Local**: Git commands that run locally (fast, can run frequently)
 //! - **Remote**: API calls like PR status via `gh` (slower, rate-limited)
+//!
+//! Local polls are driven by a filesystem watcher on the active worktree (see
+//! [`watcher`]); the fixed interval is only used when watching isn't possible.
+//!
+//! Projects can override both intervals, or turn remote polling off entirely
+//! (`Project::polling`); the overrides of the active worktree's project apply.
+//! During a project's quiet hours its worktrees are only polled on request or
+//! when they change on disk.
+//! Remote polling is also slowed on battery and paused on metered connections
+//! (see [`power`]).
+//!
+//! Worktrees other than the active one are polled in the background, more or
+//! less often depending on their recent activity (see [`activity`]).
+//!
+//! User-defined cron jobs run on their own thread (see [`scheduler`]), as do
+//! opt-in scheduled AI reviews (see [`scheduled_review`]).
+//!
+//! All polling can be paused from the tray (see [`crate::tray`]); while paused
+//! the loop behaves as if the app were unfocused.
+//!
+//! Focus and active worktree changes also drive time tracking (see
+//! [`time_tracking`]).
+//!
+//! Polls that find the base branch moved can update the worktree from it
+//! automatically when enabled (see [`auto_update`]), and PRs whose checks
+//! start failing can be handed to a fix-CI session (see [`ci_watcher`]).
+//! Remote polls also prefetch PR and issue contexts of polled worktrees
+//! (see [`crate::projects::context_prefetch`]).
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
@@ -15,12 +43,26 @@ use std::time::Duration;
 
 use tauri::AppHandle;
 
-use crate::gh_cli::config::resolve_gh_binary;
 use crate::http_server::EmitExt;
 use crate::projects::git_status::{get_branch_status, ActiveWorktreeInfo, GitBranchStatus};
-use crate::projects::pr_status::{get_pr_status, PrStatus};
-
+use crate::projects::pr_status::PrStatus;
+use crate::projects::repo_provider::for_repo;
+use crate::projects::storage::load_projects_data;
+use crate::projects::types::PollingOverrides;
+use activity::ActivityTracker;
+use power::{PowerMonitor, RemotePollingMode};
+use watcher::WorktreeWatcher;
+
+pub mod activity;
+pub mod auto_update;
+pub mod ci_watcher;
 pub mod commands;
+pub mod digest;
+pub mod power;
+pub mod scheduled_review;
+pub mod scheduler;
+pub mod time_tracking;
+pub mod watcher;
 
 // ============================================================================
 // Local polling constants (git commands that run locally)
@@ -57,11 +99,14 @@ pub const DEFAULT_REMOTE_POLL_INTERVAL: u64 = 60;
 /// for the active worktree when the application is focused.
 ///
 /// Polling is split into local (git commands) and remote (API calls) categories:
-/// - Local polls run on focus changes with a short debounce (10s)
+/// - Local polls run when the worktree watcher sees a change, and on focus
+///   changes with a short debounce (10s)
 /// - Remote polls run on a separate, longer interval (default 60s)
 pub struct BackgroundTaskManager {
     app: AppHandle,
     is_focused: Arc<AtomicBool>,
+    /// Set while the user paused all polling
+    paused: Arc<AtomicBool>,
     active_worktree: Arc<Mutex<Option<ActiveWorktreeInfo>>>,
     /// Interval for local git status polling (background timer)
     poll_interval_secs: Arc<AtomicU64>,
@@ -76,6 +121,14 @@ pub struct BackgroundTaskManager {
     last_local_poll_times: Arc<Mutex<HashMap<String, u64>>>,
     /// Per-worktree timestamps of last remote poll
     last_remote_poll_times: Arc<Mutex<HashMap<String, u64>>>,
+    /// Filesystem watcher on the active worktree
+    watcher: Arc<WorktreeWatcher>,
+    /// Polling overrides of the active worktree's project
+    project_overrides: Arc<Mutex<Option<PollingOverrides>>>,
+    /// Battery and metered-connection state
+    power: Arc<PowerMonitor>,
+    /// Activity of background (non-active) worktrees
+    activity: Arc<ActivityTracker>,
 }
 
 impl BackgroundTaskManager {
@@ -84,6 +137,7 @@ impl BackgroundTaskManager {
         Self {
             app,
             is_focused: Arc::new(AtomicBool::new(true)), // Assume focused on startup
+            paused: Arc::new(AtomicBool::new(false)),
             active_worktree: Arc::new(Mutex::new(None)),
             poll_interval_secs: Arc::new(AtomicU64::new(DEFAULT_POLL_INTERVAL)),
             remote_poll_interval_secs: Arc::new(AtomicU64::new(DEFAULT_REMOTE_POLL_INTERVAL)),
@@ -92,6 +146,10 @@ impl BackgroundTaskManager {
             immediate_remote_poll: Arc::new(AtomicBool::new(false)),
             last_local_poll_times: Arc::new(Mutex::new(HashMap::new())),
             last_remote_poll_times: Arc::new(Mutex::new(HashMap::new())),
+            watcher: Arc::new(WorktreeWatcher::new()),
+            project_overrides: Arc::new(Mutex::new(None)),
+            power: Arc::new(PowerMonitor::new()),
+            activity: Arc::new(ActivityTracker::new()),
         }
     }
 
@@ -101,13 +159,15 @@ impl BackgroundTaskManager {
     /// for the active worktree when the application is focused.
     ///
     /// The polling loop handles two types of checks:
-    /// - **Local**: Git commands (fast, 10s debounce on focus events)
+    /// - **Local**: Git commands, run when the worktree changes on disk (or on
+    ///   the local interval if it can't be watched), 10s debounce on focus events
     /// - **Remote**: PR status via `gh` (separate interval, default 60s)
     pub fn start(&self) {
         log::trace!("Starting background task manager");
 
         let app = self.app.clone();
         let is_focused = Arc::clone(&self.is_focused);
+        let paused = Arc::clone(&self.paused);
         let active_worktree = Arc::clone(&self.active_worktree);
         let poll_interval_secs = Arc::clone(&self.poll_interval_secs);
         let remote_poll_interval_secs = Arc::clone(&self.remote_poll_interval_secs);
@@ -116,6 +176,13 @@ impl BackgroundTaskManager {
         let immediate_remote_poll = Arc::clone(&self.immediate_remote_poll);
         let last_local_poll_times = Arc::clone(&self.last_local_poll_times);
         let last_remote_poll_times = Arc::clone(&self.last_remote_poll_times);
+        let watcher = Arc::clone(&self.watcher);
+        let project_overrides = Arc::clone(&self.project_overrides);
+        let power = Arc::clone(&self.power);
+        let activity = Arc::clone(&self.activity);
+
+        scheduler::start(self.app.clone(), Arc::clone(&self.shutdown));
+        scheduled_review::start(self.app.clone(), Arc::clone(&self.shutdown));
 
         thread::spawn(move || {
             log::trace!("Background task polling loop started");
@@ -127,8 +194,8 @@ impl BackgroundTaskManager {
                     break;
                 }
 
-                // Only poll when app is focused
-                if !is_focused.load(Ordering::Relaxed) {
+                // Only poll when app is focused and polling isn't paused
+                if !is_focused.load(Ordering::Relaxed) || paused.load(Ordering::Relaxed) {
                     thread::sleep(Duration::from_secs(1));
                     continue;
                 }
@@ -141,11 +208,45 @@ impl BackgroundTaskManager {
 
                 if worktree_info.is_none() {
                     log::trace!("No active worktree for polling");
+                    watcher.watch(None);
+                    crate::projects::file_listing::set_live(None);
+                    poll_background_worktrees(&app, &activity, &power, None);
                     thread::sleep(Duration::from_secs(1));
                     continue;
                 }
 
+                // Effective intervals for the active worktree's project
+                let overrides = project_overrides
+                    .lock()
+                    .unwrap()
+                    .clone()
+                    .unwrap_or_default();
+                let local_interval = overrides
+                    .local_interval
+                    .map(|s| s.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL))
+                    .unwrap_or_else(|| poll_interval_secs.load(Ordering::Relaxed));
+                let configured_remote_interval = overrides
+                    .remote_interval
+                    .map(|s| s.clamp(MIN_REMOTE_POLL_INTERVAL, MAX_REMOTE_POLL_INTERVAL))
+                    .unwrap_or_else(|| remote_poll_interval_secs.load(Ordering::Relaxed));
+                let remote_enabled = overrides.remote_enabled;
+                let quiet = in_quiet_hours(&overrides);
+
+                // Slowed on battery; None while paused on a metered connection
+                let power_remote_interval = power
+                    .status(&app)
+                    .remote_interval(configured_remote_interval);
+                let remote_paused = power_remote_interval.is_none();
+                let remote_interval = power_remote_interval.unwrap_or(configured_remote_interval);
+
+                let mut watching = false;
                 if let Some(info) = worktree_info {
+                    poll_background_worktrees(&app, &activity, &power, Some(&info.worktree_id));
+
+                    watching = watcher.watch(Some(&info.worktree_path));
+                    crate::projects::file_listing::set_live(
+                        watching.then_some(info.worktree_path.as_str()),
+                    );
                     log::trace!(
                         "Polling loop: worktree={}, pr_number={:?}, pr_url={:?}",
                         info.worktree_id,
@@ -167,9 +268,29 @@ impl BackgroundTaskManager {
                     };
                     let time_since_local = now.saturating_sub(last_local);
                     let is_immediate_local = immediate_poll.swap(false, Ordering::Relaxed);
-
-                    let should_poll_local =
-                        is_immediate_local || time_since_local >= MIN_LOCAL_POLL_DEBOUNCE;
+                    let changes = watcher.take_change();
+                    if let Some(changes) = &changes {
+                        if !changes.paths.is_empty() || changes.overflowed {
+                            crate::projects::file_listing::apply_changes(
+                                &app,
+                                &info.worktree_path,
+                                &changes.paths,
+                                changes.overflowed,
+                            );
+                        }
+                    }
+                    let has_changed = changes.is_some();
+
+                    // A watched worktree only needs the timer to pick up new
+                    // upstream commits (fetched during the poll), so it runs
+                    // on the remote interval, and not at all without remote polling
+                    let timer_due = !quiet
+                        && if watching {
+                            remote_enabled && !remote_paused && time_since_local >= remote_interval
+                        } else {
+                            time_since_local >= MIN_LOCAL_POLL_DEBOUNCE
+                        };
+                    let should_poll_local = is_immediate_local || has_changed || timer_due;
 
                     if should_poll_local {
                         {
@@ -177,7 +298,11 @@ impl BackgroundTaskManager {
                             times.insert(info.worktree_id.clone(), now);
                         }
 
-                        match get_branch_status(&info) {
+                        watcher.begin_poll();
+                        let result = get_branch_status(&info, remote_enabled && !remote_paused);
+                        watcher.end_poll();
+
+                        match result {
                             Ok(status) => {
                                 log::trace!(
                                     "Git status for {}: behind={}, ahead={}, has_updates={}",
@@ -187,6 +312,13 @@ impl BackgroundTaskManager {
                                     status.has_updates
                                 );
 
+                                let push = remote_enabled && !remote_paused;
+                                let status =
+                                    if auto_update::maybe_update(&app, &info, &status, push) {
+                                        get_branch_status(&info, false).unwrap_or(status)
+                                    } else {
+                                        status
+                                    };
                                 if let Err(e) = emit_git_status(&app, status) {
                                     log::error!("Failed to emit git status event: {e}");
                                 }
@@ -203,18 +335,20 @@ impl BackgroundTaskManager {
                     // ================================================================
                     // Remote polling (PR status - separate, longer interval)
                     // ================================================================
-                    if let (Some(pr_number), Some(pr_url)) = (&info.pr_number, &info.pr_url) {
+                    if let (true, Some(pr_number), Some(pr_url)) =
+                        (remote_enabled, &info.pr_number, &info.pr_url)
+                    {
                         let last_remote = {
                             let times = last_remote_poll_times.lock().unwrap();
                             times.get(&info.worktree_id).copied().unwrap_or(0)
                         };
                         let time_since_remote = now.saturating_sub(last_remote);
-                        let remote_interval = remote_poll_interval_secs.load(Ordering::Relaxed);
                         let is_immediate_remote =
                             immediate_remote_poll.swap(false, Ordering::Relaxed);
 
-                        let should_poll_remote =
-                            is_immediate_remote || time_since_remote >= remote_interval;
+                        // A requested refresh still runs while paused
+                        let should_poll_remote = is_immediate_remote
+                            || (!remote_paused && !quiet && time_since_remote >= remote_interval);
 
                         log::trace!(
                             "Remote poll check: should_poll={}, is_immediate={}, time_since={}s, interval={}s",
@@ -231,13 +365,12 @@ impl BackgroundTaskManager {
                                 times.insert(info.worktree_id.clone(), now);
                             }
 
-                            let gh = resolve_gh_binary(&app);
-                            match get_pr_status(
+                            let provider = for_repo(&app, &info.worktree_path);
+                            match provider.pr_status(
                                 &info.worktree_path,
                                 *pr_number,
                                 pr_url,
                                 &info.worktree_id,
-                                &gh,
                             ) {
                                 Ok(status) => {
                                     log::trace!(
@@ -250,6 +383,14 @@ impl BackgroundTaskManager {
                                     if let Err(e) = emit_pr_status(&app, status) {
                                         log::error!("Failed to emit PR status event: {e}");
                                     }
+
+                                    // Warm the PR/issue contexts so loading them is instant
+                                    crate::projects::context_prefetch::spawn_prefetch(
+                                        &app,
+                                        &info.worktree_id,
+                                        &info.worktree_path,
+                                        Some(*pr_number),
+                                    );
                                 }
                                 Err(e) => {
                                     log::warn!("Failed to get PR status for #{}: {e}", pr_number);
@@ -261,13 +402,20 @@ impl BackgroundTaskManager {
 
                 // Wait for a short interval before next check
                 // Use 1-second sleep intervals to respond to shutdown/focus/immediate changes quickly
-                let interval = poll_interval_secs.load(Ordering::Relaxed);
+                let interval = if watching {
+                    remote_interval
+                } else {
+                    local_interval
+                };
                 for _ in 0..interval {
-                    // Break early if shutdown, unfocused, or immediate poll requested
+                    // Break early if shutdown, unfocused, paused, immediate poll
+                    // requested, or the worktree changed on disk
                     if shutdown.load(Ordering::Relaxed)
                         || !is_focused.load(Ordering::Relaxed)
+                        || paused.load(Ordering::Relaxed)
                         || immediate_poll.load(Ordering::Relaxed)
                         || immediate_remote_poll.load(Ordering::Relaxed)
+                        || watcher.has_settled_change()
                     {
                         break;
                     }
@@ -295,6 +443,10 @@ impl BackgroundTaskManager {
         if focused && !was_focused {
             // App gained focus - check if we should poll immediately
             let worktree_info = self.active_worktree.lock().ok().and_then(|g| g.clone());
+            time_tracking::switch(
+                &self.app,
+                worktree_info.as_ref().map(|i| i.worktree_id.as_str()),
+            );
 
             if let Some(info) = worktree_info {
                 let now = std::time::SystemTime::now()
@@ -324,9 +476,32 @@ impl BackgroundTaskManager {
             }
         } else if !focused && was_focused {
             log::trace!("App lost focus: polling paused");
+            time_tracking::switch(&self.app, None);
+        }
+    }
+
+    /// Pause or resume all polling, emitting `polling:paused-changed`
+    ///
+    /// Resuming triggers an immediate local poll.
+    pub fn set_paused(&self, paused: bool) {
+        let was_paused = self.paused.swap(paused, Ordering::Relaxed);
+        if paused == was_paused {
+            return;
+        }
+        log::info!("Polling {}", if paused { "paused" } else { "resumed" });
+        if !paused {
+            self.immediate_poll.store(true, Ordering::Relaxed);
+        }
+        if let Err(e) = self.app.emit_all("polling:paused-changed", &paused) {
+            log::error!("Failed to emit polling:paused-changed event: {e}");
         }
     }
 
+    /// Whether all polling is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
     /// Set the active worktree for polling
     ///
     /// Pass `None` to clear the active worktree and stop polling.
@@ -337,6 +512,15 @@ impl BackgroundTaskManager {
             "Active worktree changed: {:?}",
             info.as_ref().map(|i| &i.worktree_id)
         );
+        let overrides = info
+            .as_ref()
+            .and_then(|i| project_overrides_for(&self.app, &i.worktree_id));
+        *self.project_overrides.lock().unwrap() = overrides;
+
+        if self.is_focused.load(Ordering::Relaxed) {
+            time_tracking::switch(&self.app, info.as_ref().map(|i| i.worktree_id.as_str()));
+        }
+
         let mut guard = self.active_worktree.lock().unwrap();
         let should_poll_immediately = info.is_some();
         *guard = info;
@@ -348,9 +532,28 @@ impl BackgroundTaskManager {
         }
     }
 
+    /// Reload the polling overrides of the active worktree's project (after
+    /// they change)
+    pub fn refresh_project_overrides(&self) {
+        let worktree_id = self
+            .active_worktree
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|i| i.worktree_id.clone());
+        let overrides = worktree_id.and_then(|id| project_overrides_for(&self.app, &id));
+        *self.project_overrides.lock().unwrap() = overrides;
+    }
+
+    /// Current battery/network state affecting remote polling
+    pub fn polling_status(&self, app: &AppHandle) -> power::PollingStatus {
+        self.power.status(app)
+    }
+
     /// Set the local polling interval in seconds
     ///
     /// The interval will be clamped to the valid range (10-600 seconds).
+    /// Only used for worktrees that can't be watched for changes.
     pub fn set_poll_interval(&self, seconds: u64) {
         let clamped = seconds.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL);
         log::trace!("Setting local git poll interval to {clamped} seconds");
@@ -397,6 +600,81 @@ impl BackgroundTaskManager {
     }
 }
 
+/// Poll the background worktrees that are due, given their activity
+fn poll_background_worktrees(
+    app: &AppHandle,
+    activity: &ActivityTracker,
+    power: &PowerMonitor,
+    active_worktree_id: Option<&str>,
+) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let due = activity.due(app, now, active_worktree_id);
+    if due.is_empty() {
+        return;
+    }
+
+    let remote_paused = power.status(app).remote_polling == RemotePollingMode::Paused;
+    for poll in due {
+        log::trace!("Background poll for worktree {}", poll.info.worktree_id);
+        let remote = poll.remote_enabled && !remote_paused;
+        let result = get_branch_status(&poll.info, remote).map(|status| {
+            if auto_update::maybe_update(app, &poll.info, &status, remote) {
+                get_branch_status(&poll.info, false).unwrap_or(status)
+            } else {
+                status
+            }
+        });
+        match &result {
+            Ok(status) => {
+                if let Err(e) = emit_git_status(app, status.clone()) {
+                    log::error!("Failed to emit git status event: {e}");
+                }
+            }
+            Err(e) => log::trace!(
+                "Failed to get git status for {}: {e}",
+                poll.info.worktree_id
+            ),
+        }
+        activity.record(&poll.info, now, result.as_ref().ok());
+
+        if remote && poll.info.pr_number.is_some() {
+            crate::projects::context_prefetch::spawn_prefetch(
+                app,
+                &poll.info.worktree_id,
+                &poll.info.worktree_path,
+                poll.info.pr_number,
+            );
+        }
+    }
+}
+
+/// Whether it is currently within a project's quiet hours
+pub(crate) fn in_quiet_hours(overrides: &PollingOverrides) -> bool {
+    use chrono::Timelike;
+
+    let now = chrono::Local::now();
+    overrides
+        .quiet_hours
+        .as_ref()
+        .is_some_and(|q| q.contains(now.hour() * 60 + now.minute()))
+}
+
+/// Polling overrides of the project a worktree belongs to
+fn project_overrides_for(app: &AppHandle, worktree_id: &str) -> Option<PollingOverrides> {
+    let data = match load_projects_data(app) {
+        Ok(data) => data,
+        Err(e) => {
+            log::warn!("Failed to load projects for polling overrides: {e}");
+            return None;
+        }
+    };
+    let worktree = data.find_worktree(worktree_id)?;
+    data.find_project(&worktree.project_id)?.polling.clone()
+}
+
 /// Emit a git status event to the frontend
 fn emit_git_status(app: &AppHandle, status: GitBranchStatus) -> Result<(), String> {
     app.emit_all("git:status-update", &status)
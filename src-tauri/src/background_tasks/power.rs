@@ -0,0 +1,228 @@
+//! Battery and network awareness for remote polling
+//!
+//! Remote polls (git fetch, PR status via `gh`) are slowed down while the
+//! machine runs on battery and paused on metered connections. The state is
+//! detected with OS facilities and re-checked periodically; changes are
+//! emitted as `polling:status-changed` so the UI can explain stale data.
+//!
+//! Detection is best effort: anything that can't be determined counts as
+//! "on AC power" and "not metered", so polling behaves as before.
+//! - macOS: `pmset -g batt` (metered connections aren't detected)
+//! - Linux: `/sys/class/power_supply`, and NetworkManager's `Metered` property
+//! - Windows: `GetSystemPowerStatus`, and the connection cost of the internet
+//!   connection profile
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::http_server::EmitExt;
+
+/// How long a detected state is reused before checking again
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Remote interval multiplier while on battery
+pub const BATTERY_SLOWDOWN: u64 = 3;
+
+/// How remote polling currently runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemotePollingMode {
+    Normal,
+    /// On battery: remote polls run less often
+    Slowed,
+    /// On a metered connection: remote polls only run when requested
+    Paused,
+}
+
+/// Power and network state affecting background polling
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PollingStatus {
+    pub on_battery: bool,
+    pub metered: bool,
+    pub remote_polling: RemotePollingMode,
+}
+
+impl PollingStatus {
+    fn new(on_battery: bool, metered: bool) -> Self {
+        let remote_polling = if metered {
+            RemotePollingMode::Paused
+        } else if on_battery {
+            RemotePollingMode::Slowed
+        } else {
+            RemotePollingMode::Normal
+        };
+        Self {
+            on_battery,
+            metered,
+            remote_polling,
+        }
+    }
+
+    /// The remote interval to use given the configured one, or None when
+    /// remote polling is paused
+    pub fn remote_interval(&self, configured: u64) -> Option<u64> {
+        match self.remote_polling {
+            RemotePollingMode::Normal => Some(configured),
+            RemotePollingMode::Slowed => Some(configured * BATTERY_SLOWDOWN),
+            RemotePollingMode::Paused => None,
+        }
+    }
+}
+
+/// Caches the detected state and emits changes
+#[derive(Default)]
+pub struct PowerMonitor {
+    last: Mutex<Option<(Instant, PollingStatus)>>,
+}
+
+impl PowerMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current status, re-detecting it if the cached one is stale.
+    /// Emits `polling:status-changed` when it differs from the previous one.
+    pub fn status(&self, app: &AppHandle) -> PollingStatus {
+        let previous = {
+            let last = self.last.lock().unwrap();
+            match last.as_ref() {
+                Some((at, status)) if at.elapsed() < RECHECK_INTERVAL => return status.clone(),
+                Some((_, status)) => Some(status.clone()),
+                None => None,
+            }
+        };
+
+        let status = PollingStatus::new(on_battery(), is_metered());
+        if previous.as_ref() != Some(&status) {
+            log::info!(
+                "Polling status: on_battery={}, metered={}, remote={:?}",
+                status.on_battery,
+                status.metered,
+                status.remote_polling
+            );
+            if let Err(e) = app.emit_all("polling:status-changed", &status) {
+                log::error!("Failed to emit polling:status-changed event: {e}");
+            }
+        }
+        *self.last.lock().unwrap() = Some((Instant::now(), status.clone()));
+        status
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    crate::platform::silent_command("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn is_metered() -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut discharging = false;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(dir.join("type")).as_str() {
+            // Any connected charger means AC power
+            "Mains" | "USB" if read(dir.join("online")) == "1" => return false,
+            "Battery" if read(dir.join("status")) == "Discharging" => discharging = true,
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[cfg(target_os = "linux")]
+fn is_metered() -> bool {
+    // NMMetered: 1 = yes, 3 = guessed yes
+    crate::platform::silent_command("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| matches!(String::from_utf8_lossy(&o.stdout).trim(), "u 1" | "u 3"))
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    // SAFETY: GetSystemPowerStatus only writes to the struct we pass
+    let ok = unsafe { GetSystemPowerStatus(&mut status) } != 0;
+    // ACLineStatus: 0 = offline, 1 = online, 255 = unknown
+    ok && status.ACLineStatus == 0
+}
+
+#[cfg(windows)]
+fn is_metered() -> bool {
+    const SCRIPT: &str = "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime] | Out-Null; \
+        $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+        if ($p) { $p.GetConnectionCost().NetworkCostType }";
+    crate::platform::silent_command("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .output()
+        .map(|o| {
+            matches!(
+                String::from_utf8_lossy(&o.stdout).trim(),
+                "Fixed" | "Variable"
+            )
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn on_battery() -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn is_metered() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_interval_by_state() {
+        assert_eq!(
+            PollingStatus::new(false, false).remote_interval(60),
+            Some(60)
+        );
+        assert_eq!(
+            PollingStatus::new(true, false).remote_interval(60),
+            Some(60 * BATTERY_SLOWDOWN)
+        );
+        // Metered wins over battery
+        let metered = PollingStatus::new(true, true);
+        assert_eq!(metered.remote_polling, RemotePollingMode::Paused);
+        assert_eq!(metered.remote_interval(60), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Scheduled background AI reviews
+//!
+//! When `scheduled_review_hours` is set, worktrees whose branch gained commits
+//! since their last review are reviewed with the code review magic prompt
+//! every that many hours. Results are cached in `scheduled-reviews.json` next
+//! to the UI state, keyed by worktree, and each finished review is emitted as
+//! `review:scheduled_complete`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+use crate::projects::git_queue;
+use crate::projects::storage::load_projects_data;
+use crate::projects::types::SessionType;
+use crate::projects::ReviewResponse;
+
+/// Seconds between checks whether a review pass is due
+const CHECK_INTERVAL: u64 = 10 * 60;
+
+/// Latest scheduled review of a worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledReview {
+    pub worktree_id: String,
+    /// HEAD commit that was reviewed
+    pub commit: String,
+    pub reviewed_at: u64,
+    pub review: ReviewResponse,
+}
+
+/// Cached scheduled reviews
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledReviews {
+    /// When the last review pass ran
+    #[serde(default)]
+    pub last_run_at: u64,
+    /// worktreeId → latest review
+    #[serde(default)]
+    pub reviews: HashMap<String, ScheduledReview>,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = crate::locations::app_data_dir(app)?;
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {e}"))?;
+    Ok(app_data_dir.join("scheduled-reviews.json"))
+}
+
+/// Load the cached reviews (empty when none were cached yet)
+pub fn load(app: &AppHandle) -> Result<ScheduledReviews, String> {
+    let path = cache_path(app)?;
+    if !path.exists() {
+        return Ok(ScheduledReviews::default());
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read scheduled reviews: {e}"))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse scheduled reviews: {e}"))
+}
+
+fn save(app: &AppHandle, reviews: &ScheduledReviews) -> Result<(), String> {
+    let path = cache_path(app)?;
+    let json = serde_json::to_string_pretty(reviews)
+        .map_err(|e| format!("Failed to serialize scheduled reviews: {e}"))?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write scheduled reviews: {e}"))?;
+    std::fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to finalize scheduled reviews: {e}"))
+}
+
+/// Whether a review pass is due `hours` after the last one
+fn is_due(last_run_at: u64, hours: u32, now: u64) -> bool {
+    hours > 0 && now >= last_run_at + hours as u64 * 3600
+}
+
+/// Whether a branch `ahead` commits of its base, at `head`, needs a new review
+fn needs_review(previous: Option<&ScheduledReview>, head: &str, ahead: u32) -> bool {
+    ahead > 0 && previous.is_none_or(|r| r.commit != head)
+}
+
+fn git_output(path: &str, args: &[&str]) -> Option<String> {
+    git_queue::read_command()
+        .args(args)
+        .current_dir(path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Review every worktree with new commits since its last review
+fn run_pass(app: &AppHandle) -> Result<(), String> {
+    let preferences = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))?;
+    let data = load_projects_data(app)?;
+    let mut cache = load(app)?;
+    cache.last_run_at = now();
+    cache
+        .reviews
+        .retain(|id, _| data.find_worktree(id).is_some());
+
+    for worktree in &data.worktrees {
+        if worktree.session_type == SessionType::Base
+            || worktree.archived_at.is_some()
+            || !std::path::Path::new(&worktree.path).exists()
+        {
+            continue;
+        }
+        let Some(project) = data.find_project(&worktree.project_id) else {
+            continue;
+        };
+        let Some(head) = git_output(&worktree.path, &["rev-parse", "HEAD"]) else {
+            continue;
+        };
+        let ahead = git_output(
+            &worktree.path,
+            &[
+                "rev-list",
+                "--count",
+                &format!("{}..HEAD", project.default_branch),
+            ],
+        )
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+        if !needs_review(cache.reviews.get(&worktree.id), &head, ahead) {
+            continue;
+        }
+
+        log::info!("Running scheduled review of {}", worktree.name);
+        let review = tauri::async_runtime::block_on(crate::projects::run_review_with_ai(
+            app.clone(),
+            worktree.path.clone(),
+            Some(preferences.magic_prompts.code_review.clone()),
+            Some(preferences.magic_prompt_models.code_review_model.clone()),
+            None,
+        ));
+        let review = match review {
+            Ok(review) => review,
+            Err(e) => {
+                log::warn!("Scheduled review of {} failed: {e}", worktree.name);
+                continue;
+            }
+        };
+        let result = ScheduledReview {
+            worktree_id: worktree.id.clone(),
+            commit: head,
+            reviewed_at: now(),
+            review,
+        };
+        if let Err(e) = app.emit_all("review:scheduled_complete", &result) {
+            log::error!("Failed to emit review:scheduled_complete event: {e}");
+        }
+        cache.reviews.insert(worktree.id.clone(), result);
+        // Save as we go so a long pass doesn't lose finished reviews
+        save(app, &cache)?;
+    }
+    save(app, &cache)
+}
+
+/// Start the loop running review passes every `scheduled_review_hours`
+pub fn start(app: AppHandle, shutdown: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        log::trace!("Scheduled review loop started");
+        while !shutdown.load(Ordering::Relaxed) {
+            let hours = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))
+                .map(|p| p.scheduled_review_hours)
+                .unwrap_or(0);
+            let last_run_at = load(&app).map(|c| c.last_run_at).unwrap_or(0);
+            if is_due(last_run_at, hours, now()) {
+                if let Err(e) = run_pass(&app) {
+                    log::warn!("Failed to run scheduled reviews: {e}");
+                }
+            }
+            // Sleep in 1-second steps so shutdown isn't held up for minutes
+            for _ in 0..CHECK_INTERVAL {
+                if shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_scheduling() {
+        assert!(!is_due(0, 0, 1_700_000_000));
+        assert!(is_due(0, 6, 1_700_000_000));
+        assert!(!is_due(1_700_000_000, 6, 1_700_000_000 + 3600));
+        assert!(is_due(1_700_000_000, 6, 1_700_000_000 + 6 * 3600));
+
+        let previous = ScheduledReview {
+            worktree_id: "w1".to_string(),
+            commit: "abc".to_string(),
+            reviewed_at: 0,
+            review: ReviewResponse {
+                summary: String::new(),
+                findings: Vec::new(),
+                approval_status: "approved".to_string(),
+            },
+        };
+        assert!(needs_review(None, "abc", 1));
+        assert!(!needs_review(None, "abc", 0));
+        assert!(!needs_review(Some(&previous), "abc", 2));
+        assert!(needs_review(Some(&previous), "def", 2));
+    }
+}
@@ -0,0 +1,581 @@
+//! Cron-style scheduled jobs
+//!
+//! Users can schedule jobs (run a script, trigger a magic prompt, fetch and
+//! prune a repository) with standard 5-field cron expressions, evaluated in
+//! local time. Jobs and their run history are stored in the database; a job
+//! missed while Jean was closed runs once when it starts again.
+//!
+//! Digest jobs compile a daily activity summary (see [`super::digest`]).
+//!
+//! Every run emits `scheduler:job-finished`; failed runs also show a native
+//! notification. Magic prompts need the UI, so they are handed to the
+//! frontend with a `scheduler:magic-prompt` event.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use croner::Cron;
+use once_cell::sync::Lazy;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::ci_watcher::tail;
+use crate::command_audit::AuditedCommand;
+use crate::db::with_db;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+use crate::projects::git::user_shell_command;
+use crate::projects::git_queue;
+use crate::projects::storage::load_projects_data;
+
+/// Seconds between checks for due jobs
+const CHECK_INTERVAL: u64 = 30;
+
+/// Runs kept per job
+const MAX_RUNS_PER_JOB: i64 = 50;
+
+/// Output kept per run (the tail is kept when longer)
+const MAX_OUTPUT_LEN: usize = 64 * 1024;
+
+/// Magic prompts a job can trigger (keys of `MagicPrompts`)
+pub const MAGIC_PROMPTS: [&str; 4] = [
+    "code_review",
+    "commit_message",
+    "pr_content",
+    "context_summary",
+];
+
+/// Jobs currently running (a job never overlaps with itself)
+static RUNNING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// What a job does when it runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobAction {
+    /// Run a shell command in the worktree (or project) directory
+    Script { command: String },
+    /// Ask the UI to run a magic prompt in the worktree
+    MagicPrompt { prompt: String },
+    /// `git fetch --all --prune` in the project repository
+    FetchPrune,
+    /// Sum up the last day's sessions, commits, PRs and cost (see [`super::digest`])
+    Digest {
+        /// Show the summary as a native notification
+        #[serde(default)]
+        notify: bool,
+        /// Write the digest as markdown
+        #[serde(default)]
+        export: bool,
+        /// Directory for exported digests (None = `<app data>/digests`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        export_dir: Option<String>,
+        /// Only cover the job's project (otherwise all projects)
+        #[serde(default)]
+        project_only: bool,
+    },
+}
+
+/// A user-defined scheduled job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    /// 5-field cron expression (minute hour day-of-month month day-of-week)
+    pub schedule: String,
+    pub action: JobAction,
+    pub project_id: String,
+    /// Worktree to run in (the project's repository when None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_id: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_at: Option<u64>,
+    /// Next scheduled run (computed, not stored)
+    #[serde(default, skip_deserializing)]
+    pub next_run_at: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A job as created or edited in the UI
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduledJobInput {
+    /// Existing job to update (a new job when None)
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub schedule: String,
+    pub action: JobAction,
+    pub project_id: String,
+    #[serde(default)]
+    pub worktree_id: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// A finished run of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub job_id: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub success: bool,
+    pub output: String,
+    /// Whether the run was started by hand rather than the schedule
+    #[serde(default)]
+    pub manual: bool,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn parse_schedule(schedule: &str) -> Result<Cron, String> {
+    Cron::new(schedule.trim())
+        .parse()
+        .map_err(|e| format!("Invalid cron expression '{schedule}': {e}"))
+}
+
+/// The first occurrence of `schedule` strictly after `after` (Unix seconds)
+pub fn next_occurrence(schedule: &str, after: u64) -> Result<Option<u64>, String> {
+    let cron = parse_schedule(schedule)?;
+    let Some(start) = Local.timestamp_opt(after as i64, 0).single() else {
+        return Ok(None);
+    };
+    Ok(cron
+        .find_next_occurrence(&start, false)
+        .ok()
+        .map(|t| t.timestamp().max(0) as u64))
+}
+
+/// Check a job before saving it
+pub fn validate(job: &ScheduledJob) -> Result<(), String> {
+    if job.name.trim().is_empty() {
+        return Err("Job name is empty".to_string());
+    }
+    parse_schedule(&job.schedule)?;
+    match &job.action {
+        JobAction::Script { command } if command.trim().is_empty() => {
+            Err("Script command is empty".to_string())
+        }
+        JobAction::MagicPrompt { prompt } if !MAGIC_PROMPTS.contains(&prompt.as_str()) => {
+            Err(format!("Unknown magic prompt: {prompt}"))
+        }
+        JobAction::MagicPrompt { .. } if job.worktree_id.is_none() => {
+            Err("Magic prompt jobs need a worktree".to_string())
+        }
+        JobAction::Digest {
+            notify: false,
+            export: false,
+            ..
+        } => Err("Digest jobs need a notification or an export".to_string()),
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+fn with_next_run(mut job: ScheduledJob) -> ScheduledJob {
+    let after = job.last_run_at.unwrap_or(job.created_at).max(now());
+    job.next_run_at = job
+        .enabled
+        .then(|| next_occurrence(&job.schedule, after).ok().flatten())
+        .flatten();
+    job
+}
+
+/// All scheduled jobs, oldest first
+pub fn list_jobs(app: &AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    let jobs = with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare("SELECT data FROM scheduled_jobs ORDER BY created_at")
+            .map_err(|e| format!("Failed to list scheduled jobs: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to list scheduled jobs: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list scheduled jobs: {e}"))
+    })?;
+    Ok(jobs
+        .iter()
+        .filter_map(|data| match serde_json::from_str::<ScheduledJob>(data) {
+            Ok(job) => Some(with_next_run(job)),
+            Err(e) => {
+                log::warn!("Skipping unreadable scheduled job: {e}");
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn get_job(app: &AppHandle, job_id: &str) -> Result<ScheduledJob, String> {
+    let data = with_db(app, |conn| {
+        conn.query_row(
+            "SELECT data FROM scheduled_jobs WHERE id = ?1",
+            params![job_id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load scheduled job: {e}"))
+    })?
+    .ok_or_else(|| format!("Scheduled job not found: {job_id}"))?;
+    let job =
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse scheduled job: {e}"))?;
+    Ok(with_next_run(job))
+}
+
+pub fn save_job(app: &AppHandle, job: &ScheduledJob) -> Result<(), String> {
+    let data = serde_json::to_string(job)
+        .map_err(|e| format!("Failed to serialize scheduled job: {e}"))?;
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO scheduled_jobs (id, created_at, data) VALUES (?1, ?2, ?3)",
+            params![job.id, job.created_at as i64, data],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save scheduled job: {e}"))
+    })
+}
+
+/// Create or update a job from UI input
+pub fn upsert_job(app: &AppHandle, input: ScheduledJobInput) -> Result<ScheduledJob, String> {
+    let existing = match &input.id {
+        Some(id) => Some(get_job(app, id)?),
+        None => None,
+    };
+    let job = ScheduledJob {
+        id: input.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: input.name.trim().to_string(),
+        schedule: input.schedule.trim().to_string(),
+        action: input.action,
+        project_id: input.project_id,
+        worktree_id: input.worktree_id,
+        enabled: input.enabled,
+        created_at: existing.as_ref().map_or_else(now, |j| j.created_at),
+        last_run_at: existing.and_then(|j| j.last_run_at),
+        next_run_at: None,
+    };
+    validate(&job)?;
+    save_job(app, &job)?;
+    Ok(with_next_run(job))
+}
+
+pub fn delete_job(app: &AppHandle, job_id: &str) -> Result<(), String> {
+    with_db(app, |conn| {
+        conn.execute("DELETE FROM scheduled_jobs WHERE id = ?1", params![job_id])
+            .and_then(|_| conn.execute("DELETE FROM job_runs WHERE job_id = ?1", params![job_id]))
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete scheduled job: {e}"))
+    })
+}
+
+/// Newest runs of a job first
+pub fn list_runs(app: &AppHandle, job_id: &str, limit: usize) -> Result<Vec<JobRun>, String> {
+    let rows = with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT data FROM job_runs WHERE job_id = ?1 ORDER BY started_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to list job runs: {e}"))?;
+        let rows = stmt
+            .query_map(params![job_id, limit as i64], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to list job runs: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list job runs: {e}"))
+    })?;
+    Ok(rows
+        .iter()
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect())
+}
+
+fn record_run(app: &AppHandle, run: &JobRun) -> Result<(), String> {
+    let data =
+        serde_json::to_string(run).map_err(|e| format!("Failed to serialize job run: {e}"))?;
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT INTO job_runs (job_id, started_at, data) VALUES (?1, ?2, ?3)",
+            params![run.job_id, run.started_at as i64, data],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM job_runs WHERE job_id = ?1 AND id NOT IN
+                 (SELECT id FROM job_runs WHERE job_id = ?1 ORDER BY started_at DESC, id DESC LIMIT ?2)",
+                params![run.job_id, MAX_RUNS_PER_JOB],
+            )
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record job run: {e}"))
+    })
+}
+
+// ============================================================================
+// Running jobs
+// ============================================================================
+
+/// Payload of `scheduler:magic-prompt`
+#[derive(Debug, Clone, Serialize)]
+struct MagicPromptEvent {
+    job_id: String,
+    prompt: String,
+    project_id: String,
+    worktree_id: String,
+}
+
+/// Execute a job's action, returning its output
+fn execute(app: &AppHandle, job: &ScheduledJob) -> Result<String, String> {
+    let data = load_projects_data(app)?;
+    let project = data
+        .find_project(&job.project_id)
+        .ok_or_else(|| format!("Project not found: {}", job.project_id))?;
+    let (dir, branch) = match &job.worktree_id {
+        Some(id) => {
+            let worktree = data
+                .find_worktree(id)
+                .ok_or_else(|| format!("Worktree not found: {id}"))?;
+            (worktree.path.clone(), worktree.branch.clone())
+        }
+        None => (project.path.clone(), project.default_branch.clone()),
+    };
+    if !Path::new(&dir).is_dir() {
+        return Err(format!("Directory not found: {dir}"));
+    }
+
+    match &job.action {
+        JobAction::Script { command } => {
+            let output = user_shell_command(command)
+                .current_dir(&dir)
+                .env("JEAN_WORKSPACE_PATH", &dir)
+                .env("JEAN_ROOT_PATH", &project.path)
+                .env("JEAN_BRANCH", &branch)
+                .output_audited()
+                .map_err(|e| format!("Failed to run script: {e}"))?;
+            let combined = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            if output.status.success() {
+                Ok(combined)
+            } else {
+                Err(format!("Script exited with {}\n{combined}", output.status))
+            }
+        }
+        JobAction::FetchPrune => {
+            let _queued = git_queue::lock(&project.path);
+            let _slot = git_queue::fetch_slot();
+            let output = silent_command("git")
+                .args(["fetch", "--all", "--prune"])
+                .current_dir(&project.path)
+                .output_audited()
+                .map_err(|e| format!("Failed to run git fetch: {e}"))?;
+            let combined = String::from_utf8_lossy(&output.stderr).to_string();
+            if output.status.success() {
+                Ok(combined)
+            } else {
+                Err(format!("git fetch failed: {}", combined.trim()))
+            }
+        }
+        JobAction::MagicPrompt { prompt } => {
+            let event = MagicPromptEvent {
+                job_id: job.id.clone(),
+                prompt: prompt.clone(),
+                project_id: job.project_id.clone(),
+                worktree_id: job.worktree_id.clone().unwrap_or_default(),
+            };
+            app.emit_all("scheduler:magic-prompt", &event)
+                .map_err(|e| format!("Failed to emit scheduler:magic-prompt event: {e}"))?;
+            Ok(format!("Triggered {prompt}"))
+        }
+        JobAction::Digest {
+            notify,
+            export,
+            export_dir,
+            project_only,
+        } => {
+            let project_id = project_only.then_some(job.project_id.as_str());
+            let digest = super::digest::collect(app, now(), project_id)?;
+            let mut output = digest.summary();
+            if *notify {
+                super::digest::notify(app, &digest);
+            }
+            if *export {
+                let path = super::digest::export(app, &digest, export_dir.as_deref())?;
+                output.push_str(&format!("\nWritten to {}", path.display()));
+            }
+            Ok(output)
+        }
+    }
+}
+
+fn notify_failure(app: &AppHandle, job: &ScheduledJob, error: &str) {
+    #[cfg(not(mobile))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        let body = error.lines().next().unwrap_or(error).to_string();
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("Scheduled job failed: {}", job.name))
+            .body(body)
+            .show()
+        {
+            log::error!("Failed to show job failure notification: {e}");
+        }
+    }
+    #[cfg(mobile)]
+    let _ = (app, job, error);
+}
+
+/// Run a job now on a background thread (unless it is already running)
+pub fn spawn_run(app: &AppHandle, job: ScheduledJob, manual: bool) -> bool {
+    if !RUNNING.lock().unwrap().insert(job.id.clone()) {
+        log::trace!("Scheduled job {} is already running", job.id);
+        return false;
+    }
+
+    let app = app.clone();
+    thread::spawn(move || {
+        let started_at = now();
+        log::info!("Running scheduled job {} ({})", job.name, job.id);
+
+        // Record the run time first so a slow job isn't started again
+        if !manual {
+            let mut updated = job.clone();
+            updated.last_run_at = Some(started_at);
+            if let Err(e) = save_job(&app, &updated) {
+                log::error!("Failed to update scheduled job {}: {e}", job.id);
+            }
+        }
+
+        let result = execute(&app, &job);
+        let run = JobRun {
+            job_id: job.id.clone(),
+            started_at,
+            finished_at: now(),
+            success: result.is_ok(),
+            output: tail(
+                match &result {
+                    Ok(output) => output,
+                    Err(e) => e,
+                },
+                MAX_OUTPUT_LEN,
+            ),
+            manual,
+        };
+        if let Err(e) = &result {
+            log::warn!("Scheduled job {} failed: {e}", job.name);
+            notify_failure(&app, &job, e);
+        }
+        if let Err(e) = record_run(&app, &run) {
+            log::error!("{e}");
+        }
+        if let Err(e) = app.emit_all("scheduler:job-finished", &run) {
+            log::error!("Failed to emit scheduler:job-finished event: {e}");
+        }
+
+        RUNNING.lock().unwrap().remove(&job.id);
+    });
+    true
+}
+
+/// Whether a job is due at `now`
+fn is_due(job: &ScheduledJob, now: u64) -> bool {
+    if !job.enabled {
+        return false;
+    }
+    let after = job.last_run_at.unwrap_or(job.created_at);
+    matches!(next_occurrence(&job.schedule, after), Ok(Some(next)) if next <= now)
+}
+
+/// Start the scheduler loop
+pub fn start(app: AppHandle, shutdown: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        log::trace!("Job scheduler started");
+        while !shutdown.load(Ordering::Relaxed) {
+            match list_jobs(&app) {
+                Ok(jobs) => {
+                    let now = now();
+                    for job in jobs.into_iter().filter(|job| is_due(job, now)) {
+                        spawn_run(&app, job, false);
+                    }
+                }
+                Err(e) => log::warn!("Failed to check scheduled jobs: {e}"),
+            }
+            thread::sleep(Duration::from_secs(CHECK_INTERVAL));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(schedule: &str, action: JobAction) -> ScheduledJob {
+        ScheduledJob {
+            id: "job-1".to_string(),
+            name: "Nightly".to_string(),
+            schedule: schedule.to_string(),
+            action,
+            project_id: "p1".to_string(),
+            worktree_id: None,
+            enabled: true,
+            created_at: 0,
+            last_run_at: None,
+            next_run_at: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_job() {
+        assert!(validate(&job("0 3 * * *", JobAction::FetchPrune)).is_ok());
+        assert!(validate(&job("not cron", JobAction::FetchPrune)).is_err());
+        let script = JobAction::Script {
+            command: " ".to_string(),
+        };
+        assert!(validate(&job("* * * * *", script)).is_err());
+        let prompt = JobAction::MagicPrompt {
+            prompt: "code_review".to_string(),
+        };
+        // Magic prompts need a worktree
+        assert!(validate(&job("* * * * *", prompt)).is_err());
+        let digest = JobAction::Digest {
+            notify: false,
+            export: false,
+            export_dir: None,
+            project_only: false,
+        };
+        assert!(validate(&job("0 18 * * *", digest)).is_err());
+    }
+
+    #[test]
+    fn test_due_after_last_run() {
+        let mut every_hour = job("0 * * * *", JobAction::FetchPrune);
+        every_hour.created_at = 1_700_000_000;
+        let next = next_occurrence(&every_hour.schedule, every_hour.created_at)
+            .unwrap()
+            .unwrap();
+        assert!(next > every_hour.created_at && next - every_hour.created_at <= 3600);
+
+        assert!(!is_due(&every_hour, next - 1));
+        assert!(is_due(&every_hour, next));
+        every_hour.last_run_at = Some(next);
+        assert!(!is_due(&every_hour, next + 60));
+    }
+}
@@ -0,0 +1,321 @@
+//! Time tracking per worktree and session
+//!
+//! Time counts while the app is focused on a worktree. An interval starts when
+//! the app gains focus or the active worktree changes, and ends when focus is
+//! lost or another worktree is opened. Messages sent in the active worktree
+//! attribute the time that follows to their session. Intervals end at most
+//! [`IDLE_TIMEOUT`] after the last activity, so a window left focused
+//! overnight isn't counted. Closed intervals are stored in the `time_entries`
+//! table and summed up by [`report`].
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::chat::storage::load_metadata;
+use crate::db::with_db;
+use crate::projects::storage::load_projects_data;
+
+/// Seconds after the last activity an interval stops counting
+pub const IDLE_TIMEOUT: u64 = 30 * 60;
+
+/// A span of active time in a worktree (and session)
+#[derive(Debug, Clone, PartialEq)]
+struct Interval {
+    worktree_id: String,
+    session_id: Option<String>,
+    started_at: u64,
+    /// Last focus change, worktree switch or message
+    last_activity: u64,
+}
+
+impl Interval {
+    fn new(worktree_id: &str, session_id: Option<&str>, now: u64) -> Self {
+        Self {
+            worktree_id: worktree_id.to_string(),
+            session_id: session_id.map(str::to_string),
+            started_at: now,
+            last_activity: now,
+        }
+    }
+
+    /// The interval as counted so far
+    fn entry(&self, now: u64) -> TimeEntry {
+        TimeEntry {
+            worktree_id: self.worktree_id.clone(),
+            session_id: self.session_id.clone(),
+            started_at: self.started_at,
+            ended_at: now.min(self.last_activity + IDLE_TIMEOUT),
+        }
+    }
+}
+
+/// A row of the `time_entries` table
+#[derive(Debug, Clone, PartialEq)]
+struct TimeEntry {
+    worktree_id: String,
+    session_id: Option<String>,
+    started_at: u64,
+    ended_at: u64,
+}
+
+/// The interval being counted
+static CURRENT: Lazy<Mutex<Option<Interval>>> = Lazy::new(|| Mutex::new(None));
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn save(app: &AppHandle, interval: &Interval, now: u64) {
+    let entry = interval.entry(now);
+    if entry.ended_at <= entry.started_at {
+        return;
+    }
+    let result = with_db(app, |conn| {
+        conn.execute(
+            "INSERT INTO time_entries (worktree_id, session_id, started_at, ended_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                entry.worktree_id,
+                entry.session_id,
+                entry.started_at as i64,
+                entry.ended_at as i64
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save time entry: {e}"))
+    });
+    if let Err(e) = result {
+        log::warn!("{e}");
+    }
+}
+
+/// Close the current interval and start counting time in a worktree (None
+/// when the app loses focus or no worktree is open)
+pub fn switch(app: &AppHandle, worktree_id: Option<&str>) {
+    let now = now();
+    let mut current = CURRENT.lock().unwrap();
+    // Keep attributing time to the same session when focus comes back
+    let session_id = match (current.as_ref(), worktree_id) {
+        (Some(interval), Some(id)) if interval.worktree_id == id => interval.session_id.clone(),
+        _ => None,
+    };
+    if let Some(interval) = current.take() {
+        save(app, &interval, now);
+    }
+    *current = worktree_id.map(|id| Interval::new(id, session_id.as_deref(), now));
+}
+
+/// Record activity in a session, which keeps the current interval counting and
+/// attributes it to the session. Ignored unless the worktree is being tracked.
+pub fn record_activity(app: &AppHandle, worktree_id: &str, session_id: &str) {
+    let now = now();
+    let mut current = CURRENT.lock().unwrap();
+    let Some(interval) = current.as_mut().filter(|i| i.worktree_id == worktree_id) else {
+        return;
+    };
+    if interval.session_id.as_deref() == Some(session_id) {
+        interval.last_activity = now;
+        return;
+    }
+    save(app, interval, now);
+    *interval = Interval::new(worktree_id, Some(session_id), now);
+}
+
+/// Time range of a report (unix seconds, end exclusive)
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TimeRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// Time spent in one session (None = time not attributed to a session)
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionTime {
+    pub session_id: Option<String>,
+    pub session_name: Option<String>,
+    pub seconds: u64,
+}
+
+/// Time spent in a worktree
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeTime {
+    pub worktree_id: String,
+    pub worktree_name: String,
+    pub branch: String,
+    pub seconds: u64,
+    pub sessions: Vec<SessionTime>,
+}
+
+/// Time spent in a project
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectTime {
+    pub project_id: String,
+    pub project_name: String,
+    pub seconds: u64,
+    pub worktrees: Vec<WorktreeTime>,
+}
+
+/// Active time per project, worktree and session in a range
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeReport {
+    pub from: u64,
+    pub to: u64,
+    pub seconds: u64,
+    pub projects: Vec<ProjectTime>,
+}
+
+/// Seconds per worktree and session of the entries, clipped to the range
+fn totals(
+    entries: &[TimeEntry],
+    range: TimeRange,
+) -> BTreeMap<String, BTreeMap<Option<String>, u64>> {
+    let mut totals: BTreeMap<String, BTreeMap<Option<String>, u64>> = BTreeMap::new();
+    for entry in entries {
+        let start = entry.started_at.max(range.from);
+        let end = entry.ended_at.min(range.to);
+        if end <= start {
+            continue;
+        }
+        *totals
+            .entry(entry.worktree_id.clone())
+            .or_default()
+            .entry(entry.session_id.clone())
+            .or_default() += end - start;
+    }
+    totals
+}
+
+/// Stored entries overlapping the range, plus the interval being counted
+fn load_entries(app: &AppHandle, range: TimeRange, now: u64) -> Result<Vec<TimeEntry>, String> {
+    let mut entries = with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT worktree_id, session_id, started_at, ended_at FROM time_entries
+                 WHERE started_at < ?2 AND ended_at > ?1",
+            )
+            .map_err(|e| format!("Failed to load time entries: {e}"))?;
+        let rows = stmt
+            .query_map(params![range.from as i64, range.to as i64], |row| {
+                Ok(TimeEntry {
+                    worktree_id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    started_at: row.get::<_, i64>(2)? as u64,
+                    ended_at: row.get::<_, i64>(3)? as u64,
+                })
+            })
+            .map_err(|e| format!("Failed to load time entries: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to load time entries: {e}"))
+    })?;
+    if let Some(interval) = CURRENT.lock().unwrap().as_ref() {
+        entries.push(interval.entry(now));
+    }
+    Ok(entries)
+}
+
+/// Active time per project, worktree and session in a range
+pub fn report(app: &AppHandle, range: TimeRange) -> Result<TimeReport, String> {
+    if range.to <= range.from {
+        return Err("The end of the range must be after its start".to_string());
+    }
+    let now = now();
+    let totals = totals(&load_entries(app, range, now)?, range);
+    let data = load_projects_data(app)?;
+
+    let mut projects: BTreeMap<String, ProjectTime> = BTreeMap::new();
+    for (worktree_id, sessions) in totals {
+        let worktree = data.find_worktree(&worktree_id);
+        let (project_id, worktree_name, branch) = match worktree {
+            Some(w) => (w.project_id.clone(), w.name.clone(), w.branch.clone()),
+            // Deleted worktrees still count towards the total
+            None => (String::new(), worktree_id.clone(), String::new()),
+        };
+        let sessions: Vec<SessionTime> = sessions
+            .into_iter()
+            .map(|(session_id, seconds)| SessionTime {
+                session_name: session_id
+                    .as_deref()
+                    .and_then(|id| load_metadata(app, id).ok().flatten())
+                    .map(|m| m.name),
+                session_id,
+                seconds,
+            })
+            .collect();
+        let seconds = sessions.iter().map(|s| s.seconds).sum();
+
+        let project = projects
+            .entry(project_id.clone())
+            .or_insert_with(|| ProjectTime {
+                project_name: data
+                    .find_project(&project_id)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| "Deleted worktrees".to_string()),
+                project_id,
+                seconds: 0,
+                worktrees: Vec::new(),
+            });
+        project.seconds += seconds;
+        project.worktrees.push(WorktreeTime {
+            worktree_id,
+            worktree_name,
+            branch,
+            seconds,
+            sessions,
+        });
+    }
+
+    let mut projects: Vec<ProjectTime> = projects.into_values().collect();
+    projects.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+    for project in &mut projects {
+        project.worktrees.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+    }
+    Ok(TimeReport {
+        from: range.from,
+        to: range.to,
+        seconds: projects.iter().map(|p| p.seconds).sum(),
+        projects,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals_clip_to_range_and_idle_timeout() {
+        let entry = |worktree: &str, session: Option<&str>, start: u64, end: u64| TimeEntry {
+            ended_at: end,
+            ..Interval::new(worktree, session, start).entry(start)
+        };
+        // Idle for longer than the timeout: cut IDLE_TIMEOUT after the last activity
+        let idle = Interval::new("w2", Some("s2"), 2_000).entry(9_500);
+        assert_eq!(idle.ended_at, 2_000 + IDLE_TIMEOUT);
+
+        let entries = vec![
+            // Starts before the range
+            entry("w1", None, 900, 1_200),
+            entry("w1", Some("s1"), 1_200, 1_500),
+            idle,
+            // Outside the range
+            entry("w3", None, 10_000, 10_100),
+        ];
+        let range = TimeRange {
+            from: 1_000,
+            to: 9_000,
+        };
+        let totals = totals(&entries, range);
+
+        assert_eq!(totals["w1"][&None], 200);
+        assert_eq!(totals["w1"][&Some("s1".to_string())], 300);
+        assert_eq!(totals["w2"][&Some("s2".to_string())], IDLE_TIMEOUT);
+        assert!(!totals.contains_key("w3"));
+    }
+}
@@ -0,0 +1,431 @@
+//! Filesystem watcher for the active worktree
+//!
+//! Watches the worktree's files and its git directory (HEAD, index, refs) so
+//! local git status is recomputed when something actually changes instead of
+//! on a fixed interval. Events are coalesced: a burst of changes (a build, a
+//! checkout) results in a single refresh once things settle.
+//!
+//! If the watcher can't be set up (too many directories, inotify limits, not a
+//! git repository), the polling loop falls back to interval polling.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ignore::gitignore::Gitignore;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Milliseconds without new events before a change is considered settled
+const SETTLE_MS: u64 = 750;
+
+/// Milliseconds after a status poll during which git directory events are
+/// ignored, since the poll itself touches them (`git status` refreshes the
+/// index, `git fetch` updates remote refs)
+const OWN_POLL_GRACE_MS: u64 = 1500;
+
+/// Worktrees with more directories than this fall back to interval polling
+const MAX_WATCHED_DIRS: usize = 20_000;
+
+/// Changed paths remembered between refreshes; beyond this, consumers are
+/// told to rescan instead
+const MAX_CHANGED_PATHS: usize = 5_000;
+
+/// Where a relevant change happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    /// A tracked (non-ignored) file in the worktree
+    WorkingTree,
+    /// HEAD, the index or a ref
+    GitDir,
+}
+
+/// Paths watched for one worktree
+struct WatchTargets {
+    worktree: PathBuf,
+    /// The worktree's own git directory (`.git/worktrees/<name>` for linked worktrees)
+    git_dir: PathBuf,
+    /// The repository's shared git directory (refs, packed-refs)
+    common_dir: PathBuf,
+    /// `.gitignore` matchers by the directory containing them
+    gitignores: HashMap<PathBuf, Gitignore>,
+}
+
+impl WatchTargets {
+    fn resolve(worktree_path: &str) -> Result<Self, String> {
+        let worktree = std::fs::canonicalize(worktree_path)
+            .map_err(|e| format!("Failed to resolve worktree path: {e}"))?;
+
+        let output = silent_command("git")
+            .args(["rev-parse", "--absolute-git-dir", "--git-common-dir"])
+            .current_dir(&worktree)
+            .output_audited()
+            .map_err(|e| format!("Failed to run git rev-parse: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Failed to resolve git directory: {}",
+                stderr.trim()
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        let (Some(git_dir), Some(common_dir)) = (lines.next(), lines.next()) else {
+            return Err("Failed to resolve git directory: unexpected output".to_string());
+        };
+        // --git-common-dir may be relative to the worktree
+        let git_dir = std::fs::canonicalize(git_dir)
+            .map_err(|e| format!("Failed to resolve git directory: {e}"))?;
+        let common_dir = std::fs::canonicalize(worktree.join(common_dir))
+            .map_err(|e| format!("Failed to resolve git directory: {e}"))?;
+
+        Ok(Self {
+            worktree,
+            git_dir,
+            common_dir,
+            gitignores: HashMap::new(),
+        })
+    }
+
+    /// Whether `path` is gitignored. As in git, the closest `.gitignore`
+    /// that matches decides.
+    fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        for dir in path.ancestors().skip(1) {
+            if let Some(gitignore) = self.gitignores.get(dir) {
+                let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+                if !matched.is_none() {
+                    return matched.is_ignore();
+                }
+            }
+            if dir == self.worktree {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Whether a change to `path` can affect git status
+    fn classify(&self, path: &Path) -> Option<ChangeKind> {
+        // Checked first: a main checkout's git dir lives inside the worktree
+        for dir in [&self.git_dir, &self.common_dir] {
+            let Ok(rel) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let name = path.file_name()?.to_string_lossy();
+            let noisy = name.ends_with(".lock")
+                || name == "FETCH_HEAD"
+                || rel.starts_with("objects")
+                || rel.starts_with("logs");
+            return (!noisy).then_some(ChangeKind::GitDir);
+        }
+
+        let rel = path.strip_prefix(&self.worktree).ok()?;
+        if rel.components().any(|c| c.as_os_str() == ".git") {
+            return None;
+        }
+        if self.is_ignored(path) {
+            return None;
+        }
+        Some(ChangeKind::WorkingTree)
+    }
+}
+
+/// State shared between the watcher's event handler and the polling loop
+#[derive(Default)]
+struct Signals {
+    /// Time of the latest relevant event in milliseconds (0 = nothing pending)
+    last_change_ms: AtomicU64,
+    /// Ignore git directory events until this time
+    ignore_git_until_ms: AtomicU64,
+    /// Directories created since the last refresh, to be watched
+    new_dirs: Mutex<Vec<PathBuf>>,
+    /// Working tree paths (relative to the worktree) changed since the last refresh
+    changed_paths: Mutex<HashSet<PathBuf>>,
+    /// More than `MAX_CHANGED_PATHS` changed
+    paths_overflowed: AtomicBool,
+}
+
+/// Changes seen since the last refresh
+#[derive(Debug, Default)]
+pub struct WorktreeChanges {
+    /// Changed working tree paths, relative to the worktree
+    pub paths: Vec<PathBuf>,
+    /// Too many paths changed to list them
+    pub overflowed: bool,
+}
+
+struct ActiveWatch {
+    worktree_path: String,
+    /// None if the watcher failed to start (interval polling is used instead)
+    watcher: Option<RecommendedWatcher>,
+}
+
+/// Watches the active worktree for changes that affect its git status
+#[derive(Default)]
+pub struct WorktreeWatcher {
+    signals: Arc<Signals>,
+    active: Mutex<Option<ActiveWatch>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Directories under `root` to watch, skipping `.git` and gitignored ones
+fn watched_dirs(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let walker = ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut dirs = Vec::new();
+    for entry in walker.flatten() {
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            dirs.push(entry.into_path());
+            if dirs.len() > MAX_WATCHED_DIRS {
+                return Err(format!(
+                    "{} has more than {MAX_WATCHED_DIRS} directories",
+                    root.display()
+                ));
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// `.gitignore` files of the watched directories
+fn load_gitignores(dirs: &[PathBuf]) -> HashMap<PathBuf, Gitignore> {
+    dirs.iter()
+        .filter(|dir| dir.join(".gitignore").is_file())
+        .map(|dir| {
+            let (gitignore, error) = Gitignore::new(dir.join(".gitignore"));
+            if let Some(e) = error {
+                log::trace!("Partially invalid .gitignore in {}: {e}", dir.display());
+            }
+            (dir.clone(), gitignore)
+        })
+        .collect()
+}
+
+fn handle_event(result: notify::Result<Event>, targets: &WatchTargets, signals: &Signals) {
+    let event = match result {
+        Ok(event) => event,
+        Err(e) => {
+            log::warn!("Worktree watcher error: {e}");
+            return;
+        }
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    let may_add_dir = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    );
+
+    let now = now_ms();
+    for path in &event.paths {
+        match targets.classify(path) {
+            Some(ChangeKind::GitDir)
+                if now < signals.ignore_git_until_ms.load(Ordering::Relaxed) => {}
+            Some(kind) => {
+                if kind == ChangeKind::WorkingTree {
+                    if may_add_dir && path.is_dir() {
+                        signals.new_dirs.lock().unwrap().push(path.clone());
+                    }
+                    if let Ok(relative) = path.strip_prefix(&targets.worktree) {
+                        let mut changed = signals.changed_paths.lock().unwrap();
+                        if changed.len() < MAX_CHANGED_PATHS {
+                            changed.insert(relative.to_path_buf());
+                        } else {
+                            signals.paths_overflowed.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+                signals.last_change_ms.store(now, Ordering::Relaxed);
+            }
+            None => {}
+        }
+    }
+}
+
+fn start_watcher(
+    worktree_path: &str,
+    signals: &Arc<Signals>,
+) -> Result<RecommendedWatcher, String> {
+    let mut targets = WatchTargets::resolve(worktree_path)?;
+    let dirs = watched_dirs(&targets.worktree)?;
+    targets.gitignores = load_gitignores(&dirs);
+    let targets = Arc::new(targets);
+
+    let handler_targets = Arc::clone(&targets);
+    let handler_signals = Arc::clone(signals);
+    let mut watcher = notify::recommended_watcher(move |result| {
+        handle_event(result, &handler_targets, &handler_signals)
+    })
+    .map_err(|e| format!("Failed to create watcher: {e}"))?;
+
+    let watch = |watcher: &mut RecommendedWatcher, path: &Path, mode| {
+        watcher
+            .watch(path, mode)
+            .map_err(|e| format!("Failed to watch {}: {e}", path.display()))
+    };
+    for dir in &dirs {
+        watch(&mut watcher, dir, RecursiveMode::NonRecursive)?;
+    }
+    watch(&mut watcher, &targets.git_dir, RecursiveMode::NonRecursive)?;
+    if targets.common_dir != targets.git_dir {
+        watch(
+            &mut watcher,
+            &targets.common_dir,
+            RecursiveMode::NonRecursive,
+        )?;
+    }
+    watch(
+        &mut watcher,
+        &targets.common_dir.join("refs"),
+        RecursiveMode::Recursive,
+    )?;
+
+    log::trace!(
+        "Watching {} directories in {}",
+        dirs.len(),
+        targets.worktree.display()
+    );
+    Ok(watcher)
+}
+
+impl WorktreeWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch `worktree_path` (or nothing), replacing the previous target.
+    ///
+    /// Returns whether the worktree is being watched; `false` means the caller
+    /// should fall back to interval polling.
+    pub fn watch(&self, worktree_path: Option<&str>) -> bool {
+        let mut active = self.active.lock().unwrap();
+        let Some(worktree_path) = worktree_path else {
+            *active = None;
+            return false;
+        };
+        if let Some(current) = active.as_ref().filter(|a| a.worktree_path == worktree_path) {
+            return current.watcher.is_some();
+        }
+
+        // Stop the old watcher before clearing its pending state
+        *active = None;
+        self.signals.last_change_ms.store(0, Ordering::Relaxed);
+        self.signals.new_dirs.lock().unwrap().clear();
+        self.signals.changed_paths.lock().unwrap().clear();
+        self.signals
+            .paths_overflowed
+            .store(false, Ordering::Relaxed);
+
+        let watcher = match start_watcher(worktree_path, &self.signals) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                log::warn!("Falling back to interval polling for {worktree_path}: {e}");
+                None
+            }
+        };
+        let watching = watcher.is_some();
+        *active = Some(ActiveWatch {
+            worktree_path: worktree_path.to_string(),
+            watcher,
+        });
+        watching
+    }
+
+    /// Whether a change was seen and no further events arrived for a moment
+    pub fn has_settled_change(&self) -> bool {
+        let last = self.signals.last_change_ms.load(Ordering::Relaxed);
+        last != 0 && now_ms().saturating_sub(last) >= SETTLE_MS
+    }
+
+    /// Consume a settled change, watching any directories created since.
+    /// Returns the changed working tree paths (empty if only git state changed).
+    pub fn take_change(&self) -> Option<WorktreeChanges> {
+        if !self.has_settled_change() {
+            return None;
+        }
+        self.signals.last_change_ms.store(0, Ordering::Relaxed);
+        let changes = WorktreeChanges {
+            paths: std::mem::take(&mut *self.signals.changed_paths.lock().unwrap())
+                .into_iter()
+                .collect(),
+            overflowed: self.signals.paths_overflowed.swap(false, Ordering::Relaxed),
+        };
+
+        let new_dirs = std::mem::take(&mut *self.signals.new_dirs.lock().unwrap());
+        let mut active = self.active.lock().unwrap();
+        if let Some(watcher) = active.as_mut().and_then(|a| a.watcher.as_mut()) {
+            for dir in new_dirs
+                .iter()
+                .flat_map(|d| watched_dirs(d).unwrap_or_default())
+            {
+                if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                    log::trace!("Failed to watch new directory {}: {e}", dir.display());
+                }
+            }
+        }
+        Some(changes)
+    }
+
+    /// Call before running a status poll, so its own git writes are ignored
+    pub fn begin_poll(&self) {
+        self.signals
+            .ignore_git_until_ms
+            .store(u64::MAX, Ordering::Relaxed);
+    }
+
+    /// Call after a status poll completes
+    pub fn end_poll(&self) {
+        self.signals
+            .ignore_git_until_ms
+            .store(now_ms() + OWN_POLL_GRACE_MS, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_ignores_noise() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n*.log\n").unwrap();
+        std::fs::create_dir(root.join("app")).unwrap();
+        std::fs::write(root.join("app/.gitignore"), "/target/\n!keep.log\n").unwrap();
+        let targets = WatchTargets {
+            worktree: root.clone(),
+            git_dir: root.join(".git"),
+            common_dir: root.join(".git"),
+            gitignores: load_gitignores(&[root.clone(), root.join("app")]),
+        };
+
+        let kind = |rel: &str| targets.classify(&root.join(rel));
+        assert_eq!(kind("src/main.rs"), Some(ChangeKind::WorkingTree));
+        assert_eq!(kind("node_modules/pkg/index.js"), None);
+        assert_eq!(kind("debug.log"), None);
+        assert_eq!(kind("app/target/debug/app"), None);
+        assert_eq!(kind("target/notes.md"), Some(ChangeKind::WorkingTree));
+        assert_eq!(kind("app/keep.log"), Some(ChangeKind::WorkingTree));
+        assert_eq!(kind(".git/index"), Some(ChangeKind::GitDir));
+        assert_eq!(kind(".git/refs/heads/main"), Some(ChangeKind::GitDir));
+        assert_eq!(kind(".git/index.lock"), None);
+        assert_eq!(kind(".git/FETCH_HEAD"), None);
+        assert_eq!(kind(".git/objects/ab/cdef"), None);
+    }
+}
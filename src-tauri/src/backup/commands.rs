@@ -0,0 +1,165 @@
+//! Tauri commands for backup and restore
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::{
+    default_backup_path, extract_backup, move_entries, read_manifest, write_backup, BackupManifest,
+    BACKUPS_DIR_NAME,
+};
+use crate::http_server::EmitExt;
+
+/// Result of creating a backup
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub manifest: BackupManifest,
+}
+
+/// Result of restoring a backup
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub manifest: BackupManifest,
+    /// Where the data that was replaced has been moved to
+    pub previous_data_path: String,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Validate extracted backup contents before anything is replaced
+fn validate_extracted(dir: &Path) -> Result<(), String> {
+    crate::db::check_integrity(&dir.join(crate::db::DB_FILE_NAME))?;
+
+    let prefs_path = dir.join("preferences.json");
+    if prefs_path.exists() {
+        let contents = fs::read_to_string(&prefs_path)
+            .map_err(|e| format!("Failed to read backed up preferences: {e}"))?;
+        serde_json::from_str::<crate::AppPreferences>(&contents)
+            .map_err(|e| format!("Backed up preferences are invalid: {e}"))?;
+    }
+
+    Ok(())
+}
+
+/// Create a single archive with projects, sessions, contexts and preferences.
+///
+/// Written to `destination` if given, otherwise to `<app data>/backups/`.
+#[tauri::command]
+pub async fn create_backup(
+    app: AppHandle,
+    destination: Option<String>,
+) -> Result<BackupInfo, String> {
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
+    let created_at = now_secs();
+    let dest = match destination {
+        Some(d) => PathBuf::from(d),
+        None => default_backup_path(&app_data_dir, created_at),
+    };
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+    }
+
+    log::trace!("Creating backup at {dest:?}");
+
+    let snapshot = app_data_dir.join(format!(".backup-{}.db", uuid::Uuid::new_v4()));
+    crate::db::snapshot_to(&app, &snapshot)?;
+
+    let app_version = app.package_info().version.to_string();
+    let result = write_backup(&app_data_dir, &snapshot, &dest, &app_version, created_at);
+    let _ = fs::remove_file(&snapshot);
+
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_file(&dest);
+            return Err(e);
+        }
+    };
+
+    let size_bytes = fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    log::trace!("Backup created: {dest:?} ({size_bytes} bytes)");
+
+    Ok(BackupInfo {
+        path: dest.to_string_lossy().to_string(),
+        size_bytes,
+        manifest,
+    })
+}
+
+/// Restore app data from a backup archive.
+///
+/// The archive is validated (manifest version, database integrity, preferences)
+/// before anything is touched. Current data is moved to
+/// `<app data>/backups/pre-restore-<timestamp>/` rather than deleted.
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, path: String) -> Result<RestoreResult, String> {
+    if crate::updater::has_running_sessions() {
+        return Err("Cannot restore a backup while sessions are running".to_string());
+    }
+
+    let archive_path = PathBuf::from(&path);
+    let manifest = read_manifest(&archive_path)?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
+
+    log::trace!(
+        "Restoring backup {path} (created {} by Jean {})",
+        manifest.created_at,
+        manifest.app_version
+    );
+
+    let staging_dir = app_data_dir.join(format!(".restore-{}", uuid::Uuid::new_v4()));
+    let result = (|| {
+        extract_backup(&archive_path, &staging_dir)?;
+        validate_extracted(&staging_dir)?;
+
+        let previous_dir = app_data_dir
+            .join(BACKUPS_DIR_NAME)
+            .join(format!("pre-restore-{}", now_secs()));
+
+        crate::db::with_db_closed(|| {
+            // Stale WAL files would be replayed on top of the restored database
+            let db_path = app_data_dir.join(crate::db::DB_FILE_NAME);
+            for suffix in ["-wal", "-shm"] {
+                let mut sidecar = db_path.as_os_str().to_owned();
+                sidecar.push(suffix);
+                let _ = fs::remove_file(PathBuf::from(sidecar));
+            }
+
+            move_entries(&app_data_dir, &previous_dir, &manifest.entries)?;
+            if let Err(e) = move_entries(&staging_dir, &app_data_dir, &manifest.entries) {
+                // Put the current data back where it was
+                if let Err(e) = move_entries(&previous_dir, &app_data_dir, &manifest.entries) {
+                    log::error!("Failed to roll back restore from {previous_dir:?}: {e}");
+                }
+                return Err(e);
+            }
+            Ok(())
+        })?;
+
+        Ok::<_, String>(previous_dir)
+    })();
+    let _ = fs::remove_dir_all(&staging_dir);
+    let previous_dir = result?;
+
+    let restore_result = RestoreResult {
+        manifest,
+        previous_data_path: previous_dir.to_string_lossy().to_string(),
+    };
+
+    if let Err(e) = app.emit_all("backup:restored", &restore_result) {
+        log::error!("Failed to emit backup:restored event: {e}");
+    }
+
+    Ok(restore_result)
+}
@@ -0,0 +1,324 @@
+//! Full backup and restore of Jean's app data
+//!
+//! A backup is a single zip archive containing the database (projects,
+//! worktrees, session indexes and metadata), preferences, UI state, session
+//! run logs, saved contexts, pasted files and avatars, plus a manifest used to
+//! validate the archive on restore.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub mod commands;
+
+/// Archive format version, bumped on incompatible layout changes
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest entry inside the archive
+pub const MANIFEST_NAME: &str = "backup-manifest.json";
+
+/// Directory (inside app data) where backups are written by default
+pub const BACKUPS_DIR_NAME: &str = "backups";
+
+/// Single files included in a backup (relative to the app data directory).
+/// The database is added separately from a consistent snapshot.
+pub const BACKUP_FILES: &[&str] = &["preferences.json", "ui-state.json", "onboarding.json"];
+
+/// Directories included in a backup (relative to the app data directory)
+pub const BACKUP_DIRS: &[&str] = &[
+    "sessions/data",
+    "session-context",
+    "pasted-images",
+    "pasted-texts",
+    "avatars",
+];
+
+/// Check that a manifest entry is one of the names a backup is made of, so a
+/// crafted manifest can't make restore replace paths outside the app data
+/// directory
+fn validate_entry(entry: &str) -> Result<(), String> {
+    let known = entry == crate::db::DB_FILE_NAME
+        || BACKUP_FILES.contains(&entry)
+        || BACKUP_DIRS.contains(&entry);
+    let relative = Path::new(entry)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)));
+    if known && relative {
+        Ok(())
+    } else {
+        Err(format!("Backup manifest has an invalid entry: {entry}"))
+    }
+}
+
+/// Manifest stored at the root of every backup archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: u64,
+    /// Top-level entries included in the archive
+    pub entries: Vec<String>,
+}
+
+/// Add a file to the archive under `name`
+fn add_file<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    path: &Path,
+    name: &str,
+) -> Result<(), String> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {path:?}: {e}"))?;
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to add {name} to backup: {e}"))?;
+    std::io::copy(&mut file, zip).map_err(|e| format!("Failed to write {name} to backup: {e}"))?;
+    Ok(())
+}
+
+/// Recursively add a directory to the archive under `prefix`
+fn add_dir<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    dir: &Path,
+    prefix: &str,
+) -> Result<u32, String> {
+    let mut count = 0;
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {dir:?}: {e}"))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let entry_name = format!("{prefix}/{name}");
+
+        if path.is_dir() {
+            count += add_dir(zip, &path, &entry_name)?;
+        } else if path.is_file() {
+            add_file(zip, &path, &entry_name)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Write a backup archive from the app data directory.
+///
+/// `db_snapshot` is a consistent copy of the database (see `db::snapshot_to`).
+pub fn write_backup(
+    app_data_dir: &Path,
+    db_snapshot: &Path,
+    dest: &Path,
+    app_version: &str,
+    created_at: u64,
+) -> Result<BackupManifest, String> {
+    let file = File::create(dest).map_err(|e| format!("Failed to create backup file: {e}"))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut entries = Vec::new();
+
+    add_file(&mut zip, db_snapshot, crate::db::DB_FILE_NAME)?;
+    entries.push(crate::db::DB_FILE_NAME.to_string());
+
+    for name in BACKUP_FILES {
+        let path = app_data_dir.join(name);
+        if path.is_file() {
+            add_file(&mut zip, &path, name)?;
+            entries.push(name.to_string());
+        }
+    }
+
+    for name in BACKUP_DIRS {
+        let path = app_data_dir.join(name);
+        if path.is_dir() {
+            let count = add_dir(&mut zip, &path, name)?;
+            log::trace!("Added {count} file(s) from {name} to backup");
+            entries.push(name.to_string());
+        }
+    }
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        created_at,
+        entries,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {e}"))?;
+
+    zip.start_file(MANIFEST_NAME, zip::write::SimpleFileOptions::default())
+        .map_err(|e| format!("Failed to add manifest to backup: {e}"))?;
+    zip.write_all(manifest_json.as_bytes())
+        .map_err(|e| format!("Failed to write backup manifest: {e}"))?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize backup: {e}"))?;
+
+    Ok(manifest)
+}
+
+/// Read and validate the manifest of a backup archive
+pub fn read_manifest(archive_path: &Path) -> Result<BackupManifest, String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open backup: {e}"))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a valid backup archive: {e}"))?;
+
+    let mut manifest_file = archive
+        .by_name(MANIFEST_NAME)
+        .map_err(|_| "Backup is missing its manifest".to_string())?;
+    let mut contents = String::new();
+    manifest_file
+        .read_to_string(&mut contents)
+        .map_err(|e| format!("Failed to read backup manifest: {e}"))?;
+
+    let manifest: BackupManifest =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid backup manifest: {e}"))?;
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} is newer than supported ({BACKUP_FORMAT_VERSION}). Update Jean to restore it.",
+            manifest.format_version
+        ));
+    }
+    for entry in &manifest.entries {
+        validate_entry(entry)?;
+    }
+    if !manifest
+        .entries
+        .iter()
+        .any(|e| e == crate::db::DB_FILE_NAME)
+    {
+        return Err("Backup does not contain a database".to_string());
+    }
+
+    Ok(manifest)
+}
+
+/// Extract a backup archive into `dest` (entries escaping `dest` are skipped)
+pub fn extract_backup(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open backup: {e}"))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a valid backup archive: {e}"))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read backup entry: {e}"))?;
+
+        let outpath = match file.enclosed_name() {
+            Some(path) => dest.join(path),
+            None => continue,
+        };
+
+        if file.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| format!("Failed to create directory: {e}"))?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                fs::create_dir_all(p)
+                    .map_err(|e| format!("Failed to create parent directory: {e}"))?;
+            }
+            let mut outfile =
+                File::create(&outpath).map_err(|e| format!("Failed to create file: {e}"))?;
+            std::io::copy(&mut file, &mut outfile)
+                .map_err(|e| format!("Failed to extract file: {e}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn move_entry(source: &Path, target: &Path) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {parent:?}: {e}"))?;
+    }
+    if target.is_dir() {
+        fs::remove_dir_all(target).map_err(|e| format!("Failed to remove {target:?}: {e}"))?;
+    } else if target.exists() {
+        fs::remove_file(target).map_err(|e| format!("Failed to remove {target:?}: {e}"))?;
+    }
+    fs::rename(source, target).map_err(|e| format!("Failed to move {source:?} to {target:?}: {e}"))
+}
+
+/// Move `entries` from `from_dir` to `to_dir`, replacing whatever is there.
+/// If a move fails, the entries already moved are moved back.
+pub fn move_entries(from_dir: &Path, to_dir: &Path, entries: &[String]) -> Result<(), String> {
+    let mut moved: Vec<&String> = Vec::new();
+    for entry in entries {
+        validate_entry(entry)?;
+        let source = from_dir.join(entry);
+        if !source.exists() {
+            continue;
+        }
+        if let Err(e) = move_entry(&source, &to_dir.join(entry)) {
+            for done in moved.into_iter().rev() {
+                if let Err(e) = move_entry(&to_dir.join(done), &from_dir.join(done)) {
+                    log::error!("Failed to move {done} back after a failed restore: {e}");
+                }
+            }
+            return Err(e);
+        }
+        moved.push(entry);
+    }
+    Ok(())
+}
+
+/// Default backup file path: <app data>/backups/jean-backup-<timestamp>.zip
+pub fn default_backup_path(app_data_dir: &Path, created_at: u64) -> PathBuf {
+    app_data_dir
+        .join(BACKUPS_DIR_NAME)
+        .join(format!("jean-backup-{created_at}.zip"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_roundtrip() {
+        let source = tempfile::tempdir().unwrap();
+        let root = source.path();
+        fs::write(root.join("preferences.json"), "{}").unwrap();
+        fs::create_dir_all(root.join("sessions/data/sess-1")).unwrap();
+        fs::write(root.join("sessions/data/sess-1/run-1.jsonl"), "{}\n").unwrap();
+        let db_snapshot = root.join("snapshot.db");
+        fs::write(&db_snapshot, "db").unwrap();
+
+        let archive = root.join("backup.zip");
+        let manifest = write_backup(root, &db_snapshot, &archive, "0.1.0", 42).unwrap();
+        assert!(manifest.entries.contains(&"jean.db".to_string()));
+        assert!(manifest.entries.contains(&"sessions/data".to_string()));
+        assert!(!manifest.entries.contains(&"avatars".to_string()));
+
+        let read = read_manifest(&archive).unwrap();
+        assert_eq!(read.created_at, 42);
+
+        let dest = tempfile::tempdir().unwrap();
+        extract_backup(&archive, dest.path()).unwrap();
+        assert!(dest.path().join("jean.db").exists());
+        assert!(dest
+            .path()
+            .join("sessions/data/sess-1/run-1.jsonl")
+            .exists());
+    }
+
+    #[test]
+    fn test_validate_entry() {
+        assert!(validate_entry("jean.db").is_ok());
+        assert!(validate_entry("sessions/data").is_ok());
+        assert!(validate_entry("/etc").is_err());
+        assert!(validate_entry("../projects").is_err());
+        assert!(validate_entry("sessions/../..").is_err());
+        assert!(validate_entry("notes.txt").is_err());
+    }
+
+    #[test]
+    fn test_read_manifest_rejects_non_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-a-backup.zip");
+        fs::write(&path, "hello").unwrap();
+        assert!(read_manifest(&path).is_err());
+    }
+}
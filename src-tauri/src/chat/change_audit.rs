@@ -0,0 +1,291 @@
+//! Audit log of AI-initiated file changes
+//!
+//! Every file Claude writes or edits (Write, Edit, MultiEdit and NotebookEdit
+//! tool results in the stream) and every file saved through
+//! `write_file_content` is recorded, without its content, in an append-only
+//! JSONL file per worktree (`change-audit/<worktree id>.jsonl` in the app data
+//! directory). Entries are never rewritten; the file is deleted with its
+//! worktree.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::types::ToolCall;
+use crate::projects::storage::load_projects_data;
+
+/// Claude tools that change files
+const FILE_TOOLS: [&str; 4] = ["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// Tool call IDs already recorded, per worktree, loaded from its log on the
+/// first append. The lock also serializes appends from concurrent sessions.
+static RECORDED: Lazy<Mutex<HashMap<String, HashSet<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Who made a change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    /// A tool call of a Claude session
+    Claude,
+    /// A save from Jean's file editor
+    Editor,
+}
+
+/// A recorded file change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChangeAuditEntry {
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+    pub worktree_id: String,
+    pub source: ChangeSource,
+    /// Tool name, or `write_file_content` for editor saves
+    pub tool: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    /// Bytes written (whole-file writes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+    /// Replacements made (edits)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edits: Option<usize>,
+    /// The tool reported an error, so the file may be unchanged
+    #[serde(default)]
+    pub failed: bool,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn audit_path(data_dir: &Path, worktree_id: &str) -> PathBuf {
+    data_dir.join("change-audit").join(format!(
+        "{}.jsonl",
+        super::storage::sanitize_filename(worktree_id)
+    ))
+}
+
+fn audit_file(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, String> {
+    let path = audit_path(&crate::locations::app_data_dir(app)?, worktree_id);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create change audit directory: {e}"))?;
+    }
+    Ok(path)
+}
+
+/// Path, bytes written and edits made by a file tool call (None for other
+/// tools)
+fn file_change(
+    name: &str,
+    input: &serde_json::Value,
+) -> Option<(String, Option<usize>, Option<usize>)> {
+    if !FILE_TOOLS.contains(&name) {
+        return None;
+    }
+    let path = input
+        .get("file_path")
+        .or_else(|| input.get("notebook_path"))
+        .and_then(|v| v.as_str())?
+        .to_string();
+    let (bytes, edits) = match name {
+        "Write" => (
+            input.get("content").and_then(|v| v.as_str()).map(str::len),
+            None,
+        ),
+        "MultiEdit" => (
+            None,
+            input.get("edits").and_then(|v| v.as_array()).map(Vec::len),
+        ),
+        _ => (None, Some(1)),
+    };
+    Some((path, bytes, edits))
+}
+
+/// Append an entry. Entries of a tool call already recorded (a resumed run
+/// re-reads its output from the start) are skipped.
+fn append(app: &AppHandle, entry: &ChangeAuditEntry) -> Result<(), String> {
+    let path = audit_file(app, &entry.worktree_id)?;
+    let mut recorded = RECORDED.lock().unwrap();
+    let ids = recorded
+        .entry(entry.worktree_id.clone())
+        .or_insert_with(|| {
+            let log = std::fs::read_to_string(&path).unwrap_or_default();
+            parse_entries(&log, None)
+                .into_iter()
+                .filter_map(|e| e.tool_use_id)
+                .collect()
+        });
+    if let Some(tool_use_id) = &entry.tool_use_id {
+        if ids.contains(tool_use_id) {
+            return Ok(());
+        }
+    }
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize change audit entry: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open change audit log: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("Failed to write change audit log: {e}"))?;
+    if let Some(tool_use_id) = &entry.tool_use_id {
+        ids.insert(tool_use_id.clone());
+    }
+    Ok(())
+}
+
+/// Record the result of a Claude tool call if it changed a file
+pub fn record_tool_result(
+    app: &AppHandle,
+    session_id: &str,
+    worktree_id: &str,
+    tool_call: &ToolCall,
+    is_error: bool,
+) {
+    let Some((path, bytes, edits)) = file_change(&tool_call.name, &tool_call.input) else {
+        return;
+    };
+    let entry = ChangeAuditEntry {
+        timestamp: now(),
+        worktree_id: worktree_id.to_string(),
+        source: ChangeSource::Claude,
+        tool: tool_call.name.clone(),
+        path,
+        session_id: Some(session_id.to_string()),
+        tool_use_id: Some(tool_call.id.clone()),
+        bytes,
+        edits,
+        failed: is_error,
+    };
+    if let Err(e) = append(app, &entry) {
+        log::warn!("Failed to record {} of {}: {e}", entry.tool, entry.path);
+    }
+}
+
+/// Record a file saved through `write_file_content`, against the worktree
+/// containing it (files outside worktrees aren't recorded)
+pub fn record_editor_write(app: &AppHandle, path: &str, bytes: usize) {
+    let worktree_id = load_projects_data(app).ok().and_then(|data| {
+        data.worktrees
+            .iter()
+            .filter(|w| Path::new(path).starts_with(&w.path))
+            .max_by_key(|w| w.path.len())
+            .map(|w| w.id.clone())
+    });
+    let Some(worktree_id) = worktree_id else {
+        return;
+    };
+    let entry = ChangeAuditEntry {
+        timestamp: now(),
+        worktree_id,
+        source: ChangeSource::Editor,
+        tool: "write_file_content".to_string(),
+        path: path.to_string(),
+        session_id: None,
+        tool_use_id: None,
+        bytes: Some(bytes),
+        edits: None,
+        failed: false,
+    };
+    if let Err(e) = append(app, &entry) {
+        log::warn!("Failed to record write of {path}: {e}");
+    }
+}
+
+/// Delete a worktree's change audit log
+pub fn delete(app: &AppHandle, worktree_id: &str) {
+    if let Ok(data_dir) = crate::locations::app_data_dir(app) {
+        delete_in(&data_dir, worktree_id);
+    }
+}
+
+/// `delete` for a given app data directory (used by `jean-cli`)
+pub fn delete_in(data_dir: &Path, worktree_id: &str) {
+    RECORDED.lock().unwrap().remove(worktree_id);
+    let path = audit_path(data_dir, worktree_id);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to delete change audit log of {worktree_id}: {e}");
+        }
+    }
+}
+
+/// Entries of a JSONL log recorded at or after `since` (malformed lines, e.g.
+/// a partial write, are skipped)
+fn parse_entries(log: &str, since: Option<u64>) -> Vec<ChangeAuditEntry> {
+    log.lines()
+        .filter_map(|line| serde_json::from_str::<ChangeAuditEntry>(line).ok())
+        .filter(|entry| since.is_none_or(|since| entry.timestamp >= since))
+        .collect()
+}
+
+/// File changes made in a worktree, oldest first, optionally only those
+/// recorded at or after `since` (Unix seconds)
+#[tauri::command]
+pub async fn get_change_audit(
+    app: AppHandle,
+    worktree_id: String,
+    since: Option<u64>,
+) -> Result<Vec<ChangeAuditEntry>, String> {
+    let path = audit_file(&app, &worktree_id)?;
+    let log = match std::fs::read_to_string(&path) {
+        Ok(log) => log,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read change audit log: {e}")),
+    };
+    Ok(parse_entries(&log, since))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_file_change() {
+        assert_eq!(
+            file_change(
+                "Write",
+                &json!({"file_path": "/w/a.rs", "content": "fn a() {}"})
+            ),
+            Some(("/w/a.rs".to_string(), Some(9), None))
+        );
+        assert_eq!(
+            file_change(
+                "MultiEdit",
+                &json!({"file_path": "/w/a.rs", "edits": [{}, {}]})
+            ),
+            Some(("/w/a.rs".to_string(), None, Some(2)))
+        );
+        assert_eq!(
+            file_change("NotebookEdit", &json!({"notebook_path": "/w/n.ipynb"})),
+            Some(("/w/n.ipynb".to_string(), None, Some(1)))
+        );
+        assert_eq!(file_change("Read", &json!({"file_path": "/w/a.rs"})), None);
+    }
+
+    #[test]
+    fn test_parse_entries_since() {
+        let log = r#"{"timestamp":100,"worktree_id":"w1","source":"claude","tool":"Edit","path":"/w/a.rs","tool_use_id":"t1","edits":1}
+{"timestamp":200,"worktree_id":"w1","source":"editor","tool":"write_file_content","path":"/w/b.rs","bytes":3}
+{"timestamp":300,"worktree"#;
+        assert_eq!(parse_entries(log, None).len(), 2);
+        let recent = parse_entries(log, Some(150));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].source, ChangeSource::Editor);
+        assert!(!recent[0].failed);
+    }
+}
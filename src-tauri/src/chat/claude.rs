@@ -1,11 +1,10 @@
-use tauri::Manager;
-
 use super::types::{
     CompactMetadata, ContentBlock, EffortLevel, ThinkingLevel, ToolCall, UsageData,
 };
 use crate::http_server::EmitExt;
 use crate::projects::github_issues::{
-    get_github_contexts_dir, get_worktree_issue_refs, get_worktree_pr_refs,
+    get_github_contexts_dir, get_tracker_context_path, get_worktree_issue_refs,
+    get_worktree_pr_refs, get_worktree_tracker_refs,
 };
 
 // =============================================================================
@@ -137,6 +136,31 @@ struct CompactedEvent {
 // Detached Claude CLI execution
 // =============================================================================
 
+/// Combined context file of a session's runs. It holds plaintext copies of
+/// the attached contexts, so it's deleted once the run is over.
+fn combined_context_path(
+    app_data_dir: &std::path::Path,
+    worktree_id: &str,
+    session_id: &str,
+) -> std::path::PathBuf {
+    app_data_dir
+        .join("combined-contexts")
+        .join(format!("{worktree_id}-{session_id}-combined.md"))
+}
+
+/// Delete a session's combined context file, once its run is over
+pub fn remove_combined_context(app: &tauri::AppHandle, worktree_id: &str, session_id: &str) {
+    let Ok(app_data_dir) = crate::locations::app_data_dir(app) else {
+        return;
+    };
+    let path = combined_context_path(&app_data_dir, worktree_id, session_id);
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Failed to delete combined context file {path:?}: {e}");
+        }
+    }
+}
+
 /// Build CLI arguments for Claude CLI.
 ///
 /// Returns a tuple of (args, env_vars) where env_vars are (key, value) pairs.
@@ -167,7 +191,7 @@ fn build_claude_args(
     args.push("--verbose".to_string());
 
     // Add app data directories
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::locations::app_data_dir(app) {
         if cfg!(debug_assertions) {
             args.push("--add-dir".to_string());
             args.push(app_data_dir.to_string_lossy().to_string());
@@ -201,6 +225,16 @@ fn build_claude_args(
         }
     }
 
+    // MCP servers of jean.json and the project's settings
+    match crate::projects::mcp::config_file(app, worktree_id) {
+        Ok(Some(path)) => {
+            args.push("--mcp-config".to_string());
+            args.push(path.to_string_lossy().to_string());
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to prepare MCP config: {e}"),
+    }
+
     // Model
     if let Some(m) = model {
         args.push("--model".to_string());
@@ -216,6 +250,9 @@ fn build_claude_args(
     args.push("--permission-mode".to_string());
     args.push(perm_mode.to_string());
 
+    // Claude CLI settings, passed as a single --settings JSON
+    let mut settings = serde_json::Map::new();
+
     // Thinking/Effort configuration
     // If disable_thinking_in_non_plan_modes is true and mode is build/yolo, force off
     let is_non_plan_override = disable_thinking_in_non_plan_modes && {
@@ -232,9 +269,7 @@ fn build_claude_args(
         };
 
         if let Some(effort_value) = effective_effort.effort_value() {
-            let settings = format!(r#"{{"effortLevel": "{effort_value}"}}"#);
-            args.push("--settings".to_string());
-            args.push(settings);
+            settings.insert("effortLevel".to_string(), effort_value.into());
         }
         // If Off, don't send any thinking/effort settings
     } else {
@@ -246,13 +281,10 @@ fn build_claude_args(
         };
 
         if let Some(level) = effective_thinking_level {
-            let settings = if level.is_enabled() {
-                r#"{"alwaysThinkingEnabled": true}"#
-            } else {
-                r#"{"alwaysThinkingEnabled": false}"#
-            };
-            args.push("--settings".to_string());
-            args.push(settings.to_string());
+            settings.insert(
+                "alwaysThinkingEnabled".to_string(),
+                level.is_enabled().into(),
+            );
 
             if let Some(tokens) = level.thinking_tokens() {
                 env_vars.push(("MAX_THINKING_TOKENS".to_string(), tokens.to_string()));
@@ -260,9 +292,24 @@ fn build_claude_args(
         }
     }
 
-    // Allowed tools
+    // Tool permissions of the project and session
+    let tool_policy = super::tool_policy::for_session(app, worktree_id, session_id);
+    if let Some(permissions) = super::tool_policy::permissions_settings(&tool_policy) {
+        settings.insert("permissions".to_string(), permissions);
+    }
+
+    if !settings.is_empty() {
+        args.push("--settings".to_string());
+        args.push(serde_json::Value::Object(settings).to_string());
+    }
+
+    // Allowed tools (approvals never override the tool policy's deny list)
     if let Some(tools) = allowed_tools {
         for tool in tools {
+            if super::tool_policy::is_denied(&tool_policy, tool) {
+                log::warn!("Not allowing {tool}: denied by the tool policy");
+                continue;
+            }
             args.push("--allowedTools".to_string());
             args.push(tool.clone());
         }
@@ -364,8 +411,20 @@ fn build_claude_args(
         }
     }
 
+    // Check for issue context files from other trackers (Jira, Linear)
+    if let Ok(tracker_refs) = get_worktree_tracker_refs(app, worktree_id, None) {
+        for (provider, id) in tracker_refs {
+            if let Ok(file_path) = get_tracker_context_path(app, &provider, &id) {
+                if file_path.exists() {
+                    log::trace!("Adding {provider} issue context file: {:?}", file_path);
+                    all_context_paths.push(file_path);
+                }
+            }
+        }
+    }
+
     // Check for attached saved context files
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::locations::app_data_dir(app) {
         let saved_contexts_dir = app_data_dir.join("session-context");
         if saved_contexts_dir.exists() {
             let prefix = format!("{worktree_id}-context-");
@@ -395,11 +454,11 @@ fn build_claude_args(
     // If we have context files OR system prompt parts, create a combined context file
     let has_system_prompts = !system_prompt_parts.is_empty();
     if !all_context_paths.is_empty() || has_system_prompts {
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
-            let combined_contexts_dir = app_data_dir.join("combined-contexts");
-            let _ = std::fs::create_dir_all(&combined_contexts_dir);
-
-            let combined_file = combined_contexts_dir.join(format!("{worktree_id}-combined.md"));
+        if let Ok(app_data_dir) = crate::locations::app_data_dir(app) {
+            let combined_file = combined_context_path(&app_data_dir, worktree_id, session_id);
+            if let Some(dir) = combined_file.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
 
             // Count issues, PRs, and saved contexts for the header
             let issue_count = all_context_paths
@@ -462,7 +521,7 @@ fn build_claude_args(
             }
 
             for path in &all_context_paths {
-                if let Ok(content) = std::fs::read_to_string(path) {
+                if let Ok(content) = crate::encryption::read_to_string(path) {
                     log::debug!("Adding context file to combined: {:?}", path);
                     combined_content.push_str(&content);
                     combined_content.push_str("\n\n---\n\n");
@@ -553,7 +612,20 @@ pub fn execute_claude_detached(
         error_msg
     })?;
 
-    if !cli_path.exists() {
+    // Projects using a devcontainer run the container's Claude CLI instead
+    let container = crate::projects::devcontainer::exec_for(app, &working_dir.to_string_lossy())
+        .map_err(|e| {
+            log::error!("{e}");
+            let error_event = ErrorEvent {
+                session_id: session_id.to_string(),
+                worktree_id: worktree_id.to_string(),
+                error: e.clone(),
+            };
+            let _ = app.emit_all("chat:error", &error_event);
+            e
+        })?;
+
+    if container.is_none() && !cli_path.exists() {
         let error_msg =
             "Claude CLI not installed. Please complete setup in Settings > Advanced.".to_string();
         log::error!("{error_msg}");
@@ -582,6 +654,28 @@ pub fn execute_claude_detached(
         ai_language,
     );
 
+    // In a devcontainer, the exec wrapper (docker / devcontainer CLI) is the
+    // spawned process and passes the environment on to Claude
+    let (cli_path, args, env_vars) = match container {
+        Some(exec) => {
+            let argv: Vec<String> = std::iter::once("claude".to_string()).chain(args).collect();
+            let env: Vec<(&str, &str)> = env_vars
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            let (program, args) = exec.command_line(&argv, &env, false);
+            (std::path::PathBuf::from(program), args, Vec::new())
+        }
+        None => {
+            // direnv / Nix environment of the worktree, so Claude's tools match it
+            let mut env_vars = env_vars;
+            env_vars.extend(crate::projects::dev_env::env_for(
+                &working_dir.to_string_lossy(),
+            ));
+            (cli_path, args, env_vars)
+        }
+    };
+
     // Log the full Claude CLI command for debugging
     log::debug!(
         "Claude CLI command: {} {}",
@@ -625,16 +719,10 @@ pub fn execute_claude_detached(
 
     // Tail the output file for real-time updates
     // Use match to ensure unregister_process is always called, even on error
-    let response = match tail_claude_output(app, session_id, worktree_id, output_file, pid) {
-        Ok(resp) => {
-            super::registry::unregister_process(session_id);
-            resp
-        }
-        Err(e) => {
-            super::registry::unregister_process(session_id);
-            return Err(e);
-        }
-    };
+    let result = tail_claude_output(app, session_id, worktree_id, output_file, pid);
+    super::registry::unregister_process(session_id);
+    remove_combined_context(app, worktree_id, session_id);
+    let response = result?;
 
     Ok((pid, response))
 }
@@ -905,6 +993,19 @@ pub fn tail_claude_output(
                                         tool_calls.iter_mut().find(|t| t.id == tool_id)
                                     {
                                         tc.output = Some(output.to_string());
+
+                                        // Record file writes/edits in the change audit log
+                                        let is_error = block
+                                            .get("is_error")
+                                            .and_then(|v| v.as_bool())
+                                            .unwrap_or(false);
+                                        super::change_audit::record_tool_result(
+                                            app,
+                                            session_id,
+                                            worktree_id,
+                                            tc,
+                                            is_error,
+                                        );
                                     }
 
                                     // Emit tool_result event
@@ -949,6 +1050,7 @@ pub fn tail_claude_output(
                                 .get("cache_creation_input_tokens")
                                 .and_then(|v| v.as_u64())
                                 .unwrap_or(0),
+                            cost_usd: msg.get("total_cost_usd").and_then(|v| v.as_f64()),
                         });
                         log::trace!(
                             "Token usage: input={}, output={}, cache_read={}, cache_create={}",
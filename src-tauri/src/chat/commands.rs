@@ -3,24 +3,25 @@ use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use uuid::Uuid;
 
+use super::export::{self, ExportFormat, ExportOptions};
 use super::naming::{spawn_naming_task, NamingRequest};
 use super::registry::cancel_process;
 use super::run_log;
 use super::storage::{
-    delete_session_data, get_data_dir, get_index_path, get_session_dir, load_metadata,
-    load_sessions, with_sessions_mut,
+    delete_session_data, get_data_dir, get_session_dir, load_metadata, load_sessions, move_index,
+    with_sessions_mut,
 };
 use super::types::{
     AllSessionsEntry, AllSessionsResponse, ChatMessage, ClaudeContext, EffortLevel, MessageRole,
-    RunStatus, Session, SessionDigest, ThinkingLevel, WorktreeSessions,
+    RunStatus, Session, SessionDigest, SessionMessagesPage, ThinkingLevel, WorktreeSessions,
 };
 use crate::claude_cli::get_cli_binary_path;
 use crate::http_server::EmitExt;
 use crate::platform::silent_command;
-use crate::projects::storage::load_projects_data;
+use crate::projects::storage::{load_projects_data, with_projects_data_mut};
 use crate::projects::types::SessionType;
 
 /// Get current Unix timestamp in seconds
@@ -166,6 +167,50 @@ pub async fn get_session(
     Ok(session)
 }
 
+/// Default number of messages per page for `get_session_messages`
+const DEFAULT_MESSAGES_PAGE_SIZE: usize = 100;
+
+/// Get a page of a session's messages, for lazily loading long transcripts.
+///
+/// Returns up to `limit` messages ending just before the message with ID
+/// `before`, or the newest messages when `before` is None. Messages are in
+/// chronological order; `has_more` tells whether older messages remain.
+#[tauri::command]
+pub async fn get_session_messages(
+    app: AppHandle,
+    session_id: String,
+    before: Option<String>,
+    limit: Option<usize>,
+) -> Result<SessionMessagesPage, String> {
+    log::trace!("Getting messages for session: {session_id} (before: {before:?})");
+    let limit = limit.unwrap_or(DEFAULT_MESSAGES_PAGE_SIZE).max(1);
+    run_log::load_session_messages_page(&app, &session_id, before.as_deref(), limit)
+}
+
+/// Export a session's full message history to `path` as Markdown or JSON.
+/// Thinking is kept and tool output included unless the options say otherwise.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_session(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+    session_id: String,
+    path: String,
+    format: ExportFormat,
+    strip_thinking: Option<bool>,
+    include_tool_output: Option<bool>,
+) -> Result<(), String> {
+    log::trace!("Exporting session {session_id} to {path} as {format:?}");
+    let session = get_session(app, worktree_id, worktree_path, session_id).await?;
+    let options = ExportOptions {
+        strip_thinking: strip_thinking.unwrap_or(false),
+        include_tool_output: include_tool_output.unwrap_or(true),
+    };
+    let content = export::render(session, format, options)?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write export: {e}"))
+}
+
 /// Create a new session tab
 #[tauri::command]
 pub async fn create_session(
@@ -192,6 +237,21 @@ pub async fn create_session(
     })
 }
 
+/// Fork a session at a message into a new session tab in the same worktree
+///
+/// The fork keeps the history up to and including the message at
+/// `from_message_index` (forking at a user message keeps only what came
+/// before it) and resumes its own copy of the Claude CLI conversation.
+#[tauri::command]
+pub async fn fork_session(
+    app: AppHandle,
+    session_id: String,
+    from_message_index: usize,
+) -> Result<Session, String> {
+    log::trace!("Forking session {session_id} at message {from_message_index}");
+    super::fork::fork_session(&app, &session_id, from_message_index)
+}
+
 /// Rename a session tab
 #[tauri::command]
 pub async fn rename_session(
@@ -297,6 +357,30 @@ fn extract_text_file_paths(content: &str) -> Vec<String> {
         .collect()
 }
 
+/// Replace references to encrypted pasted text files with their content, since
+/// the Claude CLI can't read sealed files with its Read tool. The stored user
+/// message keeps the reference.
+fn inline_sealed_text_files(content: &str) -> String {
+    use regex::Regex;
+    let re = Regex::new(r"\[Text file attached: (.+?) - Use the Read tool to view this file\]")
+        .expect("Invalid regex");
+    re.replace_all(content, |cap: &regex::Captures| {
+        let path = std::path::Path::new(&cap[1]);
+        let sealed = std::fs::read(path).is_ok_and(|bytes| crate::encryption::is_sealed(&bytes));
+        if !sealed {
+            return cap[0].to_string();
+        }
+        match crate::encryption::read_to_string(path) {
+            Ok(text) => format!("<pasted-text>\n{text}\n</pasted-text>"),
+            Err(e) => {
+                log::warn!("Failed to decrypt pasted text {}: {e}", &cap[1]);
+                cap[0].to_string()
+            }
+        }
+    })
+    .into_owned()
+}
+
 /// Delete a pasted file (image or text) by path - internal helper
 /// Does not validate path (validation done at command level)
 fn delete_pasted_file(path: &str) {
@@ -408,7 +492,7 @@ pub async fn archive_session(
     let messages = run_log::load_session_messages(&app, &session_id).unwrap_or_default();
     let should_delete = messages.is_empty();
 
-    with_sessions_mut(&app, &worktree_path, &worktree_id, |sessions| {
+    let new_active = with_sessions_mut(&app, &worktree_path, &worktree_id, |sessions| {
         // Find the index before archiving/deleting
         let session_index = sessions.sessions.iter().position(|s| s.id == session_id);
 
@@ -504,7 +588,14 @@ pub async fn archive_session(
             );
         }
         Ok(sessions.active_session_id.clone())
-    })
+    })?;
+
+    // Compress the archived transcript in the background
+    if !should_delete {
+        run_log::spawn_compress_logs(app, Some(session_id), worktree_id);
+    }
+
+    Ok(new_active)
 }
 
 /// Unarchive a session (restore it to the session list)
@@ -559,7 +650,7 @@ pub async fn restore_session_with_base(
     log::trace!("Restoring session with base session check: {session_id}");
 
     // Load projects data to check if worktree exists
-    let mut projects_data = load_projects_data(&app)?;
+    let projects_data = load_projects_data(&app)?;
 
     // Check if the worktree exists
     if let Some(existing) = projects_data.find_worktree(&worktree_id) {
@@ -601,37 +692,44 @@ pub async fn restore_session_with_base(
     log::trace!("Recreating base session for project: {}", project.name);
 
     // Create new base session
-    let new_worktree = crate::projects::types::Worktree {
-        id: uuid::Uuid::new_v4().to_string(),
-        project_id: project_id.clone(),
-        name: project.default_branch.clone(),
-        path: project.path.clone(),
-        branch: project.default_branch.clone(),
-        created_at: now(),
-        setup_output: None,
-        setup_script: None,
-        session_type: SessionType::Base,
-        pr_number: None,
-        pr_url: None,
-        cached_pr_status: None,
-        cached_check_status: None,
-        cached_behind_count: None,
-        cached_ahead_count: None,
-        cached_status_at: None,
-        cached_uncommitted_added: None,
-        cached_uncommitted_removed: None,
-        cached_branch_diff_added: None,
-        cached_branch_diff_removed: None,
-        cached_base_branch_ahead_count: None,
-        cached_base_branch_behind_count: None,
-        cached_worktree_ahead_count: None,
-        cached_unpushed_count: None,
-        order: 0,
-        archived_at: None,
-    };
+    let new_worktree = with_projects_data_mut(&app, |data| {
+        let new_worktree = crate::projects::types::Worktree {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project_id.clone(),
+            name: project.default_branch.clone(),
+            path: project.path.clone(),
+            branch: project.default_branch.clone(),
+            created_at: now(),
+            setup_output: None,
+            setup_script: None,
+            session_type: SessionType::Base,
+            pr_number: None,
+            pr_url: None,
+            cached_pr_status: None,
+            cached_check_status: None,
+            cached_behind_count: None,
+            cached_ahead_count: None,
+            cached_status_at: None,
+            cached_uncommitted_added: None,
+            cached_uncommitted_removed: None,
+            cached_branch_diff_added: None,
+            cached_branch_diff_removed: None,
+            cached_base_branch_ahead_count: None,
+            cached_base_branch_behind_count: None,
+            cached_worktree_ahead_count: None,
+            cached_unpushed_count: None,
+            order: 0,
+            archived_at: None,
+            reviewed_commit: None,
+            checklist_override: None,
+            stack_parent: None,
+            archived_stash: None,
+            env_profile: None,
+        };
 
-    projects_data.add_worktree(new_worktree.clone());
-    crate::projects::storage::save_projects_data(&app, &projects_data)?;
+        data.add_worktree(new_worktree.clone());
+        Ok(new_worktree)
+    })?;
 
     // Atomically migrate sessions to new worktree
     let restored_session = with_sessions_mut(&app, &worktree_path, &worktree_id, |sessions| {
@@ -646,19 +744,11 @@ pub async fn restore_session_with_base(
         session.archived_at = None;
         let restored = session.clone();
 
-        // Update the sessions file's worktree_id to the new one
-        sessions.worktree_id = new_worktree.id.clone();
-
         Ok(restored)
     })?;
 
-    // Delete the old sessions file (outside lock - it's already been saved with new worktree_id)
-    let old_sessions_path = get_index_path(&app, &worktree_id)?;
-    if old_sessions_path.exists() {
-        if let Err(e) = std::fs::remove_file(&old_sessions_path) {
-            log::warn!("Failed to remove old sessions file: {e}");
-        }
-    }
+    // Re-key the sessions index to the new worktree
+    move_index(&app, &worktree_id, &new_worktree.id)?;
 
     log::trace!("Base session recreated and sessions migrated");
 
@@ -818,6 +908,28 @@ pub async fn set_active_session(
 // Chat Commands (now session-based)
 // ============================================================================
 
+/// Minimal cancelled message returned when a send is undone before any
+/// content arrived (not persisted, just for UI)
+fn undo_send_message(session_id: &str) -> ChatMessage {
+    ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        session_id: session_id.to_string(),
+        role: MessageRole::Assistant,
+        content: String::new(),
+        timestamp: now(),
+        tool_calls: vec![],
+        content_blocks: vec![],
+        cancelled: true,
+        plan_approved: false,
+        model: None,
+        execution_mode: None,
+        thinking_level: None,
+        effort_level: None,
+        recovered: false,
+        usage: None,
+    }
+}
+
 /// Send a message to Claude and get a response
 ///
 /// This command:
@@ -828,6 +940,9 @@ pub async fn set_active_session(
 /// 5. Adds the assistant response
 /// 6. Saves the updated session
 /// 7. Returns the assistant message
+///
+/// With the `max_concurrent_sessions` preference set, the message waits for a
+/// free Claude CLI process slot before running (see `scheduler`).
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub async fn send_chat_message(
@@ -856,6 +971,17 @@ pub async fn send_chat_message(
         return Err("Worktree path cannot be empty".to_string());
     }
 
+    crate::background_tasks::time_tracking::record_activity(&app, &worktree_id, &session_id);
+
+    // A chat-specific language takes precedence over the one sent by the UI
+    let ai_language = match crate::load_preferences(app.clone()).await {
+        Ok(prefs) => prefs
+            .ai_language_override(crate::AiArtifact::Chat)
+            .map(str::to_string)
+            .or(ai_language),
+        Err(_) => ai_language,
+    };
+
     // Load sessions
     let mut sessions = load_sessions(&app, &worktree_path, &worktree_id)?;
 
@@ -931,6 +1057,7 @@ pub async fn send_chat_message(
                     existing_branch_names: existing_names,
                     generate_session_name: generate_session,
                     generate_branch_name: generate_branch,
+                    language_instruction: prefs.ai_language_instruction(crate::AiArtifact::Naming),
                 };
 
                 // Spawn in background - does not block chat
@@ -992,6 +1119,29 @@ pub async fn send_chat_message(
         }
     };
 
+    // Wait for a Claude CLI process slot; the message is dropped if it's
+    // cancelled while queued
+    let max_concurrent = crate::load_preferences(app.clone())
+        .await
+        .map(|prefs| prefs.max_concurrent_sessions)
+        .unwrap_or(0);
+    let Some(_slot) =
+        super::scheduler::acquire(&app, &session_id, &worktree_id, max_concurrent as usize).await
+    else {
+        log::trace!("Queued message cancelled for session: {session_id}");
+        return Ok(undo_send_message(&session_id));
+    };
+
+    // Checkpoint the worktree before a run that may edit files
+    if let Some(mode @ ("build" | "yolo")) = execution_mode.as_deref() {
+        crate::projects::snapshots::auto_snapshot(
+            &app,
+            &worktree_path,
+            &format!("Before {mode} run"),
+        )
+        .await;
+    }
+
     // Generate user message ID early (needed for run log)
     let user_message_id = Uuid::new_v4().to_string();
 
@@ -1037,7 +1187,8 @@ pub async fn send_chat_message(
     let run_id = run_log_writer.run_id().to_string();
 
     // Write input file with the user message
-    run_log::write_input_file(&app, &session_id, &run_id, &message)?;
+    let cli_message = inline_sealed_text_files(&message);
+    run_log::write_input_file(&app, &session_id, &run_id, &cli_message)?;
 
     // Use passed parameter for thinking override (computed by frontend based on preference + manual override)
     let disable_thinking_in_non_plan_modes = disable_thinking_for_mode.unwrap_or(false);
@@ -1047,6 +1198,7 @@ pub async fn send_chat_message(
 
     // Execute Claude CLI in detached mode
     // If resume fails with "session not found", retry without the session ID
+    let run_started = std::time::Instant::now();
     let mut claude_session_id_for_call = claude_session_id.clone();
     let (pid, claude_response) = loop {
         log::trace!("About to call execute_claude_detached...");
@@ -1145,24 +1297,7 @@ pub async fn send_chat_message(
         })?;
 
         log::trace!("Chat cancelled with no meaningful content for session: {session_id}");
-        // Return a minimal cancelled message (not persisted, just for UI)
-        return Ok(ChatMessage {
-            id: Uuid::new_v4().to_string(),
-            session_id: session_id.clone(),
-            role: MessageRole::Assistant,
-            content: String::new(),
-            timestamp: now(),
-            tool_calls: vec![],
-            content_blocks: vec![],
-            cancelled: true,
-            plan_approved: false,
-            model: None,
-            execution_mode: None,
-            thinking_level: None,
-            effort_level: None,
-            recovered: false,
-            usage: None,
-        });
+        return Ok(undo_send_message(&session_id));
     }
 
     // Create assistant message with tool calls and content blocks
@@ -1216,10 +1351,21 @@ pub async fn send_chat_message(
         Ok(())
     })?;
 
+    // A response arriving counts as activity too, so long runs stay tracked
+    crate::background_tasks::time_tracking::record_activity(&app, &worktree_id, &session_id);
+
     if claude_response.cancelled {
         log::trace!("Chat message cancelled but partial response saved for session: {session_id}");
     } else {
         log::trace!("Chat message sent and response received for session: {session_id}");
+        crate::notifications::notify(
+            &app,
+            &worktree_id,
+            crate::notifications::NotificationEvent::SessionFinished {
+                session: session_name,
+                duration_secs: run_started.elapsed().as_secs(),
+            },
+        );
     }
     Ok(assistant_msg)
 }
@@ -1303,7 +1449,7 @@ pub async fn set_session_thinking_level(
     })
 }
 
-/// Cancel a running Claude chat request for a session
+/// Cancel a running (or queued) Claude chat request for a session
 /// Returns true if a process was found and cancelled, false if no process was running
 #[tauri::command]
 pub async fn cancel_chat_message(
@@ -1315,11 +1461,18 @@ pub async fn cancel_chat_message(
     cancel_process(&app, &session_id, &worktree_id)
 }
 
-/// Check if any sessions have running Claude processes
+/// Serve a session's queued message before other queued ones (called when
+/// the session gets focus). Returns whether it was queued.
+#[tauri::command]
+pub async fn prioritize_chat_session(app: AppHandle, session_id: String) -> Result<bool, String> {
+    Ok(super::scheduler::prioritize(&app, &session_id))
+}
+
+/// Check if any sessions have running Claude processes or messages queued for one
 /// Used for quit confirmation dialog to prevent accidental closure during active sessions
 #[tauri::command]
 pub fn has_running_sessions() -> bool {
-    !super::registry::get_running_sessions().is_empty()
+    !super::registry::get_running_sessions().is_empty() || super::scheduler::has_queued()
 }
 
 /// Save a cancelled message to chat history
@@ -1407,6 +1560,7 @@ pub async fn save_pasted_image(
     app: AppHandle,
     data: String,
     mime_type: String,
+    worktree_id: Option<String>,
 ) -> Result<SaveImageResponse, String> {
     log::trace!("Saving pasted image, mime_type: {mime_type}");
 
@@ -1463,6 +1617,15 @@ pub async fn save_pasted_image(
         .to_string();
 
     log::trace!("Image saved to: {path_str}");
+    paste_history::record(
+        &app,
+        worktree_id.as_deref(),
+        PasteKind::Image,
+        &filename,
+        &path_str,
+        image_data.len(),
+        None,
+    );
 
     Ok(SaveImageResponse {
         id: Uuid::new_v4().to_string(),
@@ -1479,6 +1642,7 @@ pub async fn save_pasted_image(
 pub async fn save_dropped_image(
     app: AppHandle,
     source_path: String,
+    worktree_id: Option<String>,
 ) -> Result<SaveImageResponse, String> {
     log::trace!("Saving dropped image from: {source_path}");
 
@@ -1543,6 +1707,15 @@ pub async fn save_dropped_image(
         .to_string();
 
     log::trace!("Dropped image saved to: {path_str}");
+    paste_history::record(
+        &app,
+        worktree_id.as_deref(),
+        PasteKind::Image,
+        &filename,
+        &path_str,
+        metadata.len() as usize,
+        None,
+    );
 
     Ok(SaveImageResponse {
         id: Uuid::new_v4().to_string(),
@@ -1569,10 +1742,7 @@ pub async fn delete_pasted_image(app: AppHandle, path: String) -> Result<(), Str
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/images/ or new app data pasted-images/
@@ -1596,6 +1766,8 @@ pub async fn delete_pasted_image(app: AppHandle, path: String) -> Result<(), Str
 // Text Paste Commands (for large text pastes in chat)
 // ============================================================================
 
+use super::documents;
+use super::paste_history::{self, PasteKind};
 use super::storage::get_pastes_dir;
 use super::types::{ReadTextResponse, SaveTextResponse};
 
@@ -1607,7 +1779,11 @@ const MAX_TEXT_SIZE: usize = 10 * 1024 * 1024;
 /// Large text pastes (500+ chars) are saved as files instead of being inlined.
 /// Returns the saved file path for referencing in messages.
 #[tauri::command]
-pub async fn save_pasted_text(app: AppHandle, content: String) -> Result<SaveTextResponse, String> {
+pub async fn save_pasted_text(
+    app: AppHandle,
+    content: String,
+    worktree_id: Option<String>,
+) -> Result<SaveTextResponse, String> {
     let size = content.len();
     log::trace!("Saving pasted text, size: {size} bytes");
 
@@ -1618,8 +1794,19 @@ pub async fn save_pasted_text(app: AppHandle, content: String) -> Result<SaveTex
         ));
     }
 
+    write_pasted_text(&app, &content, worktree_id.as_deref())
+}
+
+/// Save text to the pastes directory
+fn write_pasted_text(
+    app: &AppHandle,
+    content: &str,
+    worktree_id: Option<&str>,
+) -> Result<SaveTextResponse, String> {
+    let size = content.len();
+
     // Get the pastes directory (now in app data dir)
-    let pastes_dir = get_pastes_dir(&app)?;
+    let pastes_dir = get_pastes_dir(app)?;
 
     // Generate unique filename
     let timestamp = now();
@@ -1629,7 +1816,8 @@ pub async fn save_pasted_text(app: AppHandle, content: String) -> Result<SaveTex
 
     // Write file atomically (temp file + rename)
     let temp_path = file_path.with_extension("tmp");
-    std::fs::write(&temp_path, &content).map_err(|e| format!("Failed to write text file: {e}"))?;
+    crate::encryption::write(&temp_path, content)
+        .map_err(|e| format!("Failed to write text file: {e}"))?;
 
     std::fs::rename(&temp_path, &file_path)
         .map_err(|e| format!("Failed to finalize text file: {e}"))?;
@@ -1640,6 +1828,15 @@ pub async fn save_pasted_text(app: AppHandle, content: String) -> Result<SaveTex
         .to_string();
 
     log::trace!("Text file saved to: {path_str}");
+    paste_history::record(
+        app,
+        worktree_id,
+        PasteKind::Text,
+        &filename,
+        &path_str,
+        size,
+        Some(content),
+    );
 
     Ok(SaveTextResponse {
         id: Uuid::new_v4().to_string(),
@@ -1649,6 +1846,67 @@ pub async fn save_pasted_text(app: AppHandle, content: String) -> Result<SaveTex
     })
 }
 
+/// Save a pasted document (PDF, docx) as pasted text
+///
+/// The document data should be base64-encoded. Its text is extracted and
+/// saved like a large text paste, under a heading with the file name.
+#[tauri::command]
+pub async fn save_pasted_document(
+    app: AppHandle,
+    data: String,
+    filename: String,
+    worktree_id: Option<String>,
+) -> Result<SaveTextResponse, String> {
+    log::trace!("Saving pasted document: {filename}");
+
+    let bytes = STANDARD
+        .decode(&data)
+        .map_err(|e| format!("Failed to decode base64 document data: {e}"))?;
+    save_document_text(&app, &bytes, &filename, worktree_id.as_deref())
+}
+
+/// Save a dropped document file (PDF, docx) as pasted text
+#[tauri::command]
+pub async fn save_dropped_document(
+    app: AppHandle,
+    source_path: String,
+    worktree_id: Option<String>,
+) -> Result<SaveTextResponse, String> {
+    log::trace!("Saving dropped document from: {source_path}");
+
+    let source = std::path::PathBuf::from(&source_path);
+    let metadata =
+        std::fs::metadata(&source).map_err(|_| format!("Source file not found: {source_path}"))?;
+    if metadata.len() as usize > documents::MAX_DOCUMENT_SIZE {
+        return Err(format!(
+            "Document too large: {} bytes. Maximum size: {} bytes (25MB)",
+            metadata.len(),
+            documents::MAX_DOCUMENT_SIZE
+        ));
+    }
+    let bytes = std::fs::read(&source).map_err(|e| format!("Failed to read document: {e}"))?;
+    let filename = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(source_path);
+    save_document_text(&app, &bytes, &filename, worktree_id.as_deref())
+}
+
+fn save_document_text(
+    app: &AppHandle,
+    bytes: &[u8],
+    filename: &str,
+    worktree_id: Option<&str>,
+) -> Result<SaveTextResponse, String> {
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .ok_or_else(|| "File has no extension".to_string())?;
+    let text = documents::extract_text(bytes, extension)?;
+    log::trace!("Extracted {} bytes of text from {filename}", text.len());
+    write_pasted_text(app, &format!("# {filename}\n\n{text}"), worktree_id)
+}
+
 /// Delete a pasted text file
 ///
 /// Validates that the path is within allowed directories before deleting.
@@ -1667,10 +1925,7 @@ pub async fn delete_pasted_text(app: AppHandle, path: String) -> Result<(), Stri
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/pastes/ or new app data pasted-texts/
@@ -1707,10 +1962,7 @@ pub async fn read_pasted_text(app: AppHandle, path: String) -> Result<ReadTextRe
 
     // Validate that the path is within allowed directories
     let path_str = file_path.to_string_lossy();
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
     let app_data_str = app_data_dir.to_string_lossy();
 
     // Check if path is in old .jean/pastes/ or new app data pasted-texts/
@@ -1735,9 +1987,10 @@ pub async fn read_pasted_text(app: AppHandle, path: String) -> Result<ReadTextRe
         ));
     }
 
-    // Read file content
-    let content = std::fs::read_to_string(&file_path)
+    // Read file content (decrypting it if sealed)
+    let content = crate::encryption::read_to_string(&file_path)
         .map_err(|e| format!("Failed to read text file: {e}"))?;
+    let size = content.len();
 
     log::trace!("Successfully read pasted text file: {path} ({size} bytes)");
     Ok(ReadTextResponse { content, size })
@@ -1793,7 +2046,39 @@ pub async fn read_file_content(path: String) -> Result<String, String> {
     }
 
     // Read the file content
-    std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {e}"))
+    let bytes = std::fs::read(&file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+    if super::file_preview::looks_binary(&bytes) {
+        return Err(format!(
+            "Binary file: {path} (use read_file_preview for metadata)"
+        ));
+    }
+    String::from_utf8(bytes).map_err(|e| format!("Failed to read file: {e}"))
+}
+
+/// Read file metadata and a byte range of its content for the inline viewer
+///
+/// Unlike `read_file_content`, this never fails on large or binary files:
+/// it reports size, MIME type, image dimensions and whether the file is
+/// binary, and returns up to `length` bytes of text (default 1MB) starting
+/// at `offset`, so the viewer can load further ranges on demand.
+#[tauri::command]
+pub async fn read_file_preview(
+    path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
+) -> Result<super::types::FilePreview, String> {
+    log::trace!("Reading file preview: {path} (offset: {offset:?}, length: {length:?})");
+
+    let file_path = std::path::PathBuf::from(&path);
+    if !file_path.exists() {
+        return Err(format!("File not found: {path}"));
+    }
+
+    super::file_preview::read_preview(
+        &file_path,
+        offset.unwrap_or(0),
+        length.unwrap_or(super::file_preview::DEFAULT_PREVIEW_BYTES),
+    )
 }
 
 /// Write file content to disk
@@ -1801,7 +2086,11 @@ pub async fn read_file_content(path: String) -> Result<String, String> {
 /// Used to save file content when editing in the inline editor.
 /// Has a 10MB size limit to prevent memory issues with large files.
 #[tauri::command]
-pub async fn write_file_content(path: String, content: String) -> Result<(), String> {
+pub async fn write_file_content(
+    app: AppHandle,
+    path: String,
+    content: String,
+) -> Result<(), String> {
     log::trace!("Writing file content: {path}");
 
     let file_path = std::path::PathBuf::from(&path);
@@ -1817,17 +2106,26 @@ pub async fn write_file_content(path: String, content: String) -> Result<(), Str
     }
 
     // Write the file content
-    std::fs::write(&file_path, &content).map_err(|e| format!("Failed to write file: {e}"))
+    std::fs::write(&file_path, &content).map_err(|e| format!("Failed to write file: {e}"))?;
+    super::change_audit::record_editor_write(&app, &path, content.len());
+    Ok(())
 }
 
 /// Open a file in the user's preferred editor
 ///
-/// Uses the editor preference (vscode, cursor, xcode) to open files.
+/// Uses the editor preference (vscode, cursor, xcode, or a JetBrains IDE) to
+/// open files.
 #[tauri::command]
 pub async fn open_file_in_default_app(path: String, editor: Option<String>) -> Result<(), String> {
     let editor_app = editor.unwrap_or_else(|| "vscode".to_string());
     log::trace!("Opening file in {editor_app}: {path}");
 
+    let jetbrains_args = crate::platform::jetbrains::launch_args(&path, None);
+    if let Some(result) = crate::platform::jetbrains::spawn(&editor_app, &jetbrains_args) {
+        result.map_err(|e| format!("Failed to open {editor_app}: {e}"))?;
+        return Ok(());
+    }
+
     #[cfg(target_os = "macos")]
     {
         let result = match editor_app.as_str() {
@@ -1841,9 +2139,10 @@ pub async fn open_file_in_default_app(path: String, editor: Option<String>) -> R
 
     #[cfg(target_os = "windows")]
     {
+        // .cmd shims, run through cmd.exe
         let result = match editor_app.as_str() {
-            "cursor" => std::process::Command::new("cursor").arg(&path).spawn(),
-            _ => std::process::Command::new("code").arg(&path).spawn(),
+            "cursor" => crate::platform::cli_command("cursor", &[&path]).spawn(),
+            _ => crate::platform::cli_command("code", &[&path]).spawn(),
         };
 
         result.map_err(|e| format!("Failed to open {editor_app}: {e}"))?;
@@ -1852,8 +2151,8 @@ pub async fn open_file_in_default_app(path: String, editor: Option<String>) -> R
     #[cfg(target_os = "linux")]
     {
         let result = match editor_app.as_str() {
-            "cursor" => std::process::Command::new("cursor").arg(&path).spawn(),
-            _ => std::process::Command::new("code").arg(&path).spawn(),
+            "cursor" => crate::platform::cli_command("cursor", &[&path]).spawn(),
+            _ => crate::platform::cli_command("code", &[&path]).spawn(),
         };
 
         result.map_err(|e| format!("Failed to open {editor_app}: {e}"))?;
@@ -2021,7 +2320,7 @@ pub async fn save_context_file(
 
     // Write content atomically (temp file + rename)
     let temp_path = file_path.with_extension("tmp");
-    std::fs::write(&temp_path, &content)
+    crate::encryption::write(&temp_path, &content)
         .map_err(|e| format!("Failed to write context file: {e}"))?;
 
     std::fs::rename(&temp_path, &file_path)
@@ -2067,7 +2366,8 @@ pub async fn read_context_file(app: AppHandle, path: String) -> Result<String, S
         return Err("Invalid context file path".to_string());
     }
 
-    std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read context file: {e}"))
+    crate::encryption::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read context file: {e}"))
 }
 
 /// Delete a saved context file
@@ -2479,10 +2779,17 @@ pub async fn generate_context_from_session(
         .map(|s| s.as_str())
         .unwrap_or(CONTEXT_SUMMARY_PROMPT);
 
-    let prompt = prompt_template
+    let mut prompt = prompt_template
         .replace("{project_name}", &project_name)
         .replace("{date}", &today)
         .replace("{conversation}", &conversation_history);
+    let language = crate::load_preferences(app.clone())
+        .await
+        .ok()
+        .and_then(|p| p.ai_language_instruction(crate::AiArtifact::ContextSummary));
+    if let Some(instruction) = language {
+        prompt.push_str(&format!("\n\n{instruction}"));
+    }
 
     // 4. Call Claude CLI with JSON schema (non-streaming)
     // If JSON parsing fails, use fallback slug from project + session name
@@ -2513,7 +2820,7 @@ pub async fn generate_context_from_session(
 
     // Write content atomically
     let temp_path = file_path.with_extension("tmp");
-    std::fs::write(&temp_path, &summary)
+    crate::encryption::write(&temp_path, &summary)
         .map_err(|e| format!("Failed to write context file: {e}"))?;
 
     std::fs::rename(&temp_path, &file_path)
@@ -2553,18 +2860,16 @@ pub async fn get_session_debug_info(
     session_id: String,
 ) -> Result<SessionDebugInfo, String> {
     // Get app data directory
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     let app_data_str = app_data_dir.to_str().unwrap_or("unknown").to_string();
 
-    // Get index file path (was sessions file)
-    let sessions_file = get_index_path(&app, &worktree_id)?
+    // Session index and metadata live in the database
+    let db_path = crate::db::get_db_path(&app)?
         .to_str()
         .unwrap_or("unknown")
         .to_string();
+    let sessions_file = db_path.clone();
 
     // Get data directory (was runs directory)
     let runs_dir = get_data_dir(&app)?
@@ -2578,45 +2883,27 @@ pub async fn get_session_debug_info(
     let claude_session_id = session.and_then(|s| s.claude_session_id.clone());
 
     // Try to find Claude CLI's JSONL file
-    let claude_jsonl_file = claude_session_id.as_ref().and_then(|sid| {
-        // Claude CLI stores sessions in ~/.claude/projects/<project-hash>/<session-id>.jsonl
-        let home = dirs::home_dir()?;
-        let claude_projects = home.join(".claude").join("projects");
-
-        // We need to search for the session file in the projects directory
-        // The project hash is based on the worktree path
-        if claude_projects.exists() {
-            for entry in std::fs::read_dir(&claude_projects).ok()? {
-                let entry = entry.ok()?;
-                let project_dir = entry.path();
-                if project_dir.is_dir() {
-                    let session_file = project_dir.join(format!("{sid}.jsonl"));
-                    if session_file.exists() {
-                        return session_file.to_str().map(|s| s.to_string());
-                    }
-                }
-            }
-        }
-        None
-    });
+    let claude_jsonl_file = claude_session_id
+        .as_deref()
+        .and_then(super::fork::claude_transcript_path)
+        .and_then(|path| path.to_str().map(|s| s.to_string()));
 
-    // Get session directory and metadata file path (was manifest)
+    // Session directory holds the JSONL run logs
     let session_dir = get_session_dir(&app, &session_id)?;
-    let metadata_path = session_dir.join("metadata.json");
-    let manifest_file = if metadata_path.exists() {
-        metadata_path.to_str().map(|s| s.to_string())
-    } else {
-        None
-    };
 
     // Load metadata to get run info
     let metadata = load_metadata(&app, &session_id)?;
+    let manifest_file = metadata.as_ref().map(|_| db_path.clone());
 
     // Build JSONL file info list
     let mut run_log_files = Vec::new();
     if let Some(metadata) = metadata {
         for run in &metadata.runs {
-            let jsonl_path = session_dir.join(format!("{}.jsonl", run.run_id));
+            let mut jsonl_path = session_dir.join(format!("{}.jsonl", run.run_id));
+            if !jsonl_path.exists() {
+                // Archived sessions keep compressed logs
+                jsonl_path = run_log::compressed_path(&jsonl_path);
+            }
             if jsonl_path.exists() {
                 // Truncate user message preview to 50 chars
                 let preview = if run.user_message.len() > 50 {
@@ -2644,6 +2931,9 @@ pub async fn get_session_debug_info(
             acc.output_tokens += u.output_tokens;
             acc.cache_read_input_tokens += u.cache_read_input_tokens;
             acc.cache_creation_input_tokens += u.cache_creation_input_tokens;
+            if let Some(cost) = u.cost_usd {
+                acc.cost_usd = Some(acc.cost_usd.unwrap_or(0.0) + cost);
+            }
             acc
         },
     );
@@ -2761,6 +3051,11 @@ pub async fn resume_session(
                 &output_file,
                 pid,
             );
+            super::claude::remove_combined_context(
+                &app_clone,
+                &worktree_id_clone,
+                &session_id_clone,
+            );
 
             match result {
                 Ok(response) => {
@@ -2992,7 +3287,10 @@ pub async fn generate_session_digest(
     let conversation_history = format_messages_for_summary(&messages);
 
     // Build digest prompt
-    let prompt = SESSION_DIGEST_PROMPT.replace("{conversation}", &conversation_history);
+    let mut prompt = SESSION_DIGEST_PROMPT.replace("{conversation}", &conversation_history);
+    if let Some(instruction) = prefs.ai_language_instruction(crate::AiArtifact::SessionDigest) {
+        prompt.push_str(&format!("\n\n{instruction}"));
+    }
 
     // Call Claude CLI with JSON schema (non-streaming)
     execute_digest_claude(&app, &prompt, &prefs.session_recap_model)
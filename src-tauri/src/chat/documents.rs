@@ -0,0 +1,124 @@
+//! Text extraction from attached documents
+//!
+//! PDFs and Word documents pasted or dropped into the chat are attached as
+//! pasted text: their text is extracted here and saved like a large paste.
+//! Both the document and the extracted text are size-bounded.
+
+use std::io::{Cursor, Read};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Extensions of documents that can be attached
+pub const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "docx"];
+
+/// Maximum document size in bytes (25MB)
+pub const MAX_DOCUMENT_SIZE: usize = 25 * 1024 * 1024;
+
+/// Maximum extracted text kept, in bytes
+const MAX_EXTRACTED_LEN: usize = 200 * 1024;
+
+/// Maximum uncompressed size of a docx body read, in bytes
+const MAX_DOCX_XML_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Runs of text, tabs, line breaks and paragraph ends in `word/document.xml`
+static DOCX_TOKEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<w:t(?:\s[^>]*)?>([^<]*)</w:t>|<w:tab/>|<w:br/>|</w:p>").expect("valid docx regex")
+});
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of a Word document's body, one line per paragraph
+fn docx_text(xml: &str) -> String {
+    let mut text = String::new();
+    for token in DOCX_TOKEN.captures_iter(xml) {
+        match token.get(1) {
+            Some(run) => text.push_str(&unescape_xml(run.as_str())),
+            None => match &token[0] {
+                "<w:tab/>" => text.push('\t'),
+                _ => text.push('\n'),
+            },
+        }
+    }
+    text
+}
+
+fn extract_docx(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Failed to open docx: {e}"))?;
+    let body = archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Failed to read docx body: {e}"))?;
+    let mut xml = String::new();
+    body.take(MAX_DOCX_XML_SIZE)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read docx body: {e}"))?;
+    Ok(docx_text(&xml))
+}
+
+/// Cut text to the maximum length, on a char boundary, noting what was left out
+fn bound(text: &str) -> String {
+    let text = text.trim();
+    if text.len() <= MAX_EXTRACTED_LEN {
+        return text.to_string();
+    }
+    let mut end = MAX_EXTRACTED_LEN;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n\n[Truncated: {} more bytes of text not included]",
+        &text[..end],
+        text.len() - end
+    )
+}
+
+/// Extract the text of a document, by file extension
+pub fn extract_text(bytes: &[u8], extension: &str) -> Result<String, String> {
+    if bytes.len() > MAX_DOCUMENT_SIZE {
+        return Err(format!(
+            "Document too large: {} bytes. Maximum size: {MAX_DOCUMENT_SIZE} bytes (25MB)",
+            bytes.len()
+        ));
+    }
+    let text = match extension.to_lowercase().as_str() {
+        "pdf" => pdf_extract::extract_text_from_mem(bytes)
+            .map_err(|e| format!("Failed to extract PDF text: {e}"))?,
+        "docx" => extract_docx(bytes)?,
+        other => {
+            return Err(format!(
+                "Unsupported document type: .{other}. Supported types: {}",
+                DOCUMENT_EXTENSIONS.join(", ")
+            ))
+        }
+    };
+    if text.trim().is_empty() {
+        return Err("No text found in document (scanned PDFs are not supported)".to_string());
+    }
+    Ok(bound(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docx_text_and_bound() {
+        let xml = r#"<w:body><w:p><w:r><w:t>Design</w:t></w:r><w:r><w:t xml:space="preserve"> &amp; spec</w:t></w:r></w:p><w:p><w:r><w:t>a</w:t><w:tab/><w:t>b</w:t></w:r></w:p></w:body>"#;
+        assert_eq!(docx_text(xml), "Design & spec\na\tb\n");
+
+        let long = "é".repeat(MAX_EXTRACTED_LEN);
+        let bounded = bound(&long);
+        assert!(bounded.starts_with(&"é".repeat(MAX_EXTRACTED_LEN / 2)));
+        assert!(bounded.ends_with(&format!(
+            "[Truncated: {} more bytes of text not included]",
+            MAX_EXTRACTED_LEN
+        )));
+    }
+}
@@ -0,0 +1,202 @@
+//! Session export to Markdown and JSON
+//!
+//! Renders a session's full message history (text, thinking, tool calls and
+//! plans) for archiving outside the app. Attached images stay as references
+//! to their saved files; Markdown renders them as image links.
+
+use chrono::DateTime;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::types::{ChatMessage, ContentBlock, MessageRole, Session, ToolCall};
+
+/// The reference the frontend adds to a message for each attached image
+static IMAGE_REF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\[Image attached: (.+?) - Use the Read tool to view this image\]").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExportOptions {
+    pub strip_thinking: bool,
+    pub include_tool_output: bool,
+}
+
+/// Drop what the options leave out of the export
+fn filter(session: &mut Session, options: ExportOptions) {
+    for message in &mut session.messages {
+        if options.strip_thinking {
+            message
+                .content_blocks
+                .retain(|b| !matches!(b, ContentBlock::Thinking { .. }));
+        }
+        if !options.include_tool_output {
+            for call in &mut message.tool_calls {
+                call.output = None;
+            }
+        }
+    }
+}
+
+/// A fenced code block whose fence can't be closed by the content
+fn fence(content: &str, lang: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat(longest.max(2) + 1);
+    format!("{ticks}{lang}\n{}\n{ticks}\n\n", content.trim_end())
+}
+
+fn format_time(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn render_tool_call(out: &mut String, call: &ToolCall, plan_approved: bool) {
+    if call.name == "ExitPlanMode" {
+        let plan = call
+            .input
+            .get("plan")
+            .and_then(|p| p.as_str())
+            .unwrap_or("");
+        let status = if plan_approved { " (approved)" } else { "" };
+        out.push_str(&format!("**Plan{status}**\n\n{}\n\n", plan.trim()));
+        return;
+    }
+    out.push_str(&format!("**Tool: {}**\n\n", call.name));
+    let input = serde_json::to_string_pretty(&call.input).unwrap_or_default();
+    out.push_str(&fence(&input, "json"));
+    if let Some(output) = &call.output {
+        out.push_str("Output:\n\n");
+        out.push_str(&fence(output, ""));
+    }
+}
+
+fn render_message(out: &mut String, message: &ChatMessage) {
+    let role = match message.role {
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+    };
+    out.push_str(&format!(
+        "## {role} · {}\n\n",
+        format_time(message.timestamp)
+    ));
+    let text = |text: &str| format!("{}\n\n", IMAGE_REF.replace_all(text.trim(), "![image]($1)"));
+    let tool_call = |id: &str| message.tool_calls.iter().find(|c| c.id == id);
+
+    if message.content_blocks.is_empty() {
+        if !message.content.trim().is_empty() {
+            out.push_str(&text(&message.content));
+        }
+        for call in &message.tool_calls {
+            render_tool_call(out, call, message.plan_approved);
+        }
+    } else {
+        for block in &message.content_blocks {
+            match block {
+                ContentBlock::Text { text: t } => out.push_str(&text(t)),
+                ContentBlock::Thinking { thinking } => out.push_str(&format!(
+                    "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n\n",
+                    thinking.trim()
+                )),
+                ContentBlock::ToolUse { tool_call_id } => {
+                    if let Some(call) = tool_call(tool_call_id) {
+                        render_tool_call(out, call, message.plan_approved);
+                    }
+                }
+            }
+        }
+    }
+    if message.cancelled {
+        out.push_str("*Cancelled*\n\n");
+    }
+}
+
+fn to_markdown(session: &Session) -> String {
+    let mut out = format!(
+        "# {}\n\nCreated {}, {} messages\n\n",
+        session.name,
+        format_time(session.created_at),
+        session.messages.len()
+    );
+    for message in &session.messages {
+        render_message(&mut out, message);
+    }
+    out
+}
+
+/// Render a session with its messages loaded
+pub fn render(
+    mut session: Session,
+    format: ExportFormat,
+    options: ExportOptions,
+) -> Result<String, String> {
+    filter(&mut session, options);
+    match format {
+        ExportFormat::Markdown => Ok(to_markdown(&session)),
+        ExportFormat::Json => serde_json::to_string_pretty(&session)
+            .map_err(|e| format!("Failed to serialize session: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let mut session = Session::new("Fix login".to_string(), 0);
+        session.messages = vec![
+            ChatMessage {
+                content: "Why? [Image attached: /tmp/a.png - Use the Read tool to view this image]"
+                    .to_string(),
+                ..Default::default()
+            },
+            ChatMessage {
+                role: MessageRole::Assistant,
+                tool_calls: vec![ToolCall {
+                    id: "t1".to_string(),
+                    name: "Bash".to_string(),
+                    input: serde_json::json!({ "command": "ls" }),
+                    output: Some("```\nsrc".to_string()),
+                    parent_tool_use_id: None,
+                }],
+                content_blocks: vec![
+                    ContentBlock::Thinking {
+                        thinking: "Hmm".to_string(),
+                    },
+                    ContentBlock::ToolUse {
+                        tool_call_id: "t1".to_string(),
+                    },
+                    ContentBlock::Text {
+                        text: "Done".to_string(),
+                    },
+                ],
+                ..Default::default()
+            },
+        ];
+        let options = ExportOptions {
+            strip_thinking: false,
+            include_tool_output: true,
+        };
+        let markdown = render(session.clone(), ExportFormat::Markdown, options).unwrap();
+        assert!(markdown.contains("Why? ![image](/tmp/a.png)"));
+        assert!(markdown.contains("<summary>Thinking</summary>"));
+        assert!(markdown.contains("````\n```\nsrc\n````"));
+
+        let options = ExportOptions {
+            strip_thinking: true,
+            include_tool_output: false,
+        };
+        let markdown = render(session, ExportFormat::Markdown, options).unwrap();
+        assert!(!markdown.contains("Hmm"));
+        assert!(!markdown.contains("Output:"));
+        assert!(markdown.contains("**Tool: Bash**"));
+    }
+}
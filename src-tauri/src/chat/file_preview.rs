@@ -0,0 +1,163 @@
+//! File inspection for the inline file viewer
+//!
+//! Reads a bounded byte range of a file along with metadata (size, MIME type,
+//! image dimensions), so large and binary files can be previewed instead of
+//! failing to load.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::types::{FilePreview, ImageDimensions};
+
+/// Bytes read when no length is given
+pub const DEFAULT_PREVIEW_BYTES: u64 = 1024 * 1024;
+
+/// Largest range that can be read at once
+pub const MAX_PREVIEW_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Bytes inspected for binary detection
+const SNIFF_BYTES: usize = 8192;
+
+/// Whether `bytes` look like binary data: a NUL byte, or invalid UTF-8 that
+/// isn't just a character cut off at the end of the buffer
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    if sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// Decode `bytes` as UTF-8, dropping a character cut off at either end of the
+/// range. Returns the text and the number of bytes skipped at the start.
+fn decode_range(bytes: &[u8], at_start_of_file: bool) -> (String, usize) {
+    // A range starting mid-file may begin inside a multi-byte character
+    let skip = if at_start_of_file {
+        0
+    } else {
+        bytes
+            .iter()
+            .take(3)
+            .take_while(|b| (**b & 0b1100_0000) == 0b1000_0000)
+            .count()
+    };
+    let bytes = &bytes[skip..];
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) => e.valid_up_to(),
+    };
+    (String::from_utf8_lossy(&bytes[..valid]).to_string(), skip)
+}
+
+/// Inspect `path` and read up to `length` bytes starting at `offset`.
+///
+/// Text content is only returned for non-binary files.
+pub fn read_preview(path: &Path, offset: u64, length: u64) -> Result<FilePreview, String> {
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("Failed to read file metadata: {e}"))?;
+    if metadata.is_dir() {
+        return Err(format!("Not a file: {}", path.display()));
+    }
+    let size = metadata.len();
+    let length = length.min(MAX_PREVIEW_BYTES);
+
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
+
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    (&mut file)
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("Failed to read file: {e}"))?;
+    let is_binary = looks_binary(&head);
+
+    let mime_type = mime_guess::from_path(path)
+        .first()
+        .map(|m| m.essence_str().to_string())
+        .unwrap_or_else(|| {
+            if is_binary {
+                "application/octet-stream".to_string()
+            } else {
+                "text/plain".to_string()
+            }
+        });
+    let image_dimensions = mime_type
+        .starts_with("image/")
+        .then(|| imagesize::blob_size(&head).ok())
+        .flatten()
+        .map(|size| ImageDimensions {
+            width: size.width as u32,
+            height: size.height as u32,
+        });
+
+    let modified_at = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    let (content, offset, bytes_read) = if is_binary || offset >= size {
+        (None, offset.min(size), 0)
+    } else {
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("Failed to read file: {e}"))?;
+        let mut bytes = Vec::new();
+        file.take(length)
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read file: {e}"))?;
+        let (text, skipped) = decode_range(&bytes, offset == 0);
+        let start = offset + skipped as u64;
+        let read = text.len() as u64;
+        (Some(text), start, read)
+    };
+
+    Ok(FilePreview {
+        size,
+        mime_type,
+        is_binary,
+        image_dimensions,
+        modified_at,
+        content,
+        offset,
+        bytes_read,
+        truncated: offset + bytes_read < size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary("héllo".as_bytes()));
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0"));
+        assert!(looks_binary(b"abc\xffdef"));
+        // A multi-byte character cut off by the sniff window is still text
+        assert!(!looks_binary(&"é".as_bytes()[..1]));
+    }
+
+    #[test]
+    fn test_read_preview_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "aé bc").unwrap();
+
+        let full = read_preview(&path, 0, 100).unwrap();
+        assert_eq!(full.content.as_deref(), Some("aé bc"));
+        assert_eq!(full.mime_type, "text/markdown");
+        assert!(!full.truncated);
+
+        // Cut inside "é": the partial character is dropped
+        let head = read_preview(&path, 0, 2).unwrap();
+        assert_eq!(head.content.as_deref(), Some("a"));
+        assert!(head.truncated);
+
+        // Starting inside "é": skips to the next character
+        let tail = read_preview(&path, 2, 100).unwrap();
+        assert_eq!(tail.content.as_deref(), Some(" bc"));
+        assert_eq!(tail.offset, 3);
+    }
+}
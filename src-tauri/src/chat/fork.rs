@@ -0,0 +1,199 @@
+//! Forking sessions
+//!
+//! A fork is a sibling session in the same worktree holding a copy of the
+//! source session's history up to a message. Its run logs are copied, and the
+//! Claude CLI transcript is copied under a new Claude session ID, cut before
+//! the first prompt past the fork point, so resuming the fork continues from
+//! there while the source conversation is left untouched.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use super::run_log::{compressed_path, get_run_log_path, runs_through_message};
+use super::storage::{load_metadata, save_metadata, with_index_mut};
+use super::types::{RunEntry, RunStatus, Session, SessionMetadata};
+
+/// Find the Claude CLI transcript of a Claude session
+/// (`~/.claude/projects/<project-hash>/<session-id>.jsonl`)
+pub fn claude_transcript_path(claude_session_id: &str) -> Option<PathBuf> {
+    let claude_projects = dirs::home_dir()?.join(".claude").join("projects");
+    fs::read_dir(&claude_projects)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(format!("{claude_session_id}.jsonl")))
+        .find(|path| path.exists())
+}
+
+/// Whether a transcript line is a prompt typed by the user (not a tool
+/// result, a subagent message or a CLI-injected message)
+fn is_prompt(line: &serde_json::Value) -> bool {
+    if line["type"] != "user"
+        || line["isSidechain"].as_bool() == Some(true)
+        || line["isMeta"].as_bool() == Some(true)
+    {
+        return false;
+    }
+    match &line["message"]["content"] {
+        serde_json::Value::String(_) => true,
+        serde_json::Value::Array(blocks) => !blocks.iter().any(|b| b["type"] == "tool_result"),
+        _ => false,
+    }
+}
+
+/// The transcript lines of the first `prompts` prompts and their responses,
+/// moved to the Claude session `session_id`
+pub fn truncate_transcript(lines: &[String], prompts: usize, session_id: &str) -> Vec<String> {
+    let mut seen = 0;
+    let mut kept = Vec::new();
+    for line in lines {
+        let Ok(mut value) = serde_json::from_str::<serde_json::Value>(line) else {
+            kept.push(line.clone());
+            continue;
+        };
+        if is_prompt(&value) {
+            if seen == prompts {
+                break;
+            }
+            seen += 1;
+        }
+        if value.get("sessionId").is_some() {
+            value["sessionId"] = serde_json::Value::String(session_id.to_string());
+        }
+        kept.push(value.to_string());
+    }
+    kept
+}
+
+/// Copy a Claude transcript up to `prompts` prompts into a new Claude
+/// session next to it, returning the new session's ID
+fn fork_transcript(claude_session_id: &str, prompts: usize) -> Result<String, String> {
+    let source = claude_transcript_path(claude_session_id)
+        .ok_or_else(|| format!("Claude CLI transcript not found: {claude_session_id}"))?;
+    let contents = fs::read_to_string(&source)
+        .map_err(|e| format!("Failed to read Claude CLI transcript: {e}"))?;
+    let lines: Vec<String> = contents.lines().map(str::to_string).collect();
+
+    let fork_id = uuid::Uuid::new_v4().to_string();
+    let mut forked = truncate_transcript(&lines, prompts, &fork_id).join("\n");
+    forked.push('\n');
+    let target = source.with_file_name(format!("{fork_id}.jsonl"));
+    fs::write(&target, forked).map_err(|e| format!("Failed to write forked transcript: {e}"))?;
+    Ok(fork_id)
+}
+
+/// Copy a run's log (or its compressed copy) between sessions
+fn copy_run_log(app: &AppHandle, from: &str, to: &str, run_id: &str) -> Result<(), String> {
+    let source = get_run_log_path(app, from, run_id)?;
+    let target = get_run_log_path(app, to, run_id)?;
+    let copy = |source: &Path, target: &Path| {
+        fs::copy(source, target)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy run log {run_id}: {e}"))
+    };
+    if source.exists() {
+        copy(&source, &target)
+    } else if compressed_path(&source).exists() {
+        copy(&compressed_path(&source), &compressed_path(&target))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_undo_send(run: &RunEntry) -> bool {
+    run.status == RunStatus::Cancelled && run.assistant_message_id.is_none()
+}
+
+/// Fork a session at the message at `from_message_index`, registering the
+/// fork next to it in its worktree
+pub fn fork_session(
+    app: &AppHandle,
+    session_id: &str,
+    from_message_index: usize,
+) -> Result<Session, String> {
+    let source = load_metadata(app, session_id)?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let kept = runs_through_message(&source.runs, from_message_index)?;
+    let runs = source.runs[..kept].to_vec();
+    if runs.iter().any(|r| r.status == RunStatus::Running) {
+        return Err("Cannot fork a session at a message that is still running".to_string());
+    }
+
+    // Resume point: the Claude session of the last kept run
+    let source_claude_id = runs
+        .iter()
+        .rev()
+        .find_map(|r| r.claude_session_id.clone())
+        .or_else(|| {
+            source
+                .claude_session_id
+                .clone()
+                .filter(|_| kept == source.runs.len())
+        });
+    let prompts = runs.iter().filter(|r| !is_undo_send(r)).count();
+    let claude_session_id = match source_claude_id {
+        Some(id) if prompts > 0 => Some(fork_transcript(&id, prompts)?),
+        _ => None,
+    };
+
+    let fork_id = uuid::Uuid::new_v4().to_string();
+    for run in &runs {
+        copy_run_log(app, session_id, &fork_id, &run.run_id)?;
+    }
+
+    let mut fork = SessionMetadata::new(
+        fork_id.clone(),
+        source.worktree_id.clone(),
+        format!("{} (fork)", source.name),
+        0,
+    );
+    fork.claude_session_id = claude_session_id;
+    fork.selected_model = source.selected_model.clone();
+    fork.selected_thinking_level = source.selected_thinking_level.clone();
+    fork.session_naming_completed = true;
+    fork.approved_plan_message_ids = source.approved_plan_message_ids.clone();
+    fork.runs = runs;
+
+    with_index_mut(app, &source.worktree_id, |index| {
+        fork.order = index.sessions.len() as u32;
+        index.sessions.push(fork.to_index_entry());
+        index.active_session_id = Some(fork_id.clone());
+        Ok(())
+    })?;
+    save_metadata(app, &fork)?;
+
+    log::info!(
+        "Forked session {session_id} at message {from_message_index} into {fork_id} ({kept} run(s))"
+    );
+    Ok(fork.to_session())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_transcript() {
+        let lines: Vec<String> = [
+            r#"{"type":"summary","summary":"Login"}"#,
+            r#"{"type":"user","sessionId":"old","message":{"role":"user","content":"first"}}"#,
+            r#"{"type":"assistant","sessionId":"old","message":{"role":"assistant","content":[]}}"#,
+            r#"{"type":"user","sessionId":"old","message":{"role":"user","content":[{"type":"tool_result"}]}}"#,
+            r#"{"type":"user","sessionId":"old","isSidechain":true,"message":{"role":"user","content":"agent"}}"#,
+            r#"{"type":"user","sessionId":"old","message":{"role":"user","content":"second"}}"#,
+            r#"{"type":"assistant","sessionId":"old","message":{"role":"assistant","content":[]}}"#,
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let forked = truncate_transcript(&lines, 1, "new");
+        assert_eq!(forked.len(), 5);
+        assert!(forked[1..]
+            .iter()
+            .all(|l| l.contains(r#""sessionId":"new""#)));
+        assert_eq!(truncate_transcript(&lines, 2, "new").len(), 7);
+        assert_eq!(truncate_transcript(&lines, 0, "new").len(), 1);
+    }
+}
@@ -1,11 +1,19 @@
+pub mod change_audit;
 mod claude;
 mod commands;
 pub mod detached;
+mod documents;
+pub mod export;
+pub mod file_preview;
+mod fork;
 mod naming;
+pub mod paste_history;
 pub mod registry;
 pub mod run_log;
+mod scheduler;
 pub mod storage;
 pub mod tail;
+pub mod tool_policy;
 pub mod types;
 
 pub use commands::*;
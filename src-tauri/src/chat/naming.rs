@@ -5,8 +5,10 @@
 
 use crate::claude_cli::get_cli_binary_path;
 use crate::platform::silent_command;
+use crate::projects::branch_naming::BranchNameScheme;
 use crate::projects::git;
-use crate::projects::storage::{load_projects_data, save_projects_data};
+use crate::projects::storage::{load_projects_data, update_worktree};
+use crate::projects::tickets;
 
 use super::storage::with_sessions_mut;
 use crate::http_server::EmitExt;
@@ -14,7 +16,7 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::Stdio;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 /// Request for combined naming (session + branch)
 #[derive(Debug, Clone)]
@@ -27,6 +29,8 @@ pub struct NamingRequest {
     pub existing_branch_names: Vec<String>,
     pub generate_session_name: bool,
     pub generate_branch_name: bool,
+    /// Instruction to write the names in the user's language
+    pub language_instruction: Option<String>,
 }
 
 /// Successful session rename result (for event emission)
@@ -336,6 +340,10 @@ fn generate_names(app: &AppHandle, request: &NamingRequest) -> Result<NamingOutp
         (false, false, true) => format!("{FILE_MENTION_INSTRUCTION_PREFIX}{base_prompt}"),
         (false, false, false) => base_prompt,
     };
+    let prompt = match &request.language_instruction {
+        Some(instruction) => format!("{prompt}\n\n{instruction}"),
+        None => prompt,
+    };
 
     let model_alias = get_cli_model_alias(&request.model);
 
@@ -363,7 +371,7 @@ fn generate_names(app: &AppHandle, request: &NamingRequest) -> Result<NamingOutp
         // Add directories for Claude to read attachments
         // In dev mode: full directory access (useful for debugging)
         // In prod mode: only specific directories (security)
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
+        if let Ok(app_data_dir) = crate::locations::app_data_dir(app) {
             if cfg!(debug_assertions) {
                 cmd.arg("--add-dir").arg(&app_data_dir);
                 log::trace!("Added full app data directory to naming scope: {app_data_dir:?}");
@@ -509,6 +517,20 @@ fn validate_session_name(name: &str) -> Result<String, String> {
     Ok(final_name)
 }
 
+/// Conventional type prefixes Claude sometimes puts in branch names
+const BRANCH_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "refactor", "test", "chore", "style", "perf",
+];
+
+/// Type prefix (`feat` in `feat/add-login`) of a generated branch name
+fn branch_type(name: &str) -> Option<&'static str> {
+    let (prefix, _) = name.trim().split_once('/')?;
+    BRANCH_TYPES
+        .iter()
+        .copied()
+        .find(|kind| prefix.eq_ignore_ascii_case(kind))
+}
+
 /// Validate and sanitize a branch name
 fn validate_branch_name(name: &str) -> Result<String, String> {
     let name = name.trim().to_lowercase();
@@ -518,16 +540,7 @@ fn validate_branch_name(name: &str) -> Result<String, String> {
     }
 
     // Remove any type prefix if Claude accidentally included one
-    let name = name
-        .strip_prefix("feat/")
-        .or_else(|| name.strip_prefix("fix/"))
-        .or_else(|| name.strip_prefix("docs/"))
-        .or_else(|| name.strip_prefix("refactor/"))
-        .or_else(|| name.strip_prefix("test/"))
-        .or_else(|| name.strip_prefix("chore/"))
-        .or_else(|| name.strip_prefix("style/"))
-        .or_else(|| name.strip_prefix("perf/"))
-        .unwrap_or(&name);
+    let name = branch_type(&name).map_or(name.as_str(), |kind| &name[kind.len() + 1..]);
 
     // Sanitize: keep only alphanumeric and hyphens
     let sanitized: String = name
@@ -625,13 +638,11 @@ fn apply_branch_name(
         })?;
 
     // Update worktree metadata
-    if let Ok(mut data) = load_projects_data(app) {
-        if let Some(worktree) = data.find_worktree_mut(&request.worktree_id) {
-            worktree.name = final_branch_name.clone();
-            worktree.branch = final_branch_name.clone();
-            let _ = save_projects_data(app, &data);
-        }
-    }
+    let _ = update_worktree(app, &request.worktree_id, |worktree| {
+        worktree.name = final_branch_name.clone();
+        worktree.branch = final_branch_name.clone();
+        Ok(())
+    });
 
     Ok(BranchNameResult {
         worktree_id: request.worktree_id.clone(),
@@ -640,6 +651,33 @@ fn apply_branch_name(
     })
 }
 
+/// Shape a generated branch name with the worktree project's naming scheme
+/// (using the type Claude picked) and ticket pattern (ticket from the first
+/// message)
+fn apply_project_naming(
+    app: &AppHandle,
+    request: &NamingRequest,
+    kind: Option<&str>,
+    name: String,
+) -> String {
+    let Ok(data) = load_projects_data(app) else {
+        return name;
+    };
+    let Some(project) = data
+        .find_worktree(&request.worktree_id)
+        .and_then(|worktree| data.find_project(&worktree.project_id))
+    else {
+        return name;
+    };
+    let name = BranchNameScheme::for_project(project, kind.map(str::to_string))
+        .and_then(|scheme| scheme.apply(&name, None))
+        .unwrap_or(name);
+    match tickets::extract_for_project(project, &request.first_message) {
+        Some(ticket) => tickets::thread_into_branch(&name, &ticket),
+        None => name,
+    }
+}
+
 /// Execute the combined naming workflow
 fn execute_naming(app: &AppHandle, request: &NamingRequest) {
     // Skip if nothing to generate
@@ -704,7 +742,9 @@ fn execute_naming(app: &AppHandle, request: &NamingRequest) {
     // Apply branch name if requested and generated
     if request.generate_branch_name {
         if let Some(branch_name) = &naming_result.branch_name {
-            match validate_branch_name(branch_name) {
+            let validated = validate_branch_name(branch_name)
+                .map(|name| apply_project_naming(app, request, branch_type(branch_name), name));
+            match validated {
                 Ok(validated_name) => match apply_branch_name(app, request, &validated_name) {
                     Ok(result) => {
                         log::trace!(
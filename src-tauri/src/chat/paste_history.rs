@@ -0,0 +1,219 @@
+//! Paste history per worktree
+//!
+//! Pasted and dropped images, texts and documents are recorded against the
+//! worktree they were pasted in, so they can be listed and attached again
+//! instead of being one-shot files. The history keeps the most recent
+//! [`MAX_ENTRIES`] pastes per worktree; entries whose file is gone are dropped
+//! when the history is listed.
+
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::db::with_db;
+
+/// Pastes kept per worktree
+const MAX_ENTRIES: usize = 50;
+
+/// Characters of pasted text shown in the history
+const PREVIEW_LEN: usize = 120;
+
+/// What was pasted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteKind {
+    Image,
+    Text,
+}
+
+/// A paste in a worktree's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteEntry {
+    pub id: String,
+    pub worktree_id: String,
+    pub kind: PasteKind,
+    pub filename: String,
+    pub path: String,
+    pub size: usize,
+    /// Start of the text (texts only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    pub created_at: u64,
+    pub last_used_at: u64,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// First line(s) of a text, collapsed to one line
+fn preview(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= PREVIEW_LEN {
+        return collapsed;
+    }
+    let cut: String = collapsed.chars().take(PREVIEW_LEN).collect();
+    format!("{cut}…")
+}
+
+fn save_entry(app: &AppHandle, entry: &PasteEntry) -> Result<(), String> {
+    let data =
+        serde_json::to_string(entry).map_err(|e| format!("Failed to serialize paste: {e}"))?;
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO paste_history (id, worktree_id, last_used_at, data)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![entry.id, entry.worktree_id, entry.last_used_at as i64, data],
+        )
+        .map_err(|e| format!("Failed to save paste history: {e}"))?;
+        // Forget the oldest pastes beyond the limit (their files stay until cleanup)
+        conn.execute(
+            "DELETE FROM paste_history WHERE worktree_id = ?1 AND id NOT IN (
+                SELECT id FROM paste_history WHERE worktree_id = ?1
+                ORDER BY last_used_at DESC LIMIT ?2
+             )",
+            params![entry.worktree_id, MAX_ENTRIES as i64],
+        )
+        .map_err(|e| format!("Failed to trim paste history: {e}"))?;
+        Ok(())
+    })
+}
+
+fn load_entry(app: &AppHandle, id: &str) -> Result<Option<PasteEntry>, String> {
+    let data: Option<String> = with_db(app, |conn| {
+        conn.query_row(
+            "SELECT data FROM paste_history WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load paste: {e}"))
+    })?;
+    data.map(|d| serde_json::from_str(&d).map_err(|e| format!("Failed to parse paste: {e}")))
+        .transpose()
+}
+
+fn delete_entry(app: &AppHandle, id: &str) -> Result<(), String> {
+    with_db(app, |conn| {
+        conn.execute("DELETE FROM paste_history WHERE id = ?1", params![id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete paste from history: {e}"))
+    })
+}
+
+/// Record a saved paste in a worktree's history. Failures are logged, since
+/// the paste itself succeeded.
+pub fn record(
+    app: &AppHandle,
+    worktree_id: Option<&str>,
+    kind: PasteKind,
+    filename: &str,
+    path: &str,
+    size: usize,
+    text: Option<&str>,
+) {
+    let Some(worktree_id) = worktree_id.filter(|id| !id.is_empty()) else {
+        return;
+    };
+    let now = now();
+    let entry = PasteEntry {
+        id: Uuid::new_v4().to_string(),
+        worktree_id: worktree_id.to_string(),
+        kind,
+        filename: filename.to_string(),
+        path: path.to_string(),
+        size,
+        preview: text.map(preview),
+        created_at: now,
+        last_used_at: now,
+    };
+    if let Err(e) = save_entry(app, &entry) {
+        log::warn!("{e}");
+    }
+}
+
+/// Pastes of a worktree, most recently used first
+#[tauri::command]
+pub async fn list_paste_history(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Vec<PasteEntry>, String> {
+    log::trace!("Listing paste history for worktree {worktree_id}");
+    let rows: Vec<String> = with_db(&app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT data FROM paste_history WHERE worktree_id = ?1
+                 ORDER BY last_used_at DESC",
+            )
+            .map_err(|e| format!("Failed to load paste history: {e}"))?;
+        let rows = stmt
+            .query_map(params![worktree_id], |row| row.get(0))
+            .map_err(|e| format!("Failed to load paste history: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to load paste history: {e}"))
+    })?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for data in rows {
+        let entry: PasteEntry =
+            serde_json::from_str(&data).map_err(|e| format!("Failed to parse paste: {e}"))?;
+        if std::path::Path::new(&entry.path).exists() {
+            entries.push(entry);
+        } else {
+            delete_entry(&app, &entry.id)?;
+        }
+    }
+    Ok(entries)
+}
+
+/// Attach a paste from the history again, returning it with its file path
+#[tauri::command]
+pub async fn reattach_paste(app: AppHandle, id: String) -> Result<PasteEntry, String> {
+    log::trace!("Reattaching paste {id}");
+    let mut entry = load_entry(&app, &id)?.ok_or_else(|| format!("Paste not found: {id}"))?;
+    if !std::path::Path::new(&entry.path).exists() {
+        delete_entry(&app, &id)?;
+        return Err(format!(
+            "The file of paste {} no longer exists",
+            entry.filename
+        ));
+    }
+    entry.last_used_at = now();
+    save_entry(&app, &entry)?;
+    Ok(entry)
+}
+
+/// Remove a paste from the history and delete its file
+#[tauri::command]
+pub async fn delete_paste_history_entry(app: AppHandle, id: String) -> Result<(), String> {
+    log::trace!("Deleting paste {id} from history");
+    let Some(entry) = load_entry(&app, &id)? else {
+        return Ok(());
+    };
+    match entry.kind {
+        PasteKind::Image => super::delete_pasted_image(app.clone(), entry.path).await?,
+        PasteKind::Text => super::delete_pasted_text(app.clone(), entry.path).await?,
+    }
+    delete_entry(&app, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(
+            preview("  fn main() {\n    run();\n}\n"),
+            "fn main() { run(); }"
+        );
+        let long = "word ".repeat(100);
+        let preview = preview(&long);
+        assert_eq!(preview.chars().count(), PREVIEW_LEN + 1);
+        assert!(preview.ends_with('…'));
+    }
+}
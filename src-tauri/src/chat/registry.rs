@@ -113,6 +113,17 @@ pub fn cancel_process(
             log::error!("Failed to emit chat:cancelled event: {e}");
         }
 
+        Ok(true)
+    } else if super::scheduler::cancel_queued(app, session_id) {
+        // Still waiting for a process slot: nothing ran yet
+        let event = CancelledEvent {
+            session_id: session_id.to_string(),
+            worktree_id: worktree_id.to_string(),
+            undo_send: true,
+        };
+        if let Err(e) = app.emit_all("chat:cancelled", &event) {
+            log::error!("Failed to emit chat:cancelled event: {e}");
+        }
         Ok(true)
     } else {
         log::trace!("No running process found for session: {session_id}");
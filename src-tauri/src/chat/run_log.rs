@@ -2,10 +2,15 @@
 //!
 //! This module handles writing and reading JSONL log files that contain
 //! the raw Claude CLI output. Each run (Claude execution) gets its own file.
+//!
+//! When a session or worktree is archived its finished run logs are compressed
+//! with zstd (`{run_id}.jsonl.zst`); readers fall back to the compressed file
+//! transparently. With encryption at rest enabled, logs are sealed once their
+//! run finishes (see `crate::encryption`).
 
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use uuid::Uuid;
@@ -14,7 +19,8 @@ use super::storage::{
     get_session_dir, list_all_session_ids, load_metadata, save_metadata, with_metadata_mut,
 };
 use super::types::{
-    ChatMessage, ContentBlock, MessageRole, RunEntry, RunStatus, ToolCall, UsageData,
+    ChatMessage, ContentBlock, MessageRole, RunEntry, RunStatus, SessionMessagesPage, ToolCall,
+    UsageData,
 };
 
 // ============================================================================
@@ -94,9 +100,18 @@ impl RunLogWriter {
         )?;
 
         log::trace!("Run completed: {}", self.run_id);
+        self.seal_log();
         Ok(())
     }
 
+    /// Encrypt the finished log if encryption at rest is enabled (the CLI is
+    /// done writing to it)
+    fn seal_log(&self) {
+        if let Ok(path) = self.output_file_path() {
+            crate::encryption::seal_if_enabled(&path);
+        }
+    }
+
     /// Mark the run as cancelled and update the metadata
     pub fn cancel(&mut self, assistant_message_id: Option<&str>) -> Result<(), String> {
         let now = now_timestamp();
@@ -121,6 +136,7 @@ impl RunLogWriter {
         )?;
 
         log::trace!("Run cancelled: {}", self.run_id);
+        self.seal_log();
         Ok(())
     }
 
@@ -414,23 +430,35 @@ pub fn get_run_log_path(
     Ok(session_dir.join(format!("{run_id}.jsonl")))
 }
 
-/// Read all lines from a run's JSONL file
+/// Read all lines from a run's JSONL file (or its compressed copy)
 pub fn read_run_log(
     app: &tauri::AppHandle,
     session_id: &str,
     run_id: &str,
 ) -> Result<Vec<String>, String> {
     let path = get_run_log_path(app, session_id, run_id)?;
+    read_log_lines(&path)
+}
 
-    if !path.exists() {
-        return Ok(vec![]);
-    }
-
-    let file = File::open(&path).map_err(|e| format!("Failed to open run log: {e}"))?;
-
-    let reader = BufReader::new(file);
-    let lines: Result<Vec<_>, _> = reader.lines().collect();
+/// Read lines from a JSONL file, falling back to `{path}.zst` if only the
+/// compressed copy exists. Encrypted logs are decrypted transparently.
+fn read_log_lines(path: &Path) -> Result<Vec<String>, String> {
+    let bytes = if path.exists() {
+        fs::read(path).map_err(|e| format!("Failed to open run log: {e}"))?
+    } else {
+        let compressed = compressed_path(path);
+        if !compressed.exists() {
+            return Ok(vec![]);
+        }
+        let raw = fs::read(&compressed).map_err(|e| format!("Failed to open run log: {e}"))?;
+        // Sealed after compression (logs archived before encryption was enabled)
+        let raw = crate::encryption::open(raw)?;
+        zstd::stream::decode_all(raw.as_slice())
+            .map_err(|e| format!("Failed to decompress run log: {e}"))?
+    };
+    let bytes = crate::encryption::open(bytes)?;
 
+    let lines: Result<Vec<_>, _> = BufReader::new(bytes.as_slice()).lines().collect();
     lines.map_err(|e| format!("Failed to read run log: {e}"))
 }
 
@@ -595,6 +623,129 @@ pub fn parse_run_to_message(lines: &[String], run: &RunEntry) -> Result<ChatMess
 // Message Loading
 // ============================================================================
 
+/// A message position in a session, known from run metadata alone
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MessageSlot {
+    /// Index into the session's runs
+    run: usize,
+    /// Whether this is the run's assistant response (user message otherwise)
+    assistant: bool,
+}
+
+/// List the messages a session's runs produce, in chronological order,
+/// without reading any run logs
+fn message_slots(runs: &[RunEntry]) -> Vec<MessageSlot> {
+    let mut slots = Vec::with_capacity(runs.len() * 2);
+    for (index, run) in runs.iter().enumerate() {
+        // Skip user message for instant-cancelled runs (undo_send)
+        // These have Cancelled status but no assistant_message_id
+        let is_undo_send = run.status == RunStatus::Cancelled && run.assistant_message_id.is_none();
+        if is_undo_send {
+            continue;
+        }
+        slots.push(MessageSlot {
+            run: index,
+            assistant: false,
+        });
+        // Add assistant message if run has completed/cancelled/crashed
+        if run.status != RunStatus::Running {
+            slots.push(MessageSlot {
+                run: index,
+                assistant: true,
+            });
+        }
+    }
+    slots
+}
+
+/// Number of leading runs that make up a session's history up to the message
+/// at `message_index`: through its run for an assistant message, or just
+/// before it for a user message (so the prompt can be sent differently)
+pub fn runs_through_message(runs: &[RunEntry], message_index: usize) -> Result<usize, String> {
+    let slot = message_slots(runs)
+        .get(message_index)
+        .copied()
+        .ok_or_else(|| format!("Message index out of range: {message_index}"))?;
+    Ok(if slot.assistant {
+        slot.run + 1
+    } else {
+        slot.run
+    })
+}
+
+/// The message ID of a slot, if known before parsing its log
+fn slot_message_id(runs: &[RunEntry], slot: MessageSlot) -> Option<&str> {
+    let run = &runs[slot.run];
+    if slot.assistant {
+        run.assistant_message_id.as_deref()
+    } else {
+        Some(&run.user_message_id)
+    }
+}
+
+/// Range of slots for a page of up to `limit` messages ending just before the
+/// message `before` (or at the newest message)
+fn page_range(
+    runs: &[RunEntry],
+    slots: &[MessageSlot],
+    before: Option<&str>,
+    limit: usize,
+) -> Result<std::ops::Range<usize>, String> {
+    let end = match before {
+        Some(id) => slots
+            .iter()
+            .position(|slot| slot_message_id(runs, *slot) == Some(id))
+            .ok_or_else(|| format!("Message not found: {id}"))?,
+        None => slots.len(),
+    };
+    Ok(end.saturating_sub(limit)..end)
+}
+
+/// Build the message for a slot, parsing the run log for assistant responses
+fn load_slot_message(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    run: &RunEntry,
+    assistant: bool,
+) -> Result<ChatMessage, String> {
+    if !assistant {
+        return Ok(ChatMessage {
+            id: run.user_message_id.clone(),
+            session_id: session_id.to_string(),
+            role: MessageRole::User,
+            content: run.user_message.clone(),
+            timestamp: run.started_at,
+            tool_calls: vec![],
+            content_blocks: vec![],
+            cancelled: false,
+            plan_approved: false,
+            model: run.model.clone(),
+            execution_mode: run.execution_mode.clone(),
+            thinking_level: run.thinking_level.clone(),
+            effort_level: run.effort_level.clone(),
+            recovered: false,
+            usage: None, // User messages don't have token usage
+        });
+    }
+
+    let lines = read_run_log(app, session_id, &run.run_id)?;
+
+    // Parse JSONL content (may only have metadata header if crashed early)
+    let mut assistant_msg = parse_run_to_message(&lines, run)?;
+    assistant_msg.session_id = session_id.to_string();
+
+    // For crashed runs with no content (only metadata header), add placeholder
+    if run.status == RunStatus::Crashed
+        && assistant_msg.content.is_empty()
+        && assistant_msg.tool_calls.is_empty()
+    {
+        assistant_msg.content =
+            "*Response lost - Jean was closed before receiving a response.*".to_string();
+    }
+
+    Ok(assistant_msg)
+}
+
 /// Load all messages for a session by parsing JSONL files
 /// Returns messages in chronological order (user message, then assistant response)
 pub fn load_session_messages(
@@ -606,56 +757,70 @@ pub fn load_session_messages(
         None => return Ok(vec![]),
     };
 
-    let mut messages = Vec::new();
+    message_slots(&metadata.runs)
+        .into_iter()
+        .map(|slot| load_slot_message(app, session_id, &metadata.runs[slot.run], slot.assistant))
+        .collect()
+}
 
-    for run in &metadata.runs {
-        // Skip user message for instant-cancelled runs (undo_send)
-        // These have Cancelled status but no assistant_message_id
-        let is_undo_send = run.status == RunStatus::Cancelled && run.assistant_message_id.is_none();
+/// Load a session's messages from position `start` on, along with its total
+/// message count (used to index new messages incrementally)
+pub fn load_session_messages_from(
+    app: &tauri::AppHandle,
+    metadata: &SessionMetadata,
+    start: usize,
+) -> Result<(Vec<ChatMessage>, usize), String> {
+    let slots = message_slots(&metadata.runs);
+    let messages = slots
+        .iter()
+        .skip(start)
+        .map(|slot| load_slot_message(app, &metadata.id, &metadata.runs[slot.run], slot.assistant))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((messages, slots.len()))
+}
 
-        if !is_undo_send {
-            // Add user message
-            messages.push(ChatMessage {
-                id: run.user_message_id.clone(),
-                session_id: session_id.to_string(),
-                role: MessageRole::User,
-                content: run.user_message.clone(),
-                timestamp: run.started_at,
-                tool_calls: vec![],
-                content_blocks: vec![],
-                cancelled: false,
-                plan_approved: false,
-                model: run.model.clone(),
-                execution_mode: run.execution_mode.clone(),
-                thinking_level: run.thinking_level.clone(),
-                effort_level: run.effort_level.clone(),
-                recovered: false,
-                usage: None, // User messages don't have token usage
-            });
+/// Load a page of up to `limit` messages ending just before the message
+/// `before` (or the newest messages when None), in chronological order.
+///
+/// Only the run logs of the returned messages are read, so opening a long
+/// session doesn't parse its whole history.
+pub fn load_session_messages_page(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    before: Option<&str>,
+    limit: usize,
+) -> Result<SessionMessagesPage, String> {
+    let metadata = match load_metadata(app, session_id)? {
+        Some(m) => m,
+        None => {
+            return Ok(SessionMessagesPage {
+                messages: vec![],
+                has_more: false,
+                total: 0,
+            })
         }
+    };
 
-        // Add assistant message if run has completed/cancelled/crashed
-        if run.status != RunStatus::Running && !is_undo_send {
-            let lines = read_run_log(app, session_id, &run.run_id)?;
-
-            // Parse JSONL content (may only have metadata header if crashed early)
-            let mut assistant_msg = parse_run_to_message(&lines, run)?;
-            assistant_msg.session_id = session_id.to_string();
-
-            // For crashed runs with no content (only metadata header), add placeholder
-            if run.status == RunStatus::Crashed
-                && assistant_msg.content.is_empty()
-                && assistant_msg.tool_calls.is_empty()
-            {
-                assistant_msg.content =
-                    "*Response lost - Jean was closed before receiving a response.*".to_string();
-            }
-
-            messages.push(assistant_msg);
+    let slots = message_slots(&metadata.runs);
+    let range = page_range(&metadata.runs, &slots, before, limit)?;
+    let has_more = range.start > 0;
+    let mut messages = slots[range]
+        .iter()
+        .map(|slot| load_slot_message(app, session_id, &metadata.runs[slot.run], slot.assistant))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Apply approved plan status from session metadata
+    for msg in &mut messages {
+        if metadata.approved_plan_message_ids.contains(&msg.id) {
+            msg.plan_approved = true;
         }
     }
 
-    Ok(messages)
+    Ok(SessionMessagesPage {
+        messages,
+        has_more,
+        total: slots.len(),
+    })
 }
 
 /// Mark any running run for this session as cancelled (called by cancel_process)
@@ -831,6 +996,112 @@ pub fn mark_run_as_crashed(
     Ok(())
 }
 
+// ============================================================================
+// Compression (archived sessions)
+// ============================================================================
+
+/// zstd level for archived run logs (good ratio on repetitive JSON, still fast)
+const COMPRESSION_LEVEL: i32 = 9;
+
+/// Path of the compressed copy of a run log: `{run_id}.jsonl.zst`
+pub fn compressed_path(path: &Path) -> PathBuf {
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(".zst");
+    PathBuf::from(compressed)
+}
+
+/// Compress a JSONL file to `{path}.zst` and remove the original.
+/// Returns `(original_size, compressed_size)`.
+fn compress_file(path: &Path) -> Result<(u64, u64), String> {
+    let target = compressed_path(path);
+    let temp = target.with_extension("zst.tmp");
+
+    let result = (|| {
+        let mut input = File::open(path).map_err(|e| format!("Failed to open run log: {e}"))?;
+        let output =
+            File::create(&temp).map_err(|e| format!("Failed to create compressed log: {e}"))?;
+        let mut encoder = zstd::stream::write::Encoder::new(output, COMPRESSION_LEVEL)
+            .map_err(|e| format!("Failed to start compression: {e}"))?;
+        let original = std::io::copy(&mut input, &mut encoder)
+            .map_err(|e| format!("Failed to compress run log: {e}"))?;
+        let output = encoder
+            .finish()
+            .map_err(|e| format!("Failed to finish compression: {e}"))?;
+        output
+            .sync_all()
+            .map_err(|e| format!("Failed to flush compressed log: {e}"))?;
+        let compressed = output.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok((original, compressed))
+    })();
+
+    match result {
+        Ok(sizes) => {
+            fs::rename(&temp, &target)
+                .map_err(|e| format!("Failed to finalize compressed log: {e}"))?;
+            fs::remove_file(path).map_err(|e| format!("Failed to remove run log: {e}"))?;
+            Ok(sizes)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+/// Compress the finished run logs of a session (called when it is archived).
+/// Returns the number of bytes saved.
+pub fn compress_session_logs(app: &tauri::AppHandle, session_id: &str) -> Result<u64, String> {
+    let Some(metadata) = load_metadata(app, session_id)? else {
+        return Ok(0);
+    };
+    let session_dir = get_session_dir(app, session_id)?;
+
+    let mut saved = 0;
+    for run in &metadata.runs {
+        // Logs of live runs are still being appended to
+        if matches!(run.status, RunStatus::Running | RunStatus::Resumable) {
+            continue;
+        }
+        let path = session_dir.join(format!("{}.jsonl", run.run_id));
+        if !path.exists() {
+            continue;
+        }
+        let (original, compressed) = compress_file(&path)?;
+        saved += original.saturating_sub(compressed);
+    }
+
+    if saved > 0 {
+        log::trace!("Compressed run logs for session {session_id}, saved {saved} bytes");
+    }
+    Ok(saved)
+}
+
+/// Compress run logs for every session of a worktree (called when it is archived)
+pub fn compress_worktree_logs(app: &tauri::AppHandle, worktree_id: &str) -> Result<u64, String> {
+    let sessions = super::storage::load_sessions_by_id(app, worktree_id)?;
+    let mut saved = 0;
+    for session in &sessions.sessions {
+        match compress_session_logs(app, &session.id) {
+            Ok(bytes) => saved += bytes,
+            Err(e) => log::warn!("Failed to compress logs for session {}: {e}", session.id),
+        }
+    }
+    Ok(saved)
+}
+
+/// Compress archived logs in the background so archiving stays instant
+pub fn spawn_compress_logs(app: tauri::AppHandle, session_id: Option<String>, worktree_id: String) {
+    std::thread::spawn(move || {
+        let result = match session_id {
+            Some(session_id) => compress_session_logs(&app, &session_id),
+            None => compress_worktree_logs(&app, &worktree_id),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to compress archived run logs: {e}");
+        }
+    });
+}
+
 // ============================================================================
 // Cleanup Functions
 // ============================================================================
@@ -848,7 +1119,10 @@ pub fn delete_run_logs(app: &tauri::AppHandle, session_id: &str) -> Result<usize
             .flatten()
         {
             let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "jsonl") {
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "jsonl" || ext == "zst")
+            {
                 fs::remove_file(&path).map_err(|e| format!("Failed to delete run log: {e}"))?;
                 deleted += 1;
             }
@@ -868,3 +1142,91 @@ fn now_timestamp() -> u64 {
         .unwrap_or_default()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_log_reads_transparently() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run-1.jsonl");
+        fs::write(&path, "{\"type\":\"a\"}\n{\"type\":\"b\"}\n").unwrap();
+
+        let (original, _) = compress_file(&path).unwrap();
+        assert_eq!(original, 26);
+        assert!(!path.exists());
+        assert!(compressed_path(&path).exists());
+
+        let lines = read_log_lines(&path).unwrap();
+        assert_eq!(lines, vec!["{\"type\":\"a\"}", "{\"type\":\"b\"}"]);
+    }
+
+    fn run(id: &str, status: RunStatus, assistant_message_id: Option<&str>) -> RunEntry {
+        RunEntry {
+            run_id: format!("run-{id}"),
+            user_message_id: format!("user-{id}"),
+            user_message: String::new(),
+            model: None,
+            execution_mode: None,
+            thinking_level: None,
+            effort_level: None,
+            started_at: 0,
+            ended_at: None,
+            status,
+            assistant_message_id: assistant_message_id.map(str::to_string),
+            cancelled: false,
+            recovered: false,
+            claude_session_id: None,
+            pid: None,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_message_pages() {
+        let runs = vec![
+            run("1", RunStatus::Completed, Some("reply-1")),
+            // Undo-send: no messages
+            run("2", RunStatus::Cancelled, None),
+            run("3", RunStatus::Completed, Some("reply-3")),
+            // Still running: user message only
+            run("4", RunStatus::Running, None),
+        ];
+        let slots = message_slots(&runs);
+        assert_eq!(slots.len(), 5);
+
+        let newest = page_range(&runs, &slots, None, 2).unwrap();
+        assert_eq!(newest, 3..5);
+        let older = page_range(&runs, &slots, Some("user-3"), 2).unwrap();
+        assert_eq!(older, 0..2);
+        assert_eq!(slot_message_id(&runs, slots[older.start]), Some("user-1"));
+        assert_eq!(
+            slot_message_id(&runs, slots[older.end - 1]),
+            Some("reply-1")
+        );
+
+        assert!(page_range(&runs, &slots, Some("user-2"), 2).is_err());
+    }
+
+    #[test]
+    fn test_runs_through_message() {
+        let runs = vec![
+            run("1", RunStatus::Completed, Some("reply-1")),
+            run("2", RunStatus::Cancelled, None),
+            run("3", RunStatus::Completed, Some("reply-3")),
+        ];
+        // Forking at a reply keeps its run, at a prompt only the runs before
+        assert_eq!(runs_through_message(&runs, 1).unwrap(), 1);
+        assert_eq!(runs_through_message(&runs, 2).unwrap(), 2);
+        assert_eq!(runs_through_message(&runs, 3).unwrap(), 3);
+        assert!(runs_through_message(&runs, 4).is_err());
+    }
+
+    #[test]
+    fn test_missing_log_reads_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = read_log_lines(&dir.path().join("missing.jsonl")).unwrap();
+        assert!(lines.is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+//! Claude CLI process scheduler
+//!
+//! Caps how many Claude CLI processes run at once (the
+//! `max_concurrent_sessions` preference, 0 = no limit). A message sent while
+//! every slot is taken waits in a queue and `chat:queued` is emitted with its
+//! position; `chat:started` is emitted when it gets a slot. Slots are handed
+//! to queued messages in order, except that the focused session can be moved
+//! ahead of the others. A queued message can be cancelled like a running one.
+
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::oneshot;
+
+use crate::http_server::EmitExt;
+
+/// A message waiting for a slot
+struct Waiter {
+    session_id: String,
+    worktree_id: String,
+    /// Focused session, served before the others
+    priority: bool,
+    ready: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    queue: Vec<Waiter>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+/// Payload of `chat:queued`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedEvent {
+    pub session_id: String,
+    pub worktree_id: String,
+    /// 1-based position in the queue
+    pub position: usize,
+}
+
+/// Payload of `chat:started`
+#[derive(Debug, Clone, Serialize)]
+pub struct StartedEvent {
+    pub session_id: String,
+    pub worktree_id: String,
+}
+
+/// A taken process slot, handed to the next queued message when dropped
+pub struct Slot {
+    app: AppHandle,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut state = STATE.lock().unwrap();
+        // Receivers of cancelled messages are gone; skip them
+        while let Some(waiter) = next_waiter(&mut state.queue) {
+            if waiter.ready.send(()).is_ok() {
+                emit_positions(&self.app, &state.queue);
+                return;
+            }
+        }
+        state.running = state.running.saturating_sub(1);
+    }
+}
+
+/// Remove the next waiter to serve: the first prioritized one, else the
+/// oldest
+fn next_waiter(queue: &mut Vec<Waiter>) -> Option<Waiter> {
+    if queue.is_empty() {
+        return None;
+    }
+    let index = queue.iter().position(|w| w.priority).unwrap_or(0);
+    Some(queue.remove(index))
+}
+
+fn emit_positions(app: &AppHandle, queue: &[Waiter]) {
+    let mut ordered: Vec<&Waiter> = queue.iter().filter(|w| w.priority).collect();
+    ordered.extend(queue.iter().filter(|w| !w.priority));
+    for (i, waiter) in ordered.into_iter().enumerate() {
+        let event = QueuedEvent {
+            session_id: waiter.session_id.clone(),
+            worktree_id: waiter.worktree_id.clone(),
+            position: i + 1,
+        };
+        if let Err(e) = app.emit_all("chat:queued", &event) {
+            log::error!("Failed to emit chat:queued event: {e}");
+        }
+    }
+}
+
+fn emit_started(app: &AppHandle, session_id: &str, worktree_id: &str) {
+    let event = StartedEvent {
+        session_id: session_id.to_string(),
+        worktree_id: worktree_id.to_string(),
+    };
+    if let Err(e) = app.emit_all("chat:started", &event) {
+        log::error!("Failed to emit chat:started event: {e}");
+    }
+}
+
+/// Wait for a process slot for a session, with at most `limit` processes
+/// running (0 = no limit). Returns None if the message was cancelled while
+/// queued.
+pub async fn acquire(
+    app: &AppHandle,
+    session_id: &str,
+    worktree_id: &str,
+    limit: usize,
+) -> Option<Slot> {
+    let ready = {
+        let mut state = STATE.lock().unwrap();
+        if limit == 0 || (state.running < limit && state.queue.is_empty()) {
+            state.running += 1;
+            None
+        } else {
+            let (ready, wait) = oneshot::channel();
+            state.queue.push(Waiter {
+                session_id: session_id.to_string(),
+                worktree_id: worktree_id.to_string(),
+                priority: false,
+                ready,
+            });
+            log::info!(
+                "Queued Claude CLI run for session {session_id} ({} running, limit {limit})",
+                state.running
+            );
+            emit_positions(app, &state.queue);
+            Some(wait)
+        }
+    };
+    if let Some(wait) = ready {
+        // The slot is handed over by the dropped one; an error means the
+        // message was cancelled
+        wait.await.ok()?;
+    }
+    emit_started(app, session_id, worktree_id);
+    Some(Slot { app: app.clone() })
+}
+
+/// Move a session's queued message ahead of the others (the session got
+/// focus). Returns whether it was queued.
+pub fn prioritize(app: &AppHandle, session_id: &str) -> bool {
+    let mut state = STATE.lock().unwrap();
+    // Only one session has focus at a time
+    for waiter in state.queue.iter_mut() {
+        waiter.priority = waiter.session_id == session_id;
+    }
+    let queued = state.queue.iter().any(|w| w.session_id == session_id);
+    if queued {
+        emit_positions(app, &state.queue);
+    }
+    queued
+}
+
+/// Whether any message is waiting for a slot
+pub fn has_queued() -> bool {
+    // Receivers of cancelled messages are gone; those don't count
+    STATE
+        .lock()
+        .unwrap()
+        .queue
+        .iter()
+        .any(|w| !w.ready.is_closed())
+}
+
+/// Drop a session's queued message. Returns whether it was queued.
+pub fn cancel_queued(app: &AppHandle, session_id: &str) -> bool {
+    let mut state = STATE.lock().unwrap();
+    let before = state.queue.len();
+    state.queue.retain(|w| w.session_id != session_id);
+    let cancelled = state.queue.len() != before;
+    if cancelled {
+        log::trace!("Cancelled queued Claude CLI run for session {session_id}");
+        emit_positions(app, &state.queue);
+    }
+    cancelled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waiter(session_id: &str, priority: bool) -> Waiter {
+        Waiter {
+            session_id: session_id.to_string(),
+            worktree_id: "w1".to_string(),
+            priority,
+            ready: oneshot::channel().0,
+        }
+    }
+
+    #[test]
+    fn test_next_waiter_prefers_focused_session() {
+        let mut queue = vec![waiter("a", false), waiter("b", true), waiter("c", false)];
+        let order: Vec<String> = std::iter::from_fn(|| next_waiter(&mut queue))
+            .map(|w| w.session_id)
+            .collect();
+        assert_eq!(order, vec!["b", "a", "c"]);
+    }
+}
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{BufReader, BufWriter};
+use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
-use tauri::{AppHandle, Manager};
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::AppHandle;
+
+use crate::db::{lock_exclusive, with_db, SESSION_INDEX_LOCK, SESSION_METADATA_LOCK};
 
 use super::types::{
     SavedContextsMetadata, Session, SessionIndexEntry, SessionMetadata, WorktreeIndex,
@@ -71,10 +73,7 @@ pub fn sanitize_filename(name: &str) -> String {
 /// Get the sessions base directory in app data (creates if not exists)
 /// Structure: sessions/
 pub fn get_sessions_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let sessions_dir = app_data_dir.join("sessions");
 
@@ -84,17 +83,6 @@ pub fn get_sessions_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(sessions_dir)
 }
 
-/// Get the index directory (creates if not exists)
-/// Structure: sessions/index/
-pub fn get_index_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let sessions_dir = get_sessions_dir(app)?;
-    let index_dir = sessions_dir.join("index");
-
-    fs::create_dir_all(&index_dir).map_err(|e| format!("Failed to create index directory: {e}"))?;
-
-    Ok(index_dir)
-}
-
 /// Get the data directory (creates if not exists)
 /// Structure: sessions/data/
 pub fn get_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
@@ -106,19 +94,11 @@ pub fn get_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
-/// Get the index file path for a worktree
-/// Path: sessions/index/{worktree_id}.json
-pub fn get_index_path(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, String> {
-    let index_dir = get_index_dir(app)?;
-    let safe_id = sanitize_filename(worktree_id);
-    Ok(index_dir.join(format!("{safe_id}.json")))
-}
-
 /// Get the session data directory (creates if not exists)
-/// Path: sessions/data/{session_id}/
+/// Path: sessions/data/{session_id}/ (holds run logs; metadata lives in the database)
 pub fn get_session_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
     let data_dir = get_data_dir(app)?;
-    let session_dir = data_dir.join(session_id);
+    let session_dir = data_dir.join(sanitize_filename(session_id));
 
     fs::create_dir_all(&session_dir)
         .map_err(|e| format!("Failed to create session directory: {e}"))?;
@@ -126,40 +106,56 @@ pub fn get_session_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, Str
     Ok(session_dir)
 }
 
-/// Get the metadata file path for a session
-/// Path: sessions/data/{session_id}/metadata.json
-pub fn get_metadata_path(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
-    let session_dir = get_session_dir(app, session_id)?;
-    Ok(session_dir.join("metadata.json"))
-}
-
-/// Get the path for a closed base session's preserved index file
-/// Path: sessions/index/base-{project_id}.json
-pub fn get_base_index_path(app: &AppHandle, project_id: &str) -> Result<PathBuf, String> {
-    let index_dir = get_index_dir(app)?;
-    let safe_id = sanitize_filename(project_id);
-    Ok(index_dir.join(format!("base-{safe_id}.json")))
+/// Database key for a closed base session's preserved index
+fn base_index_key(project_id: &str) -> String {
+    format!("base-{project_id}")
 }
 
 // ============================================================================
 // Index Operations (WorktreeIndex)
 // ============================================================================
 
-/// Load a worktree index (internal, no locking)
-fn load_index_internal(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, String> {
-    let path = get_index_path(app, worktree_id)?;
-
-    if path.exists() {
-        let contents = fs::read_to_string(&path).map_err(|e| {
-            log::error!("Failed to read index file: {e}");
-            format!("Failed to read index: {e}")
-        })?;
+/// Read a stored index row by key
+fn read_index_row(conn: &Connection, key: &str) -> Result<Option<WorktreeIndex>, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT data FROM session_indexes WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read index: {e}"))?;
 
-        let index: WorktreeIndex = serde_json::from_str(&contents).map_err(|e| {
+    json.map(|json| {
+        serde_json::from_str(&json).map_err(|e| {
             log::error!("Failed to parse index JSON: {e}");
             format!("Failed to parse index: {e}")
-        })?;
+        })
+    })
+    .transpose()
+}
+
+/// Write an index row under the given key
+fn write_index_row(conn: &Connection, key: &str, index: &WorktreeIndex) -> Result<(), String> {
+    let json = serde_json::to_string(index).map_err(|e| {
+        log::error!("Failed to serialize index: {e}");
+        format!("Failed to serialize index: {e}")
+    })?;
 
+    conn.execute(
+        "INSERT OR REPLACE INTO session_indexes (key, data) VALUES (?1, ?2)",
+        params![key, json],
+    )
+    .map_err(|e| {
+        log::error!("Failed to write index: {e}");
+        format!("Failed to write index: {e}")
+    })?;
+    Ok(())
+}
+
+/// Load a worktree index (internal, no locking)
+fn load_index_internal(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, String> {
+    if let Some(index) = with_db(app, |conn| read_index_row(conn, worktree_id))? {
         return Ok(index);
     }
 
@@ -168,26 +164,10 @@ fn load_index_internal(app: &AppHandle, worktree_id: &str) -> Result<WorktreeInd
     Ok(WorktreeIndex::new(worktree_id.to_string()))
 }
 
-/// Save a worktree index (internal, no locking - atomic write)
+/// Save a worktree index (internal, no locking)
 fn save_index_internal(app: &AppHandle, index: &WorktreeIndex) -> Result<(), String> {
     log::trace!("Saving index for worktree: {}", index.worktree_id);
-    let path = get_index_path(app, &index.worktree_id)?;
-    let temp_path = path.with_extension("tmp");
-
-    let json_content = serde_json::to_string_pretty(index).map_err(|e| {
-        log::error!("Failed to serialize index: {e}");
-        format!("Failed to serialize index: {e}")
-    })?;
-
-    fs::write(&temp_path, &json_content).map_err(|e| {
-        log::error!("Failed to write index file: {e}");
-        format!("Failed to write index: {e}")
-    })?;
-
-    fs::rename(&temp_path, &path).map_err(|e| {
-        log::error!("Failed to finalize index file: {e}");
-        format!("Failed to finalize index: {e}")
-    })?;
+    with_db(app, |conn| write_index_row(conn, &index.worktree_id, index))?;
 
     log::trace!(
         "Saved {} sessions in index for worktree {}",
@@ -197,16 +177,21 @@ fn save_index_internal(app: &AppHandle, index: &WorktreeIndex) -> Result<(), Str
     Ok(())
 }
 
+/// Whether an index exists for the given key
+fn index_exists(app: &AppHandle, key: &str) -> Result<bool, String> {
+    with_db(app, |conn| Ok(read_index_row(conn, key)?.is_some()))
+}
+
 /// Load a worktree index (with locking for thread safety)
 pub fn load_index(app: &AppHandle, worktree_id: &str) -> Result<WorktreeIndex, String> {
     let lock = get_index_lock(worktree_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
 
     let index = load_index_internal(app, worktree_id)?;
 
-    // If this was a new index file, save it
-    let index_path = get_index_path(app, worktree_id)?;
-    if !index_path.exists() {
+    // If this was a new index, save it
+    if !index_exists(app, worktree_id)? {
         save_index_internal(app, &index)?;
     }
 
@@ -221,6 +206,7 @@ where
 {
     let lock = get_index_lock(worktree_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
 
     let mut index = load_index_internal(app, worktree_id)?;
     let result = f(&mut index)?;
@@ -238,37 +224,39 @@ fn load_metadata_internal(
     app: &AppHandle,
     session_id: &str,
 ) -> Result<Option<SessionMetadata>, String> {
-    let path = get_metadata_path(app, session_id)?;
-
-    if !path.exists() {
-        return Ok(None);
-    }
-
-    let file =
-        File::open(&path).map_err(|e| format!("Failed to open metadata file {path:?}: {e}"))?;
-
-    let reader = BufReader::new(file);
-    let metadata: SessionMetadata = serde_json::from_reader(reader)
-        .map_err(|e| format!("Failed to parse metadata file {path:?}: {e}"))?;
+    let json: Option<String> = with_db(app, |conn| {
+        conn.query_row(
+            "SELECT data FROM session_metadata WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read metadata for session {session_id}: {e}"))
+    })?;
 
-    Ok(Some(metadata))
+    json.map(|json| {
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse metadata for session {session_id}: {e}"))
+    })
+    .transpose()
 }
 
-/// Save session metadata (internal, no locking - atomic write)
+/// Save session metadata (internal, no locking)
 fn save_metadata_internal(app: &AppHandle, metadata: &SessionMetadata) -> Result<(), String> {
-    let path = get_metadata_path(app, &metadata.id)?;
-    let temp_path = path.with_extension("tmp");
-
-    let file = File::create(&temp_path)
-        .map_err(|e| format!("Failed to create temp metadata file: {e}"))?;
-
-    let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, metadata)
-        .map_err(|e| format!("Failed to write metadata: {e}"))?;
-
-    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to rename metadata file: {e}"))?;
+    let json =
+        serde_json::to_string(metadata).map_err(|e| format!("Failed to write metadata: {e}"))?;
+
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO session_metadata (session_id, worktree_id, data)
+             VALUES (?1, ?2, ?3)",
+            params![metadata.id, metadata.worktree_id, json],
+        )
+        .map_err(|e| format!("Failed to write metadata: {e}"))
+    })?;
 
     log::trace!("Saved metadata for session: {}", metadata.id);
+    crate::search::session_saved(app, metadata);
     Ok(())
 }
 
@@ -283,6 +271,7 @@ pub fn load_metadata(app: &AppHandle, session_id: &str) -> Result<Option<Session
 pub fn save_metadata(app: &AppHandle, metadata: &SessionMetadata) -> Result<(), String> {
     let lock = get_metadata_lock(&metadata.id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_METADATA_LOCK)?;
     save_metadata_internal(app, metadata)
 }
 
@@ -301,6 +290,7 @@ where
 {
     let lock = get_metadata_lock(session_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_METADATA_LOCK)?;
 
     let mut metadata = load_metadata_internal(app, session_id)?.unwrap_or_else(|| {
         SessionMetadata::new(
@@ -321,39 +311,41 @@ where
 pub fn delete_session_data(app: &AppHandle, session_id: &str) -> Result<(), String> {
     let lock = get_metadata_lock(session_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_METADATA_LOCK)?;
+
+    with_db(app, |conn| {
+        conn.execute(
+            "DELETE FROM session_metadata WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| format!("Failed to delete session metadata: {e}"))
+    })?;
+    crate::search::remove_session(app, session_id)?;
 
     let data_dir = get_data_dir(app)?;
-    let session_dir = data_dir.join(session_id);
+    let session_dir = data_dir.join(sanitize_filename(session_id));
 
     if session_dir.exists() {
         fs::remove_dir_all(&session_dir)
             .map_err(|e| format!("Failed to delete session directory: {e}"))?;
-        log::trace!("Deleted session data for: {session_id}");
     }
+    log::trace!("Deleted session data for: {session_id}");
 
     Ok(())
 }
 
-/// List all session IDs in the data directory (for recovery scanning)
+/// List all session IDs with stored metadata (for recovery scanning)
 pub fn list_all_session_ids(app: &AppHandle) -> Result<Vec<String>, String> {
-    let data_dir = get_data_dir(app)?;
-    let mut session_ids = Vec::new();
-
-    let entries =
-        fs::read_dir(&data_dir).map_err(|e| format!("Failed to read data directory: {e}"))?;
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-
-        // Check if it's a directory with a metadata.json
-        if path.is_dir() && path.join("metadata.json").exists() {
-            if let Some(session_id) = path.file_name().and_then(|n| n.to_str()) {
-                session_ids.push(session_id.to_string());
-            }
-        }
-    }
-
-    Ok(session_ids)
+    with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare("SELECT session_id FROM session_metadata")
+            .map_err(|e| format!("Failed to list sessions: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to list sessions: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list sessions: {e}"))
+    })
 }
 
 // ============================================================================
@@ -475,10 +467,12 @@ where
         Ok(())
     })?;
 
-    // Save metadata for each session
+    // Save metadata for each session, taking the locks in the same order as
+    // `with_metadata_mut` (session mutex, then the file lock)
     for session in &sessions.sessions {
         let lock = get_metadata_lock(&session.id);
         let _guard = lock.lock().unwrap();
+        let _file_lock = lock_exclusive(app, SESSION_METADATA_LOCK)?;
 
         let mut metadata = load_metadata_internal(app, &session.id)?.unwrap_or_else(|| {
             SessionMetadata::new(
@@ -496,9 +490,97 @@ where
     Ok(result)
 }
 
-/// Get the index file path (for backward compatibility with old get_sessions_path)
-pub fn get_sessions_path(app: &AppHandle, worktree_id: &str) -> Result<PathBuf, String> {
-    get_index_path(app, worktree_id)
+/// All stored indexes with their keys: a worktree ID, or `base-{project_id}`
+/// for a closed base session's preserved index
+pub fn list_indexes(app: &AppHandle) -> Result<Vec<(String, WorktreeIndex)>, String> {
+    let rows: Vec<(String, String)> = with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare("SELECT key, data FROM session_indexes")
+            .map_err(|e| format!("Failed to list indexes: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to list indexes: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list indexes: {e}"))
+    })?;
+
+    let mut indexes = Vec::with_capacity(rows.len());
+    for (key, json) in rows {
+        match serde_json::from_str(&json) {
+            Ok(index) => indexes.push((key, index)),
+            Err(e) => log::warn!("Skipping unparseable index {key}: {e}"),
+        }
+    }
+    Ok(indexes)
+}
+
+/// Owner of a stored session index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKey<'a> {
+    Worktree(&'a str),
+    Base(&'a str),
+}
+
+/// Tell a worktree's index key apart from a preserved base index key
+pub fn parse_index_key(key: &str) -> IndexKey<'_> {
+    match key.strip_prefix("base-") {
+        Some(project_id) => IndexKey::Base(project_id),
+        None => IndexKey::Worktree(key),
+    }
+}
+
+/// Delete a worktree's session index (e.g. when the worktree is removed)
+pub fn delete_index(app: &AppHandle, worktree_id: &str) -> Result<(), String> {
+    let lock = get_index_lock(worktree_id);
+    let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
+
+    with_db(app, |conn| {
+        conn.execute("DELETE FROM session_indexes WHERE key = ?1", [worktree_id])
+            .map_err(|e| format!("Failed to delete sessions for {worktree_id}: {e}"))
+    })?;
+    log::trace!("Deleted session index for worktree: {worktree_id}");
+    Ok(())
+}
+
+/// Move a session index to a different worktree (e.g. when a base session is recreated)
+pub fn move_index(
+    app: &AppHandle,
+    from_worktree_id: &str,
+    to_worktree_id: &str,
+) -> Result<(), String> {
+    let lock = get_index_lock(from_worktree_id);
+    let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
+
+    with_db(app, |conn| {
+        let Some(mut index) = read_index_row(conn, from_worktree_id)? else {
+            return Ok(());
+        };
+        index.worktree_id = to_worktree_id.to_string();
+
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to move sessions: {e}"))?;
+        write_index_row(&tx, to_worktree_id, &index)?;
+        tx.execute(
+            "DELETE FROM session_indexes WHERE key = ?1",
+            [from_worktree_id],
+        )
+        .map_err(|e| format!("Failed to remove old sessions: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to move sessions: {e}"))
+    })
+}
+
+/// Delete a project's preserved base session index
+pub fn delete_base_index(app: &AppHandle, project_id: &str) -> Result<(), String> {
+    let key = base_index_key(project_id);
+    with_db(app, |conn| {
+        conn.execute("DELETE FROM session_indexes WHERE key = ?1", [&key])
+            .map_err(|e| format!("Failed to delete base sessions for {project_id}: {e}"))
+    })?;
+    Ok(())
 }
 
 /// Load sessions by worktree_id only (for cleanup when worktree path may not exist)
@@ -506,18 +588,12 @@ pub fn load_sessions_by_id(app: &AppHandle, worktree_id: &str) -> Result<Worktre
     load_sessions(app, "", worktree_id)
 }
 
-/// Get the path for a closed base session's preserved index file
-/// (Backward compatible with old get_closed_base_sessions_path)
-pub fn get_closed_base_sessions_path(app: &AppHandle, project_id: &str) -> Result<PathBuf, String> {
-    get_base_index_path(app, project_id)
-}
-
 // ============================================================================
 // Base Session Preservation
 // ============================================================================
 
 /// Preserve sessions when closing a base session
-/// Moves index file to base-{project_id}.json
+/// Re-keys the worktree's index as base-{project_id}
 pub fn preserve_base_sessions(
     app: &AppHandle,
     worktree_id: &str,
@@ -525,23 +601,41 @@ pub fn preserve_base_sessions(
 ) -> Result<(), String> {
     let lock = get_index_lock(worktree_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
 
-    let current_path = get_index_path(app, worktree_id)?;
-    let preserved_path = get_base_index_path(app, project_id)?;
+    let preserved_key = base_index_key(project_id);
+    with_db(app, |conn| {
+        // Already preserved (or nothing to preserve): keep the existing base index
+        if read_index_row(conn, worktree_id)?.is_none() {
+            return Ok(());
+        }
 
-    if current_path.exists() {
-        fs::rename(&current_path, &preserved_path).map_err(|e| {
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to preserve base sessions: {e}"))?;
+        tx.execute(
+            "DELETE FROM session_indexes WHERE key = ?1",
+            [&preserved_key],
+        )
+        .map_err(|e| format!("Failed to preserve base sessions: {e}"))?;
+        tx.execute(
+            "UPDATE session_indexes SET key = ?1 WHERE key = ?2",
+            params![preserved_key, worktree_id],
+        )
+        .map_err(|e| {
             log::error!("Failed to preserve base sessions: {e}");
             format!("Failed to preserve base sessions: {e}")
         })?;
-        log::trace!("Preserved base sessions from {current_path:?} to {preserved_path:?}");
-    }
+        tx.commit()
+            .map_err(|e| format!("Failed to preserve base sessions: {e}"))
+    })?;
 
+    log::trace!("Preserved base sessions from {worktree_id} as {preserved_key}");
     Ok(())
 }
 
 /// Restore preserved sessions when reopening a base session
-/// Loads from base-{project_id}.json and updates worktree_id
+/// Loads the base-{project_id} index and re-keys it to the new worktree_id
 pub fn restore_base_sessions(
     app: &AppHandle,
     project_id: &str,
@@ -549,39 +643,41 @@ pub fn restore_base_sessions(
 ) -> Result<Option<WorktreeIndex>, String> {
     let lock = get_index_lock(new_worktree_id);
     let _guard = lock.lock().unwrap();
+    let _file_lock = lock_exclusive(app, SESSION_INDEX_LOCK)?;
 
-    let preserved_path = get_base_index_path(app, project_id)?;
-
-    if !preserved_path.exists() {
-        log::trace!("No preserved base sessions found for project {project_id}");
-        return Ok(None);
-    }
-
-    // Load the preserved index
-    let contents = fs::read_to_string(&preserved_path)
-        .map_err(|e| format!("Failed to read preserved index: {e}"))?;
-
-    let mut index: WorktreeIndex = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse preserved index: {e}"))?;
+    let preserved_key = base_index_key(project_id);
+    let restored = with_db(app, |conn| {
+        let Some(mut index) = read_index_row(conn, &preserved_key)? else {
+            return Ok(None);
+        };
 
-    // Update the worktree_id to the new one
-    index.worktree_id = new_worktree_id.to_string();
+        // Update the worktree_id to the new one
+        index.worktree_id = new_worktree_id.to_string();
 
-    // Save to the new location
-    save_index_internal(app, &index)?;
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to restore base sessions: {e}"))?;
+        write_index_row(&tx, new_worktree_id, &index)?;
+        tx.execute(
+            "DELETE FROM session_indexes WHERE key = ?1",
+            [&preserved_key],
+        )
+        .map_err(|e| format!("Failed to delete preserved index: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to restore base sessions: {e}"))?;
 
-    // Delete the preserved file
-    fs::remove_file(&preserved_path).map_err(|e| {
-        log::warn!("Failed to delete preserved index file: {e}");
-        format!("Failed to delete preserved index: {e}")
+        Ok(Some(index))
     })?;
 
-    log::trace!(
-        "Restored {} sessions for base session {new_worktree_id}",
-        index.sessions.len()
-    );
+    match &restored {
+        Some(index) => log::trace!(
+            "Restored {} sessions for base session {new_worktree_id}",
+            index.sessions.len()
+        ),
+        None => log::trace!("No preserved base sessions found for project {project_id}"),
+    }
 
-    Ok(Some(index))
+    Ok(restored)
 }
 
 // ============================================================================
@@ -591,10 +687,7 @@ pub fn restore_base_sessions(
 /// Get the images directory path in app data directory (creates if not exists)
 /// Used for storing pasted images: ~/Library/Application Support/<app>/pasted-images/
 pub fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let path = app_data_dir.join("pasted-images");
 
@@ -606,10 +699,7 @@ pub fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
 /// Get the pastes directory path in app data directory (creates if not exists)
 /// Used for storing pasted text files: ~/Library/Application Support/<app>/pasted-texts/
 pub fn get_pastes_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let path = app_data_dir.join("pasted-texts");
 
@@ -621,10 +711,7 @@ pub fn get_pastes_dir(app: &AppHandle) -> Result<PathBuf, String> {
 /// Get the saved contexts directory path in app data directory (creates if not exists)
 /// Used for storing conversation context summaries: ~/Library/Application Support/<app>/session-context/
 pub fn get_saved_contexts_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let path = app_data_dir.join("session-context");
 
@@ -701,6 +788,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_index_key() {
+        assert_eq!(parse_index_key("wt-1"), IndexKey::Worktree("wt-1"));
+        assert_eq!(
+            parse_index_key(&base_index_key("p-1")),
+            IndexKey::Base("p-1")
+        );
+    }
+
     #[test]
     fn test_worktree_index_new() {
         let index = WorktreeIndex::new("test-worktree".to_string());
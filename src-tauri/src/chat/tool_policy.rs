@@ -0,0 +1,168 @@
+//! Tool permission policies
+//!
+//! A project and each of its sessions can have a `ToolPolicy`: allow, deny
+//! and ask lists of Claude permission rules. The session's lists are added to
+//! the project's and passed to the Claude CLI as the `permissions` of its
+//! `--settings`, next to the `--permission-mode` of the execution mode.
+//! Policies are read from storage on every run, so a resumed session always
+//! runs under the current policy, and tools approved in the chat are dropped
+//! when the policy denies them.
+
+use tauri::AppHandle;
+
+use super::storage::{load_metadata, with_metadata_mut};
+use crate::projects::storage::{load_projects_data, with_projects_data_mut};
+use crate::projects::types::ToolPolicy;
+
+/// Append the rules of `extra` missing from `rules`
+fn extend_unique(rules: &mut Vec<String>, extra: &[String]) {
+    for rule in extra {
+        if !rules.contains(rule) {
+            rules.push(rule.clone());
+        }
+    }
+}
+
+/// A project's policy with a session's on top
+fn combine(project: Option<&ToolPolicy>, session: Option<&ToolPolicy>) -> ToolPolicy {
+    let mut policy = project.cloned().unwrap_or_default();
+    if let Some(session) = session {
+        extend_unique(&mut policy.allow, &session.allow);
+        extend_unique(&mut policy.deny, &session.deny);
+        extend_unique(&mut policy.ask, &session.ask);
+    }
+    policy
+}
+
+/// Whether a tool rule is covered by a deny rule (`Bash` denies
+/// `Bash(git push:*)`)
+pub fn is_denied(policy: &ToolPolicy, tool: &str) -> bool {
+    policy
+        .deny
+        .iter()
+        .any(|rule| tool == rule || tool.starts_with(&format!("{rule}(")))
+}
+
+/// The `permissions` object of Claude CLI settings (None for an empty policy)
+pub fn permissions_settings(policy: &ToolPolicy) -> Option<serde_json::Value> {
+    if policy == &ToolPolicy::default() {
+        return None;
+    }
+    Some(serde_json::json!({
+        "allow": policy.allow,
+        "deny": policy.deny,
+        "ask": policy.ask,
+    }))
+}
+
+/// Policy in effect for a session of a worktree
+pub fn for_session(app: &AppHandle, worktree_id: &str, session_id: &str) -> ToolPolicy {
+    let project_policy = load_projects_data(app).ok().and_then(|data| {
+        let worktree = data.find_worktree(worktree_id)?;
+        data.find_project(&worktree.project_id)?.tool_policy.clone()
+    });
+    let session_policy = load_metadata(app, session_id)
+        .ok()
+        .flatten()
+        .and_then(|metadata| metadata.tool_policy);
+    combine(project_policy.as_ref(), session_policy.as_ref())
+}
+
+/// Get the tool policy of a session (when `session_id` is given) or of a
+/// project
+#[tauri::command]
+pub async fn get_tool_policy(
+    app: AppHandle,
+    project_id: Option<String>,
+    session_id: Option<String>,
+) -> Result<Option<ToolPolicy>, String> {
+    match (session_id, project_id) {
+        (Some(session_id), _) => Ok(load_metadata(&app, &session_id)?
+            .ok_or_else(|| format!("Session not found: {session_id}"))?
+            .tool_policy),
+        (None, Some(project_id)) => Ok(load_projects_data(&app)?
+            .find_project(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?
+            .tool_policy
+            .clone()),
+        (None, None) => Err("A project or session ID is required".to_string()),
+    }
+}
+
+/// Set (or with None, clear) the tool policy of a session (when `session_id`
+/// is given) or of a project. Applies from the next message, resumed sessions
+/// included.
+#[tauri::command]
+pub async fn set_tool_policy(
+    app: AppHandle,
+    project_id: Option<String>,
+    session_id: Option<String>,
+    policy: Option<ToolPolicy>,
+) -> Result<(), String> {
+    let policy = policy.filter(|p| p != &ToolPolicy::default());
+    match (session_id, project_id) {
+        (Some(session_id), _) => {
+            let metadata = load_metadata(&app, &session_id)?
+                .ok_or_else(|| format!("Session not found: {session_id}"))?;
+            log::trace!("Setting tool policy of session {session_id}: {policy:?}");
+            with_metadata_mut(
+                &app,
+                &session_id,
+                &metadata.worktree_id,
+                &metadata.name,
+                metadata.order,
+                |metadata| {
+                    metadata.tool_policy = policy;
+                    Ok(())
+                },
+            )
+        }
+        (None, Some(project_id)) => with_projects_data_mut(&app, |data| {
+            let project = data
+                .find_project_mut(&project_id)
+                .ok_or_else(|| format!("Project not found: {project_id}"))?;
+            log::trace!("Setting tool policy of {}: {policy:?}", project.name);
+            project.tool_policy = policy;
+            Ok(())
+        }),
+        (None, None) => Err("A project or session ID is required".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[&str]) -> Vec<String> {
+        rules.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn test_combine_and_deny() {
+        let project = ToolPolicy {
+            allow: rules(&["Bash(npm run test:*)"]),
+            deny: rules(&["WebFetch"]),
+            ask: Vec::new(),
+        };
+        let session = ToolPolicy {
+            allow: rules(&["Bash(npm run test:*)", "Edit(src/**)"]),
+            deny: rules(&["Bash(git push:*)"]),
+            ask: rules(&["Bash"]),
+        };
+        let policy = combine(Some(&project), Some(&session));
+        assert_eq!(
+            policy.allow,
+            rules(&["Bash(npm run test:*)", "Edit(src/**)"])
+        );
+        assert_eq!(policy.deny, rules(&["WebFetch", "Bash(git push:*)"]));
+
+        assert!(is_denied(&policy, "WebFetch(domain:example.com)"));
+        assert!(is_denied(&policy, "Bash(git push:*)"));
+        assert!(!is_denied(&policy, "WebFetchX"));
+        assert!(!is_denied(&policy, "Bash(git status)"));
+
+        assert_eq!(permissions_settings(&combine(None, None)), None);
+        let settings = permissions_settings(&policy).unwrap();
+        assert_eq!(settings["ask"], serde_json::json!(["Bash"]));
+    }
+}
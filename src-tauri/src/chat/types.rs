@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::projects::types::ToolPolicy;
+
 // ============================================================================
 // Session Digest Types
 // ============================================================================
@@ -45,6 +47,9 @@ pub struct UsageData {
     /// Cache creation tokens (cached for future requests)
     #[serde(default)]
     pub cache_creation_input_tokens: u64,
+    /// Cost of the run in USD, as reported by Claude CLI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
 }
 
 // ============================================================================
@@ -280,6 +285,34 @@ pub struct ReadTextResponse {
     pub size: usize,
 }
 
+/// Pixel size of an image file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Metadata and (part of) the content of a file, for the inline viewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePreview {
+    /// Total file size in bytes
+    pub size: u64,
+    pub mime_type: String,
+    pub is_binary: bool,
+    /// Set for images whose header could be read
+    pub image_dimensions: Option<ImageDimensions>,
+    /// Unix timestamp of the last modification
+    pub modified_at: Option<u64>,
+    /// Text of the requested range (None for binary files)
+    pub content: Option<String>,
+    /// Byte offset where `content` starts (moved forward past a partial character)
+    pub offset: u64,
+    /// Bytes of the file covered by `content`
+    pub bytes_read: u64,
+    /// Whether the file continues past the returned range
+    pub truncated: bool,
+}
+
 // ============================================================================
 // Session Types (for multiple tabs per worktree)
 // ============================================================================
@@ -412,7 +445,7 @@ impl Session {
 }
 
 /// Lightweight session entry for index files (fast tab rendering)
-/// Stored in sessions/index/{worktree_id}.json
+/// Stored in the `session_indexes` table of the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionIndexEntry {
     /// Unique session identifier (UUID v4)
@@ -706,6 +739,16 @@ pub struct AllSessionsResponse {
     pub entries: Vec<AllSessionsEntry>,
 }
 
+/// A page of a session's messages, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessagesPage {
+    pub messages: Vec<ChatMessage>,
+    /// Whether older messages exist before this page
+    pub has_more: bool,
+    /// Total number of messages in the session
+    pub total: usize,
+}
+
 // ============================================================================
 // Run Types (for NDJSON-based persistence)
 // ============================================================================
@@ -775,7 +818,7 @@ pub struct RunEntry {
 }
 
 /// Session metadata - single source of truth for session data and run history
-/// Stored in sessions/data/{session_id}/metadata.json
+/// Stored in the `session_metadata` table of the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMetadata {
     /// Unique session identifier (UUID v4)
@@ -843,6 +886,9 @@ pub struct SessionMetadata {
     /// Persisted session digest (recap summary)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub digest: Option<SessionDigest>,
+    /// Tool permissions of this session, on top of the project's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_policy: Option<ToolPolicy>,
 
     /// Run history - each entry corresponds to one Claude CLI execution
     #[serde(default)]
@@ -882,11 +928,11 @@ pub struct RunLogFileInfo {
 pub struct SessionDebugInfo {
     /// App data directory path
     pub app_data_dir: String,
-    /// Path to the index JSON file for this worktree
+    /// Path to the database holding this worktree's session index
     pub sessions_file: String,
     /// Path to the data directory (contains all session directories)
     pub runs_dir: String,
-    /// Path to this session's metadata file (if exists)
+    /// Path to the database holding this session's metadata (if it exists)
     pub manifest_file: Option<String>,
     /// Claude CLI session ID (if any)
     pub claude_session_id: Option<String>,
@@ -928,6 +974,7 @@ impl SessionMetadata {
             plan_file_path: None,
             pending_plan_message_id: None,
             digest: None,
+            tool_policy: None,
             runs: vec![],
             version: 1,
         }
@@ -1116,6 +1163,7 @@ mod tests {
             output_tokens: 200,
             cache_read_input_tokens: 50,
             cache_creation_input_tokens: 25,
+            cost_usd: None,
         };
 
         let json = serde_json::to_string(&usage).unwrap();
@@ -6,6 +6,7 @@ use std::io::Write;
 use tauri::AppHandle;
 
 use super::config::{ensure_cli_dir, get_cli_binary_path};
+use crate::command_audit::AuditedCommand;
 use crate::http_server::EmitExt;
 use crate::platform::silent_command;
 
@@ -87,7 +88,10 @@ pub async fn check_claude_cli_installed(app: AppHandle) -> Result<ClaudeCliStatu
 
     // Try to get the version by running claude --version
     // Use the binary directly - shell wrapper causes PowerShell parsing issues on Windows
-    let version = match silent_command(&binary_path).arg("--version").output() {
+    let version = match silent_command(&binary_path)
+        .arg("--version")
+        .output_audited()
+    {
         Ok(output) => {
             if output.status.success() {
                 let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -253,6 +257,11 @@ fn get_platform() -> Result<&'static str, String> {
         return Ok("win32-x64");
     }
 
+    #[cfg(all(target_os = "windows", target_arch = "aarch64"))]
+    {
+        return Ok("win32-arm64");
+    }
+
     #[allow(unreachable_code)]
     Err("Unsupported platform".to_string())
 }
@@ -425,7 +434,7 @@ pub async fn install_claude_cli(app: AppHandle, version: Option<String>) -> Resu
         let _ = silent_command("xattr")
             .args(["-d", "com.apple.quarantine"])
             .arg(&binary_path)
-            .output();
+            .output_audited();
         // Ignore errors - attribute might not exist
     }
 
@@ -471,7 +480,7 @@ pub async fn check_claude_cli_auth(app: AppHandle) -> Result<ClaudeAuthStatus, S
             "-p",
             "Reply with just the word OK",
         ])
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to execute Claude CLI: {e}"))?;
 
     if output.status.success() {
@@ -0,0 +1,484 @@
+//! `jean-cli`: headless companion for scripts and CI
+//!
+//! Works on the same database as the app (resolved like the app does,
+//! including `locations.json` overrides) and takes the same cross-instance
+//! locks, so it is safe to run while Jean is open. The app picks up changes
+//! the next time it reloads projects data.
+//!
+//! ```text
+//! jean-cli projects [--json]
+//! jean-cli worktrees [<project>] [--json]
+//! jean-cli status <worktree> [--json]
+//! jean-cli create <project> [--name <name>] [--base <branch>] [--no-setup]
+//! jean-cli archive <worktree>
+//! jean-cli delete <worktree> [--force]
+//! jean-cli prompt <worktree> <message> [--continue] [--model <model>]
+//! ```
+//!
+//! Projects are matched by ID or name, worktrees by ID (or ID prefix), name,
+//! branch or path. `prompt` runs the Claude CLI headlessly in the worktree
+//! and streams its output; the run isn't added to the app's session history.
+//! `archive` and `delete` share their data and git steps with the app's
+//! commands, but can't stop sessions running in the app.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::command_audit::AuditedCommand;
+use crate::projects::storage::{
+    get_project_worktrees_dir, load_projects_data_in, with_projects_data_mut_in,
+};
+use crate::projects::types::{Project, ProjectsData, SessionType, Worktree};
+use crate::projects::{git, git_queue, names};
+
+const USAGE: &str = "Usage:
+  jean-cli projects [--json]
+  jean-cli worktrees [<project>] [--json]
+  jean-cli status <worktree> [--json]
+  jean-cli create <project> [--name <name>] [--base <branch>] [--no-setup]
+  jean-cli archive <worktree>
+  jean-cli delete <worktree> [--force]
+  jean-cli prompt <worktree> <message> [--continue] [--model <model>]";
+
+/// Parsed command line: positional arguments, `--flag value` options and
+/// boolean switches
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    switches: Vec<String>,
+}
+
+/// Options that take a value; every other `--flag` is a switch
+const VALUE_OPTIONS: [&str; 3] = ["name", "base", "model"];
+
+impl Args {
+    fn parse(raw: &[String]) -> Result<Self, String> {
+        let mut args = Args::default();
+        let mut iter = raw.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) if VALUE_OPTIONS.contains(&flag) => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| format!("--{flag} needs a value"))?;
+                    args.options.push((flag.to_string(), value.clone()));
+                }
+                Some(flag) => args.switches.push(flag.to_string()),
+                None => args.positional.push(arg.clone()),
+            }
+        }
+        Ok(args)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|s| s == name)
+    }
+
+    fn positional(&self, index: usize, what: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing {what}\n\n{USAGE}"))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn find_project<'a>(data: &'a ProjectsData, query: &str) -> Result<&'a Project, String> {
+    data.projects
+        .iter()
+        .filter(|p| !p.is_folder)
+        .find(|p| p.id == query || p.name == query)
+        .ok_or_else(|| format!("Project not found: {query}"))
+}
+
+fn find_worktree<'a>(data: &'a ProjectsData, query: &str) -> Result<&'a Worktree, String> {
+    let mut matches = data
+        .worktrees
+        .iter()
+        .filter(|w| w.id == query || w.name == query || w.branch == query || w.path == query);
+    if let Some(worktree) = matches.next() {
+        if matches.next().is_some() {
+            return Err(format!("'{query}' matches several worktrees, use its ID"));
+        }
+        return Ok(worktree);
+    }
+    let mut prefixed = data.worktrees.iter().filter(|w| w.id.starts_with(query));
+    match (prefixed.next(), prefixed.next()) {
+        (Some(worktree), None) => Ok(worktree),
+        (Some(_), Some(_)) => Err(format!("Worktree ID prefix '{query}' is ambiguous")),
+        _ => Err(format!("Worktree not found: {query}")),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {e}"))?;
+    println!("{json}");
+    Ok(())
+}
+
+fn list_projects(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let projects: Vec<&Project> = data.projects.iter().filter(|p| !p.is_folder).collect();
+    if args.switch("json") {
+        return print_json(&projects);
+    }
+    for project in projects {
+        println!(
+            "{}\t{}\t{}",
+            &project.id[..8.min(project.id.len())],
+            project.name,
+            project.path
+        );
+    }
+    Ok(())
+}
+
+fn list_worktrees(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let project_id = match args.positional.get(1) {
+        Some(query) => Some(find_project(&data, query)?.id.clone()),
+        None => None,
+    };
+    let worktrees: Vec<&Worktree> = data
+        .worktrees
+        .iter()
+        .filter(|w| w.archived_at.is_none())
+        .filter(|w| match &project_id {
+            Some(id) => w.project_id == *id,
+            None => true,
+        })
+        .collect();
+    if args.switch("json") {
+        return print_json(&worktrees);
+    }
+    for worktree in worktrees {
+        println!(
+            "{}\t{}\t{}\t{}",
+            &worktree.id[..8.min(worktree.id.len())],
+            worktree.name,
+            worktree.branch,
+            worktree.path
+        );
+    }
+    Ok(())
+}
+
+/// Worktree status: cached remote state from the app plus live local changes
+#[derive(Serialize)]
+struct WorktreeStatus<'a> {
+    worktree: &'a Worktree,
+    project: Option<&'a str>,
+    changed_files: usize,
+}
+
+fn status(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let worktree = find_worktree(&data, args.positional(1, "worktree")?)?;
+
+    let output = git_queue::read_command()
+        .args(["status", "--porcelain"])
+        .current_dir(&worktree.path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git status: {e}"))?;
+    let changed_files = String::from_utf8_lossy(&output.stdout).lines().count();
+
+    let status = WorktreeStatus {
+        worktree,
+        project: data
+            .find_project(&worktree.project_id)
+            .map(|p| p.name.as_str()),
+        changed_files,
+    };
+    if args.switch("json") {
+        return print_json(&status);
+    }
+
+    println!("Worktree: {} ({})", worktree.name, worktree.id);
+    println!("Project:  {}", status.project.unwrap_or("?"));
+    println!("Branch:   {}", worktree.branch);
+    println!("Path:     {}", worktree.path);
+    println!("Changes:  {changed_files} file(s)");
+    if let (Some(ahead), Some(behind)) = (worktree.cached_ahead_count, worktree.cached_behind_count)
+    {
+        println!("Base:     {ahead} ahead, {behind} behind");
+    }
+    if let Some(pr_url) = &worktree.pr_url {
+        let pr_status = worktree.cached_pr_status.as_deref().unwrap_or("unknown");
+        println!("PR:       {pr_url} ({pr_status})");
+    }
+    if let Some(checks) = &worktree.cached_check_status {
+        println!("Checks:   {checks}");
+    }
+    Ok(())
+}
+
+fn create(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let project = find_project(&data, args.positional(1, "project")?)?.clone();
+
+    let preferred_base = args.option("base").unwrap_or(&project.default_branch);
+    let base = git::get_valid_base_branch(&project.path, preferred_base)?;
+    let name = match args.option("name") {
+        Some(name) if data.worktree_name_exists(&project.id, name) => {
+            return Err(format!("A worktree named {name} already exists"));
+        }
+        Some(name) => name.to_string(),
+        None => names::generate_unique_workspace_name(|n| {
+            data.worktree_name_exists(&project.id, n) || git::branch_exists(&project.path, n)
+        }),
+    };
+    if git::branch_exists(&project.path, &name) {
+        return Err(format!("Branch already exists: {name}"));
+    }
+
+    let worktree_path = get_project_worktrees_dir(&project.name)?.join(&name);
+    let worktree_path = worktree_path
+        .to_str()
+        .ok_or_else(|| "Invalid worktree path".to_string())?
+        .to_string();
+    if Path::new(&worktree_path).exists() {
+        return Err(format!("Directory already exists: {worktree_path}"));
+    }
+
+    eprintln!("Creating worktree {name} from {base}...");
+    git::create_worktree(&project.path, &worktree_path, &name, &base)?;
+
+    let mut setup_output = None;
+    let mut setup_script = None;
+    if !args.switch("no-setup") {
+        if let Some(script) = git::read_jean_config(&project.path).and_then(|c| c.scripts.setup) {
+            eprintln!("Running setup script...");
+            // A new worktree has no profile selected yet: "default" applies
+            let profile_env = crate::projects::env_profiles::profile_vars(
+                &git::read_jean_config(&worktree_path).unwrap_or_default(),
+                None,
+            );
+            match git::run_setup_script(&worktree_path, &project.path, &name, &script, &profile_env)
+            {
+                Ok(output) => {
+                    setup_output = Some(output);
+                    setup_script = Some(script);
+                }
+                Err(e) => {
+                    let _ = git::remove_worktree(&project.path, &worktree_path);
+                    let _ = git::delete_branch(&project.path, &name);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    let worktree = with_projects_data_mut_in(data_dir, |data| {
+        let max_order = data
+            .worktrees
+            .iter()
+            .filter(|w| w.project_id == project.id)
+            .map(|w| w.order)
+            .max()
+            .unwrap_or(0);
+        let worktree = Worktree {
+            id: Uuid::new_v4().to_string(),
+            project_id: project.id.clone(),
+            name: name.clone(),
+            path: worktree_path.clone(),
+            branch: name.clone(),
+            created_at: now(),
+            setup_output,
+            setup_script,
+            session_type: SessionType::Worktree,
+            pr_number: None,
+            pr_url: None,
+            cached_pr_status: None,
+            cached_check_status: None,
+            cached_behind_count: None,
+            cached_ahead_count: None,
+            cached_status_at: None,
+            cached_uncommitted_added: None,
+            cached_uncommitted_removed: None,
+            cached_branch_diff_added: None,
+            cached_branch_diff_removed: None,
+            cached_base_branch_ahead_count: None,
+            cached_base_branch_behind_count: None,
+            cached_worktree_ahead_count: None,
+            cached_unpushed_count: None,
+            order: max_order + 1,
+            archived_at: None,
+            reviewed_commit: None,
+            checklist_override: None,
+            stack_parent: None,
+            archived_stash: None,
+            env_profile: None,
+        };
+        data.add_worktree(worktree.clone());
+        Ok(worktree)
+    })?;
+
+    println!("{}\t{}", worktree.id, worktree.path);
+    Ok(())
+}
+
+fn archive(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let worktree = find_worktree(&data, args.positional(1, "worktree")?)?.clone();
+    crate::projects::check_archivable(&worktree)?;
+
+    let stash = crate::projects::stash_for_archive(&worktree);
+    let recorded = with_projects_data_mut_in(data_dir, |data| {
+        let stored = data
+            .find_worktree_mut(&worktree.id)
+            .ok_or_else(|| format!("Worktree not found: {}", worktree.id))?;
+        crate::projects::mark_archived(stored, stash.clone())
+    });
+    if let Err(e) = recorded {
+        crate::projects::unstash_failed_archive(&worktree, stash);
+        return Err(e);
+    }
+    eprintln!("Archived {}", worktree.name);
+    Ok(())
+}
+
+fn delete(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let worktree = find_worktree(&data, args.positional(1, "worktree")?)?.clone();
+    if worktree.session_type == SessionType::Base {
+        return Err("Base sessions can't be deleted".to_string());
+    }
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?
+        .clone();
+    if !args.switch("force") && git::has_uncommitted_changes(&worktree.path) {
+        return Err(format!(
+            "{} has uncommitted changes (use --force to delete anyway)",
+            worktree.name
+        ));
+    }
+
+    // Same order as the app: drop it from the data first, then remove the checkout
+    with_projects_data_mut_in(data_dir, |data| {
+        data.remove_worktree(&worktree.id);
+        Ok(())
+    })?;
+    eprintln!("Deleting worktree {}...", worktree.name);
+    crate::projects::remove_worktree_checkout(
+        &worktree.id,
+        &project.path,
+        &worktree.path,
+        &worktree.branch,
+    )?;
+    crate::chat::change_audit::delete_in(data_dir, &worktree.id);
+    eprintln!("Deleted {}", worktree.name);
+    Ok(())
+}
+
+/// The Claude CLI installed by Jean, or `claude` on PATH
+fn claude_binary(data_dir: &Path) -> PathBuf {
+    let embedded = data_dir
+        .join(crate::claude_cli::CLI_DIR_NAME)
+        .join(crate::claude_cli::CLI_BINARY_NAME);
+    if embedded.exists() {
+        embedded
+    } else {
+        PathBuf::from("claude")
+    }
+}
+
+fn prompt(data_dir: &Path, args: &Args) -> Result<(), String> {
+    let data = load_projects_data_in(data_dir)?;
+    let worktree = find_worktree(&data, args.positional(1, "worktree")?)?;
+    let message = args.positional(2, "message")?;
+
+    let mut cmd = std::process::Command::new(claude_binary(data_dir));
+    cmd.args(["--print", "--output-format", "text"]);
+    if args.switch("continue") {
+        cmd.arg("--continue");
+    }
+    if let Some(model) = args.option("model") {
+        cmd.args(["--model", model]);
+    }
+    let status = cmd
+        .arg(message)
+        .current_dir(&worktree.path)
+        .envs(crate::projects::dev_env::env_for(&worktree.path))
+        .status()
+        .map_err(|e| format!("Failed to run Claude CLI: {e}"))?;
+    if !status.success() {
+        return Err(format!("Claude CLI exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Run `jean-cli` with its arguments (without the program name); returns the
+/// process exit code
+pub fn run(raw_args: Vec<String>) -> i32 {
+    let result = Args::parse(&raw_args).and_then(|args| {
+        let command = args.positional.first().map(String::as_str);
+        if command.is_none() || args.switch("help") {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        let data_dir = crate::locations::init_headless()?;
+        if !data_dir.join(crate::db::DB_FILE_NAME).exists() {
+            return Err(format!(
+                "No Jean data found in {} (start the app once first)",
+                data_dir.display()
+            ));
+        }
+        match command {
+            Some("projects") => list_projects(&data_dir, &args),
+            Some("worktrees") => list_worktrees(&data_dir, &args),
+            Some("status") => status(&data_dir, &args),
+            Some("create") => create(&data_dir, &args),
+            Some("archive") => archive(&data_dir, &args),
+            Some("delete") => delete(&data_dir, &args),
+            Some("prompt") => prompt(&data_dir, &args),
+            Some(other) => Err(format!("Unknown command: {other}\n\n{USAGE}")),
+            None => Ok(()),
+        }
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("jean-cli: {e}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let raw: Vec<String> = ["create", "my-app", "--name", "fix-login", "--no-setup"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let args = Args::parse(&raw).unwrap();
+        assert_eq!(args.positional, vec!["create", "my-app"]);
+        assert_eq!(args.option("name"), Some("fix-login"));
+        assert!(args.switch("no-setup"));
+        assert!(!args.switch("json"));
+
+        assert!(Args::parse(&["create".to_string(), "--base".to_string()]).is_err());
+    }
+}
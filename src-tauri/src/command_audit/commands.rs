@@ -0,0 +1,33 @@
+//! Tauri commands for the command audit log
+
+use tauri::AppHandle;
+
+use super::{query_entries, AuditEntry};
+
+/// Default number of entries returned
+const DEFAULT_LIMIT: u32 = 200;
+
+/// Get the most recent external commands, newest first.
+///
+/// With `worktree_id`, only commands run inside that worktree are returned.
+#[tauri::command]
+pub async fn get_command_audit_log(
+    app: AppHandle,
+    worktree_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<AuditEntry>, String> {
+    let dir = match &worktree_id {
+        Some(id) => {
+            let data = crate::projects::storage::load_projects_data(&app)?;
+            let worktree = data
+                .find_worktree(id)
+                .ok_or_else(|| format!("Worktree not found: {id}"))?;
+            Some(worktree.path.clone())
+        }
+        None => None,
+    };
+
+    crate::db::with_db(&app, |conn| {
+        query_entries(conn, dir.as_deref(), limit.unwrap_or(DEFAULT_LIMIT))
+    })
+}
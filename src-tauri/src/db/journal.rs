@@ -0,0 +1,356 @@
+//! Write-ahead journal for operations that span several stores
+//!
+//! Operations like merging a worktree touch projects data, session indexes and
+//! context references separately. Each such operation records its steps in the
+//! `journal` table before touching anything:
+//!
+//! 1. `begin` writes the entry as **pending** (nothing applied yet)
+//! 2. `commit` marks it **committed** and applies the steps in order, recording
+//!    progress after each one, then removes the entry
+//!
+//! On startup, `recover` rolls back pending entries (the operation never reached
+//! its point of no return, so the stores were never touched) and rolls forward
+//! committed ones by re-applying their remaining steps. Steps are idempotent so
+//! re-applying a step that was interrupted half-way is safe.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::with_db;
+
+/// A single idempotent change to one store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalStep {
+    /// Remove a worktree record from projects data
+    RemoveWorktree { worktree_id: String },
+    /// Delete a worktree's session index (best effort: failures are logged)
+    DeleteSessionIndex { worktree_id: String },
+    /// Re-key a base session's index so it can be restored on reopen
+    PreserveBaseSessions {
+        worktree_id: String,
+        project_id: String,
+    },
+    /// Drop the worktree's loaded issue/PR context references
+    RemoveContextReferences { worktree_id: String },
+    /// Remove the git worktree and its branch (failures are logged, not fatal)
+    RemoveGitWorktree {
+        project_path: String,
+        worktree_path: String,
+        branch: String,
+    },
+}
+
+/// Journal entry state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalState {
+    /// Steps recorded, operation not yet past its point of no return
+    Pending,
+    /// Operation decided; steps must be applied to completion
+    Committed,
+}
+
+/// A journaled multi-store operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    /// Operation name, for logging (e.g. "merge_worktree_to_base")
+    pub operation: String,
+    pub state: JournalState,
+    pub steps: Vec<JournalStep>,
+    /// Number of steps already applied
+    pub completed_steps: usize,
+    pub created_at: u64,
+}
+
+/// What startup recovery did with an unfinished entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    RolledBack,
+    RolledForward,
+}
+
+/// Outcome of recovering a single journal entry
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredOperation {
+    pub id: String,
+    pub operation: String,
+    pub action: RecoveryAction,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ============================================================================
+// Table access
+// ============================================================================
+
+fn write_entry(conn: &Connection, entry: &JournalEntry) -> Result<(), String> {
+    let json =
+        serde_json::to_string(entry).map_err(|e| format!("Failed to serialize journal: {e}"))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO journal (id, created_at, data) VALUES (?1, ?2, ?3)",
+        params![entry.id, entry.created_at as i64, json],
+    )
+    .map_err(|e| format!("Failed to write journal entry {}: {e}", entry.id))?;
+    Ok(())
+}
+
+fn delete_entry(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM journal WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete journal entry {id}: {e}"))?;
+    Ok(())
+}
+
+fn read_entries(conn: &Connection) -> Result<Vec<JournalEntry>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, data FROM journal ORDER BY created_at")
+        .map_err(|e| format!("Failed to read journal: {e}"))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| format!("Failed to read journal: {e}"))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let (id, data) = row.map_err(|e| format!("Failed to read journal row: {e}"))?;
+        match serde_json::from_str(&data) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                log::warn!("Dropping unreadable journal entry {id}: {e}");
+                delete_entry(conn, &id)?;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// ============================================================================
+// Step application
+// ============================================================================
+
+fn apply_step(app: &AppHandle, step: &JournalStep) -> Result<(), String> {
+    match step {
+        JournalStep::RemoveWorktree { worktree_id } => {
+            crate::projects::storage::with_projects_data_mut(app, |data| {
+                data.remove_worktree(worktree_id);
+                Ok(())
+            })
+        }
+        JournalStep::DeleteSessionIndex { worktree_id } => {
+            // A leftover index is harmless; failing here would leave the
+            // worktree half-closed while its sessions are already gone
+            if let Err(e) = crate::chat::storage::delete_index(app, worktree_id) {
+                log::warn!("Failed to delete session index of {worktree_id}: {e}");
+            }
+            Ok(())
+        }
+        JournalStep::PreserveBaseSessions {
+            worktree_id,
+            project_id,
+        } => crate::chat::preserve_base_sessions(app, worktree_id, project_id),
+        JournalStep::RemoveContextReferences { worktree_id } => {
+            crate::projects::github_issues::cleanup_issue_contexts_for_worktree(app, worktree_id)
+        }
+        JournalStep::RemoveGitWorktree {
+            project_path,
+            worktree_path,
+            branch,
+        } => {
+            // Ignore errors: the worktree/branch may already be gone
+            if let Err(e) = crate::projects::git::remove_worktree(project_path, worktree_path) {
+                log::warn!("Failed to remove worktree (may already be deleted): {e}");
+            }
+            if let Err(e) = crate::projects::git::delete_branch(project_path, branch) {
+                log::warn!("Failed to delete branch (may already be deleted): {e}");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Apply the remaining steps of a committed entry, then remove it
+fn apply_remaining(app: &AppHandle, entry: &mut JournalEntry) -> Result<(), String> {
+    while entry.completed_steps < entry.steps.len() {
+        let step = &entry.steps[entry.completed_steps];
+        apply_step(app, step).map_err(|e| {
+            format!(
+                "Journal {} ({}) failed at step {}: {e}",
+                entry.id, entry.operation, entry.completed_steps
+            )
+        })?;
+        entry.completed_steps += 1;
+        with_db(app, |conn| write_entry(conn, entry))?;
+    }
+
+    with_db(app, |conn| delete_entry(conn, &entry.id))
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Record an operation's steps without applying them
+pub fn begin(
+    app: &AppHandle,
+    operation: &str,
+    steps: Vec<JournalStep>,
+) -> Result<JournalEntry, String> {
+    let entry = JournalEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        operation: operation.to_string(),
+        state: JournalState::Pending,
+        steps,
+        completed_steps: 0,
+        created_at: now(),
+    };
+    with_db(app, |conn| write_entry(conn, &entry))?;
+    log::trace!("Journal {} begun for {operation}", entry.id);
+    Ok(entry)
+}
+
+/// Mark an operation as decided and apply all of its steps
+pub fn commit(app: &AppHandle, mut entry: JournalEntry) -> Result<(), String> {
+    entry.state = JournalState::Committed;
+    with_db(app, |conn| write_entry(conn, &entry))?;
+    apply_remaining(app, &mut entry)?;
+    log::trace!("Journal {} committed for {}", entry.id, entry.operation);
+    Ok(())
+}
+
+/// Discard a pending operation (nothing was applied)
+pub fn abort(app: &AppHandle, entry: JournalEntry) -> Result<(), String> {
+    with_db(app, |conn| delete_entry(conn, &entry.id))?;
+    log::trace!("Journal {} aborted for {}", entry.id, entry.operation);
+    Ok(())
+}
+
+/// Journal and apply an operation that has no separate point of no return
+pub fn run(app: &AppHandle, operation: &str, steps: Vec<JournalStep>) -> Result<(), String> {
+    let entry = begin(app, operation, steps)?;
+    commit(app, entry)
+}
+
+/// Decide what recovery should do with an unfinished entry
+pub fn recovery_action(entry: &JournalEntry) -> RecoveryAction {
+    match entry.state {
+        JournalState::Pending => RecoveryAction::RolledBack,
+        JournalState::Committed => RecoveryAction::RolledForward,
+    }
+}
+
+/// Finish or discard operations interrupted by a crash. Called on startup.
+pub fn recover(app: &AppHandle) -> Result<Vec<RecoveredOperation>, String> {
+    let entries = with_db(app, |conn| read_entries(conn))?;
+    let mut recovered = Vec::new();
+
+    for mut entry in entries {
+        let action = recovery_action(&entry);
+        match action {
+            RecoveryAction::RolledBack => {
+                with_db(app, |conn| delete_entry(conn, &entry.id))?;
+            }
+            RecoveryAction::RolledForward => {
+                // Leave the entry in place on failure so the next startup retries
+                if let Err(e) = apply_remaining(app, &mut entry) {
+                    log::error!("{e}");
+                    continue;
+                }
+            }
+        }
+
+        log::info!(
+            "Recovered interrupted {} ({:?}, {}/{} steps were applied)",
+            entry.operation,
+            action,
+            entry.completed_steps,
+            entry.steps.len()
+        );
+        recovered.push(RecoveredOperation {
+            id: entry.id,
+            operation: entry.operation,
+            action,
+        });
+    }
+
+    Ok(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_connection;
+
+    fn entry(state: JournalState) -> JournalEntry {
+        JournalEntry {
+            id: "j1".to_string(),
+            operation: "merge_worktree_to_base".to_string(),
+            state,
+            steps: vec![
+                JournalStep::RemoveContextReferences {
+                    worktree_id: "wt-1".to_string(),
+                },
+                JournalStep::RemoveWorktree {
+                    worktree_id: "wt-1".to_string(),
+                },
+            ],
+            completed_steps: 1,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_entries_roundtrip_through_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_connection(&conn).unwrap();
+
+        write_entry(&conn, &entry(JournalState::Committed)).unwrap();
+        let entries = read_entries(&conn).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].state, JournalState::Committed);
+        assert_eq!(entries[0].completed_steps, 1);
+        assert_eq!(entries[0].steps, entry(JournalState::Committed).steps);
+
+        delete_entry(&conn, "j1").unwrap();
+        assert!(read_entries(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_entries_are_dropped() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_connection(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO journal (id, created_at, data) VALUES ('bad', 0, 'not json')",
+            [],
+        )
+        .unwrap();
+
+        assert!(read_entries(&conn).unwrap().is_empty());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM journal", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_recovery_action() {
+        assert_eq!(
+            recovery_action(&entry(JournalState::Pending)),
+            RecoveryAction::RolledBack
+        );
+        assert_eq!(
+            recovery_action(&entry(JournalState::Committed)),
+            RecoveryAction::RolledForward
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! One-time import of legacy JSON storage into SQLite
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use super::{get_meta, set_meta};
+use crate::chat::types::{SessionMetadata, WorktreeIndex};
+use crate::projects::types::ProjectsData;
+
+/// Meta key marking that legacy JSON files were imported
+const LEGACY_MIGRATED_KEY: &str = "legacy_json_migrated";
+
+/// Suffix appended to legacy files/directories after a successful import
+const MIGRATED_SUFFIX: &str = "migrated";
+
+/// Import projects.json and session JSON files into the database.
+///
+/// Runs inside a single transaction and only once per database. Legacy files
+/// are renamed (not deleted) afterwards so they can be inspected or restored.
+pub fn migrate_legacy_json(conn: &mut Connection, app_data_dir: &Path) -> Result<(), String> {
+    if get_meta(conn, LEGACY_MIGRATED_KEY)?.is_some() {
+        return Ok(());
+    }
+
+    let projects_path = app_data_dir.join("projects.json");
+    let index_dir = app_data_dir.join("sessions").join("index");
+    let data_dir = app_data_dir.join("sessions").join("data");
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start migration transaction: {e}"))?;
+
+    let mut imported_projects = 0;
+    let mut imported_indexes = 0;
+    let mut metadata_files = Vec::new();
+
+    if projects_path.exists() {
+        let contents = fs::read_to_string(&projects_path)
+            .map_err(|e| format!("Failed to read legacy projects file: {e}"))?;
+        let data: ProjectsData = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse legacy projects file: {e}"))?;
+
+        for (position, project) in data.projects.iter().enumerate() {
+            let json = serde_json::to_string(project)
+                .map_err(|e| format!("Failed to serialize project: {e}"))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO projects (id, position, data) VALUES (?1, ?2, ?3)",
+                params![project.id, position as i64, json],
+            )
+            .map_err(|e| format!("Failed to import project {}: {e}", project.id))?;
+        }
+        for (position, worktree) in data.worktrees.iter().enumerate() {
+            let json = serde_json::to_string(worktree)
+                .map_err(|e| format!("Failed to serialize worktree: {e}"))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO worktrees (id, project_id, position, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![worktree.id, worktree.project_id, position as i64, json],
+            )
+            .map_err(|e| format!("Failed to import worktree {}: {e}", worktree.id))?;
+        }
+        imported_projects = data.projects.len();
+    }
+
+    if let Ok(entries) = fs::read_dir(&index_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let index: WorktreeIndex = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+            {
+                Ok(index) => index,
+                Err(e) => {
+                    log::warn!("Skipping unreadable session index {path:?}: {e}");
+                    continue;
+                }
+            };
+
+            // Preserved base-session indexes keep their file name as key
+            let key = if stem.starts_with("base-") {
+                stem.to_string()
+            } else {
+                index.worktree_id.clone()
+            };
+            let json = serde_json::to_string(&index)
+                .map_err(|e| format!("Failed to serialize session index: {e}"))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO session_indexes (key, data) VALUES (?1, ?2)",
+                params![key, json],
+            )
+            .map_err(|e| format!("Failed to import session index {key}: {e}"))?;
+            imported_indexes += 1;
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(&data_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path().join("metadata.json");
+            if !path.exists() {
+                continue;
+            }
+
+            let metadata: SessionMetadata = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+            {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    log::warn!("Skipping unreadable session metadata {path:?}: {e}");
+                    continue;
+                }
+            };
+
+            let json = serde_json::to_string(&metadata)
+                .map_err(|e| format!("Failed to serialize session metadata: {e}"))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO session_metadata (session_id, worktree_id, data)
+                 VALUES (?1, ?2, ?3)",
+                params![metadata.id, metadata.worktree_id, json],
+            )
+            .map_err(|e| format!("Failed to import session metadata {}: {e}", metadata.id))?;
+            metadata_files.push(path);
+        }
+    }
+
+    set_meta(&tx, LEGACY_MIGRATED_KEY, "1")?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit migration: {e}"))?;
+
+    log::info!(
+        "Imported legacy storage: {imported_projects} projects, {imported_indexes} session indexes, {} session metadata files",
+        metadata_files.len()
+    );
+
+    // Keep legacy files around (renamed) so nothing is lost if the import needs inspecting
+    if projects_path.exists() {
+        rename_migrated(&projects_path);
+    }
+    if index_dir.exists() {
+        rename_migrated(&index_dir);
+    }
+    for path in metadata_files {
+        rename_migrated(&path);
+    }
+
+    Ok(())
+}
+
+fn rename_migrated(path: &Path) {
+    let mut target = path.as_os_str().to_owned();
+    target.push(format!(".{MIGRATED_SUFFIX}"));
+    if let Err(e) = fs::rename(path, &target) {
+        log::warn!("Failed to rename migrated legacy file {path:?}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_connection;
+
+    #[test]
+    fn test_migrates_legacy_projects_and_sessions_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        fs::write(
+            root.join("projects.json"),
+            r#"{"projects":[{"id":"p1","name":"Proj","path":"/tmp/proj","default_branch":"main","added_at":0}],"worktrees":[]}"#,
+        )
+        .unwrap();
+
+        let index_dir = root.join("sessions").join("index");
+        fs::create_dir_all(&index_dir).unwrap();
+        let index = WorktreeIndex::new("wt-1".to_string());
+        fs::write(
+            index_dir.join("wt-1.json"),
+            serde_json::to_string(&index).unwrap(),
+        )
+        .unwrap();
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_connection(&conn).unwrap();
+        migrate_legacy_json(&mut conn, root).unwrap();
+
+        let project_count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM projects", [], |r| r.get(0))
+            .unwrap();
+        let index_key: String = conn
+            .query_row("SELECT key FROM session_indexes", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(project_count, 1);
+        assert_eq!(index_key, "wt-1");
+
+        // Legacy files are renamed, not deleted
+        assert!(!root.join("projects.json").exists());
+        assert!(root.join("projects.json.migrated").exists());
+        assert!(root.join("sessions").join("index.migrated").exists());
+
+        // A second run is a no-op
+        fs::write(root.join("projects.json"), "not json").unwrap();
+        migrate_legacy_json(&mut conn, root).unwrap();
+    }
+}
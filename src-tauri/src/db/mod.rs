@@ -0,0 +1,330 @@
+//! SQLite-backed persistent storage
+//!
+//! Projects, worktrees, session indexes and session metadata are stored in a
+//! single `jean.db` database in the app data directory. Each record is kept as
+//! a JSON document in its own row, so updating one worktree's cached status no
+//! longer rewrites every project, and concurrent writers are serialized by
+//! SQLite transactions instead of racing on temp-file renames.
+//!
+//! Legacy JSON files (projects.json, sessions/index/*.json and
+//! sessions/data/*/metadata.json) are imported once on first open.
+//!
+//! SQLite serializes individual transactions, but read-modify-write cycles that
+//! span several calls (load, edit, save) are guarded by advisory lock files in
+//! `locks/` (see [`lock_exclusive`]) so that a second app instance or the CLI
+//! companion can't clobber them.
+//!
+//! Operations spanning several stores are recorded in the `journal` table
+//! (see [`journal`]) so they can be recovered after a crash.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+use crate::platform::FileLock;
+
+pub mod journal;
+mod migrate;
+
+/// Database file name in the app data directory
+pub const DB_FILE_NAME: &str = "jean.db";
+
+/// Current schema version (stored in the `meta` table)
+const SCHEMA_VERSION: u32 = 4;
+
+/// Schema for all tables. Records are stored as JSON documents; the extra
+/// columns exist for ordering and lookups.
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS projects (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS worktrees (
+    id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_worktrees_project ON worktrees(project_id);
+CREATE TABLE IF NOT EXISTS session_indexes (
+    key TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS session_metadata (
+    session_id TEXT PRIMARY KEY,
+    worktree_id TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_session_metadata_worktree ON session_metadata(worktree_id);
+CREATE TABLE IF NOT EXISTS journal (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS trashed_worktrees (
+    worktree_id TEXT PRIMARY KEY,
+    trashed_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS command_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at INTEGER NOT NULL,
+    cwd TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_command_audit_cwd ON command_audit(cwd);
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS job_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_job_runs_job ON job_runs(job_id, started_at);
+CREATE TABLE IF NOT EXISTS time_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worktree_id TEXT NOT NULL,
+    session_id TEXT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_time_entries_started ON time_entries(started_at);
+CREATE TABLE IF NOT EXISTS paste_history (
+    id TEXT PRIMARY KEY,
+    worktree_id TEXT NOT NULL,
+    last_used_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_paste_history_worktree ON paste_history(worktree_id, last_used_at);
+CREATE TABLE IF NOT EXISTS test_runs (
+    worktree_id TEXT PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS search_sessions (
+    session_id TEXT PRIMARY KEY,
+    worktree_id TEXT NOT NULL,
+    indexed_messages INTEGER NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS search_messages USING fts5(
+    content,
+    session_id UNINDEXED,
+    message_id UNINDEXED,
+    message_index UNINDEXED,
+    role UNINDEXED,
+    timestamp UNINDEXED,
+    tokenize = 'porter unicode61'
+);
+"#;
+
+/// Cross-instance lock for projects and worktrees
+pub const PROJECTS_LOCK: &str = "projects";
+
+/// Cross-instance lock for session indexes
+pub const SESSION_INDEX_LOCK: &str = "session-index";
+
+/// Cross-instance lock for session metadata
+pub const SESSION_METADATA_LOCK: &str = "session-metadata";
+
+/// Open database connection, shared by all threads.
+/// The mutex serializes access; SQLite transactions provide atomicity.
+static DB: Lazy<Mutex<Option<(PathBuf, Connection)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Get the path to the database file
+pub fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = crate::locations::app_data_dir(app)?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {e}"))?;
+
+    Ok(app_data_dir.join(DB_FILE_NAME))
+}
+
+/// Get the path of a named lock file
+fn get_lock_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let db_path = get_db_path(app)?;
+    let app_data_dir = db_path
+        .parent()
+        .ok_or_else(|| "Invalid database path".to_string())?;
+    Ok(lock_path_in(app_data_dir, name))
+}
+
+/// Path of a named lock file in an app data directory
+fn lock_path_in(app_data_dir: &Path, name: &str) -> PathBuf {
+    app_data_dir.join("locks").join(format!("{name}.lock"))
+}
+
+/// Take a named cross-instance lock for a read-modify-write cycle.
+///
+/// Lock order is projects, then session index, then session metadata; never
+/// take a lock while already holding one later in that order.
+pub fn lock_exclusive(app: &AppHandle, name: &str) -> Result<FileLock, String> {
+    FileLock::exclusive(&get_lock_path(app, name)?)
+}
+
+/// [`lock_exclusive`] for callers without an app handle (the CLI companion)
+pub fn lock_exclusive_in(app_data_dir: &Path, name: &str) -> Result<FileLock, String> {
+    FileLock::exclusive(&lock_path_in(app_data_dir, name))
+}
+
+/// Open a connection and make sure the schema exists
+pub fn open_connection(path: &Path) -> Result<Connection, String> {
+    let conn =
+        Connection::open(path).map_err(|e| format!("Failed to open database {path:?}: {e}"))?;
+    init_connection(&conn)?;
+    Ok(conn)
+}
+
+/// Apply pragmas and create the schema on a fresh connection
+pub fn init_connection(conn: &Connection) -> Result<(), String> {
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| format!("Failed to set database busy timeout: {e}"))?;
+    // WAL lets readers proceed while a writer holds the lock
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to enable WAL mode: {e}"))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create database schema: {e}"))?;
+    set_meta(conn, "schema_version", &SCHEMA_VERSION.to_string())?;
+    Ok(())
+}
+
+/// Read a value from the `meta` table
+pub fn get_meta(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    use rusqlite::OptionalExtension;
+
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map_err(|e| format!("Failed to read meta '{key}': {e}"))
+}
+
+/// Write a value to the `meta` table
+pub fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )
+    .map_err(|e| format!("Failed to write meta '{key}': {e}"))?;
+    Ok(())
+}
+
+/// Run a closure with the shared database connection.
+///
+/// Opens the database (and imports legacy JSON data) on first use.
+///
+/// The connection is guarded by a global mutex that isn't reentrant: `f` must
+/// not call `with_db` again, directly or through a storage function that
+/// takes an `AppHandle` (e.g. `load_projects_data`), or the thread would
+/// deadlock. Such a nested call fails with an error instead. Work that needs
+/// several stores in one closure composes the helpers that take a
+/// `&Connection`.
+pub fn with_db<F, T>(app: &AppHandle, f: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, String>,
+{
+    with_db_at(get_db_path(app)?, f)
+}
+
+/// [`with_db`] for the database at a known path (the CLI companion)
+pub fn with_db_at<F, T>(path: PathBuf, f: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, String>,
+{
+    let _entered = DbReentryGuard::enter()?;
+    let mut guard = DB.lock().unwrap();
+
+    let needs_open = match guard.as_ref() {
+        Some((open_path, _)) => open_path != &path,
+        None => true,
+    };
+
+    if needs_open {
+        let mut conn = open_connection(&path)?;
+        let app_data_dir = path
+            .parent()
+            .ok_or_else(|| "Invalid database path".to_string())?;
+        migrate::migrate_legacy_json(&mut conn, app_data_dir)?;
+        *guard = Some((path, conn));
+    }
+
+    let (_, conn) = guard.as_ref().expect("database connection initialized");
+    f(conn)
+}
+
+thread_local! {
+    /// Whether this thread is inside a `with_db` closure
+    static IN_DB: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Marks the current thread as holding the database mutex, so a nested
+/// `with_db` call fails instead of deadlocking
+struct DbReentryGuard;
+
+impl DbReentryGuard {
+    fn enter() -> Result<Self, String> {
+        if IN_DB.with(|in_db| in_db.replace(true)) {
+            log::error!("Nested with_db call");
+            return Err("Database accessed from inside a database operation".to_string());
+        }
+        Ok(DbReentryGuard)
+    }
+}
+
+impl Drop for DbReentryGuard {
+    fn drop(&mut self) {
+        IN_DB.with(|in_db| in_db.set(false));
+    }
+}
+
+/// Write a consistent copy of the database to `dest` (used for backups)
+pub fn snapshot_to(app: &AppHandle, dest: &Path) -> Result<(), String> {
+    let dest = dest.to_string_lossy().to_string();
+    with_db(app, |conn| {
+        conn.execute("VACUUM INTO ?1", [&dest])
+            .map_err(|e| format!("Failed to snapshot database: {e}"))?;
+        Ok(())
+    })
+}
+
+/// Check that a database file is readable and passes SQLite's integrity check
+pub fn check_integrity(path: &Path) -> Result<(), String> {
+    let conn =
+        Connection::open(path).map_err(|e| format!("Failed to open database {path:?}: {e}"))?;
+    let result: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to check database integrity: {e}"))?;
+
+    if result != "ok" {
+        return Err(format!("Database integrity check failed: {result}"));
+    }
+    Ok(())
+}
+
+/// Close the shared connection and run `f` while no connection is open, so
+/// the database file can be safely replaced. The next `with_db` call reopens it.
+pub fn with_db_closed<F, T>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String>,
+{
+    let mut guard = DB.lock().unwrap();
+    if let Some((_, conn)) = guard.take() {
+        conn.close()
+            .map_err(|(_, e)| format!("Failed to close database: {e}"))?;
+    }
+    f()
+}
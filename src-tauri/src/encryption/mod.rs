@@ -0,0 +1,301 @@
+//! Optional encryption at rest
+//!
+//! When the `encrypt_at_rest` preference is on, session transcripts (finished
+//! run logs), pasted texts, saved session contexts and GitHub context files
+//! are sealed with XChaCha20-Poly1305 before they are written to disk. The key
+//! is generated on first use and kept in the OS keychain, never in the app
+//! data directory.
+//!
+//! Sealed files start with a magic header, so readers decrypt transparently
+//! and plaintext files written before encryption was enabled keep working.
+//!
+//! Files the Claude CLI reads directly (the running run's log, its input file
+//! and the combined context file) stay plaintext while the run needs them;
+//! pasted texts are inlined into the CLI input instead of read from disk. The
+//! combined context file can't be sealed at all, so it's deleted when the run
+//! ends.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use once_cell::sync::Lazy;
+
+/// Header identifying a sealed file (followed by the nonce and ciphertext)
+const MAGIC: &[u8; 8] = b"JEANENC1";
+
+const NONCE_LEN: usize = 24;
+
+/// Keychain entry holding the base64-encoded key
+const KEYCHAIN_SERVICE: &str = "com.jean.desktop";
+const KEYCHAIN_ACCOUNT: &str = "storage-encryption-key";
+
+/// Whether new files are sealed (mirrors the `encrypt_at_rest` preference)
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Key loaded from the keychain, cached for the lifetime of the process
+static KEY: Lazy<Mutex<Option<Key>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Update whether new files are sealed (called when preferences load or change)
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Failed to access the OS keychain: {e}"))
+}
+
+/// Load the key from the keychain, generating and storing one if `create` is
+/// set and none exists yet
+fn load_key(create: bool) -> Result<Key, String> {
+    let mut cached = KEY.lock().unwrap();
+    if let Some(key) = cached.as_ref() {
+        return Ok(*key);
+    }
+
+    let entry = keychain_entry()?;
+    let key = match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| format!("Failed to decode encryption key: {e}"))?;
+            if bytes.len() != 32 {
+                return Err("Encryption key in the OS keychain is invalid".to_string());
+            }
+            *Key::from_slice(&bytes)
+        }
+        Err(keyring::Error::NoEntry) if create => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            entry
+                .set_password(&STANDARD.encode(key))
+                .map_err(|e| format!("Failed to store encryption key in the OS keychain: {e}"))?;
+            log::info!("Generated storage encryption key");
+            key
+        }
+        Err(keyring::Error::NoEntry) => {
+            return Err("Encryption key not found in the OS keychain".to_string())
+        }
+        Err(e) => return Err(format!("Failed to read encryption key: {e}")),
+    };
+
+    *cached = Some(key);
+    Ok(key)
+}
+
+/// Make sure a key exists, so enabling encryption fails up front when the
+/// keychain is unavailable
+pub fn ensure_key() -> Result<(), String> {
+    load_key(true).map(|_| ())
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+fn seal_with(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| format!("Failed to encrypt data: {e}"))?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open_with(key: &Key, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if !is_sealed(&bytes) {
+        return Ok(bytes);
+    }
+    let body = &bytes[MAGIC.len()..];
+    if body.len() < NONCE_LEN {
+        return Err("Failed to decrypt data: file is truncated".to_string());
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt data: wrong key or corrupted file".to_string())
+}
+
+/// Encrypt `plaintext` into the sealed file format
+pub fn seal(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    seal_with(&load_key(true)?, plaintext)
+}
+
+/// Decrypt sealed data; plaintext data is returned unchanged
+pub fn open(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if !is_sealed(&bytes) {
+        return Ok(bytes);
+    }
+    open_with(&load_key(false)?, bytes)
+}
+
+/// Write a file, sealing it when encryption is enabled
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    let contents = contents.as_ref();
+    let result = if is_enabled() {
+        std::fs::write(path, seal(contents)?)
+    } else {
+        std::fs::write(path, contents)
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Read a file, decrypting it if sealed
+pub fn read(path: &Path) -> Result<Vec<u8>, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    open(bytes)
+}
+
+/// Read a UTF-8 file, decrypting it if sealed
+pub fn read_to_string(path: &Path) -> Result<String, String> {
+    String::from_utf8(read(path)?).map_err(|e| format!("File is not valid UTF-8: {e}"))
+}
+
+/// Rewrite `path` sealed (`seal == true`) or in plaintext, atomically.
+/// Returns whether the file changed.
+pub fn convert_file(path: &Path, seal_file: bool) -> Result<bool, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if is_sealed(&bytes) == seal_file {
+        return Ok(false);
+    }
+    let converted = if seal_file {
+        seal(&bytes)?
+    } else {
+        open(bytes)?
+    };
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".enc.tmp");
+    let temp = std::path::PathBuf::from(temp);
+    std::fs::write(&temp, converted)
+        .map_err(|e| format!("Failed to write {}: {e}", temp.display()))?;
+    std::fs::rename(&temp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        format!("Failed to replace {}: {e}", path.display())
+    })?;
+    Ok(true)
+}
+
+/// Seal a finished file if encryption is enabled (failures are logged, since
+/// the plaintext copy is still usable)
+pub fn seal_if_enabled(path: &Path) {
+    if !is_enabled() || !path.exists() {
+        return;
+    }
+    if let Err(e) = convert_file(path, true) {
+        log::warn!("Failed to encrypt {}: {e}", path.display());
+    }
+}
+
+/// Seal or decrypt every existing transcript, pasted text, saved context and
+/// GitHub context file, after the preference changes. Logs of running runs are skipped; they
+/// are sealed when the run finishes. Returns the number of files converted.
+pub fn convert_existing(app: &tauri::AppHandle, seal_files: bool) -> Result<usize, String> {
+    let mut paths = Vec::new();
+
+    let app_data_dir = crate::locations::app_data_dir(app)?;
+    for dir in ["pasted-texts", "git-context", "session-context"] {
+        let Ok(entries) = std::fs::read_dir(app_data_dir.join(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            // The references index and saved context names are bookkeeping,
+            // not context content
+            if path.is_file()
+                && name != "references.json"
+                && name != "session-context-metadata.json"
+                && !name.ends_with(".tmp")
+            {
+                paths.push(path);
+            }
+        }
+    }
+
+    let mut running_sessions = Vec::new();
+    for session_id in crate::chat::storage::list_all_session_ids(app)? {
+        let Some(metadata) = crate::chat::storage::load_metadata(app, &session_id)? else {
+            continue;
+        };
+        if metadata
+            .runs
+            .iter()
+            .any(|run| run.status == crate::chat::types::RunStatus::Running)
+        {
+            running_sessions.push(session_id.clone());
+        }
+        for run in &metadata.runs {
+            if run.status == crate::chat::types::RunStatus::Running {
+                continue;
+            }
+            let path = crate::chat::run_log::get_run_log_path(app, &session_id, &run.run_id)?;
+            let compressed = crate::chat::run_log::compressed_path(&path);
+            paths.extend([path, compressed].into_iter().filter(|p| p.exists()));
+        }
+    }
+
+    // Combined context files are plaintext copies of contexts the CLI reads
+    // itself, so instead of sealing them, those left over by finished runs
+    // are deleted
+    if seal_files {
+        if let Ok(entries) = std::fs::read_dir(app_data_dir.join("combined-contexts")) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if running_sessions.iter().any(|id| name.contains(id.as_str())) {
+                    continue;
+                }
+                if let Err(e) = std::fs::remove_file(entry.path()) {
+                    log::warn!("Failed to delete combined context {name}: {e}");
+                }
+            }
+        }
+    }
+
+    let mut converted = 0;
+    for path in paths {
+        match convert_file(&path, seal_files) {
+            Ok(true) => converted += 1,
+            Ok(false) => {}
+            Err(e) => log::warn!("Skipping {}: {e}", path.display()),
+        }
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_round_trip() {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = seal_with(&key, b"secret transcript").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed
+            .windows(b"secret".len())
+            .any(|w| w == b"secret".as_slice()));
+        assert_eq!(
+            open_with(&key, sealed.clone()).unwrap(),
+            b"secret transcript"
+        );
+
+        // Plaintext passes through, and the wrong key fails
+        assert_eq!(open_with(&key, b"plain".to_vec()).unwrap(), b"plain");
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(open_with(&other, sealed).is_err());
+    }
+}
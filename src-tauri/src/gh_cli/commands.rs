@@ -1,5 +1,6 @@
 //! Tauri commands for GitHub CLI management
 
+use crate::command_audit::AuditedCommand;
 use crate::platform::silent_command;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
@@ -79,7 +80,10 @@ pub async fn check_gh_cli_installed(app: AppHandle) -> Result<GhCliStatus, Strin
 
     // Try to get the version by running gh --version
     // Use the binary directly - shell wrapper causes PowerShell parsing issues on Windows
-    let version = match silent_command(&binary_path).arg("--version").output() {
+    let version = match silent_command(&binary_path)
+        .arg("--version")
+        .output_audited()
+    {
         Ok(output) => {
             if output.status.success() {
                 let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -213,7 +217,7 @@ pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(
         ));
     }
 
-    let cli_dir = ensure_gh_cli_dir(&app)?;
+    ensure_gh_cli_dir(&app)?;
     let binary_path = get_gh_cli_binary_path(&app)?;
 
     // Emit progress: starting
@@ -268,8 +272,9 @@ pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(
     // Emit progress: extracting
     emit_progress(&app, "extracting", "Extracting archive...", 40);
 
-    // Create temp directory for extraction
-    let temp_dir = cli_dir.join("temp");
+    // Create temp directory for extraction (in the cache directory, so an
+    // interrupted install doesn't leave files in app data)
+    let temp_dir = crate::locations::app_cache_dir(&app)?.join("gh-cli-extract");
     std::fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp directory: {e}"))?;
 
@@ -310,7 +315,7 @@ pub async fn install_gh_cli(app: AppHandle, version: Option<String>) -> Result<(
     log::trace!("Verifying binary at {:?}", binary_path);
     let version_output = silent_command(&binary_path)
         .arg("--version")
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to verify GitHub CLI: {e}"))?;
 
     if !version_output.status.success() {
@@ -511,7 +516,7 @@ pub async fn check_gh_cli_auth(app: AppHandle) -> Result<GhAuthStatus, String> {
 
     let output = silent_command(&binary_path)
         .args(["auth", "status"])
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to execute GitHub CLI: {e}"))?;
 
     // gh auth status returns exit code 0 if authenticated, non-zero otherwise
@@ -1,7 +1,7 @@
 //! Configuration and path management for the embedded GitHub CLI
 
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 /// Directory name for storing the GitHub CLI binary
 pub const GH_CLI_DIR_NAME: &str = "gh-cli";
@@ -19,10 +19,7 @@ pub const GH_CLI_BINARY_NAME: &str = "gh.exe";
 ///          `~/.local/share/jean/gh-cli/` (Linux)
 ///          `%APPDATA%/jean/gh-cli/` (Windows)
 pub fn get_gh_cli_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
     Ok(app_data_dir.join(GH_CLI_DIR_NAME))
 }
 
@@ -0,0 +1,273 @@
+//! Local automation API
+//!
+//! A small REST API for launchers and scripts (Raycast, Alfred, shell
+//! scripts), separate from the web-access server: it only ever binds to
+//! 127.0.0.1, is off unless `automation_api_enabled` is set, and every request
+//! needs `Authorization: Bearer <automation_api_token>`.
+//!
+//! - `GET  /v1/status` — projects and active worktrees with cached git/PR status
+//! - `GET  /v1/worktrees/{id}` — one worktree
+//! - `POST /v1/worktrees` — create a worktree (`projectId`, optional
+//!   `baseBranch`, `customName`); same arguments as the `create_worktree` command
+//! - `POST /v1/messages` — send a message (`worktreeId`, `message`, optional
+//!   `sessionId`, `model`); starts a new session when `sessionId` is omitted and
+//!   returns immediately with the session ID
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use tokio::sync::{oneshot, Mutex};
+
+use super::auth;
+use super::dispatch::dispatch_command;
+use crate::projects::storage::load_projects_data;
+
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+    token: String,
+}
+
+/// The running server: its config and shutdown signal
+struct Running {
+    port: u16,
+    token: String,
+    shutdown_tx: oneshot::Sender<()>,
+}
+
+static RUNNING: Lazy<Mutex<Option<Running>>> = Lazy::new(|| Mutex::new(None));
+
+/// Automation API status, for the preferences UI
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationApiStatus {
+    pub running: bool,
+    pub url: Option<String>,
+}
+
+/// Whether the request carries the API token as a bearer token
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| auth::validate_token(provided.trim(), token))
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn unauthorized() -> Response {
+    error(StatusCode::UNAUTHORIZED, "Invalid or missing bearer token")
+}
+
+async fn status_handler(headers: HeaderMap, State(state): State<ApiState>) -> Response {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    match load_projects_data(&state.app) {
+        Ok(data) => {
+            let projects: Vec<_> = data.projects.iter().filter(|p| !p.is_folder).collect();
+            let worktrees: Vec<_> = data
+                .worktrees
+                .iter()
+                .filter(|w| w.archived_at.is_none())
+                .collect();
+            Json(json!({ "projects": projects, "worktrees": worktrees })).into_response()
+        }
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn worktree_handler(
+    headers: HeaderMap,
+    Path(worktree_id): Path<String>,
+    State(state): State<ApiState>,
+) -> Response {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    match load_projects_data(&state.app) {
+        Ok(data) => match data.find_worktree(&worktree_id) {
+            Some(worktree) => Json(worktree).into_response(),
+            None => error(StatusCode::NOT_FOUND, "Worktree not found"),
+        },
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn create_worktree_handler(
+    headers: HeaderMap,
+    State(state): State<ApiState>,
+    Json(body): Json<Value>,
+) -> Response {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    match dispatch_command(&state.app, "create_worktree", body).await {
+        Ok(worktree) => (StatusCode::CREATED, Json(worktree)).into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendMessageRequest {
+    worktree_id: String,
+    message: String,
+    session_id: Option<String>,
+    model: Option<String>,
+}
+
+async fn send_message_handler(
+    headers: HeaderMap,
+    State(state): State<ApiState>,
+    Json(body): Json<SendMessageRequest>,
+) -> Response {
+    if !authorized(&headers, &state.token) {
+        return unauthorized();
+    }
+    let worktree_path = match load_projects_data(&state.app) {
+        Ok(data) => match data.find_worktree(&body.worktree_id) {
+            Some(worktree) => worktree.path.clone(),
+            None => return error(StatusCode::NOT_FOUND, "Worktree not found"),
+        },
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let session_id = match body.session_id {
+        Some(id) => id,
+        None => {
+            let args = json!({ "worktreeId": body.worktree_id, "worktreePath": worktree_path });
+            let session = match dispatch_command(&state.app, "create_session", args).await {
+                Ok(session) => session,
+                Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+            };
+            match session.get("id").and_then(Value::as_str) {
+                Some(id) => id.to_string(),
+                None => {
+                    return error(StatusCode::INTERNAL_SERVER_ERROR, "Session has no ID");
+                }
+            }
+        }
+    };
+
+    // The run can take minutes; answer right away and let it continue
+    let args = json!({
+        "sessionId": session_id,
+        "worktreeId": body.worktree_id,
+        "worktreePath": worktree_path,
+        "message": body.message,
+        "model": body.model,
+    });
+    let app = state.app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = dispatch_command(&app, "send_chat_message", args).await {
+            log::warn!("Automation API: message failed: {e}");
+        }
+    });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "sessionId": session_id })),
+    )
+        .into_response()
+}
+
+async fn start(app: AppHandle, port: u16, token: String) -> Result<Running, String> {
+    let state = ApiState {
+        app,
+        token: token.clone(),
+    };
+    let router = Router::new()
+        .route("/v1/status", get(status_handler))
+        .route("/v1/worktrees", post(create_worktree_handler))
+        .route("/v1/worktrees/{id}", get(worktree_handler))
+        .route("/v1/messages", post(send_message_handler))
+        .with_state(state);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind automation API to port {port}: {e}"))?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    tauri::async_runtime::spawn(async move {
+        log::info!("Automation API listening on {addr}");
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+                log::info!("Automation API shutting down");
+            })
+            .await
+            .unwrap_or_else(|e| log::error!("Automation API error: {e}"));
+    });
+
+    Ok(Running {
+        port,
+        token,
+        shutdown_tx,
+    })
+}
+
+/// Apply the automation API preferences: start, restart (port or token
+/// changed) or stop the server
+pub async fn apply(
+    app: &AppHandle,
+    enabled: bool,
+    port: u16,
+    token: Option<String>,
+) -> Result<(), String> {
+    let mut running = RUNNING.lock().await;
+    let token = token.filter(|t| !t.is_empty());
+    if let (true, Some(current), Some(token)) = (enabled, running.as_ref(), token.as_ref()) {
+        if current.port == port && current.token == *token {
+            return Ok(());
+        }
+    }
+    if let Some(current) = running.take() {
+        let _ = current.shutdown_tx.send(());
+    }
+    if !enabled {
+        return Ok(());
+    }
+    let token = token.ok_or_else(|| "Automation API token missing".to_string())?;
+    *running = Some(start(app.clone(), port, token).await?);
+    Ok(())
+}
+
+/// Current automation API status
+pub async fn status() -> AutomationApiStatus {
+    let running = RUNNING.lock().await;
+    AutomationApiStatus {
+        running: running.is_some(),
+        url: running
+            .as_ref()
+            .map(|r| format!("http://127.0.0.1:{}", r.port)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(!authorized(&headers, "secret"));
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(authorized(&headers, "secret"));
+        headers.insert(header::AUTHORIZATION, "Bearer other".parse().unwrap());
+        assert!(!authorized(&headers, "secret"));
+    }
+}
@@ -52,6 +52,27 @@ pub async fn dispatch_command(
             let result = crate::projects::add_project(app.clone(), path, parent_id).await?;
             to_value(result)
         }
+        "scan_projects_dir" => {
+            let root_dir: String = field(&args, "rootDir", "root_dir")?;
+            let max_depth: Option<usize> = field_opt(&args, "maxDepth", "max_depth")?;
+            let result =
+                crate::projects::scan_projects_dir(app.clone(), root_dir, max_depth).await?;
+            to_value(result)
+        }
+        "scan_and_import_projects" => {
+            let root_dir: String = field(&args, "rootDir", "root_dir")?;
+            let paths: Option<Vec<String>> = from_field_opt(&args, "paths")?;
+            let mirror_folders: bool =
+                field_opt(&args, "mirrorFolders", "mirror_folders")?.unwrap_or(false);
+            let result = crate::projects::scan_and_import_projects(
+                app.clone(),
+                root_dir,
+                paths,
+                mirror_folders,
+            )
+            .await?;
+            to_value(result)
+        }
         "remove_project" => {
             let project_id: String = field(&args, "projectId", "project_id")?;
             crate::projects::remove_project(app.clone(), project_id).await?;
@@ -74,6 +95,8 @@ pub async fn dispatch_command(
             let issue_context = field_opt(&args, "issueContext", "issue_context")?;
             let pr_context = field_opt(&args, "prContext", "pr_context")?;
             let custom_name = field_opt(&args, "customName", "custom_name")?;
+            let template_id = field_opt(&args, "templateId", "template_id")?;
+            let parent_worktree_id = field_opt(&args, "parentWorktreeId", "parent_worktree_id")?;
             let result = crate::projects::create_worktree(
                 app.clone(),
                 project_id,
@@ -81,6 +104,8 @@ pub async fn dispatch_command(
                 issue_context,
                 pr_context,
                 custom_name,
+                template_id,
+                parent_worktree_id,
             )
             .await?;
             emit_cache_invalidation(app, &["projects"]);
@@ -101,11 +126,121 @@ pub async fn dispatch_command(
             let project_id: String = field(&args, "projectId", "project_id")?;
             let default_branch: Option<String> =
                 field_opt(&args, "defaultBranch", "default_branch")?;
+            let use_devcontainer: Option<bool> =
+                field_opt(&args, "useDevcontainer", "use_devcontainer")?;
+            let templates = from_field_opt(&args, "templates")?;
+            let branch_name_template: Option<String> =
+                field_opt(&args, "branchNameTemplate", "branch_name_template")?;
+            let ticket_pattern: Option<String> =
+                field_opt(&args, "ticketPattern", "ticket_pattern")?;
+            let slack = from_field_opt(&args, "slack")?;
+            let pr_checklist = field_opt(&args, "prChecklist", "pr_checklist")?;
+            let license_policy = field_opt(&args, "licensePolicy", "license_policy")?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
+            let mcp_servers = field_opt(&args, "mcpServers", "mcp_servers")?;
+            let result = crate::projects::update_project_settings(
+                app.clone(),
+                project_id,
+                default_branch,
+                use_devcontainer,
+                templates,
+                branch_name_template,
+                ticket_pattern,
+                slack,
+                pr_checklist,
+                license_policy,
+                remote,
+                mcp_servers,
+            )
+            .await?;
+            to_value(result)
+        }
+        "list_remotes" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result = crate::projects::list_remotes(app.clone(), project_id).await?;
+            to_value(result)
+        }
+        "detect_devcontainer" => {
+            let path: String = from_field(&args, "path")?;
+            let result = crate::projects::detect_devcontainer(path).await?;
+            to_value(result)
+        }
+        "list_packages" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let worktree_path: Option<String> = field_opt(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::projects::list_packages(app.clone(), project_id, worktree_path).await?;
+            to_value(result)
+        }
+        "detect_toolchains" => {
+            let path: String = from_field(&args, "path")?;
+            let result = crate::projects::detect_toolchains(path).await?;
+            to_value(result)
+        }
+        "install_toolchains" => {
+            let path: String = from_field(&args, "path")?;
+            let result = crate::projects::install_toolchains(path).await?;
+            to_value(result)
+        }
+        "list_env_profiles" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let result =
-                crate::projects::update_project_settings(app.clone(), project_id, default_branch)
+                crate::projects::env_profiles::list_env_profiles(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "set_worktree_env_profile" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let profile: Option<String> = from_field_opt(&args, "profile")?;
+            let result = crate::projects::env_profiles::set_worktree_env_profile(
+                app.clone(),
+                worktree_id,
+                profile,
+            )
+            .await?;
+            to_value(result)
+        }
+        "create_snapshot" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let label: String = from_field(&args, "label")?;
+            let result =
+                crate::projects::snapshots::create_snapshot(app.clone(), worktree_id, label)
                     .await?;
             to_value(result)
         }
+        "list_snapshots" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::snapshots::list_snapshots(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "restore_snapshot" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let snapshot_id: String = field(&args, "snapshotId", "snapshot_id")?;
+            let result =
+                crate::projects::snapshots::restore_snapshot(app.clone(), worktree_id, snapshot_id)
+                    .await?;
+            to_value(result)
+        }
+        "compose_up" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result = crate::projects::compose_up(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "compose_down" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let remove_volumes: Option<bool> = field_opt(&args, "removeVolumes", "remove_volumes")?;
+            let result =
+                crate::projects::compose_down(app.clone(), worktree_id, remove_volumes).await?;
+            to_value(result)
+        }
+        "compose_logs" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let service: Option<String> = from_field_opt(&args, "service")?;
+            let tail: Option<u32> = from_field_opt(&args, "tail")?;
+            let result =
+                crate::projects::compose_logs(app.clone(), worktree_id, service, tail).await?;
+            to_value(result)
+        }
         "reorder_projects" => {
             let project_ids: Vec<String> = field(&args, "projectIds", "project_ids")?;
             crate::projects::reorder_projects(app.clone(), project_ids).await?;
@@ -157,13 +292,17 @@ pub async fn dispatch_command(
         "git_pull" => {
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
             let base_branch: String = field(&args, "baseBranch", "base_branch")?;
-            let result = crate::projects::git_pull(worktree_path, base_branch).await?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
+            let result =
+                crate::projects::git_pull(app.clone(), worktree_path, base_branch, remote).await?;
             to_value(result)
         }
         "git_push" => {
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
             let pr_number: Option<u32> = field_opt(&args, "prNumber", "pr_number")?;
-            let result = crate::projects::git_push(app.clone(), worktree_path, pr_number).await?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
+            let result =
+                crate::projects::git_push(app.clone(), worktree_path, pr_number, remote).await?;
             to_value(result)
         }
         "commit_changes" => {
@@ -193,26 +332,176 @@ pub async fn dispatch_command(
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
             let magic_prompt: Option<String> = field_opt(&args, "magicPrompt", "magic_prompt")?;
             let model: Option<String> = from_field_opt(&args, "model")?;
+            let override_reason: Option<String> =
+                field_opt(&args, "overrideReason", "override_reason")?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
             let result = crate::projects::create_pr_with_ai_content(
                 app.clone(),
                 worktree_path,
                 magic_prompt,
                 model,
+                override_reason,
+                remote,
+            )
+            .await?;
+            to_value(result)
+        }
+        "restack_worktree" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::stacks::restack_worktree(app.clone(), worktree_id).await?;
+            emit_cache_invalidation(app, &["projects"]);
+            to_value(result)
+        }
+        "restack_children" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::stacks::restack_children(app.clone(), worktree_id).await?;
+            emit_cache_invalidation(app, &["projects"]);
+            to_value(result)
+        }
+        "check_dependency_licenses" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result = crate::projects::license_check::check_dependency_licenses(
+                app.clone(),
+                worktree_path,
+            )
+            .await?;
+            to_value(result)
+        }
+        "run_pr_checklist" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let result =
+                crate::projects::pr_checklist::run_pr_checklist(app.clone(), worktree_path).await?;
+            to_value(result)
+        }
+        "export_review_report" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::review_report::export_review_report(app.clone(), worktree_id)
+                    .await?;
+            to_value(result)
+        }
+        "export_review_sarif" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::review_sarif::export_review_sarif(app.clone(), worktree_id)
+                    .await?;
+            to_value(result)
+        }
+        "import_lint_findings" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let tool: crate::projects::lint_import::LintTool = from_field(&args, "tool")?;
+            let output: String = from_field(&args, "output")?;
+            let result = crate::projects::lint_import::import_lint_findings(
+                app.clone(),
+                worktree_id,
+                tool,
+                output,
+            )
+            .await?;
+            to_value(result)
+        }
+        "run_tests" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let scope: Option<String> = from_field_opt(&args, "scope")?;
+            let fix_failures: Option<bool> = field_opt(&args, "fixFailures", "fix_failures")?;
+            let result = crate::projects::test_runner::run_tests(
+                app.clone(),
+                worktree_id,
+                scope,
+                fix_failures,
+            )
+            .await?;
+            to_value(result)
+        }
+        "get_test_results" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::test_runner::get_test_results(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "get_diff_coverage" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::coverage::get_diff_coverage(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "run_security_audit" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let triage: Option<bool> = from_field_opt(&args, "triage")?;
+            let result = crate::projects::security_audit::run_security_audit(
+                app.clone(),
+                worktree_id,
+                triage,
             )
             .await?;
             to_value(result)
         }
+        "get_cleanup_suggestions" => {
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
+            let result =
+                crate::projects::cleanup::get_cleanup_suggestions(app.clone(), project_id).await?;
+            to_value(result)
+        }
+        "apply_cleanup_action" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let action = from_field(&args, "action")?;
+            crate::projects::cleanup::apply_cleanup_action(app.clone(), worktree_id, action)
+                .await?;
+            emit_cache_invalidation(app, &["projects"]);
+            Ok(Value::Null)
+        }
+        "get_project_dashboard" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let result =
+                crate::projects::dashboard::get_project_dashboard(app.clone(), project_id).await?;
+            to_value(result)
+        }
+        "list_pr_checks" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::ci_checks::list_pr_checks(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "get_check_run_log" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let check_id: String = field(&args, "checkId", "check_id")?;
+            let result =
+                crate::projects::ci_checks::get_check_run_log(app.clone(), worktree_id, check_id)
+                    .await?;
+            to_value(result)
+        }
+        "investigate_ci_failure" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::ci_checks::investigate_ci_failure(app.clone(), worktree_id)
+                    .await?;
+            to_value(result)
+        }
+        "redact_secrets" => {
+            let text: String = from_field(&args, "text")?;
+            let result = crate::redaction::redact_secrets(text).await?;
+            to_value(result)
+        }
         "create_commit_with_ai" => {
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
             let custom_prompt: Option<String> = field_opt(&args, "magicPrompt", "magic_prompt")?;
             let push: bool = from_field_opt(&args, "push")?.unwrap_or(false);
             let model: Option<String> = from_field_opt(&args, "model")?;
+            let message: Option<String> = from_field_opt(&args, "message")?;
+            let no_verify: Option<bool> = field_opt(&args, "noVerify", "no_verify")?;
+            let fix_hook_failures: Option<bool> =
+                field_opt(&args, "fixHookFailures", "fix_hook_failures")?;
             let result = crate::projects::create_commit_with_ai(
                 app.clone(),
                 worktree_path,
                 custom_prompt,
                 push,
                 model,
+                message,
+                no_verify,
+                fix_hook_failures,
             )
             .await?;
             to_value(result)
@@ -221,11 +510,13 @@ pub async fn dispatch_command(
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
             let magic_prompt: Option<String> = field_opt(&args, "magicPrompt", "magic_prompt")?;
             let model: Option<String> = from_field_opt(&args, "model")?;
+            let package_path: Option<String> = field_opt(&args, "packagePath", "package_path")?;
             let result = crate::projects::run_review_with_ai(
                 app.clone(),
                 worktree_path,
                 magic_prompt,
                 model,
+                package_path,
             )
             .await?;
             to_value(result)
@@ -277,6 +568,30 @@ pub async fn dispatch_command(
             let result = crate::projects::list_worktree_files(worktree_path, max_files).await?;
             to_value(result)
         }
+        "list_worktree_files_page" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let cursor: Option<String> = from_field_opt(&args, "cursor")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result =
+                crate::projects::list_worktree_files_page(worktree_path, cursor, limit).await?;
+            to_value(result)
+        }
+        "search_worktree_files" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let query: String = from_field(&args, "query")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result =
+                crate::projects::search_worktree_files(worktree_path, query, limit).await?;
+            to_value(result)
+        }
+        "search_in_worktree" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let query: String = from_field(&args, "query")?;
+            let options: Option<crate::projects::code_search::SearchOptions> =
+                from_field_opt(&args, "options")?;
+            let result = crate::projects::search_in_worktree(worktree_path, query, options).await?;
+            to_value(result)
+        }
 
         // =====================================================================
         // GitHub Issues & PRs
@@ -364,6 +679,83 @@ pub async fn dispatch_command(
             emit_cache_invalidation(app, &["contexts"]);
             Ok(Value::Null)
         }
+        "list_jira_issues" => {
+            let jql: Option<String> = from_field_opt(&args, "jql")?;
+            let result = crate::projects::jira::list_jira_issues(app.clone(), jql).await?;
+            to_value(result)
+        }
+        "load_jira_issue_context" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let issue_key: String = field(&args, "issueKey", "issue_key")?;
+            let result =
+                crate::projects::jira::load_jira_issue_context(app.clone(), worktree_id, issue_key)
+                    .await?;
+            to_value(result)
+        }
+        "list_loaded_jira_contexts" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::jira::list_loaded_jira_contexts(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "remove_jira_issue_context" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let issue_key: String = field(&args, "issueKey", "issue_key")?;
+            crate::projects::jira::remove_jira_issue_context(app.clone(), worktree_id, issue_key)
+                .await?;
+            emit_cache_invalidation(app, &["contexts"]);
+            Ok(Value::Null)
+        }
+        "list_linear_issues" => {
+            let result = crate::projects::linear::list_linear_issues(app.clone()).await?;
+            to_value(result)
+        }
+        "load_linear_issue_context" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let identifier: String = from_field(&args, "identifier")?;
+            let result = crate::projects::linear::load_linear_issue_context(
+                app.clone(),
+                worktree_id,
+                identifier,
+            )
+            .await?;
+            to_value(result)
+        }
+        "list_loaded_linear_contexts" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::linear::list_loaded_linear_contexts(app.clone(), worktree_id)
+                    .await?;
+            to_value(result)
+        }
+        "remove_linear_issue_context" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let identifier: String = from_field(&args, "identifier")?;
+            crate::projects::linear::remove_linear_issue_context(
+                app.clone(),
+                worktree_id,
+                identifier,
+            )
+            .await?;
+            emit_cache_invalidation(app, &["contexts"]);
+            Ok(Value::Null)
+        }
+        "create_worktree_from_jira_issue" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let issue_key: String = field(&args, "issueKey", "issue_key")?;
+            let base_branch: Option<String> = field_opt(&args, "baseBranch", "base_branch")?;
+            let template_id = field_opt(&args, "templateId", "template_id")?;
+            let result = crate::projects::jira::create_worktree_from_jira_issue(
+                app.clone(),
+                project_id,
+                issue_key,
+                base_branch,
+                template_id,
+            )
+            .await?;
+            emit_cache_invalidation(app, &["projects"]);
+            to_value(result)
+        }
         "get_issue_context_content" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let issue_number: u32 = field(&args, "issueNumber", "issue_number")?;
@@ -463,6 +855,36 @@ pub async fn dispatch_command(
                     .await?;
             to_value(result)
         }
+        "export_session" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let path: String = from_field(&args, "path")?;
+            let format: crate::chat::export::ExportFormat = from_field(&args, "format")?;
+            let strip_thinking: Option<bool> = field_opt(&args, "stripThinking", "strip_thinking")?;
+            let include_tool_output: Option<bool> =
+                field_opt(&args, "includeToolOutput", "include_tool_output")?;
+            crate::chat::export_session(
+                app.clone(),
+                worktree_id,
+                worktree_path,
+                session_id,
+                path,
+                format,
+                strip_thinking,
+                include_tool_output,
+            )
+            .await?;
+            Ok(Value::Null)
+        }
+        "get_session_messages" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let before: Option<String> = from_field_opt(&args, "before")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result =
+                crate::chat::get_session_messages(app.clone(), session_id, before, limit).await?;
+            to_value(result)
+        }
         "create_session" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
@@ -471,6 +893,13 @@ pub async fn dispatch_command(
                 crate::chat::create_session(app.clone(), worktree_id, worktree_path, name).await?;
             to_value(result)
         }
+        "fork_session" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let from_message_index: usize = field(&args, "fromMessageIndex", "from_message_index")?;
+            let result =
+                crate::chat::fork_session(app.clone(), session_id, from_message_index).await?;
+            to_value(result)
+        }
         "rename_session" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
@@ -562,6 +991,36 @@ pub async fn dispatch_command(
             crate::chat::cancel_chat_message(app.clone(), session_id, worktree_id).await?;
             Ok(Value::Null)
         }
+        "prioritize_chat_session" => {
+            let session_id: String = field(&args, "sessionId", "session_id")?;
+            let result = crate::chat::prioritize_chat_session(app.clone(), session_id).await?;
+            to_value(result)
+        }
+        "get_tool_policy" => {
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
+            let session_id: Option<String> = field_opt(&args, "sessionId", "session_id")?;
+            let result =
+                crate::chat::tool_policy::get_tool_policy(app.clone(), project_id, session_id)
+                    .await?;
+            to_value(result)
+        }
+        "set_tool_policy" => {
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
+            let session_id: Option<String> = field_opt(&args, "sessionId", "session_id")?;
+            let policy: Option<crate::projects::types::ToolPolicy> =
+                from_field_opt(&args, "policy")?;
+            crate::chat::tool_policy::set_tool_policy(app.clone(), project_id, session_id, policy)
+                .await?;
+            Ok(Value::Null)
+        }
+        "get_change_audit" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let since: Option<u64> = from_field_opt(&args, "since")?;
+            let result =
+                crate::chat::change_audit::get_change_audit(app.clone(), worktree_id, since)
+                    .await?;
+            to_value(result)
+        }
         "clear_session_history" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
@@ -698,6 +1157,13 @@ pub async fn dispatch_command(
             let result = crate::chat::read_file_content(file_path).await?;
             to_value(result)
         }
+        "read_file_preview" => {
+            let path: String = from_field(&args, "path")?;
+            let offset: Option<u64> = from_field_opt(&args, "offset")?;
+            let length: Option<u64> = from_field_opt(&args, "length")?;
+            let result = crate::chat::read_file_preview(path, offset, length).await?;
+            to_value(result)
+        }
         "read_plan_file" => {
             let path: String = from_field(&args, "path")?;
             let result = crate::chat::read_plan_file(path).await?;
@@ -742,8 +1208,14 @@ pub async fn dispatch_command(
         }
         "set_git_poll_interval" => {
             let seconds: u64 = from_field(&args, "seconds")?;
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
             let state = app.state::<crate::background_tasks::BackgroundTaskManager>();
-            crate::background_tasks::commands::set_git_poll_interval(state, seconds)?;
+            crate::background_tasks::commands::set_git_poll_interval(
+                app.clone(),
+                state,
+                seconds,
+                project_id,
+            )?;
             Ok(Value::Null)
         }
         "get_git_poll_interval" => {
@@ -762,6 +1234,71 @@ pub async fn dispatch_command(
             let result = crate::background_tasks::commands::get_remote_poll_interval(state)?;
             to_value(result)
         }
+        "get_polling_status" => {
+            let state = app.state::<crate::background_tasks::BackgroundTaskManager>();
+            let result = crate::background_tasks::commands::get_polling_status(app.clone(), state)?;
+            to_value(result)
+        }
+        "set_polling_paused" => {
+            let state = app.state::<crate::background_tasks::BackgroundTaskManager>();
+            let paused: bool = from_field(&args, "paused")?;
+            crate::background_tasks::commands::set_polling_paused(state, paused)?;
+            Ok(Value::Null)
+        }
+        "list_scheduled_jobs" => {
+            let result = crate::background_tasks::commands::list_scheduled_jobs(app.clone())?;
+            to_value(result)
+        }
+        "save_scheduled_job" => {
+            let job: crate::background_tasks::scheduler::ScheduledJobInput =
+                from_field(&args, "job")?;
+            let result = crate::background_tasks::commands::save_scheduled_job(app.clone(), job)?;
+            to_value(result)
+        }
+        "delete_scheduled_job" => {
+            let job_id: String = field(&args, "jobId", "job_id")?;
+            crate::background_tasks::commands::delete_scheduled_job(app.clone(), job_id)?;
+            Ok(Value::Null)
+        }
+        "run_scheduled_job_now" => {
+            let job_id: String = field(&args, "jobId", "job_id")?;
+            let result =
+                crate::background_tasks::commands::run_scheduled_job_now(app.clone(), job_id)?;
+            to_value(result)
+        }
+        "get_scheduled_job_runs" => {
+            let job_id: String = field(&args, "jobId", "job_id")?;
+            let limit: Option<usize> = from_field_opt(&args, "limit")?;
+            let result = crate::background_tasks::commands::get_scheduled_job_runs(
+                app.clone(),
+                job_id,
+                limit,
+            )?;
+            to_value(result)
+        }
+        "get_time_report" => {
+            let range: crate::background_tasks::time_tracking::TimeRange =
+                from_field(&args, "range")?;
+            let result = crate::background_tasks::commands::get_time_report(app.clone(), range)?;
+            to_value(result)
+        }
+        "get_scheduled_reviews" => {
+            let result = crate::background_tasks::commands::get_scheduled_reviews(app.clone())?;
+            to_value(result)
+        }
+        "set_project_poll_overrides" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let overrides: Option<crate::projects::types::PollingOverrides> =
+                from_field_opt(&args, "overrides")?;
+            let state = app.state::<crate::background_tasks::BackgroundTaskManager>();
+            let result = crate::background_tasks::commands::set_project_poll_overrides(
+                app.clone(),
+                state,
+                project_id,
+                overrides,
+            )?;
+            to_value(result)
+        }
 
         // =====================================================================
         // Terminal
@@ -787,6 +1324,11 @@ pub async fn dispatch_command(
             let result = crate::projects::cleanup_old_archives(app.clone(), retention_days).await?;
             to_value(result)
         }
+        "archive_stale_worktrees" => {
+            let days: Option<u32> = from_field_opt(&args, "days")?;
+            let result = crate::projects::archive_stale_worktrees(app.clone(), days).await?;
+            to_value(result)
+        }
 
         // =====================================================================
         // HTTP Server control (exposed so web clients can check status)
@@ -795,6 +1337,18 @@ pub async fn dispatch_command(
             let result = crate::http_server::server::get_server_status(app.clone()).await;
             to_value(result)
         }
+        "get_automation_api_status" => {
+            let result = crate::http_server::automation::status().await;
+            to_value(result)
+        }
+        "get_automation_api_token" => {
+            let result = crate::get_automation_api_token(app.clone()).await?;
+            to_value(result)
+        }
+        "regenerate_automation_api_token" => {
+            let result = crate::regenerate_automation_api_token(app.clone()).await?;
+            to_value(result)
+        }
 
         // =====================================================================
         // Core / Utility
@@ -887,9 +1441,21 @@ pub async fn dispatch_command(
         }
         "permanently_delete_worktree" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
-            crate::projects::permanently_delete_worktree(app.clone(), worktree_id).await?;
+            let use_trash: Option<bool> = field_opt(&args, "useTrash", "use_trash")?;
+            crate::projects::permanently_delete_worktree(app.clone(), worktree_id, use_trash)
+                .await?;
             Ok(Value::Null)
         }
+        "list_trashed_worktrees" => {
+            let result = crate::projects::list_trashed_worktrees(app.clone()).await?;
+            to_value(result)
+        }
+        "restore_trashed_worktree" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::projects::restore_trashed_worktree(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
         "delete_all_archives" => {
             let result = crate::projects::delete_all_archives(app.clone()).await?;
             to_value(result)
@@ -910,6 +1476,24 @@ pub async fn dispatch_command(
             // NATIVE ONLY: Cannot open native editor from browser
             Ok(Value::Null)
         }
+        "apply_patch_hunk" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let file: String = from_field(&args, "file")?;
+            let hunk: crate::projects::git_status::DiffHunk = from_field(&args, "hunk")?;
+            crate::projects::apply_patch_hunk(worktree_path, file, hunk).await?;
+            Ok(Value::Null)
+        }
+        "discard_hunk" => {
+            let worktree_path: String = field(&args, "worktreePath", "worktree_path")?;
+            let file: String = from_field(&args, "file")?;
+            let hunk: crate::projects::git_status::DiffHunk = from_field(&args, "hunk")?;
+            crate::projects::discard_hunk(worktree_path, file, hunk).await?;
+            Ok(Value::Null)
+        }
+        "open_diff_in_external_tool" | "open_conflict_in_merge_tool" => {
+            // NATIVE ONLY: Cannot open native diff tools from browser
+            Ok(Value::Null)
+        }
         "open_pull_request" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let title: Option<String> = from_field_opt(&args, "title")?;
@@ -950,8 +1534,24 @@ pub async fn dispatch_command(
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
             let commit_message: Option<String> =
                 field_opt(&args, "commitMessage", "commit_message")?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
             let result =
-                crate::projects::rebase_worktree(app.clone(), worktree_id, commit_message).await?;
+                crate::projects::rebase_worktree(app.clone(), worktree_id, commit_message, remote)
+                    .await?;
+            to_value(result)
+        }
+
+        "plan_interactive_rebase" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result = crate::projects::plan_interactive_rebase(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "execute_interactive_rebase" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let actions: Vec<crate::projects::git::RebaseAction> = from_field(&args, "actions")?;
+            let result =
+                crate::projects::execute_interactive_rebase(app.clone(), worktree_id, actions)
+                    .await?;
             to_value(result)
         }
 
@@ -974,7 +1574,9 @@ pub async fn dispatch_command(
         }
         "fetch_and_merge_base" => {
             let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
-            let result = crate::projects::fetch_and_merge_base(app.clone(), worktree_id).await?;
+            let remote: Option<String> = from_field_opt(&args, "remote")?;
+            let result =
+                crate::projects::fetch_and_merge_base(app.clone(), worktree_id, remote).await?;
             to_value(result)
         }
 
@@ -1211,7 +1813,9 @@ pub async fn dispatch_command(
         "save_pasted_image" => {
             let data: String = from_field(&args, "data")?;
             let mime_type: String = field(&args, "mimeType", "mime_type")?;
-            let result = crate::chat::save_pasted_image(app.clone(), data, mime_type).await?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::chat::save_pasted_image(app.clone(), data, mime_type, worktree_id).await?;
             to_value(result)
         }
         "save_dropped_image" => {
@@ -1225,9 +1829,22 @@ pub async fn dispatch_command(
         }
         "save_pasted_text" => {
             let content: String = from_field(&args, "content")?;
-            let result = crate::chat::save_pasted_text(app.clone(), content).await?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let result = crate::chat::save_pasted_text(app.clone(), content, worktree_id).await?;
+            to_value(result)
+        }
+        "save_pasted_document" => {
+            let data: String = from_field(&args, "data")?;
+            let filename: String = from_field(&args, "filename")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::chat::save_pasted_document(app.clone(), data, filename, worktree_id).await?;
             to_value(result)
         }
+        "save_dropped_document" => {
+            // NATIVE ONLY: Drag-drop from native file paths doesn't work in browser
+            Ok(Value::Null)
+        }
         "delete_pasted_text" => {
             let path: String = from_field(&args, "path")?;
             crate::chat::delete_pasted_text(app.clone(), path).await?;
@@ -1238,6 +1855,22 @@ pub async fn dispatch_command(
             let result = crate::chat::read_pasted_text(app.clone(), path).await?;
             to_value(result)
         }
+        "list_paste_history" => {
+            let worktree_id: String = field(&args, "worktreeId", "worktree_id")?;
+            let result =
+                crate::chat::paste_history::list_paste_history(app.clone(), worktree_id).await?;
+            to_value(result)
+        }
+        "reattach_paste" => {
+            let id: String = from_field(&args, "id")?;
+            let result = crate::chat::paste_history::reattach_paste(app.clone(), id).await?;
+            to_value(result)
+        }
+        "delete_paste_history_entry" => {
+            let id: String = from_field(&args, "id")?;
+            crate::chat::paste_history::delete_paste_history_entry(app.clone(), id).await?;
+            Ok(Value::Null)
+        }
 
         // =====================================================================
         // File Operations (additional)
@@ -1245,7 +1878,7 @@ pub async fn dispatch_command(
         "write_file_content" => {
             let path: String = from_field(&args, "path")?;
             let content: String = from_field(&args, "content")?;
-            crate::chat::write_file_content(path, content).await?;
+            crate::chat::write_file_content(app.clone(), path, content).await?;
             Ok(Value::Null)
         }
         "open_file_in_default_app" => {
@@ -1339,6 +1972,218 @@ pub async fn dispatch_command(
             Ok(Value::Null)
         }
 
+        // =====================================================================
+        // Onboarding
+        // =====================================================================
+        "get_onboarding_state" => {
+            let result = crate::onboarding::commands::get_onboarding_state(app.clone()).await?;
+            to_value(result)
+        }
+        "complete_onboarding_step" => {
+            let step: crate::onboarding::OnboardingStep = from_field(&args, "step")?;
+            let result =
+                crate::onboarding::commands::complete_onboarding_step(app.clone(), step).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Plugins
+        // =====================================================================
+        "send_slack_test_message" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            crate::slack::commands::send_slack_test_message(app.clone(), project_id).await?;
+            Ok(Value::Null)
+        }
+        "send_test_webhook" => {
+            let rule = from_field(&args, "rule")?;
+            crate::notifications::commands::send_test_webhook(rule).await?;
+            Ok(Value::Null)
+        }
+        "list_plugins" => {
+            let result = crate::plugins::commands::list_plugins(app.clone()).await?;
+            to_value(result)
+        }
+        "run_plugin_command" => {
+            let plugin_id: String = field(&args, "pluginId", "plugin_id")?;
+            let command: String = from_field(&args, "command")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let input: Option<Value> = from_field_opt(&args, "input")?;
+            let result = crate::plugins::commands::run_plugin_command(
+                app.clone(),
+                plugin_id,
+                command,
+                worktree_id,
+                input,
+            )
+            .await?;
+            to_value(result)
+        }
+        "set_plugin_trusted" => {
+            let plugin_id: String = field(&args, "pluginId", "plugin_id")?;
+            let trusted: bool = from_field(&args, "trusted")?;
+            let result =
+                crate::plugins::commands::set_plugin_trusted(app.clone(), plugin_id, trusted)
+                    .await?;
+            to_value(result)
+        }
+        "get_plugin_context" => {
+            let plugin_id: String = field(&args, "pluginId", "plugin_id")?;
+            let provider: String = from_field(&args, "provider")?;
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let result = crate::plugins::commands::get_plugin_context(
+                app.clone(),
+                plugin_id,
+                provider,
+                worktree_id,
+            )
+            .await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // App Updates
+        // =====================================================================
+        "check_for_app_update" => {
+            let channel: Option<String> = from_field_opt(&args, "channel")?;
+            let result =
+                crate::updater::commands::check_for_app_update(app.clone(), channel).await?;
+            to_value(result)
+        }
+        "get_release_notes" => {
+            let version: String = from_field(&args, "version")?;
+            let result = crate::updater::commands::get_release_notes(version).await?;
+            to_value(result)
+        }
+        "get_deferred_update_status" => {
+            let result = crate::updater::commands::get_deferred_update_status().await?;
+            to_value(result)
+        }
+        "install_app_update" | "cancel_deferred_update" => {
+            // NATIVE ONLY: Updates are installed by the desktop app
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // Backup
+        // =====================================================================
+        "create_backup" => {
+            let destination: Option<String> = from_field_opt(&args, "destination")?;
+            let result = crate::backup::commands::create_backup(app.clone(), destination).await?;
+            to_value(result)
+        }
+        "restore_backup" => {
+            // NATIVE ONLY: Restoring replaces the app's data directory
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // Storage Locations
+        // =====================================================================
+        "get_storage_locations" => {
+            let result = crate::locations::commands::get_storage_locations(app.clone()).await?;
+            to_value(result)
+        }
+        "set_worktrees_root" => {
+            let path: Option<String> = from_field_opt(&args, "path")?;
+            let move_existing: bool = field(&args, "moveExisting", "move_existing")?;
+            let result =
+                crate::locations::commands::set_worktrees_root(app.clone(), path, move_existing)
+                    .await?;
+            to_value(result)
+        }
+        "set_data_location" => {
+            // NATIVE ONLY: Moving app data must be done from the desktop app
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // WSL
+        // =====================================================================
+        "list_wsl_distributions" => {
+            let result = crate::platform::commands::list_wsl_distributions().await?;
+            to_value(result)
+        }
+        "validate_wsl_distribution" => {
+            let distribution: Option<String> = from_field_opt(&args, "distribution")?;
+            let result = crate::platform::commands::validate_wsl_distribution(distribution).await?;
+            to_value(result)
+        }
+        "open_url" => {
+            // NATIVE ONLY: the browser opens links itself
+            Ok(Value::Null)
+        }
+
+        // =====================================================================
+        // Storage Report
+        // =====================================================================
+        "search_sessions" => {
+            let query: String = from_field(&args, "query")?;
+            let project_id: Option<String> = field_opt(&args, "projectId", "project_id")?;
+            let result =
+                crate::search::commands::search_sessions(app.clone(), query, project_id).await?;
+            to_value(result)
+        }
+        "get_storage_report" => {
+            let result = crate::storage_report::commands::get_storage_report(app.clone()).await?;
+            to_value(result)
+        }
+        "cleanup_storage_category" => {
+            let category: crate::storage_report::StorageCategory = from_field(&args, "category")?;
+            let result =
+                crate::storage_report::commands::cleanup_storage_category(app.clone(), category)
+                    .await?;
+            to_value(result)
+        }
+        "get_worktree_disk_usage" => {
+            let project_id: String = field(&args, "projectId", "project_id")?;
+            let stale_days: Option<u32> = field_opt(&args, "staleDays", "stale_days")?;
+            let result = crate::storage_report::commands::get_worktree_disk_usage(
+                app.clone(),
+                project_id,
+                stale_days,
+            )
+            .await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Background Jobs
+        // =====================================================================
+        "list_background_jobs" => {
+            let result = crate::jobs::commands::list_background_jobs();
+            to_value(result)
+        }
+        "cancel_background_job" => {
+            let job_id: String = field(&args, "jobId", "job_id")?;
+            let result = crate::jobs::commands::cancel_background_job(app.clone(), job_id);
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Data Integrity
+        // =====================================================================
+        "verify_data_integrity" => {
+            let fix: bool = from_field_opt(&args, "fix")?.unwrap_or(false);
+            let result =
+                crate::integrity::commands::verify_data_integrity(app.clone(), fix).await?;
+            to_value(result)
+        }
+
+        // =====================================================================
+        // Command Audit Log
+        // =====================================================================
+        "get_command_audit_log" => {
+            let worktree_id: Option<String> = field_opt(&args, "worktreeId", "worktree_id")?;
+            let limit: Option<u32> = from_field_opt(&args, "limit")?;
+            let result = crate::command_audit::commands::get_command_audit_log(
+                app.clone(),
+                worktree_id,
+                limit,
+            )
+            .await?;
+            to_value(result)
+        }
+
         // =====================================================================
         // HTTP Server control (additional)
         // =====================================================================
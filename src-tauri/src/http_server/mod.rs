@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod automation;
 pub mod dispatch;
 pub mod server;
 pub mod websocket;
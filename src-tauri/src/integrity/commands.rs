@@ -0,0 +1,331 @@
+//! Tauri commands for the data integrity check
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use super::{
+    attached_context_owner, orphaned_index_keys, IntegrityIssue, IntegrityIssueKind,
+    IntegrityReport,
+};
+use crate::chat::storage::{parse_index_key, IndexKey};
+use crate::db::journal::JournalStep;
+use crate::http_server::EmitExt;
+use crate::projects::types::{ProjectsData, WorktreeDeletedEvent};
+
+/// Record an issue, repairing it first when `fix` is set and the kind allows it
+fn report(
+    issues: &mut Vec<IntegrityIssue>,
+    mut issue: IntegrityIssue,
+    fix: bool,
+    repair: impl FnOnce() -> Result<(), String>,
+) {
+    if fix && issue.kind.is_fixable() {
+        match repair() {
+            Ok(()) => issue.fixed = true,
+            Err(e) => {
+                log::warn!("Failed to repair {:?} {}: {e}", issue.kind, issue.subject);
+                issue.detail = format!("{} (repair failed: {e})", issue.detail);
+            }
+        }
+    }
+    issues.push(issue);
+}
+
+/// Remove a worktree record along with its session index and context references
+fn purge_worktree(app: &AppHandle, worktree_id: &str, project_id: &str) -> Result<(), String> {
+    crate::db::journal::run(
+        app,
+        "repair_worktree",
+        vec![
+            JournalStep::DeleteSessionIndex {
+                worktree_id: worktree_id.to_string(),
+            },
+            JournalStep::RemoveContextReferences {
+                worktree_id: worktree_id.to_string(),
+            },
+            JournalStep::RemoveWorktree {
+                worktree_id: worktree_id.to_string(),
+            },
+        ],
+    )?;
+
+    let event = WorktreeDeletedEvent {
+        id: worktree_id.to_string(),
+        project_id: project_id.to_string(),
+    };
+    if let Err(e) = app.emit_all("worktree:deleted", &event) {
+        log::error!("Failed to emit worktree:deleted event: {e}");
+    }
+    Ok(())
+}
+
+/// IDs of worktrees whose sessions and contexts must be kept: live worktrees,
+/// plus trashed ones that can still be restored
+fn known_worktree_ids(app: &AppHandle, data: &ProjectsData) -> Result<HashSet<String>, String> {
+    let mut ids: HashSet<String> = data.worktrees.iter().map(|w| w.id.clone()).collect();
+    ids.extend(
+        crate::projects::trash::list(app)?
+            .into_iter()
+            .map(|e| e.worktree.id),
+    );
+    Ok(ids)
+}
+
+/// Projects and worktrees against the filesystem
+fn check_projects(
+    app: &AppHandle,
+    fix: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> Result<(), String> {
+    let data = crate::projects::storage::load_projects_data(app)?;
+    let project_ids: HashSet<&str> = data.projects.iter().map(|p| p.id.as_str()).collect();
+
+    for project in data.projects.iter().filter(|p| !p.is_folder) {
+        if !Path::new(&project.path).exists() {
+            report(
+                issues,
+                IntegrityIssue::new(
+                    IntegrityIssueKind::MissingProjectPath,
+                    &project.id,
+                    format!("Project '{}' not found at {}", project.name, project.path),
+                ),
+                fix,
+                || Ok(()),
+            );
+        }
+    }
+
+    // Worktrees whose directory is gone were already pruned on load; their
+    // session indexes are reported as orphaned by `check_sessions`
+    for worktree in data
+        .worktrees
+        .iter()
+        .filter(|w| !project_ids.contains(w.project_id.as_str()))
+    {
+        report(
+            issues,
+            IntegrityIssue::new(
+                IntegrityIssueKind::OrphanedWorktree,
+                &worktree.id,
+                format!(
+                    "Worktree '{}' belongs to missing project {}",
+                    worktree.name, worktree.project_id
+                ),
+            ),
+            fix,
+            || purge_worktree(app, &worktree.id, &worktree.project_id),
+        );
+    }
+
+    let root = crate::locations::worktrees_root()?;
+    let tracked: HashSet<PathBuf> = data
+        .worktrees
+        .iter()
+        .map(|w| PathBuf::from(&w.path))
+        .collect();
+    for project in data.projects.iter().filter(|p| !p.is_folder) {
+        let dir = root.join(crate::projects::storage::sanitize_directory_name(
+            &project.name,
+        ));
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() && !tracked.contains(&path) {
+                report(
+                    issues,
+                    IntegrityIssue::new(
+                        IntegrityIssueKind::UntrackedWorktreeDir,
+                        path.to_string_lossy(),
+                        format!("Directory is not a worktree of '{}'", project.name),
+                    ),
+                    fix,
+                    || Ok(()),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Session indexes, metadata and data directories against worktrees
+fn check_sessions(
+    app: &AppHandle,
+    fix: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> Result<(), String> {
+    // Reload: repairs above may have removed worktrees
+    let data = crate::projects::storage::load_projects_data(app)?;
+    let worktree_ids = known_worktree_ids(app, &data)?;
+    let project_ids: HashSet<String> = data.projects.iter().map(|p| p.id.clone()).collect();
+
+    let indexes = crate::chat::storage::list_indexes(app)?;
+    let orphaned = orphaned_index_keys(
+        indexes.iter().map(|(key, _)| key.as_str()),
+        &worktree_ids,
+        &project_ids,
+    );
+    let mut indexed_sessions = HashSet::new();
+    for (key, index) in &indexes {
+        if orphaned.contains(key) {
+            let issue = IntegrityIssue::new(
+                IntegrityIssueKind::OrphanedSessionIndex,
+                key,
+                format!(
+                    "{} session(s) indexed for a worktree that no longer exists",
+                    index.sessions.len()
+                ),
+            );
+            report(issues, issue, fix, || match parse_index_key(key) {
+                IndexKey::Worktree(id) => crate::chat::storage::delete_index(app, id),
+                IndexKey::Base(id) => crate::chat::storage::delete_base_index(app, id),
+            });
+            // Keep the sessions listed when reporting only, so they aren't
+            // also flagged as orphaned metadata
+            if fix {
+                continue;
+            }
+        }
+        indexed_sessions.extend(index.sessions.iter().map(|s| s.id.clone()));
+    }
+
+    for session_id in crate::chat::storage::list_all_session_ids(app)? {
+        if indexed_sessions.contains(&session_id) {
+            continue;
+        }
+        let issue = IntegrityIssue::new(
+            IntegrityIssueKind::OrphanedSessionMetadata,
+            &session_id,
+            "Session is not listed in any worktree".to_string(),
+        );
+        report(issues, issue, fix, || {
+            crate::chat::storage::delete_session_data(app, &session_id)
+        });
+    }
+
+    let known: HashSet<String> = crate::chat::storage::list_all_session_ids(app)?
+        .iter()
+        .map(|id| crate::chat::storage::sanitize_filename(id))
+        .collect();
+    let data_dir = crate::chat::storage::get_data_dir(app)?;
+    let entries = std::fs::read_dir(&data_dir)
+        .map_err(|e| format!("Failed to read session data directory: {e}"))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.path().is_dir() || known.contains(&name) {
+            continue;
+        }
+        let path = entry.path();
+        report(
+            issues,
+            IntegrityIssue::new(
+                IntegrityIssueKind::OrphanedSessionData,
+                &name,
+                "Run logs without session metadata".to_string(),
+            ),
+            fix,
+            || {
+                std::fs::remove_dir_all(&path)
+                    .map_err(|e| format!("Failed to remove session data: {e}"))
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Issue/PR context references and attached saved contexts against worktrees
+fn check_contexts(
+    app: &AppHandle,
+    fix: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> Result<(), String> {
+    let data = crate::projects::storage::load_projects_data(app)?;
+    let worktree_ids = known_worktree_ids(app, &data)?;
+
+    let refs = crate::projects::github_issues::load_context_references(app)?;
+    let mut referencing: Vec<&String> = refs
+        .issues
+        .values()
+        .chain(refs.prs.values())
+        .flat_map(|entry| entry.worktrees.iter())
+        .filter(|id| !worktree_ids.contains(id.as_str()))
+        .collect();
+    referencing.sort();
+    referencing.dedup();
+    for worktree_id in referencing {
+        report(
+            issues,
+            IntegrityIssue::new(
+                IntegrityIssueKind::OrphanedContextReference,
+                worktree_id,
+                "Issue/PR context referenced by a worktree that no longer exists".to_string(),
+            ),
+            fix,
+            || {
+                crate::projects::github_issues::remove_all_worktree_references(app, worktree_id)
+                    .map(|_| ())
+            },
+        );
+    }
+
+    let contexts_dir = crate::chat::storage::get_saved_contexts_dir(app)?;
+    let entries = std::fs::read_dir(&contexts_dir)
+        .map_err(|e| format!("Failed to read saved contexts directory: {e}"))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(owner) = attached_context_owner(&name) else {
+            continue;
+        };
+        if worktree_ids.contains(owner) {
+            continue;
+        }
+        let path = entry.path();
+        report(
+            issues,
+            IntegrityIssue::new(
+                IntegrityIssueKind::OrphanedAttachedContext,
+                &name,
+                format!("Context attached to missing worktree {owner}"),
+            ),
+            fix,
+            || {
+                std::fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove attached context: {e}"))
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Cross-check projects, worktrees, sessions and contexts for orphans.
+///
+/// With `fix`, fixable issues are repaired; each issue reports whether it was.
+/// Repairs are refused while sessions are running, since a starting session
+/// may not be indexed yet.
+#[tauri::command]
+pub async fn verify_data_integrity(app: AppHandle, fix: bool) -> Result<IntegrityReport, String> {
+    if fix && crate::updater::has_running_sessions() {
+        return Err("Cannot repair data while sessions are running".to_string());
+    }
+
+    log::trace!("Verifying data integrity (fix: {fix})");
+
+    let mut issues = Vec::new();
+    check_projects(&app, fix, &mut issues)?;
+    check_sessions(&app, fix, &mut issues)?;
+    check_contexts(&app, fix, &mut issues)?;
+
+    let fixed = issues.iter().filter(|i| i.fixed).count() as u32;
+    log::trace!(
+        "Data integrity check found {} issue(s), fixed {fixed}",
+        issues.len()
+    );
+
+    Ok(IntegrityReport { issues, fixed })
+}
@@ -0,0 +1,143 @@
+//! Data integrity check and repair
+//!
+//! Cross-checks the stores that reference each other by ID: projects and
+//! worktrees against the filesystem, session indexes and metadata against
+//! worktrees, and context files against worktrees. Orphans left behind by
+//! crashes or manual deletions are reported and, on request, removed.
+//!
+//! Problems that can't be fixed safely (a project directory that is missing,
+//! perhaps on an unmounted volume, or untracked directories in the worktrees
+//! root) are only reported.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::chat::storage::{parse_index_key, IndexKey};
+
+pub mod commands;
+
+/// Kind of inconsistency found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// A project's repository directory no longer exists (report only)
+    MissingProjectPath,
+    /// A worktree belongs to a project that no longer exists
+    OrphanedWorktree,
+    /// A directory in the worktrees root that no worktree points to (report only)
+    UntrackedWorktreeDir,
+    /// A session index for a worktree (or closed base session) that no longer exists
+    OrphanedSessionIndex,
+    /// Session metadata not listed in any session index
+    OrphanedSessionMetadata,
+    /// A session data directory without metadata
+    OrphanedSessionData,
+    /// An issue/PR context reference to a worktree that no longer exists
+    OrphanedContextReference,
+    /// A saved context attached to a worktree that no longer exists
+    OrphanedAttachedContext,
+}
+
+impl IntegrityIssueKind {
+    /// Whether `verify_data_integrity` can repair this kind of issue
+    pub fn is_fixable(self) -> bool {
+        !matches!(
+            self,
+            IntegrityIssueKind::MissingProjectPath | IntegrityIssueKind::UntrackedWorktreeDir
+        )
+    }
+}
+
+/// A single inconsistency
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    /// ID or path of the affected record
+    pub subject: String,
+    pub detail: String,
+    pub fixed: bool,
+}
+
+impl IntegrityIssue {
+    pub fn new(kind: IntegrityIssueKind, subject: impl Into<String>, detail: String) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            detail,
+            fixed: false,
+        }
+    }
+}
+
+/// Result of an integrity check
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+    pub fixed: u32,
+}
+
+/// Index keys whose worktree (or, for a preserved base index, project) no longer exists
+pub fn orphaned_index_keys<'a>(
+    keys: impl IntoIterator<Item = &'a str>,
+    worktree_ids: &HashSet<String>,
+    project_ids: &HashSet<String>,
+) -> Vec<String> {
+    keys.into_iter()
+        .filter(|key| match parse_index_key(key) {
+            IndexKey::Worktree(id) => !worktree_ids.contains(id),
+            IndexKey::Base(id) => !project_ids.contains(id),
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Worktree ID an attached saved context file belongs to
+/// (`{worktree_id}-context-{slug}.md`). Saved contexts themselves share the
+/// directory, so the prefix must be a worktree UUID.
+pub fn attached_context_owner(file_name: &str) -> Option<&str> {
+    if !file_name.ends_with(".md") {
+        return None;
+    }
+    let (worktree_id, _) = file_name.split_once("-context-")?;
+    uuid::Uuid::parse_str(worktree_id).ok()?;
+    Some(worktree_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_orphaned_index_keys() {
+        let worktrees = set(&["wt-1"]);
+        let projects = set(&["p-1"]);
+        let keys = ["wt-1", "wt-2", "base-p-1", "base-p-2"];
+
+        assert_eq!(
+            orphaned_index_keys(keys, &worktrees, &projects),
+            vec!["wt-2".to_string(), "base-p-2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_attached_context_owner() {
+        assert_eq!(
+            attached_context_owner("550e8400-e29b-41d4-a716-446655440000-context-notes.md"),
+            Some("550e8400-e29b-41d4-a716-446655440000")
+        );
+        // Saved contexts ({project}-{timestamp}-{slug}.md) are not attachments
+        assert_eq!(
+            attached_context_owner("jean-1700000000-context-notes.md"),
+            None
+        );
+        assert_eq!(
+            attached_context_owner("session-context-metadata.json"),
+            None
+        );
+    }
+}
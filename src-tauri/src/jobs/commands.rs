@@ -0,0 +1,19 @@
+//! Tauri commands for background jobs
+
+use tauri::AppHandle;
+
+use super::BackgroundJob;
+
+/// List running and recently finished background jobs
+#[tauri::command]
+pub fn list_background_jobs() -> Vec<BackgroundJob> {
+    super::list()
+}
+
+/// Ask a running background job to stop
+///
+/// Returns false if the job isn't running (anymore).
+#[tauri::command]
+pub fn cancel_background_job(app: AppHandle, job_id: String) -> bool {
+    super::cancel(&app, &job_id)
+}
@@ -0,0 +1,216 @@
+//! Background job manager
+//!
+//! Long operations that outlive the command that started them (creating and
+//! deleting worktrees, status refreshes, cleanup) run as jobs: each gets an
+//! ID, is listed by `list_background_jobs`, reports progress and can be asked
+//! to stop.
+//!
+//! Jobs run on their own thread. Cancellation is cooperative: a job checks
+//! [`JobContext::is_cancelled`] between steps and cleans up after itself.
+//!
+//! Events:
+//! - `jobs:started` / `jobs:progress` / `jobs:finished`, each carrying the
+//!   job's [`BackgroundJob`] snapshot
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::http_server::EmitExt;
+
+pub mod commands;
+
+/// Finished jobs kept for `list_background_jobs`
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    CreateWorktree,
+    CheckoutPr,
+    DeleteWorktree,
+    StatusRefresh,
+    Cleanup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of a job, as listed and emitted
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundJob {
+    pub id: String,
+    pub kind: JobKind,
+    /// Human-readable description, e.g. "Create worktree fuzzy-tiger"
+    pub label: String,
+    pub state: JobState,
+    /// Current step
+    pub message: Option<String>,
+    /// Completion from 0.0 to 1.0, when known
+    pub progress: Option<f32>,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// Whether a cancellation was requested
+    pub cancel_requested: bool,
+}
+
+struct RunningJob {
+    job: BackgroundJob,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct Jobs {
+    running: Vec<RunningJob>,
+    finished: VecDeque<BackgroundJob>,
+}
+
+static JOBS: Lazy<Mutex<Jobs>> = Lazy::new(|| Mutex::new(Jobs::default()));
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn emit(app: &AppHandle, event: &str, job: &BackgroundJob) {
+    if let Err(e) = app.emit_all(event, job) {
+        log::error!("Failed to emit {event} event: {e}");
+    }
+}
+
+/// Handle passed to a running job
+pub struct JobContext {
+    id: String,
+    app: AppHandle,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    /// Whether the job was asked to stop
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Report the current step, and optionally how far along the job is
+    pub fn progress(&self, message: impl Into<String>, progress: Option<f32>) {
+        let snapshot = {
+            let mut jobs = JOBS.lock().unwrap();
+            let Some(running) = jobs.running.iter_mut().find(|r| r.job.id == self.id) else {
+                return;
+            };
+            running.job.message = Some(message.into());
+            running.job.progress = progress.map(|p| p.clamp(0.0, 1.0));
+            running.job.clone()
+        };
+        emit(&self.app, "jobs:progress", &snapshot);
+    }
+}
+
+/// Run `f` as a background job on its own thread and return the job ID.
+/// An `Err` marks the job failed (or cancelled, if cancellation was requested).
+pub fn spawn<F>(app: &AppHandle, kind: JobKind, label: impl Into<String>, f: F) -> String
+where
+    F: FnOnce(&JobContext) -> Result<(), String> + Send + 'static,
+{
+    let id = uuid::Uuid::new_v4().to_string();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let job = BackgroundJob {
+        id: id.clone(),
+        kind,
+        label: label.into(),
+        state: JobState::Running,
+        message: None,
+        progress: None,
+        error: None,
+        started_at: now(),
+        finished_at: None,
+        cancel_requested: false,
+    };
+    log::trace!("Starting job {id}: {}", job.label);
+    JOBS.lock().unwrap().running.push(RunningJob {
+        job: job.clone(),
+        cancelled: cancelled.clone(),
+    });
+    emit(app, "jobs:started", &job);
+
+    let ctx = JobContext {
+        id: id.clone(),
+        app: app.clone(),
+        cancelled,
+    };
+    std::thread::spawn(move || {
+        let result = f(&ctx);
+        finish(&ctx, result);
+    });
+    id
+}
+
+fn finish(ctx: &JobContext, result: Result<(), String>) {
+    let snapshot = {
+        let mut jobs = JOBS.lock().unwrap();
+        let Some(index) = jobs.running.iter().position(|r| r.job.id == ctx.id) else {
+            return;
+        };
+        let mut job = jobs.running.remove(index).job;
+        job.finished_at = Some(now());
+        job.state = match &result {
+            Ok(()) => JobState::Completed,
+            Err(_) if ctx.is_cancelled() => JobState::Cancelled,
+            Err(_) => JobState::Failed,
+        };
+        job.error = result.err();
+        if job.state == JobState::Completed {
+            job.progress = Some(1.0);
+        }
+        jobs.finished.push_front(job.clone());
+        jobs.finished.truncate(MAX_FINISHED_JOBS);
+        job
+    };
+    match (&snapshot.state, &snapshot.error) {
+        (JobState::Failed, Some(e)) => log::warn!("Job failed ({}): {e}", snapshot.label),
+        (JobState::Cancelled, _) => log::trace!("Job cancelled: {}", snapshot.label),
+        _ => log::trace!("Job completed: {}", snapshot.label),
+    }
+    emit(&ctx.app, "jobs:finished", &snapshot);
+}
+
+/// Running jobs (oldest first), then recently finished ones (newest first)
+pub fn list() -> Vec<BackgroundJob> {
+    let jobs = JOBS.lock().unwrap();
+    jobs.running
+        .iter()
+        .map(|r| r.job.clone())
+        .chain(jobs.finished.iter().cloned())
+        .collect()
+}
+
+/// Ask a running job to stop. Returns false if it isn't running.
+pub fn cancel(app: &AppHandle, job_id: &str) -> bool {
+    let snapshot = {
+        let mut jobs = JOBS.lock().unwrap();
+        let Some(running) = jobs.running.iter_mut().find(|r| r.job.id == job_id) else {
+            return false;
+        };
+        running.cancelled.store(true, Ordering::Relaxed);
+        running.job.cancel_requested = true;
+        running.job.clone()
+    };
+    log::trace!("Cancellation requested for job {job_id}");
+    emit(app, "jobs:progress", &snapshot);
+    true
+}
@@ -9,13 +9,31 @@ use tauri::{AppHandle, Emitter, Manager};
 use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
 
 mod background_tasks;
+mod backup;
 mod chat;
 mod claude_cli;
+pub mod cli;
+mod command_audit;
+mod db;
+mod encryption;
 mod gh_cli;
 pub mod http_server;
+mod integrity;
+mod jobs;
+mod locations;
+pub mod notifications;
+mod onboarding;
 mod platform;
+mod plugins;
 mod projects;
+mod redaction;
+mod search;
+mod slack;
+mod storage_report;
 mod terminal;
+mod tray;
+mod ui_state;
+mod updater;
 
 // Validation functions
 fn validate_filename(filename: &str) -> Result<(), String> {
@@ -80,9 +98,17 @@ pub struct AppPreferences {
     #[serde(default = "default_effort_level")]
     pub default_effort_level: String, // Effort level for Opus 4.6: low, medium, high, max
     #[serde(default = "default_terminal")]
-    pub terminal: String, // Terminal app: terminal, warp, ghostty
+    pub terminal: String, // Terminal app: terminal, warp, ghostty, iterm2, alacritty, kitty, wezterm (macOS), powershell, cmd, windows-terminal (Windows), custom
+    #[serde(default)]
+    pub custom_terminal_command: Option<String>, // Command for the "custom" terminal, {path} = worktree path
     #[serde(default = "default_editor")]
-    pub editor: String, // Editor app: vscode, cursor, xcode
+    pub editor: String, // Editor app: vscode, cursor, xcode, intellij, webstorm, rustrover, goland, pycharm
+    #[serde(default)]
+    pub browser: Option<String>, // Browser for GitHub pages, PR links and dev-server URLs: chrome, chromium, edge, brave, arc, firefox, safari (None = system default)
+    #[serde(default)]
+    pub browser_profile: Option<String>, // Browser profile to open URLs in (Chromium profile directory or Firefox profile name)
+    #[serde(default)]
+    pub diff_tool: Option<String>, // External diff/merge tool for git difftool/mergetool: kdiff3, meld, bc, ... (None = git config)
     #[serde(default = "default_auto_branch_naming")]
     pub auto_branch_naming: bool, // Automatically generate branch names from first message
     #[serde(default = "default_branch_naming_model")]
@@ -91,6 +117,10 @@ pub struct AppPreferences {
     pub auto_session_naming: bool, // Automatically generate session names from first message
     #[serde(default = "default_session_naming_model")]
     pub session_naming_model: String, // Model for generating session names: haiku, sonnet, opus
+    #[serde(default)]
+    pub max_concurrent_sessions: u32, // Most Claude CLI processes running at once; further messages are queued (0 = no limit)
+    #[serde(default = "default_auto_snapshots")]
+    pub auto_snapshots: bool, // Snapshot worktrees before merges, hook fixes and build/yolo runs
     #[serde(default = "default_font_size")]
     pub ui_font_size: u32, // Font size for UI text in pixels (10-24)
     #[serde(default = "default_font_size")]
@@ -133,6 +163,8 @@ pub struct AppPreferences {
     pub file_edit_mode: String, // How to edit files: inline (CodeMirror) or external (VS Code, etc.)
     #[serde(default)]
     pub ai_language: String, // Preferred language for AI responses (empty = default)
+    #[serde(default)]
+    pub ai_language_overrides: AiLanguageOverrides, // Per-artifact languages that take precedence over ai_language
     #[serde(default = "default_allow_web_tools_in_plan_mode")]
     pub allow_web_tools_in_plan_mode: bool, // Allow WebFetch/WebSearch in plan mode without prompts
     #[serde(default = "default_waiting_sound")]
@@ -149,12 +181,60 @@ pub struct AppPreferences {
     pub http_server_localhost_only: bool, // Bind to localhost only (more secure)
     #[serde(default = "default_http_server_token_required")]
     pub http_server_token_required: bool, // Require token for web access (default true)
+    #[serde(default)]
+    pub automation_api_enabled: bool, // Serve the local automation API (127.0.0.1 only)
+    #[serde(default = "default_automation_api_port")]
+    pub automation_api_port: u16, // Automation API port (default: 3457)
+    #[serde(default)]
+    pub automation_api_token: Option<String>, // Bearer token for the automation API (generated when enabled)
     #[serde(default = "default_auto_archive_on_pr_merged")]
     pub auto_archive_on_pr_merged: bool, // Auto-archive worktrees when their PR is merged
     #[serde(default = "default_show_keybinding_hints")]
     pub show_keybinding_hints: bool, // Show keyboard shortcut hints at bottom of canvas views
     #[serde(default)]
     pub debug_mode_enabled: bool, // Show debug panel in chat sessions (default: false)
+    #[serde(default = "default_update_channel")]
+    pub update_channel: String, // App update channel: stable, beta
+    #[serde(default = "default_defer_updates_until_idle")]
+    pub defer_updates_until_idle: bool, // Wait for running sessions to finish before installing updates
+    #[serde(default)]
+    pub delete_worktrees_to_trash: bool, // Permanently deleting a worktree moves it to the OS trash
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32, // Days a trashed worktree can be restored before its branch and sessions are deleted
+    #[serde(default)]
+    pub encrypt_at_rest: bool, // Encrypt transcripts, pasted texts and GitHub contexts on disk (key in the OS keychain)
+    #[serde(default)]
+    pub auto_archive_stale_days: u32, // Archive worktrees with no commits, sessions or changes for this many days (0 = disabled)
+    #[serde(default = "default_auto_update_from_base")]
+    pub auto_update_from_base: String, // Update worktrees when their base branch moves: off, rebase, merge
+    #[serde(default = "default_ci_failure_action")]
+    pub ci_failure_action: String, // When a PR's checks start failing: off, offer (notify), auto (start a fix-CI session)
+    #[serde(default)]
+    pub scheduled_review_hours: u32, // Review worktrees with new commits in the background every this many hours (0 = disabled)
+    #[serde(default = "default_load_dev_environment")]
+    pub load_dev_environment: bool, // Inject .envrc (direnv) / flake.nix (Nix) environments into terminals, scripts and sessions
+    #[serde(default)]
+    pub wsl_distribution: Option<String>, // WSL distribution to run CLIs in on Windows (None = WSL default)
+    #[serde(default)]
+    pub install_missing_toolchains: bool, // Install missing mise/asdf runtimes before running setup scripts (otherwise offer to)
+    #[serde(default)]
+    pub jira_base_url: Option<String>, // Jira site URL, e.g. https://acme.atlassian.net (None = Jira disabled)
+    #[serde(default)]
+    pub jira_email: Option<String>, // Account email for Jira Cloud API tokens (None = token is a Data Center PAT)
+    #[serde(default)]
+    pub jira_api_token: Option<String>, // Jira API token or personal access token
+    #[serde(default)]
+    pub bitbucket_username: Option<String>, // Bitbucket Cloud username for API requests (None = anonymous, public repositories only)
+    #[serde(default)]
+    pub bitbucket_app_password: Option<String>, // Bitbucket Cloud app password for bitbucket_username
+    #[serde(default)]
+    pub linear_api_key: Option<String>, // Linear personal API key (None = Linear disabled)
+    #[serde(default = "default_linear_sync_issue_status")]
+    pub linear_sync_issue_status: bool, // Move loaded Linear issues to In Review / Done when the worktree's PR opens / merges
+    #[serde(default)]
+    pub webhooks: Vec<notifications::webhooks::WebhookRule>, // Outgoing webhooks fired on session and PR events
+    #[serde(default)]
+    pub commit_rules: CommitRules, // Conventional Commits rules for generated commit messages and PR titles
 }
 
 fn default_auto_branch_naming() -> bool {
@@ -169,6 +249,10 @@ fn default_auto_session_naming() -> bool {
     true // Enabled by default
 }
 
+fn default_auto_snapshots() -> bool {
+    true // Enabled by default
+}
+
 fn default_session_grouping_enabled() -> bool {
     true // Enabled by default
 }
@@ -288,6 +372,10 @@ fn default_http_server_port() -> u16 {
     3456
 }
 
+fn default_automation_api_port() -> u16 {
+    3457
+}
+
 fn default_http_server_token_required() -> bool {
     true // Require token by default for security
 }
@@ -300,6 +388,34 @@ fn default_show_keybinding_hints() -> bool {
     true // Enabled by default
 }
 
+fn default_update_channel() -> String {
+    "stable".to_string()
+}
+
+fn default_defer_updates_until_idle() -> bool {
+    true // Never interrupt running sessions by default
+}
+
+fn default_trash_retention_days() -> u32 {
+    7 // Trashed worktrees can be restored for a week
+}
+
+fn default_auto_update_from_base() -> String {
+    "off".to_string() // Never rewrite branches without being asked
+}
+
+fn default_ci_failure_action() -> String {
+    "offer".to_string() // Notify, but let the user start the session
+}
+
+fn default_load_dev_environment() -> bool {
+    true
+}
+
+fn default_linear_sync_issue_status() -> bool {
+    true
+}
+
 // =============================================================================
 // Magic Prompts - Customizable prompts for AI-powered features
 // =============================================================================
@@ -321,6 +437,10 @@ pub struct MagicPrompts {
     pub context_summary: String,
     #[serde(default = "default_resolve_conflicts_prompt")]
     pub resolve_conflicts: String,
+    #[serde(default = "default_fix_ci_prompt")]
+    pub fix_ci: String,
+    #[serde(default = "default_investigate_ci_failure_prompt")]
+    pub investigate_ci_failure: String,
 }
 
 fn default_investigate_issue_prompt() -> String {
@@ -491,6 +611,69 @@ After resolving each file's conflicts, stage it with `git add`. Then run the app
         .to_string()
 }
 
+fn default_fix_ci_prompt() -> String {
+    r#"<task>
+
+The CI checks of PR #{prNumber} are failing: {failingChecks}
+
+</task>
+
+
+<instructions>
+
+1. Read the failing job logs below and identify the first real error in each
+2. Find the cause in the code (a test, a lint, the build, or the CI configuration)
+3. Reproduce it locally if possible, using the same command CI runs
+4. Fix the cause rather than silencing the check
+5. Re-run the failing command locally to confirm the fix
+6. Commit the fix with a Conventional Commits message and push
+
+</instructions>
+
+
+<guidelines>
+
+- If the failure is flaky or unrelated to this branch, say so instead of changing code
+- Keep the fix focused on what CI reported
+
+</guidelines>"#
+        .to_string()
+}
+
+fn default_investigate_ci_failure_prompt() -> String {
+    r#"<task>
+
+Investigate why the CI checks of PR #{prNumber} are failing: {failingChecks}
+
+</task>
+
+
+<recent-commits>
+
+{recentCommits}
+
+</recent-commits>
+
+
+<instructions>
+
+1. Read the failing job logs below and find the first real error in each
+2. Check whether one of the recent commits introduced it
+3. Explore the code or CI configuration involved
+4. Explain the root cause and propose a fix, with specific files and line numbers
+
+</instructions>
+
+
+<guidelines>
+
+- Don't change any code yet; wait for confirmation of the proposed fix
+- Say so if the failure looks flaky or unrelated to this branch
+
+</guidelines>"#
+        .to_string()
+}
+
 /// Per-prompt model overrides for magic prompts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MagicPromptModels {
@@ -506,6 +689,10 @@ pub struct MagicPromptModels {
     pub context_summary_model: String,
     #[serde(default = "default_model")]
     pub resolve_conflicts_model: String,
+    #[serde(default = "default_model")]
+    pub fix_ci_model: String,
+    #[serde(default = "default_model")]
+    pub investigate_ci_failure_model: String,
 }
 
 fn default_haiku_model() -> String {
@@ -521,6 +708,8 @@ impl Default for MagicPromptModels {
             code_review_model: default_haiku_model(),
             context_summary_model: default_model(),
             resolve_conflicts_model: default_model(),
+            fix_ci_model: default_model(),
+            investigate_ci_failure_model: default_model(),
         }
     }
 }
@@ -535,10 +724,133 @@ impl Default for MagicPrompts {
             code_review: default_code_review_prompt(),
             context_summary: default_context_summary_prompt(),
             resolve_conflicts: default_resolve_conflicts_prompt(),
+            fix_ci: default_fix_ci_prompt(),
+            investigate_ci_failure: default_investigate_ci_failure_prompt(),
         }
     }
 }
 
+// =============================================================================
+// Commit Rules - Conventional Commits checks for generated messages
+// =============================================================================
+
+/// Rules AI-generated commit messages and PR titles must follow
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommitRules {
+    #[serde(default = "default_commit_rules_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_commit_types")]
+    pub types: Vec<String>, // Allowed types (feat, fix, ...)
+    #[serde(default)]
+    pub scopes: Vec<String>, // Allowed scopes (empty = any scope)
+    #[serde(default)]
+    pub require_scope: bool,
+    #[serde(default = "default_max_subject_length")]
+    pub max_subject_length: usize, // Longest allowed first line, in characters
+    #[serde(default)]
+    pub reject_invalid: bool, // Fail on generated messages that break the rules instead of fixing or regenerating them
+    #[serde(default)]
+    pub check_manual_commits: bool, // Also reject hand-written messages that break the rules
+}
+
+fn default_commit_rules_enabled() -> bool {
+    true
+}
+
+fn default_commit_types() -> Vec<String> {
+    [
+        "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore",
+        "revert",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_max_subject_length() -> usize {
+    72
+}
+
+impl Default for CommitRules {
+    fn default() -> Self {
+        Self {
+            enabled: default_commit_rules_enabled(),
+            types: default_commit_types(),
+            scopes: Vec::new(),
+            require_scope: false,
+            max_subject_length: default_max_subject_length(),
+            reject_invalid: false,
+            check_manual_commits: false,
+        }
+    }
+}
+
+// =============================================================================
+// AI Language - per-artifact language overrides
+// =============================================================================
+
+/// Kinds of AI-generated text that can be written in their own language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiArtifact {
+    Chat,
+    Naming,
+    CommitMessage,
+    PrContent,
+    CodeReview,
+    ContextSummary,
+    SessionDigest,
+}
+
+/// Languages per artifact (None or empty = use `ai_language`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AiLanguageOverrides {
+    #[serde(default)]
+    pub chat: Option<String>,
+    #[serde(default)]
+    pub naming: Option<String>, // Branch and session names
+    #[serde(default)]
+    pub commit_message: Option<String>,
+    #[serde(default)]
+    pub pr_content: Option<String>,
+    #[serde(default)]
+    pub code_review: Option<String>,
+    #[serde(default)]
+    pub context_summary: Option<String>,
+    #[serde(default)]
+    pub session_digest: Option<String>,
+}
+
+impl AppPreferences {
+    /// Language set specifically for an artifact
+    pub fn ai_language_override(&self, artifact: AiArtifact) -> Option<&str> {
+        let overrides = &self.ai_language_overrides;
+        let language = match artifact {
+            AiArtifact::Chat => &overrides.chat,
+            AiArtifact::Naming => &overrides.naming,
+            AiArtifact::CommitMessage => &overrides.commit_message,
+            AiArtifact::PrContent => &overrides.pr_content,
+            AiArtifact::CodeReview => &overrides.code_review,
+            AiArtifact::ContextSummary => &overrides.context_summary,
+            AiArtifact::SessionDigest => &overrides.session_digest,
+        };
+        language.as_deref().map(str::trim).filter(|l| !l.is_empty())
+    }
+
+    /// Language an artifact is written in (None = the model's default)
+    pub fn ai_language_for(&self, artifact: AiArtifact) -> Option<&str> {
+        self.ai_language_override(artifact)
+            .or_else(|| Some(self.ai_language.trim()).filter(|l| !l.is_empty()))
+    }
+
+    /// Instruction appended to a generation prompt to write in the artifact's
+    /// language
+    pub fn ai_language_instruction(&self, artifact: AiArtifact) -> Option<String> {
+        self.ai_language_for(artifact).map(|lang| {
+            format!("Write all text of your response in {lang}. Keep code, identifiers and JSON keys as they are.")
+        })
+    }
+}
+
 impl Default for AppPreferences {
     fn default() -> Self {
         Self {
@@ -546,11 +858,17 @@ impl Default for AppPreferences {
             selected_model: default_model(),
             thinking_level: default_thinking_level(),
             terminal: default_terminal(),
+            custom_terminal_command: None,
             editor: default_editor(),
+            browser: None,
+            browser_profile: None,
+            diff_tool: None,
             auto_branch_naming: default_auto_branch_naming(),
             branch_naming_model: default_branch_naming_model(),
             auto_session_naming: default_auto_session_naming(),
             session_naming_model: default_session_naming_model(),
+            max_concurrent_sessions: 0,
+            auto_snapshots: default_auto_snapshots(),
             ui_font_size: 16,
             chat_font_size: 16,
             ui_font: default_ui_font(),
@@ -572,6 +890,7 @@ impl Default for AppPreferences {
             magic_prompt_models: MagicPromptModels::default(),
             file_edit_mode: default_file_edit_mode(),
             ai_language: String::new(),
+            ai_language_overrides: AiLanguageOverrides::default(),
             allow_web_tools_in_plan_mode: default_allow_web_tools_in_plan_mode(),
             waiting_sound: default_waiting_sound(),
             review_sound: default_review_sound(),
@@ -580,10 +899,34 @@ impl Default for AppPreferences {
             http_server_token: None,
             http_server_localhost_only: true, // Default to localhost-only for security
             http_server_token_required: default_http_server_token_required(),
+            automation_api_enabled: false,
+            automation_api_port: default_automation_api_port(),
+            automation_api_token: None,
             auto_archive_on_pr_merged: default_auto_archive_on_pr_merged(),
             show_keybinding_hints: default_show_keybinding_hints(),
             debug_mode_enabled: false,
             default_effort_level: default_effort_level(),
+            update_channel: default_update_channel(),
+            defer_updates_until_idle: default_defer_updates_until_idle(),
+            delete_worktrees_to_trash: false,
+            trash_retention_days: default_trash_retention_days(),
+            encrypt_at_rest: false,
+            auto_archive_stale_days: 0,
+            auto_update_from_base: default_auto_update_from_base(),
+            ci_failure_action: default_ci_failure_action(),
+            scheduled_review_hours: 0,
+            load_dev_environment: default_load_dev_environment(),
+            wsl_distribution: None,
+            install_missing_toolchains: false,
+            jira_base_url: None,
+            jira_email: None,
+            jira_api_token: None,
+            bitbucket_username: None,
+            bitbucket_app_password: None,
+            linear_api_key: None,
+            linear_sync_issue_status: default_linear_sync_issue_status(),
+            webhooks: Vec::new(),
+            commit_rules: CommitRules::default(),
         }
     }
 }
@@ -684,10 +1027,7 @@ impl Default for UIState {
 }
 
 fn get_preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&app_data_dir)
@@ -721,10 +1061,34 @@ async fn load_preferences(app: AppHandle) -> Result<AppPreferences, String> {
 }
 
 #[tauri::command]
-async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Result<(), String> {
+async fn save_preferences(app: AppHandle, mut preferences: AppPreferences) -> Result<(), String> {
     // Validate theme value
     validate_theme(&preferences.theme)?;
 
+    // The frontend never sees the automation API token, so it saves None; keep
+    // the stored one (it's changed through `regenerate_automation_api_token`)
+    if preferences.automation_api_token.is_none() {
+        preferences.automation_api_token = load_preferences(app.clone())
+            .await
+            .ok()
+            .and_then(|stored| stored.automation_api_token);
+    }
+    // The automation API always needs a token; create it the first time it's enabled
+    if preferences.automation_api_enabled && preferences.automation_api_token.is_none() {
+        preferences.automation_api_token = Some(http_server::auth::generate_token());
+    }
+
+    // Webhooks need a valid URL and payload template
+    notifications::webhooks::prepare(&mut preferences.webhooks)?;
+
+    projects::conventional_commits::validate_rules(&preferences.commit_rules)?;
+
+    // Enabling encryption needs a key; fail before saving if the keychain is unavailable
+    let encryption_changed = preferences.encrypt_at_rest != encryption::is_enabled();
+    if encryption_changed && preferences.encrypt_at_rest {
+        encryption::ensure_key()?;
+    }
+
     log::trace!("Saving preferences to disk: {preferences:?}");
     let prefs_path = get_preferences_path(&app)?;
 
@@ -750,14 +1114,46 @@ async fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Result
     })?;
 
     log::trace!("Successfully saved preferences to {prefs_path:?}");
+
+    background_tasks::auto_update::set_mode(
+        background_tasks::auto_update::AutoUpdateMode::from_preference(
+            &preferences.auto_update_from_base,
+        ),
+    );
+    projects::dev_env::set_enabled(preferences.load_dev_environment);
+    projects::toolchain::set_auto_install(preferences.install_missing_toolchains);
+    platform::browser::set_preferred(
+        preferences.browser.clone(),
+        preferences.browser_profile.clone(),
+    );
+    if let Err(e) = http_server::automation::apply(
+        &app,
+        preferences.automation_api_enabled,
+        preferences.automation_api_port,
+        preferences.automation_api_token.clone(),
+    )
+    .await
+    {
+        log::error!("Failed to apply automation API preferences: {e}");
+    }
+
+    if encryption_changed {
+        let enabled = preferences.encrypt_at_rest;
+        encryption::set_enabled(enabled);
+        std::thread::spawn(move || match encryption::convert_existing(&app, enabled) {
+            Ok(count) => log::info!(
+                "{} {count} stored file(s)",
+                if enabled { "Encrypted" } else { "Decrypted" }
+            ),
+            Err(e) => log::error!("Failed to convert stored files: {e}"),
+        });
+    }
+
     Ok(())
 }
 
 fn get_ui_state_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&app_data_dir)
@@ -768,6 +1164,12 @@ fn get_ui_state_path(app: &AppHandle) -> Result<PathBuf, String> {
 
 #[tauri::command]
 async fn load_ui_state(app: AppHandle) -> Result<UIState, String> {
+    // A queued save that hasn't hit disk yet is the most recent state
+    if let Some(ui_state) = ui_state::pending_state() {
+        log::trace!("Returning pending UI state");
+        return Ok(ui_state);
+    }
+
     log::trace!("Loading UI state from disk");
     let state_path = get_ui_state_path(&app)?;
 
@@ -790,33 +1192,13 @@ async fn load_ui_state(app: AppHandle) -> Result<UIState, String> {
     Ok(ui_state)
 }
 
+/// Queue UI state for writing. Rapid saves (resizes, tab switches) are
+/// coalesced by the write-behind layer in `ui_state`.
 #[tauri::command]
 async fn save_ui_state(app: AppHandle, ui_state: UIState) -> Result<(), String> {
-    log::trace!("Saving UI state to disk: {ui_state:?}");
+    log::trace!("Queueing UI state save: {ui_state:?}");
     let state_path = get_ui_state_path(&app)?;
-
-    let json_content = serde_json::to_string_pretty(&ui_state).map_err(|e| {
-        log::error!("Failed to serialize UI state: {e}");
-        format!("Failed to serialize UI state: {e}")
-    })?;
-
-    // Write to a temporary file first, then rename (atomic operation)
-    // Use unique temp file to avoid race conditions with concurrent saves
-    let temp_path = state_path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
-
-    std::fs::write(&temp_path, json_content).map_err(|e| {
-        log::error!("Failed to write UI state file: {e}");
-        format!("Failed to write UI state file: {e}")
-    })?;
-
-    std::fs::rename(&temp_path, &state_path).map_err(|e| {
-        // Clean up temp file on rename failure
-        let _ = std::fs::remove_file(&temp_path);
-        log::error!("Failed to finalize UI state file: {e}");
-        format!("Failed to finalize UI state file: {e}")
-    })?;
-
-    log::trace!("Saved UI state to {state_path:?}");
+    ui_state::queue_write(state_path, ui_state);
     Ok(())
 }
 
@@ -859,10 +1241,7 @@ async fn send_native_notification(
 
 // Recovery functions - simple pattern for saving JSON data to disk
 fn get_recovery_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let recovery_dir = app_data_dir.join("recovery");
 
@@ -1188,6 +1567,29 @@ async fn get_http_server_status(
     Ok(http_server::server::get_server_status(app).await)
 }
 
+/// Status of the local automation API (enabled via preferences)
+#[tauri::command]
+async fn get_automation_api_status() -> Result<http_server::automation::AutomationApiStatus, String>
+{
+    Ok(http_server::automation::status().await)
+}
+
+/// The automation API token (None until the API is first enabled)
+#[tauri::command]
+async fn get_automation_api_token(app: AppHandle) -> Result<Option<String>, String> {
+    Ok(load_preferences(app).await?.automation_api_token)
+}
+
+/// Replace the automation API token, invalidating the old one
+#[tauri::command]
+async fn regenerate_automation_api_token(app: AppHandle) -> Result<String, String> {
+    let new_token = http_server::auth::generate_token();
+    let mut prefs = load_preferences(app.clone()).await?;
+    prefs.automation_api_token = Some(new_token.clone());
+    save_preferences(app.clone(), prefs).await?;
+    Ok(new_token)
+}
+
 #[tauri::command]
 async fn regenerate_http_token(app: AppHandle) -> Result<String, String> {
     let new_token = http_server::auth::generate_token();
@@ -1401,8 +1803,86 @@ pub fn run() {
                 }
             }
 
-            // Recover any incomplete runs from previous session (crash recovery)
             let app_handle = app.handle().clone();
+
+            // Resolve configured data/worktree locations before any storage access
+            locations::init(&app_handle);
+
+            // Record external commands from here on
+            command_audit::init(&app_handle);
+
+            // Finish or discard multi-store operations interrupted by a crash
+            match db::journal::recover(&app_handle) {
+                Ok(recovered) => {
+                    if !recovered.is_empty() {
+                        log::info!(
+                            "Recovered {} interrupted operation(s) from previous session",
+                            recovered.len()
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to recover interrupted operations: {e}");
+                }
+            }
+
+            // Apply preferences mirrored by backend state (encryption at rest, auto-update from base,
+            // project environments, automation API)
+            let app_handle_encryption = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Ok(prefs) = load_preferences(app_handle_encryption.clone()).await {
+                    if let Err(e) = http_server::automation::apply(
+                        &app_handle_encryption,
+                        prefs.automation_api_enabled,
+                        prefs.automation_api_port,
+                        prefs.automation_api_token.clone(),
+                    )
+                    .await
+                    {
+                        log::error!("Failed to start automation API: {e}");
+                    }
+                    encryption::set_enabled(prefs.encrypt_at_rest);
+                    background_tasks::auto_update::set_mode(
+                        background_tasks::auto_update::AutoUpdateMode::from_preference(
+                            &prefs.auto_update_from_base,
+                        ),
+                    );
+                    projects::dev_env::set_enabled(prefs.load_dev_environment);
+                    projects::toolchain::set_auto_install(prefs.install_missing_toolchains);
+                    platform::browser::set_preferred(prefs.browser, prefs.browser_profile);
+                }
+            });
+
+            // Finish deleting trashed worktrees whose restore window has passed
+            let app_handle_trash = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let retention_days = load_preferences(app_handle_trash.clone())
+                    .await
+                    .map(|p| p.trash_retention_days)
+                    .unwrap_or_else(|_| default_trash_retention_days());
+                let app = app_handle_trash.clone();
+                let label = "Purge expired trashed worktrees";
+                jobs::spawn(&app, jobs::JobKind::Cleanup, label, move |_job| {
+                    let purged = projects::trash::purge_expired(&app_handle_trash, retention_days)?;
+                    if purged > 0 {
+                        log::trace!("Purged {purged} expired trashed worktree(s)");
+                    }
+                    Ok(())
+                });
+            });
+
+            // Archive worktrees left untouched for longer than the configured window
+            projects::auto_archive::start(app_handle.clone());
+
+            // Index sessions saved before the search index existed
+            let search_app = app_handle.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = search::index_all(&search_app) {
+                    log::warn!("Failed to build session search index: {e}");
+                }
+            });
+
+            // Recover any incomplete runs from previous session (crash recovery)
             match chat::run_log::recover_incomplete_runs(&app_handle) {
                 Ok(recovered) => {
                     if !recovered.is_empty() {
@@ -1523,6 +2003,12 @@ pub fn run() {
             app.manage(task_manager);
             log::trace!("Background task manager initialized");
 
+            if !headless {
+                if let Err(e) = tray::init(app.handle()) {
+                    log::error!("Failed to create tray icon: {e}");
+                }
+            }
+
             // Initialize HTTP server infrastructure
             let (broadcaster, _) = http_server::WsBroadcaster::new();
             app.manage(broadcaster);
@@ -1600,6 +2086,8 @@ pub fn run() {
             projects::set_git_identity,
             projects::list_projects,
             projects::add_project,
+            projects::scan_projects_dir,
+            projects::scan_and_import_projects,
             projects::init_git_in_folder,
             projects::init_project,
             projects::remove_project,
@@ -1617,15 +2105,40 @@ pub fn run() {
             projects::list_archived_worktrees,
             projects::import_worktree,
             projects::permanently_delete_worktree,
+            projects::list_trashed_worktrees,
+            projects::restore_trashed_worktree,
             projects::cleanup_old_archives,
+            projects::archive_stale_worktrees,
             projects::delete_all_archives,
             projects::rename_worktree,
             projects::open_worktree_in_finder,
             projects::open_project_worktrees_folder,
             projects::open_worktree_in_terminal,
             projects::open_worktree_in_editor,
+            projects::apply_patch_hunk,
+            projects::discard_hunk,
+            projects::open_diff_in_external_tool,
+            projects::open_conflict_in_merge_tool,
             projects::open_pull_request,
             projects::create_pr_with_ai_content,
+            projects::pr_checklist::run_pr_checklist,
+            projects::license_check::check_dependency_licenses,
+            projects::review_report::export_review_report,
+            projects::review_sarif::export_review_sarif,
+            projects::lint_import::import_lint_findings,
+            projects::test_runner::run_tests,
+            projects::test_runner::get_test_results,
+            projects::coverage::get_diff_coverage,
+            projects::security_audit::run_security_audit,
+            projects::cleanup::get_cleanup_suggestions,
+            projects::cleanup::apply_cleanup_action,
+            projects::dashboard::get_project_dashboard,
+            projects::ci_checks::list_pr_checks,
+            projects::ci_checks::get_check_run_log,
+            projects::ci_checks::investigate_ci_failure,
+            redaction::redact_secrets,
+            projects::stacks::restack_worktree,
+            projects::stacks::restack_children,
             projects::create_commit_with_ai,
             projects::run_review_with_ai,
             projects::commit_changes,
@@ -1634,14 +2147,32 @@ pub fn run() {
             projects::get_github_branch_url,
             projects::get_github_repo_url,
             projects::list_worktree_files,
+            projects::list_worktree_files_page,
+            projects::search_worktree_files,
+            projects::search_in_worktree,
             projects::get_project_branches,
             projects::update_project_settings,
+            projects::list_remotes,
+            projects::detect_devcontainer,
+            projects::list_packages,
+            projects::detect_toolchains,
+            projects::install_toolchains,
+            projects::env_profiles::list_env_profiles,
+            projects::env_profiles::set_worktree_env_profile,
+            projects::snapshots::create_snapshot,
+            projects::snapshots::list_snapshots,
+            projects::snapshots::restore_snapshot,
+            projects::compose_up,
+            projects::compose_down,
+            projects::compose_logs,
             projects::get_pr_prompt,
             projects::get_review_prompt,
             projects::save_worktree_pr,
             projects::clear_worktree_pr,
             projects::update_worktree_cached_status,
             projects::rebase_worktree,
+            projects::plan_interactive_rebase,
+            projects::execute_interactive_rebase,
             projects::has_uncommitted_changes,
             projects::get_git_diff,
             projects::git_pull,
@@ -1662,6 +2193,17 @@ pub fn run() {
             projects::load_issue_context,
             projects::list_loaded_issue_contexts,
             projects::remove_issue_context,
+            // Jira commands
+            projects::jira::list_jira_issues,
+            projects::jira::load_jira_issue_context,
+            projects::jira::list_loaded_jira_contexts,
+            projects::jira::remove_jira_issue_context,
+            projects::jira::create_worktree_from_jira_issue,
+            // Linear commands
+            projects::linear::list_linear_issues,
+            projects::linear::load_linear_issue_context,
+            projects::linear::list_loaded_linear_contexts,
+            projects::linear::remove_linear_issue_context,
             // GitHub PR commands
             projects::list_github_prs,
             projects::search_github_prs,
@@ -1699,7 +2241,10 @@ pub fn run() {
             chat::get_sessions,
             chat::list_all_sessions,
             chat::get_session,
+            chat::get_session_messages,
+            chat::export_session,
             chat::create_session,
+            chat::fork_session,
             chat::rename_session,
             chat::update_session_state,
             chat::close_session,
@@ -1717,6 +2262,10 @@ pub fn run() {
             chat::set_session_model,
             chat::set_session_thinking_level,
             chat::cancel_chat_message,
+            chat::prioritize_chat_session,
+            chat::tool_policy::get_tool_policy,
+            chat::tool_policy::set_tool_policy,
+            chat::change_audit::get_change_audit,
             chat::has_running_sessions,
             chat::save_cancelled_message,
             chat::mark_plan_approved,
@@ -1726,12 +2275,18 @@ pub fn run() {
             chat::delete_pasted_image,
             // Chat commands - Text paste handling
             chat::save_pasted_text,
+            chat::save_pasted_document,
+            chat::save_dropped_document,
             chat::delete_pasted_text,
             chat::read_pasted_text,
+            chat::paste_history::list_paste_history,
+            chat::paste_history::reattach_paste,
+            chat::paste_history::delete_paste_history_entry,
             // Chat commands - Plan file handling
             chat::read_plan_file,
             // Chat commands - File content preview/edit
             chat::read_file_content,
+            chat::read_file_preview,
             chat::write_file_content,
             chat::open_file_in_default_app,
             // Chat commands - Saved context handling
@@ -1761,6 +2316,18 @@ pub fn run() {
             gh_cli::check_gh_cli_auth,
             gh_cli::get_available_gh_versions,
             gh_cli::install_gh_cli,
+            // Onboarding commands
+            onboarding::commands::get_onboarding_state,
+            onboarding::commands::complete_onboarding_step,
+            // Plugin commands
+            plugins::commands::list_plugins,
+            plugins::commands::run_plugin_command,
+            plugins::commands::get_plugin_context,
+            plugins::commands::set_plugin_trusted,
+            // Slack commands
+            slack::commands::send_slack_test_message,
+            // Notification commands
+            notifications::commands::send_test_webhook,
             // Background task commands
             background_tasks::commands::set_app_focus_state,
             background_tasks::commands::set_active_worktree_for_polling,
@@ -1770,16 +2337,59 @@ pub fn run() {
             background_tasks::commands::set_remote_poll_interval,
             background_tasks::commands::get_remote_poll_interval,
             background_tasks::commands::trigger_immediate_remote_poll,
+            background_tasks::commands::set_project_poll_overrides,
+            background_tasks::commands::get_polling_status,
+            background_tasks::commands::set_polling_paused,
+            background_tasks::commands::list_scheduled_jobs,
+            background_tasks::commands::save_scheduled_job,
+            background_tasks::commands::delete_scheduled_job,
+            background_tasks::commands::run_scheduled_job_now,
+            background_tasks::commands::get_scheduled_job_runs,
+            background_tasks::commands::get_time_report,
+            background_tasks::commands::get_scheduled_reviews,
+            // App update commands
+            updater::commands::check_for_app_update,
+            updater::commands::get_release_notes,
+            updater::commands::install_app_update,
+            updater::commands::get_deferred_update_status,
+            updater::commands::cancel_deferred_update,
+            // Backup commands
+            backup::commands::create_backup,
+            backup::commands::restore_backup,
+            // Storage location commands
+            locations::commands::get_storage_locations,
+            locations::commands::set_worktrees_root,
+            locations::commands::set_data_location,
+            // WSL commands
+            platform::commands::list_wsl_distributions,
+            platform::commands::validate_wsl_distribution,
+            platform::commands::open_url,
+            // Storage report commands
+            search::commands::search_sessions,
+            storage_report::commands::get_storage_report,
+            storage_report::commands::cleanup_storage_category,
+            storage_report::commands::get_worktree_disk_usage,
+            // Background job commands
+            jobs::commands::list_background_jobs,
+            jobs::commands::cancel_background_job,
+            // Data integrity commands
+            integrity::commands::verify_data_integrity,
+            // Command audit log commands
+            command_audit::commands::get_command_audit_log,
             // HTTP server commands
             start_http_server,
             stop_http_server,
             get_http_server_status,
+            get_automation_api_status,
+            get_automation_api_token,
+            regenerate_automation_api_token,
             regenerate_http_token,
         ])
         .build(tauri::generate_context!())
         .expect("error building tauri application")
         .run(move |_app_handle, event| match &event {
             tauri::RunEvent::Exit => {
+                ui_state::flush();
                 eprintln!("[TERMINAL CLEANUP] RunEvent::Exit received");
                 let killed = terminal::cleanup_all_terminals();
                 eprintln!("[TERMINAL CLEANUP] Killed {killed} terminal(s)");
@@ -1790,6 +2400,7 @@ pub fn run() {
                     api.prevent_exit();
                     return;
                 }
+                ui_state::flush();
                 eprintln!("[TERMINAL CLEANUP] RunEvent::ExitRequested received");
                 let killed = terminal::cleanup_all_terminals();
                 eprintln!("[TERMINAL CLEANUP] Killed {killed} terminal(s) on ExitRequested");
@@ -1800,6 +2411,7 @@ pub fn run() {
                     if headless {
                         return;
                     }
+                    ui_state::flush();
                     eprintln!("[TERMINAL CLEANUP] Window {label} close requested");
                     let killed = terminal::cleanup_all_terminals();
                     eprintln!("[TERMINAL CLEANUP] Killed {killed} terminal(s) on CloseRequested");
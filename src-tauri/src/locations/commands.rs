@@ -0,0 +1,180 @@
+//! Tauri commands for configuring storage locations
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::{
+    app_cache_dir, app_config_dir, app_data_dir, current, default_app_data_dir,
+    default_worktrees_root, move_data_dir, save, validate_dir, worktrees_root, LocationsConfig,
+};
+use crate::projects::types::SessionType;
+
+/// Effective and default storage locations
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageLocations {
+    pub data_dir: String,
+    pub default_data_dir: String,
+    pub worktrees_root: String,
+    pub default_worktrees_root: String,
+    /// Where `locations.json` is kept
+    pub config_dir: String,
+    /// Where recreatable files (e.g. download scratch space) go
+    pub cache_dir: String,
+}
+
+/// A worktree that could not be moved to the new root
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeMoveFailure {
+    pub worktree_id: String,
+    pub path: String,
+    pub error: String,
+}
+
+/// Result of changing the worktrees root
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreesRootChange {
+    pub locations: StorageLocations,
+    pub moved: u32,
+    pub failed: Vec<WorktreeMoveFailure>,
+}
+
+fn path_string(path: PathBuf) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Get the current and default storage locations
+#[tauri::command]
+pub async fn get_storage_locations(app: AppHandle) -> Result<StorageLocations, String> {
+    Ok(StorageLocations {
+        data_dir: path_string(app_data_dir(&app)?),
+        default_data_dir: path_string(default_app_data_dir(&app)?),
+        worktrees_root: path_string(worktrees_root()?),
+        default_worktrees_root: path_string(default_worktrees_root()?),
+        config_dir: path_string(app_config_dir(&app)?),
+        cache_dir: path_string(app_cache_dir(&app)?),
+    })
+}
+
+/// Change where new worktrees are created (`None` resets to ~/jean).
+///
+/// With `move_existing`, worktrees under the old root are moved with
+/// `git worktree move` and their stored paths updated. Worktrees that fail to
+/// move (e.g. git can't move across volumes) are reported and left in place.
+#[tauri::command]
+pub async fn set_worktrees_root(
+    app: AppHandle,
+    path: Option<String>,
+    move_existing: bool,
+) -> Result<WorktreesRootChange, String> {
+    let old_root = worktrees_root()?;
+    let new_root = match &path {
+        Some(p) => validate_dir(p)?,
+        None => default_worktrees_root()?,
+    };
+    std::fs::create_dir_all(&new_root)
+        .map_err(|e| format!("Failed to create worktrees root: {e}"))?;
+
+    log::trace!("Changing worktrees root from {old_root:?} to {new_root:?}");
+
+    save(
+        &app,
+        LocationsConfig {
+            worktrees_root: path.map(|_| path_string(new_root.clone())),
+            ..current()
+        },
+    )?;
+
+    let mut moved = 0;
+    let mut failed = Vec::new();
+
+    if move_existing && old_root != new_root {
+        let data = crate::projects::storage::load_projects_data(&app)?;
+        for worktree in &data.worktrees {
+            if worktree.session_type == SessionType::Base {
+                continue;
+            }
+            let Ok(relative) = PathBuf::from(&worktree.path)
+                .strip_prefix(&old_root)
+                .map(|p| p.to_path_buf())
+            else {
+                continue;
+            };
+            let Some(project) = data.find_project(&worktree.project_id) else {
+                continue;
+            };
+
+            let new_path = path_string(new_root.join(relative));
+            let result =
+                crate::projects::git::move_worktree(&project.path, &worktree.path, &new_path)
+                    .and_then(|()| {
+                        crate::projects::storage::update_worktree(&app, &worktree.id, |w| {
+                            w.path = new_path.clone();
+                            Ok(())
+                        })
+                    });
+
+            match result {
+                Ok(()) => moved += 1,
+                Err(error) => {
+                    log::warn!("Failed to move worktree {}: {error}", worktree.path);
+                    failed.push(WorktreeMoveFailure {
+                        worktree_id: worktree.id.clone(),
+                        path: worktree.path.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(WorktreesRootChange {
+        locations: get_storage_locations(app).await?,
+        moved,
+        failed,
+    })
+}
+
+/// Move all app data to a new directory (`None` moves it back to the default).
+///
+/// The target must be empty. Refused while sessions are running, since their
+/// run logs are being written.
+#[tauri::command]
+pub async fn set_data_location(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<StorageLocations, String> {
+    if crate::updater::has_running_sessions() {
+        return Err("Cannot move app data while sessions are running".to_string());
+    }
+
+    let old_dir = app_data_dir(&app)?;
+    let new_dir = match &path {
+        Some(p) => validate_dir(p)?,
+        None => default_app_data_dir(&app)?,
+    };
+    if old_dir == new_dir {
+        return get_storage_locations(app).await;
+    }
+
+    log::trace!("Moving app data from {old_dir:?} to {new_dir:?}");
+
+    // Make sure pending UI state lands in the old directory before it moves
+    crate::ui_state::flush();
+
+    crate::db::with_db_closed(|| {
+        let moved = move_data_dir(&old_dir, &new_dir)?;
+        save(
+            &app,
+            LocationsConfig {
+                data_dir: path.map(|_| path_string(new_dir.clone())),
+                ..current()
+            },
+        )?;
+        log::info!("Moved {moved} item(s) to new data location {new_dir:?}");
+        Ok(())
+    })?;
+
+    get_storage_locations(app).await
+}
@@ -0,0 +1,298 @@
+//! Configurable storage locations
+//!
+//! By default app data lives in the platform app-data directory and worktrees
+//! are created under `~/jean/<project>`. Both can be overridden, e.g. to keep
+//! worktrees on a different volume than the home directory.
+//!
+//! Overrides are stored in `locations.json` in the platform config directory,
+//! since preferences themselves live in the (relocatable) data directory.
+//! Always resolve the data directory through [`app_data_dir`].
+//!
+//! On Linux the platform directories follow the XDG base directory spec
+//! (`$XDG_DATA_HOME`, `$XDG_CONFIG_HOME`, `$XDG_CACHE_HOME`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+pub mod commands;
+
+/// File (in the config directory) holding location overrides
+const LOCATIONS_FILE_NAME: &str = "locations.json";
+
+/// Bundle identifier from tauri.conf.json, which names the platform directories
+const APP_IDENTIFIER: &str = "com.jean.desktop";
+
+/// Default worktrees root, relative to the home directory
+const DEFAULT_WORKTREES_DIR_NAME: &str = "jean";
+
+/// Location overrides. `None` means the platform default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocationsConfig {
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    #[serde(default)]
+    pub worktrees_root: Option<String>,
+}
+
+/// Overrides loaded at startup (see `init`)
+static CONFIG: Lazy<RwLock<LocationsConfig>> =
+    Lazy::new(|| RwLock::new(LocationsConfig::default()));
+
+/// The platform default app-data directory (ignores overrides)
+pub fn default_app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {e}"))
+}
+
+/// The platform config directory (`$XDG_CONFIG_HOME/<app>` on Linux)
+pub fn app_config_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {e}"))
+}
+
+/// The platform cache directory (`$XDG_CACHE_HOME/<app>` on Linux), for
+/// files that can be recreated at any time
+pub fn app_cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get app cache directory: {e}"))
+}
+
+/// The default worktrees root (~/jean)
+pub fn default_worktrees_root() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home_dir.join(DEFAULT_WORKTREES_DIR_NAME))
+}
+
+/// The app-data directory, honoring a configured override
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match current().data_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => default_app_data_dir(app),
+    }
+}
+
+/// The worktrees root, honoring a configured override
+pub fn worktrees_root() -> Result<PathBuf, String> {
+    match current().worktrees_root {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => default_worktrees_root(),
+    }
+}
+
+/// Current location overrides
+pub fn current() -> LocationsConfig {
+    CONFIG.read().unwrap().clone()
+}
+
+fn get_locations_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_config_dir(app)?.join(LOCATIONS_FILE_NAME))
+}
+
+/// Move `locations.json` from the default app-data directory, where it was
+/// kept before, to the config directory (only differs on Linux)
+fn migrate_legacy_locations_file(app: &AppHandle) -> Result<(), String> {
+    let path = get_locations_path(app)?;
+    let legacy_path = default_app_data_dir(app)?.join(LOCATIONS_FILE_NAME);
+    if path == legacy_path || path.exists() || !legacy_path.exists() {
+        return Ok(());
+    }
+    move_path(&legacy_path, &path)?;
+    log::info!("Moved storage locations to {}", path.display());
+    Ok(())
+}
+
+/// Load location overrides. Called once during app setup, before any storage access.
+pub fn init(app: &AppHandle) {
+    if let Err(e) = migrate_legacy_locations_file(app) {
+        log::warn!("Failed to move storage locations to the config directory: {e}");
+    }
+
+    let config = match get_locations_path(app) {
+        Ok(path) => read_config(&path),
+        Err(_) => LocationsConfig::default(),
+    };
+
+    if config != LocationsConfig::default() {
+        log::info!("Using storage locations: {config:?}");
+    }
+    *CONFIG.write().unwrap() = config;
+}
+
+/// Read location overrides from a `locations.json` (defaults if missing or invalid)
+fn read_config(path: &Path) -> LocationsConfig {
+    if !path.exists() {
+        return LocationsConfig::default();
+    }
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read storage locations, using defaults: {e}");
+            LocationsConfig::default()
+        })
+}
+
+/// Load location overrides without an app handle (the CLI companion) and
+/// return the app data directory. Platform directories are resolved the way
+/// Tauri does: `<platform dir>/<bundle identifier>`.
+pub fn init_headless() -> Result<PathBuf, String> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| "Failed to get config directory".to_string())?
+        .join(APP_IDENTIFIER);
+    *CONFIG.write().unwrap() = read_config(&config_dir.join(LOCATIONS_FILE_NAME));
+
+    match current().data_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::data_dir()
+            .map(|dir| dir.join(APP_IDENTIFIER))
+            .ok_or_else(|| "Failed to get app data directory".to_string()),
+    }
+}
+
+/// Persist and apply new location overrides
+pub fn save(app: &AppHandle, config: LocationsConfig) -> Result<(), String> {
+    let path = get_locations_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create app config directory: {e}"))?;
+    }
+
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize storage locations: {e}"))?;
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write storage locations: {e}"))?;
+    fs::rename(&temp_path, &path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to finalize storage locations: {e}")
+    })?;
+
+    *CONFIG.write().unwrap() = config;
+    Ok(())
+}
+
+/// Validate a user-supplied directory: absolute, and not a file
+pub fn validate_dir(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(format!("{} is not an absolute path", path.display()));
+    }
+    if path.exists() && !path.is_dir() {
+        return Err(format!("{} is not a directory", path.display()));
+    }
+    Ok(path)
+}
+
+/// Move a file or directory, falling back to copy + delete across volumes
+pub fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {parent:?}: {e}"))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from).map_err(|e| format!("Failed to remove {from:?}: {e}"))
+    } else {
+        fs::remove_file(from).map_err(|e| format!("Failed to remove {from:?}: {e}"))
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    if from.is_dir() {
+        fs::create_dir_all(to).map_err(|e| format!("Failed to create {to:?}: {e}"))?;
+        let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {from:?}: {e}"))?;
+        for entry in entries.flatten() {
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {from:?} to {to:?}: {e}"))
+    }
+}
+
+/// Move the contents of the data directory `from` into `to`.
+///
+/// `to` must be empty (or not exist). The locations file stays behind since
+/// it always lives in the default directory.
+pub fn move_data_dir(from: &Path, to: &Path) -> Result<u32, String> {
+    if to.starts_with(from) {
+        return Err("The new data location can't be inside the current one".to_string());
+    }
+    // The default directory always keeps the locations file, so moving back
+    // to it must tolerate that one entry
+    if to.exists()
+        && fs::read_dir(to)
+            .map_err(|e| format!("Failed to read {to:?}: {e}"))?
+            .flatten()
+            .any(|e| e.file_name() != LOCATIONS_FILE_NAME)
+    {
+        return Err(format!("{} is not empty", to.display()));
+    }
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {to:?}: {e}"))?;
+
+    let mut moved = 0;
+    if from.exists() {
+        let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {from:?}: {e}"))?;
+        for entry in entries.flatten() {
+            if entry.file_name() == LOCATIONS_FILE_NAME {
+                continue;
+            }
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_data_dir_moves_everything_but_locations_file() {
+        let from = tempfile::tempdir().unwrap();
+        let to_parent = tempfile::tempdir().unwrap();
+        let to = to_parent.path().join("data");
+
+        fs::write(from.path().join("jean.db"), "db").unwrap();
+        fs::write(from.path().join(LOCATIONS_FILE_NAME), "{}").unwrap();
+        fs::create_dir_all(from.path().join("sessions/data/s1")).unwrap();
+        fs::write(from.path().join("sessions/data/s1/run.jsonl"), "{}").unwrap();
+
+        assert_eq!(move_data_dir(from.path(), &to).unwrap(), 2);
+        assert!(to.join("jean.db").exists());
+        assert!(to.join("sessions/data/s1/run.jsonl").exists());
+        assert!(!to.join(LOCATIONS_FILE_NAME).exists());
+        assert!(from.path().join(LOCATIONS_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_move_data_dir_rejects_non_empty_or_nested_target() {
+        let from = tempfile::tempdir().unwrap();
+        let to = tempfile::tempdir().unwrap();
+        fs::write(to.path().join("other"), "").unwrap();
+
+        assert!(move_data_dir(from.path(), to.path()).is_err());
+        assert!(move_data_dir(from.path(), &from.path().join("nested")).is_err());
+    }
+
+    #[test]
+    fn test_validate_dir() {
+        assert!(validate_dir("relative/path").is_err());
+        let dir = tempfile::tempdir().unwrap();
+        assert!(validate_dir(dir.path().to_str().unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,10 @@
+//! Tauri commands for notification rules
+
+use super::webhooks::{self, WebhookRule};
+
+/// Send sample event data to a webhook, to check its URL and payload template
+#[tauri::command]
+pub async fn send_test_webhook(rule: WebhookRule) -> Result<(), String> {
+    log::trace!("Sending test webhook '{}'", rule.name);
+    webhooks::send(&rule, &webhooks::sample_vars()).await
+}
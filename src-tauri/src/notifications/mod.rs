@@ -0,0 +1,133 @@
+//! Notification rules
+//!
+//! Session and PR events (a session run finishing, a PR opening, CI starting
+//! to fail) are turned into a set of template variables once and handed to
+//! every channel whose rules match: the project's Slack settings and the
+//! outgoing webhooks configured in preferences.
+
+use tauri::AppHandle;
+
+use crate::projects::storage::load_projects_data;
+
+pub mod commands;
+pub mod webhooks;
+
+/// Event a notification is about
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    SessionFinished { session: String, duration_secs: u64 },
+    PrOpened,
+    CiFailed,
+}
+
+impl NotificationEvent {
+    /// Event name used in webhook filters and payloads
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SessionFinished { .. } => "session_finished",
+            Self::PrOpened => "pr_opened",
+            Self::CiFailed => "ci_failed",
+        }
+    }
+}
+
+/// Template variables for an event
+pub type Vars = Vec<(&'static str, String)>;
+
+/// `1h 5m`, `12m 3s`, `40s`
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m")
+    } else if m > 0 {
+        format!("{m}m {s}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+/// Fill a template's `{name}` placeholders, passing values through `escape`
+pub fn render_with(
+    template: &str,
+    vars: &[(&str, String)],
+    escape: impl Fn(&str) -> String,
+) -> String {
+    vars.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &escape(value))
+        })
+}
+
+/// Fill a template's `{name}` placeholders
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    render_with(template, vars, str::to_string)
+}
+
+async fn dispatch(
+    app: AppHandle,
+    worktree_id: String,
+    event: NotificationEvent,
+) -> Result<(), String> {
+    let data = load_projects_data(&app)?;
+    let Some(worktree) = data.find_worktree(&worktree_id) else {
+        return Ok(());
+    };
+    let Some(project) = data.find_project(&worktree.project_id) else {
+        return Ok(());
+    };
+
+    let mut vars: Vars = vec![
+        ("event", event.name().to_string()),
+        ("project", project.name.clone()),
+        ("project_id", project.id.clone()),
+        ("worktree", worktree.name.clone()),
+        ("worktree_id", worktree.id.clone()),
+        ("branch", worktree.branch.clone()),
+        (
+            "pr_number",
+            worktree
+                .pr_number
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+        ),
+        ("pr_url", worktree.pr_url.clone().unwrap_or_default()),
+    ];
+    if let NotificationEvent::SessionFinished {
+        session,
+        duration_secs,
+    } = &event
+    {
+        vars.push(("session", session.clone()));
+        vars.push(("duration", format_duration(*duration_secs)));
+        vars.push(("duration_secs", duration_secs.to_string()));
+    }
+
+    if let Some(settings) = &project.slack {
+        if let Err(e) = crate::slack::send(settings, &event, &vars).await {
+            log::warn!("Failed to send Slack notification: {e}");
+        }
+    }
+
+    let preferences = crate::load_preferences(app.clone()).await?;
+    for rule in preferences
+        .webhooks
+        .iter()
+        .filter(|rule| rule.matches(&event, &project.id))
+    {
+        if let Err(e) = webhooks::send(rule, &vars).await {
+            log::warn!("Failed to send webhook '{}': {e}", rule.name);
+        }
+    }
+    Ok(())
+}
+
+/// Send an event about a worktree to every matching channel, in the background
+pub fn notify(app: &AppHandle, worktree_id: &str, event: NotificationEvent) {
+    let app = app.clone();
+    let worktree_id = worktree_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = dispatch(app, worktree_id, event).await {
+            log::warn!("Failed to send notifications: {e}");
+        }
+    });
+}
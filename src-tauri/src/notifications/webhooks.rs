@@ -0,0 +1,179 @@
+//! Outgoing webhooks
+//!
+//! Each rule posts JSON to a URL for the events (and projects) it lists. The
+//! payload is the rule's template with `{name}` placeholders filled in with
+//! JSON-escaped values, so `{"content": "{worktree}: {event}"}` works for
+//! Discord and similar services; without a template every variable is sent
+//! as a flat JSON object.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{render_with, NotificationEvent};
+
+/// Events a webhook can subscribe to
+pub const EVENTS: &[&str] = &["session_finished", "pr_opened", "ci_failed"];
+
+/// An outgoing webhook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookRule {
+    /// Unique identifier (assigned on save when empty)
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Events that fire the webhook (empty = all)
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Projects the webhook is limited to (empty = all)
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+    /// JSON payload template (None = all variables as a JSON object)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_template: Option<String>,
+    /// Extra request headers (e.g. an authorization token)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookRule {
+    /// Whether the rule fires for an event in a project
+    pub fn matches(&self, event: &NotificationEvent, project_id: &str) -> bool {
+        self.enabled
+            && (self.events.is_empty() || self.events.iter().any(|e| e == event.name()))
+            && (self.project_ids.is_empty() || self.project_ids.iter().any(|p| p == project_id))
+    }
+
+    /// JSON body for a set of variables
+    pub fn payload(&self, vars: &[(&str, String)]) -> Result<Value, String> {
+        let Some(template) = self
+            .payload_template
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+        else {
+            let object = vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), Value::String(value.clone())))
+                .collect();
+            return Ok(Value::Object(object));
+        };
+        let body = render_with(template, vars, |value| {
+            let quoted = Value::String(value.to_string()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        });
+        serde_json::from_str(&body)
+            .map_err(|e| format!("Webhook '{}' payload is not valid JSON: {e}", self.name))
+    }
+}
+
+/// Sample variables for validating and testing webhooks
+pub fn sample_vars() -> Vec<(&'static str, String)> {
+    [
+        ("event", "session_finished"),
+        ("project", "my-project"),
+        ("project_id", "00000000-0000-0000-0000-000000000000"),
+        ("worktree", "fuzzy-tiger"),
+        ("worktree_id", "00000000-0000-0000-0000-000000000000"),
+        ("branch", "fuzzy-tiger"),
+        ("pr_number", "42"),
+        ("pr_url", "https://github.com/owner/repo/pull/42"),
+        ("session", "Session 1"),
+        ("duration", "12m 34s"),
+        ("duration_secs", "754"),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name, value.to_string()))
+    .collect()
+}
+
+/// Check webhook rules before they're saved, giving new rules an ID
+pub fn prepare(rules: &mut [WebhookRule]) -> Result<(), String> {
+    for rule in rules {
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+        }
+        if !rule.url.starts_with("https://") && !rule.url.starts_with("http://") {
+            return Err(format!("Webhook '{}' needs an http(s) URL", rule.name));
+        }
+        if let Some(event) = rule.events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+            return Err(format!("Webhook '{}': unknown event '{event}'", rule.name));
+        }
+        rule.payload(&sample_vars())?;
+    }
+    Ok(())
+}
+
+/// Post an event to a webhook
+pub async fn send(rule: &WebhookRule, vars: &[(&str, String)]) -> Result<(), String> {
+    let payload = rule.payload(vars)?;
+    let client = reqwest::Client::builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut request = client.post(&rule.url).json(&payload);
+    for (name, value) in &rule.headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to call webhook: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Webhook returned status: {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(template: Option<&str>) -> WebhookRule {
+        WebhookRule {
+            id: String::new(),
+            name: "Discord".to_string(),
+            url: "https://discord.example/hook".to_string(),
+            enabled: true,
+            events: vec!["pr_opened".to_string()],
+            project_ids: Vec::new(),
+            payload_template: template.map(str::to_string),
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_webhook_rule() {
+        let discord = rule(Some(r#"{"content": "{worktree} opened \"{session}\""}"#));
+        assert!(discord.matches(&NotificationEvent::PrOpened, "p1"));
+        assert!(!discord.matches(&NotificationEvent::CiFailed, "p1"));
+
+        let vars = vec![
+            ("worktree", "fuzzy-tiger".to_string()),
+            ("session", "Fix \"login\"".to_string()),
+        ];
+        assert_eq!(
+            discord.payload(&vars).unwrap(),
+            serde_json::json!({ "content": "fuzzy-tiger opened \"Fix \"login\"\"" })
+        );
+        assert_eq!(
+            rule(None).payload(&vars).unwrap()["worktree"],
+            "fuzzy-tiger"
+        );
+
+        let mut rules = vec![rule(Some("{not json"))];
+        assert!(prepare(&mut rules).is_err());
+        let mut rules = vec![rule(None)];
+        prepare(&mut rules).unwrap();
+        assert!(!rules[0].id.is_empty());
+    }
+}
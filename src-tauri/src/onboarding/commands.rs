@@ -0,0 +1,146 @@
+//! Tauri commands for the onboarding wizard
+
+use tauri::AppHandle;
+
+use super::{
+    build_state, load_progress, save_progress, OnboardingProgress, OnboardingState, OnboardingStep,
+    OnboardingStepStatus,
+};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+use crate::projects::storage::get_worktrees_base_dir;
+
+/// Check whether git is installed and a global identity is configured
+async fn check_git() -> (bool, Option<String>) {
+    let probe = tauri::async_runtime::spawn_blocking(|| {
+        silent_command("git").arg("--version").output_audited()
+    })
+    .await;
+    let version = match probe {
+        Ok(Ok(output)) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        Ok(Ok(_)) => return (false, Some("git --version failed".to_string())),
+        Ok(Err(e)) => return (false, Some(format!("git not found: {e}"))),
+        Err(e) => return (false, Some(format!("Failed to check git: {e}"))),
+    };
+
+    match crate::projects::check_git_identity().await {
+        Ok(identity) if identity.name.is_some() && identity.email.is_some() => {
+            (true, Some(version))
+        }
+        _ => (
+            false,
+            Some("git user.name and user.email are not configured".to_string()),
+        ),
+    }
+}
+
+/// Check whether the worktree root exists and is writable
+fn check_worktree_root() -> (bool, Option<String>) {
+    let base_dir = match get_worktrees_base_dir() {
+        Ok(dir) => dir,
+        Err(e) => return (false, Some(e)),
+    };
+
+    let probe = base_dir.join(format!(".jean-write-test-{}", uuid::Uuid::new_v4()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            (true, Some(base_dir.to_string_lossy().to_string()))
+        }
+        Err(e) => (
+            false,
+            Some(format!("{} is not writable: {e}", base_dir.display())),
+        ),
+    }
+}
+
+/// Run the live check for a single step
+async fn check_step(
+    app: &AppHandle,
+    step: OnboardingStep,
+    progress: &OnboardingProgress,
+) -> OnboardingStepStatus {
+    let (passed, detail) = match step {
+        OnboardingStep::Git => check_git().await,
+        OnboardingStep::ClaudeCli => {
+            match crate::claude_cli::check_claude_cli_installed(app.clone()).await {
+                Ok(status) => (status.installed, status.version),
+                Err(e) => (false, Some(e)),
+            }
+        }
+        OnboardingStep::ClaudeAuth => {
+            match crate::claude_cli::check_claude_cli_auth(app.clone()).await {
+                Ok(status) => (status.authenticated, status.error),
+                Err(e) => (false, Some(e)),
+            }
+        }
+        OnboardingStep::GhAuth => match crate::gh_cli::check_gh_cli_auth(app.clone()).await {
+            Ok(status) => (status.authenticated, status.error),
+            Err(e) => (false, Some(e)),
+        },
+        OnboardingStep::WorktreeRoot => check_worktree_root(),
+    };
+
+    OnboardingStepStatus {
+        step,
+        passed,
+        acknowledged: progress.is_acknowledged(step),
+        detail,
+    }
+}
+
+/// Evaluate all steps and persist the resulting progress
+async fn evaluate(
+    app: &AppHandle,
+    mut progress: OnboardingProgress,
+) -> Result<OnboardingState, String> {
+    let mut steps = Vec::with_capacity(OnboardingStep::ALL.len());
+    for step in OnboardingStep::ALL {
+        // Skip the (slow) auth check when the CLI itself is missing
+        if step == OnboardingStep::ClaudeAuth
+            && steps
+                .iter()
+                .any(|s: &OnboardingStepStatus| s.step == OnboardingStep::ClaudeCli && !s.passed)
+        {
+            steps.push(OnboardingStepStatus {
+                step,
+                passed: false,
+                acknowledged: progress.is_acknowledged(step),
+                detail: Some("Claude CLI not installed".to_string()),
+            });
+            continue;
+        }
+        steps.push(check_step(app, step, &progress).await);
+    }
+
+    let had_completed_at = progress.completed_at.is_some();
+    let state = build_state(steps, &mut progress);
+    if !had_completed_at && progress.completed_at.is_some() {
+        save_progress(app, &progress)?;
+    }
+
+    Ok(state)
+}
+
+/// Get the current onboarding state, re-running all prerequisite checks
+#[tauri::command]
+pub async fn get_onboarding_state(app: AppHandle) -> Result<OnboardingState, String> {
+    log::trace!("Getting onboarding state");
+    let progress = load_progress(&app)?;
+    evaluate(&app, progress).await
+}
+
+/// Mark an onboarding step as completed (or skipped) and return the new state
+#[tauri::command]
+pub async fn complete_onboarding_step(
+    app: AppHandle,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    log::trace!("Completing onboarding step: {step:?}");
+    let mut progress = load_progress(&app)?;
+    progress.acknowledge(step);
+    save_progress(&app, &progress)?;
+    evaluate(&app, progress).await
+}
@@ -0,0 +1,220 @@
+//! First-run onboarding state machine
+//!
+//! Walks new users through the prerequisites Jean depends on (git, Claude CLI,
+//! GitHub CLI and the worktree root directory) instead of letting them discover
+//! failing commands one at a time.
+//!
+//! Each step is re-checked live on every call to `get_onboarding_state`. A step
+//! counts as done when its check passes, or when the user explicitly completed
+//! (acknowledged/skipped) it via `complete_onboarding_step`.
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+pub mod commands;
+
+/// Onboarding steps, in the order the wizard presents them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    /// git is installed and a global user identity is configured
+    Git,
+    /// Claude CLI is installed
+    ClaudeCli,
+    /// Claude CLI is authenticated
+    ClaudeAuth,
+    /// GitHub CLI is installed and authenticated
+    GhAuth,
+    /// Worktree root directory exists and is writable
+    WorktreeRoot,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 5] = [
+        OnboardingStep::Git,
+        OnboardingStep::ClaudeCli,
+        OnboardingStep::ClaudeAuth,
+        OnboardingStep::GhAuth,
+        OnboardingStep::WorktreeRoot,
+    ];
+}
+
+/// Result of checking a single onboarding step
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingStepStatus {
+    pub step: OnboardingStep,
+    /// Whether the live check for this step passed
+    pub passed: bool,
+    /// Whether the user marked this step as completed (e.g. skipped it)
+    pub acknowledged: bool,
+    /// Human-readable detail (version, path, or error message)
+    pub detail: Option<String>,
+}
+
+impl OnboardingStepStatus {
+    pub fn is_done(&self) -> bool {
+        self.passed || self.acknowledged
+    }
+}
+
+/// Full onboarding state returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    pub steps: Vec<OnboardingStepStatus>,
+    /// First step that is neither passing nor acknowledged, if any
+    pub current_step: Option<OnboardingStep>,
+    /// Whether onboarding is finished (all steps done)
+    pub completed: bool,
+    /// When onboarding was first finished (unix seconds)
+    pub completed_at: Option<u64>,
+}
+
+/// Persisted onboarding progress (onboarding.json in app data dir)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+    /// Steps the user explicitly completed or skipped
+    #[serde(default)]
+    pub acknowledged_steps: Vec<OnboardingStep>,
+    /// When onboarding was first finished (unix seconds)
+    #[serde(default)]
+    pub completed_at: Option<u64>,
+}
+
+impl OnboardingProgress {
+    pub fn is_acknowledged(&self, step: OnboardingStep) -> bool {
+        self.acknowledged_steps.contains(&step)
+    }
+
+    pub fn acknowledge(&mut self, step: OnboardingStep) {
+        if !self.is_acknowledged(step) {
+            self.acknowledged_steps.push(step);
+        }
+    }
+}
+
+/// Build the overall state from per-step statuses, advancing the state machine
+pub fn build_state(
+    steps: Vec<OnboardingStepStatus>,
+    progress: &mut OnboardingProgress,
+) -> OnboardingState {
+    let current_step = steps.iter().find(|s| !s.is_done()).map(|s| s.step);
+    let completed = current_step.is_none();
+
+    if completed && progress.completed_at.is_none() {
+        progress.completed_at = Some(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        );
+    }
+
+    OnboardingState {
+        steps,
+        current_step,
+        completed,
+        completed_at: progress.completed_at,
+    }
+}
+
+fn get_onboarding_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = crate::locations::app_data_dir(app)?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {e}"))?;
+
+    Ok(app_data_dir.join("onboarding.json"))
+}
+
+/// Load onboarding progress from disk (defaults if missing or unreadable)
+pub fn load_progress(app: &AppHandle) -> Result<OnboardingProgress, String> {
+    let path = get_onboarding_path(app)?;
+
+    if !path.exists() {
+        return Ok(OnboardingProgress::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read onboarding file: {e}"))?;
+
+    Ok(serde_json::from_str(&contents).unwrap_or_else(|e| {
+        log::warn!("Failed to parse onboarding file, starting fresh: {e}");
+        OnboardingProgress::default()
+    }))
+}
+
+/// Save onboarding progress to disk (atomic temp file + rename)
+pub fn save_progress(app: &AppHandle, progress: &OnboardingProgress) -> Result<(), String> {
+    let path = get_onboarding_path(app)?;
+
+    let json_content = serde_json::to_string_pretty(progress)
+        .map_err(|e| format!("Failed to serialize onboarding progress: {e}"))?;
+
+    let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+
+    std::fs::write(&temp_path, json_content)
+        .map_err(|e| format!("Failed to write onboarding file: {e}"))?;
+
+    std::fs::rename(&temp_path, &path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        format!("Failed to finalize onboarding file: {e}")
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(step: OnboardingStep, passed: bool, acknowledged: bool) -> OnboardingStepStatus {
+        OnboardingStepStatus {
+            step,
+            passed,
+            acknowledged,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_current_step_is_first_unfinished() {
+        let mut progress = OnboardingProgress::default();
+        let state = build_state(
+            vec![
+                status(OnboardingStep::Git, true, false),
+                status(OnboardingStep::ClaudeCli, false, false),
+                status(OnboardingStep::GhAuth, false, false),
+            ],
+            &mut progress,
+        );
+        assert_eq!(state.current_step, Some(OnboardingStep::ClaudeCli));
+        assert!(!state.completed);
+        assert!(state.completed_at.is_none());
+    }
+
+    #[test]
+    fn test_acknowledged_steps_count_as_done() {
+        let mut progress = OnboardingProgress::default();
+        let state = build_state(
+            vec![
+                status(OnboardingStep::Git, true, false),
+                status(OnboardingStep::GhAuth, false, true),
+            ],
+            &mut progress,
+        );
+        assert!(state.completed);
+        assert!(state.current_step.is_none());
+        assert!(progress.completed_at.is_some());
+    }
+
+    #[test]
+    fn test_acknowledge_is_idempotent() {
+        let mut progress = OnboardingProgress::default();
+        progress.acknowledge(OnboardingStep::GhAuth);
+        progress.acknowledge(OnboardingStep::GhAuth);
+        assert_eq!(progress.acknowledged_steps, vec![OnboardingStep::GhAuth]);
+    }
+}
@@ -0,0 +1,166 @@
+// Preferred browser for opening URLs
+//
+// GitHub pages, PR links and dev-server URLs open in the browser chosen in
+// preferences (optionally with a browser profile) instead of the system
+// default. The preference is mirrored here by `save_preferences` and at
+// startup, so callers don't need to load preferences themselves.
+
+use std::process::Command;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+/// Browser engines, for the profile argument they take
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Engine {
+    Chromium,
+    Firefox,
+    Safari,
+}
+
+/// Preference value, macOS app, Linux executable, Windows `start` name, engine
+const BROWSERS: &[(&str, &str, &str, &str, Engine)] = &[
+    (
+        "chrome",
+        "Google Chrome",
+        "google-chrome",
+        "chrome",
+        Engine::Chromium,
+    ),
+    (
+        "chromium",
+        "Chromium",
+        "chromium",
+        "chromium",
+        Engine::Chromium,
+    ),
+    (
+        "edge",
+        "Microsoft Edge",
+        "microsoft-edge",
+        "msedge",
+        Engine::Chromium,
+    ),
+    (
+        "brave",
+        "Brave Browser",
+        "brave-browser",
+        "brave",
+        Engine::Chromium,
+    ),
+    ("arc", "Arc", "arc", "arc", Engine::Chromium),
+    ("firefox", "Firefox", "firefox", "firefox", Engine::Firefox),
+    ("safari", "Safari", "safari", "safari", Engine::Safari),
+];
+
+/// (browser, profile) from preferences; browser None = system default
+static PREFERRED: Lazy<Mutex<(Option<String>, Option<String>)>> =
+    Lazy::new(|| Mutex::new((None, None)));
+
+/// Apply the `browser` and `browser_profile` preferences
+pub fn set_preferred(browser: Option<String>, profile: Option<String>) {
+    let browser = browser.filter(|b| !b.is_empty() && b != "default");
+    let profile = profile.filter(|p| !p.trim().is_empty());
+    *PREFERRED.lock().unwrap() = (browser, profile);
+}
+
+/// Arguments selecting `profile` before the URL
+fn profile_args(engine: Engine, profile: Option<&str>) -> Vec<String> {
+    match (engine, profile) {
+        (Engine::Chromium, Some(profile)) => vec![format!("--profile-directory={profile}")],
+        (Engine::Firefox, Some(profile)) => vec!["-P".to_string(), profile.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+fn system_default(url: &str) -> Command {
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = Command::new("open");
+        cmd.arg(url);
+        cmd
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(url);
+        cmd
+    }
+    #[cfg(target_os = "windows")]
+    {
+        super::cmd_command("start", &["", url])
+    }
+}
+
+fn browser_command(browser: &str, profile: Option<&str>, url: &str) -> Result<Command, String> {
+    let &(_, _mac_app, _linux_exe, _windows_name, engine) = BROWSERS
+        .iter()
+        .find(|(id, ..)| *id == browser)
+        .ok_or_else(|| format!("Unknown browser: {browser}"))?;
+    let mut args = profile_args(engine, profile);
+    args.push(url.to_string());
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = Command::new("open");
+        if args.len() > 1 {
+            // Profile arguments only reach a new instance
+            cmd.args(["-na", _mac_app, "--args"]).args(&args);
+        } else {
+            cmd.args(["-a", _mac_app, url]);
+        }
+        Ok(cmd)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if engine == Engine::Safari {
+            return Err("Safari is only available on macOS".to_string());
+        }
+        let mut cmd = super::host_command(_linux_exe);
+        cmd.args(&args);
+        Ok(cmd)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if engine == Engine::Safari {
+            return Err("Safari is only available on macOS".to_string());
+        }
+        // `start` finds browsers through their App Paths registration
+        let start_args: Vec<&str> = ["", _windows_name]
+            .into_iter()
+            .chain(args.iter().map(String::as_str))
+            .collect();
+        Ok(super::cmd_command("start", &start_args))
+    }
+}
+
+/// Open a URL in the preferred browser (the system default when none is set)
+pub fn open_url(url: &str) -> Result<(), String> {
+    let (browser, profile) = PREFERRED.lock().unwrap().clone();
+    let mut cmd = match &browser {
+        Some(browser) => browser_command(browser, profile.as_deref(), url)?,
+        None => system_default(url),
+    };
+    let name = browser.as_deref().unwrap_or("browser");
+    cmd.spawn()
+        .map_err(|e| format!("Failed to open {name}: {e}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_args() {
+        assert_eq!(
+            profile_args(Engine::Chromium, Some("Profile 2")),
+            vec!["--profile-directory=Profile 2"]
+        );
+        assert_eq!(
+            profile_args(Engine::Firefox, Some("work")),
+            vec!["-P", "work"]
+        );
+        assert!(profile_args(Engine::Safari, Some("work")).is_empty());
+    }
+}
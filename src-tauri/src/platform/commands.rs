@@ -0,0 +1,32 @@
+//! Tauri commands for platform integration (WSL, browser)
+
+use super::browser;
+use super::wsl::{self, WslDistroStatus};
+
+/// List the installed WSL distributions (Windows only)
+#[tauri::command]
+pub async fn list_wsl_distributions() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(wsl::list_distributions)
+        .await
+        .map_err(|e| format!("Failed to list WSL distributions: {e}"))?
+}
+
+/// Check that a WSL distribution (None = the WSL default) has the binaries
+/// Jean needs and can reach the worktrees root
+#[tauri::command]
+pub async fn validate_wsl_distribution(
+    distribution: Option<String>,
+) -> Result<WslDistroStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        wsl::validate_distribution(distribution.as_deref().filter(|d| !d.is_empty()))
+    })
+    .await
+    .map_err(|e| format!("Failed to validate WSL distribution: {e}"))
+}
+
+/// Open a URL (PR links, dev-server URLs) in the preferred browser
+#[tauri::command]
+pub async fn open_url(url: String) -> Result<(), String> {
+    log::trace!("Opening URL: {url}");
+    browser::open_url(&url)
+}
@@ -0,0 +1,65 @@
+// Advisory cross-process file locks
+//
+// Used to keep multiple app instances (or the CLI companion) from interleaving
+// read-modify-write cycles on shared storage. Locks are advisory: they only
+// coordinate processes that also take them. The lock is released when the
+// guard is dropped (or the process exits).
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+/// Guard holding an advisory lock on a lock file
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+fn open_lock_file(path: &Path) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create lock directory: {e}"))?;
+    }
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("Failed to open lock file {path:?}: {e}"))
+}
+
+impl FileLock {
+    /// Block until an exclusive lock is acquired (for writers)
+    pub fn exclusive(path: &Path) -> Result<Self, String> {
+        let file = open_lock_file(path)?;
+        file.lock()
+            .map_err(|e| format!("Failed to lock {path:?}: {e}"))?;
+        Ok(Self { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.unlock() {
+            log::warn!("Failed to release file lock: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusive_lock_blocks_other_handles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locks").join("test.lock");
+
+        let guard = FileLock::exclusive(&path).unwrap();
+        let other = File::open(&path).unwrap();
+        assert!(other.try_lock_shared().is_err());
+
+        drop(guard);
+        assert!(other.try_lock().is_ok());
+    }
+}
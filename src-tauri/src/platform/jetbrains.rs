@@ -0,0 +1,107 @@
+// JetBrains IDE launchers
+//
+// IntelliJ IDEA, WebStorm, RustRover, GoLand and PyCharm are started through
+// their command-line launchers (`idea`, `webstorm`, ...). Those are on PATH
+// when the user created them, otherwise JetBrains Toolbox keeps them in its
+// scripts directory. On macOS the app bundle is the last resort.
+
+use std::path::PathBuf;
+use std::process::Child;
+
+/// Editor preference value, launcher name, macOS app name
+const IDES: &[(&str, &str, &str)] = &[
+    ("intellij", "idea", "IntelliJ IDEA"),
+    ("webstorm", "webstorm", "WebStorm"),
+    ("rustrover", "rustrover", "RustRover"),
+    ("goland", "goland", "GoLand"),
+    ("pycharm", "pycharm", "PyCharm"),
+];
+
+/// Whether an editor preference value is a JetBrains IDE
+pub fn is_jetbrains(editor: &str) -> bool {
+    IDES.iter().any(|(id, _, _)| *id == editor)
+}
+
+/// Launcher arguments opening `path`, at `line` when given
+pub fn launch_args(path: &str, line: Option<u32>) -> Vec<String> {
+    match line {
+        Some(line) => vec!["--line".to_string(), line.to_string(), path.to_string()],
+        None => vec![path.to_string()],
+    }
+}
+
+/// JetBrains Toolbox's generated launcher scripts
+fn toolbox_scripts_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        dirs::home_dir().map(|h| h.join("Library/Application Support/JetBrains/Toolbox/scripts"))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        dirs::data_local_dir().map(|d| d.join("JetBrains/Toolbox/scripts"))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        dirs::data_local_dir().map(|d| d.join("JetBrains\\Toolbox\\scripts"))
+    }
+}
+
+/// Path of an IDE launcher: PATH first, then the Toolbox scripts directory
+fn find_launcher(launcher: &str) -> Option<PathBuf> {
+    if let Ok(path) = which::which(launcher) {
+        return Some(path);
+    }
+    let script_name = if cfg!(windows) {
+        format!("{launcher}.cmd")
+    } else {
+        launcher.to_string()
+    };
+    toolbox_scripts_dir()
+        .map(|dir| dir.join(script_name))
+        .filter(|p| p.is_file())
+}
+
+/// Start a JetBrains IDE with `args`. `None` if `editor` isn't one.
+pub fn spawn(editor: &str, args: &[String]) -> Option<std::io::Result<Child>> {
+    let (_, launcher, _app_name) = IDES.iter().find(|(id, _, _)| *id == editor)?;
+    let Some(path) = find_launcher(launcher) else {
+        #[cfg(target_os = "macos")]
+        {
+            return Some(
+                std::process::Command::new("open")
+                    .args(["-na", &format!("{_app_name}.app"), "--args"])
+                    .args(args)
+                    .spawn(),
+            );
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            return Some(Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{launcher} launcher not found"),
+            )));
+        }
+    };
+
+    let path = path.to_string_lossy().to_string();
+    if cfg!(windows) && path.ends_with(".cmd") {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        return Some(super::cli_command(&path, &args).spawn());
+    }
+    Some(super::host_command(&path).args(args).spawn())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_args() {
+        assert!(is_jetbrains("rustrover"));
+        assert!(!is_jetbrains("vscode"));
+        assert_eq!(
+            launch_args("/repo/src/main.rs", Some(12)),
+            vec!["--line", "12", "/repo/src/main.rs"]
+        );
+    }
+}
@@ -1,7 +1,15 @@
 // Cross-platform abstractions for shell execution and process management
 
+pub mod browser;
+pub mod commands;
+pub mod file_lock;
+pub mod jetbrains;
 pub mod process;
+pub mod sandbox;
 pub mod shell;
+pub mod wsl;
 
+pub use file_lock::*;
 pub use process::*;
+pub use sandbox::*;
 pub use shell::*;
@@ -0,0 +1,47 @@
+// Sandboxed (Flatpak) environment detection
+//
+// Inside a Flatpak sandbox, host applications (terminals, editors) aren't
+// visible on PATH; they are started on the host with `flatpak-spawn --host`.
+// Files, directories and URLs are opened with `xdg-open`, which inside the
+// sandbox is routed through the desktop portal.
+
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::process::Command;
+
+use once_cell::sync::Lazy;
+
+static IS_FLATPAK: Lazy<bool> = Lazy::new(|| {
+    std::env::var_os("FLATPAK_ID").is_some() || std::path::Path::new("/.flatpak-info").exists()
+});
+
+/// Whether Jean runs inside a Flatpak sandbox
+pub fn is_flatpak() -> bool {
+    *IS_FLATPAK
+}
+
+/// Command for a host application: run through `flatpak-spawn --host` when
+/// sandboxed, directly otherwise. Only for applications that open their own
+/// UI; the working directory and environment are not forwarded.
+pub fn host_command(program: &str) -> Command {
+    if is_flatpak() {
+        let mut cmd = Command::new("flatpak-spawn");
+        cmd.args(["--host", program]);
+        cmd
+    } else {
+        Command::new(program)
+    }
+}
+
+/// Whether an executable exists on the host's PATH (looked up on the host
+/// when sandboxed)
+pub fn host_executable_exists(name: &str) -> bool {
+    if !is_flatpak() {
+        return super::executable_exists(name);
+    }
+    Command::new("flatpak-spawn")
+        .args(["--host", "which", name])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
@@ -27,3 +27,151 @@ pub fn executable_exists(name: &str) -> bool {
 pub fn executable_exists(name: &str) -> bool {
     which::which(name).is_ok()
 }
+
+/// Quote a string as a PowerShell literal: single quotes, with embedded
+/// single quotes doubled. Nothing inside is expanded.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn powershell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Quote an argument for a cmd.exe command line. Inside double quotes
+/// `&`, `|`, `<`, `>` and `^` are literal. Double quotes can't occur in
+/// Windows paths (and are percent-encoded in URLs), so they are dropped.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn cmd_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', ""))
+}
+
+/// Run `program args...` through `cmd /c`, for builtins like `start` and for
+/// CLIs installed as `.cmd` shims (VS Code, Cursor, npm packages), which
+/// can't be spawned directly.
+///
+/// The command line is passed verbatim: Rust's own argument quoting escapes
+/// quotes as `\"`, which cmd.exe doesn't understand.
+#[cfg(windows)]
+pub fn cmd_command(program: &str, args: &[&str]) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+
+    let line = std::iter::once(program.to_string())
+        .chain(args.iter().map(|arg| cmd_quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut cmd = std::process::Command::new("cmd");
+    // /s: strip exactly the outer quotes, keeping the quoted arguments intact
+    cmd.args(["/d", "/s", "/c"]).raw_arg(format!("\"{line}\""));
+    cmd
+}
+
+/// Command for a CLI that may be a `.cmd` shim on Windows (see [`cmd_command`]);
+/// elsewhere the program is spawned directly (on the host when sandboxed).
+pub fn cli_command(program: &str, args: &[&str]) -> std::process::Command {
+    #[cfg(windows)]
+    {
+        cmd_command(program, args)
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = super::host_command(program);
+        cmd.args(args);
+        cmd
+    }
+}
+
+/// Quote a string for a POSIX shell: single quotes, with embedded single
+/// quotes written as `'\''`
+pub fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Expand a user command template: `{path}` becomes the quoted `path`
+/// (POSIX shell quoting, cmd.exe quoting on Windows)
+pub fn expand_path_template(template: &str, path: &str) -> String {
+    let quoted = if cfg!(windows) {
+        cmd_quote(path)
+    } else {
+        sh_quote(path)
+    };
+    template.replace("{path}", &quoted)
+}
+
+/// Run a user command template (see [`expand_path_template`]) through the
+/// system shell: `sh -c` (on the host when sandboxed), `cmd /c` on Windows
+pub fn template_command(template: &str, path: &str) -> std::process::Command {
+    let line = expand_path_template(template, path);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/d", "/s", "/c"]).raw_arg(format!("\"{line}\""));
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = super::host_command("sh");
+        cmd.args(["-c", &line]);
+        cmd
+    }
+}
+
+/// Translate a Windows path to the path a WSL distribution sees:
+/// `C:\Users\me` -> `/mnt/c/Users/me`, and `\\wsl$\<distro>\home\me`
+/// (or `\\wsl.localhost\...`) -> `/home/me`. Other paths only get forward slashes.
+pub fn to_wsl_path(path: &str) -> String {
+    let unified = path.replace('\\', "/");
+    for prefix in ["//wsl$/", "//wsl.localhost/"] {
+        if let Some(rest) = unified.strip_prefix(prefix) {
+            // Skip the distribution name
+            return match rest.split_once('/') {
+                Some((_, inner)) => format!("/{inner}"),
+                None => "/".to_string(),
+            };
+        }
+    }
+    let bytes = unified.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = (bytes[0] as char).to_ascii_lowercase();
+        let rest = unified[2..].trim_start_matches('/');
+        return format!("/mnt/{drive}/{rest}")
+            .trim_end_matches('/')
+            .to_string();
+    }
+    unified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_quoting() {
+        assert_eq!(
+            powershell_quote(r"C:\Users\o'brien\repo"),
+            r"'C:\Users\o''brien\repo'"
+        );
+        assert_eq!(
+            cmd_quote("https://github.com/o/r/pulls?q=a&b"),
+            "\"https://github.com/o/r/pulls?q=a&b\""
+        );
+        assert_eq!(cmd_quote(""), "\"\"");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_path_template() {
+        assert_eq!(
+            expand_path_template("foot --working-directory={path}", "/home/me/o'brien"),
+            r"foot --working-directory='/home/me/o'\''brien'"
+        );
+    }
+
+    #[test]
+    fn test_to_wsl_path() {
+        assert_eq!(to_wsl_path(r"C:\Users\me\jean"), "/mnt/c/Users/me/jean");
+        assert_eq!(to_wsl_path(r"D:\"), "/mnt/d");
+        assert_eq!(to_wsl_path(r"\\wsl$\Ubuntu\home\me\repo"), "/home/me/repo");
+        assert_eq!(to_wsl_path(r"\\wsl.localhost\Debian\home\me"), "/home/me");
+        assert_eq!(to_wsl_path("/home/me"), "/home/me");
+    }
+}
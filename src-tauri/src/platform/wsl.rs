@@ -0,0 +1,150 @@
+// WSL distribution discovery and validation
+//
+// On Windows, CLIs can run inside a WSL distribution instead of natively. The
+// `wsl_distribution` preference picks the distribution (None = the WSL
+// default); validation checks it has the binaries Jean drives and can reach
+// the worktrees root.
+
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::command_audit::AuditedCommand;
+use crate::platform::{silent_command, to_wsl_path};
+
+/// Binaries Jean runs inside the distribution
+pub const REQUIRED_BINARIES: [&str; 3] = ["git", "claude", "gh"];
+
+/// Result of validating a WSL distribution
+#[derive(Debug, Clone, Serialize)]
+pub struct WslDistroStatus {
+    /// Distribution checked (None = the WSL default)
+    pub distribution: Option<String>,
+    /// Whether the distribution could be started
+    pub available: bool,
+    /// Required binaries not found on the distribution's login PATH
+    pub missing_binaries: Vec<String>,
+    /// Whether the worktrees root is reachable from inside the distribution
+    pub worktrees_root_reachable: bool,
+    pub error: Option<String>,
+}
+
+/// `wsl.exe`, targeting `distribution` when given
+fn wsl_command(distribution: Option<&str>) -> Command {
+    let mut cmd = silent_command("wsl.exe");
+    if let Some(distribution) = distribution {
+        cmd.args(["--distribution", distribution]);
+    }
+    cmd
+}
+
+/// Decode `wsl.exe` output, which is UTF-16LE for its own messages and lists
+fn decode_output(bytes: &[u8]) -> String {
+    let looks_utf16 = bytes.len() >= 2 && bytes.iter().skip(1).step_by(2).all(|b| *b == 0);
+    if looks_utf16 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).to_string()
+    }
+}
+
+/// Distribution names from `wsl.exe --list --quiet` output
+fn parse_distributions(bytes: &[u8]) -> Vec<String> {
+    decode_output(bytes)
+        .lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}'))
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Installed WSL distributions
+pub fn list_distributions() -> Result<Vec<String>, String> {
+    let output = silent_command("wsl.exe")
+        .args(["--list", "--quiet"])
+        .output_audited()
+        .map_err(|e| format!("Failed to run wsl.exe: {e}"))?;
+    if !output.status.success() {
+        let message = decode_output(&output.stderr) + &decode_output(&output.stdout);
+        return Err(format!(
+            "Failed to list WSL distributions: {}",
+            message.trim()
+        ));
+    }
+    Ok(parse_distributions(&output.stdout))
+}
+
+/// Check that `distribution` starts, has the required binaries and can see
+/// the worktrees root
+pub fn validate_distribution(distribution: Option<&str>) -> WslDistroStatus {
+    let mut status = WslDistroStatus {
+        distribution: distribution.map(str::to_string),
+        available: false,
+        missing_binaries: Vec::new(),
+        worktrees_root_reachable: false,
+        error: None,
+    };
+
+    let worktrees_root = match crate::projects::storage::get_worktrees_base_dir() {
+        Ok(dir) => to_wsl_path(&dir.to_string_lossy()),
+        Err(e) => {
+            status.error = Some(e);
+            return status;
+        }
+    };
+
+    // One login shell run: print missing binaries, then whether the root exists.
+    // Arguments are passed positionally so paths need no quoting.
+    let script = r#"root="$1"; shift; for b in "$@"; do command -v "$b" >/dev/null 2>&1 || echo "missing:$b"; done; [ -d "$root" ] && echo root:ok; true"#;
+    let output = wsl_command(distribution)
+        .args(["--exec", "sh", "-lc", script, "sh", &worktrees_root])
+        .args(REQUIRED_BINARIES)
+        .output_audited();
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            status.error = Some(format!("Failed to run wsl.exe: {e}"));
+            return status;
+        }
+    };
+    if !output.status.success() {
+        let stderr = decode_output(&output.stderr);
+        status.error = Some(format!(
+            "Failed to start WSL distribution {}: {}",
+            distribution.unwrap_or("(default)"),
+            stderr.trim()
+        ));
+        return status;
+    }
+
+    status.available = true;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(binary) = line.trim().strip_prefix("missing:") {
+            status.missing_binaries.push(binary.to_string());
+        } else if line.trim() == "root:ok" {
+            status.worktrees_root_reachable = true;
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_distributions_utf16() {
+        let text = "\u{feff}Ubuntu-22.04\r\ndocker-desktop\r\n\r\n";
+        let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(
+            parse_distributions(&bytes),
+            vec!["Ubuntu-22.04".to_string(), "docker-desktop".to_string()]
+        );
+        assert_eq!(parse_distributions(b"Debian\n"), vec!["Debian".to_string()]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Tauri commands for script plugins
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::{find_plugin, find_plugin_info, invoke, set_trusted, PluginInfo, PluginWorktree};
+use crate::projects::storage::load_projects_data;
+
+/// Worktree details passed to a plugin call
+fn plugin_worktree(
+    app: &AppHandle,
+    worktree_id: Option<&str>,
+) -> Result<Option<PluginWorktree>, String> {
+    let Some(worktree_id) = worktree_id else {
+        return Ok(None);
+    };
+    let data = load_projects_data(app)?;
+    let worktree = data
+        .find_worktree(worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    Ok(Some(PluginWorktree {
+        id: worktree.id.clone(),
+        path: worktree.path.clone(),
+        branch: worktree.branch.clone(),
+    }))
+}
+
+/// List installed plugins, including ones whose manifest is invalid
+#[tauri::command]
+pub async fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, String> {
+    log::trace!("Listing plugins");
+    super::list_plugins(&app)
+}
+
+/// Trust (or stop trusting) a plugin. Plugins aren't sandboxed, so the UI
+/// calls this only after showing the plugin's command and the environment
+/// variables it asks for and getting the user's explicit approval.
+#[tauri::command]
+pub async fn set_plugin_trusted(
+    app: AppHandle,
+    plugin_id: String,
+    trusted: bool,
+) -> Result<PluginInfo, String> {
+    let info = find_plugin_info(&app, &plugin_id)?;
+    let manifest = info
+        .manifest
+        .as_ref()
+        .expect("found plugins have a manifest");
+    log::info!("Setting plugin {plugin_id} trusted: {trusted}");
+    set_trusted(&app, manifest, trusted)?;
+    Ok(PluginInfo { trusted, ..info })
+}
+
+/// Run a plugin command and return its JSON response
+#[tauri::command]
+pub async fn run_plugin_command(
+    app: AppHandle,
+    plugin_id: String,
+    command: String,
+    worktree_id: Option<String>,
+    input: Option<Value>,
+) -> Result<Value, String> {
+    log::trace!("Running plugin command {plugin_id}/{command}");
+    let (dir, manifest) = find_plugin(&app, &plugin_id)?;
+    if !manifest.commands.iter().any(|c| c.id == command) {
+        return Err(format!("Plugin {plugin_id} has no command {command}"));
+    }
+    let worktree = plugin_worktree(&app, worktree_id.as_deref())?;
+    let input = input.unwrap_or(Value::Null);
+    tauri::async_runtime::spawn_blocking(move || {
+        invoke(
+            &dir,
+            &manifest,
+            "command",
+            &command,
+            &input,
+            worktree.as_ref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to run plugin: {e}"))?
+}
+
+/// Get context from a plugin's context provider, as markdown
+///
+/// Providers answer with a string or `{ "content": "..." }`.
+#[tauri::command]
+pub async fn get_plugin_context(
+    app: AppHandle,
+    plugin_id: String,
+    provider: String,
+    worktree_id: Option<String>,
+) -> Result<String, String> {
+    log::trace!("Getting plugin context {plugin_id}/{provider}");
+    let (dir, manifest) = find_plugin(&app, &plugin_id)?;
+    if !manifest.context_providers.iter().any(|p| p.id == provider) {
+        return Err(format!(
+            "Plugin {plugin_id} has no context provider {provider}"
+        ));
+    }
+    let worktree = plugin_worktree(&app, worktree_id.as_deref())?;
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        invoke(
+            &dir,
+            &manifest,
+            "context",
+            &provider,
+            &Value::Null,
+            worktree.as_ref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to run plugin: {e}"))??;
+
+    match response {
+        Value::String(content) => Ok(content),
+        Value::Object(mut map) => match map.remove("content") {
+            Some(Value::String(content)) => Ok(content),
+            _ => Err(format!("Plugin {plugin_id} returned no context content")),
+        },
+        Value::Null => Ok(String::new()),
+        _ => Err(format!("Plugin {plugin_id} returned no context content")),
+    }
+}
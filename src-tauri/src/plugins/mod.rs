@@ -0,0 +1,441 @@
+//! Script plugins
+//!
+//! Integrations that don't belong in core (issue trackers, internal tooling)
+//! live in `<app data>/plugins/<plugin>/plugin.json`. A plugin contributes:
+//!
+//! - commands, run through the generic `run_plugin_command` command
+//! - magic actions: prompt templates shown next to the built-in ones
+//! - context providers, which produce markdown to attach to a session
+//!
+//! Plugins are executables (`command` in the manifest, run from the plugin
+//! directory). Each call starts the process with a JSON request on stdin and
+//! reads one JSON response from stdout. Processes run with a cleared
+//! environment (only `PATH`, `HOME`, locale variables and the variables the
+//! manifest lists in `env`), are killed after `timeout_secs`, and their
+//! output is capped. WASM modules aren't supported yet; manifests asking for
+//! them are listed with an error.
+//!
+//! Plugins are NOT sandboxed. A plugin runs as the user, with full access to
+//! their files and the network; clearing the environment only keeps variables
+//! it didn't ask for (tokens in Jean's environment) from leaking to it. So a
+//! plugin is only run once the user has trusted it (`set_plugin_trusted`,
+//! after the UI has shown its command and requested variables). Trust covers
+//! that command and those variables: a manifest changing either is untrusted
+//! again until approved.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+pub mod commands;
+
+/// Plugins directory in the app data directory
+const PLUGINS_DIR_NAME: &str = "plugins";
+
+/// Manifest file name in a plugin directory
+const MANIFEST_FILE_NAME: &str = "plugin.json";
+
+/// Trusted plugins, in the app data directory
+const TRUST_FILE_NAME: &str = "plugin-trust.json";
+
+/// Default and maximum run time of a plugin call
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+/// Largest response read from a plugin
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Variables passed through from Jean's environment besides the manifest's `env`
+const BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    "SYSTEMROOT",
+];
+
+/// A command a plugin provides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A prompt template shown with the built-in magic actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginMagicAction {
+    pub id: String,
+    pub label: String,
+    pub prompt: String,
+}
+
+/// A source of session context (e.g. the ticket behind a branch)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginContextProvider {
+    pub id: String,
+    pub label: String,
+}
+
+/// `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `script` (default) or `wasm` (not supported yet)
+    #[serde(default = "default_runtime")]
+    pub runtime: String,
+    /// Program and arguments, relative to the plugin directory or on PATH
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Extra environment variables the plugin may read (e.g. an API token)
+    #[serde(default)]
+    pub env: Vec<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    #[serde(default)]
+    pub magic_actions: Vec<PluginMagicAction>,
+    #[serde(default)]
+    pub context_providers: Vec<PluginContextProvider>,
+}
+
+fn default_runtime() -> String {
+    "script".to_string()
+}
+
+/// An installed plugin, as listed to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    /// Plugin directory
+    pub path: String,
+    /// Parsed manifest (None if it couldn't be read)
+    pub manifest: Option<PluginManifest>,
+    /// Why the plugin can't be used
+    pub error: Option<String>,
+    /// The user approved running the plugin as its manifest stands
+    pub trusted: bool,
+}
+
+/// What the user approved when trusting a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct TrustedPlugin {
+    id: String,
+    command: Vec<String>,
+    env: Vec<String>,
+}
+
+impl TrustedPlugin {
+    fn of(manifest: &PluginManifest) -> Self {
+        Self {
+            id: manifest.id.clone(),
+            command: manifest.command.clone(),
+            env: manifest.env.clone(),
+        }
+    }
+}
+
+/// Worktree a plugin call runs for
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginWorktree {
+    pub id: String,
+    pub path: String,
+    pub branch: String,
+}
+
+/// Request written to the plugin's stdin
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    /// `command` or `context`
+    kind: &'a str,
+    id: &'a str,
+    input: &'a Value,
+    worktree: Option<&'a PluginWorktree>,
+}
+
+pub fn get_plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::locations::app_data_dir(app)?.join(PLUGINS_DIR_NAME))
+}
+
+/// Parse and validate a manifest
+fn parse_manifest(content: &str) -> Result<PluginManifest, String> {
+    let manifest: PluginManifest =
+        serde_json::from_str(content).map_err(|e| format!("Invalid plugin.json: {e}"))?;
+    if manifest.id.is_empty()
+        || !manifest
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid plugin id: {:?}", manifest.id));
+    }
+    match manifest.runtime.as_str() {
+        "script" if manifest.command.is_empty() => {
+            Err("Script plugins need a `command`".to_string())
+        }
+        "script" => Ok(manifest),
+        "wasm" => Err("WASM plugins are not supported yet".to_string()),
+        other => Err(format!("Unknown plugin runtime: {other}")),
+    }
+}
+
+fn trust_file(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::locations::app_data_dir(app)?.join(TRUST_FILE_NAME))
+}
+
+fn load_trusted(app: &AppHandle) -> Result<Vec<TrustedPlugin>, String> {
+    match std::fs::read_to_string(trust_file(app)?) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {TRUST_FILE_NAME}: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {TRUST_FILE_NAME}: {e}")),
+    }
+}
+
+/// Trust (or stop trusting) a plugin as its manifest stands
+pub fn set_trusted(
+    app: &AppHandle,
+    manifest: &PluginManifest,
+    trusted: bool,
+) -> Result<(), String> {
+    let mut entries = load_trusted(app)?;
+    entries.retain(|entry| entry.id != manifest.id);
+    if trusted {
+        entries.push(TrustedPlugin::of(manifest));
+    }
+    let content = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("Failed to serialize plugin trust: {e}"))?;
+    std::fs::write(trust_file(app)?, content)
+        .map_err(|e| format!("Failed to write {TRUST_FILE_NAME}: {e}"))
+}
+
+fn load_plugin(dir: &Path, trusted: &[TrustedPlugin]) -> PluginInfo {
+    let path = dir.to_string_lossy().to_string();
+    let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE_NAME))
+        .map_err(|e| format!("Failed to read plugin.json: {e}"))
+        .and_then(|content| parse_manifest(&content));
+    match manifest {
+        Ok(manifest) => PluginInfo {
+            path,
+            trusted: trusted.contains(&TrustedPlugin::of(&manifest)),
+            manifest: Some(manifest),
+            error: None,
+        },
+        Err(e) => PluginInfo {
+            path,
+            manifest: None,
+            error: Some(e),
+            trusted: false,
+        },
+    }
+}
+
+/// All plugin directories in the plugins directory, sorted by name
+pub fn list_plugins(app: &AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let dir = get_plugins_dir(app)?;
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let trusted = load_trusted(app)?;
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.join(MANIFEST_FILE_NAME).is_file())
+        .collect();
+    dirs.sort();
+    Ok(dirs.iter().map(|d| load_plugin(d, &trusted)).collect())
+}
+
+/// A plugin with a valid manifest, trusted or not
+pub fn find_plugin_info(app: &AppHandle, plugin_id: &str) -> Result<PluginInfo, String> {
+    list_plugins(app)?
+        .into_iter()
+        .find(|info| info.manifest.as_ref().is_some_and(|m| m.id == plugin_id))
+        .ok_or_else(|| format!("Plugin not found: {plugin_id}"))
+}
+
+/// Directory and manifest of a usable (trusted) plugin
+pub fn find_plugin(app: &AppHandle, plugin_id: &str) -> Result<(PathBuf, PluginManifest), String> {
+    let info = find_plugin_info(app, plugin_id)?;
+    if !info.trusted {
+        return Err(format!(
+            "Plugin {plugin_id} isn't trusted yet. Review it in Settings before running it."
+        ));
+    }
+    let manifest = info.manifest.expect("found plugins have a manifest");
+    Ok((PathBuf::from(info.path), manifest))
+}
+
+/// Read a pipe to the end in a background thread, keeping at most the cap
+fn read_capped<R: Read + Send + 'static>(pipe: R) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.take(MAX_OUTPUT_BYTES as u64 + 1).read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Run one plugin call and return its JSON response
+pub fn invoke(
+    plugin_dir: &Path,
+    manifest: &PluginManifest,
+    kind: &str,
+    id: &str,
+    input: &Value,
+    worktree: Option<&PluginWorktree>,
+) -> Result<Value, String> {
+    let (program, args) = manifest
+        .command
+        .split_first()
+        .ok_or_else(|| "Plugin has no command".to_string())?;
+    // Programs shipped with the plugin are resolved against its directory
+    let local = plugin_dir.join(program);
+    let program = if local.is_file() {
+        local.to_string_lossy().to_string()
+    } else {
+        program.clone()
+    };
+
+    let mut cmd = Command::new(&program);
+    cmd.args(args)
+        .current_dir(worktree.map_or(plugin_dir, |w| Path::new(&w.path)))
+        .env_clear()
+        .env("JEAN_PLUGIN_DIR", plugin_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for name in BASE_ENV
+        .iter()
+        .copied()
+        .chain(manifest.env.iter().map(String::as_str))
+    {
+        if let Some(value) = std::env::var_os(name) {
+            cmd.env(name, value);
+        }
+    }
+
+    let request = serde_json::to_vec(&PluginRequest {
+        kind,
+        id,
+        input,
+        worktree,
+    })
+    .map_err(|e| format!("Failed to serialize plugin request: {e}"))?;
+
+    log::trace!("Running plugin {} ({kind} {id})", manifest.id);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin {}: {e}", manifest.id))?;
+    let stdout = read_capped(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_capped(child.stderr.take().expect("stderr is piped"));
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written from its own thread so a plugin that never reads its input
+        // can't block us past the timeout (killing it closes the pipe). One
+        // that closes the pipe early is fine.
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&request);
+        });
+    }
+
+    let timeout = Duration::from_secs(
+        manifest
+            .timeout_secs
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .min(MAX_TIMEOUT_SECS),
+    );
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Plugin {} timed out after {}s",
+                    manifest.id,
+                    timeout.as_secs()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for plugin {}: {e}", manifest.id)),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(format!(
+            "Plugin {} failed ({status}): {}",
+            manifest.id,
+            stderr.trim()
+        ));
+    }
+    if stdout.len() > MAX_OUTPUT_BYTES {
+        return Err(format!("Plugin {} output is too large", manifest.id));
+    }
+    if stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(&stdout)
+        .map_err(|e| format!("Plugin {} returned invalid JSON: {e}", manifest.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse_manifest(
+            r#"{"id": "jira", "name": "Jira", "command": ["node", "index.js"],
+                "context_providers": [{"id": "ticket", "label": "Jira ticket"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.runtime, "script");
+        assert_eq!(manifest.context_providers[0].id, "ticket");
+
+        assert!(parse_manifest(r#"{"id": "../x", "name": "X", "command": ["a"]}"#).is_err());
+        assert!(parse_manifest(r#"{"id": "x", "name": "X", "runtime": "wasm"}"#).is_err());
+    }
+
+    #[test]
+    fn test_trust_covers_command_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |manifest: &str| std::fs::write(dir.path().join(MANIFEST_FILE_NAME), manifest);
+        write(r#"{"id": "jira", "name": "Jira", "command": ["node", "index.js"]}"#).unwrap();
+        let trusted = vec![TrustedPlugin::of(
+            load_plugin(dir.path(), &[]).manifest.as_ref().unwrap(),
+        )];
+        assert!(load_plugin(dir.path(), &trusted).trusted);
+
+        write(r#"{"id": "jira", "name": "Jira", "command": ["node", "index.js"], "env": ["AWS_SECRET_ACCESS_KEY"]}"#)
+            .unwrap();
+        assert!(!load_plugin(dir.path(), &trusted).trusted);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invoke_script_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = parse_manifest(
+            r#"{"id": "echo", "name": "Echo", "command": ["sh", "-c", "cat >/dev/null; echo '{\"ok\": true}'"]}"#,
+        )
+        .unwrap();
+        let result = invoke(dir.path(), &manifest, "command", "ping", &Value::Null, None).unwrap();
+        assert_eq!(result, serde_json::json!({ "ok": true }));
+    }
+}
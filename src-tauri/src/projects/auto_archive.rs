@@ -0,0 +1,227 @@
+//! Auto-archive rule for stale worktrees
+//!
+//! When `auto_archive_stale_days` is set, worktrees with no commits, no session
+//! activity and no uncommitted changes for that many days are archived, and a
+//! single notification summarizes what was archived. This is separate from the
+//! archive retention cleanup, which deletes worktrees that are already archived.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::git;
+use super::storage::load_projects_data;
+use super::types::{SessionType, Worktree};
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+
+/// How often the rule is re-applied while the app is running
+const RECHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// A worktree archived by the rule
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoArchivedWorktree {
+    pub id: String,
+    pub name: String,
+    pub project_name: String,
+}
+
+/// Summary emitted as `worktrees:auto-archived`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AutoArchiveSummary {
+    pub archived: Vec<AutoArchivedWorktree>,
+}
+
+/// Activity signals for a worktree, as unix timestamps
+#[derive(Debug, Default)]
+pub struct WorktreeActivity {
+    pub created_at: u64,
+    pub last_commit_at: Option<u64>,
+    pub last_session_at: Option<u64>,
+    pub has_uncommitted_changes: bool,
+    pub session_running: bool,
+}
+
+/// Whether a worktree had no activity since `cutoff`
+pub fn is_stale(activity: &WorktreeActivity, cutoff: u64) -> bool {
+    !activity.has_uncommitted_changes
+        && !activity.session_running
+        && activity.created_at < cutoff
+        && activity.last_commit_at.is_none_or(|at| at < cutoff)
+        && activity.last_session_at.is_none_or(|at| at < cutoff)
+}
+
+/// Apply the rule on startup and every few hours, using the current preference
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let days = tauri::async_runtime::block_on(crate::load_preferences(app.clone()))
+            .map(|p| p.auto_archive_stale_days)
+            .unwrap_or(0);
+        if days > 0 {
+            if let Err(e) = tauri::async_runtime::block_on(archive_stale(&app, days)) {
+                log::warn!("Failed to auto-archive stale worktrees: {e}");
+            }
+        }
+        std::thread::sleep(RECHECK_INTERVAL);
+    });
+}
+
+/// Archive every worktree idle for at least `days` days, then emit and notify
+/// a summary if anything was archived
+pub async fn archive_stale(app: &AppHandle, days: u32) -> Result<AutoArchiveSummary, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let cutoff = now.saturating_sub(days as u64 * 86400);
+
+    let data = load_projects_data(app)?;
+    let running = crate::chat::registry::get_running_sessions();
+
+    let mut summary = AutoArchiveSummary::default();
+    for worktree in &data.worktrees {
+        if worktree.session_type == SessionType::Base
+            || worktree.archived_at.is_some()
+            || !std::path::Path::new(&worktree.path).exists()
+        {
+            continue;
+        }
+        let activity = worktree_activity(app, worktree, &running);
+        if !is_stale(&activity, cutoff) {
+            continue;
+        }
+
+        if let Err(e) = super::archive_worktree(app.clone(), worktree.id.clone()).await {
+            log::warn!("Failed to auto-archive worktree {}: {e}", worktree.name);
+            continue;
+        }
+        log::info!(
+            "Auto-archived worktree {} (idle for {days}+ days)",
+            worktree.name
+        );
+        summary.archived.push(AutoArchivedWorktree {
+            id: worktree.id.clone(),
+            name: worktree.name.clone(),
+            project_name: data
+                .find_project(&worktree.project_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+        });
+    }
+
+    if !summary.archived.is_empty() {
+        if let Err(e) = app.emit_all("worktrees:auto-archived", &summary) {
+            log::error!("Failed to emit worktrees:auto-archived event: {e}");
+        }
+        notify(app, &summary);
+    }
+    Ok(summary)
+}
+
+/// Activity signals of a worktree: commits, session runs and uncommitted changes
+pub fn worktree_activity(
+    app: &AppHandle,
+    worktree: &Worktree,
+    running: &[String],
+) -> WorktreeActivity {
+    let mut activity = WorktreeActivity {
+        created_at: worktree.created_at,
+        last_commit_at: last_commit_time(&worktree.path),
+        has_uncommitted_changes: git::has_uncommitted_changes(&worktree.path),
+        ..Default::default()
+    };
+
+    let Ok(index) = crate::chat::storage::load_index(app, &worktree.id) else {
+        return activity;
+    };
+    for entry in &index.sessions {
+        if running.contains(&entry.id) {
+            activity.session_running = true;
+        }
+        let Ok(Some(metadata)) = crate::chat::storage::load_metadata(app, &entry.id) else {
+            continue;
+        };
+        let last_run_at = metadata
+            .runs
+            .iter()
+            .map(|run| run.ended_at.unwrap_or(run.started_at))
+            .max();
+        let last_at = last_run_at.unwrap_or(metadata.created_at);
+        activity.last_session_at = activity.last_session_at.max(Some(last_at));
+    }
+    activity
+}
+
+/// Unix timestamp of the HEAD commit
+fn last_commit_time(repo_path: &str) -> Option<u64> {
+    let output = crate::platform::silent_command("git")
+        .args(["log", "-1", "--format=%ct"])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn notify(app: &AppHandle, summary: &AutoArchiveSummary) {
+    #[cfg(not(mobile))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        let count = summary.archived.len();
+        let title = if count == 1 {
+            "Archived 1 stale worktree".to_string()
+        } else {
+            format!("Archived {count} stale worktrees")
+        };
+        let body = summary
+            .archived
+            .iter()
+            .map(|w| format!("{} ({})", w.name, w.project_name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            log::error!("Failed to show auto-archive notification: {e}");
+        }
+    }
+    #[cfg(mobile)]
+    let _ = (app, summary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let cutoff = 1_000_000;
+        let idle = WorktreeActivity {
+            created_at: cutoff - 10,
+            last_commit_at: Some(cutoff - 5),
+            last_session_at: None,
+            ..Default::default()
+        };
+        assert!(is_stale(&idle, cutoff));
+
+        let recent_session = WorktreeActivity {
+            last_session_at: Some(cutoff + 1),
+            ..idle
+        };
+        assert!(!is_stale(&recent_session, cutoff));
+
+        let dirty = WorktreeActivity {
+            created_at: cutoff - 10,
+            has_uncommitted_changes: true,
+            ..Default::default()
+        };
+        assert!(!is_stale(&dirty, cutoff));
+
+        // A worktree created within the window is never stale
+        let new = WorktreeActivity {
+            created_at: cutoff + 10,
+            ..Default::default()
+        };
+        assert!(!is_stale(&new, cutoff));
+    }
+}
@@ -0,0 +1,551 @@
+//! Bitbucket Cloud support through the REST API
+//!
+//! Like GitLab merge requests, Bitbucket issues and pull requests are
+//! converted into the GitHub types the rest of the app already uses (source
+//! and destination branches are the head and base refs, build statuses are
+//! the checks), so the issue/PR UI and the PR status poller work unchanged.
+//! Used through `repo_provider::Bitbucket`.
+//!
+//! Requests are authenticated with the `bitbucket_username` and
+//! `bitbucket_app_password` preferences; without them only public
+//! repositories can be read.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use super::git::RepoIdentifier;
+use super::github_issues::{
+    GitHubAuthor, GitHubComment, GitHubIssue, GitHubPullRequest, GitHubPullRequestDetail,
+};
+use super::pr_status::{compute_display_status, CheckStatus, PrState, PrStatus, ReviewDecision};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+const API_BASE: &str = "https://api.bitbucket.org/2.0";
+
+/// Host of Bitbucket Cloud remotes
+pub const HOST: &str = "bitbucket.org";
+
+/// Issues and PRs listed at once, like `gh ... -L 100`
+const LIST_LIMIT: usize = 100;
+
+/// Largest page size the API accepts for pull requests
+const PAGE_LEN: &str = "50";
+
+/// Issue states that count as open
+const OPEN_ISSUE_STATES: [&str; 3] = ["new", "open", "on hold"];
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbUser {
+    #[serde(default)]
+    display_name: String,
+    nickname: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BbContent {
+    #[serde(default)]
+    raw: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbBranch {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbRepository {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbEndpoint {
+    branch: BbBranch,
+    repository: Option<BbRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbParticipant {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    approved: bool,
+    /// "approved", "changes_requested" or null
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbPullRequest {
+    id: u32,
+    title: String,
+    #[serde(default)]
+    description: String,
+    /// OPEN, MERGED, DECLINED or SUPERSEDED
+    state: String,
+    source: BbEndpoint,
+    destination: BbEndpoint,
+    created_on: String,
+    author: BbUser,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    participants: Vec<BbParticipant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbComment {
+    #[serde(default)]
+    content: BbContent,
+    user: BbUser,
+    created_on: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbIssue {
+    id: u32,
+    title: String,
+    #[serde(default)]
+    content: BbContent,
+    state: String,
+    created_on: String,
+    reporter: Option<BbUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BbBuildStatus {
+    /// SUCCESSFUL, FAILED, INPROGRESS or STOPPED
+    state: String,
+}
+
+/// Workspace and repository slug of a Bitbucket Cloud remote
+/// (`git@bitbucket.org:ws/repo.git`, `https://user@bitbucket.org/ws/repo`)
+pub fn parse_remote(remote_url: &str) -> Option<RepoIdentifier> {
+    if super::repo_provider::remote_host(remote_url)? != HOST {
+        return None;
+    }
+    let (_, path) = remote_url.split_once(HOST)?;
+    let path = path
+        .trim_start_matches([':', '/'])
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some(RepoIdentifier {
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+    })
+}
+
+fn author(user: BbUser) -> GitHubAuthor {
+    GitHubAuthor {
+        login: user.nickname.unwrap_or(user.display_name),
+    }
+}
+
+fn non_empty(text: String) -> Option<String> {
+    (!text.is_empty()).then_some(text)
+}
+
+/// Bitbucket PR states as GitHub spells them
+fn github_state(state: &str) -> String {
+    match state {
+        "DECLINED" | "SUPERSEDED" => "CLOSED".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn issue_state(state: &str) -> String {
+    if OPEN_ISSUE_STATES.contains(&state) {
+        "OPEN".to_string()
+    } else {
+        "CLOSED".to_string()
+    }
+}
+
+fn pull_request(pr: BbPullRequest) -> GitHubPullRequest {
+    GitHubPullRequest {
+        number: pr.id,
+        title: pr.title,
+        body: non_empty(pr.description),
+        state: github_state(&pr.state),
+        head_ref_name: pr.source.branch.name,
+        base_ref_name: pr.destination.branch.name,
+        is_draft: pr.draft,
+        created_at: pr.created_on,
+        author: author(pr.author),
+        labels: Vec::new(),
+    }
+}
+
+/// A Bitbucket Cloud repository and the app whose preferences hold the
+/// credentials
+pub struct Client {
+    app: AppHandle,
+    repo: RepoIdentifier,
+}
+
+impl Client {
+    pub fn new(app: &AppHandle, repo: RepoIdentifier) -> Self {
+        Self {
+            app: app.clone(),
+            repo,
+        }
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!(
+            "{API_BASE}/repositories/{}/{}/{path}",
+            self.repo.owner, self.repo.repo
+        )
+    }
+
+    /// GET a JSON resource. Providers are called from async commands and from
+    /// plain threads alike, so the request runs to completion on a thread of
+    /// its own.
+    fn get<T: DeserializeOwned + Send + 'static>(
+        &self,
+        url: String,
+        query: Vec<(&'static str, String)>,
+    ) -> Result<T, String> {
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            tauri::async_runtime::block_on(async move {
+                let prefs = crate::load_preferences(app).await?;
+                let client = reqwest::Client::builder()
+                    .user_agent("Jean-App/1.0")
+                    .build()
+                    .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+                let mut request = client
+                    .get(&url)
+                    .query(&query)
+                    .header("Accept", "application/json");
+                let username = prefs.bitbucket_username.filter(|u| !u.trim().is_empty());
+                let password = prefs.bitbucket_app_password.filter(|p| !p.is_empty());
+                if let (Some(username), Some(password)) = (username, password) {
+                    request = request.basic_auth(username, Some(password));
+                }
+
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| format!("Failed to reach Bitbucket: {e}"))?;
+                match response.status().as_u16() {
+                    401 | 403 => {
+                        return Err("Bitbucket rejected the request. Set a Bitbucket username and app password in preferences.".to_string());
+                    }
+                    404 => return Err(format!("Not found on Bitbucket: {url}")),
+                    _ if !response.status().is_success() => {
+                        return Err(format!(
+                            "Bitbucket API returned status: {}",
+                            response.status()
+                        ));
+                    }
+                    _ => {}
+                }
+                response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse Bitbucket API response: {e}"))
+            })
+        })
+        .join()
+        .map_err(|_| "Bitbucket request thread panicked".to_string())?
+    }
+
+    /// Up to `LIST_LIMIT` items of a paginated collection
+    fn list<T: DeserializeOwned + Send + 'static>(
+        &self,
+        url: String,
+        mut query: Vec<(&'static str, String)>,
+    ) -> Result<Vec<T>, String> {
+        query.push(("pagelen", PAGE_LEN.to_string()));
+        let mut page: Page<T> = self.get(url, query)?;
+        let mut items = std::mem::take(&mut page.values);
+        while let Some(next) = page.next.take().filter(|_| items.len() < LIST_LIMIT) {
+            // `next` already carries the query
+            page = self.get(next, Vec::new())?;
+            items.append(&mut page.values);
+        }
+        items.truncate(LIST_LIMIT);
+        Ok(items)
+    }
+
+    fn pull_request(&self, id: u32) -> Result<BbPullRequest, String> {
+        self.get(self.repo_url(&format!("pullrequests/{id}")), Vec::new())
+    }
+
+    /// List issues: state is "open", "closed" or "all"
+    pub fn list_issues(&self, state: &str) -> Result<Vec<GitHubIssue>, String> {
+        let open = OPEN_ISSUE_STATES
+            .iter()
+            .map(|s| format!("state=\"{s}\""))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let mut query = vec![("sort", "-created_on".to_string())];
+        match state {
+            "open" => query.push(("q", open)),
+            "closed" => query.push(("q", format!("NOT ({open})"))),
+            _ => {}
+        }
+        let issues: Vec<BbIssue> = self.list(self.repo_url("issues"), query).map_err(|e| {
+            format!("Failed to list Bitbucket issues (is the issue tracker enabled?): {e}")
+        })?;
+        Ok(issues
+            .into_iter()
+            .map(|issue| GitHubIssue {
+                number: issue.id,
+                title: issue.title,
+                body: non_empty(issue.content.raw),
+                state: issue_state(&issue.state),
+                labels: Vec::new(),
+                created_at: issue.created_on,
+                author: issue.reporter.map(author).unwrap_or(GitHubAuthor {
+                    login: String::new(),
+                }),
+            })
+            .collect())
+    }
+
+    /// List pull requests: state is "open", "closed", "merged" or "all"
+    pub fn list_pull_requests(&self, state: &str) -> Result<Vec<GitHubPullRequest>, String> {
+        let states: &[&str] = match state {
+            "closed" => &["DECLINED", "SUPERSEDED"],
+            "merged" => &["MERGED"],
+            "all" => &["OPEN", "MERGED", "DECLINED", "SUPERSEDED"],
+            _ => &["OPEN"],
+        };
+        let mut query: Vec<_> = states.iter().map(|s| ("state", s.to_string())).collect();
+        query.push(("sort", "-created_on".to_string()));
+        let prs: Vec<BbPullRequest> = self.list(self.repo_url("pullrequests"), query)?;
+        Ok(prs.into_iter().map(pull_request).collect())
+    }
+
+    /// A pull request with its comments (deleted comments left out)
+    pub fn get_pull_request(&self, id: u32) -> Result<GitHubPullRequestDetail, String> {
+        let pr = pull_request(self.pull_request(id)?);
+        let comments: Vec<BbComment> = self.list(
+            self.repo_url(&format!("pullrequests/{id}/comments")),
+            vec![("sort", "created_on".to_string())],
+        )?;
+        Ok(GitHubPullRequestDetail {
+            number: pr.number,
+            title: pr.title,
+            body: pr.body,
+            state: pr.state,
+            head_ref_name: pr.head_ref_name,
+            base_ref_name: pr.base_ref_name,
+            is_draft: pr.is_draft,
+            created_at: pr.created_at,
+            author: pr.author,
+            labels: pr.labels,
+            comments: comments
+                .into_iter()
+                .filter(|c| !c.deleted)
+                .map(|c| GitHubComment {
+                    body: c.content.raw,
+                    author: author(c.user),
+                    created_at: c.created_on,
+                })
+                .collect(),
+            // Approvals carry no review text
+            reviews: Vec::new(),
+        })
+    }
+
+    /// Check out a pull request in a worktree, returning the local branch.
+    /// PRs from forks are fetched from the fork's repository.
+    pub fn checkout_pull_request(
+        &self,
+        worktree_path: &str,
+        id: u32,
+        branch_name: Option<&str>,
+    ) -> Result<String, String> {
+        log::trace!("Checking out Bitbucket PR #{id} in {worktree_path}");
+        let pr = self.pull_request(id)?;
+        let source_branch = pr.source.branch.name;
+        let local_branch = branch_name.unwrap_or(&source_branch).to_string();
+        let own_repo = format!("{}/{}", self.repo.owner, self.repo.repo);
+        let remote = match pr.source.repository.map(|r| r.full_name) {
+            Some(full_name) if !full_name.eq_ignore_ascii_case(&own_repo) => {
+                format!("https://{HOST}/{full_name}.git")
+            }
+            _ => "origin".to_string(),
+        };
+
+        let git = |args: &[&str]| {
+            let output = silent_command("git")
+                .args(args)
+                .current_dir(worktree_path)
+                .output_audited()
+                .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(format!("Failed to checkout PR #{id}: {}", stderr.trim()))
+            }
+        };
+        git(&["fetch", &remote, &source_branch])?;
+        if remote == "origin" {
+            git(&[
+                "checkout",
+                "-B",
+                &local_branch,
+                "--track",
+                &format!("origin/{source_branch}"),
+            ])?;
+        } else {
+            git(&["checkout", "-B", &local_branch, "FETCH_HEAD"])?;
+        }
+        Ok(local_branch)
+    }
+
+    /// Status of a pull request, as the PR status poller reports it
+    pub fn pull_request_status(
+        &self,
+        id: u32,
+        pr_url: &str,
+        worktree_id: &str,
+    ) -> Result<PrStatus, String> {
+        log::trace!("Fetching Bitbucket PR status for #{id}");
+        let pr = self.pull_request(id)?;
+        let statuses: Vec<BbBuildStatus> = self.list(
+            self.repo_url(&format!("pullrequests/{id}/statuses")),
+            Vec::new(),
+        )?;
+        Ok(status_from(&pr, &statuses, pr_url, worktree_id))
+    }
+}
+
+fn check_status(statuses: &[BbBuildStatus]) -> Option<CheckStatus> {
+    let any = |state: &str| statuses.iter().any(|s| s.state == state);
+    if statuses.is_empty() {
+        None
+    } else if any("FAILED") {
+        Some(CheckStatus::Failure)
+    } else if any("STOPPED") {
+        Some(CheckStatus::Error)
+    } else if any("INPROGRESS") {
+        Some(CheckStatus::Pending)
+    } else {
+        Some(CheckStatus::Success)
+    }
+}
+
+fn review_decision(participants: &[BbParticipant]) -> Option<ReviewDecision> {
+    if participants
+        .iter()
+        .any(|p| p.state.as_deref() == Some("changes_requested"))
+    {
+        Some(ReviewDecision::ChangesRequested)
+    } else if participants.iter().any(|p| p.approved) {
+        Some(ReviewDecision::Approved)
+    } else if participants.iter().any(|p| p.role == "REVIEWER") {
+        Some(ReviewDecision::ReviewRequired)
+    } else {
+        None
+    }
+}
+
+fn status_from(
+    pr: &BbPullRequest,
+    statuses: &[BbBuildStatus],
+    pr_url: &str,
+    worktree_id: &str,
+) -> PrStatus {
+    let state = match pr.state.as_str() {
+        "MERGED" => PrState::Merged,
+        "DECLINED" | "SUPERSEDED" => PrState::Closed,
+        _ => PrState::Open,
+    };
+    let review_decision = review_decision(&pr.participants);
+    PrStatus {
+        worktree_id: worktree_id.to_string(),
+        pr_number: pr.id,
+        pr_url: pr_url.to_string(),
+        display_status: compute_display_status(&state, pr.draft, &review_decision),
+        state,
+        is_draft: pr.draft,
+        review_decision,
+        check_status: check_status(statuses),
+        // Bitbucket doesn't report conflicts on pull requests
+        mergeable: None,
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::pr_status::PrDisplayStatus;
+
+    #[test]
+    fn test_parse_remote() {
+        let expected = Some(RepoIdentifier {
+            owner: "acme".to_string(),
+            repo: "api".to_string(),
+        });
+        assert_eq!(parse_remote("git@bitbucket.org:acme/api.git"), expected);
+        assert_eq!(
+            parse_remote("https://ann@bitbucket.org/acme/api.git"),
+            expected
+        );
+        assert_eq!(parse_remote("ssh://git@bitbucket.org/acme/api"), expected);
+        assert_eq!(parse_remote("https://github.com/acme/api"), None);
+        assert_eq!(
+            parse_remote("https://bitbucket.org.example.com/acme/api"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_convert_pull_request() {
+        let pr: BbPullRequest = serde_json::from_str(
+            r#"{"id":9,"title":"Fix login","description":"","state":"OPEN",
+                "source":{"branch":{"name":"fix-login"},"repository":{"full_name":"ann/api"}},
+                "destination":{"branch":{"name":"main"},"repository":{"full_name":"acme/api"}},
+                "created_on":"2024-01-02T00:00:00+00:00","author":{"display_name":"Ann","nickname":"ann"},
+                "participants":[{"role":"REVIEWER","approved":false,"state":null}]}"#,
+        )
+        .unwrap();
+        let statuses = [
+            BbBuildStatus {
+                state: "SUCCESSFUL".to_string(),
+            },
+            BbBuildStatus {
+                state: "FAILED".to_string(),
+            },
+        ];
+        let status = status_from(
+            &pr,
+            &statuses,
+            "https://bitbucket.org/acme/api/pull-requests/9",
+            "w1",
+        );
+        assert_eq!(status.check_status, Some(CheckStatus::Failure));
+        assert_eq!(status.review_decision, Some(ReviewDecision::ReviewRequired));
+        assert_eq!(status.display_status, PrDisplayStatus::Review);
+
+        let pr = pull_request(pr);
+        assert_eq!(pr.head_ref_name, "fix-login");
+        assert_eq!(pr.body, None);
+        assert_eq!(pr.author.login, "ann");
+    }
+}
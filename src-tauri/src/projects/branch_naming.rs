@@ -0,0 +1,215 @@
+//! Per-project branch naming schemes
+//!
+//! A project can set `branch_name_template` (e.g. `{user}/{type}/{slug}`) to
+//! shape the branches Jean creates from issues, for new worktrees and for
+//! automatic branch naming. Placeholders:
+//!
+//! - `{user}` — git `user.name` (or the `user.email` local part), slugified
+//! - `{type}` — the worktree template's name, or the conventional type the
+//!   branch namer picked (`feat`, `fix`...)
+//! - `{slug}` — short slug of the issue title or generated name
+//! - `{number}` — issue number
+//!
+//! Placeholders without a value are dropped along with the separator next to
+//! them, and the result must be a valid git branch name.
+
+use super::types::Project;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Values for a branch name template
+#[derive(Debug, Clone, Default)]
+pub struct BranchNameVars {
+    pub user: Option<String>,
+    pub kind: Option<String>,
+    pub slug: String,
+    pub number: Option<u32>,
+}
+
+/// Lowercase, with anything but letters, digits, `.` and `_` turned into `-`
+fn slugify(s: &str) -> String {
+    let slug: String = s
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Check a branch name against git's ref name rules (`git check-ref-format`)
+pub fn validate_ref_name(name: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("Invalid branch name '{name}': {reason}"));
+    if name.is_empty() || name == "@" {
+        return invalid("empty");
+    }
+    if name.starts_with('-') {
+        return invalid("starts with '-'");
+    }
+    if name.starts_with('/') || name.ends_with('/') || name.contains("//") {
+        return invalid("empty path component");
+    }
+    if name.ends_with('.') {
+        return invalid("ends with '.'");
+    }
+    if name.contains("..") || name.contains("@{") {
+        return invalid("contains '..' or '@{'");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_ascii_control() || " ~^:?*[\\".contains(*c))
+    {
+        return invalid(&format!("contains {c:?}"));
+    }
+    if name
+        .split('/')
+        .any(|part| part.starts_with('.') || part.ends_with(".lock"))
+    {
+        return invalid("component starts with '.' or ends with '.lock'");
+    }
+    Ok(())
+}
+
+/// Render a branch name template
+pub fn render(template: &str, vars: &BranchNameVars) -> Result<String, String> {
+    let user = vars.user.as_deref().map(slugify).unwrap_or_default();
+    let kind = vars.kind.as_deref().map(slugify).unwrap_or_default();
+    let number = vars.number.map(|n| n.to_string()).unwrap_or_default();
+    let rendered = template
+        .replace("{user}", &user)
+        .replace("{type}", &kind)
+        .replace("{slug}", &vars.slug)
+        .replace("{number}", &number);
+
+    // Drop separators left behind by empty placeholders
+    let name = rendered
+        .split('/')
+        .map(|part| {
+            part.split('-')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    validate_ref_name(&name)?;
+    Ok(name)
+}
+
+/// Check that a template renders to a valid branch name
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if !template.contains("{slug}") && !template.contains("{number}") {
+        return Err("Branch name template needs {slug} or {number}".to_string());
+    }
+    let sample = BranchNameVars {
+        user: Some("user".to_string()),
+        kind: Some("feat".to_string()),
+        slug: "sample".to_string(),
+        number: Some(1),
+    };
+    render(template, &sample).map(|_| ())
+}
+
+/// A project's naming scheme with the values known up front
+#[derive(Debug, Clone)]
+pub struct BranchNameScheme {
+    pub template: String,
+    pub user: Option<String>,
+    pub kind: Option<String>,
+}
+
+impl BranchNameScheme {
+    /// Scheme for a project, if it has a branch name template
+    pub fn for_project(project: &Project, kind: Option<String>) -> Option<Self> {
+        let template = project
+            .branch_name_template
+            .clone()
+            .filter(|t| !t.trim().is_empty())?;
+        Some(Self {
+            template,
+            user: git_user(&project.path),
+            kind,
+        })
+    }
+
+    /// Branch name for a slug, or None (logged) if the template doesn't
+    /// produce a valid name
+    pub fn apply(&self, slug: &str, number: Option<u32>) -> Option<String> {
+        let vars = BranchNameVars {
+            user: self.user.clone(),
+            kind: self.kind.clone(),
+            slug: slug.to_string(),
+            number,
+        };
+        match render(&self.template, &vars) {
+            Ok(name) => Some(name),
+            Err(e) => {
+                log::warn!("Branch name template not applied: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// `{user}` for a repository: git user.name, else the user.email local part
+pub fn git_user(repo_path: &str) -> Option<String> {
+    let config = |key: &str| {
+        let output = silent_command("git")
+            .args(["config", key])
+            .current_dir(repo_path)
+            .output_audited()
+            .ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !value.is_empty()).then_some(value)
+    };
+    config("user.name").or_else(|| {
+        config("user.email").and_then(|email| email.split('@').next().map(str::to_string))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars = BranchNameVars {
+            user: Some("Ada Lovelace".to_string()),
+            kind: Some("fix".to_string()),
+            slug: "login-bug".to_string(),
+            number: Some(42),
+        };
+        assert_eq!(
+            render("{user}/{type}/{number}-{slug}", &vars).unwrap(),
+            "ada-lovelace/fix/42-login-bug"
+        );
+
+        let vars = BranchNameVars {
+            slug: "fuzzy-tiger".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("{user}/{type}/{number}-{slug}", &vars).unwrap(),
+            "fuzzy-tiger"
+        );
+        assert!(render("feat..{slug}", &vars).is_err());
+    }
+
+    #[test]
+    fn test_validate_ref_name() {
+        assert!(validate_ref_name("team/feat/x").is_ok());
+        for bad in ["a b", "a..b", "a/.b", "a.lock", "a/", "-a", "a~1", "a@{1}"] {
+            assert!(validate_ref_name(bad).is_err(), "{bad}");
+        }
+        assert!(validate_template("{type}-only").is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! PR checks and GitHub Actions logs
+//!
+//! The cached check status of a worktree only says whether its PR's checks
+//! pass. These commands list the individual checks (`gh pr checks`) and load
+//! the log of a GitHub Actions job (`gh run view --log`), so failing CI output
+//! can be read or attached as context. Checks that don't come from Actions
+//! have no job and therefore no log.
+//!
+//! `investigate_ci_failure` bundles the failing checks, the failed steps of
+//! their logs and the branch's recent commits into the
+//! `investigate_ci_failure` magic prompt, and emits it as
+//! `ci:investigate-prompt` for the frontend to send to the worktree's active
+//! session.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::git_queue;
+use super::storage::load_projects_data;
+use super::types::Worktree;
+use crate::background_tasks::ci_watcher::{
+    build_prompt, failing_checks, failing_logs, job_id, pr_checks, tail,
+};
+use crate::command_audit::AuditedCommand;
+use crate::gh_cli::config::resolve_gh_binary;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+
+/// Most log bytes returned for a job (the end of the log is kept)
+const MAX_LOG_BYTES: usize = 100_000;
+
+/// Recent commits included in an investigation prompt
+const RECENT_COMMITS: &str = "10";
+
+/// A check of a PR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrCheck {
+    /// GitHub Actions job ID, used to fetch the log (None for other checks)
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    /// e.g. SUCCESS, FAILURE, IN_PROGRESS, SKIPPED
+    #[serde(default)]
+    pub state: String,
+    /// pass, fail, pending, skipping or cancel
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub workflow: String,
+    #[serde(default)]
+    pub link: String,
+    #[serde(default, alias = "startedAt")]
+    pub started_at: Option<String>,
+    #[serde(default, alias = "completedAt")]
+    pub completed_at: Option<String>,
+}
+
+/// Fill in the job IDs of checks from their links
+fn with_job_ids(mut checks: Vec<PrCheck>) -> Vec<PrCheck> {
+    for check in &mut checks {
+        check.id = job_id(&check.link).map(str::to_string);
+    }
+    checks
+}
+
+fn worktree_with_pr(app: &AppHandle, worktree_id: &str) -> Result<(Worktree, u32), String> {
+    let data = load_projects_data(app)?;
+    let worktree = data
+        .find_worktree(worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?
+        .clone();
+    let pr_number = worktree
+        .pr_number
+        .ok_or_else(|| format!("Worktree {} has no pull request", worktree.name))?;
+    Ok((worktree, pr_number))
+}
+
+/// Prepared investigation prompt, payload of `ci:investigate-prompt`
+#[derive(Debug, Clone, Serialize)]
+pub struct CiInvestigationPrompt {
+    pub worktree_id: String,
+    /// Active session of the worktree the prompt is meant for (None when the
+    /// worktree has no session yet)
+    pub session_id: Option<String>,
+    pub pr_number: u32,
+    pub failing_checks: Vec<String>,
+    pub prompt: String,
+    /// Model configured for the investigate-CI-failure prompt
+    pub model: String,
+}
+
+fn fetch_checks(
+    app: &AppHandle,
+    worktree: &Worktree,
+    pr_number: u32,
+) -> Result<Vec<PrCheck>, String> {
+    pr_checks(
+        &resolve_gh_binary(app),
+        &worktree.path,
+        pr_number,
+        "name,state,bucket,workflow,link,startedAt,completedAt",
+    )
+    .map(with_job_ids)
+}
+
+/// List the checks of a worktree's PR
+#[tauri::command]
+pub async fn list_pr_checks(app: AppHandle, worktree_id: String) -> Result<Vec<PrCheck>, String> {
+    let (worktree, pr_number) = worktree_with_pr(&app, &worktree_id)?;
+    log::trace!("Listing checks of PR #{pr_number} for {}", worktree.name);
+    fetch_checks(&app, &worktree, pr_number)
+}
+
+/// Load the log of a GitHub Actions job of a worktree's PR (truncated to its
+/// last 100KB)
+#[tauri::command]
+pub async fn get_check_run_log(
+    app: AppHandle,
+    worktree_id: String,
+    check_id: String,
+) -> Result<String, String> {
+    if check_id.is_empty() || !check_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid check ID: {check_id}"));
+    }
+    let (worktree, _) = worktree_with_pr(&app, &worktree_id)?;
+    log::trace!("Loading log of job {check_id} for {}", worktree.name);
+
+    let output = silent_command(resolve_gh_binary(&app))
+        .args(["run", "view", "--job", &check_id, "--log"])
+        .current_dir(&worktree.path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run gh run view: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to load check log: {}", stderr.trim()));
+    }
+    Ok(tail(
+        &String::from_utf8_lossy(&output.stdout),
+        MAX_LOG_BYTES,
+    ))
+}
+
+/// One-line summaries of the branch's most recent commits
+fn recent_commits(repo_path: &str) -> String {
+    git_queue::read_command()
+        .args(["log", "--oneline", "--no-decorate", "-n", RECENT_COMMITS])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Prepare the investigate-CI-failure prompt for a worktree's PR and emit it
+/// as `ci:investigate-prompt` for its active session
+#[tauri::command]
+pub async fn investigate_ci_failure(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<CiInvestigationPrompt, String> {
+    let (worktree, pr_number) = worktree_with_pr(&app, &worktree_id)?;
+    let gh = resolve_gh_binary(&app);
+    let failing = failing_checks(&gh, &worktree.path, pr_number)?;
+    if failing.is_empty() {
+        return Err(format!("No failing checks on PR #{pr_number}"));
+    }
+    log::info!(
+        "Preparing CI investigation for PR #{pr_number} ({} failing check(s)) in {}",
+        failing.len(),
+        worktree.name
+    );
+
+    let preferences = crate::load_preferences(app.clone()).await?;
+    let names: Vec<String> = failing.iter().map(|c| c.name.clone()).collect();
+    let logs = failing_logs(&gh, &worktree.path, &failing);
+    let commits = recent_commits(&worktree.path);
+    let commits = if commits.is_empty() {
+        "(none found)"
+    } else {
+        &commits
+    };
+    let template = preferences
+        .magic_prompts
+        .investigate_ci_failure
+        .replace("{recentCommits}", commits);
+    let prompt = build_prompt(&template, pr_number, &failing, &logs);
+    let session_id = crate::chat::storage::load_index(&app, &worktree.id)?.active_session_id;
+
+    let payload = CiInvestigationPrompt {
+        worktree_id,
+        session_id,
+        pr_number,
+        failing_checks: names,
+        prompt,
+        model: preferences
+            .magic_prompt_models
+            .investigate_ci_failure_model
+            .clone(),
+    };
+    app.emit_all("ci:investigate-prompt", &payload)
+        .map_err(|e| format!("Failed to emit ci:investigate-prompt event: {e}"))?;
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checks() {
+        let json = r#"[
+            {"name":"test","state":"FAILURE","bucket":"fail","workflow":"CI",
+             "link":"https://github.com/o/r/actions/runs/1/job/42","startedAt":"2024-01-01T00:00:00Z"},
+            {"name":"vercel","state":"SUCCESS","bucket":"pass","link":"https://vercel.com/o/r"}
+        ]"#;
+        let checks = with_job_ids(serde_json::from_str(json).unwrap());
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].id.as_deref(), Some("42"));
+        assert_eq!(checks[0].workflow, "CI");
+        assert_eq!(checks[1].id, None);
+        assert_eq!(checks[1].completed_at, None);
+    }
+}
@@ -0,0 +1,259 @@
+//! Worktree cleanup suggestions
+//!
+//! Flags worktrees that are likely done with: their PR was merged a while
+//! ago, their branch is fully merged into the branch it targets, or they have
+//! had no commits or session activity for a long time. Unlike the auto-archive
+//! rule nothing is changed automatically; each suggestion carries the action
+//! to apply with `apply_cleanup_action`.
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::auto_archive::{is_stale, worktree_activity, WorktreeActivity};
+use super::storage::load_projects_data;
+use super::types::{SessionType, Worktree};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Days since a PR was merged before its worktree is suggested for deletion
+const MERGED_PR_DAYS: u64 = 3;
+
+/// Days without activity before a worktree is suggested for archiving
+const INACTIVE_DAYS: u64 = 30;
+
+const DAY_SECS: u64 = 86400;
+
+/// What to do with a suggested worktree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupAction {
+    Archive,
+    Delete,
+}
+
+/// Why a worktree is suggested for cleanup
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CleanupReason {
+    /// The worktree's PR was merged `days` days ago
+    PrMerged { pr_number: u32, days: u64 },
+    /// Every commit of the branch is on the branch it targets
+    BranchMerged { target_branch: String },
+    /// No commits or session activity for `days` days
+    Inactive { days: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupSuggestion {
+    pub worktree_id: String,
+    pub worktree_name: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub reasons: Vec<CleanupReason>,
+    /// Uncommitted changes that deleting would lose
+    pub has_uncommitted_changes: bool,
+    /// Suggested action; the frontend offers both
+    pub action: CleanupAction,
+}
+
+/// Unix timestamp of the latest commit or session activity
+fn last_activity(activity: &WorktreeActivity) -> u64 {
+    [activity.last_commit_at, activity.last_session_at]
+        .into_iter()
+        .flatten()
+        .fold(activity.created_at, u64::max)
+}
+
+/// Reasons to clean up a worktree, given what is known about it
+pub fn reasons(
+    activity: &WorktreeActivity,
+    merged_pr: Option<(u32, u64)>,
+    merged_into: Option<&str>,
+    now: u64,
+) -> Vec<CleanupReason> {
+    let mut reasons = Vec::new();
+    if let Some((pr_number, merged_at)) = merged_pr {
+        let days = now.saturating_sub(merged_at) / DAY_SECS;
+        if days >= MERGED_PR_DAYS {
+            reasons.push(CleanupReason::PrMerged { pr_number, days });
+        }
+    }
+    if let Some(target_branch) = merged_into {
+        reasons.push(CleanupReason::BranchMerged {
+            target_branch: target_branch.to_string(),
+        });
+    }
+    if is_stale(activity, now.saturating_sub(INACTIVE_DAYS * DAY_SECS)) {
+        reasons.push(CleanupReason::Inactive {
+            days: now.saturating_sub(last_activity(activity)) / DAY_SECS,
+        });
+    }
+    reasons
+}
+
+/// Suggested action: merged work with nothing uncommitted can go
+fn action(reasons: &[CleanupReason], has_uncommitted_changes: bool) -> CleanupAction {
+    let merged = reasons
+        .iter()
+        .any(|r| !matches!(r, CleanupReason::Inactive { .. }));
+    if merged && !has_uncommitted_changes {
+        CleanupAction::Delete
+    } else {
+        CleanupAction::Archive
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrMergedAt {
+    merged_at: Option<String>,
+}
+
+/// When the worktree's PR was merged, as a unix timestamp
+fn pr_merged_at(app: &AppHandle, worktree: &Worktree, pr_number: u32) -> Option<u64> {
+    let gh = crate::gh_cli::config::resolve_gh_binary(app);
+    let output = silent_command(&gh)
+        .args(["pr", "view", &pr_number.to_string(), "--json", "mergedAt"])
+        .current_dir(&worktree.path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let pr: PrMergedAt = serde_json::from_slice(&output.stdout).ok()?;
+    let merged_at = DateTime::parse_from_rfc3339(&pr.merged_at?).ok()?;
+    u64::try_from(merged_at.timestamp()).ok()
+}
+
+/// Whether the branch has commits of its own that are all on the target
+/// branch. A branch that never got a commit isn't "merged".
+fn branch_merged(worktree: &Worktree, target_branch: &str, activity: &WorktreeActivity) -> bool {
+    if activity
+        .last_commit_at
+        .is_none_or(|at| at <= worktree.created_at)
+    {
+        return false;
+    }
+    silent_command("git")
+        .args([
+            "merge-base",
+            "--is-ancestor",
+            "HEAD",
+            &format!("origin/{target_branch}"),
+        ])
+        .current_dir(&worktree.path)
+        .output_audited()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Worktrees that look done with, optionally for one project
+#[tauri::command]
+pub async fn get_cleanup_suggestions(
+    app: AppHandle,
+    project_id: Option<String>,
+) -> Result<Vec<CleanupSuggestion>, String> {
+    log::trace!("Collecting cleanup suggestions");
+    let data = load_projects_data(&app)?;
+    let running = crate::chat::registry::get_running_sessions();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut suggestions = Vec::new();
+    for worktree in &data.worktrees {
+        if worktree.session_type == SessionType::Base
+            || worktree.archived_at.is_some()
+            || project_id
+                .as_ref()
+                .is_some_and(|id| *id != worktree.project_id)
+            || !std::path::Path::new(&worktree.path).exists()
+        {
+            continue;
+        }
+        let Some(project) = data.find_project(&worktree.project_id) else {
+            continue;
+        };
+        let activity = worktree_activity(&app, worktree, &running);
+        if activity.session_running {
+            continue;
+        }
+
+        let merged_pr = worktree
+            .pr_number
+            .filter(|_| worktree.cached_pr_status.as_deref() == Some("merged"))
+            .and_then(|n| Some((n, pr_merged_at(&app, worktree, n)?)));
+        let target_branch = super::stacks::pr_base(&data, project, worktree);
+        let merged_into =
+            branch_merged(worktree, target_branch, &activity).then_some(target_branch);
+
+        let reasons = reasons(&activity, merged_pr, merged_into, now);
+        if reasons.is_empty() {
+            continue;
+        }
+        suggestions.push(CleanupSuggestion {
+            worktree_id: worktree.id.clone(),
+            worktree_name: worktree.name.clone(),
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            action: action(&reasons, activity.has_uncommitted_changes),
+            reasons,
+            has_uncommitted_changes: activity.has_uncommitted_changes,
+        });
+    }
+    Ok(suggestions)
+}
+
+/// Archive or delete a suggested worktree
+#[tauri::command]
+pub async fn apply_cleanup_action(
+    app: AppHandle,
+    worktree_id: String,
+    action: CleanupAction,
+) -> Result<(), String> {
+    log::trace!("Applying cleanup action {action:?} to worktree {worktree_id}");
+    match action {
+        CleanupAction::Archive => super::archive_worktree(app, worktree_id).await,
+        CleanupAction::Delete => super::delete_worktree(app, worktree_id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reasons() {
+        let now = 100 * DAY_SECS;
+        let active = WorktreeActivity {
+            created_at: now - 60 * DAY_SECS,
+            last_commit_at: Some(now - DAY_SECS),
+            ..Default::default()
+        };
+        // Merged too recently to suggest
+        assert!(reasons(&active, Some((7, now - DAY_SECS)), None, now).is_empty());
+
+        let merged = reasons(&active, Some((7, now - 10 * DAY_SECS)), Some("main"), now);
+        assert_eq!(
+            merged,
+            vec![
+                CleanupReason::PrMerged {
+                    pr_number: 7,
+                    days: 10
+                },
+                CleanupReason::BranchMerged {
+                    target_branch: "main".to_string()
+                },
+            ]
+        );
+        assert_eq!(action(&merged, false), CleanupAction::Delete);
+        assert_eq!(action(&merged, true), CleanupAction::Archive);
+
+        let idle = WorktreeActivity {
+            last_commit_at: Some(now - 45 * DAY_SECS),
+            ..active
+        };
+        let inactive = reasons(&idle, None, None, now);
+        assert_eq!(inactive, vec![CleanupReason::Inactive { days: 45 }]);
+        assert_eq!(action(&inactive, false), CleanupAction::Archive);
+    }
+}
@@ -0,0 +1,226 @@
+//! Code search within a worktree
+//!
+//! Searches file contents with ripgrep's matcher and searcher (the `grep`
+//! crate), walking the worktree with the same .gitignore rules as the file
+//! listing. Results are capped so a broad query can't flood the UI or a prompt.
+
+use std::path::Path;
+
+use grep::matcher::Matcher;
+use grep::regex::RegexMatcherBuilder;
+use grep::searcher::sinks::UTF8;
+use grep::searcher::{BinaryDetection, SearcherBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+/// Matches returned when no limit is given
+const DEFAULT_MAX_RESULTS: usize = 500;
+
+/// Largest number of matches that can be requested
+const MAX_RESULTS: usize = 5000;
+
+/// Snippets longer than this are cut down to the area around the match
+const MAX_SNIPPET_LEN: usize = 300;
+
+/// Files larger than this are skipped
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Options for `search_in_worktree`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression (literal text otherwise)
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Only search paths matching these globs (e.g. "src/**/*.rs")
+    pub include: Vec<String>,
+    /// Skip paths matching these globs
+    pub exclude: Vec<String>,
+    pub max_results: Option<usize>,
+}
+
+/// A matching line
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    /// File path relative to the worktree root
+    pub path: String,
+    /// 1-based line number
+    pub line_number: u64,
+    /// The matching line (shortened around the match if very long)
+    pub snippet: String,
+    /// Byte range of the first match within `snippet`
+    pub match_start: usize,
+    pub match_end: usize,
+}
+
+/// Search results
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    /// Whether the search stopped at the result limit
+    pub truncated: bool,
+    pub files_searched: usize,
+}
+
+/// Cut a long line down to the area around `start..end`, on char boundaries.
+/// Returns the snippet and the match range within it.
+fn snippet(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    let line = line.trim_end_matches(['\n', '\r']);
+    if line.len() <= MAX_SNIPPET_LEN {
+        return (line.to_string(), start, end.min(line.len()));
+    }
+    let mut from = start.saturating_sub(MAX_SNIPPET_LEN / 3);
+    while !line.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (from + MAX_SNIPPET_LEN).min(line.len());
+    while !line.is_char_boundary(to) {
+        to -= 1;
+    }
+    (line[from..to].to_string(), start - from, end.min(to) - from)
+}
+
+/// Search the files of `root` for `query`
+pub fn search(root: &Path, query: &str, options: &SearchOptions) -> Result<SearchResults, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS);
+
+    let matcher = RegexMatcherBuilder::new()
+        .fixed_strings(!options.regex)
+        .case_insensitive(!options.case_sensitive)
+        .word(options.whole_word)
+        .build(query)
+        .map_err(|e| format!("Invalid search query: {e}"))?;
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid include pattern '{glob}': {e}"))?;
+    }
+    for glob in &options.exclude {
+        overrides
+            .add(&format!("!{glob}"))
+            .map_err(|e| format!("Invalid exclude pattern '{glob}': {e}"))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| format!("Invalid search patterns: {e}"))?;
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .overrides(overrides)
+        .max_filesize(Some(MAX_FILE_SIZE))
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut searcher = SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .line_number(true)
+        .build();
+
+    let mut matches = Vec::new();
+    let mut files_searched = 0;
+    let mut truncated = false;
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if matches.len() >= max_results {
+            truncated = true;
+            break;
+        }
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        files_searched += 1;
+
+        let result = searcher.search_path(
+            &matcher,
+            path,
+            UTF8(|line_number, line| {
+                if matches.len() >= max_results {
+                    truncated = true;
+                    return Ok(false);
+                }
+                let (start, end) = match matcher.find(line.as_bytes())? {
+                    Some(m) => (m.start(), m.end()),
+                    None => (0, 0),
+                };
+                let (snippet, match_start, match_end) = snippet(line, start, end);
+                matches.push(SearchMatch {
+                    path: relative.clone(),
+                    line_number,
+                    snippet,
+                    match_start,
+                    match_end,
+                });
+                Ok(true)
+            }),
+        );
+        if let Err(e) = result {
+            // Unreadable or non-UTF-8 files are skipped
+            log::trace!("Skipping {relative} in search: {e}");
+        }
+    }
+
+    Ok(SearchResults {
+        matches,
+        truncated,
+        files_searched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_respects_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn main() {}\nlet Main = 1;\n").unwrap();
+        std::fs::write(root.join("notes.md"), "main idea\n").unwrap();
+
+        let all = search(root, "main", &SearchOptions::default()).unwrap();
+        assert_eq!(all.matches.len(), 3);
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            include: vec!["*.rs".to_string()],
+            ..Default::default()
+        };
+        let scoped = search(root, "main", &options).unwrap();
+        assert_eq!(scoped.matches.len(), 1);
+        let m = &scoped.matches[0];
+        assert_eq!(m.line_number, 1);
+        assert_eq!(&m.snippet[m.match_start..m.match_end], "main");
+
+        let options = SearchOptions {
+            max_results: Some(1),
+            ..Default::default()
+        };
+        assert!(search(root, "main", &options).unwrap().truncated);
+    }
+
+    #[test]
+    fn test_snippet_shortens_long_lines() {
+        let line = format!("{}needle{}", "a".repeat(1000), "b".repeat(1000));
+        let (text, start, end) = snippet(&line, 1000, 1006);
+
+        assert!(text.len() <= MAX_SNIPPET_LEN);
+        assert_eq!(&text[start..end], "needle");
+    }
+}
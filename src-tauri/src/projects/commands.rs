@@ -1,32 +1,53 @@
-use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::Write;
 use std::path::Path;
 use std::process::Stdio;
 use std::thread;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tauri_plugin_dialog::DialogExt;
 use uuid::Uuid;
 
+use super::branch_naming::{self, BranchNameScheme};
+use super::commit_hooks::{self, CommitOutcome};
+use super::compose;
+use super::conventional_commits;
+use super::coverage;
+use super::devcontainer;
 use super::git;
 use super::git::get_repo_identifier;
 use super::github_issues::{
-    add_issue_reference, add_pr_reference, format_issue_context_markdown,
+    add_issue_labels, add_issue_reference, add_pr_reference, format_issue_context_markdown,
     format_pr_context_markdown, generate_branch_name_from_issue, generate_branch_name_from_pr,
     get_github_contexts_dir, get_github_pr, get_pr_diff, IssueContext, PullRequestContext,
 };
+use super::import_scan::{self, ScannedRepo};
+use super::license_check;
+use super::linear;
 use super::names::generate_unique_workspace_name;
-use super::storage::{get_project_worktrees_dir, load_projects_data, save_projects_data};
+use super::packages;
+use super::pr_checklist;
+use super::stacks;
+use super::storage::{
+    get_project_worktrees_dir, load_projects_data, update_worktree, with_projects_data_mut,
+};
+use super::tickets;
+use super::toolchain;
 use super::types::{
-    MergeType, Project, SessionType, Worktree, WorktreeArchivedEvent, WorktreeBranchExistsEvent,
+    LicensePolicy, McpServer, MergeType, PrChecklist, Project, ProjectsData, SessionType,
+    SlackSettings, Worktree, WorktreeArchivedEvent, WorktreeBranchExistsEvent,
     WorktreeCreateErrorEvent, WorktreeCreatedEvent, WorktreeCreatingEvent,
     WorktreeDeleteErrorEvent, WorktreeDeletedEvent, WorktreeDeletingEvent, WorktreePathExistsEvent,
-    WorktreePermanentlyDeletedEvent, WorktreeUnarchivedEvent,
+    WorktreePermanentlyDeletedEvent, WorktreeTemplate, WorktreeUnarchivedEvent,
 };
 use crate::claude_cli::get_cli_binary_path;
+use crate::command_audit::AuditedCommand;
+use crate::db::journal::JournalStep;
 use crate::gh_cli::config::resolve_gh_binary;
 use crate::http_server::EmitExt;
+use crate::jobs::JobKind;
+use crate::notifications::{self, NotificationEvent};
 use crate::platform::silent_command;
 
 /// Get current Unix timestamp
@@ -43,7 +64,7 @@ fn now() -> u64 {
 pub async fn check_git_identity() -> Result<GitIdentity, String> {
     let name = silent_command("git")
         .args(["config", "--global", "user.name"])
-        .output()
+        .output_audited()
         .ok()
         .filter(|o| o.status.success())
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
@@ -51,7 +72,7 @@ pub async fn check_git_identity() -> Result<GitIdentity, String> {
 
     let email = silent_command("git")
         .args(["config", "--global", "user.email"])
-        .output()
+        .output_audited()
         .ok()
         .filter(|o| o.status.success())
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
@@ -65,7 +86,7 @@ pub async fn check_git_identity() -> Result<GitIdentity, String> {
 pub async fn set_git_identity(name: String, email: String) -> Result<(), String> {
     let name_output = silent_command("git")
         .args(["config", "--global", "user.name", &name])
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to set git user.name: {e}"))?;
 
     if !name_output.status.success() {
@@ -75,7 +96,7 @@ pub async fn set_git_identity(name: String, email: String) -> Result<(), String>
 
     let email_output = silent_command("git")
         .args(["config", "--global", "user.email", &email])
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to set git user.email: {e}"))?;
 
     if !email_output.status.success() {
@@ -123,31 +144,180 @@ pub async fn add_project(
     // Fall back to "main" if HEAD doesn't exist yet (no commits)
     let default_branch = git::get_current_branch(&path).unwrap_or_else(|_| "main".to_string());
 
-    // Check if project already exists
-    let mut data = load_projects_data(&app)?;
-    if data.projects.iter().any(|p| p.path == path) {
-        return Err(format!("Project already exists: {path}"));
+    let project = with_projects_data_mut(&app, |data| {
+        // Check if project already exists
+        if data.projects.iter().any(|p| p.path == path) {
+            return Err(format!("Project already exists: {path}"));
+        }
+
+        // Create project with order at the end of the specified parent level
+        let max_order = data.get_next_order(parent_id.as_deref());
+        let project = Project {
+            id: Uuid::new_v4().to_string(),
+            name,
+            path,
+            default_branch,
+            added_at: now(),
+            order: max_order,
+            parent_id,
+            is_folder: false,
+            avatar_path: None,
+            polling: None,
+            use_devcontainer: false,
+            templates: Vec::new(),
+            branch_name_template: None,
+            ticket_pattern: None,
+            slack: None,
+            pr_checklist: None,
+            license_policy: None,
+            remote: None,
+            mcp_servers: Default::default(),
+            tool_policy: None,
+        };
+        data.add_project(project.clone());
+        Ok(project)
+    })?;
+
+    log::trace!("Successfully added project: {}", project.name);
+    Ok(project)
+}
+
+/// Preview the git repositories under a directory for bulk import
+#[tauri::command]
+pub async fn scan_projects_dir(
+    app: AppHandle,
+    root_dir: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<ScannedRepo>, String> {
+    log::trace!("Scanning for git repositories under {root_dir}");
+    let root = std::path::PathBuf::from(&root_dir);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {root_dir}"));
+    }
+    let max_depth = max_depth.unwrap_or(import_scan::DEFAULT_MAX_DEPTH);
+    let mut repos =
+        tauri::async_runtime::spawn_blocking(move || import_scan::scan(&root, max_depth))
+            .await
+            .map_err(|e| format!("Failed to scan {root_dir}: {e}"))?;
+
+    let data = load_projects_data(&app)?;
+    for repo in &mut repos {
+        repo.already_added = data.projects.iter().any(|p| p.path == repo.path);
     }
+    log::trace!("Found {} repositories", repos.len());
+    Ok(repos)
+}
 
-    // Create project with order at the end of the specified parent level
-    let max_order = data.get_next_order(parent_id.as_deref());
-    let project = Project {
+/// Folder with this name under `parent_id`, created if missing
+fn find_or_create_folder(data: &mut ProjectsData, name: &str, parent_id: Option<String>) -> String {
+    if let Some(folder) = data
+        .projects
+        .iter()
+        .find(|p| p.is_folder && p.name == name && p.parent_id == parent_id)
+    {
+        return folder.id.clone();
+    }
+    let folder = Project {
         id: Uuid::new_v4().to_string(),
-        name,
-        path,
-        default_branch,
+        name: name.to_string(),
+        path: String::new(),
+        default_branch: String::new(),
         added_at: now(),
-        order: max_order,
+        order: data.get_next_order(parent_id.as_deref()),
         parent_id,
-        is_folder: false,
+        is_folder: true,
         avatar_path: None,
+        polling: None,
+        use_devcontainer: false,
+        templates: Vec::new(),
+        branch_name_template: None,
+        ticket_pattern: None,
+        slack: None,
+        pr_checklist: None,
+        license_policy: None,
+        remote: None,
+        mcp_servers: Default::default(),
+        tool_policy: None,
     };
+    let id = folder.id.clone();
+    data.add_project(folder);
+    id
+}
 
-    data.add_project(project.clone());
-    save_projects_data(&app, &data)?;
+/// Scan a directory for git repositories and add them as projects
+///
+/// `paths` selects repositories from a `scan_projects_dir` preview (None =
+/// every repository not added yet). With `mirror_folders`, each project is
+/// filed into sidebar folders named after the directories between the root
+/// and the repository.
+#[tauri::command]
+pub async fn scan_and_import_projects(
+    app: AppHandle,
+    root_dir: String,
+    paths: Option<Vec<String>>,
+    mirror_folders: bool,
+) -> Result<Vec<Project>, String> {
+    let repos = scan_projects_dir(app.clone(), root_dir, None).await?;
+    log::trace!("Importing projects (mirror_folders: {mirror_folders})");
+
+    // Resolve default branches before taking the projects lock
+    let repos: Vec<_> = repos
+        .into_iter()
+        .filter(|repo| match &paths {
+            Some(paths) => paths.contains(&repo.path),
+            None => true,
+        })
+        .map(|repo| {
+            // Fall back to "main" if HEAD doesn't exist yet (no commits)
+            let default_branch =
+                git::get_current_branch(&repo.path).unwrap_or_else(|_| "main".to_string());
+            (repo, default_branch)
+        })
+        .collect();
 
-    log::trace!("Successfully added project: {}", project.name);
-    Ok(project)
+    let imported = with_projects_data_mut(&app, |data| {
+        let mut imported = Vec::new();
+        for (repo, default_branch) in repos {
+            if data.projects.iter().any(|p| p.path == repo.path) {
+                continue;
+            }
+
+            let mut parent_id = None;
+            if mirror_folders {
+                for folder in import_scan::folder_names(&repo.relative_dirs) {
+                    parent_id = Some(find_or_create_folder(data, &folder, parent_id));
+                }
+            }
+
+            let project = Project {
+                id: Uuid::new_v4().to_string(),
+                name: repo.name,
+                path: repo.path,
+                default_branch,
+                added_at: now(),
+                order: data.get_next_order(parent_id.as_deref()),
+                parent_id,
+                is_folder: false,
+                avatar_path: None,
+                polling: None,
+                use_devcontainer: false,
+                templates: Vec::new(),
+                branch_name_template: None,
+                ticket_pattern: None,
+                slack: None,
+                pr_checklist: None,
+                license_policy: None,
+                remote: None,
+                mcp_servers: Default::default(),
+                tool_policy: None,
+            };
+            data.add_project(project.clone());
+            imported.push(project);
+        }
+        Ok(imported)
+    })?;
+    log::trace!("Imported {} projects", imported.len());
+    Ok(imported)
 }
 
 /// Initialize git in an existing folder (without adding to project list)
@@ -183,7 +353,7 @@ pub async fn init_git_in_folder(path: String) -> Result<String, String> {
         let has_commits = silent_command("git")
             .args(["rev-parse", "HEAD"])
             .current_dir(&path)
-            .output()
+            .output_audited()
             .map(|o| o.status.success())
             .unwrap_or(false);
 
@@ -199,7 +369,7 @@ pub async fn init_git_in_folder(path: String) -> Result<String, String> {
         let output = silent_command("git")
             .args(["init"])
             .current_dir(&path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to run git init: {e}"))?;
 
         if !output.status.success() {
@@ -212,7 +382,7 @@ pub async fn init_git_in_folder(path: String) -> Result<String, String> {
     let add_output = silent_command("git")
         .args(["add", "."])
         .current_dir(&path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git add: {e}"))?;
 
     if !add_output.status.success() {
@@ -224,7 +394,7 @@ pub async fn init_git_in_folder(path: String) -> Result<String, String> {
     let commit_output = silent_command("git")
         .args(["commit", "-m", "Initial commit"])
         .current_dir(&path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git commit: {e}"))?;
 
     if !commit_output.status.success() {
@@ -238,7 +408,7 @@ pub async fn init_git_in_folder(path: String) -> Result<String, String> {
             let empty_commit = silent_command("git")
                 .args(["commit", "--allow-empty", "-m", "Initial commit"])
                 .current_dir(&path)
-                .output()
+                .output_audited()
                 .map_err(|e| format!("Failed to create empty commit: {e}"))?;
 
             if !empty_commit.status.success() {
@@ -273,28 +443,39 @@ pub async fn init_project(
     // Get it from git to be sure
     let default_branch = git::get_current_branch(&path).unwrap_or_else(|_| "main".to_string());
 
-    // Check if project already exists
-    let mut data = load_projects_data(&app)?;
-    if data.projects.iter().any(|p| p.path == path) {
-        return Err(format!("Project already exists: {path}"));
-    }
-
-    // Create project with order at the end of the specified parent level
-    let max_order = data.get_next_order(parent_id.as_deref());
-    let project = Project {
-        id: Uuid::new_v4().to_string(),
-        name,
-        path,
-        default_branch,
-        added_at: now(),
-        order: max_order,
-        parent_id,
-        is_folder: false,
-        avatar_path: None,
-    };
+    let project = with_projects_data_mut(&app, |data| {
+        // Check if project already exists
+        if data.projects.iter().any(|p| p.path == path) {
+            return Err(format!("Project already exists: {path}"));
+        }
 
-    data.add_project(project.clone());
-    save_projects_data(&app, &data)?;
+        // Create project with order at the end of the specified parent level
+        let max_order = data.get_next_order(parent_id.as_deref());
+        let project = Project {
+            id: Uuid::new_v4().to_string(),
+            name,
+            path,
+            default_branch,
+            added_at: now(),
+            order: max_order,
+            parent_id,
+            is_folder: false,
+            avatar_path: None,
+            polling: None,
+            use_devcontainer: false,
+            templates: Vec::new(),
+            branch_name_template: None,
+            ticket_pattern: None,
+            slack: None,
+            pr_checklist: None,
+            license_policy: None,
+            remote: None,
+            mcp_servers: Default::default(),
+            tool_policy: None,
+        };
+        data.add_project(project.clone());
+        Ok(project)
+    })?;
 
     log::trace!("Successfully initialized project: {}", project.name);
     Ok(project)
@@ -307,64 +488,57 @@ pub async fn init_project(
 pub async fn remove_project(app: AppHandle, project_id: String) -> Result<(), String> {
     log::trace!("Removing project: {project_id}");
 
-    let mut data = load_projects_data(&app)?;
-
-    // Check if project has active (non-archived) worktrees
-    let has_active_worktrees = data
-        .worktrees
-        .iter()
-        .any(|w| w.project_id == project_id && w.archived_at.is_none());
+    let (project_path, archived_worktree_ids) = with_projects_data_mut(&app, |data| {
+        // Check if project has active (non-archived) worktrees
+        let has_active_worktrees = data
+            .worktrees
+            .iter()
+            .any(|w| w.project_id == project_id && w.archived_at.is_none());
+
+        if has_active_worktrees {
+            return Err(
+                "Cannot remove project with existing worktrees. Delete worktrees first."
+                    .to_string(),
+            );
+        }
 
-    if has_active_worktrees {
-        return Err(
-            "Cannot remove project with existing worktrees. Delete worktrees first.".to_string(),
-        );
-    }
+        // Collect archived worktrees for this project to clean up
+        let archived_worktree_ids: Vec<String> = data
+            .worktrees
+            .iter()
+            .filter(|w| w.project_id == project_id && w.archived_at.is_some())
+            .map(|w| w.id.clone())
+            .collect();
+
+        // Remove archived worktrees from data
+        for worktree_id in &archived_worktree_ids {
+            data.remove_worktree(worktree_id);
+            log::trace!("Removed archived worktree: {worktree_id}");
+        }
 
-    // Collect archived worktrees for this project to clean up
-    let archived_worktree_ids: Vec<String> = data
-        .worktrees
-        .iter()
-        .filter(|w| w.project_id == project_id && w.archived_at.is_some())
-        .map(|w| w.id.clone())
-        .collect();
+        // Remove project
+        let project = data
+            .remove_project(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
+        Ok((project.path, archived_worktree_ids))
+    })?;
 
-    // Remove archived worktrees from data
+    // Drop the archived worktrees' snapshots from the project's repo
     for worktree_id in &archived_worktree_ids {
-        data.remove_worktree(worktree_id);
-        log::trace!("Removed archived worktree: {worktree_id}");
+        super::snapshots::delete_all(&project_path, worktree_id);
     }
 
-    // Remove project
-    data.remove_project(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?;
-
-    save_projects_data(&app, &data)?;
-
     // Clean up sessions files for archived worktrees (in background, non-blocking)
     for worktree_id in archived_worktree_ids {
-        if let Ok(sessions_file) = crate::chat::storage::get_sessions_path(&app, &worktree_id) {
-            if sessions_file.exists() {
-                if let Err(e) = std::fs::remove_file(&sessions_file) {
-                    log::warn!("Failed to delete sessions file for {worktree_id}: {e}");
-                } else {
-                    log::trace!("Deleted sessions file for archived worktree: {worktree_id}");
-                }
-            }
+        if let Err(e) = crate::chat::storage::delete_index(&app, &worktree_id) {
+            log::warn!("Failed to delete sessions for {worktree_id}: {e}");
         }
+        crate::chat::change_audit::delete(&app, &worktree_id);
     }
 
-    // Also clean up preserved base sessions file for this project
-    if let Ok(base_sessions_file) =
-        crate::chat::storage::get_closed_base_sessions_path(&app, &project_id)
-    {
-        if base_sessions_file.exists() {
-            if let Err(e) = std::fs::remove_file(&base_sessions_file) {
-                log::warn!("Failed to delete base sessions file for project {project_id}: {e}");
-            } else {
-                log::trace!("Deleted base sessions file for project: {project_id}");
-            }
-        }
+    // Also clean up preserved base sessions for this project
+    if let Err(e) = crate::chat::storage::delete_base_index(&app, &project_id) {
+        log::warn!("Failed to delete base sessions for project {project_id}: {e}");
     }
 
     log::trace!("Successfully removed project: {project_id}");
@@ -398,6 +572,19 @@ pub async fn get_worktree(app: AppHandle, worktree_id: String) -> Result<Worktre
         .ok_or_else(|| format!("Worktree not found: {worktree_id}"))
 }
 
+/// Scripts to run in a new worktree: the template's setup script (or
+/// jean.json's when it has none), then the template's extra scripts
+fn setup_scripts(config_setup: Option<String>, template: Option<&WorktreeTemplate>) -> Vec<String> {
+    let setup = match template.and_then(|t| t.setup_script.clone()) {
+        Some(script) => Some(script),
+        None => config_setup,
+    };
+    setup
+        .into_iter()
+        .chain(template.iter().flat_map(|t| t.scripts.clone()))
+        .collect()
+}
+
 /// Create a new worktree for a project (runs in background)
 ///
 /// This command returns immediately with a "pending" worktree.
@@ -406,7 +593,17 @@ pub async fn get_worktree(app: AppHandle, worktree_id: String) -> Result<Worktre
 /// - `worktree:creating` - Emitted immediately when creation starts
 /// - `worktree:created` - Emitted when creation completes successfully
 /// - `worktree:error` - Emitted if creation fails
+///
+/// `template_id` selects one of the project's worktree templates, which
+/// provides the base branch (unless one is given), a branch name prefix,
+/// labels for the issue, a setup script replacing jean.json's, extra setup
+/// scripts and an initial prompt.
+///
+/// `parent_worktree_id` stacks the new worktree on another worktree of the
+/// project: it branches off the parent's branch and is restacked when the
+/// parent merges (see [`stacks`]).
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_worktree(
     app: AppHandle,
     project_id: String,
@@ -414,6 +611,8 @@ pub async fn create_worktree(
     issue_context: Option<IssueContext>,
     pr_context: Option<PullRequestContext>,
     custom_name: Option<String>,
+    template_id: Option<String>,
+    parent_worktree_id: Option<String>,
 ) -> Result<Worktree, String> {
     log::trace!("Creating worktree for project: {project_id}");
 
@@ -424,10 +623,47 @@ pub async fn create_worktree(
         .ok_or_else(|| format!("Project not found: {project_id}"))?
         .clone();
 
-    // Use provided base branch or project's default branch, with validation
-    let preferred_base = base_branch.unwrap_or_else(|| project.default_branch.clone());
+    let template = match &template_id {
+        Some(id) => Some(
+            project
+                .find_template(id)
+                .ok_or_else(|| format!("Worktree template not found: {id}"))?
+                .clone(),
+        ),
+        None => None,
+    };
+
+    // Stacked worktrees branch off their parent's branch
+    let stack_parent = match &parent_worktree_id {
+        Some(id) => Some(stacks::new_parent(&data, &project, id)?),
+        None => None,
+    };
+
+    // Use the parent's branch, or the provided base branch, the template's or
+    // project's default branch, with validation
+    let preferred_base = match &stack_parent {
+        Some(parent) => parent.branch.clone(),
+        None => base_branch
+            .or_else(|| template.as_ref().and_then(|t| t.base_branch.clone()))
+            .unwrap_or_else(|| project.default_branch.clone()),
+    };
     let base = git::get_valid_base_branch(&project.path, &preferred_base)?;
 
+    // Generated names follow the project's naming scheme and get the template's
+    // prefix (custom and PR names are used as-is)
+    let scheme = BranchNameScheme::for_project(&project, template.as_ref().map(|t| t.name.clone()));
+    let prefix = template
+        .as_ref()
+        .and_then(|t| t.branch_prefix.clone())
+        .unwrap_or_default();
+    let name_exists = |n: &str| data.worktree_name_exists(&project_id, &format!("{prefix}{n}"));
+    let apply_scheme = |n: &str| {
+        scheme
+            .as_ref()
+            .and_then(|s| s.apply(n, None))
+            .unwrap_or_else(|| n.to_string())
+    };
+
     // Generate workspace name - use custom name, PR-based name, issue-based name, or random name
     let name = if let Some(custom) = custom_name {
         // Use the provided custom name directly (already validated as unique by caller)
@@ -448,22 +684,28 @@ pub async fn create_worktree(
             pr_branch
         }
     } else if let Some(ref ctx) = issue_context {
-        let issue_branch = generate_branch_name_from_issue(ctx.number, &ctx.title);
+        let mut issue_branch =
+            generate_branch_name_from_issue(ctx.number, &ctx.title, scheme.as_ref());
+        if let Some(ticket) = tickets::extract_for_project(&project, &ctx.title) {
+            issue_branch = tickets::thread_into_branch(&issue_branch, &ticket);
+        }
         // Check if this branch name already exists, if so, add a suffix
-        if data.worktree_name_exists(&project_id, &issue_branch) {
+        let issue_branch = if name_exists(&issue_branch) {
             let mut counter = 2;
             loop {
                 let candidate = format!("{issue_branch}-{counter}");
-                if !data.worktree_name_exists(&project_id, &candidate) {
+                if !name_exists(&candidate) {
                     break candidate;
                 }
                 counter += 1;
             }
         } else {
             issue_branch
-        }
+        };
+        format!("{prefix}{issue_branch}")
     } else {
-        generate_unique_workspace_name(|n| data.worktree_name_exists(&project_id, n))
+        let random = generate_unique_workspace_name(|n| name_exists(&apply_scheme(n)));
+        format!("{prefix}{}", apply_scheme(&random))
     };
 
     // Build worktree path: ~/jean/<project-name>/<workspace-name>
@@ -518,6 +760,11 @@ pub async fn create_worktree(
         cached_unpushed_count: None,
         order: 0, // Placeholder, actual order is set in background thread
         archived_at: None,
+        reviewed_commit: None,
+        checklist_override: None,
+        stack_parent: stack_parent.clone(),
+        archived_stash: None,
+        env_profile: None,
     };
 
     // Clone values for the background thread
@@ -530,9 +777,12 @@ pub async fn create_worktree(
     let base_clone = base.clone();
     let issue_context_clone = issue_context.clone();
     let pr_context_clone = pr_context.clone();
+    let template_clone = template.clone();
+    let stack_parent_clone = stack_parent.clone();
 
     // Spawn background thread for git operations
-    thread::spawn(move || {
+    let label = format!("Create worktree {name}");
+    crate::jobs::spawn(&app, JobKind::CreateWorktree, label, move |job| {
         log::trace!("Background: Creating git worktree {name_clone} at {worktree_path_clone}");
 
         // Check if path already exists
@@ -591,7 +841,7 @@ pub async fn create_worktree(
             if let Err(e) = app_clone.emit_all("worktree:error", &error_event) {
                 log::error!("Failed to emit worktree:error event: {e}");
             }
-            return;
+            return Err(error_event.error);
         }
 
         // For PR context, we use a temp branch + gh pr checkout pattern
@@ -661,11 +911,20 @@ pub async fn create_worktree(
                     if let Err(e) = app_clone.emit_all("worktree:error", &error_event) {
                         log::error!("Failed to emit worktree:error event: {e}");
                     }
-                    return;
+                    return Err(error_event.error);
                 }
                 (name_clone.clone(), None, name_clone.clone())
             };
 
+        if job.is_cancelled() {
+            return Err(cancel_worktree_creation(
+                &app_clone,
+                worktree_id_clone,
+                project_id_clone,
+            ));
+        }
+        job.progress("Creating git worktree", None);
+
         // Create the git worktree (this is the slow operation)
         if let Err(e) = git::create_worktree(
             &project_path,
@@ -682,7 +941,7 @@ pub async fn create_worktree(
             if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                 log::error!("Failed to emit worktree:error event: {emit_err}");
             }
-            return;
+            return Err(error_event.error);
         }
 
         log::trace!("Background: Git worktree created successfully");
@@ -694,11 +953,10 @@ pub async fn create_worktree(
                 ctx.number
             );
 
-            match git::gh_pr_checkout(
+            match super::repo_provider::for_repo(&app_clone, &project_path).checkout_pr(
                 &worktree_path_clone,
                 ctx.number,
                 Some(&ctx.head_ref_name),
-                &resolve_gh_binary(&app_clone),
             ) {
                 Ok(branch) => {
                     log::trace!("Background: gh pr checkout succeeded, branch: {branch}");
@@ -730,7 +988,7 @@ pub async fn create_worktree(
                     if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                         log::error!("Failed to emit worktree:error event: {emit_err}");
                     }
-                    return;
+                    return Err(error_event.error);
                 }
             }
         } else {
@@ -752,7 +1010,7 @@ pub async fn create_worktree(
                         let context_file =
                             contexts_dir.join(format!("{repo_key}-issue-{}.md", ctx.number));
                         let context_content = format_issue_context_markdown(ctx);
-                        if let Err(e) = std::fs::write(&context_file, context_content) {
+                        if let Err(e) = crate::encryption::write(&context_file, context_content) {
                             log::warn!("Background: Failed to write issue context file: {e}");
                         } else {
                             // Add reference for this worktree
@@ -774,6 +1032,21 @@ pub async fn create_worktree(
             } else {
                 log::warn!("Background: Could not get repo identifier for issue context");
             }
+
+            // Label the issue as the template asks
+            if let Some(template) = template_clone
+                .as_ref()
+                .filter(|t| !t.issue_labels.is_empty())
+            {
+                if let Err(e) = add_issue_labels(
+                    &resolve_gh_binary(&app_clone),
+                    &project_path,
+                    ctx.number,
+                    &template.issue_labels,
+                ) {
+                    log::warn!("Background: Failed to label issue #{}: {e}", ctx.number);
+                }
+            }
         }
 
         // Write PR context file if provided (to shared git-context directory)
@@ -807,7 +1080,7 @@ pub async fn create_worktree(
                         let context_file =
                             contexts_dir.join(format!("{repo_key}-pr-{}.md", ctx.number));
                         let context_content = format_pr_context_markdown(&ctx_with_diff);
-                        if let Err(e) = std::fs::write(&context_file, context_content) {
+                        if let Err(e) = crate::encryption::write(&context_file, context_content) {
                             log::warn!("Background: Failed to write PR context file: {e}");
                         } else {
                             // Add reference for this worktree
@@ -831,44 +1104,49 @@ pub async fn create_worktree(
             }
         }
 
-        // Check for jean.json and run setup script
-        let (setup_output, setup_script) = if let Some(config) =
-            git::read_jean_config(&project_path)
-        {
-            if let Some(script) = config.scripts.setup {
-                log::trace!("Background: Found jean.json with setup script, executing...");
-                match git::run_setup_script(
-                    &worktree_path_clone,
-                    &project_path,
-                    &final_branch,
-                    &script,
-                ) {
-                    Ok(output) => (Some(output), Some(script)),
-                    Err(e) => {
-                        log::error!("Background: Setup script failed: {e}");
-                        // Clean up: remove the worktree since setup failed
-                        let _ = git::remove_worktree(&project_path, &worktree_path_clone);
-                        let _ = git::delete_branch(&project_path, &final_branch);
-                        let error_event = WorktreeCreateErrorEvent {
-                            id: worktree_id_clone,
-                            project_id: project_id_clone,
-                            error: format!("Setup script failed: {e}"),
-                        };
-                        if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
-                            log::error!("Failed to emit worktree:error event: {emit_err}");
-                        }
-                        return;
+        // Run the setup script (the template's or jean.json's), then the
+        // template's scripts
+        let scripts = setup_scripts(
+            git::read_jean_config(&project_path).and_then(|config| config.scripts.setup),
+            template_clone.as_ref(),
+        );
+        let mut outputs = Vec::new();
+        for script in &scripts {
+            log::trace!("Background: Running setup script...");
+            job.progress("Running setup script", None);
+            match devcontainer::run_setup_script(
+                &app_clone,
+                &worktree_path_clone,
+                &project_path,
+                &final_branch,
+                script,
+            ) {
+                Ok(output) => outputs.push(output),
+                Err(e) => {
+                    log::error!("Background: Setup script failed: {e}");
+                    // Clean up: remove the worktree since setup failed
+                    let _ = git::remove_worktree(&project_path, &worktree_path_clone);
+                    let _ = git::delete_branch(&project_path, &final_branch);
+                    let error_event = WorktreeCreateErrorEvent {
+                        id: worktree_id_clone,
+                        project_id: project_id_clone,
+                        error: format!("Setup script failed: {e}"),
+                    };
+                    if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
+                        log::error!("Failed to emit worktree:error event: {emit_err}");
                     }
+                    return Err(error_event.error);
                 }
-            } else {
-                (None, None)
             }
-        } else {
+        }
+        let (setup_output, setup_script) = if scripts.is_empty() {
             (None, None)
+        } else {
+            (Some(outputs.join("\n")), Some(scripts.join("\n")))
         };
 
         // Save to storage
-        if let Ok(mut data) = load_projects_data(&app_clone) {
+        let saved = with_projects_data_mut(&app_clone, |data| {
             // Get max order for worktrees in this project
             let max_order = data
                 .worktrees
@@ -906,10 +1184,19 @@ pub async fn create_worktree(
                 cached_unpushed_count: None,
                 order: max_order + 1,
                 archived_at: None,
+                reviewed_commit: None,
+                checklist_override: None,
+                stack_parent: stack_parent_clone,
+                archived_stash: None,
+                env_profile: None,
             };
 
             data.add_worktree(worktree.clone());
-            if let Err(e) = save_projects_data(&app_clone, &data) {
+            Ok(worktree)
+        });
+        let worktree = match saved {
+            Ok(worktree) => worktree,
+            Err(e) => {
                 log::error!("Background: Failed to save worktree data: {e}");
                 let error_event = WorktreeCreateErrorEvent {
                     id: worktree_id_clone,
@@ -919,35 +1206,43 @@ pub async fn create_worktree(
                 if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                     log::error!("Failed to emit worktree:error event: {emit_err}");
                 }
-                return;
+                return Err(error_event.error);
             }
+        };
 
-            // Emit success event
-            log::trace!(
-                "Background: Worktree created successfully: {}",
-                worktree.name
-            );
-            let created_event = WorktreeCreatedEvent { worktree };
-            if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
-                log::error!("Failed to emit worktree:created event: {e}");
-            }
-        } else {
-            log::error!("Background: Failed to load projects data for saving");
-            let error_event = WorktreeCreateErrorEvent {
-                id: worktree_id_clone,
-                project_id: project_id_clone,
-                error: "Failed to load projects data".to_string(),
-            };
-            if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
-                log::error!("Failed to emit worktree:error event: {emit_err}");
-            }
+        // Emit success event
+        log::trace!(
+            "Background: Worktree created successfully: {}",
+            worktree.name
+        );
+        let created_event = WorktreeCreatedEvent {
+            worktree,
+            initial_prompt: template_clone.and_then(|t| t.initial_prompt),
+        };
+        if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
+            log::error!("Failed to emit worktree:created event: {e}");
         }
+        Ok(())
     });
 
     log::trace!("Returning pending worktree: {}", pending_worktree.name);
     Ok(pending_worktree)
 }
 
+/// Emit `worktree:error` for a creation job cancelled before it touched the
+/// repository, returning the job's error
+fn cancel_worktree_creation(app: &AppHandle, worktree_id: String, project_id: String) -> String {
+    let error_event = WorktreeCreateErrorEvent {
+        id: worktree_id,
+        project_id,
+        error: "Worktree creation was cancelled".to_string(),
+    };
+    if let Err(e) = app.emit_all("worktree:error", &error_event) {
+        log::error!("Failed to emit worktree:error event: {e}");
+    }
+    error_event.error
+}
+
 /// Create a worktree from an existing branch (runs in background)
 ///
 /// This command is used when a branch already exists and the user wants to
@@ -1026,6 +1321,11 @@ pub async fn create_worktree_from_existing_branch(
         cached_unpushed_count: None,
         order: 0, // Placeholder, actual order is set in background thread
         archived_at: None,
+        reviewed_commit: None,
+        checklist_override: None,
+        stack_parent: None,
+        archived_stash: None,
+        env_profile: None,
     };
 
     // Clone values for the background thread
@@ -1040,7 +1340,8 @@ pub async fn create_worktree_from_existing_branch(
     let pr_context_clone = pr_context.clone();
 
     // Spawn background thread for git operations
-    thread::spawn(move || {
+    let label = format!("Create worktree {name} from {branch_name}");
+    crate::jobs::spawn(&app, JobKind::CreateWorktree, label, move |job| {
         log::trace!("Background: Creating git worktree {name_clone} at {worktree_path_clone} using existing branch {branch_name_clone}");
 
         // Check if path already exists
@@ -1055,9 +1356,18 @@ pub async fn create_worktree_from_existing_branch(
             if let Err(e) = app_clone.emit_all("worktree:error", &error_event) {
                 log::error!("Failed to emit worktree:error event: {e}");
             }
-            return;
+            return Err(error_event.error);
         }
 
+        if job.is_cancelled() {
+            return Err(cancel_worktree_creation(
+                &app_clone,
+                worktree_id_clone,
+                project_id_clone,
+            ));
+        }
+        job.progress("Creating git worktree", None);
+
         // Create the git worktree from existing branch
         if let Err(e) = git::create_worktree_from_existing_branch(
             &project_path,
@@ -1073,7 +1383,7 @@ pub async fn create_worktree_from_existing_branch(
             if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                 log::error!("Failed to emit worktree:error event: {emit_err}");
             }
-            return;
+            return Err(error_event.error);
         }
 
         log::trace!("Background: Git worktree created successfully from existing branch");
@@ -1093,7 +1403,7 @@ pub async fn create_worktree_from_existing_branch(
                         let context_file =
                             contexts_dir.join(format!("{repo_key}-issue-{}.md", ctx.number));
                         let context_content = format_issue_context_markdown(ctx);
-                        if let Err(e) = std::fs::write(&context_file, context_content) {
+                        if let Err(e) = crate::encryption::write(&context_file, context_content) {
                             log::warn!("Background: Failed to write issue context file: {e}");
                         } else {
                             if let Err(e) = add_issue_reference(
@@ -1145,7 +1455,7 @@ pub async fn create_worktree_from_existing_branch(
                         let context_file =
                             contexts_dir.join(format!("{repo_key}-pr-{}.md", ctx.number));
                         let context_content = format_pr_context_markdown(&ctx_with_diff);
-                        if let Err(e) = std::fs::write(&context_file, context_content) {
+                        if let Err(e) = crate::encryption::write(&context_file, context_content) {
                             log::warn!("Background: Failed to write PR context file: {e}");
                         } else {
                             if let Err(e) = add_pr_reference(
@@ -1172,7 +1482,9 @@ pub async fn create_worktree_from_existing_branch(
         {
             if let Some(script) = config.scripts.setup {
                 log::trace!("Background: Found jean.json with setup script, executing...");
-                match git::run_setup_script(
+                job.progress("Running setup script", None);
+                match devcontainer::run_setup_script(
+                    &app_clone,
                     &worktree_path_clone,
                     &project_path,
                     &name_clone,
@@ -1192,7 +1504,7 @@ pub async fn create_worktree_from_existing_branch(
                         if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                             log::error!("Failed to emit worktree:error event: {emit_err}");
                         }
-                        return;
+                        return Err(error_event.error);
                     }
                 }
             } else {
@@ -1203,7 +1515,7 @@ pub async fn create_worktree_from_existing_branch(
         };
 
         // Save to storage
-        if let Ok(mut data) = load_projects_data(&app_clone) {
+        let saved = with_projects_data_mut(&app_clone, |data| {
             // Get max order for worktrees in this project
             let max_order = data
                 .worktrees
@@ -1241,10 +1553,19 @@ pub async fn create_worktree_from_existing_branch(
                 cached_unpushed_count: None,
                 order: max_order + 1,
                 archived_at: None,
+                reviewed_commit: None,
+                checklist_override: None,
+                stack_parent: None,
+                archived_stash: None,
+                env_profile: None,
             };
 
             data.add_worktree(worktree.clone());
-            if let Err(e) = save_projects_data(&app_clone, &data) {
+            Ok(worktree)
+        });
+        let worktree = match saved {
+            Ok(worktree) => worktree,
+            Err(e) => {
                 log::error!("Background: Failed to save worktree data: {e}");
                 let error_event = WorktreeCreateErrorEvent {
                     id: worktree_id_clone,
@@ -1254,29 +1575,23 @@ pub async fn create_worktree_from_existing_branch(
                 if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                     log::error!("Failed to emit worktree:error event: {emit_err}");
                 }
-                return;
+                return Err(error_event.error);
             }
+        };
 
-            // Emit success event
-            log::trace!(
-                "Background: Worktree created successfully from existing branch: {}",
-                worktree.name
-            );
-            let created_event = WorktreeCreatedEvent { worktree };
-            if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
-                log::error!("Failed to emit worktree:created event: {e}");
-            }
-        } else {
-            log::error!("Background: Failed to load projects data for saving");
-            let error_event = WorktreeCreateErrorEvent {
-                id: worktree_id_clone,
-                project_id: project_id_clone,
-                error: "Failed to load projects data".to_string(),
-            };
-            if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
-                log::error!("Failed to emit worktree:error event: {emit_err}");
-            }
+        // Emit success event
+        log::trace!(
+            "Background: Worktree created successfully from existing branch: {}",
+            worktree.name
+        );
+        let created_event = WorktreeCreatedEvent {
+            worktree,
+            initial_prompt: None,
+        };
+        if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
+            log::error!("Failed to emit worktree:created event: {e}");
         }
+        Ok(())
     });
 
     log::trace!("Returning pending worktree: {}", pending_worktree.name);
@@ -1406,6 +1721,11 @@ pub async fn checkout_pr(
         cached_unpushed_count: None,
         order: 0, // Will be updated in background thread
         archived_at: None,
+        reviewed_commit: None,
+        checklist_override: None,
+        stack_parent: None,
+        archived_stash: None,
+        env_profile: None,
     };
 
     // Clone values for background thread
@@ -1425,9 +1745,19 @@ pub async fn checkout_pr(
     let pr_reviews = pr_detail.reviews.clone();
 
     // Do the heavy lifting in a background thread
-    thread::spawn(move || {
+    let label = format!("Check out PR #{pr_number}");
+    crate::jobs::spawn(&app, JobKind::CheckoutPr, label, move |job| {
         log::trace!("Background: Creating worktree for PR #{pr_number}");
 
+        if job.is_cancelled() {
+            return Err(cancel_worktree_creation(
+                &app_clone,
+                worktree_id_clone,
+                project_id_clone,
+            ));
+        }
+        job.progress("Creating git worktree", None);
+
         // Step 1: Create worktree with a temporary branch based on base branch
         // This gives us a working directory where we can run gh pr checkout
         if let Err(e) = git::create_worktree(
@@ -1445,7 +1775,7 @@ pub async fn checkout_pr(
             if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                 log::error!("Failed to emit worktree:error event: {emit_err}");
             }
-            return;
+            return Err(error_event.error);
         }
 
         log::trace!("Background: Worktree created, now running gh pr checkout {pr_number}");
@@ -1464,12 +1794,9 @@ pub async fn checkout_pr(
         // Step 2: Run gh pr checkout inside the worktree
         // This checks out the actual PR branch and sets up tracking
         // Pass the local branch name to ensure no conflicts with checked-out branches
-        let actual_branch = match git::gh_pr_checkout(
-            &worktree_path_clone,
-            pr_number,
-            Some(&local_branch_name),
-            &resolve_gh_binary(&app_clone),
-        ) {
+        let actual_branch = match super::repo_provider::for_repo(&app_clone, &project_path)
+            .checkout_pr(&worktree_path_clone, pr_number, Some(&local_branch_name))
+        {
             Ok(branch) => {
                 log::trace!("Background: gh pr checkout succeeded, branch: {branch}");
                 branch
@@ -1487,7 +1814,7 @@ pub async fn checkout_pr(
                 if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                     log::error!("Failed to emit worktree:error event: {emit_err}");
                 }
-                return;
+                return Err(error_event.error);
             }
         };
 
@@ -1508,7 +1835,9 @@ pub async fn checkout_pr(
         {
             if let Some(script) = config.scripts.setup {
                 log::trace!("Background: Found jean.json with setup script, executing...");
-                match git::run_setup_script(
+                job.progress("Running setup script", None);
+                match devcontainer::run_setup_script(
+                    &app_clone,
                     &worktree_path_clone,
                     &project_path,
                     &actual_branch,
@@ -1528,7 +1857,7 @@ pub async fn checkout_pr(
                         if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                             log::error!("Failed to emit worktree:error event: {emit_err}");
                         }
-                        return;
+                        return Err(error_event.error);
                     }
                 }
             } else {
@@ -1579,7 +1908,7 @@ pub async fn checkout_pr(
 
                     let context_file = contexts_dir.join(format!("{repo_key}-pr-{pr_number}.md"));
                     let context_content = format_pr_context_markdown(&pr_context);
-                    if let Err(e) = std::fs::write(&context_file, context_content) {
+                    if let Err(e) = crate::encryption::write(&context_file, context_content) {
                         log::warn!("Background: Failed to write PR context file: {e}");
                     } else {
                         // Add reference for this worktree
@@ -1595,7 +1924,7 @@ pub async fn checkout_pr(
         }
 
         // Save to storage
-        if let Ok(mut data) = load_projects_data(&app_clone) {
+        let saved = with_projects_data_mut(&app_clone, |data| {
             // Get max order for worktrees in this project
             let max_order = data
                 .worktrees
@@ -1633,10 +1962,19 @@ pub async fn checkout_pr(
                 cached_unpushed_count: None,
                 order: max_order + 1,
                 archived_at: None,
+                reviewed_commit: None,
+                checklist_override: None,
+                stack_parent: None,
+                archived_stash: None,
+                env_profile: None,
             };
 
             data.add_worktree(worktree.clone());
-            if let Err(e) = save_projects_data(&app_clone, &data) {
+            Ok(worktree)
+        });
+        let worktree = match saved {
+            Ok(worktree) => worktree,
+            Err(e) => {
                 log::error!("Background: Failed to save worktree data: {e}");
                 let error_event = WorktreeCreateErrorEvent {
                     id: worktree_id_clone,
@@ -1646,30 +1984,24 @@ pub async fn checkout_pr(
                 if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
                     log::error!("Failed to emit worktree:error event: {emit_err}");
                 }
-                return;
+                return Err(error_event.error);
             }
+        };
 
-            // Emit success event
-            log::trace!(
-                "Background: Worktree created successfully for PR #{}: {}",
-                pr_number,
-                worktree.name
-            );
-            let created_event = WorktreeCreatedEvent { worktree };
-            if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
-                log::error!("Failed to emit worktree:created event: {e}");
-            }
-        } else {
-            log::error!("Background: Failed to load projects data for saving");
-            let error_event = WorktreeCreateErrorEvent {
-                id: worktree_id_clone,
-                project_id: project_id_clone,
-                error: "Failed to load projects data".to_string(),
-            };
-            if let Err(emit_err) = app_clone.emit_all("worktree:error", &error_event) {
-                log::error!("Failed to emit worktree:error event: {emit_err}");
-            }
+        // Emit success event
+        log::trace!(
+            "Background: Worktree created successfully for PR #{}: {}",
+            pr_number,
+            worktree.name
+        );
+        let created_event = WorktreeCreatedEvent {
+            worktree,
+            initial_prompt: None,
+        };
+        if let Err(e) = app_clone.emit_all("worktree:created", &created_event) {
+            log::error!("Failed to emit worktree:created event: {e}");
         }
+        Ok(())
     });
 
     log::trace!(
@@ -1680,6 +2012,25 @@ pub async fn checkout_pr(
     Ok(pending_worktree)
 }
 
+/// Stop a worktree's compose stack, then remove its snapshots, git worktree
+/// and branch. Shared by `delete_worktree` and `jean-cli delete`.
+pub fn remove_worktree_checkout(
+    worktree_id: &str,
+    project_path: &str,
+    worktree_path: &str,
+    branch: &str,
+) -> Result<(), String> {
+    // Stop the worktree's compose stack before its files disappear
+    compose::teardown(worktree_id, worktree_path);
+    super::snapshots::delete_all(project_path, worktree_id);
+
+    log::trace!("Removing git worktree at {worktree_path}");
+    git::remove_worktree(project_path, worktree_path)?;
+
+    log::trace!("Git worktree removed, deleting branch {branch}");
+    git::delete_branch(project_path, branch)
+}
+
 /// Delete a worktree (runs in background)
 ///
 /// This command returns immediately after emitting a deleting event.
@@ -1708,6 +2059,7 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
     {
         log::warn!("Failed to cleanup PR contexts: {e}");
     }
+    crate::chat::change_audit::delete(&app, &worktree_id);
 
     let data = load_projects_data(&app)?;
 
@@ -1733,9 +2085,10 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
 
     // Remove from storage SYNCHRONOUSLY to avoid race conditions with other operations
     // (e.g., archive/unarchive could be overwritten if we save in background thread)
-    let mut data = load_projects_data(&app)?;
-    data.remove_worktree(&worktree_id);
-    save_projects_data(&app, &data)?;
+    with_projects_data_mut(&app, |data| {
+        data.remove_worktree(&worktree_id);
+        Ok(())
+    })?;
     log::trace!("Worktree removed from storage: {worktree_id}");
 
     // Emit deleting event immediately
@@ -1758,28 +2111,18 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
 
     // Spawn background thread for git operations only
     // Storage is already updated, so git failures won't corrupt other data
-    thread::spawn(move || {
-        log::trace!("Background: Removing git worktree at {worktree_path}");
-
-        // Remove the git worktree (this can be slow for large repos)
-        if let Err(e) = git::remove_worktree(&project_path, &worktree_path) {
-            log::error!("Background: Failed to remove worktree: {e}");
-            let error_event = WorktreeDeleteErrorEvent {
-                id: worktree_id_clone,
-                project_id: project_id_clone,
-                error: e,
-            };
-            if let Err(emit_err) = app_clone.emit_all("worktree:delete_error", &error_event) {
-                log::error!("Failed to emit worktree:delete_error event: {emit_err}");
-            }
-            return;
-        }
-
-        log::trace!("Background: Git worktree removed, deleting branch {worktree_branch}");
+    let label = format!("Delete worktree {worktree_name}");
+    crate::jobs::spawn(&app, JobKind::DeleteWorktree, label, move |job| {
+        job.progress("Removing git worktree", None);
 
-        // Delete the branch
-        if let Err(e) = git::delete_branch(&project_path, &worktree_branch) {
-            log::error!("Background: Failed to delete branch: {e}");
+        // Remove the git worktree and its branch (this can be slow for large repos)
+        if let Err(e) = remove_worktree_checkout(
+            &worktree_id_clone,
+            &project_path,
+            &worktree_path,
+            &worktree_branch,
+        ) {
+            log::error!("Background: Failed to delete worktree: {e}");
             let error_event = WorktreeDeleteErrorEvent {
                 id: worktree_id_clone,
                 project_id: project_id_clone,
@@ -1788,7 +2131,7 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
             if let Err(emit_err) = app_clone.emit_all("worktree:delete_error", &error_event) {
                 log::error!("Failed to emit worktree:delete_error event: {emit_err}");
             }
-            return;
+            return Err(error_event.error);
         }
 
         // Emit success event
@@ -1800,6 +2143,7 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
         if let Err(e) = app_clone.emit_all("worktree:deleted", &deleted_event) {
             log::error!("Failed to emit worktree:deleted event: {e}");
         }
+        Ok(())
     });
 
     log::trace!(
@@ -1816,52 +2160,61 @@ pub async fn delete_worktree(app: AppHandle, worktree_id: String) -> Result<(),
 pub async fn create_base_session(app: AppHandle, project_id: String) -> Result<Worktree, String> {
     log::trace!("Creating base session for project: {project_id}");
 
-    let mut data = load_projects_data(&app)?;
+    let created = with_projects_data_mut(&app, |data| {
+        // Check if base session already exists - return existing for reopening
+        if let Some(existing) = data.find_base_session(&project_id) {
+            log::trace!("Returning existing base session: {}", existing.name);
+            return Ok(Err(existing.clone()));
+        }
 
-    // Check if base session already exists - return existing for reopening
-    if let Some(existing) = data.find_base_session(&project_id) {
-        log::trace!("Returning existing base session: {}", existing.name);
-        return Ok(existing.clone());
-    }
+        let project = data
+            .find_project(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?
+            .clone();
+
+        // Create base session record (NO git worktree creation)
+        // Base sessions always have order 0 (first in list)
+        let session = Worktree {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.clone(),
+            name: project.default_branch.clone(),
+            path: project.path.clone(), // Uses project's base directory directly
+            branch: project.default_branch.clone(),
+            created_at: now(),
+            setup_output: None,
+            setup_script: None,
+            session_type: SessionType::Base,
+            pr_number: None,
+            pr_url: None,
+            cached_pr_status: None,
+            cached_check_status: None,
+            cached_behind_count: None,
+            cached_ahead_count: None,
+            cached_status_at: None,
+            cached_uncommitted_added: None,
+            cached_uncommitted_removed: None,
+            cached_branch_diff_added: None,
+            cached_branch_diff_removed: None,
+            cached_base_branch_ahead_count: None,
+            cached_base_branch_behind_count: None,
+            cached_worktree_ahead_count: None,
+            cached_unpushed_count: None,
+            order: 0, // Base sessions are always first
+            archived_at: None,
+            reviewed_commit: None,
+            checklist_override: None,
+            stack_parent: None,
+            archived_stash: None,
+            env_profile: None,
+        };
 
-    let project = data
-        .find_project(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?
-        .clone();
-
-    // Create base session record (NO git worktree creation)
-    // Base sessions always have order 0 (first in list)
-    let session = Worktree {
-        id: Uuid::new_v4().to_string(),
-        project_id: project_id.clone(),
-        name: project.default_branch.clone(),
-        path: project.path.clone(), // Uses project's base directory directly
-        branch: project.default_branch.clone(),
-        created_at: now(),
-        setup_output: None,
-        setup_script: None,
-        session_type: SessionType::Base,
-        pr_number: None,
-        pr_url: None,
-        cached_pr_status: None,
-        cached_check_status: None,
-        cached_behind_count: None,
-        cached_ahead_count: None,
-        cached_status_at: None,
-        cached_uncommitted_added: None,
-        cached_uncommitted_removed: None,
-        cached_branch_diff_added: None,
-        cached_branch_diff_removed: None,
-        cached_base_branch_ahead_count: None,
-        cached_base_branch_behind_count: None,
-        cached_worktree_ahead_count: None,
-        cached_unpushed_count: None,
-        order: 0, // Base sessions are always first
-        archived_at: None,
-    };
-
-    data.add_worktree(session.clone());
-    save_projects_data(&app, &data)?;
+        data.add_worktree(session.clone());
+        Ok(Ok((session, project)))
+    })?;
+    let (session, project) = match created {
+        Ok(created) => created,
+        Err(existing) => return Ok(existing),
+    };
 
     // Try to restore preserved sessions from a previous close
     // This migrates base-{project_id}.json to {new_worktree_id}.json
@@ -1907,7 +2260,7 @@ async fn close_base_session_internal(
 ) -> Result<(), String> {
     log::trace!("Closing base session: {worktree_id} (preserve_sessions: {preserve_sessions})");
 
-    let mut data = load_projects_data(app)?;
+    let data = load_projects_data(app)?;
 
     let worktree = data
         .find_worktree(worktree_id)
@@ -1919,30 +2272,32 @@ async fn close_base_session_internal(
         return Err("Not a base session. Use delete_worktree instead.".to_string());
     }
 
-    if preserve_sessions {
-        // Preserve the sessions file before removing the worktree
-        // This renames {worktree_id}.json to base-{project_id}.json
-        crate::chat::preserve_base_sessions(app, worktree_id, &worktree.project_id)?;
+    let project_id = worktree.project_id.clone();
+
+    let sessions_step = if preserve_sessions {
+        // Preserve the sessions index as base-{project_id} before removing the worktree
+        JournalStep::PreserveBaseSessions {
+            worktree_id: worktree_id.to_string(),
+            project_id: project_id.clone(),
+        }
     } else {
-        // Delete the sessions file entirely for a clean close
-        if let Ok(sessions_file) = crate::chat::storage::get_sessions_path(app, worktree_id) {
-            if sessions_file.exists() {
-                if let Err(e) = std::fs::remove_file(&sessions_file) {
-                    log::warn!("Failed to delete sessions file for {worktree_id}: {e}");
-                } else {
-                    log::trace!(
-                        "Deleted sessions file for clean base session close: {worktree_id}"
-                    );
-                }
-            }
+        // Delete the sessions entirely for a clean close
+        JournalStep::DeleteSessionIndex {
+            worktree_id: worktree_id.to_string(),
         }
-    }
-
-    let project_id = worktree.project_id.clone();
+    };
 
     // Remove from data (NO git operations - we don't delete the project directory!)
-    data.remove_worktree(worktree_id);
-    save_projects_data(app, &data)?;
+    crate::db::journal::run(
+        app,
+        "close_base_session",
+        vec![
+            sessions_step,
+            JournalStep::RemoveWorktree {
+                worktree_id: worktree_id.to_string(),
+            },
+        ],
+    )?;
 
     // Emit deleted event so other clients clear their ChatWindow state
     let deleted_event = WorktreeDeletedEvent {
@@ -1964,7 +2319,8 @@ async fn close_base_session_internal(
 /// Archive a worktree (keeps git worktree/branch on disk, just hides from UI)
 ///
 /// Unlike delete_worktree, this does NOT remove the git worktree or branch.
-/// It only marks the worktree as archived by setting archived_at timestamp.
+/// It marks the worktree as archived by setting archived_at timestamp, and
+/// stashes uncommitted changes, which `unarchive_worktree` restores.
 ///
 /// Note: Base sessions cannot be archived - use close_base_session instead.
 #[tauri::command]
@@ -1974,12 +2330,39 @@ pub async fn archive_worktree(app: AppHandle, worktree_id: String) -> Result<(),
     // Cancel any running Claude processes for this worktree
     crate::chat::registry::cancel_processes_for_worktree(&app, &worktree_id);
 
-    let mut data = load_projects_data(&app)?;
-
+    let data = load_projects_data(&app)?;
     let worktree = data
-        .find_worktree_mut(&worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?
+        .clone();
+    check_archivable(&worktree)?;
+
+    // Stash outside the projects lock, then record it
+    let stash = stash_for_archive(&worktree);
+    if let Err(e) = update_worktree(&app, &worktree_id, |w| mark_archived(w, stash.clone())) {
+        unstash_failed_archive(&worktree, stash);
+        return Err(e);
+    }
+    let project_id = worktree.project_id;
+
+    // Emit archived event
+    let event = WorktreeArchivedEvent {
+        id: worktree_id.clone(),
+        project_id,
+    };
+    if let Err(e) = app.emit_all("worktree:archived", &event) {
+        log::error!("Failed to emit worktree:archived event: {e}");
+    }
 
+    // Compress the worktree's session transcripts in the background
+    crate::chat::run_log::spawn_compress_logs(app.clone(), None, worktree_id.clone());
+
+    log::trace!("Successfully archived worktree: {worktree_id}");
+    Ok(())
+}
+
+/// Check that a worktree can be archived
+pub fn check_archivable(worktree: &Worktree) -> Result<(), String> {
     // Base sessions cannot be archived - they should be closed instead
     if worktree.session_type == SessionType::Base {
         return Err(
@@ -1991,25 +2374,40 @@ pub async fn archive_worktree(app: AppHandle, worktree_id: String) -> Result<(),
     if worktree.archived_at.is_some() {
         return Err("Worktree is already archived".to_string());
     }
+    Ok(())
+}
 
-    let project_id = worktree.project_id.clone();
-
-    // Set archived timestamp
-    worktree.archived_at = Some(now());
-
-    // Save the updated data
-    save_projects_data(&app, &data)?;
+/// Set a worktree's uncommitted work aside so it survives until the worktree
+/// is restored. Returns the stash, or None when there was nothing to stash.
+pub fn stash_for_archive(worktree: &Worktree) -> Option<String> {
+    let label = format!("jean: archived {}", worktree.name);
+    match git::stash_worktree_changes(&worktree.path, &label) {
+        Ok(stash) => stash,
+        Err(e) => {
+            log::warn!("Failed to stash changes of worktree {}: {e}", worktree.id);
+            None
+        }
+    }
+}
 
-    // Emit archived event
-    let event = WorktreeArchivedEvent {
-        id: worktree_id.clone(),
-        project_id,
-    };
-    if let Err(e) = app.emit_all("worktree:archived", &event) {
-        log::error!("Failed to emit worktree:archived event: {e}");
+/// Put back a stash taken by `stash_for_archive` when recording the archive
+/// failed, so the changes aren't stranded in the stash list.
+pub fn unstash_failed_archive(worktree: &Worktree, stash: Option<String>) {
+    if let Some(stash) = stash {
+        if let Err(e) = git::restore_stashed_changes(&worktree.path, &stash) {
+            log::warn!("Failed to restore stash of worktree {}: {e}", worktree.id);
+        }
     }
+}
 
-    log::trace!("Successfully archived worktree: {worktree_id}");
+/// Record a worktree as archived along with the stash taken for it. Shared by
+/// `archive_worktree` and `jean-cli archive`, which stash before taking the
+/// projects lock.
+pub fn mark_archived(worktree: &mut Worktree, stash: Option<String>) -> Result<(), String> {
+    // Re-check: the worktree may have changed while it was being stashed
+    check_archivable(worktree)?;
+    worktree.archived_at = Some(now());
+    worktree.archived_stash = stash;
     Ok(())
 }
 
@@ -2020,36 +2418,37 @@ pub async fn archive_worktree(app: AppHandle, worktree_id: String) -> Result<(),
 pub async fn unarchive_worktree(app: AppHandle, worktree_id: String) -> Result<Worktree, String> {
     log::trace!("Unarchiving worktree: {worktree_id}");
 
-    let mut data = load_projects_data(&app)?;
-
-    let worktree = data
-        .find_worktree_mut(&worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let (restored_worktree, stash) = update_worktree(&app, &worktree_id, |worktree| {
+        // Verify it's archived
+        if worktree.archived_at.is_none() {
+            return Err("Worktree is not archived".to_string());
+        }
 
-    // Verify it's archived
-    if worktree.archived_at.is_none() {
-        return Err("Worktree is not archived".to_string());
-    }
+        // For non-base sessions, validate git worktree still exists
+        if worktree.session_type != SessionType::Base {
+            let path = std::path::Path::new(&worktree.path);
+            if !path.exists() {
+                return Err(format!(
+                    "Git worktree directory no longer exists: {}. The worktree may need to be permanently deleted.",
+                    worktree.path
+                ));
+            }
+        }
 
-    // For non-base sessions, validate git worktree still exists
-    if worktree.session_type != SessionType::Base {
-        let path = std::path::Path::new(&worktree.path);
-        if !path.exists() {
-            return Err(format!(
-                "Git worktree directory no longer exists: {}. The worktree may need to be permanently deleted.",
-                worktree.path
-            ));
+        // Clear archived timestamp
+        worktree.archived_at = None;
+        let stash = worktree.archived_stash.take();
+        Ok((worktree.clone(), stash))
+    })?;
+
+    // Bring back the changes stashed when archiving. On failure the stash is
+    // left in `git stash list` under its "jean: archived" label.
+    if let Some(stash) = stash {
+        if let Err(e) = git::restore_stashed_changes(&restored_worktree.path, &stash) {
+            log::warn!("Failed to restore stashed changes of worktree {worktree_id}: {e}");
         }
     }
 
-    // Clear archived timestamp
-    worktree.archived_at = None;
-
-    let restored_worktree = worktree.clone();
-
-    // Save the updated data
-    save_projects_data(&app, &data)?;
-
     // Emit unarchived event
     let event = WorktreeUnarchivedEvent {
         worktree: restored_worktree.clone(),
@@ -2118,65 +2517,71 @@ pub async fn import_worktree(
         .ok_or_else(|| format!("Invalid path: {path}"))?
         .to_string();
 
-    let mut data = load_projects_data(&app)?;
-
-    // Verify project exists
-    let _ = data
-        .find_project(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?;
-
-    // Check if a worktree with this path already exists
-    if data.worktrees.iter().any(|w| w.path == path) {
-        return Err(format!(
-            "A worktree with this path is already tracked: {path}"
-        ));
-    }
+    let worktree = with_projects_data_mut(&app, |data| {
+        // Verify project exists
+        let _ = data
+            .find_project(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
 
-    // Get max order for worktrees in this project
-    let max_order = data
-        .worktrees
-        .iter()
-        .filter(|w| w.project_id == project_id)
-        .map(|w| w.order)
-        .max()
-        .unwrap_or(0);
+        // Check if a worktree with this path already exists
+        if data.worktrees.iter().any(|w| w.path == path) {
+            return Err(format!(
+                "A worktree with this path is already tracked: {path}"
+            ));
+        }
 
-    // Create the worktree record
-    let worktree = Worktree {
-        id: Uuid::new_v4().to_string(),
-        project_id: project_id.clone(),
-        name,
-        path: path.clone(),
-        branch,
-        created_at: now(),
-        setup_output: None,
-        setup_script: None,
-        session_type: SessionType::Worktree,
-        pr_number: None,
-        pr_url: None,
-        cached_pr_status: None,
-        cached_check_status: None,
-        cached_behind_count: None,
-        cached_ahead_count: None,
-        cached_status_at: None,
-        cached_uncommitted_added: None,
-        cached_uncommitted_removed: None,
-        cached_branch_diff_added: None,
-        cached_branch_diff_removed: None,
-        cached_base_branch_ahead_count: None,
-        cached_base_branch_behind_count: None,
-        cached_worktree_ahead_count: None,
-        cached_unpushed_count: None,
-        order: max_order + 1,
-        archived_at: None,
-    };
+        // Get max order for worktrees in this project
+        let max_order = data
+            .worktrees
+            .iter()
+            .filter(|w| w.project_id == project_id)
+            .map(|w| w.order)
+            .max()
+            .unwrap_or(0);
+
+        // Create the worktree record
+        let worktree = Worktree {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.clone(),
+            name,
+            path: path.clone(),
+            branch,
+            created_at: now(),
+            setup_output: None,
+            setup_script: None,
+            session_type: SessionType::Worktree,
+            pr_number: None,
+            pr_url: None,
+            cached_pr_status: None,
+            cached_check_status: None,
+            cached_behind_count: None,
+            cached_ahead_count: None,
+            cached_status_at: None,
+            cached_uncommitted_added: None,
+            cached_uncommitted_removed: None,
+            cached_branch_diff_added: None,
+            cached_branch_diff_removed: None,
+            cached_base_branch_ahead_count: None,
+            cached_base_branch_behind_count: None,
+            cached_worktree_ahead_count: None,
+            cached_unpushed_count: None,
+            order: max_order + 1,
+            archived_at: None,
+            reviewed_commit: None,
+            checklist_override: None,
+            stack_parent: None,
+            archived_stash: None,
+            env_profile: None,
+        };
 
-    data.add_worktree(worktree.clone());
-    save_projects_data(&app, &data)?;
+        data.add_worktree(worktree.clone());
+        Ok(worktree)
+    })?;
 
     // Emit created event
     let event = WorktreeCreatedEvent {
         worktree: worktree.clone(),
+        initial_prompt: None,
     };
     if let Err(e) = app.emit_all("worktree:created", &event) {
         log::error!("Failed to emit worktree:created event: {e}");
@@ -2190,10 +2595,15 @@ pub async fn import_worktree(
 ///
 /// This is the "true delete" that removes the worktree from disk.
 /// Only works on archived worktrees to prevent accidental deletion.
+///
+/// With `use_trash` (defaults to the `delete_worktrees_to_trash` preference),
+/// the directory is moved to the OS trash instead and the branch and sessions
+/// are kept, so the worktree can be restored until the trash retention expires.
 #[tauri::command]
 pub async fn permanently_delete_worktree(
     app: AppHandle,
     worktree_id: String,
+    use_trash: Option<bool>,
 ) -> Result<(), String> {
     log::trace!("Permanently deleting archived worktree: {worktree_id}");
 
@@ -2216,11 +2626,23 @@ pub async fn permanently_delete_worktree(
         .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?
         .clone();
 
+    let use_trash = match use_trash {
+        Some(use_trash) => use_trash,
+        None => crate::load_preferences(app.clone())
+            .await
+            .map(|p| p.delete_worktrees_to_trash)
+            .unwrap_or(false),
+    };
+    if use_trash && worktree.session_type != SessionType::Base {
+        return trash_worktree(&app, worktree, project);
+    }
+
     // Remove from storage SYNCHRONOUSLY to avoid race conditions with other operations
     // (e.g., archive/unarchive could be overwritten if we save in background thread)
-    let mut data = load_projects_data(&app)?;
-    data.remove_worktree(&worktree_id);
-    save_projects_data(&app, &data)?;
+    with_projects_data_mut(&app, |data| {
+        data.remove_worktree(&worktree_id);
+        Ok(())
+    })?;
     log::trace!("Worktree removed from storage: {worktree_id}");
 
     // Clone values for background thread
@@ -2235,7 +2657,8 @@ pub async fn permanently_delete_worktree(
 
     // Spawn background thread for git operations and cleanup only
     // Storage is already updated, so git failures won't corrupt other data
-    thread::spawn(move || {
+    let label = format!("Permanently delete worktree {worktree_name}");
+    crate::jobs::spawn(&app, JobKind::DeleteWorktree, label, move |job| {
         // Clean up issue context files for this worktree
         if let Err(e) = crate::projects::github_issues::cleanup_issue_contexts_for_worktree(
             &app_clone,
@@ -2254,7 +2677,10 @@ pub async fn permanently_delete_worktree(
 
         // Only remove git worktree/branch for non-base sessions
         if !is_base_session {
+            compose::teardown(&worktree_id_clone, &worktree_path);
+
             log::trace!("Background: Removing git worktree at {worktree_path}");
+            job.progress("Removing git worktree", None);
 
             // Remove the git worktree (ignore errors if already gone)
             if let Err(e) = git::remove_worktree(&project_path, &worktree_path) {
@@ -2268,20 +2694,13 @@ pub async fn permanently_delete_worktree(
                 log::warn!("Background: Failed to delete branch (may already be deleted): {e}");
             }
         }
+        super::snapshots::delete_all(&project_path, &worktree_id_clone);
 
-        // Delete the sessions file for this worktree
-        if let Ok(app_data_dir) = app_clone.path().app_data_dir() {
-            let sessions_file = app_data_dir
-                .join("sessions")
-                .join(format!("{worktree_id_clone}.json"));
-            if sessions_file.exists() {
-                if let Err(e) = std::fs::remove_file(&sessions_file) {
-                    log::warn!("Failed to delete sessions file: {e}");
-                } else {
-                    log::trace!("Deleted sessions file for worktree: {worktree_id_clone}");
-                }
-            }
+        // Delete the sessions for this worktree
+        if let Err(e) = crate::chat::storage::delete_index(&app_clone, &worktree_id_clone) {
+            log::warn!("Failed to delete sessions: {e}");
         }
+        crate::chat::change_audit::delete(&app_clone, &worktree_id_clone);
 
         // Emit success event
         log::trace!("Background: Worktree permanently deleted: {worktree_name}");
@@ -2292,6 +2711,7 @@ pub async fn permanently_delete_worktree(
         if let Err(e) = app_clone.emit_all("worktree:permanently_deleted", &event) {
             log::error!("Failed to emit worktree:permanently_deleted event: {e}");
         }
+        Ok(())
     });
 
     log::trace!(
@@ -2301,6 +2721,91 @@ pub async fn permanently_delete_worktree(
     Ok(())
 }
 
+/// Soft delete: move the worktree directory to the OS trash and keep its branch
+/// and sessions until `trash::purge_expired` runs
+fn trash_worktree(app: &AppHandle, worktree: Worktree, project: Project) -> Result<(), String> {
+    let worktree_id = worktree.id.clone();
+    let project_id = worktree.project_id.clone();
+    let worktree_path = worktree.path.clone();
+
+    // Record it first, so a trashed directory is never left without a way back
+    super::trash::record(
+        app,
+        &super::trash::TrashedWorktree {
+            worktree,
+            project_path: project.path,
+            trashed_at: now(),
+        },
+    )?;
+    if let Err(e) = super::trash::move_to_trash(&worktree_path) {
+        if let Err(e) = super::trash::take(app, &worktree_id) {
+            log::error!("Failed to drop trash record of {worktree_id}: {e}");
+        }
+        return Err(e);
+    }
+
+    with_projects_data_mut(app, |data| {
+        data.remove_worktree(&worktree_id);
+        Ok(())
+    })?;
+    log::trace!("Worktree moved to trash: {worktree_id}");
+
+    let event = WorktreePermanentlyDeletedEvent {
+        id: worktree_id,
+        project_id,
+    };
+    if let Err(e) = app.emit_all("worktree:permanently_deleted", &event) {
+        log::error!("Failed to emit worktree:permanently_deleted event: {e}");
+    }
+    Ok(())
+}
+
+/// List worktrees that were moved to the trash and can still be restored
+#[tauri::command]
+pub async fn list_trashed_worktrees(
+    app: AppHandle,
+) -> Result<Vec<super::trash::TrashedWorktree>, String> {
+    super::trash::list(&app)
+}
+
+/// Restore a trashed worktree (as archived, like it was when deleted)
+///
+/// The directory is restored from the OS trash where supported, otherwise the
+/// worktree is recreated from its branch without uncommitted changes.
+#[tauri::command]
+pub async fn restore_trashed_worktree(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Worktree, String> {
+    log::trace!("Restoring trashed worktree: {worktree_id}");
+
+    let entry = super::trash::list(&app)?
+        .into_iter()
+        .find(|e| e.worktree.id == worktree_id)
+        .ok_or_else(|| format!("Trashed worktree not found: {worktree_id}"))?;
+
+    if load_projects_data(&app)?
+        .find_project(&entry.worktree.project_id)
+        .is_none()
+    {
+        return Err(format!("Project not found: {}", entry.worktree.project_id));
+    }
+
+    super::trash::restore_directory(&entry)?;
+
+    let worktree = entry.worktree;
+    with_projects_data_mut(&app, |data| {
+        if data.find_worktree(&worktree.id).is_none() {
+            data.add_worktree(worktree.clone());
+        }
+        Ok(())
+    })?;
+    super::trash::take(&app, &worktree_id)?;
+
+    log::trace!("Restored trashed worktree: {}", worktree.name);
+    Ok(worktree)
+}
+
 /// Open a project's worktrees folder in the system file explorer (~/jean/<project-name>)
 #[tauri::command]
 pub async fn open_project_worktrees_folder(project_name: String) -> Result<(), String> {
@@ -2362,20 +2867,63 @@ fn format_open_error(app_name: &str, error: &std::io::Error) -> String {
     }
 }
 
-/// Open a worktree path in the configured terminal app
+/// Open a worktree path in the configured terminal app. `custom` runs
+/// `custom_command` (the `custom_terminal_command` preference) with `{path}`
+/// replaced by the quoted worktree path.
 #[tauri::command]
 pub async fn open_worktree_in_terminal(
     worktree_path: String,
     terminal: Option<String>,
+    custom_command: Option<String>,
 ) -> Result<(), String> {
     let terminal_app = terminal.unwrap_or_else(|| "terminal".to_string());
     log::trace!("Opening worktree in {terminal_app}: {worktree_path}");
 
+    if terminal_app == "custom" {
+        let template = custom_command
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| "No custom terminal command configured".to_string())?;
+        crate::platform::template_command(&template, &worktree_path)
+            .current_dir(&worktree_path)
+            .spawn()
+            .map_err(|e| format_open_error(&template, &e))?;
+        log::trace!("Opened custom terminal in {worktree_path}");
+        return Ok(());
+    }
+
     #[cfg(target_os = "macos")]
     {
         let escaped_path = worktree_path.replace("'", "'\\''");
 
+        // Terminals that take the working directory as a launch argument
+        let direct_args: Option<(&str, Vec<&str>)> = match terminal_app.as_str() {
+            "alacritty" => Some(("Alacritty", vec!["--working-directory", &worktree_path])),
+            "kitty" => Some(("kitty", vec!["--directory", &worktree_path])),
+            "wezterm" => Some(("WezTerm", vec!["start", "--cwd", &worktree_path])),
+            _ => None,
+        };
+        if let Some((app_name, args)) = direct_args {
+            std::process::Command::new("open")
+                .args(["-na", app_name, "--args"])
+                .args(args)
+                .spawn()
+                .map_err(|e| format_open_error(app_name, &e))?;
+            return Ok(());
+        }
+
         let script = match terminal_app.as_str() {
+            "iterm2" => {
+                format!(
+                    r#"tell application "iTerm"
+                        activate
+                        set newWindow to (create window with default profile)
+                        tell current session of newWindow
+                            write text "cd '{}' && clear"
+                        end tell
+                    end tell"#,
+                    escaped_path
+                )
+            }
             "warp" => {
                 // Warp uses a different AppleScript approach
                 format!(
@@ -2449,8 +2997,8 @@ pub async fn open_worktree_in_terminal(
 
         let mut opened = false;
         for (term, args) in terminals {
-            if crate::platform::executable_exists(term) {
-                match std::process::Command::new(term).args(args).spawn() {
+            if crate::platform::host_executable_exists(term) {
+                match crate::platform::host_command(term).args(args).spawn() {
                     Ok(_) => {
                         log::trace!("Opened terminal with {term}");
                         opened = true;
@@ -2470,20 +3018,27 @@ pub async fn open_worktree_in_terminal(
 
     #[cfg(target_os = "windows")]
     {
+        let set_location = crate::platform::powershell_quote(&worktree_path);
         match terminal_app.as_str() {
             "powershell" => {
                 std::process::Command::new("powershell")
                     .args([
                         "-NoExit",
                         "-Command",
-                        &format!("Set-Location '{worktree_path}'"),
+                        &format!("Set-Location -LiteralPath {set_location}"),
                     ])
                     .spawn()
                     .map_err(|e| format_open_error("PowerShell", &e))?;
             }
             "cmd" => {
+                use std::os::windows::process::CommandExt;
+
                 std::process::Command::new("cmd")
-                    .args(["/k", &format!("cd /d \"{worktree_path}\"")])
+                    .arg("/k")
+                    .raw_arg(format!(
+                        "cd /d {}",
+                        crate::platform::cmd_quote(&worktree_path)
+                    ))
                     .spawn()
                     .map_err(|e| format_open_error("CMD", &e))?;
             }
@@ -2501,7 +3056,7 @@ pub async fn open_worktree_in_terminal(
                             .args([
                                 "-NoExit",
                                 "-Command",
-                                &format!("Set-Location '{worktree_path}'"),
+                                &format!("Set-Location -LiteralPath {set_location}"),
                             ])
                             .spawn()
                             .map_err(|e| format_open_error("PowerShell", &e))?;
@@ -2515,14 +3070,47 @@ pub async fn open_worktree_in_terminal(
     Ok(())
 }
 
-/// Open a worktree path in the configured editor app (macOS)
+/// Editor CLI arguments opening `worktree_path`, optionally at `file` (relative
+/// to the worktree or absolute) and `line`. VS Code and Cursor open the file in
+/// the worktree's window (`-g file:line`); Xcode opens the file (`xed -l line`).
+fn editor_args(
+    editor_app: &str,
+    worktree_path: &str,
+    file: Option<&str>,
+    line: Option<u32>,
+) -> Vec<String> {
+    let Some(file) = file else {
+        return vec![worktree_path.to_string()];
+    };
+    let file_path = std::path::Path::new(worktree_path)
+        .join(file)
+        .to_string_lossy()
+        .to_string();
+    match (editor_app, line) {
+        ("xcode", Some(line)) => vec!["-l".to_string(), line.to_string(), file_path],
+        ("xcode", None) => vec![file_path],
+        (_, Some(line)) => vec![
+            worktree_path.to_string(),
+            "-g".to_string(),
+            format!("{file_path}:{line}"),
+        ],
+        (_, None) => vec![worktree_path.to_string(), file_path],
+    }
+}
+
+/// Open a worktree path in the configured editor app, optionally at a file
+/// and line (review findings, diff hunks)
 #[tauri::command]
 pub async fn open_worktree_in_editor(
     worktree_path: String,
     editor: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
 ) -> Result<(), String> {
     let editor_app = editor.unwrap_or_else(|| "vscode".to_string());
-    log::trace!("Opening worktree in {editor_app}: {worktree_path}");
+    log::trace!(
+        "Opening worktree in {editor_app}: {worktree_path} (file: {file:?}, line: {line:?})"
+    );
 
     // If opening jean.json and it doesn't exist, create template
     if worktree_path.ends_with("jean.json") {
@@ -2541,26 +3129,39 @@ pub async fn open_worktree_in_editor(
         }
     }
 
+    // JetBrains IDEs: same launchers on all platforms
+    if crate::platform::jetbrains::is_jetbrains(&editor_app) {
+        let target = match &file {
+            Some(file) => std::path::Path::new(&worktree_path)
+                .join(file)
+                .to_string_lossy()
+                .to_string(),
+            None => worktree_path.clone(),
+        };
+        let args = crate::platform::jetbrains::launch_args(&target, line);
+        if let Some(Err(e)) = crate::platform::jetbrains::spawn(&editor_app, &args) {
+            return Err(format_open_error(&editor_app, &e));
+        }
+        log::trace!("Successfully opened {editor_app}");
+        return Ok(());
+    }
+
+    let args = editor_args(&editor_app, &worktree_path, file.as_deref(), line);
+
     #[cfg(target_os = "macos")]
     {
         let result = match editor_app.as_str() {
             "cursor" => {
                 // Cursor uses the same CLI pattern as VS Code
-                std::process::Command::new("cursor")
-                    .arg(&worktree_path)
-                    .spawn()
+                std::process::Command::new("cursor").args(&args).spawn()
             }
             "xcode" => {
                 // Use xed (Xcode Editor) to open in Xcode
-                std::process::Command::new("xed")
-                    .arg(&worktree_path)
-                    .spawn()
+                std::process::Command::new("xed").args(&args).spawn()
             }
             _ => {
                 // Default to VS Code
-                std::process::Command::new("code")
-                    .arg(&worktree_path)
-                    .spawn()
+                std::process::Command::new("code").args(&args).spawn()
             }
         };
 
@@ -2577,18 +3178,16 @@ pub async fn open_worktree_in_editor(
     #[cfg(any(target_os = "linux", target_os = "windows"))]
     {
         // VS Code and Cursor CLI work the same on all platforms
+        // (on Windows they are .cmd shims, run through cmd.exe)
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
         let result = match editor_app.as_str() {
-            "cursor" => std::process::Command::new("cursor")
-                .arg(&worktree_path)
-                .spawn(),
+            "cursor" => crate::platform::cli_command("cursor", &args).spawn(),
             "xcode" => {
                 return Err("Xcode is only available on macOS".to_string());
             }
             _ => {
                 // Default to VS Code
-                std::process::Command::new("code")
-                    .arg(&worktree_path)
-                    .spawn()
+                crate::platform::cli_command("code", &args).spawn()
             }
         };
 
@@ -2620,7 +3219,7 @@ pub async fn get_github_repo_url(repo_path: String) -> Result<String, String> {
     git::get_github_url(&repo_path)
 }
 
-/// Open a branch on GitHub in the browser (native only)
+/// Open a branch on GitHub in the preferred browser (native only)
 #[tauri::command]
 pub async fn open_branch_on_github(repo_path: String, branch: String) -> Result<(), String> {
     log::trace!("Opening branch on GitHub: {branch} in {repo_path}");
@@ -2630,34 +3229,10 @@ pub async fn open_branch_on_github(repo_path: String, branch: String) -> Result<
 
     log::trace!("Opening GitHub branch URL: {url}");
 
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&url)
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&url)
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/c", "start", "", &url])
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    Ok(())
+    crate::platform::browser::open_url(&url)
 }
 
-/// Open the project's GitHub page in the browser
+/// Open the project's GitHub page in the preferred browser
 #[tauri::command]
 pub async fn open_project_on_github(app: AppHandle, project_id: String) -> Result<(), String> {
     log::trace!("Opening project on GitHub: {project_id}");
@@ -2673,31 +3248,7 @@ pub async fn open_project_on_github(app: AppHandle, project_id: String) -> Resul
 
     log::trace!("Opening GitHub URL: {github_url}");
 
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&github_url)
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&github_url)
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/c", "start", "", &github_url])
-            .spawn()
-            .map_err(|e| format!("Failed to open browser: {e}"))?;
-    }
-
-    Ok(())
+    crate::platform::browser::open_url(&github_url)
 }
 
 /// Rename a worktree (display name only, doesn't affect git branch)
@@ -2709,43 +3260,43 @@ pub async fn rename_worktree(
 ) -> Result<Worktree, String> {
     log::trace!("Renaming worktree: {worktree_id} to {new_name}");
 
-    let mut data = load_projects_data(&app)?;
+    let updated_worktree = with_projects_data_mut(&app, |data| {
+        // Find the worktree first to check session type
+        let worktree = data
+            .find_worktree(&worktree_id)
+            .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
 
-    // Find the worktree first to check session type
-    let worktree = data
-        .find_worktree(&worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+        let project_id = worktree.project_id.clone();
 
-    let project_id = worktree.project_id.clone();
+        // Display name only - just trim whitespace, no branch sanitization needed
+        let new_name = new_name.trim().to_string();
+        if new_name.is_empty() {
+            return Err("Name cannot be empty".to_string());
+        }
+        log::trace!("Worktree display name: {new_name}");
 
-    // Display name only - just trim whitespace, no branch sanitization needed
-    let new_name = new_name.trim().to_string();
-    if new_name.is_empty() {
-        return Err("Name cannot be empty".to_string());
-    }
-    log::trace!("Worktree display name: {new_name}");
+        // Check if name already exists for this project (excluding current worktree)
+        let name_exists = data
+            .worktrees
+            .iter()
+            .any(|w| w.project_id == project_id && w.name == new_name && w.id != worktree_id);
 
-    // Check if name already exists for this project (excluding current worktree)
-    let name_exists = data
-        .worktrees
-        .iter()
-        .any(|w| w.project_id == project_id && w.name == new_name && w.id != worktree_id);
+        if name_exists {
+            return Err(format!(
+                "A worktree named '{new_name}' already exists in this project"
+            ));
+        }
 
-    if name_exists {
-        return Err(format!(
-            "A worktree named '{new_name}' already exists in this project"
-        ));
-    }
+        // Update the worktree name
+        let worktree = data
+            .find_worktree_mut(&worktree_id)
+            .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
 
-    // Update the worktree name
-    let worktree = data
-        .find_worktree_mut(&worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
-
-    worktree.name = new_name.clone();
-    let updated_worktree = worktree.clone();
+        worktree.name = new_name.clone();
+        let updated_worktree = worktree.clone();
 
-    save_projects_data(&app, &data)?;
+        Ok(updated_worktree)
+    })?;
 
     log::trace!("Successfully renamed worktree to: {new_name}");
     Ok(updated_worktree)
@@ -2767,6 +3318,9 @@ pub async fn commit_changes(
         .find_worktree(&worktree_id)
         .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
 
+    let prefs = crate::load_preferences(app.clone()).await?;
+    conventional_commits::check_manual(&prefs.commit_rules, &message)?;
+
     let result = git::commit_changes(&worktree.path, &message, stage_all.unwrap_or(false))?;
 
     log::trace!(
@@ -2795,6 +3349,9 @@ pub async fn open_pull_request(
         .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
 
     // Use the worktree path for the PR creation
+    let remote = data
+        .find_project(&worktree.project_id)
+        .map_or(git::DEFAULT_REMOTE, |p| p.remote());
     let gh = resolve_gh_binary(&app);
     let result = git::open_pull_request(
         &worktree.path,
@@ -2802,6 +3359,7 @@ pub async fn open_pull_request(
         body.as_deref(),
         draft.unwrap_or(false),
         &gh,
+        remote,
     )?;
 
     log::trace!(
@@ -2832,14 +3390,8 @@ pub async fn list_worktree_files(
     let max = max_files.unwrap_or(5000);
     let mut files = Vec::new();
 
-    // Use ignore crate's WalkBuilder which respects .gitignore by default
-    let walker = WalkBuilder::new(&worktree_path)
-        .hidden(false) // Include hidden files (user may want .env.example etc)
-        .git_ignore(true) // Respect .gitignore
-        .git_global(true) // Respect global gitignore
-        .git_exclude(true) // Respect .git/info/exclude
-        .require_git(false) // Work even if not a git repo
-        .build();
+    // Respects .gitignore, includes hidden files (user may want .env.example etc)
+    let walker = super::file_listing::worktree_walker(Path::new(&worktree_path)).build();
 
     let worktree_path_ref = Path::new(&worktree_path);
 
@@ -2905,6 +3457,70 @@ pub async fn list_worktree_files(
     Ok(files)
 }
 
+/// List a page of a worktree's files, respecting .gitignore
+///
+/// Files are sorted by path; pass the returned `next_cursor` to continue.
+/// Changes to the active worktree's listing are emitted as
+/// `worktree-files:changed` events.
+#[tauri::command]
+pub async fn list_worktree_files_page(
+    worktree_path: String,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<super::file_listing::WorktreeFilesPage, String> {
+    log::trace!("Listing files page in worktree: {worktree_path} (cursor: {cursor:?})");
+
+    super::file_listing::list_page(
+        &worktree_path,
+        cursor.as_deref(),
+        limit.unwrap_or(super::file_listing::DEFAULT_PAGE_SIZE),
+    )
+}
+
+/// Fuzzy-search a worktree's file paths for the @-mention picker
+///
+/// Queries hit an in-memory index of the worktree (kept up to date by the
+/// worktree watcher for the active worktree) instead of walking the directory.
+#[tauri::command]
+pub async fn search_worktree_files(
+    worktree_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<super::file_listing::WorktreeFileMatch>, String> {
+    log::trace!("Searching files in worktree {worktree_path} for {query:?}");
+
+    super::file_listing::search(
+        &worktree_path,
+        &query,
+        limit.unwrap_or(super::file_listing::DEFAULT_SEARCH_LIMIT),
+    )
+}
+
+/// Search file contents in a worktree, respecting .gitignore
+///
+/// Returns matching lines with their file and line number, up to
+/// `options.max_results`.
+#[tauri::command]
+pub async fn search_in_worktree(
+    worktree_path: String,
+    query: String,
+    options: Option<super::code_search::SearchOptions>,
+) -> Result<super::code_search::SearchResults, String> {
+    log::trace!("Searching worktree {worktree_path} for {query:?}");
+
+    let root = Path::new(&worktree_path);
+    if !root.is_dir() {
+        return Err(format!("Worktree not found: {worktree_path}"));
+    }
+    let results = super::code_search::search(root, &query, &options.unwrap_or_default())?;
+    log::trace!(
+        "Found {} match(es) in {} file(s)",
+        results.matches.len(),
+        results.files_searched
+    );
+    Ok(results)
+}
+
 /// Get available branches for a project (prefers remote branches if available)
 ///
 /// This command fetches from origin first to get the latest branches,
@@ -2952,49 +3568,256 @@ pub async fn get_project_branches(
     Ok(branches)
 }
 
-/// Update project settings (currently just default_branch)
+/// List the git remotes of a project's repository
 #[tauri::command]
+pub async fn list_remotes(
+    app: AppHandle,
+    project_id: String,
+) -> Result<Vec<git::GitRemote>, String> {
+    log::trace!("Listing remotes for project: {project_id}");
+    let data = load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    git::list_remotes(&project.path)
+}
+
+/// Update project settings (default branch, devcontainer use, worktree templates,
+/// branch naming scheme, ticket pattern, Slack notifications, pre-PR checklist,
+/// license policy, preferred remote, MCP servers)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_project_settings(
     app: AppHandle,
     project_id: String,
     default_branch: Option<String>,
+    use_devcontainer: Option<bool>,
+    templates: Option<Vec<WorktreeTemplate>>,
+    branch_name_template: Option<String>,
+    ticket_pattern: Option<String>,
+    slack: Option<SlackSettings>,
+    pr_checklist: Option<PrChecklist>,
+    license_policy: Option<LicensePolicy>,
+    remote: Option<String>,
+    mcp_servers: Option<BTreeMap<String, McpServer>>,
 ) -> Result<Project, String> {
     log::trace!("Updating settings for project: {project_id}");
 
-    let mut data = load_projects_data(&app)?;
+    let updated_project = with_projects_data_mut(&app, |data| {
+        let project = data
+            .find_project_mut(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
 
-    let project = data
-        .find_project_mut(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+        if let Some(branch) = default_branch {
+            log::trace!(
+                "Updating default branch from '{}' to '{}'",
+                project.default_branch,
+                branch
+            );
+            project.default_branch = branch;
+        }
 
-    if let Some(branch) = default_branch {
-        log::trace!(
-            "Updating default branch from '{}' to '{}'",
-            project.default_branch,
-            branch
-        );
-        project.default_branch = branch;
-    }
+        if let Some(enabled) = use_devcontainer {
+            log::trace!("Setting use_devcontainer to {enabled}");
+            project.use_devcontainer = enabled;
+        }
+
+        if let Some(mut templates) = templates {
+            for template in &mut templates {
+                if template.id.is_empty() {
+                    template.id = Uuid::new_v4().to_string();
+                }
+            }
+            log::trace!("Setting {} worktree templates", templates.len());
+            project.templates = templates;
+        }
+
+        // An empty template clears the scheme
+        if let Some(template) = branch_name_template {
+            let template = template.trim().to_string();
+            if !template.is_empty() {
+                branch_naming::validate_template(&template)?;
+            }
+            log::trace!("Setting branch name template to '{template}'");
+            project.branch_name_template = Some(template).filter(|t| !t.is_empty());
+        }
+
+        // An empty pattern turns ticket threading off
+        if let Some(pattern) = ticket_pattern {
+            let pattern = pattern.trim().to_string();
+            if !pattern.is_empty() {
+                tickets::compile(&pattern)?;
+            }
+            log::trace!("Setting ticket pattern to '{pattern}'");
+            project.ticket_pattern = Some(pattern).filter(|p| !p.is_empty());
+        }
+
+        // Settings with no webhook or bot token turn notifications off
+        if let Some(settings) = slack {
+            let configured = [&settings.webhook_url, &settings.bot_token]
+                .iter()
+                .any(|v| v.as_deref().is_some_and(|v| !v.trim().is_empty()));
+            log::trace!("Setting Slack notifications (configured: {configured})");
+            project.slack = configured.then_some(settings);
+        }
+
+        // A checklist with no items turns it off
+        if let Some(mut checklist) = pr_checklist {
+            for command in [&mut checklist.test_command, &mut checklist.lint_command] {
+                *command = command
+                    .take()
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty());
+            }
+            let configured = checklist != PrChecklist::default();
+            log::trace!("Setting pre-PR checklist (configured: {configured})");
+            project.pr_checklist = configured.then_some(checklist);
+        }
+
+        // A policy that allows and denies nothing turns the check off
+        if let Some(mut policy) = license_policy {
+            for list in [&mut policy.allow, &mut policy.deny] {
+                *list = list
+                    .drain(..)
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect();
+            }
+            let configured =
+                !policy.allow.is_empty() || !policy.deny.is_empty() || policy.deny_unknown;
+            log::trace!("Setting license policy (configured: {configured})");
+            project.license_policy = configured.then_some(policy);
+        }
+
+        // An empty remote goes back to origin
+        if let Some(remote) = remote {
+            let remote = remote.trim().to_string();
+            if !remote.is_empty()
+                && !git::list_remotes(&project.path)?
+                    .iter()
+                    .any(|r| r.name == remote)
+            {
+                return Err(format!("Remote not found: {remote}"));
+            }
+            log::trace!("Setting preferred remote to '{remote}'");
+            project.remote = Some(remote).filter(|r| !r.is_empty());
+        }
+
+        // An empty map removes the project's MCP servers
+        if let Some(servers) = mcp_servers {
+            super::mcp::validate(&servers)?;
+            log::trace!("Setting {} MCP server(s)", servers.len());
+            project.mcp_servers = servers;
+        }
 
-    let updated_project = project.clone();
-    save_projects_data(&app, &data)?;
+        let updated_project = project.clone();
+        Ok(updated_project)
+    })?;
 
     log::trace!("Successfully updated project settings");
     Ok(updated_project)
 }
 
+fn find_worktree_path(app: &AppHandle, worktree_id: &str) -> Result<String, String> {
+    let data = load_projects_data(app)?;
+    data.find_worktree(worktree_id)
+        .map(|w| w.path.clone())
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))
+}
+
+/// Start the worktree's docker compose stack (jean.json `services`)
+#[tauri::command]
+pub async fn compose_up(app: AppHandle, worktree_id: String) -> Result<String, String> {
+    let worktree_path = find_worktree_path(&app, &worktree_id)?;
+    compose::up(&worktree_id, &worktree_path)
+}
+
+/// Stop and remove the worktree's docker compose stack
+#[tauri::command]
+pub async fn compose_down(
+    app: AppHandle,
+    worktree_id: String,
+    remove_volumes: Option<bool>,
+) -> Result<String, String> {
+    let worktree_path = find_worktree_path(&app, &worktree_id)?;
+    compose::down(
+        &worktree_id,
+        &worktree_path,
+        remove_volumes.unwrap_or(false),
+    )
+}
+
+/// Recent logs of the worktree's docker compose stack (or one service)
+#[tauri::command]
+pub async fn compose_logs(
+    app: AppHandle,
+    worktree_id: String,
+    service: Option<String>,
+    tail: Option<u32>,
+) -> Result<String, String> {
+    let worktree_path = find_worktree_path(&app, &worktree_id)?;
+    compose::logs(&worktree_id, &worktree_path, service.as_deref(), tail)
+}
+
+/// Detect a devcontainer config (and running container) in a checkout
+#[tauri::command]
+pub async fn detect_devcontainer(path: String) -> Result<devcontainer::DevcontainerInfo, String> {
+    Ok(devcontainer::detect(&path))
+}
+
+/// Detect runtimes pinned with mise/asdf in a checkout and which are missing
+#[tauri::command]
+pub async fn detect_toolchains(path: String) -> Result<toolchain::ToolchainInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || toolchain::detect(&path))
+        .await
+        .map_err(|e| format!("Failed to detect toolchains: {e}"))?
+}
+
+/// List the packages of a monorepo project (pnpm/npm workspaces, Cargo
+/// workspace, Nx), read from a worktree when given or the project checkout
+#[tauri::command]
+pub async fn list_packages(
+    app: AppHandle,
+    project_id: String,
+    worktree_path: Option<String>,
+) -> Result<Vec<packages::WorkspacePackage>, String> {
+    log::trace!("Listing packages for project: {project_id}");
+    let path = match worktree_path {
+        Some(path) => path,
+        None => {
+            let data = load_projects_data(&app)?;
+            data.find_project(&project_id)
+                .ok_or_else(|| format!("Project not found: {project_id}"))?
+                .path
+                .clone()
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || packages::list(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| format!("Failed to list packages: {e}"))
+}
+
+/// Install the runtimes pinned with mise/asdf in a checkout
+#[tauri::command]
+pub async fn install_toolchains(path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || toolchain::install(&path))
+        .await
+        .map_err(|e| format!("Failed to install toolchains: {e}"))?
+}
+
 /// Rebase a worktree's branch onto the base branch
 ///
 /// This command:
 /// 1. Commits any uncommitted changes (if commit_message provided)
-/// 2. Fetches from origin
-/// 3. Rebases onto origin/{base_branch}
+/// 2. Fetches from the remote (`remote`, else the project's preferred remote)
+/// 3. Rebases onto {remote}/{base_branch}
 /// 4. Force pushes with lease
 #[tauri::command]
 pub async fn rebase_worktree(
     app: AppHandle,
     worktree_id: String,
     commit_message: Option<String>,
+    remote: Option<String>,
 ) -> Result<String, String> {
     log::trace!("Rebasing worktree: {worktree_id}");
 
@@ -3008,16 +3831,60 @@ pub async fn rebase_worktree(
         .find_project(&worktree.project_id)
         .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
 
+    let remote = remote.unwrap_or_else(|| project.remote().to_string());
     let result = git::rebase_onto_base(
         &worktree.path,
         &project.default_branch,
         commit_message.as_deref(),
+        &remote,
     )?;
 
     log::trace!("Successfully rebased worktree: {}", worktree.name);
     Ok(result)
 }
 
+/// List the commits a worktree's branch has on top of its base (or the branch
+/// it is stacked on), oldest first, for an interactive rebase
+#[tauri::command]
+pub async fn plan_interactive_rebase(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Vec<git::RebaseCommit>, String> {
+    log::trace!("Planning interactive rebase of worktree: {worktree_id}");
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let base = stacks::pr_base(&data, project, worktree);
+    git::list_rebase_commits(&worktree.path, base, project.remote())
+}
+
+/// Rewrite a worktree's commits as listed in `actions` (pick, squash, fixup,
+/// drop or reword, in the new order). Nothing is pushed. Returns the new HEAD.
+#[tauri::command]
+pub async fn execute_interactive_rebase(
+    app: AppHandle,
+    worktree_id: String,
+    actions: Vec<git::RebaseAction>,
+) -> Result<String, String> {
+    log::trace!(
+        "Interactive rebase of worktree {worktree_id} ({} actions)",
+        actions.len()
+    );
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let base = stacks::pr_base(&data, project, worktree);
+    git::interactive_rebase(&worktree.path, base, project.remote(), &actions)
+}
+
 /// Check if a worktree has uncommitted changes
 #[tauri::command]
 pub async fn has_uncommitted_changes(app: AppHandle, worktree_id: String) -> Result<bool, String> {
@@ -3055,7 +3922,7 @@ pub async fn get_pr_prompt(app: AppHandle, worktree_path: String) -> Result<Stri
         .find_project(&worktree.project_id)
         .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
 
-    let target_branch = &project.default_branch;
+    let target_branch = stacks::pr_base(&data, project, worktree);
     let context = git::generate_pr_context(&worktree_path, target_branch)?;
 
     let mut prompt = format!(
@@ -3154,7 +4021,7 @@ pub async fn get_review_prompt(
     let diff_output = silent_command("git")
         .args(["diff", &format!("origin/{target_branch}...HEAD")])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git diff: {e}"))?;
 
     let full_diff = if diff_output.status.success() {
@@ -3172,7 +4039,7 @@ pub async fn get_review_prompt(
             "--pretty=format:%h %s",
         ])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git log: {e}"))?;
 
     let commit_history = if log_output.status.success() {
@@ -3186,7 +4053,7 @@ pub async fn get_review_prompt(
     let uncommitted_output = silent_command("git")
         .args(["diff", "HEAD"])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git diff HEAD: {e}"))?;
 
     let uncommitted_diff = if uncommitted_output.status.success() {
@@ -3199,7 +4066,7 @@ pub async fn get_review_prompt(
     let untracked_output = silent_command("git")
         .args(["ls-files", "--others", "--exclude-standard"])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to list untracked files: {e}"))?;
 
     let untracked_files: Vec<String> = if untracked_output.status.success() {
@@ -3347,6 +4214,7 @@ The code meets best practices and no issues were identified.
 ---
 {uncommitted_section}{commit_section}{diff_section}"#
     );
+    let prompt = crate::redaction::redact_prompt(&prompt, "review prompt");
 
     log::trace!(
         "Generated review prompt for branch {} (diff: {} bytes, uncommitted: {} bytes, untracked: {} files, commits: {} lines)",
@@ -3372,18 +4240,14 @@ pub async fn save_worktree_pr(
 ) -> Result<(), String> {
     log::trace!("Saving PR info for worktree {worktree_id}: #{pr_number}");
 
-    let mut data = load_projects_data(&app)?;
+    update_worktree(&app, &worktree_id, |worktree| {
+        worktree.pr_number = Some(pr_number);
+        worktree.pr_url = Some(pr_url);
+        Ok(())
+    })?;
 
-    let worktree = data
-        .worktrees
-        .iter_mut()
-        .find(|w| w.id == worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
-
-    worktree.pr_number = Some(pr_number);
-    worktree.pr_url = Some(pr_url);
-
-    save_projects_data(&app, &data)?;
+    linear::spawn_sync_issue_status(&app, &worktree_id, linear::PrEvent::Opened);
+    notifications::notify(&app, &worktree_id, NotificationEvent::PrOpened);
 
     log::trace!("Successfully saved PR #{pr_number} for worktree {worktree_id}");
     Ok(())
@@ -3396,18 +4260,11 @@ pub async fn save_worktree_pr(
 pub async fn clear_worktree_pr(app: AppHandle, worktree_id: String) -> Result<(), String> {
     log::trace!("Clearing PR info for worktree {worktree_id}");
 
-    let mut data = load_projects_data(&app)?;
-
-    let worktree = data
-        .worktrees
-        .iter_mut()
-        .find(|w| w.id == worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
-
-    worktree.pr_number = None;
-    worktree.pr_url = None;
-
-    save_projects_data(&app, &data)?;
+    update_worktree(&app, &worktree_id, |worktree| {
+        worktree.pr_number = None;
+        worktree.pr_url = None;
+        Ok(())
+    })?;
 
     log::trace!("Successfully cleared PR info for worktree {worktree_id}");
     Ok(())
@@ -3438,60 +4295,74 @@ pub async fn update_worktree_cached_status(
 ) -> Result<(), String> {
     log::trace!("Updating cached status for worktree {worktree_id}");
 
-    let mut data = load_projects_data(&app)?;
+    use crate::background_tasks::ci_watcher;
 
-    let worktree = data
-        .worktrees
-        .iter_mut()
-        .find(|w| w.id == worktree_id)
-        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    // Single-row update so polling never rewrites (or races with) other worktrees
+    let (checks_started_failing, pr_merged) = update_worktree(&app, &worktree_id, |worktree| {
+        let was_merged = worktree.cached_pr_status.as_deref() == Some("merged");
+        // Only update fields that are provided, preserve existing values for None
+        if pr_status.is_some() {
+            worktree.cached_pr_status = pr_status;
+        }
+        let was_failing = ci_watcher::is_failing(worktree.cached_check_status.as_deref());
+        if check_status.is_some() {
+            worktree.cached_check_status = check_status;
+        }
+        if behind_count.is_some() {
+            worktree.cached_behind_count = behind_count;
+        }
+        if ahead_count.is_some() {
+            worktree.cached_ahead_count = ahead_count;
+        }
+        if uncommitted_added.is_some() {
+            worktree.cached_uncommitted_added = uncommitted_added;
+        }
+        if uncommitted_removed.is_some() {
+            worktree.cached_uncommitted_removed = uncommitted_removed;
+        }
+        if branch_diff_added.is_some() {
+            worktree.cached_branch_diff_added = branch_diff_added;
+        }
+        if branch_diff_removed.is_some() {
+            worktree.cached_branch_diff_removed = branch_diff_removed;
+        }
+        if base_branch_ahead_count.is_some() {
+            worktree.cached_base_branch_ahead_count = base_branch_ahead_count;
+        }
+        if base_branch_behind_count.is_some() {
+            worktree.cached_base_branch_behind_count = base_branch_behind_count;
+        }
+        if worktree_ahead_count.is_some() {
+            worktree.cached_worktree_ahead_count = worktree_ahead_count;
+        }
+        if unpushed_count.is_some() {
+            worktree.cached_unpushed_count = unpushed_count;
+        }
+        worktree.cached_status_at = Some(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        );
+        Ok((
+            !was_failing && ci_watcher::is_failing(worktree.cached_check_status.as_deref()),
+            !was_merged && worktree.cached_pr_status.as_deref() == Some("merged"),
+        ))
+    })?;
 
-    // Only update fields that are provided, preserve existing values for None
-    if pr_status.is_some() {
-        worktree.cached_pr_status = pr_status;
-    }
-    if check_status.is_some() {
-        worktree.cached_check_status = check_status;
-    }
-    if behind_count.is_some() {
-        worktree.cached_behind_count = behind_count;
-    }
-    if ahead_count.is_some() {
-        worktree.cached_ahead_count = ahead_count;
-    }
-    if uncommitted_added.is_some() {
-        worktree.cached_uncommitted_added = uncommitted_added;
-    }
-    if uncommitted_removed.is_some() {
-        worktree.cached_uncommitted_removed = uncommitted_removed;
-    }
-    if branch_diff_added.is_some() {
-        worktree.cached_branch_diff_added = branch_diff_added;
-    }
-    if branch_diff_removed.is_some() {
-        worktree.cached_branch_diff_removed = branch_diff_removed;
-    }
-    if base_branch_ahead_count.is_some() {
-        worktree.cached_base_branch_ahead_count = base_branch_ahead_count;
-    }
-    if base_branch_behind_count.is_some() {
-        worktree.cached_base_branch_behind_count = base_branch_behind_count;
-    }
-    if worktree_ahead_count.is_some() {
-        worktree.cached_worktree_ahead_count = worktree_ahead_count;
-    }
-    if unpushed_count.is_some() {
-        worktree.cached_unpushed_count = unpushed_count;
+    if pr_merged {
+        linear::spawn_sync_issue_status(&app, &worktree_id, linear::PrEvent::Merged);
+        stacks::spawn_restack_children(&app, &worktree_id);
     }
-    worktree.cached_status_at = Some(
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0),
-    );
-
-    save_projects_data(&app, &data)?;
 
+    if checks_started_failing {
+        notifications::notify(&app, &worktree_id, NotificationEvent::CiFailed);
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = ci_watcher::on_checks_failed(app, worktree_id).await {
+                log::warn!("Failed to handle CI failure: {e}");
+            }
+        });
+    }
     Ok(())
 }
 
@@ -3500,6 +4371,8 @@ pub async fn update_worktree_cached_status(
 /// `diff_type` can be:
 /// - "uncommitted": Working directory changes vs HEAD
 /// - "branch": All changes in current branch vs base branch
+///
+/// Unchanged files are served from the previous diff of the worktree.
 #[tauri::command]
 pub async fn get_git_diff(
     worktree_path: String,
@@ -3508,7 +4381,61 @@ pub async fn get_git_diff(
 ) -> Result<super::git_status::GitDiff, String> {
     log::trace!("Getting {diff_type} diff for {worktree_path}");
 
-    super::git_status::get_git_diff(&worktree_path, &diff_type, base_branch.as_deref())
+    super::diff_cache::get_git_diff_cached(&worktree_path, &diff_type, base_branch.as_deref())
+}
+
+/// Stage one hunk of a file's uncommitted diff
+#[tauri::command]
+pub async fn apply_patch_hunk(
+    worktree_path: String,
+    file: String,
+    hunk: super::git_status::DiffHunk,
+) -> Result<(), String> {
+    log::trace!("Staging hunk {} of {file} in {worktree_path}", hunk.header);
+    super::hunks::stage_hunk(&worktree_path, &file, &hunk)
+}
+
+/// Revert one hunk of a file's uncommitted diff (staged or not) to HEAD
+#[tauri::command]
+pub async fn discard_hunk(
+    worktree_path: String,
+    file: String,
+    hunk: super::git_status::DiffHunk,
+) -> Result<(), String> {
+    log::trace!(
+        "Discarding hunk {} of {file} in {worktree_path}",
+        hunk.header
+    );
+    super::hunks::discard_hunk(&worktree_path, &file, &hunk)
+}
+
+/// Open a worktree's diff (`uncommitted` or `branch`, optionally one file) in
+/// the external diff tool (the `diff_tool` preference, or git's `diff.tool`)
+#[tauri::command]
+pub async fn open_diff_in_external_tool(
+    worktree_path: String,
+    diff_type: String,
+    base_branch: Option<String>,
+    file: Option<String>,
+    tool: Option<String>,
+) -> Result<(), String> {
+    super::external_diff::open_diff(
+        &worktree_path,
+        &diff_type,
+        base_branch.as_deref(),
+        file.as_deref(),
+        tool.as_deref(),
+    )
+}
+
+/// Open a conflicted file in the external merge tool (`git mergetool`)
+#[tauri::command]
+pub async fn open_conflict_in_merge_tool(
+    worktree_path: String,
+    file: String,
+    tool: Option<String>,
+) -> Result<(), String> {
+    super::external_diff::open_merge_tool(&worktree_path, &file, tool.as_deref())
 }
 
 /// Reorder projects in the sidebar
@@ -3516,19 +4443,19 @@ pub async fn get_git_diff(
 pub async fn reorder_projects(app: AppHandle, project_ids: Vec<String>) -> Result<(), String> {
     log::trace!("Reordering projects: {:?}", project_ids);
 
-    let mut data = load_projects_data(&app)?;
-
-    // Update order based on position in the provided array
-    for (index, project_id) in project_ids.iter().enumerate() {
-        if let Some(project) = data.projects.iter_mut().find(|p| p.id == *project_id) {
-            project.order = index as u32;
+    with_projects_data_mut(&app, |data| {
+        // Update order based on position in the provided array
+        for (index, project_id) in project_ids.iter().enumerate() {
+            if let Some(project) = data.projects.iter_mut().find(|p| p.id == *project_id) {
+                project.order = index as u32;
+            }
         }
-    }
 
-    // Sort projects by new order
-    data.projects.sort_by_key(|p| p.order);
+        // Sort projects by new order
+        data.projects.sort_by_key(|p| p.order);
 
-    save_projects_data(&app, &data)?;
+        Ok(())
+    })?;
     log::trace!("Projects reordered successfully");
     Ok(())
 }
@@ -3547,20 +4474,20 @@ pub async fn reorder_worktrees(
         worktree_ids
     );
 
-    let mut data = load_projects_data(&app)?;
-
-    // Update order based on position in the provided array
-    // Start from 1 since base sessions always have order 0
-    for (index, worktree_id) in worktree_ids.iter().enumerate() {
-        if let Some(worktree) = data.worktrees.iter_mut().find(|w| w.id == *worktree_id) {
-            // Skip base sessions - they always stay at order 0
-            if worktree.session_type != SessionType::Base {
-                worktree.order = (index + 1) as u32;
+    with_projects_data_mut(&app, |data| {
+        // Update order based on position in the provided array
+        // Start from 1 since base sessions always have order 0
+        for (index, worktree_id) in worktree_ids.iter().enumerate() {
+            if let Some(worktree) = data.worktrees.iter_mut().find(|w| w.id == *worktree_id) {
+                // Skip base sessions - they always stay at order 0
+                if worktree.session_type != SessionType::Base {
+                    worktree.order = (index + 1) as u32;
+                }
             }
         }
-    }
 
-    save_projects_data(&app, &data)?;
+        Ok(())
+    })?;
     log::trace!(
         "Worktrees reordered successfully for project {}",
         project_id
@@ -3606,6 +4533,9 @@ pub struct CreatePrResponse {
     pub pr_number: u32,
     pub pr_url: String,
     pub title: String,
+    /// New dependencies with disallowed licenses the PR was created with
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub license_warnings: Vec<String>,
 }
 
 /// Extract structured output from Claude CLI stream-json response
@@ -3643,12 +4573,18 @@ fn extract_structured_output(output: &str) -> Result<String, String> {
     Err("No structured output found in Claude response".to_string())
 }
 
-/// Get git diff between current branch and target branch
-fn get_branch_diff(repo_path: &str, target_branch: &str) -> Result<String, String> {
+/// Get git diff between current branch and target branch on `remote`
+fn get_branch_diff(
+    repo_path: &str,
+    remote: &str,
+    target_branch: &str,
+    pathspec: Option<&str>,
+) -> Result<String, String> {
     let output = silent_command("git")
-        .args(["diff", &format!("origin/{target_branch}...HEAD")])
+        .args(["diff", &format!("{remote}/{target_branch}...HEAD")])
+        .args(pathspec.map(|p| ["--", p]).into_iter().flatten())
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get git diff: {e}"))?;
 
     if !output.status.success() {
@@ -3670,12 +4606,22 @@ fn get_branch_diff(repo_path: &str, target_branch: &str) -> Result<String, Strin
     }
 }
 
-/// Get commit messages between current branch and target branch
-fn get_branch_commits(repo_path: &str, target_branch: &str) -> Result<String, String> {
+/// Get commit messages between current branch and target branch on `remote`
+fn get_branch_commits(
+    repo_path: &str,
+    remote: &str,
+    target_branch: &str,
+    pathspec: Option<&str>,
+) -> Result<String, String> {
     let output = silent_command("git")
-        .args(["log", "--oneline", &format!("origin/{target_branch}..HEAD")])
+        .args([
+            "log",
+            "--oneline",
+            &format!("{remote}/{target_branch}..HEAD"),
+        ])
+        .args(pathspec.map(|p| ["--", p]).into_iter().flatten())
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get git log: {e}"))?;
 
     if !output.status.success() {
@@ -3686,16 +4632,16 @@ fn get_branch_commits(repo_path: &str, target_branch: &str) -> Result<String, St
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// Count commits between current branch and target branch
-fn count_branch_commits(repo_path: &str, target_branch: &str) -> Result<u32, String> {
+/// Count commits between current branch and target branch on `remote`
+fn count_branch_commits(repo_path: &str, remote: &str, target_branch: &str) -> Result<u32, String> {
     let output = silent_command("git")
         .args([
             "rev-list",
             "--count",
-            &format!("origin/{target_branch}..HEAD"),
+            &format!("{remote}/{target_branch}..HEAD"),
         ])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to count commits: {e}"))?;
 
     if !output.status.success() {
@@ -3713,9 +4659,11 @@ fn generate_pr_content(
     app: &AppHandle,
     repo_path: &str,
     current_branch: &str,
+    remote: &str,
     target_branch: &str,
     custom_prompt: Option<&str>,
     model: Option<&str>,
+    instructions: Option<&str>,
 ) -> Result<PrContentResponse, String> {
     let cli_path = get_cli_binary_path(app)?;
 
@@ -3724,25 +4672,29 @@ fn generate_pr_content(
     }
 
     // Get diff and commits
-    let diff = get_branch_diff(repo_path, target_branch)?;
+    let diff = get_branch_diff(repo_path, remote, target_branch, None)?;
     if diff.trim().is_empty() {
         return Err("No changes to create PR for".to_string());
     }
 
-    let commits = get_branch_commits(repo_path, target_branch)?;
-    let commit_count = count_branch_commits(repo_path, target_branch)?;
+    let commits = get_branch_commits(repo_path, remote, target_branch, None)?;
+    let commit_count = count_branch_commits(repo_path, remote, target_branch)?;
 
     // Build prompt - use custom if provided and non-empty, otherwise use default
     let prompt_template = custom_prompt
         .filter(|p| !p.trim().is_empty())
         .unwrap_or(PR_CONTENT_PROMPT);
 
-    let prompt = prompt_template
+    let mut prompt = prompt_template
         .replace("{current_branch}", current_branch)
         .replace("{target_branch}", target_branch)
         .replace("{commit_count}", &commit_count.to_string())
         .replace("{commits}", &commits)
         .replace("{diff}", &diff);
+    if let Some(instructions) = instructions {
+        prompt.push_str(&format!("\n\n{instructions}"));
+    }
+    let prompt = crate::redaction::redact_prompt(&prompt, "PR content prompt");
 
     log::trace!("Generating PR content with Claude CLI (JSON schema)");
 
@@ -3830,16 +4782,27 @@ fn parse_pr_output(output: &str) -> Result<(u32, String), String> {
 /// Create a PR with AI-generated title and body
 ///
 /// This command:
-/// 1. Stages and commits any uncommitted changes (if any)
-/// 2. Pushes the branch to remote
-/// 3. Generates PR title and body using Claude CLI with JSON schema
-/// 4. Creates the PR using gh CLI
+/// 1. Evaluates the project's pre-PR checklist and license policy (if any)
+/// 2. Stages and commits any uncommitted changes (if any)
+/// 3. Pushes the branch to remote
+/// 4. Generates PR title and body using Claude CLI with JSON schema
+/// 5. Creates the PR using gh CLI
+///
+/// A failed checklist stops the PR unless `override_reason` is given, in which
+/// case the override is recorded on the worktree and noted in the PR body.
+/// New dependencies whose licenses the project's policy doesn't allow are
+/// handled the same way, or only noted when the policy just warns.
+///
+/// The branch is pushed to `remote`, else the project's preferred remote, and
+/// the checks and PR content compare against the target branch on it.
 #[tauri::command]
 pub async fn create_pr_with_ai_content(
     app: AppHandle,
     worktree_path: String,
     custom_prompt: Option<String>,
     model: Option<String>,
+    override_reason: Option<String>,
+    remote: Option<String>,
 ) -> Result<CreatePrResponse, String> {
     log::trace!("Creating PR for: {worktree_path}");
 
@@ -3854,8 +4817,10 @@ pub async fn create_pr_with_ai_content(
     let project = data
         .find_project(&worktree.project_id)
         .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let remote = remote.unwrap_or_else(|| project.remote().to_string());
 
-    let target_branch = &project.default_branch;
+    // Stacked worktrees open their PR against the parent's branch
+    let target_branch = stacks::pr_base(&data, project, worktree);
     let current_branch = git::get_current_branch(&worktree_path)?;
 
     // Check if we're on the target branch (can't create PR to same branch)
@@ -3865,6 +4830,21 @@ pub async fn create_pr_with_ai_content(
         ));
     }
 
+    let checklist_override = pr_checklist::enforce(
+        project,
+        worktree,
+        &remote,
+        target_branch,
+        override_reason.as_deref(),
+    )?;
+    let license_warnings = license_check::enforce(
+        project.license_policy.as_ref(),
+        worktree,
+        &remote,
+        target_branch,
+        override_reason.as_deref(),
+    )?;
+
     // Stage and commit uncommitted changes if any
     let uncommitted = git::get_uncommitted_count(&worktree_path)?;
     if uncommitted > 0 {
@@ -3874,7 +4854,7 @@ pub async fn create_pr_with_ai_content(
         let stage_output = silent_command("git")
             .args(["add", "-A"])
             .current_dir(&worktree_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to stage changes: {e}"))?;
 
         if !stage_output.status.success() {
@@ -3886,7 +4866,7 @@ pub async fn create_pr_with_ai_content(
         let commit_output = silent_command("git")
             .args(["commit", "-m", "chore: prepare for PR"])
             .current_dir(&worktree_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to commit: {e}"))?;
 
         if !commit_output.status.success() {
@@ -3898,12 +4878,12 @@ pub async fn create_pr_with_ai_content(
         }
     }
 
-    // Push the branch
-    log::trace!("Pushing branch to remote");
+    // Push the branch; gh opens the PR from the pushed branch's remote
+    log::trace!("Pushing branch to {remote}");
     let push_output = silent_command("git")
-        .args(["push", "-u", "origin", "HEAD"])
+        .args(["push", "-u", &remote, "HEAD"])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to push: {e}"))?;
 
     if !push_output.status.success() {
@@ -3915,15 +4895,48 @@ pub async fn create_pr_with_ai_content(
 
     // Generate PR content using Claude CLI
     log::trace!("Generating PR content with AI");
-    let pr_content = generate_pr_content(
-        &app,
-        &worktree_path,
-        &current_branch,
-        target_branch,
-        custom_prompt.as_deref(),
-        model.as_deref(),
-    )?;
-
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let language = prefs.ai_language_instruction(crate::AiArtifact::PrContent);
+    let generate = |feedback: Option<&str>| {
+        let instructions: Vec<&str> = language.as_deref().into_iter().chain(feedback).collect();
+        let instructions = instructions.join("\n\n");
+        generate_pr_content(
+            &app,
+            &worktree_path,
+            &current_branch,
+            &remote,
+            target_branch,
+            custom_prompt.as_deref(),
+            model.as_deref(),
+            (!instructions.is_empty()).then_some(instructions.as_str()),
+        )
+    };
+    let mut pr_content = generate(None)?;
+
+    // Regenerated titles come with a new body
+    let mut regenerated_body = None;
+    pr_content.title =
+        conventional_commits::enforce(&prefs.commit_rules, pr_content.title, |feedback| {
+            let content = generate(Some(feedback))?;
+            regenerated_body = Some(content.body);
+            Ok(content.title)
+        })?;
+    if let Some(body) = regenerated_body {
+        pr_content.body = body;
+    }
+    if let Some(ticket) = tickets::extract_for_project(project, &current_branch) {
+        pr_content.title = tickets::thread_into_title(&pr_content.title, &ticket);
+    }
+    if let Some(checklist_override) = &checklist_override {
+        pr_content
+            .body
+            .push_str(&pr_checklist::override_note(checklist_override));
+    }
+    if !license_warnings.is_empty() {
+        pr_content
+            .body
+            .push_str(&license_check::warning_note(&license_warnings));
+    }
     log::trace!("Generated PR title: {}", pr_content.title);
 
     // Create the PR using gh CLI
@@ -3941,7 +4954,7 @@ pub async fn create_pr_with_ai_content(
             &pr_content.body,
         ])
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr create: {e}"))?;
 
     if !output.status.success() {
@@ -3957,10 +4970,18 @@ pub async fn create_pr_with_ai_content(
 
     log::trace!("Successfully created PR #{pr_number}: {pr_url}");
 
+    if let Some(checklist_override) = checklist_override {
+        update_worktree(&app, &worktree.id, |w| {
+            w.checklist_override = Some(checklist_override);
+            Ok(())
+        })?;
+    }
+
     Ok(CreatePrResponse {
         pr_number,
         pr_url,
         title: pr_content.title,
+        license_warnings,
     })
 }
 
@@ -3997,9 +5018,14 @@ struct CommitMessageResponse {
 /// Response from creating a commit with AI-generated message
 #[derive(Debug, Clone, Serialize)]
 pub struct CreateCommitResponse {
+    /// Empty when the commit hooks rejected the commit
     pub commit_hash: String,
     pub message: String,
     pub pushed: bool,
+    /// Output of the hooks that rejected the commit; retry with `no_verify`
+    /// (passing `message` back) to skip them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_failure: Option<commit_hooks::HookFailure>,
 }
 
 /// Get git status output
@@ -4007,7 +5033,7 @@ fn get_git_status(repo_path: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["status", "--short"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get git status: {e}"))?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -4018,7 +5044,7 @@ fn get_staged_diff(repo_path: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["diff", "--cached"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get staged diff: {e}"))?;
 
     let diff = String::from_utf8_lossy(&output.stdout).to_string();
@@ -4040,7 +5066,7 @@ fn get_recent_commits(repo_path: &str, count: u32) -> Result<String, String> {
     let output = silent_command("git")
         .args(["log", "--oneline", &format!("-{count}")])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get recent commits: {e}"))?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -4051,7 +5077,7 @@ fn get_remote_info(repo_path: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["remote", "-v"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get remote info: {e}"))?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -4062,7 +5088,7 @@ fn stage_all_changes(repo_path: &str) -> Result<(), String> {
     let output = silent_command("git")
         .args(["add", "-A"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to stage changes: {e}"))?;
 
     if !output.status.success() {
@@ -4078,7 +5104,7 @@ fn create_git_commit(repo_path: &str, message: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["commit", "-m", message])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to create commit: {e}"))?;
 
     if !output.status.success() {
@@ -4090,7 +5116,7 @@ fn create_git_commit(repo_path: &str, message: &str) -> Result<String, String> {
     let hash_output = silent_command("git")
         .args(["rev-parse", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get commit hash: {e}"))?;
 
     Ok(String::from_utf8_lossy(&hash_output.stdout)
@@ -4103,7 +5129,7 @@ fn push_to_remote(repo_path: &str) -> Result<(), String> {
     let output = silent_command("git")
         .args(["push"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to push: {e}"))?;
 
     if !output.status.success() {
@@ -4114,6 +5140,21 @@ fn push_to_remote(repo_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Generate a commit message, regenerating or shortening it until it follows
+/// the commit rules
+fn generate_valid_commit_message(
+    app: &AppHandle,
+    prompt: &str,
+    model: Option<&str>,
+    rules: &crate::CommitRules,
+) -> Result<CommitMessageResponse, String> {
+    let response = generate_commit_message(app, prompt, model)?;
+    let message = conventional_commits::enforce(rules, response.message, |feedback| {
+        generate_commit_message(app, &format!("{prompt}\n\n{feedback}"), model).map(|r| r.message)
+    })?;
+    Ok(CommitMessageResponse { message })
+}
+
 /// Generate commit message using Claude CLI with JSON schema
 fn generate_commit_message(
     app: &AppHandle,
@@ -4194,13 +5235,23 @@ fn generate_commit_message(
 }
 
 /// Create a commit with AI-generated message
+///
+/// Pass `message` to commit with it instead of generating one (e.g. when
+/// retrying after the commit hooks failed). When the hooks reject the commit,
+/// their output is returned in `hook_failure`; with `fix_hook_failures`,
+/// Claude is first asked to fix the reported problems and the commit is
+/// retried once. `no_verify` skips the hooks altogether.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_commit_with_ai(
     app: AppHandle,
     worktree_path: String,
     custom_prompt: Option<String>,
     push: bool,
     model: Option<String>,
+    message: Option<String>,
+    no_verify: Option<bool>,
+    fix_hook_failures: Option<bool>,
 ) -> Result<CreateCommitResponse, String> {
     log::trace!("Creating commit for: {worktree_path}");
 
@@ -4219,37 +5270,55 @@ pub async fn create_commit_with_ai(
         return Err("No staged changes to commit".to_string());
     }
 
-    // 4. Get context for commit message generation
-    let recent_commits = get_recent_commits(&worktree_path, 10)?;
-    let remote_info = get_remote_info(&worktree_path)?;
-
-    // 5. Build prompt - use custom if provided and non-empty, otherwise use default
-    let prompt_template = custom_prompt
-        .as_ref()
-        .filter(|p| !p.trim().is_empty())
-        .map(|s| s.as_str())
-        .unwrap_or(COMMIT_MESSAGE_PROMPT);
-
-    let prompt = prompt_template
-        .replace("{status}", &status)
-        .replace("{diff}", &diff)
-        .replace("{recent_commits}", &recent_commits)
-        .replace("{remote_info}", &remote_info);
-
-    // 6. Generate commit message with Claude CLI
-    let response = generate_commit_message(&app, &prompt, model.as_deref())?;
+    // 4. Generate the commit message, unless one was given
+    let message = match message.filter(|m| !m.trim().is_empty()) {
+        Some(message) => message,
+        None => {
+            generate_ai_commit_message(
+                &app,
+                &worktree_path,
+                custom_prompt,
+                &status,
+                &diff,
+                model.as_deref(),
+            )
+            .await?
+        }
+    };
 
     log::trace!(
         "Generated commit message: {}",
-        response.message.lines().next().unwrap_or("")
+        message.lines().next().unwrap_or("")
     );
 
-    // 7. Create the commit
-    let commit_hash = create_git_commit(&worktree_path, &response.message)?;
+    // 5. Create the commit, letting Claude fix what the hooks report if asked to
+    let no_verify = no_verify.unwrap_or(false);
+    let mut outcome = commit_hooks::commit(&worktree_path, &message, no_verify)?;
+    if let CommitOutcome::HookFailed(failure) = &outcome {
+        log::info!("Commit hooks {:?} rejected the commit", failure.hooks);
+        if fix_hook_failures.unwrap_or(false) {
+            super::snapshots::auto_snapshot(&app, &worktree_path, "Before fixing commit hooks")
+                .await;
+            commit_hooks::fix_with_claude(&app, &worktree_path, failure, model.as_deref())?;
+            stage_all_changes(&worktree_path)?;
+            outcome = commit_hooks::commit(&worktree_path, &message, false)?;
+        }
+    }
+    let commit_hash = match outcome {
+        CommitOutcome::Committed(hash) => hash,
+        CommitOutcome::HookFailed(failure) => {
+            return Ok(CreateCommitResponse {
+                commit_hash: String::new(),
+                message,
+                pushed: false,
+                hook_failure: Some(failure),
+            });
+        }
+    };
 
     log::trace!("Created commit: {commit_hash}");
 
-    // 8. Push if requested
+    // 6. Push if requested
     let pushed = if push {
         push_to_remote(&worktree_path)?;
         log::trace!("Pushed to remote");
@@ -4260,11 +5329,54 @@ pub async fn create_commit_with_ai(
 
     Ok(CreateCommitResponse {
         commit_hash,
-        message: response.message,
+        message,
         pushed,
+        hook_failure: None,
     })
 }
 
+/// Generate the commit message for the staged changes
+async fn generate_ai_commit_message(
+    app: &AppHandle,
+    worktree_path: &str,
+    custom_prompt: Option<String>,
+    status: &str,
+    diff: &str,
+    model: Option<&str>,
+) -> Result<String, String> {
+    // Context for commit message generation
+    let recent_commits = get_recent_commits(worktree_path, 10)?;
+    let remote_info = get_remote_info(worktree_path)?;
+
+    // Build prompt - use custom if provided and non-empty, otherwise use default
+    let prompt_template = custom_prompt
+        .as_ref()
+        .filter(|p| !p.trim().is_empty())
+        .map(|s| s.as_str())
+        .unwrap_or(COMMIT_MESSAGE_PROMPT);
+
+    let mut prompt = prompt_template
+        .replace("{status}", status)
+        .replace("{diff}", diff)
+        .replace("{recent_commits}", &recent_commits)
+        .replace("{remote_info}", &remote_info);
+    let prefs = crate::load_preferences(app.clone()).await?;
+    if let Some(instruction) = prefs.ai_language_instruction(crate::AiArtifact::CommitMessage) {
+        prompt.push_str(&format!("\n\n{instruction}"));
+    }
+    let prompt = crate::redaction::redact_prompt(&prompt, "commit message prompt");
+
+    // Generate commit message with Claude CLI, checked against the commit rules
+    let mut response = generate_valid_commit_message(app, &prompt, model, &prefs.commit_rules)?;
+    let ticket = git::get_current_branch(worktree_path)
+        .ok()
+        .and_then(|branch| tickets::ticket_for_checkout(app, worktree_path, &branch));
+    if let Some(ticket) = ticket {
+        response.message = tickets::thread_into_commit_message(&response.message, &ticket);
+    }
+    Ok(response.message)
+}
+
 // =============================================================================
 // AI-Powered Code Review
 // =============================================================================
@@ -4307,6 +5419,9 @@ pub struct ReviewFinding {
     pub title: String,
     pub description: String,
     pub suggestion: Option<String>,
+    /// Linter the finding was imported from (None = AI review)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// Structured response from AI code review
@@ -4403,9 +5518,15 @@ pub async fn run_review_with_ai(
     worktree_path: String,
     custom_prompt: Option<String>,
     model: Option<String>,
+    package_path: Option<String>,
 ) -> Result<ReviewResponse, String> {
     log::trace!("Running AI code review for: {worktree_path}");
 
+    // Limit the review to one monorepo package
+    let pathspec = package_path
+        .map(|p| packages::validate_path(std::path::Path::new(&worktree_path), &p))
+        .transpose()?;
+
     // Load projects data to find the target branch
     let data = load_projects_data(&app)?;
 
@@ -4425,16 +5546,27 @@ pub async fn run_review_with_ai(
     let current_branch = git::get_current_branch(&worktree_path)?;
 
     // Get branch diff
-    let diff = get_branch_diff(&worktree_path, target_branch)?;
+    let diff = get_branch_diff(
+        &worktree_path,
+        project.remote(),
+        target_branch,
+        pathspec.as_deref(),
+    )?;
 
     // Get commit history
-    let commits = get_branch_commits(&worktree_path, target_branch)?;
+    let commits = get_branch_commits(
+        &worktree_path,
+        project.remote(),
+        target_branch,
+        pathspec.as_deref(),
+    )?;
 
     // Get uncommitted changes
     let uncommitted_output = silent_command("git")
         .args(["diff", "HEAD"])
+        .args(pathspec.as_deref().map(|p| ["--", p]).into_iter().flatten())
         .current_dir(&worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get uncommitted diff: {e}"))?;
 
     let uncommitted_diff = if uncommitted_output.status.success() {
@@ -4466,11 +5598,33 @@ pub async fn run_review_with_ai(
         .map(|s| s.as_str())
         .unwrap_or(REVIEW_PROMPT);
 
-    let prompt = prompt_template
+    let mut prompt = prompt_template
         .replace("{branch_info}", &branch_info)
         .replace("{commits}", &commits)
-        .replace("{diff}", &diff)
-        .replace("{uncommitted_section}", &uncommitted_section);
+        .replace(
+            "{diff}",
+            &crate::redaction::redact_prompt(&diff, "review diff"),
+        )
+        .replace(
+            "{uncommitted_section}",
+            &crate::redaction::redact_prompt(&uncommitted_section, "review diff"),
+        );
+    match coverage::for_worktree(worktree, project.remote(), target_branch) {
+        Ok(Some(diff_coverage)) => {
+            if let Some(section) = coverage::prompt_section(&diff_coverage) {
+                prompt.push_str(&format!("\n\n{section}"));
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to compute diff coverage for review: {e}"),
+    }
+    let language = crate::load_preferences(app.clone())
+        .await
+        .ok()
+        .and_then(|p| p.ai_language_instruction(crate::AiArtifact::CodeReview));
+    if let Some(instruction) = language {
+        prompt.push_str(&format!("\n\n{instruction}"));
+    }
 
     // Run review with Claude CLI
     let response = generate_review(&app, &prompt, model.as_deref())?;
@@ -4481,28 +5635,59 @@ pub async fn run_review_with_ai(
         response.approval_status
     );
 
+    pr_checklist::record_review(&app, &worktree.id, &worktree_path);
+
     Ok(response)
 }
 
-/// Pull changes from remote origin for the specified base branch
+/// Preferred remote of the project a worktree path belongs to
+fn remote_for_path(app: &AppHandle, worktree_path: &str) -> String {
+    load_projects_data(app)
+        .ok()
+        .and_then(|data| {
+            let project_id = match data.worktrees.iter().find(|w| w.path == worktree_path) {
+                Some(worktree) => worktree.project_id.clone(),
+                None => data
+                    .projects
+                    .iter()
+                    .find(|p| p.path == worktree_path)?
+                    .id
+                    .clone(),
+            };
+            Some(data.find_project(&project_id)?.remote().to_string())
+        })
+        .unwrap_or_else(|| git::DEFAULT_REMOTE.to_string())
+}
+
+/// Pull changes from a remote (`remote`, else the project's preferred remote)
+/// for the specified base branch
 #[tauri::command]
-pub async fn git_pull(worktree_path: String, base_branch: String) -> Result<String, String> {
+pub async fn git_pull(
+    app: AppHandle,
+    worktree_path: String,
+    base_branch: String,
+    remote: Option<String>,
+) -> Result<String, String> {
     log::trace!("Pulling changes for worktree: {worktree_path}, base branch: {base_branch}");
-    git::git_pull(&worktree_path, &base_branch)
+    let remote = remote.unwrap_or_else(|| remote_for_path(&app, &worktree_path));
+    git::git_pull(&worktree_path, &base_branch, &remote)
 }
 
-/// Push current branch to remote. If pr_number is provided, uses PR-aware push
-/// that handles fork remotes and uses --force-with-lease.
+/// Push current branch to remote (`remote`, else the project's preferred
+/// remote). If pr_number is provided, uses PR-aware push that handles fork
+/// remotes and uses --force-with-lease.
 #[tauri::command]
 pub async fn git_push(
     app: tauri::AppHandle,
     worktree_path: String,
     pr_number: Option<u32>,
+    remote: Option<String>,
 ) -> Result<String, String> {
     log::trace!("Pushing changes for worktree: {worktree_path}, pr_number: {pr_number:?}");
+    let remote = remote.unwrap_or_else(|| remote_for_path(&app, &worktree_path));
     match pr_number {
-        Some(pr) => git::git_push_to_pr(&worktree_path, pr, &resolve_gh_binary(&app)),
-        None => git::git_push(&worktree_path),
+        Some(pr) => git::git_push_to_pr(&worktree_path, pr, &resolve_gh_binary(&app), &remote),
+        None => git::git_push(&worktree_path, &remote),
     }
 }
 
@@ -4570,6 +5755,13 @@ pub async fn merge_worktree_to_base(
         );
     }
 
+    super::snapshots::auto_snapshot(
+        &app,
+        &worktree.path,
+        &format!("Before merging into {}", project.default_branch),
+    )
+    .await;
+
     // Auto-commit uncommitted changes in worktree using AI-generated message
     if git::has_uncommitted_changes(&worktree.path) {
         log::trace!("Auto-committing uncommitted changes before merge with AI message");
@@ -4584,13 +5776,24 @@ pub async fn merge_worktree_to_base(
         let remote_info = get_remote_info(&worktree.path).unwrap_or_default();
 
         // Build prompt and generate commit message
-        let prompt = COMMIT_MESSAGE_PROMPT
+        let mut prompt = COMMIT_MESSAGE_PROMPT
             .replace("{status}", &status)
-            .replace("{diff}", &diff)
+            .replace(
+                "{diff}",
+                &crate::redaction::redact_prompt(&diff, "merge diff"),
+            )
             .replace("{recent_commits}", &recent_commits)
             .replace("{remote_info}", &remote_info);
 
-        match generate_commit_message(&app, &prompt, None) {
+        let prefs = crate::load_preferences(app.clone()).await.ok();
+        let language = prefs
+            .as_ref()
+            .and_then(|p| p.ai_language_instruction(crate::AiArtifact::CommitMessage));
+        if let Some(instruction) = language {
+            prompt.push_str(&format!("\n\n{instruction}"));
+        }
+        let rules = prefs.map(|p| p.commit_rules).unwrap_or_default();
+        match generate_valid_commit_message(&app, &prompt, None, &rules) {
             Ok(response) => {
                 // Create the commit with AI-generated message
                 match create_git_commit(&worktree.path, &response.message) {
@@ -4617,6 +5820,29 @@ pub async fn merge_worktree_to_base(
         }
     }
 
+    // Journal the cleanup before merging so that a crash after a successful
+    // merge still removes the worktree from every store on next startup
+    let journal = crate::db::journal::begin(
+        &app,
+        "merge_worktree_to_base",
+        vec![
+            JournalStep::RemoveGitWorktree {
+                project_path: project.path.clone(),
+                worktree_path: worktree.path.clone(),
+                branch: worktree.branch.clone(),
+            },
+            JournalStep::RemoveContextReferences {
+                worktree_id: worktree_id.clone(),
+            },
+            JournalStep::RemoveWorktree {
+                worktree_id: worktree_id.clone(),
+            },
+            JournalStep::DeleteSessionIndex {
+                worktree_id: worktree_id.clone(),
+            },
+        ],
+    )?;
+
     // Perform the merge in main repo
     let merge_result = git::merge_branch_to_base(
         &project.path,
@@ -4642,22 +5868,9 @@ pub async fn merge_worktree_to_base(
                 log::error!("Failed to emit worktree:deleting event: {e}");
             }
 
-            // Remove the worktree
-            if let Err(e) = git::remove_worktree(&project.path, &worktree.path) {
-                log::error!("Failed to remove worktree after merge: {e}");
-                // Continue anyway - merge succeeded
-            }
-
-            // Delete the branch
-            if let Err(e) = git::delete_branch(&project.path, &worktree.branch) {
-                log::error!("Failed to delete branch after merge: {e}");
-                // Continue anyway - merge succeeded
-            }
-
-            // Remove from storage
-            let mut data = load_projects_data(&app)?;
-            data.remove_worktree(&worktree_id);
-            save_projects_data(&app, &data)?;
+            // Remove the git worktree and branch, context references, storage
+            // record and sessions (rolled forward on startup if interrupted)
+            crate::db::journal::commit(&app, journal)?;
 
             // Emit deleted event
             let deleted_event = WorktreeDeletedEvent {
@@ -4688,6 +5901,11 @@ pub async fn merge_worktree_to_base(
                 conflicting_files
             );
 
+            // Worktree stays intact for conflict resolution
+            if let Err(e) = crate::db::journal::abort(&app, journal) {
+                log::warn!("Failed to discard merge journal: {e}");
+            }
+
             Ok(MergeWorktreeResponse {
                 success: false,
                 commit_hash: None,
@@ -4698,6 +5916,9 @@ pub async fn merge_worktree_to_base(
         }
         git::MergeResult::Error { message } => {
             log::error!("Merge failed: {message}");
+            if let Err(e) = crate::db::journal::abort(&app, journal) {
+                log::warn!("Failed to discard merge journal: {e}");
+            }
             Err(message)
         }
     }
@@ -4734,7 +5955,7 @@ pub async fn get_merge_conflicts(
     let conflict_output = silent_command("git")
         .args(["diff", "--name-only", "--diff-filter=U"])
         .current_dir(&worktree.path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check conflicts: {e}"))?;
 
     let conflicts: Vec<String> = String::from_utf8_lossy(&conflict_output.stdout)
@@ -4755,7 +5976,7 @@ pub async fn get_merge_conflicts(
     let diff_output = silent_command("git")
         .args(["diff"])
         .current_dir(&worktree.path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get conflict diff: {e}"))?;
 
     let conflict_diff = String::from_utf8_lossy(&diff_output.stdout).to_string();
@@ -4771,11 +5992,13 @@ pub async fn get_merge_conflicts(
 ///
 /// Used when a PR has merge conflicts on GitHub. This creates the conflict
 /// state locally so the user can resolve conflicts with AI assistance.
-/// If the merge is clean (no conflicts), the merge commit is kept.
+/// If the merge is clean (no conflicts), the merge commit is kept. The base
+/// branch comes from `remote`, else the project's preferred remote.
 #[tauri::command]
 pub async fn fetch_and_merge_base(
     app: AppHandle,
     worktree_id: String,
+    remote: Option<String>,
 ) -> Result<MergeConflictsResponse, String> {
     log::trace!("Fetching base branch and merging into worktree: {worktree_id}");
 
@@ -4789,24 +6012,25 @@ pub async fn fetch_and_merge_base(
 
     let base_branch = &project.default_branch;
     let worktree_path = &worktree.path;
+    let remote = remote.unwrap_or_else(|| project.remote().to_string());
 
-    // Fetch the latest base branch from origin
+    // Fetch the latest base branch from the remote
     let fetch_output = silent_command("git")
-        .args(["fetch", "origin", base_branch])
+        .args(["fetch", &remote, base_branch])
         .current_dir(worktree_path)
-        .output()
-        .map_err(|e| format!("Failed to fetch origin: {e}"))?;
+        .output_audited()
+        .map_err(|e| format!("Failed to fetch {remote}: {e}"))?;
 
     if !fetch_output.status.success() {
         let stderr = String::from_utf8_lossy(&fetch_output.stderr);
-        return Err(format!("Failed to fetch origin/{base_branch}: {stderr}"));
+        return Err(format!("Failed to fetch {remote}/{base_branch}: {stderr}"));
     }
 
-    // Merge origin/<base_branch> into current branch
+    // Merge <remote>/<base_branch> into current branch
     let merge_output = silent_command("git")
-        .args(["merge", &format!("origin/{base_branch}")])
+        .args(["merge", &format!("{remote}/{base_branch}")])
         .current_dir(worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to merge: {e}"))?;
 
     // Check if merge succeeded cleanly
@@ -4822,7 +6046,7 @@ pub async fn fetch_and_merge_base(
     let conflict_output = silent_command("git")
         .args(["diff", "--name-only", "--diff-filter=U"])
         .current_dir(worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check conflicts: {e}"))?;
 
     let conflicts: Vec<String> = String::from_utf8_lossy(&conflict_output.stdout)
@@ -4841,7 +6065,7 @@ pub async fn fetch_and_merge_base(
     let diff_output = silent_command("git")
         .args(["diff"])
         .current_dir(worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get conflict diff: {e}"))?;
 
     let conflict_diff = String::from_utf8_lossy(&diff_output.stdout).to_string();
@@ -4914,12 +6138,14 @@ pub async fn cleanup_old_archives(
         let project = data.find_project(&worktree.project_id);
 
         // Remove from storage
-        let mut current_data = load_projects_data(&app)?;
-        current_data.remove_worktree(&worktree.id);
-        save_projects_data(&app, &current_data)?;
+        with_projects_data_mut(&app, |data| {
+            data.remove_worktree(&worktree.id);
+            Ok(())
+        })?;
 
         // Perform git cleanup if we have project info and it's not a base session
         if let Some(proj) = project {
+            super::snapshots::delete_all(&proj.path, &worktree.id);
             if worktree.session_type != SessionType::Base {
                 // Remove git worktree (ignore errors if already gone)
                 if let Err(e) = git::remove_worktree(&proj.path, &worktree.path) {
@@ -4933,17 +6159,11 @@ pub async fn cleanup_old_archives(
             }
         }
 
-        // Delete the sessions file
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
-            let sessions_file = app_data_dir
-                .join("sessions")
-                .join(format!("{}.json", worktree.id));
-            if sessions_file.exists() {
-                if let Err(e) = std::fs::remove_file(&sessions_file) {
-                    log::warn!("Failed to delete sessions file: {e}");
-                }
-            }
+        // Delete the sessions
+        if let Err(e) = crate::chat::storage::delete_index(&app, &worktree.id) {
+            log::warn!("Failed to delete sessions: {e}");
         }
+        crate::chat::change_audit::delete(&app, &worktree.id);
 
         deleted_worktrees += 1;
     }
@@ -5012,6 +6232,27 @@ pub async fn cleanup_old_archives(
     })
 }
 
+/// Archive worktrees with no commits, sessions or uncommitted changes for
+/// `days` days (defaults to the `auto_archive_stale_days` preference)
+#[tauri::command]
+pub async fn archive_stale_worktrees(
+    app: AppHandle,
+    days: Option<u32>,
+) -> Result<super::auto_archive::AutoArchiveSummary, String> {
+    let days = match days {
+        Some(days) => days,
+        None => {
+            crate::load_preferences(app.clone())
+                .await?
+                .auto_archive_stale_days
+        }
+    };
+    if days == 0 {
+        return Ok(Default::default());
+    }
+    super::auto_archive::archive_stale(&app, days).await
+}
+
 /// Delete ALL archived worktrees and sessions (manual cleanup)
 ///
 /// This permanently deletes all archived items including:
@@ -5042,12 +6283,14 @@ pub async fn delete_all_archives(app: AppHandle) -> Result<CleanupResult, String
         let project = data.find_project(&worktree.project_id);
 
         // Remove from storage
-        let mut current_data = load_projects_data(&app)?;
-        current_data.remove_worktree(&worktree.id);
-        save_projects_data(&app, &current_data)?;
+        with_projects_data_mut(&app, |data| {
+            data.remove_worktree(&worktree.id);
+            Ok(())
+        })?;
 
         // Perform git cleanup if we have project info and it's not a base session
         if let Some(proj) = project {
+            super::snapshots::delete_all(&proj.path, &worktree.id);
             if worktree.session_type != SessionType::Base {
                 // Remove git worktree (ignore errors if already gone)
                 if let Err(e) = git::remove_worktree(&proj.path, &worktree.path) {
@@ -5061,17 +6304,11 @@ pub async fn delete_all_archives(app: AppHandle) -> Result<CleanupResult, String
             }
         }
 
-        // Delete the sessions file
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
-            let sessions_file = app_data_dir
-                .join("sessions")
-                .join(format!("{}.json", worktree.id));
-            if sessions_file.exists() {
-                if let Err(e) = std::fs::remove_file(&sessions_file) {
-                    log::warn!("Failed to delete sessions file: {e}");
-                }
-            }
+        // Delete the sessions
+        if let Err(e) = crate::chat::storage::delete_index(&app, &worktree.id) {
+            log::warn!("Failed to delete sessions: {e}");
         }
+        crate::chat::change_audit::delete(&app, &worktree.id);
 
         deleted_worktrees += 1;
     }
@@ -5145,57 +6382,68 @@ pub async fn create_folder(
 ) -> Result<Project, String> {
     log::trace!("Creating folder: {name}, parent: {parent_id:?}");
 
-    let mut data = load_projects_data(&app)?;
-
-    // Validate nesting level if parent_id provided
-    if let Some(ref pid) = parent_id {
-        let parent = data
-            .find_project(pid)
-            .ok_or_else(|| format!("Parent folder not found: {pid}"))?;
+    let folder = with_projects_data_mut(&app, |data| {
+        // Validate nesting level if parent_id provided
+        if let Some(ref pid) = parent_id {
+            let parent = data
+                .find_project(pid)
+                .ok_or_else(|| format!("Parent folder not found: {pid}"))?;
 
-        if !parent.is_folder {
-            return Err("Cannot create folder inside a project".to_string());
-        }
-
-        let level = data.get_nesting_level(pid);
-        if level >= 2 {
-            return Err("Maximum folder nesting depth (3) exceeded".to_string());
-        }
-    }
+            if !parent.is_folder {
+                return Err("Cannot create folder inside a project".to_string());
+            }
 
-    // Generate unique folder name if needed
-    let unique_name = if data.folder_name_exists(&name, parent_id.as_deref(), None) {
-        // Find a unique name like "New Folder (2)", "New Folder (3)", etc.
-        let mut counter = 2;
-        loop {
-            let candidate = format!("{name} ({counter})");
-            if !data.folder_name_exists(&candidate, parent_id.as_deref(), None) {
-                break candidate;
+            let level = data.get_nesting_level(pid);
+            if level >= 2 {
+                return Err("Maximum folder nesting depth (3) exceeded".to_string());
             }
-            counter += 1;
         }
-    } else {
-        name.clone()
-    };
 
-    let order = data.get_next_order(parent_id.as_deref());
+        // Generate unique folder name if needed
+        let unique_name = if data.folder_name_exists(&name, parent_id.as_deref(), None) {
+            // Find a unique name like "New Folder (2)", "New Folder (3)", etc.
+            let mut counter = 2;
+            loop {
+                let candidate = format!("{name} ({counter})");
+                if !data.folder_name_exists(&candidate, parent_id.as_deref(), None) {
+                    break candidate;
+                }
+                counter += 1;
+            }
+        } else {
+            name.clone()
+        };
 
-    let folder = Project {
-        id: Uuid::new_v4().to_string(),
-        name: unique_name.clone(),
-        path: String::new(),
-        default_branch: String::new(),
-        added_at: now(),
-        order,
-        parent_id,
-        is_folder: true,
-        avatar_path: None,
-    };
+        let order = data.get_next_order(parent_id.as_deref());
+
+        let folder = Project {
+            id: Uuid::new_v4().to_string(),
+            name: unique_name.clone(),
+            path: String::new(),
+            default_branch: String::new(),
+            added_at: now(),
+            order,
+            parent_id,
+            is_folder: true,
+            avatar_path: None,
+            polling: None,
+            use_devcontainer: false,
+            templates: Vec::new(),
+            branch_name_template: None,
+            ticket_pattern: None,
+            slack: None,
+            pr_checklist: None,
+            license_policy: None,
+            remote: None,
+            mcp_servers: Default::default(),
+            tool_policy: None,
+        };
 
-    data.add_project(folder.clone());
-    save_projects_data(&app, &data)?;
+        data.add_project(folder.clone());
+        Ok(folder)
+    })?;
 
-    log::trace!("Successfully created folder: {unique_name}");
+    log::trace!("Successfully created folder: {}", folder.name);
     Ok(folder)
 }
 
@@ -5208,36 +6456,36 @@ pub async fn rename_folder(
 ) -> Result<Project, String> {
     log::trace!("Renaming folder {folder_id} to: {name}");
 
-    let mut data = load_projects_data(&app)?;
-
-    // Get folder info first (immutable borrow)
-    let (parent_id, is_folder) = {
-        let folder = data
-            .find_project(&folder_id)
-            .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
-        (folder.parent_id.clone(), folder.is_folder)
-    };
+    let updated = with_projects_data_mut(&app, |data| {
+        // Get folder info first (immutable borrow)
+        let (parent_id, is_folder) = {
+            let folder = data
+                .find_project(&folder_id)
+                .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
+            (folder.parent_id.clone(), folder.is_folder)
+        };
 
-    if !is_folder {
-        return Err("Cannot rename: not a folder".to_string());
-    }
+        if !is_folder {
+            return Err("Cannot rename: not a folder".to_string());
+        }
 
-    // Check for duplicate folder name at the same level (excluding self)
-    if data.folder_name_exists(&name, parent_id.as_deref(), Some(&folder_id)) {
-        return Err(format!(
-            "A folder named '{name}' already exists at this level"
-        ));
-    }
+        // Check for duplicate folder name at the same level (excluding self)
+        if data.folder_name_exists(&name, parent_id.as_deref(), Some(&folder_id)) {
+            return Err(format!(
+                "A folder named '{name}' already exists at this level"
+            ));
+        }
 
-    // Now do the mutable borrow
-    let folder = data
-        .find_project_mut(&folder_id)
-        .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
+        // Now do the mutable borrow
+        let folder = data
+            .find_project_mut(&folder_id)
+            .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
 
-    folder.name = name.clone();
-    let updated = folder.clone();
+        folder.name = name.clone();
+        let updated = folder.clone();
 
-    save_projects_data(&app, &data)?;
+        Ok(updated)
+    })?;
 
     log::trace!("Successfully renamed folder to: {name}");
     Ok(updated)
@@ -5248,26 +6496,27 @@ pub async fn rename_folder(
 pub async fn delete_folder(app: AppHandle, folder_id: String) -> Result<(), String> {
     log::trace!("Deleting folder: {folder_id}");
 
-    let mut data = load_projects_data(&app)?;
-
-    // Verify it's a folder
-    let folder = data
-        .find_project(&folder_id)
-        .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
+    with_projects_data_mut(&app, |data| {
+        // Verify it's a folder
+        let folder = data
+            .find_project(&folder_id)
+            .ok_or_else(|| format!("Folder not found: {folder_id}"))?;
 
-    if !folder.is_folder {
-        return Err("Cannot delete: not a folder".to_string());
-    }
+        if !folder.is_folder {
+            return Err("Cannot delete: not a folder".to_string());
+        }
 
-    // Verify empty
-    if !data.folder_is_empty(&folder_id) {
-        return Err(
-            "Cannot delete folder: it is not empty. Move or remove all items first.".to_string(),
-        );
-    }
+        // Verify empty
+        if !data.folder_is_empty(&folder_id) {
+            return Err(
+                "Cannot delete folder: it is not empty. Move or remove all items first."
+                    .to_string(),
+            );
+        }
 
-    data.remove_project(&folder_id);
-    save_projects_data(&app, &data)?;
+        data.remove_project(&folder_id);
+        Ok(())
+    })?;
 
     log::trace!("Successfully deleted folder: {folder_id}");
     Ok(())
@@ -5283,86 +6532,85 @@ pub async fn move_item(
 ) -> Result<Project, String> {
     log::trace!("Moving item {item_id} to parent: {new_parent_id:?}, index: {target_index:?}");
 
-    let mut data = load_projects_data(&app)?;
+    let updated = with_projects_data_mut(&app, |data| {
+        // Validate target is a folder (if provided)
+        if let Some(ref pid) = new_parent_id {
+            let parent = data
+                .find_project(pid)
+                .ok_or_else(|| format!("Parent not found: {pid}"))?;
 
-    // Validate target is a folder (if provided)
-    if let Some(ref pid) = new_parent_id {
-        let parent = data
-            .find_project(pid)
-            .ok_or_else(|| format!("Parent not found: {pid}"))?;
-
-        if !parent.is_folder {
-            return Err("Cannot move into a project, only into folders".to_string());
+            if !parent.is_folder {
+                return Err("Cannot move into a project, only into folders".to_string());
+            }
         }
-    }
-
-    // Check max depth
-    if data.would_exceed_max_depth(&item_id, new_parent_id.as_deref()) {
-        return Err("Move would exceed maximum nesting depth (3)".to_string());
-    }
 
-    // Prevent moving folder into itself or descendants
-    if let Some(ref pid) = new_parent_id {
-        if item_id == *pid {
-            return Err("Cannot move folder into itself".to_string());
-        }
-        if data.is_descendant_of(pid, &item_id) {
-            return Err("Cannot move folder into its own descendant".to_string());
+        // Check max depth
+        if data.would_exceed_max_depth(&item_id, new_parent_id.as_deref()) {
+            return Err("Move would exceed maximum nesting depth (3)".to_string());
         }
-    }
-
-    // Verify item exists
-    if data.find_project(&item_id).is_none() {
-        return Err(format!("Item not found: {item_id}"));
-    }
-
-    // Get siblings in the target parent (excluding the item being moved)
-    let mut siblings: Vec<_> = data
-        .get_children(new_parent_id.as_deref())
-        .into_iter()
-        .filter(|p| p.id != item_id)
-        .cloned()
-        .collect();
 
-    // Sort siblings: folders first, then by order
-    siblings.sort_by(|a, b| {
-        if a.is_folder && !b.is_folder {
-            std::cmp::Ordering::Less
-        } else if !a.is_folder && b.is_folder {
-            std::cmp::Ordering::Greater
-        } else {
-            a.order.cmp(&b.order)
+        // Prevent moving folder into itself or descendants
+        if let Some(ref pid) = new_parent_id {
+            if item_id == *pid {
+                return Err("Cannot move folder into itself".to_string());
+            }
+            if data.is_descendant_of(pid, &item_id) {
+                return Err("Cannot move folder into its own descendant".to_string());
+            }
         }
-    });
-
-    // Insert the item at the target index
-    let insert_idx = target_index
-        .map(|i| i as usize)
-        .unwrap_or(siblings.len())
-        .min(siblings.len());
 
-    // Update the item's parent_id first
-    let item = data
-        .find_project_mut(&item_id)
-        .ok_or_else(|| format!("Item not found: {item_id}"))?;
-    item.parent_id = new_parent_id.clone();
-    let moved_item = item.clone();
+        // Verify item exists
+        if data.find_project(&item_id).is_none() {
+            return Err(format!("Item not found: {item_id}"));
+        }
 
-    // Build the new order: insert moved item at target_index
-    let mut new_order_ids: Vec<String> = siblings.iter().map(|p| p.id.clone()).collect();
-    new_order_ids.insert(insert_idx, item_id.clone());
+        // Get siblings in the target parent (excluding the item being moved)
+        let mut siblings: Vec<_> = data
+            .get_children(new_parent_id.as_deref())
+            .into_iter()
+            .filter(|p| p.id != item_id)
+            .cloned()
+            .collect();
+
+        // Sort siblings: folders first, then by order
+        siblings.sort_by(|a, b| {
+            if a.is_folder && !b.is_folder {
+                std::cmp::Ordering::Less
+            } else if !a.is_folder && b.is_folder {
+                std::cmp::Ordering::Greater
+            } else {
+                a.order.cmp(&b.order)
+            }
+        });
 
-    // Update all orders
-    for (order, id) in new_order_ids.iter().enumerate() {
-        if let Some(p) = data.find_project_mut(id) {
-            p.order = order as u32;
+        // Insert the item at the target index
+        let insert_idx = target_index
+            .map(|i| i as usize)
+            .unwrap_or(siblings.len())
+            .min(siblings.len());
+
+        // Update the item's parent_id first
+        let item = data
+            .find_project_mut(&item_id)
+            .ok_or_else(|| format!("Item not found: {item_id}"))?;
+        item.parent_id = new_parent_id.clone();
+        let moved_item = item.clone();
+
+        // Build the new order: insert moved item at target_index
+        let mut new_order_ids: Vec<String> = siblings.iter().map(|p| p.id.clone()).collect();
+        new_order_ids.insert(insert_idx, item_id.clone());
+
+        // Update all orders
+        for (order, id) in new_order_ids.iter().enumerate() {
+            if let Some(p) = data.find_project_mut(id) {
+                p.order = order as u32;
+            }
         }
-    }
 
-    save_projects_data(&app, &data)?;
-
-    // Return the updated item
-    let updated = data.find_project(&item_id).cloned().unwrap_or(moved_item);
+        // Return the updated item
+        let updated = data.find_project(&item_id).cloned().unwrap_or(moved_item);
+        Ok(updated)
+    })?;
 
     log::trace!("Successfully moved item: {item_id}");
     Ok(updated)
@@ -5381,19 +6629,19 @@ pub async fn reorder_items(
         parent_id
     );
 
-    let mut data = load_projects_data(&app)?;
-
-    // Update order for each item
-    for (index, item_id) in item_ids.iter().enumerate() {
-        if let Some(project) = data.find_project_mut(item_id) {
-            // Only update items that belong to this parent level
-            if project.parent_id == parent_id {
-                project.order = index as u32;
+    with_projects_data_mut(&app, |data| {
+        // Update order for each item
+        for (index, item_id) in item_ids.iter().enumerate() {
+            if let Some(project) = data.find_project_mut(item_id) {
+                // Only update items that belong to this parent level
+                if project.parent_id == parent_id {
+                    project.order = index as u32;
+                }
             }
         }
-    }
 
-    save_projects_data(&app, &data)?;
+        Ok(())
+    })?;
 
     log::trace!("Successfully reordered items");
     Ok(())
@@ -5441,77 +6689,99 @@ pub async fn fetch_worktrees_status(app: AppHandle, project_id: String) -> Resul
         project_id
     );
 
-    // Spawn threads to fetch status for each worktree in parallel
-    // Using std::thread since get_branch_status is synchronous (uses Command)
     let base_branch = project.default_branch.clone();
+    let fetch = project.polling.as_ref().is_none_or(|p| p.remote_enabled);
 
-    for worktree in worktrees {
-        let app_clone = app.clone();
-        let base_branch_clone = base_branch.clone();
-
-        thread::spawn(move || {
-            let info = ActiveWorktreeInfo {
-                worktree_id: worktree.id.clone(),
-                worktree_path: worktree.path.clone(),
-                base_branch: base_branch_clone,
-                pr_number: worktree.pr_number,
-                pr_url: worktree.pr_url.clone(),
-            };
+    // A single job fetches every worktree's status in parallel
+    // Using threads since get_branch_status is synchronous (uses Command)
+    let total = worktrees.len();
+    let app_clone = app.clone();
+    let label = format!("Refresh status of {}", project.name);
+    crate::jobs::spawn(&app, JobKind::StatusRefresh, label, move |job| {
+        let app = &app_clone;
+        let base_branch = &base_branch;
+        let done = std::sync::atomic::AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for worktree in &worktrees {
+                let done = &done;
+                scope.spawn(move || {
+                    if job.is_cancelled() {
+                        return;
+                    }
+                    let info = ActiveWorktreeInfo {
+                        worktree_id: worktree.id.clone(),
+                        worktree_path: worktree.path.clone(),
+                        base_branch: base_branch.clone(),
+                        pr_number: worktree.pr_number,
+                        pr_url: worktree.pr_url.clone(),
+                    };
 
-            // Fetch git status (this may take a moment as it runs git commands)
-            match get_branch_status(&info) {
-                Ok(status) => {
-                    log::trace!(
-                        "[fetch_worktrees_status] Got status for {}: behind={}, ahead={}",
-                        worktree.name,
-                        status.behind_count,
-                        status.ahead_count
-                    );
+                    // Fetch git status (this may take a moment as it runs git commands)
+                    match get_branch_status(&info, fetch) {
+                        Ok(status) => {
+                            log::trace!(
+                                "[fetch_worktrees_status] Got status for {}: behind={}, ahead={}",
+                                worktree.name,
+                                status.behind_count,
+                                status.ahead_count
+                            );
 
-                    // Emit status update event
-                    if let Err(e) = app_clone.emit_all("git:status-update", &status) {
-                        log::warn!(
-                            "Failed to emit git status for worktree {}: {e}",
-                            worktree.id
-                        );
-                    } else {
-                        log::trace!(
-                            "[fetch_worktrees_status] Emitted git:status-update for {}",
-                            worktree.name
-                        );
-                    }
+                            // Emit status update event
+                            if let Err(e) = app.emit_all("git:status-update", &status) {
+                                log::warn!(
+                                    "Failed to emit git status for worktree {}: {e}",
+                                    worktree.id
+                                );
+                            } else {
+                                log::trace!(
+                                    "[fetch_worktrees_status] Emitted git:status-update for {}",
+                                    worktree.name
+                                );
+                            }
 
-                    // Update cached values in storage
-                    if let Ok(mut data) = load_projects_data(&app_clone) {
-                        if let Some(w) = data.worktrees.iter_mut().find(|w| w.id == worktree.id) {
-                            w.cached_behind_count = Some(status.behind_count);
-                            w.cached_ahead_count = Some(status.ahead_count);
-                            w.cached_uncommitted_added = Some(status.uncommitted_added);
-                            w.cached_uncommitted_removed = Some(status.uncommitted_removed);
-                            w.cached_branch_diff_added = Some(status.branch_diff_added);
-                            w.cached_branch_diff_removed = Some(status.branch_diff_removed);
-                            w.cached_unpushed_count = Some(status.unpushed_count);
-                            w.cached_status_at = Some(status.checked_at);
-
-                            if let Err(e) = save_projects_data(&app_clone, &data) {
+                            // Update cached values in storage
+                            let result = update_worktree(app, &worktree.id, |w| {
+                                w.cached_behind_count = Some(status.behind_count);
+                                w.cached_ahead_count = Some(status.ahead_count);
+                                w.cached_uncommitted_added = Some(status.uncommitted_added);
+                                w.cached_uncommitted_removed = Some(status.uncommitted_removed);
+                                w.cached_branch_diff_added = Some(status.branch_diff_added);
+                                w.cached_branch_diff_removed = Some(status.branch_diff_removed);
+                                w.cached_unpushed_count = Some(status.unpushed_count);
+                                w.cached_status_at = Some(status.checked_at);
+                                Ok(())
+                            });
+                            if let Err(e) = result {
                                 log::warn!(
                                     "Failed to save cached status for worktree {}: {e}",
                                     worktree.id
                                 );
                             }
                         }
+                        Err(e) => {
+                            log::warn!(
+                                "Failed to get git status for worktree {}: {e}",
+                                worktree.id
+                            );
+                        }
                     }
-                }
-                Err(e) => {
-                    log::warn!("Failed to get git status for worktree {}: {e}", worktree.id);
-                }
+
+                    let done = done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                    job.progress(
+                        format!("{done}/{total} worktrees"),
+                        Some(done as f32 / total as f32),
+                    );
+                });
             }
         });
-    }
+        if job.is_cancelled() {
+            return Err("Status refresh was cancelled".to_string());
+        }
+        Ok(())
+    });
 
-    // Don't wait for threads - fire and forget
-    // Status updates will be emitted via events as they complete
-    log::trace!("[fetch_worktrees_status] Spawned status fetch threads for project: {project_id}");
+    // Don't wait for the job - status updates are emitted as they complete
+    log::trace!("[fetch_worktrees_status] Started status fetch job for project: {project_id}");
     Ok(())
 }
 
@@ -5685,10 +6955,7 @@ pub async fn list_claude_commands() -> Result<Vec<ClaudeCommand>, String> {
 
 /// Get the avatars directory, creating it if needed
 fn get_avatars_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let avatars_dir = app_data_dir.join("avatars");
     std::fs::create_dir_all(&avatars_dir)
@@ -5744,15 +7011,16 @@ pub async fn set_project_avatar(app: AppHandle, project_id: String) -> Result<Pr
     // Update project with relative path
     let relative_path = format!("avatars/{dest_filename}");
 
-    let mut data = load_projects_data(&app)?;
-    let project = data
-        .find_project_mut(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let updated_project = with_projects_data_mut(&app, |data| {
+        let project = data
+            .find_project_mut(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
 
-    project.avatar_path = Some(relative_path);
-    let updated_project = project.clone();
+        project.avatar_path = Some(relative_path);
+        let updated_project = project.clone();
 
-    save_projects_data(&app, &data)?;
+        Ok(updated_project)
+    })?;
 
     log::trace!(
         "Successfully set avatar for project: {}",
@@ -5767,29 +7035,27 @@ pub async fn set_project_avatar(app: AppHandle, project_id: String) -> Result<Pr
 pub async fn remove_project_avatar(app: AppHandle, project_id: String) -> Result<Project, String> {
     log::trace!("Removing avatar for project: {project_id}");
 
-    let mut data = load_projects_data(&app)?;
-    let project = data
-        .find_project_mut(&project_id)
-        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let updated_project = with_projects_data_mut(&app, |data| {
+        let project = data
+            .find_project_mut(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
 
-    // Delete avatar file if it exists
-    if let Some(ref avatar_path) = project.avatar_path {
-        let app_data_dir = app
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+        // Delete avatar file if it exists
+        if let Some(ref avatar_path) = project.avatar_path {
+            let app_data_dir = crate::locations::app_data_dir(&app)?;
 
-        let full_path = app_data_dir.join(avatar_path);
-        if full_path.exists() {
-            let _ = std::fs::remove_file(&full_path);
-            log::trace!("Deleted avatar file: {full_path:?}");
+            let full_path = app_data_dir.join(avatar_path);
+            if full_path.exists() {
+                let _ = std::fs::remove_file(&full_path);
+                log::trace!("Deleted avatar file: {full_path:?}");
+            }
         }
-    }
 
-    project.avatar_path = None;
-    let updated_project = project.clone();
+        project.avatar_path = None;
+        let updated_project = project.clone();
 
-    save_projects_data(&app, &data)?;
+        Ok(updated_project)
+    })?;
 
     log::trace!(
         "Successfully removed avatar for project: {}",
@@ -5802,10 +7068,7 @@ pub async fn remove_project_avatar(app: AppHandle, project_id: String) -> Result
 /// Used by frontend to resolve relative avatar paths to absolute file:// URLs
 #[tauri::command]
 pub async fn get_app_data_dir(app: AppHandle) -> Result<String, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     Ok(app_data_dir.to_string_lossy().to_string())
 }
@@ -5814,6 +7077,44 @@ pub async fn get_app_data_dir(app: AppHandle) -> Result<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_editor_args() {
+        assert_eq!(editor_args("vscode", "/repo", None, Some(3)), vec!["/repo"]);
+        assert_eq!(
+            editor_args("cursor", "/repo", Some("src/main.rs"), Some(42)),
+            vec!["/repo", "-g", "/repo/src/main.rs:42"]
+        );
+        assert_eq!(
+            editor_args("xcode", "/repo", Some("/abs/App.swift"), Some(7)),
+            vec!["-l", "7", "/abs/App.swift"]
+        );
+    }
+
+    #[test]
+    fn test_setup_scripts() {
+        let config_setup = Some("npm install".to_string());
+        assert_eq!(
+            setup_scripts(config_setup.clone(), None),
+            vec!["npm install"]
+        );
+
+        let mut template: WorktreeTemplate = serde_json::from_value(serde_json::json!({
+            "id": "hotfix",
+            "name": "Hotfix",
+            "scripts": ["make seed"],
+        }))
+        .unwrap();
+        assert_eq!(
+            setup_scripts(config_setup.clone(), Some(&template)),
+            vec!["npm install", "make seed"]
+        );
+        template.setup_script = Some("npm ci".to_string());
+        assert_eq!(
+            setup_scripts(config_setup, Some(&template)),
+            vec!["npm ci", "make seed"]
+        );
+    }
+
     #[test]
     fn test_extract_structured_output_valid() {
         let output = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"I'll create a PR"},{"type":"tool_use","id":"toolu_123","name":"StructuredOutput","input":{"title":"Add feature","body":"This PR adds..."}}]}}"#;
@@ -0,0 +1,239 @@
+//! Git hooks around AI commits
+//!
+//! `create_commit_with_ai` runs the repository's `pre-commit` and `commit-msg`
+//! hooks like any other commit. When one of them rejects the commit, the
+//! hooks' output is returned as a [`HookFailure`] instead of an error, so the
+//! UI can show it and offer to retry with `--no-verify` or to have Claude fix
+//! the reported problems first.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::claude_cli::get_cli_binary_path;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Hooks that can reject a commit
+const COMMIT_HOOKS: [&str; 2] = ["pre-commit", "commit-msg"];
+
+/// Most bytes of each hook output stream kept (the end is kept)
+const MAX_OUTPUT_BYTES: usize = 8_000;
+
+/// Turns Claude gets to fix the problems reported by the hooks
+const FIX_MAX_TURNS: &str = "30";
+
+const FIX_PROMPT: &str = "The git commit hooks of this repository rejected the staged changes. \
+Fix the problems they report by editing the files, without committing. \
+Don't disable or skip the hooks.\n\n## Hook output\n```\n{output}\n```";
+
+/// A commit rejected by the repository's hooks
+#[derive(Debug, Clone, Serialize)]
+pub struct HookFailure {
+    /// Hooks installed in the repository
+    pub hooks: Vec<String>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Result of running `git commit`
+#[derive(Debug)]
+pub enum CommitOutcome {
+    Committed(String),
+    HookFailed(HookFailure),
+}
+
+/// Directory git runs hooks from (honours `core.hooksPath`)
+fn hooks_dir(repo_path: &str) -> Option<PathBuf> {
+    let output = silent_command("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Some(if dir.is_absolute() {
+        dir
+    } else {
+        Path::new(repo_path).join(dir)
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Commit hooks git will run in a repository
+pub fn detect_hooks(repo_path: &str) -> Vec<String> {
+    let Some(dir) = hooks_dir(repo_path) else {
+        return Vec::new();
+    };
+    COMMIT_HOOKS
+        .iter()
+        .filter(|hook| is_executable(&dir.join(hook)))
+        .map(|hook| hook.to_string())
+        .collect()
+}
+
+/// The end of a hook output stream
+fn tail(text: &str) -> String {
+    let text = text.trim();
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return text.to_string();
+    }
+    let mut start = text.len() - MAX_OUTPUT_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("[...]\n{}", &text[start..])
+}
+
+/// Commit the staged changes, returning the new commit's hash, or the hooks'
+/// output if they rejected it
+pub fn commit(repo_path: &str, message: &str, no_verify: bool) -> Result<CommitOutcome, String> {
+    let mut cmd = silent_command("git");
+    cmd.args(["commit", "-m", message]);
+    if no_verify {
+        cmd.arg("--no-verify");
+    }
+    let output = cmd
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to create commit: {e}"))?;
+
+    if !output.status.success() {
+        let hooks = if no_verify {
+            Vec::new()
+        } else {
+            detect_hooks(repo_path)
+        };
+        // With hooks installed, a commit that would otherwise go through was
+        // rejected by them
+        let committable = silent_command("git")
+            .args(["commit", "--dry-run", "-m", message])
+            .current_dir(repo_path)
+            .output_audited()
+            .is_ok_and(|o| o.status.success());
+        if !hooks.is_empty() && committable {
+            return Ok(CommitOutcome::HookFailed(HookFailure {
+                hooks,
+                stdout: tail(&String::from_utf8_lossy(&output.stdout)),
+                stderr: tail(&String::from_utf8_lossy(&output.stderr)),
+            }));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to commit: {stderr}"));
+    }
+
+    let hash_output = silent_command("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to get commit hash: {e}"))?;
+    Ok(CommitOutcome::Committed(
+        String::from_utf8_lossy(&hash_output.stdout)
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// Prompt asking Claude to fix what the hooks reported
+pub fn fix_prompt(failure: &HookFailure) -> String {
+    let output = [failure.stdout.as_str(), failure.stderr.as_str()]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    FIX_PROMPT.replace("{output}", &output)
+}
+
+/// Have Claude edit the worktree to fix the problems the hooks reported
+pub fn fix_with_claude(
+    app: &AppHandle,
+    repo_path: &str,
+    failure: &HookFailure,
+    model: Option<&str>,
+) -> Result<(), String> {
+    let cli_path = get_cli_binary_path(app)?;
+    if !cli_path.exists() {
+        return Err("Claude CLI not installed".to_string());
+    }
+
+    log::info!("Asking Claude to fix commit hook failures in {repo_path}");
+    let mut child = silent_command(&cli_path)
+        .args([
+            "--print",
+            "--verbose",
+            "--input-format",
+            "stream-json",
+            "--output-format",
+            "stream-json",
+            "--model",
+            model.unwrap_or("sonnet"),
+            "--no-session-persistence",
+            "--max-turns",
+            FIX_MAX_TURNS,
+            "--permission-mode",
+            "acceptEdits",
+        ])
+        .current_dir(repo_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn Claude CLI: {e}"))?;
+
+    {
+        let stdin = child.stdin.as_mut().ok_or("Failed to open stdin")?;
+        let input_message = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": fix_prompt(failure)
+            }
+        });
+        writeln!(stdin, "{input_message}").map_err(|e| format!("Failed to write to stdin: {e}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for Claude CLI: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to fix hook failures: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_prompt_and_tail() {
+        let failure = HookFailure {
+            hooks: vec!["pre-commit".to_string()],
+            stdout: "eslint....Failed".to_string(),
+            stderr: String::new(),
+        };
+        let prompt = fix_prompt(&failure);
+        assert!(prompt.contains("```\neslint....Failed\n```"));
+
+        let long = "é".repeat(MAX_OUTPUT_BYTES);
+        let kept = tail(&long);
+        assert!(kept.starts_with("[...]\n"));
+        assert!(kept.len() <= MAX_OUTPUT_BYTES + "[...]\n".len());
+    }
+}
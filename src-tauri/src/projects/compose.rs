@@ -0,0 +1,141 @@
+//! Docker Compose stacks per worktree
+//!
+//! A `services` section in jean.json makes each worktree run its own compose
+//! project (`jean-<worktree>-<id>`), so stacks of different worktrees don't
+//! share containers, networks or volumes. Deleting a worktree tears its stack
+//! down, so nothing keeps running (or using disk) after the workspace is gone.
+
+use std::process::Command;
+
+use super::git::read_jean_config;
+use super::types::JeanServices;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Log lines returned by default
+const DEFAULT_LOG_TAIL: u32 = 200;
+
+/// Compose project name of a worktree: lowercase letters, digits and dashes
+pub fn project_name(worktree_id: &str, worktree_path: &str) -> String {
+    let dir_name = std::path::Path::new(worktree_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let sanitized: String = dir_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let id: String = worktree_id
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(8)
+        .collect::<String>()
+        .to_lowercase();
+    format!("jean-{}-{id}", sanitized.trim_matches('-'))
+}
+
+/// The worktree's services config, or an error if jean.json has none
+fn services(worktree_path: &str) -> Result<JeanServices, String> {
+    read_jean_config(worktree_path)
+        .and_then(|config| config.services)
+        .ok_or_else(|| "No services configured in jean.json".to_string())
+}
+
+fn compose_command(services: &JeanServices, worktree_id: &str, worktree_path: &str) -> Command {
+    let mut cmd = silent_command("docker");
+    cmd.args(["compose", "-p", &project_name(worktree_id, worktree_path)]);
+    if let Some(file) = &services.compose_file {
+        cmd.args(["-f", file]);
+    }
+    cmd.current_dir(worktree_path);
+    cmd
+}
+
+fn run(mut cmd: Command, action: &str) -> Result<String, String> {
+    let output = cmd
+        .output_audited()
+        .map_err(|e| format!("Failed to run docker compose {action}: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let combined = format!("{stderr}{stdout}");
+        return Err(format!(
+            "docker compose {action} failed: {}",
+            combined.trim()
+        ));
+    }
+    // Compose reports progress on stderr
+    Ok(format!("{stdout}{stderr}"))
+}
+
+/// Start the worktree's stack in the background
+pub fn up(worktree_id: &str, worktree_path: &str) -> Result<String, String> {
+    let services = services(worktree_path)?;
+    let mut cmd = compose_command(&services, worktree_id, worktree_path);
+    cmd.args(["up", "-d", "--remove-orphans"]);
+    run(cmd, "up")
+}
+
+/// Stop and remove the worktree's stack, optionally with its volumes
+pub fn down(
+    worktree_id: &str,
+    worktree_path: &str,
+    remove_volumes: bool,
+) -> Result<String, String> {
+    let services = services(worktree_path)?;
+    let mut cmd = compose_command(&services, worktree_id, worktree_path);
+    cmd.args(["down", "--remove-orphans"]);
+    if remove_volumes {
+        cmd.arg("--volumes");
+    }
+    run(cmd, "down")
+}
+
+/// Recent logs of the stack (or of one service)
+pub fn logs(
+    worktree_id: &str,
+    worktree_path: &str,
+    service: Option<&str>,
+    tail: Option<u32>,
+) -> Result<String, String> {
+    let services = services(worktree_path)?;
+    let mut cmd = compose_command(&services, worktree_id, worktree_path);
+    let tail = tail.unwrap_or(DEFAULT_LOG_TAIL).to_string();
+    cmd.args(["logs", "--no-color", "--timestamps", "--tail", &tail]);
+    if let Some(service) = service {
+        cmd.arg(service);
+    }
+    run(cmd, "logs")
+}
+
+/// Tear down the stack of a worktree that is being deleted. Does nothing when
+/// jean.json has no services; failures are only logged.
+pub fn teardown(worktree_id: &str, worktree_path: &str) {
+    let Some(services) = read_jean_config(worktree_path).and_then(|config| config.services) else {
+        return;
+    };
+    log::trace!("Tearing down compose stack of {worktree_path}");
+    if let Err(e) = down(
+        worktree_id,
+        worktree_path,
+        services.remove_volumes_on_delete,
+    ) {
+        log::warn!("Failed to tear down compose stack of {worktree_path}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_name() {
+        assert_eq!(
+            project_name(
+                "3F2A9C1E-77b0-4c1d-9a11-000000000000",
+                "/home/me/jean/My App/fuzzy_tiger"
+            ),
+            "jean-fuzzy-tiger-3f2a9c1e"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Background prefetch of PR and issue contexts
+//!
+//! Loading a PR context takes several `gh` calls (view, diff), and issue
+//! contexts one more per issue. During remote polls, the PR of a polled
+//! worktree, the issues it links to (closing keywords in the PR body) and the
+//! issues already loaded for the worktree are fetched ahead of time and kept
+//! in memory, so `load_pr_context` / `load_issue_context` and investigate
+//! actions can use them without waiting on GitHub.
+//!
+//! Entries are only served while fresh (`PREFETCH_TTL`); older ones are
+//! refetched as before.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use tauri::AppHandle;
+
+use super::git::get_repo_identifier;
+use super::github_issues::{
+    fetch_github_issue, fetch_github_pr, get_pr_diff, get_worktree_issue_refs, GitHubIssueDetail,
+    GitHubPullRequestDetail,
+};
+
+/// How long prefetched data is served before it must be refetched
+const PREFETCH_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Most cached PRs and issues; the oldest are evicted first
+const MAX_ENTRIES: usize = 64;
+
+enum Prefetched {
+    Pr {
+        detail: Box<GitHubPullRequestDetail>,
+        diff: Option<String>,
+    },
+    Issue(Box<GitHubIssueDetail>),
+}
+
+struct Entry {
+    fetched_at: Instant,
+    data: Prefetched,
+}
+
+/// Prefetched contexts, keyed like context files: `{repo_key}-pr-{n}` /
+/// `{repo_key}-issue-{n}`
+static CACHE: Lazy<Mutex<HashMap<String, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Worktrees with a prefetch in progress
+static IN_FLIGHT: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn pr_key(repo_key: &str, number: u32) -> String {
+    format!("{repo_key}-pr-{number}")
+}
+
+fn issue_key(repo_key: &str, number: u32) -> String {
+    format!("{repo_key}-issue-{number}")
+}
+
+fn is_fresh(key: &str) -> bool {
+    CACHE
+        .lock()
+        .unwrap()
+        .get(key)
+        .is_some_and(|e| e.fetched_at.elapsed() < PREFETCH_TTL)
+}
+
+fn insert(key: String, data: Prefetched) {
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|_, e| e.fetched_at.elapsed() < PREFETCH_TTL);
+    if cache.len() >= MAX_ENTRIES {
+        if let Some(oldest) = cache
+            .iter()
+            .min_by_key(|(_, e)| e.fetched_at)
+            .map(|(k, _)| k.clone())
+        {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(
+        key,
+        Entry {
+            fetched_at: Instant::now(),
+            data,
+        },
+    );
+}
+
+/// A recently prefetched PR and its diff
+pub fn cached_pr(repo_key: &str, number: u32) -> Option<(GitHubPullRequestDetail, Option<String>)> {
+    let cache = CACHE.lock().unwrap();
+    match cache.get(&pr_key(repo_key, number)) {
+        Some(Entry {
+            fetched_at,
+            data: Prefetched::Pr { detail, diff },
+        }) if fetched_at.elapsed() < PREFETCH_TTL => Some((*detail.clone(), diff.clone())),
+        _ => None,
+    }
+}
+
+/// A recently prefetched issue
+pub fn cached_issue(repo_key: &str, number: u32) -> Option<GitHubIssueDetail> {
+    let cache = CACHE.lock().unwrap();
+    match cache.get(&issue_key(repo_key, number)) {
+        Some(Entry {
+            fetched_at,
+            data: Prefetched::Issue(issue),
+        }) if fetched_at.elapsed() < PREFETCH_TTL => Some(*issue.clone()),
+        _ => None,
+    }
+}
+
+/// Issue numbers a PR body links to with closing keywords ("Fixes #12")
+pub fn linked_issue_numbers(body: &str) -> Vec<u32> {
+    const KEYWORDS: &[&str] = &[
+        "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+    ];
+
+    let words: Vec<&str> = body.split_whitespace().collect();
+    let mut numbers = Vec::new();
+    for pair in words.windows(2) {
+        let keyword = pair[0].trim_end_matches(':').to_lowercase();
+        if !KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        let Some(reference) = pair[1].strip_prefix('#') else {
+            continue;
+        };
+        let digits: String = reference.chars().take_while(char::is_ascii_digit).collect();
+        if let Ok(number) = digits.parse::<u32>() {
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+    }
+    numbers
+}
+
+/// Prefetch the PR and issue contexts of a worktree in the background.
+/// Does nothing if a prefetch for the worktree is already running, and only
+/// fetches what isn't fresh in the cache.
+pub fn spawn_prefetch(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &str,
+    pr_number: Option<u32>,
+) {
+    if !IN_FLIGHT.lock().unwrap().insert(worktree_id.to_string()) {
+        return;
+    }
+
+    let app = app.clone();
+    let worktree_id = worktree_id.to_string();
+    let worktree_path = worktree_path.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = prefetch(&app, &worktree_id, &worktree_path, pr_number) {
+            log::trace!("Context prefetch failed for worktree {worktree_id}: {e}");
+        }
+        IN_FLIGHT.lock().unwrap().remove(&worktree_id);
+    });
+}
+
+fn prefetch(
+    app: &AppHandle,
+    worktree_id: &str,
+    worktree_path: &str,
+    pr_number: Option<u32>,
+) -> Result<(), String> {
+    let repo_key = get_repo_identifier(worktree_path)?.to_key();
+    let gh = crate::gh_cli::config::resolve_gh_binary(app);
+
+    let mut issues: Vec<u32> = get_worktree_issue_refs(app, worktree_id)?
+        .iter()
+        .filter_map(|key| {
+            let (repo, number) = key.rsplit_once('-')?;
+            (repo == repo_key).then(|| number.parse().ok()).flatten()
+        })
+        .collect();
+
+    if let Some(pr_number) = pr_number {
+        let key = pr_key(&repo_key, pr_number);
+        let body = if is_fresh(&key) {
+            cached_pr(&repo_key, pr_number).and_then(|(detail, _)| detail.body)
+        } else {
+            let detail = fetch_github_pr(&gh, worktree_path, pr_number)?;
+            let diff = get_pr_diff(worktree_path, pr_number, &gh).ok();
+            let body = detail.body.clone();
+            insert(
+                key,
+                Prefetched::Pr {
+                    detail: Box::new(detail),
+                    diff,
+                },
+            );
+            log::trace!("Prefetched PR #{pr_number} context");
+            body
+        };
+        issues.extend(linked_issue_numbers(body.as_deref().unwrap_or_default()));
+    }
+
+    issues.sort_unstable();
+    issues.dedup();
+    for number in issues {
+        let key = issue_key(&repo_key, number);
+        if is_fresh(&key) {
+            continue;
+        }
+        match fetch_github_issue(&gh, worktree_path, number) {
+            Ok(issue) => {
+                insert(key, Prefetched::Issue(Box::new(issue)));
+                log::trace!("Prefetched issue #{number} context");
+            }
+            Err(e) => log::trace!("Failed to prefetch issue #{number}: {e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linked_issue_numbers() {
+        let body = "Fixes #12 and closes #34.\n\nResolves: #12\nSee #56, fix #7x";
+        assert_eq!(linked_issue_numbers(body), vec![12, 34, 7]);
+        assert!(linked_issue_numbers("Related to #9").is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+//! Conventional Commits checks for generated messages
+//!
+//! AI-generated commit messages and PR titles are checked against the
+//! `commit_rules` preference (allowed types and scopes, subject length, a
+//! blank line before the body) before they're used. A message that's only too
+//! long or missing the blank line is fixed in place; any other violation is
+//! sent back to the model as feedback, and a message that still fails after
+//! [`MAX_REGENERATIONS`] attempts is an error rather than a malformed commit.
+//! With `reject_invalid`, any violation is an error right away.
+//!
+//! Hand-written messages of manual commits are only checked (and rejected)
+//! when `check_manual_commits` is on.
+
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::CommitRules;
+
+/// Regenerations attempted before giving up
+pub const MAX_REGENERATIONS: usize = 2;
+
+/// `type(scope)!: description`
+static HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([a-z]+)(?:\(([^()\s]+)\))?(!)?: \S").expect("valid header regex"));
+
+/// A way a message breaks the rules
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// Not `type(scope): description`
+    Malformed,
+    UnknownType(String),
+    UnknownScope(String),
+    MissingScope,
+    TooLong {
+        length: usize,
+        max: usize,
+    },
+    /// The body starts right after the first line
+    MissingBlankLine,
+}
+
+impl Violation {
+    /// Whether the message can be fixed without regenerating it
+    fn is_fixable(&self) -> bool {
+        matches!(self, Self::TooLong { .. } | Self::MissingBlankLine)
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "first line is not `type(scope): description`"),
+            Self::UnknownType(t) => write!(f, "type `{t}` is not allowed"),
+            Self::UnknownScope(s) => write!(f, "scope `{s}` is not allowed"),
+            Self::MissingScope => write!(f, "a scope is required"),
+            Self::TooLong { length, max } => {
+                write!(f, "first line is {length} characters (max {max})")
+            }
+            Self::MissingBlankLine => write!(f, "no blank line between first line and body"),
+        }
+    }
+}
+
+/// Check preferences before they're saved
+pub fn validate_rules(rules: &CommitRules) -> Result<(), String> {
+    if rules.types.iter().all(|t| t.trim().is_empty()) {
+        return Err("Commit rules need at least one allowed type".to_string());
+    }
+    if rules.max_subject_length < 20 {
+        return Err("Maximum commit subject length must be at least 20".to_string());
+    }
+    Ok(())
+}
+
+/// Rule violations of a message's first line
+pub fn check(rules: &CommitRules, message: &str) -> Vec<Violation> {
+    let subject = message.lines().next().unwrap_or("").trim_end();
+    let mut violations = Vec::new();
+
+    match HEADER.captures(subject) {
+        None => violations.push(Violation::Malformed),
+        Some(caps) => {
+            let kind = &caps[1];
+            if !rules.types.iter().any(|t| t == kind) {
+                violations.push(Violation::UnknownType(kind.to_string()));
+            }
+            match caps.get(2).map(|m| m.as_str()) {
+                Some(scope)
+                    if !rules.scopes.is_empty() && !rules.scopes.iter().any(|s| s == scope) =>
+                {
+                    violations.push(Violation::UnknownScope(scope.to_string()));
+                }
+                None if rules.require_scope => violations.push(Violation::MissingScope),
+                _ => {}
+            }
+        }
+    }
+
+    let length = subject.chars().count();
+    if length > rules.max_subject_length {
+        violations.push(Violation::TooLong {
+            length,
+            max: rules.max_subject_length,
+        });
+    }
+    if message
+        .lines()
+        .nth(1)
+        .is_some_and(|line| !line.trim().is_empty())
+    {
+        violations.push(Violation::MissingBlankLine);
+    }
+    violations
+}
+
+/// Separate the body from the first line with a blank line
+fn insert_blank_line(message: &str) -> String {
+    match message.split_once('\n') {
+        Some((subject, body)) if !body.lines().next().unwrap_or("").trim().is_empty() => {
+            format!("{subject}\n\n{body}")
+        }
+        _ => message.to_string(),
+    }
+}
+
+fn describe(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Check a hand-written commit message, when the rules apply to manual commits
+pub fn check_manual(rules: &CommitRules, message: &str) -> Result<(), String> {
+    if !rules.enabled || !rules.check_manual_commits {
+        return Ok(());
+    }
+    let violations = check(rules, message);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Commit message doesn't follow the commit rules: {}",
+            describe(&violations)
+        ))
+    }
+}
+
+/// Shorten a message's first line to the maximum length, at a word boundary
+pub fn truncate_subject(rules: &CommitRules, message: &str) -> String {
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let max = rules.max_subject_length;
+    if subject.chars().count() <= max {
+        return message.to_string();
+    }
+    let cut: String = subject.chars().take(max).collect();
+    let shortened = match cut.rfind(' ') {
+        // Keep at least the `type(scope): ` prefix and a word
+        Some(i) if i > max / 2 => cut[..i].trim_end().to_string(),
+        _ => cut,
+    };
+    if rest.is_empty() {
+        shortened
+    } else {
+        format!("{shortened}\n{rest}")
+    }
+}
+
+/// Instructions for regenerating a rejected message
+fn feedback(rules: &CommitRules, message: &str, violations: &[Violation]) -> String {
+    let problems: Vec<String> = violations.iter().map(|v| format!("- {v}")).collect();
+    let scopes = if rules.scopes.is_empty() {
+        "any".to_string()
+    } else {
+        rules.scopes.join(", ")
+    };
+    format!(
+        "Your previous answer `{}` was rejected:\n{}\n\nFollow Conventional Commits: `type(scope): description`. Allowed types: {}. Allowed scopes: {scopes}{}. Keep the first line under {} characters, and leave a blank line before any body.",
+        message.lines().next().unwrap_or(""),
+        problems.join("\n"),
+        rules.types.join(", "),
+        if rules.require_scope { " (required)" } else { "" },
+        rules.max_subject_length
+    )
+}
+
+/// Make a generated message follow the rules.
+///
+/// `regenerate` is called with feedback on what was wrong and returns a new
+/// message.
+pub fn enforce(
+    rules: &CommitRules,
+    message: String,
+    mut regenerate: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    if !rules.enabled {
+        return Ok(message);
+    }
+    let mut message = message;
+    let mut attempts = 0;
+    loop {
+        let violations = check(rules, &message);
+        if violations.is_empty() {
+            return Ok(message);
+        }
+        if !rules.reject_invalid && violations.iter().all(Violation::is_fixable) {
+            log::trace!("Fixing generated message: {}", describe(&violations));
+            return Ok(truncate_subject(rules, &insert_blank_line(&message)));
+        }
+        if rules.reject_invalid || attempts == MAX_REGENERATIONS {
+            return Err(format!(
+                "Generated message doesn't follow the commit rules ({}): {}",
+                describe(&violations),
+                message.lines().next().unwrap_or("")
+            ));
+        }
+        attempts += 1;
+        log::trace!("Regenerating message (attempt {attempts}): {violations:?}");
+        message = regenerate(&feedback(rules, &message, &violations))?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_and_truncate() {
+        let rules = CommitRules {
+            scopes: vec!["api".to_string()],
+            max_subject_length: 30,
+            ..Default::default()
+        };
+        assert!(check(&rules, "feat(api): add login\n\nBody").is_empty());
+        assert!(check(&rules, "fix!: drop v1 endpoint").is_empty());
+        assert_eq!(check(&rules, "Add login"), vec![Violation::Malformed]);
+        assert_eq!(
+            check(&rules, "feature(web): add login"),
+            vec![
+                Violation::UnknownType("feature".to_string()),
+                Violation::UnknownScope("web".to_string())
+            ]
+        );
+
+        let long = "feat(api): add the new login flow for users\n\nBody";
+        assert_eq!(
+            check(&rules, long),
+            vec![Violation::TooLong {
+                length: 43,
+                max: 30
+            }]
+        );
+        assert_eq!(
+            enforce(&rules, long.to_string(), |_| unreachable!()).unwrap(),
+            "feat(api): add the new login\n\nBody"
+        );
+    }
+
+    #[test]
+    fn test_enforce_regenerates() {
+        let rules = CommitRules::default();
+        let mut calls = 0;
+        let message = enforce(&rules, "Added login".to_string(), |feedback| {
+            calls += 1;
+            assert!(feedback.contains("first line is not"));
+            Ok("feat: add login".to_string())
+        })
+        .unwrap();
+        assert_eq!((message.as_str(), calls), ("feat: add login", 1));
+
+        let result = enforce(&rules, "Added login".to_string(), |_| {
+            Ok("Still wrong".to_string())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_blank_line_and_strict_modes() {
+        let mut rules = CommitRules::default();
+        let message = "feat: add login\nUses the new session API";
+        assert_eq!(check(&rules, message), vec![Violation::MissingBlankLine]);
+        assert_eq!(
+            enforce(&rules, message.to_string(), |_| unreachable!()).unwrap(),
+            "feat: add login\n\nUses the new session API"
+        );
+        assert!(check_manual(&rules, "Added login").is_ok());
+
+        rules.reject_invalid = true;
+        rules.check_manual_commits = true;
+        assert!(enforce(&rules, message.to_string(), |_| unreachable!()).is_err());
+        assert!(check_manual(&rules, "Added login").is_err());
+        assert!(check_manual(&rules, "feat: add login\n\nBody").is_ok());
+    }
+}
@@ -0,0 +1,378 @@
+//! Diff coverage
+//!
+//! Reads the lcov or Cobertura report a test run left in a worktree and
+//! computes how many of the lines changed since the branch forked are
+//! covered. Only lines the report instruments count; changed files the report
+//! doesn't mention are listed separately. Used by `get_diff_coverage`, the
+//! pre-PR checklist's minimum diff coverage, and the AI review prompt.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::stacks;
+use super::storage::load_projects_data;
+use super::types::Worktree;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Where coverage tools write their reports, relative to the worktree
+const REPORT_PATHS: &[&str] = &[
+    "coverage/lcov.info",
+    "lcov.info",
+    "target/llvm-cov/lcov.info",
+    "coverage/cobertura-coverage.xml",
+    "coverage/cobertura.xml",
+    "cobertura.xml",
+    "coverage.xml",
+];
+
+/// Uncovered lines listed per file in the review prompt
+const MAX_PROMPT_LINES: usize = 20;
+
+/// Hits per line, per file
+type LineHits = HashMap<String, HashMap<u32, u64>>;
+
+/// Diff coverage of one file
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileCoverage {
+    pub path: String,
+    pub covered: usize,
+    /// Changed lines the report instruments
+    pub total: usize,
+    pub uncovered_lines: Vec<u32>,
+}
+
+/// Coverage of the lines changed by a branch
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffCoverage {
+    /// Report the coverage was read from
+    pub report_path: String,
+    pub covered: usize,
+    pub total: usize,
+    /// Covered share of the instrumented changed lines (None = nothing instrumented)
+    pub percent: Option<f64>,
+    pub files: Vec<FileCoverage>,
+    /// Changed files the report has no data for
+    pub files_without_coverage: Vec<String>,
+}
+
+static HUNK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@@ -\S+ \+(\d+)(?:,(\d+))? @@").expect("valid hunk regex"));
+static COBERTURA_CLASS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<class\b[^>]*\bfilename="([^"]+)""#).expect("valid class regex"));
+static COBERTURA_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<line\b[^>]*\bnumber="(\d+)"[^>]*\bhits="(\d+)""#).expect("valid line regex")
+});
+static COBERTURA_SOURCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<source>([^<]+)</source>").expect("valid source regex"));
+
+/// Path relative to the worktree, with forward slashes
+fn normalize(path: &str, root: &str) -> String {
+    let path = path.replace('\\', "/");
+    let root = format!("{}/", root.replace('\\', "/").trim_end_matches('/'));
+    path.strip_prefix(&root)
+        .unwrap_or(&path)
+        .trim_start_matches("./")
+        .to_string()
+}
+
+fn parse_lcov(content: &str, root: &str) -> LineHits {
+    let mut hits = LineHits::new();
+    let mut file: Option<String> = None;
+    for line in content.lines() {
+        if let Some(path) = line.strip_prefix("SF:") {
+            file = Some(normalize(path, root));
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut parts = data.split(',');
+            let line_number = parts.next().and_then(|n| n.parse().ok());
+            let count = parts.next().and_then(|n| n.trim().parse().ok());
+            if let (Some(file), Some(line_number), Some(count)) = (&file, line_number, count) {
+                *hits
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(line_number)
+                    .or_default() += count;
+            }
+        } else if line == "end_of_record" {
+            file = None;
+        }
+    }
+    hits
+}
+
+fn parse_cobertura(content: &str, root: &str) -> LineHits {
+    // Class file names are relative to one of the report's sources
+    let sources: Vec<String> = COBERTURA_SOURCE
+        .captures_iter(content)
+        .map(|c| normalize(c[1].trim(), root))
+        .filter(|s| !s.is_empty() && s != "." && !Path::new(s).is_absolute())
+        .collect();
+    let mut hits = LineHits::new();
+    let mut file: Option<String> = None;
+    for line in content.lines() {
+        if let Some(caps) = COBERTURA_CLASS.captures(line) {
+            let name = normalize(&caps[1], root);
+            let resolved = sources
+                .iter()
+                .map(|s| format!("{s}/{name}"))
+                .find(|p| Path::new(root).join(p).exists())
+                .unwrap_or(name);
+            file = Some(resolved);
+        }
+        for caps in COBERTURA_LINE.captures_iter(line) {
+            let (Some(file), Ok(number), Ok(count)) =
+                (&file, caps[1].parse::<u32>(), caps[2].parse::<u64>())
+            else {
+                continue;
+            };
+            *hits
+                .entry(file.clone())
+                .or_default()
+                .entry(number)
+                .or_default() += count;
+        }
+    }
+    hits
+}
+
+/// Most recently written coverage report in a worktree
+pub fn find_report(root: &Path) -> Option<PathBuf> {
+    REPORT_PATHS
+        .iter()
+        .map(|p| root.join(p))
+        .filter_map(|p| {
+            let modified = std::fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+            Some((modified, p))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, p)| p)
+}
+
+fn read_report(path: &Path, root: &str) -> Result<LineHits, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read coverage report: {e}"))?;
+    let is_xml = path.extension().is_some_and(|e| e == "xml");
+    Ok(if is_xml {
+        parse_cobertura(&content, root)
+    } else {
+        parse_lcov(&content, root)
+    })
+}
+
+/// Added and modified lines per file of a zero-context diff
+fn changed_lines(diff: &str) -> BTreeMap<String, Vec<u32>> {
+    let mut changed: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    let mut file: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").map(str::to_string);
+        } else if let Some(caps) = HUNK.captures(line) {
+            let Some(file) = &file else { continue };
+            let start: u32 = caps[1].parse().unwrap_or(0);
+            let count: u32 = caps.get(2).map_or(1, |c| c.as_str().parse().unwrap_or(0));
+            changed
+                .entry(file.clone())
+                .or_default()
+                .extend(start..start + count);
+        }
+    }
+    changed.retain(|_, lines| !lines.is_empty());
+    changed
+}
+
+/// Coverage of the changed lines
+fn compute(
+    changed: &BTreeMap<String, Vec<u32>>,
+    hits: &LineHits,
+    report_path: String,
+) -> DiffCoverage {
+    let mut files = Vec::new();
+    let mut files_without_coverage = Vec::new();
+    for (path, lines) in changed {
+        let Some(file_hits) = hits.get(path) else {
+            files_without_coverage.push(path.clone());
+            continue;
+        };
+        let instrumented: Vec<(u32, u64)> = lines
+            .iter()
+            .filter_map(|l| file_hits.get(l).map(|h| (*l, *h)))
+            .collect();
+        if instrumented.is_empty() {
+            continue;
+        }
+        let uncovered_lines: Vec<u32> = instrumented
+            .iter()
+            .filter(|(_, h)| *h == 0)
+            .map(|(l, _)| *l)
+            .collect();
+        files.push(FileCoverage {
+            path: path.clone(),
+            covered: instrumented.len() - uncovered_lines.len(),
+            total: instrumented.len(),
+            uncovered_lines,
+        });
+    }
+    let covered = files.iter().map(|f| f.covered).sum();
+    let total = files.iter().map(|f| f.total).sum();
+    DiffCoverage {
+        report_path,
+        covered,
+        total,
+        percent: (total > 0).then(|| covered as f64 * 100.0 / total as f64),
+        files,
+        files_without_coverage,
+    }
+}
+
+/// Zero-context diff of the branch and working tree since it forked from the
+/// target branch on `remote`
+fn branch_diff(path: &str, remote: &str, target_branch: &str) -> Result<String, String> {
+    let git = |args: &[&str]| -> Result<String, String> {
+        let output = silent_command("git")
+            .args(args)
+            .current_dir(path)
+            .output_audited()
+            .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let base = git(&["merge-base", &format!("{remote}/{target_branch}"), "HEAD"])?;
+    git(&["diff", "-U0", "--no-color", base.trim()])
+}
+
+/// Diff coverage of a worktree against the branch its PR targets on `remote`,
+/// from the latest coverage report. None when there is no report.
+pub fn for_worktree(
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+) -> Result<Option<DiffCoverage>, String> {
+    let Some(report) = find_report(Path::new(&worktree.path)) else {
+        return Ok(None);
+    };
+    let hits = read_report(&report, &worktree.path)?;
+    let changed = changed_lines(&branch_diff(&worktree.path, remote, target_branch)?);
+    Ok(Some(compute(
+        &changed,
+        &hits,
+        report.to_string_lossy().to_string(),
+    )))
+}
+
+/// Review prompt section listing changed lines no test covers
+pub fn prompt_section(coverage: &DiffCoverage) -> Option<String> {
+    let uncovered: Vec<&FileCoverage> = coverage
+        .files
+        .iter()
+        .filter(|f| !f.uncovered_lines.is_empty())
+        .collect();
+    if uncovered.is_empty() {
+        return None;
+    }
+    let mut section = format!(
+        "## Untested Changes\n\nThe latest coverage report covers {} of {} changed lines. These changed lines are not covered by any test; point out missing tests where it matters:\n",
+        coverage.covered, coverage.total
+    );
+    for file in uncovered {
+        let lines: Vec<String> = file
+            .uncovered_lines
+            .iter()
+            .take(MAX_PROMPT_LINES)
+            .map(u32::to_string)
+            .collect();
+        let more = file.uncovered_lines.len().saturating_sub(MAX_PROMPT_LINES);
+        section.push_str(&format!("- {}: lines {}", file.path, lines.join(", ")));
+        if more > 0 {
+            section.push_str(&format!(" (+{more} more)"));
+        }
+        section.push('\n');
+    }
+    Some(section)
+}
+
+/// Coverage of the lines a worktree's branch changes, from the latest lcov or
+/// Cobertura report in the worktree
+#[tauri::command]
+pub async fn get_diff_coverage(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<DiffCoverage, String> {
+    log::trace!("Computing diff coverage for worktree {worktree_id}");
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let target_branch = stacks::pr_base(&data, project, worktree);
+    for_worktree(worktree, project.remote(), target_branch)?.ok_or_else(|| {
+        format!(
+            "No coverage report found in {}. Run the tests with coverage (lcov or Cobertura) first.",
+            worktree.name
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_coverage() {
+        let lcov = "\
+SF:/repo/src/lib.rs
+DA:1,1
+DA:2,0
+DA:3,4
+end_of_record
+SF:/repo/src/other.rs
+DA:1,1
+end_of_record
+";
+        let hits = parse_lcov(lcov, "/repo");
+        assert_eq!(hits["src/lib.rs"][&2], 0);
+
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,0 +2,2 @@
++fn a() {}
++fn b() {}
+@@ -9 +10,0 @@
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-old
++new
+";
+        let changed = changed_lines(diff);
+        assert_eq!(changed["src/lib.rs"], vec![2, 3]);
+        assert!(!changed.contains_key("src/other.rs"));
+
+        let coverage = compute(&changed, &hits, "lcov.info".to_string());
+        assert_eq!((coverage.covered, coverage.total), (1, 2));
+        assert_eq!(coverage.percent, Some(50.0));
+        assert_eq!(coverage.files[0].uncovered_lines, vec![2]);
+        assert_eq!(coverage.files_without_coverage, vec!["README.md"]);
+
+        let cobertura = r#"<coverage><sources><source>/repo</source></sources><packages><package><classes>
+<class name="lib" filename="src/lib.rs"><lines>
+<line number="2" hits="3"/>
+</lines></class></classes></package></packages></coverage>"#;
+        assert_eq!(parse_cobertura(cobertura, "/repo")["src/lib.rs"][&2], 3);
+    }
+}
@@ -0,0 +1,220 @@
+//! Project overview
+//!
+//! Aggregates what the sidebar otherwise fetches per worktree (cached git and
+//! PR status, CI checks, running sessions and the latest session activity)
+//! into one payload, so the frontend can render a project overview with a
+//! single command. Only cached values and stored session metadata are read;
+//! no git or gh commands are run.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::storage::load_projects_data;
+use super::types::{SessionType, Worktree};
+use crate::background_tasks::ci_watcher::is_failing;
+
+/// Entries kept in the project's recent activity
+const MAX_RECENT_ACTIVITY: usize = 10;
+
+/// Cached status of one worktree
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeOverview {
+    pub id: String,
+    pub name: String,
+    pub branch: String,
+    pub session_type: SessionType,
+    pub pr_number: Option<u32>,
+    pub pr_url: Option<String>,
+    /// draft, open, review, merged or closed
+    pub pr_status: Option<String>,
+    /// success, failure, pending or error
+    pub check_status: Option<String>,
+    pub ahead_count: Option<u32>,
+    pub behind_count: Option<u32>,
+    pub unpushed_count: Option<u32>,
+    pub uncommitted_added: Option<u32>,
+    pub uncommitted_removed: Option<u32>,
+    pub branch_diff_added: Option<u32>,
+    pub branch_diff_removed: Option<u32>,
+    /// When the cached status was last refreshed
+    pub status_at: Option<u64>,
+    /// Names of the worktree's sessions that are running
+    pub running_sessions: Vec<String>,
+    /// Latest session run (or session creation), as a unix timestamp
+    pub last_activity_at: Option<u64>,
+}
+
+/// A session's latest activity
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityEntry {
+    pub worktree_id: String,
+    pub worktree_name: String,
+    pub session_id: String,
+    pub session_name: String,
+    pub at: u64,
+}
+
+/// Overview of a project's active worktrees
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectDashboard {
+    pub project_id: String,
+    pub project_name: String,
+    pub worktrees: Vec<WorktreeOverview>,
+    pub open_prs: usize,
+    /// Open PRs whose checks are failing
+    pub failing_checks: usize,
+    pub running_sessions: usize,
+    /// Most recent session activity across the project, newest first
+    pub recent_activity: Vec<ActivityEntry>,
+}
+
+fn is_open_pr(status: Option<&str>) -> bool {
+    matches!(status, Some("draft") | Some("open") | Some("review"))
+}
+
+fn overview(worktree: &Worktree) -> WorktreeOverview {
+    WorktreeOverview {
+        id: worktree.id.clone(),
+        name: worktree.name.clone(),
+        branch: worktree.branch.clone(),
+        session_type: worktree.session_type.clone(),
+        pr_number: worktree.pr_number,
+        pr_url: worktree.pr_url.clone(),
+        pr_status: worktree.cached_pr_status.clone(),
+        check_status: worktree.cached_check_status.clone(),
+        ahead_count: worktree.cached_ahead_count,
+        behind_count: worktree.cached_behind_count,
+        unpushed_count: worktree.cached_unpushed_count,
+        uncommitted_added: worktree.cached_uncommitted_added,
+        uncommitted_removed: worktree.cached_uncommitted_removed,
+        branch_diff_added: worktree.cached_branch_diff_added,
+        branch_diff_removed: worktree.cached_branch_diff_removed,
+        status_at: worktree.cached_status_at,
+        running_sessions: Vec::new(),
+        last_activity_at: None,
+    }
+}
+
+/// Totals over a project's worktrees: (open PRs, open PRs with failing checks,
+/// running sessions)
+fn totals(worktrees: &[WorktreeOverview]) -> (usize, usize, usize) {
+    let open_prs = worktrees
+        .iter()
+        .filter(|w| w.pr_number.is_some() && is_open_pr(w.pr_status.as_deref()))
+        .count();
+    // Checks of merged or closed PRs no longer need attention
+    let failing_checks = worktrees
+        .iter()
+        .filter(|w| is_open_pr(w.pr_status.as_deref()) && is_failing(w.check_status.as_deref()))
+        .count();
+    let running_sessions = worktrees.iter().map(|w| w.running_sessions.len()).sum();
+    (open_prs, failing_checks, running_sessions)
+}
+
+/// Session activity of a worktree, filling in its running sessions
+fn session_activity(
+    app: &AppHandle,
+    worktree: &Worktree,
+    overview: &mut WorktreeOverview,
+    running: &[String],
+) -> Vec<ActivityEntry> {
+    let Ok(index) = crate::chat::storage::load_index(app, &worktree.id) else {
+        return Vec::new();
+    };
+    let mut activity = Vec::new();
+    for entry in index.sessions.iter().filter(|s| s.archived_at.is_none()) {
+        if running.contains(&entry.id) {
+            overview.running_sessions.push(entry.name.clone());
+        }
+        let Ok(Some(metadata)) = crate::chat::storage::load_metadata(app, &entry.id) else {
+            continue;
+        };
+        let at = metadata
+            .runs
+            .iter()
+            .map(|run| run.ended_at.unwrap_or(run.started_at))
+            .max()
+            .unwrap_or(metadata.created_at);
+        overview.last_activity_at = overview.last_activity_at.max(Some(at));
+        activity.push(ActivityEntry {
+            worktree_id: worktree.id.clone(),
+            worktree_name: worktree.name.clone(),
+            session_id: entry.id.clone(),
+            session_name: entry.name.clone(),
+            at,
+        });
+    }
+    activity
+}
+
+/// Cached status, PRs, checks, running sessions and recent activity of a
+/// project's active worktrees
+#[tauri::command]
+pub async fn get_project_dashboard(
+    app: AppHandle,
+    project_id: String,
+) -> Result<ProjectDashboard, String> {
+    log::trace!("Building dashboard for project {project_id}");
+    let data = load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let running = crate::chat::registry::get_running_sessions();
+
+    let mut worktrees = Vec::new();
+    let mut recent_activity = Vec::new();
+    for worktree in data.worktrees_for_project(&project_id) {
+        if worktree.archived_at.is_some() {
+            continue;
+        }
+        let mut overview = overview(worktree);
+        recent_activity.extend(session_activity(&app, worktree, &mut overview, &running));
+        worktrees.push(overview);
+    }
+    recent_activity.sort_by(|a, b| b.at.cmp(&a.at));
+    recent_activity.truncate(MAX_RECENT_ACTIVITY);
+
+    let (open_prs, failing_checks, running_sessions) = totals(&worktrees);
+    Ok(ProjectDashboard {
+        project_id,
+        project_name: project.name.clone(),
+        worktrees,
+        open_prs,
+        failing_checks,
+        running_sessions,
+        recent_activity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals() {
+        let worktree = |pr_status: Option<&str>, check_status: Option<&str>| {
+            let mut worktree: Worktree = serde_json::from_value(serde_json::json!({
+                "id": "w1",
+                "project_id": "p1",
+                "name": "login",
+                "path": "/tmp/login",
+                "branch": "feat/login",
+                "created_at": 0,
+            }))
+            .unwrap();
+            worktree.pr_number = pr_status.map(|_| 1);
+            worktree.cached_pr_status = pr_status.map(str::to_string);
+            worktree.cached_check_status = check_status.map(str::to_string);
+            overview(&worktree)
+        };
+        let mut running = worktree(Some("open"), Some("failure"));
+        running.running_sessions = vec!["Session 1".to_string()];
+        let worktrees = vec![
+            running,
+            worktree(Some("merged"), Some("error")),
+            worktree(Some("draft"), Some("success")),
+            worktree(None, None),
+        ];
+        assert_eq!(totals(&worktrees), (2, 1, 1));
+    }
+}
@@ -0,0 +1,208 @@
+//! direnv / Nix environment loading
+//!
+//! Worktrees with an `.envrc` (direnv) or `flake.nix` (Nix dev shell) get that
+//! environment injected into terminals, setup scripts and Claude CLI sessions,
+//! so the tool versions they use match the project's.
+//!
+//! direnv only exports for `.envrc` files the user allowed (`direnv allow`);
+//! Jean never allows them itself. Results are cached until `.envrc`,
+//! `flake.nix` or `flake.lock` change, since `nix print-dev-env` can take a
+//! while. Disabled with the `load_dev_environment` preference.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Files whose changes invalidate a cached environment
+const WATCHED_FILES: [&str; 3] = [".envrc", "flake.nix", "flake.lock"];
+
+/// Variables from a Nix dev shell that describe the build sandbox rather than
+/// the project, and would break the user's session
+const NIX_SKIPPED_VARS: &[&str] = &[
+    "HOME",
+    "PWD",
+    "OLDPWD",
+    "SHELL",
+    "SHLVL",
+    "TERM",
+    "TMP",
+    "TMPDIR",
+    "TEMP",
+    "TEMPDIR",
+    "NIX_BUILD_TOP",
+    "NIX_LOG_FD",
+    "builder",
+    "out",
+    "outputs",
+    "name",
+    "system",
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Cached environments: worktree path -> (source mtime, variables)
+static CACHE: Lazy<Mutex<HashMap<String, (Option<SystemTime>, Vec<(String, String)>)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Apply the `load_dev_environment` preference
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Newest modification time of the watched files (None if none exist)
+fn source_mtime(path: &Path) -> Option<SystemTime> {
+    WATCHED_FILES
+        .iter()
+        .filter_map(|f| std::fs::metadata(path.join(f)).ok()?.modified().ok())
+        .max()
+}
+
+/// `direnv export json`: changed variables, `null` for unset ones
+fn load_direnv(path: &str) -> Result<Vec<(String, String)>, String> {
+    let output = silent_command("direnv")
+        .args(["export", "json"])
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run direnv: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("direnv export failed: {}", stderr.trim()));
+    }
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        // Blocked (not allowed) or nothing to export; direnv explains on stderr
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("is blocked") {
+            log::info!("direnv: .envrc in {path} is not allowed, skipping (run `direnv allow`)");
+        }
+        return Ok(Vec::new());
+    }
+    let vars: HashMap<String, Option<String>> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse direnv output: {e}"))?;
+    Ok(vars
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with("DIRENV_"))
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct NixDevEnv {
+    #[serde(default)]
+    variables: HashMap<String, NixVariable>,
+}
+
+#[derive(Deserialize)]
+struct NixVariable {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    value: serde_json::Value,
+}
+
+/// Exported string variables of a `nix print-dev-env --json` result, with
+/// the dev shell's PATH put in front of the current one
+fn parse_nix_env(json: &[u8], current_path: &str) -> Result<Vec<(String, String)>, String> {
+    let env: NixDevEnv = serde_json::from_slice(json)
+        .map_err(|e| format!("Failed to parse nix print-dev-env output: {e}"))?;
+    let mut vars: Vec<(String, String)> = env
+        .variables
+        .into_iter()
+        .filter(|(key, var)| var.kind == "exported" && !NIX_SKIPPED_VARS.contains(&key.as_str()))
+        .filter_map(|(key, var)| Some((key, var.value.as_str()?.to_string())))
+        .map(|(key, value)| {
+            if key == "PATH" && !current_path.is_empty() {
+                let joined = format!("{value}:{current_path}");
+                (key, joined)
+            } else {
+                (key, value)
+            }
+        })
+        .collect();
+    vars.sort();
+    Ok(vars)
+}
+
+fn load_nix(path: &str) -> Result<Vec<(String, String)>, String> {
+    let output = silent_command("nix")
+        .args(["print-dev-env", "--json"])
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run nix: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("nix print-dev-env failed: {}", stderr.trim()));
+    }
+    let current_path = std::env::var("PATH").unwrap_or_default();
+    parse_nix_env(&output.stdout, &current_path)
+}
+
+/// Environment variables to add for commands run in `path`. Empty when the
+/// path has no `.envrc` / `flake.nix`, loading is disabled, or loading fails
+/// (failures are logged).
+pub fn env_for(path: &str) -> Vec<(String, String)> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    let dir = Path::new(path);
+    // direnv takes precedence: an .envrc usually already does `use flake`
+    let loader: fn(&str) -> Result<Vec<(String, String)>, String> = if dir.join(".envrc").is_file()
+    {
+        load_direnv
+    } else if dir.join("flake.nix").is_file() {
+        load_nix
+    } else {
+        return Vec::new();
+    };
+
+    let mtime = source_mtime(dir);
+    if let Some((cached_mtime, vars)) = CACHE.lock().unwrap().get(path) {
+        if *cached_mtime == mtime {
+            return vars.clone();
+        }
+    }
+
+    log::trace!("Loading project environment for {path}");
+    let vars = loader(path).unwrap_or_else(|e| {
+        log::warn!("Failed to load project environment for {path}: {e}");
+        Vec::new()
+    });
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), (mtime, vars.clone()));
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nix_env() {
+        let json = br#"{"variables": {
+            "PATH": {"type": "exported", "value": "/nix/store/abc-node/bin"},
+            "NODE_ENV": {"type": "exported", "value": "development"},
+            "HOME": {"type": "exported", "value": "/homeless-shelter"},
+            "buildInputs": {"type": "var", "value": "/nix/store/abc-node"},
+            "stdenv_arr": {"type": "array", "value": ["a"]}
+        }}"#;
+        assert_eq!(
+            parse_nix_env(json, "/usr/bin").unwrap(),
+            vec![
+                ("NODE_ENV".to_string(), "development".to_string()),
+                (
+                    "PATH".to_string(),
+                    "/nix/store/abc-node/bin:/usr/bin".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Devcontainer-backed worktrees
+//!
+//! Projects with a devcontainer config (`.devcontainer/devcontainer.json`,
+//! `.devcontainer.json` or `.devcontainer/<name>/devcontainer.json`) can opt in
+//! (`use_devcontainer` on the project) to run setup scripts, terminals and
+//! Claude sessions inside the worktree's container instead of on the host.
+//!
+//! Commands go through the devcontainer CLI when it is installed
+//! (`devcontainer up` once per worktree and app run, then `devcontainer exec`).
+//! Without it, an already running container is used via `docker exec`, found
+//! by the `devcontainer.local_folder` label that VS Code and the CLI set.
+//!
+//! Host paths outside the workspace (app data, MCP configs, the main
+//! repository's `.git` directory for worktrees) are only visible inside the
+//! container if the devcontainer config mounts them.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::storage::load_projects_data;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Workspaces brought up with `devcontainer up` during this app run
+static UP: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Devcontainer detection result for a path
+#[derive(Debug, Clone, Serialize)]
+pub struct DevcontainerInfo {
+    /// Config file found in the path
+    pub config_path: Option<String>,
+    /// Whether the devcontainer CLI is installed
+    pub cli_available: bool,
+    /// ID of a running container for the path, if any
+    pub running_container: Option<String>,
+}
+
+/// How commands reach a workspace's container
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exec {
+    /// `devcontainer exec --workspace-folder <workspace>`
+    Cli { workspace: String },
+    /// `docker exec -w <workdir> <container_id>`
+    Docker {
+        container_id: String,
+        workdir: String,
+    },
+}
+
+impl Exec {
+    /// Program and arguments that run `argv` in the container with `env` set.
+    /// `tty` allocates a terminal (docker only; the CLI detects it).
+    pub fn command_line(
+        &self,
+        argv: &[String],
+        env: &[(&str, &str)],
+        tty: bool,
+    ) -> (String, Vec<String>) {
+        let mut args = Vec::new();
+        let program = match self {
+            Exec::Cli { workspace } => {
+                args.extend(["exec".to_string(), "--workspace-folder".to_string()]);
+                args.push(workspace.clone());
+                for (key, value) in env {
+                    args.push("--remote-env".to_string());
+                    args.push(format!("{key}={value}"));
+                }
+                "devcontainer"
+            }
+            Exec::Docker {
+                container_id,
+                workdir,
+            } => {
+                args.push("exec".to_string());
+                args.push(if tty { "-it" } else { "-i" }.to_string());
+                args.extend(["-w".to_string(), workdir.clone()]);
+                for (key, value) in env {
+                    args.push("-e".to_string());
+                    args.push(format!("{key}={value}"));
+                }
+                args.push(container_id.clone());
+                "docker"
+            }
+        };
+        args.extend(argv.iter().cloned());
+        (program.to_string(), args)
+    }
+
+    /// A `Command` running `argv` in the container
+    pub fn command(&self, argv: &[String], env: &[(&str, &str)]) -> Command {
+        let (program, args) = self.command_line(argv, env, false);
+        let mut cmd = silent_command(program);
+        cmd.args(args);
+        cmd
+    }
+}
+
+/// The devcontainer config of a checkout, if it has one
+pub fn find_config(path: &str) -> Option<PathBuf> {
+    let root = Path::new(path);
+    let candidates = [
+        root.join(".devcontainer").join("devcontainer.json"),
+        root.join(".devcontainer.json"),
+    ];
+    if let Some(found) = candidates.into_iter().find(|p| p.is_file()) {
+        return Some(found);
+    }
+
+    // Named configurations: .devcontainer/<name>/devcontainer.json (first by name)
+    let mut named: Vec<PathBuf> = std::fs::read_dir(root.join(".devcontainer"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("devcontainer.json"))
+        .filter(|p| p.is_file())
+        .collect();
+    named.sort();
+    named.into_iter().next()
+}
+
+fn cli_available() -> bool {
+    which::which("devcontainer").is_ok()
+}
+
+/// ID of a running container created for `path`
+fn running_container(path: &str) -> Option<String> {
+    let output = silent_command("docker")
+        .args([
+            "ps",
+            "-q",
+            "--filter",
+            &format!("label=devcontainer.local_folder={path}"),
+        ])
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// Where `path` is mounted inside the container
+fn container_workdir(container_id: &str, path: &str) -> String {
+    let template = format!(
+        "{{{{range .Mounts}}}}{{{{if eq .Source \"{}\"}}}}{{{{.Destination}}}}{{{{end}}}}{{{{end}}}}",
+        path.replace('"', "")
+    );
+    silent_command("docker")
+        .args(["inspect", "--format", &template, container_id])
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| {
+            // Default devcontainer convention
+            let name = Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            format!("/workspaces/{name}")
+        })
+}
+
+/// Detect the devcontainer setup of a path
+pub fn detect(path: &str) -> DevcontainerInfo {
+    DevcontainerInfo {
+        config_path: find_config(path).map(|p| p.to_string_lossy().to_string()),
+        cli_available: cli_available(),
+        running_container: running_container(path),
+    }
+}
+
+/// Whether commands for `path` (a worktree or project checkout) should run in
+/// its devcontainer: the owning project opted in and the checkout has a config
+fn enabled_for_path(app: &AppHandle, path: &str) -> bool {
+    let Ok(data) = load_projects_data(app) else {
+        return false;
+    };
+    let project_id = match data.worktrees.iter().find(|w| w.path == path) {
+        Some(worktree) => worktree.project_id.clone(),
+        None => match data.projects.iter().find(|p| p.path == path) {
+            Some(project) => project.id.clone(),
+            None => return false,
+        },
+    };
+    data.find_project(&project_id)
+        .is_some_and(|p| p.use_devcontainer)
+        && find_config(path).is_some()
+}
+
+/// Start (or reuse) the container of a workspace with the devcontainer CLI
+fn up(workspace: &str) -> Result<(), String> {
+    if UP.lock().unwrap().contains(workspace) {
+        return Ok(());
+    }
+    log::trace!("devcontainer up --workspace-folder {workspace}");
+    let output = silent_command("devcontainer")
+        .args(["up", "--workspace-folder", workspace])
+        .output_audited()
+        .map_err(|e| format!("Failed to run devcontainer up: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = stdout.lines().last().unwrap_or_default();
+        return Err(format!(
+            "Failed to start devcontainer: {} {}",
+            detail.trim(),
+            stderr.trim()
+        ));
+    }
+    UP.lock().unwrap().insert(workspace.to_string());
+    Ok(())
+}
+
+/// How to run commands for `path` inside its devcontainer, or `None` when
+/// they run on the host
+pub fn exec_for(app: &AppHandle, path: &str) -> Result<Option<Exec>, String> {
+    if !enabled_for_path(app, path) {
+        return Ok(None);
+    }
+    if cli_available() {
+        up(path)?;
+        return Ok(Some(Exec::Cli {
+            workspace: path.to_string(),
+        }));
+    }
+    let container_id = running_container(path).ok_or_else(|| {
+        format!(
+            "No running devcontainer for {path}. Start it (e.g. from VS Code) or install the devcontainer CLI."
+        )
+    })?;
+    let workdir = container_workdir(&container_id, path);
+    Ok(Some(Exec::Docker {
+        container_id,
+        workdir,
+    }))
+}
+
+/// Run a worktree's setup script, inside its devcontainer when enabled
+/// (see `git::run_setup_script` for the environment it gets, plus the
+/// worktree's env profile). On the host,
+/// missing mise/asdf runtimes are handled first.
+pub fn run_setup_script(
+    app: &AppHandle,
+    worktree_path: &str,
+    root_path: &str,
+    branch: &str,
+    script: &str,
+) -> Result<String, String> {
+    let profile_env = super::env_profiles::env_for(app, worktree_path);
+    let Some(exec) = exec_for(app, worktree_path)? else {
+        super::toolchain::prepare_for_setup(app, worktree_path);
+        return super::git::run_setup_script(
+            worktree_path,
+            root_path,
+            branch,
+            script,
+            &profile_env,
+        );
+    };
+    log::trace!("Running setup script in devcontainer for {worktree_path}: {script}");
+
+    // The container sees the workspace at its own path
+    let argv = [
+        "sh".to_string(),
+        "-lc".to_string(),
+        format!("export JEAN_WORKSPACE_PATH=\"$PWD\"; {script}"),
+    ];
+    let mut env: Vec<(&str, &str)> = profile_env
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    env.extend([("JEAN_ROOT_PATH", root_path), ("JEAN_BRANCH", branch)]);
+    let output = exec
+        .command(&argv, &env)
+        .output_audited()
+        .map_err(|e| format!("Failed to run setup script in devcontainer: {e}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        let combined = format!("{stdout}{stderr}").trim().to_string();
+        return Err(format!("Setup script failed in devcontainer: {combined}"));
+    }
+    Ok(format!("{stdout}{stderr}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_command_line() {
+        let exec = Exec::Docker {
+            container_id: "abc123".to_string(),
+            workdir: "/workspaces/repo".to_string(),
+        };
+        let (program, args) = exec.command_line(&["claude".to_string()], &[("A", "1")], false);
+        assert_eq!(program, "docker");
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "-i",
+                "-w",
+                "/workspaces/repo",
+                "-e",
+                "A=1",
+                "abc123",
+                "claude"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        assert!(find_config(&path).is_none());
+
+        let named = dir.path().join(".devcontainer").join("python");
+        std::fs::create_dir_all(&named).unwrap();
+        std::fs::write(named.join("devcontainer.json"), "{}").unwrap();
+        assert_eq!(find_config(&path), Some(named.join("devcontainer.json")));
+
+        std::fs::write(dir.path().join(".devcontainer.json"), "{}").unwrap();
+        assert_eq!(
+            find_config(&path),
+            Some(dir.path().join(".devcontainer.json"))
+        );
+    }
+}
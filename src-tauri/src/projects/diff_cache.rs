@@ -0,0 +1,380 @@
+//! Incremental diff computation
+//!
+//! The last `GitDiff` of each worktree is cached per file. An uncommitted diff
+//! lists changed paths with `git status` and only re-diffs the files whose
+//! status or on-disk size/mtime changed since the cached run, as long as HEAD
+//! hasn't moved. A branch diff is reused as-is while HEAD and the base ref
+//! point at the same commits.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use once_cell::sync::Lazy;
+
+use super::git_status::{
+    get_git_diff, get_untracked_file_diff, get_untracked_file_raw_patch, parse_diff,
+    split_patch_by_file, DiffFile, GitDiff,
+};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Number of worktree diffs kept in memory
+const MAX_CACHED_DIFFS: usize = 16;
+
+/// Paths passed to a single `git diff` invocation
+const PATHSPEC_CHUNK: usize = 500;
+
+/// A changed path reported by `git status`
+#[derive(Debug, Clone, PartialEq)]
+struct StatusEntry {
+    /// Two-letter status code ("M ", " M", "??", "R ", ...)
+    code: String,
+    path: String,
+    /// Source path of a rename or copy
+    orig_path: Option<String>,
+}
+
+impl StatusEntry {
+    fn is_untracked(&self) -> bool {
+        self.code == "??"
+    }
+}
+
+/// Diff of one status entry, with the fingerprint it was computed for
+#[derive(Clone)]
+struct CachedEntry {
+    fingerprint: String,
+    /// Files in the diff for this entry (none if the change nets out to nothing)
+    files: Vec<(DiffFile, String)>,
+}
+
+enum CachedDiff {
+    Uncommitted {
+        head: String,
+        entries: HashMap<String, CachedEntry>,
+    },
+    Branch {
+        head: String,
+        base: String,
+        diff: GitDiff,
+    },
+}
+
+struct CacheSlot {
+    diff: CachedDiff,
+    last_used: std::time::Instant,
+}
+
+/// Cached diffs keyed by (repo path, diff type, base branch)
+static CACHE: Lazy<Mutex<HashMap<(String, String, String), CacheSlot>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn rev_parse(repo_path: &str, rev: &str) -> Option<String> {
+    let output = silent_command("git")
+        .args(["rev-parse", "--verify", "--quiet", rev])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Parse `git status --porcelain=v1 -z` output
+fn parse_status(output: &str) -> Vec<StatusEntry> {
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0').filter(|f| !f.is_empty());
+    while let Some(field) = fields.next() {
+        if field.len() < 4 {
+            continue;
+        }
+        let code = field[..2].to_string();
+        let path = field[3..].to_string();
+        // Renames and copies are followed by their source path
+        let orig_path = if code.contains('R') || code.contains('C') {
+            fields.next().map(str::to_string)
+        } else {
+            None
+        };
+        entries.push(StatusEntry {
+            code,
+            path,
+            orig_path,
+        });
+    }
+    entries
+}
+
+fn status_entries(repo_path: &str) -> Result<Vec<StatusEntry>, String> {
+    let output = silent_command("git")
+        .args(["status", "--porcelain=v1", "-z", "--untracked-files=all"])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git status: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Git status failed: {stderr}"));
+    }
+    Ok(parse_status(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Status code plus size and mtime of the working copy: changes whenever the
+/// entry's diff against HEAD can have changed
+fn fingerprint(repo_path: &str, entry: &StatusEntry) -> String {
+    let stat =
+        |path: &str| match std::fs::symlink_metadata(std::path::Path::new(repo_path).join(path)) {
+            Ok(meta) => {
+                let mtime = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0);
+                format!("{}@{mtime}", meta.len())
+            }
+            Err(_) => "-".to_string(),
+        };
+    match &entry.orig_path {
+        Some(orig) => format!("{}:{}:{}", entry.code, stat(&entry.path), stat(orig)),
+        None => format!("{}:{}", entry.code, stat(&entry.path)),
+    }
+}
+
+/// Diff tracked paths against HEAD, returning each file with its raw patch
+fn diff_paths(repo_path: &str, paths: &[&str]) -> Result<Vec<(DiffFile, String)>, String> {
+    let mut files = Vec::new();
+    for chunk in paths.chunks(PATHSPEC_CHUNK) {
+        let output = silent_command("git")
+            .args(["-c", "core.quotePath=false", "--literal-pathspecs"])
+            .args(["diff", "HEAD", "--unified=3", "--"])
+            .args(chunk)
+            .current_dir(repo_path)
+            .output_audited()
+            .map_err(|e| format!("Failed to run git diff: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Git diff failed: {stderr}"));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for patch in split_patch_by_file(&stdout) {
+            if let Some(file) = parse_diff(patch).pop() {
+                files.push((file, patch.to_string()));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Whether a diffed file belongs to a status entry
+fn belongs_to(file: &DiffFile, entry: &StatusEntry) -> bool {
+    file.path == entry.path
+        || Some(&file.path) == entry.orig_path.as_ref()
+        || file.old_path.as_ref() == Some(&entry.path)
+}
+
+/// Build the uncommitted diff, reusing cached entries whose fingerprint matches.
+/// Returns None if some diffed file couldn't be matched back to its status
+/// entry (unusual paths), in which case the caller falls back to a full diff.
+fn uncommitted_diff(
+    repo_path: &str,
+    cached: Option<&HashMap<String, CachedEntry>>,
+) -> Result<Option<(GitDiff, HashMap<String, CachedEntry>)>, String> {
+    let status = status_entries(repo_path)?;
+
+    let mut entries: HashMap<String, CachedEntry> = HashMap::new();
+    let mut stale: Vec<(&StatusEntry, String)> = Vec::new();
+    for entry in &status {
+        let fingerprint = fingerprint(repo_path, entry);
+        match cached.and_then(|c| c.get(&entry.path)) {
+            Some(hit) if hit.fingerprint == fingerprint => {
+                entries.insert(entry.path.clone(), hit.clone());
+            }
+            _ => stale.push((entry, fingerprint)),
+        }
+    }
+    log::trace!(
+        "Diff cache for {repo_path}: {} reused, {} recomputed",
+        entries.len(),
+        stale.len()
+    );
+
+    let tracked_paths: Vec<&str> = stale
+        .iter()
+        .filter(|(e, _)| !e.is_untracked())
+        .flat_map(|(e, _)| std::iter::once(e.path.as_str()).chain(e.orig_path.as_deref()))
+        .collect();
+    let mut diffed = if tracked_paths.is_empty() {
+        Vec::new()
+    } else {
+        diff_paths(repo_path, &tracked_paths)?
+    };
+
+    for (entry, fingerprint) in stale {
+        let files = if entry.is_untracked() {
+            let file = get_untracked_file_diff(repo_path, &entry.path);
+            let raw = get_untracked_file_raw_patch(&file);
+            vec![(file, raw)]
+        } else {
+            let (mine, rest): (Vec<_>, Vec<_>) =
+                diffed.into_iter().partition(|(f, _)| belongs_to(f, entry));
+            diffed = rest;
+            mine
+        };
+        entries.insert(entry.path.clone(), CachedEntry { fingerprint, files });
+    }
+    if !diffed.is_empty() {
+        log::trace!(
+            "Diff cache: {} file(s) not matched to git status, using full diff",
+            diffed.len()
+        );
+        return Ok(None);
+    }
+
+    // Same order as a full diff: tracked files by path, then untracked files
+    let ordered: BTreeMap<(bool, &str), &CachedEntry> = status
+        .iter()
+        .filter_map(|e| {
+            entries
+                .get(&e.path)
+                .map(|c| ((e.is_untracked(), e.path.as_str()), c))
+        })
+        .collect();
+    let mut files = Vec::new();
+    let mut raw_patch = String::new();
+    for entry in ordered.values() {
+        for (file, raw) in &entry.files {
+            files.push(file.clone());
+            raw_patch.push_str(raw);
+        }
+    }
+
+    let diff = GitDiff {
+        diff_type: "uncommitted".to_string(),
+        base_ref: "HEAD".to_string(),
+        target_ref: "working directory".to_string(),
+        total_additions: files.iter().map(|f| f.additions).sum(),
+        total_deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+        raw_patch,
+    };
+    Ok(Some((diff, entries)))
+}
+
+fn store(key: (String, String, String), diff: CachedDiff) {
+    let mut cache = CACHE.lock().unwrap();
+    if cache.len() >= MAX_CACHED_DIFFS && !cache.contains_key(&key) {
+        if let Some(oldest) = cache
+            .iter()
+            .min_by_key(|(_, slot)| slot.last_used)
+            .map(|(k, _)| k.clone())
+        {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(
+        key,
+        CacheSlot {
+            diff,
+            last_used: std::time::Instant::now(),
+        },
+    );
+}
+
+/// `git_status::get_git_diff`, reusing the previous result for unchanged files
+pub fn get_git_diff_cached(
+    repo_path: &str,
+    diff_type: &str,
+    base_branch: Option<&str>,
+) -> Result<GitDiff, String> {
+    let base_branch_name = base_branch.unwrap_or("main");
+    let key = (
+        repo_path.to_string(),
+        diff_type.to_string(),
+        base_branch_name.to_string(),
+    );
+    let Some(head) = rev_parse(repo_path, "HEAD") else {
+        return get_git_diff(repo_path, diff_type, base_branch);
+    };
+
+    // Take the cached entry out while computing, so the lock isn't held
+    // across git invocations
+    let previous = CACHE.lock().unwrap().remove(&key).map(|slot| slot.diff);
+
+    match diff_type {
+        "uncommitted" => {
+            let cached = match &previous {
+                Some(CachedDiff::Uncommitted {
+                    head: cached_head,
+                    entries,
+                }) if *cached_head == head => Some(entries),
+                _ => None,
+            };
+            match uncommitted_diff(repo_path, cached)? {
+                Some((diff, entries)) => {
+                    store(key, CachedDiff::Uncommitted { head, entries });
+                    Ok(diff)
+                }
+                None => get_git_diff(repo_path, diff_type, base_branch),
+            }
+        }
+        "branch" => {
+            let base =
+                rev_parse(repo_path, &format!("origin/{base_branch_name}")).unwrap_or_default();
+            if let Some(CachedDiff::Branch {
+                head: cached_head,
+                base: cached_base,
+                diff,
+            }) = previous
+            {
+                if cached_head == head && cached_base == base {
+                    let result = diff.clone();
+                    store(key, CachedDiff::Branch { head, base, diff });
+                    return Ok(result);
+                }
+            }
+            let diff = get_git_diff(repo_path, diff_type, base_branch)?;
+            store(
+                key,
+                CachedDiff::Branch {
+                    head,
+                    base,
+                    diff: diff.clone(),
+                },
+            );
+            Ok(diff)
+        }
+        _ => Err(format!("Invalid diff_type: {diff_type}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = " M src/lib.rs\0R  new.rs\0old.rs\0?? notes/todo.md\0";
+        let entries = parse_status(output);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].code, " M");
+        assert_eq!(entries[0].path, "src/lib.rs");
+        assert_eq!(entries[1].path, "new.rs");
+        assert_eq!(entries[1].orig_path.as_deref(), Some("old.rs"));
+        assert!(entries[2].is_untracked());
+        assert_eq!(entries[2].path, "notes/todo.md");
+    }
+
+    #[test]
+    fn test_split_patch_by_file_round_trips() {
+        let patch = "diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-x\n+y\ndiff --git a/b.rs b/b.rs\n@@ -1 +1 @@\n-z\n+w\n";
+        let pieces = split_patch_by_file(patch);
+
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[1].starts_with("diff --git a/b.rs"));
+        assert_eq!(pieces.concat(), patch);
+        assert_eq!(parse_diff(pieces[0])[0].path, "a.rs");
+    }
+}
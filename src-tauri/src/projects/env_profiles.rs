@@ -0,0 +1,124 @@
+//! Environment variable profiles
+//!
+//! jean.json can define named sets of environment variables:
+//!
+//! ```json
+//! { "env": { "local": { "API_URL": "http://localhost:3000" },
+//!            "staging": { "API_URL": "https://staging.example.com" } } }
+//! ```
+//!
+//! Each worktree selects one of them (stored on the worktree); without a
+//! selection the profile named "default" is used, if there is one. The
+//! profile's variables are set in the worktree's terminals (and so in its run
+//! script) and in its setup script.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::git::read_jean_config;
+use super::storage::{load_projects_data, update_worktree};
+use super::types::{JeanConfig, Worktree};
+
+/// Profile used when a worktree has none selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Env profiles of a worktree
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvProfiles {
+    /// Profiles defined in the worktree's jean.json
+    pub profiles: Vec<String>,
+    /// Profile selected for the worktree
+    pub selected: Option<String>,
+    /// Profile in effect (the selected one, else "default" if defined)
+    pub active: Option<String>,
+}
+
+/// Name of the profile in effect for a selection
+fn active_profile(config: &JeanConfig, selected: Option<&str>) -> Option<String> {
+    let name = selected.unwrap_or(DEFAULT_PROFILE);
+    config.env.contains_key(name).then(|| name.to_string())
+}
+
+/// Variables of the profile in effect for a selection
+pub fn profile_vars(config: &JeanConfig, selected: Option<&str>) -> Vec<(String, String)> {
+    active_profile(config, selected)
+        .and_then(|name| config.env.get(&name))
+        .map(|vars| vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default()
+}
+
+/// Variables to set for commands run in a worktree, from its selected
+/// profile (empty without jean.json profiles)
+pub fn env_for(app: &AppHandle, worktree_path: &str) -> Vec<(String, String)> {
+    let Some(config) = read_jean_config(worktree_path) else {
+        return Vec::new();
+    };
+    if config.env.is_empty() {
+        return Vec::new();
+    }
+    let selected = load_projects_data(app).ok().and_then(|data| {
+        data.worktrees
+            .iter()
+            .find(|w| w.path == worktree_path)
+            .and_then(|w| w.env_profile.clone())
+    });
+    profile_vars(&config, selected.as_deref())
+}
+
+/// List the env profiles of a worktree's jean.json and the selected one
+#[tauri::command]
+pub async fn list_env_profiles(app: AppHandle, worktree_id: String) -> Result<EnvProfiles, String> {
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let config = read_jean_config(&worktree.path).unwrap_or_default();
+    Ok(EnvProfiles {
+        profiles: config.env.keys().cloned().collect(),
+        active: active_profile(&config, worktree.env_profile.as_deref()),
+        selected: worktree.env_profile.clone(),
+    })
+}
+
+/// Select the env profile of a worktree (None = back to "default"). Applies
+/// to terminals and scripts started afterwards.
+#[tauri::command]
+pub async fn set_worktree_env_profile(
+    app: AppHandle,
+    worktree_id: String,
+    profile: Option<String>,
+) -> Result<Worktree, String> {
+    let profile = profile.filter(|p| !p.trim().is_empty());
+    update_worktree(&app, &worktree_id, |worktree| {
+        if let Some(name) = &profile {
+            let config = read_jean_config(&worktree.path).unwrap_or_default();
+            if !config.env.contains_key(name) {
+                return Err(format!("Env profile not found in jean.json: {name}"));
+            }
+        }
+        log::trace!("Setting env profile of {} to {profile:?}", worktree.name);
+        worktree.env_profile = profile;
+        Ok(worktree.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_selection() {
+        let config: JeanConfig = serde_json::from_str(
+            r#"{"env": {"default": {"API_URL": "http://localhost"},
+                        "staging": {"API_URL": "https://staging", "DEBUG": "0"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            profile_vars(&config, None),
+            vec![("API_URL".to_string(), "http://localhost".to_string())]
+        );
+        assert_eq!(profile_vars(&config, Some("staging")).len(), 2);
+        assert!(profile_vars(&config, Some("missing")).is_empty());
+        assert!(profile_vars(&JeanConfig::default(), None).is_empty());
+    }
+}
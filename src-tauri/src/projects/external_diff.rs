@@ -0,0 +1,143 @@
+//! External diff and merge tools
+//!
+//! Opens a worktree's diff or a conflicted file in a desktop tool (kdiff3,
+//! meld, Beyond Compare, ...) through `git difftool` / `git mergetool`, so
+//! git's own tool definitions and the user's `diff.tool` / `merge.tool`
+//! config apply. The tool runs until the user closes it; Jean doesn't wait.
+
+use std::process::Command;
+
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Git tool name from the `diff_tool` preference (None = git config)
+fn tool_arg(tool: Option<&str>) -> Result<Option<String>, String> {
+    match tool.map(str::trim).filter(|t| !t.is_empty()) {
+        None => Ok(None),
+        Some(tool)
+            if tool
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Ok(Some(format!("--tool={tool}")))
+        }
+        Some(tool) => Err(format!("Invalid diff tool name: {tool}")),
+    }
+}
+
+/// `git difftool` arguments for a diff type (see `git_status::get_git_diff`),
+/// optionally limited to one file
+fn difftool_args(
+    diff_type: &str,
+    base_branch: Option<&str>,
+    file: Option<&str>,
+    tool: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let range = match diff_type {
+        "uncommitted" => "HEAD".to_string(),
+        "branch" => format!("origin/{}...HEAD", base_branch.unwrap_or("main")),
+        _ => return Err(format!("Invalid diff_type: {diff_type}")),
+    };
+    let mut args = vec![
+        "difftool".to_string(),
+        "--dir-diff".to_string(),
+        "--no-prompt".to_string(),
+    ];
+    args.extend(tool_arg(tool)?);
+    args.push(range);
+    if let Some(file) = file {
+        args.extend(["--".to_string(), file.to_string()]);
+    }
+    Ok(args)
+}
+
+/// Spawn a git tool command and reap it in the background
+fn spawn(mut cmd: Command, what: &str) -> Result<(), String> {
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start {what}: {e}"))?;
+    let what = what.to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => log::warn!("{what} exited with {status}"),
+        Err(e) => log::warn!("Failed to wait for {what}: {e}"),
+        _ => {}
+    });
+    Ok(())
+}
+
+/// Open the uncommitted or branch diff of a worktree in the external diff tool
+pub fn open_diff(
+    repo_path: &str,
+    diff_type: &str,
+    base_branch: Option<&str>,
+    file: Option<&str>,
+    tool: Option<&str>,
+) -> Result<(), String> {
+    let args = difftool_args(diff_type, base_branch, file, tool)?;
+    log::trace!(
+        "Opening external diff in {repo_path}: git {}",
+        args.join(" ")
+    );
+    let mut cmd = silent_command("git");
+    cmd.args(&args).current_dir(repo_path);
+    spawn(cmd, "git difftool")
+}
+
+/// Files with unresolved merge conflicts
+fn conflicted_files(repo_path: &str) -> Result<Vec<String>, String> {
+    let output = silent_command("git")
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to list conflicted files: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to list conflicted files: {}",
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Resolve a conflicted file in the external merge tool
+pub fn open_merge_tool(repo_path: &str, file: &str, tool: Option<&str>) -> Result<(), String> {
+    if !conflicted_files(repo_path)?.iter().any(|f| f == file) {
+        return Err(format!("{file} has no merge conflicts"));
+    }
+    log::trace!("Opening merge tool for {file} in {repo_path}");
+    let mut cmd = silent_command("git");
+    cmd.args(["mergetool", "--no-prompt"]);
+    cmd.args(tool_arg(tool)?);
+    cmd.args(["--", file]).current_dir(repo_path);
+    spawn(cmd, "git mergetool")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difftool_args() {
+        assert_eq!(
+            difftool_args("branch", Some("develop"), Some("src/lib.rs"), Some("meld")).unwrap(),
+            vec![
+                "difftool",
+                "--dir-diff",
+                "--no-prompt",
+                "--tool=meld",
+                "origin/develop...HEAD",
+                "--",
+                "src/lib.rs"
+            ]
+        );
+        assert_eq!(
+            difftool_args("uncommitted", None, None, None).unwrap(),
+            vec!["difftool", "--dir-diff", "--no-prompt", "HEAD"]
+        );
+        assert!(difftool_args("uncommitted", None, None, Some("meld; rm -rf")).is_err());
+    }
+}
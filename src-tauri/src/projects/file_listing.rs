@@ -0,0 +1,458 @@
+//! Paginated worktree file listing
+//!
+//! The @-file mention picker pages through a sorted snapshot of a worktree's
+//! files, using the last returned path as the cursor, so listings stay
+//! complete on very large repositories without sending everything at once.
+//!
+//! The same snapshot is the index behind fuzzy file search, so the picker
+//! doesn't re-walk the directory on every keystroke.
+//!
+//! The snapshot of the watched (active) worktree is built in the background as
+//! soon as it becomes active and kept up to date by the worktree watcher in
+//! `background_tasks`; every update is emitted as a `worktree-files:changed`
+//! event. Snapshots of other worktrees are rescanned when a listing starts
+//! over, or when a search finds them older than `UNWATCHED_INDEX_TTL`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::commands::WorktreeFile;
+use crate::http_server::EmitExt;
+
+/// Files per page when no limit is given
+pub const DEFAULT_PAGE_SIZE: usize = 1000;
+
+/// Largest page that can be requested
+pub const MAX_PAGE_SIZE: usize = 10_000;
+
+/// Search results returned when no limit is given
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// How long searches reuse the snapshot of a worktree that isn't watched
+const UNWATCHED_INDEX_TTL: Duration = Duration::from_secs(30);
+
+/// Sorted files of one worktree
+struct Snapshot {
+    /// Relative path -> extension
+    files: BTreeMap<String, String>,
+    /// Changes on every update, so the picker can tell its pages are stale
+    version: u64,
+    scanned_at: Instant,
+}
+
+#[derive(Default)]
+struct FileIndex {
+    /// Snapshots keyed by worktree path
+    snapshots: HashMap<String, Snapshot>,
+    /// Worktree kept up to date by the watcher
+    live: Option<String>,
+    /// Worktrees being scanned in the background
+    indexing: HashSet<String>,
+    next_version: u64,
+}
+
+impl FileIndex {
+    fn insert(&mut self, worktree_path: &str, files: BTreeMap<String, String>) {
+        self.next_version += 1;
+        self.snapshots.insert(
+            worktree_path.to_string(),
+            Snapshot {
+                files,
+                version: self.next_version,
+                scanned_at: Instant::now(),
+            },
+        );
+    }
+
+    fn is_live(&self, worktree_path: &str) -> bool {
+        self.live.as_deref() == Some(worktree_path)
+    }
+}
+
+static INDEX: Lazy<Mutex<FileIndex>> = Lazy::new(|| Mutex::new(FileIndex::default()));
+
+/// One page of a worktree listing
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeFilesPage {
+    pub files: Vec<WorktreeFile>,
+    /// Pass back to get the next page (None on the last page)
+    pub next_cursor: Option<String>,
+    /// Total number of files in the worktree
+    pub total: usize,
+    pub version: u64,
+}
+
+/// A fuzzy search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeFileMatch {
+    #[serde(flatten)]
+    pub file: WorktreeFile,
+    pub score: i64,
+    /// Character positions in `relative_path` matched by the query, for highlighting
+    pub positions: Vec<usize>,
+}
+
+/// Payload of the `worktree-files:changed` event
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeFilesChangedEvent {
+    pub worktree_path: String,
+    pub version: u64,
+    pub added: Vec<WorktreeFile>,
+    pub removed: Vec<String>,
+    /// Too many changes to list: the listing should be reloaded from the start
+    pub reset: bool,
+}
+
+/// Walker over a worktree's files: hidden files are included, `.git` is
+/// skipped and .gitignore rules (local, global and `.git/info/exclude`) apply
+pub fn worktree_walker(dir: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+/// Files under `dir` (only its direct children unless `recursive`), as paths
+/// relative to `root`, with the same ignore rules as `list_worktree_files`
+fn scan(root: &Path, dir: &Path, recursive: bool) -> BTreeMap<String, String> {
+    let walker = worktree_walker(dir)
+        .max_depth(if recursive { None } else { Some(1) })
+        .build();
+
+    walker
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let extension = relative
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("")
+                .to_string();
+            Some((relative.to_string_lossy().to_string(), extension))
+        })
+        .collect()
+}
+
+fn to_worktree_file((path, extension): (&String, &String)) -> WorktreeFile {
+    WorktreeFile {
+        relative_path: path.clone(),
+        extension: extension.clone(),
+    }
+}
+
+/// Page through the files of `files` after `cursor`
+fn page(
+    files: &BTreeMap<String, String>,
+    cursor: Option<&str>,
+    limit: usize,
+) -> (Vec<WorktreeFile>, Option<String>) {
+    let start = match cursor {
+        Some(cursor) => Bound::Excluded(cursor.to_string()),
+        None => Bound::Unbounded,
+    };
+    let mut range = files.range((start, Bound::Unbounded));
+    let page: Vec<WorktreeFile> = range.by_ref().take(limit).map(to_worktree_file).collect();
+    let next_cursor = match range.next() {
+        Some(_) => page.last().map(|f| f.relative_path.clone()),
+        None => None,
+    };
+    (page, next_cursor)
+}
+
+/// Run `f` on the snapshot of `worktree_path`, rescanning first unless
+/// `reuse` accepts the existing one
+fn with_snapshot<R>(
+    worktree_path: &str,
+    reuse: impl Fn(&Snapshot, bool) -> bool,
+    f: impl FnOnce(&Snapshot) -> R,
+) -> Result<R, String> {
+    let root = Path::new(worktree_path);
+    if !root.is_dir() {
+        return Err(format!("Worktree not found: {worktree_path}"));
+    }
+
+    let mut index = INDEX.lock().unwrap();
+    let live = index.is_live(worktree_path);
+    if !index
+        .snapshots
+        .get(worktree_path)
+        .is_some_and(|s| reuse(s, live))
+    {
+        // Don't hold the lock while walking a large tree
+        drop(index);
+        let files = scan(root, root, true);
+        log::trace!("Scanned {} files in {worktree_path}", files.len());
+        index = INDEX.lock().unwrap();
+        index.insert(worktree_path, files);
+    }
+
+    Ok(f(&index.snapshots[worktree_path]))
+}
+
+/// A page of the worktree's files, sorted by path.
+///
+/// Starting over (no cursor) rescans the worktree unless its snapshot is
+/// being kept up to date by the watcher.
+pub fn list_page(
+    worktree_path: &str,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<WorktreeFilesPage, String> {
+    with_snapshot(
+        worktree_path,
+        |_, live| live || cursor.is_some(),
+        |snapshot| {
+            let (files, next_cursor) = page(&snapshot.files, cursor, limit.clamp(1, MAX_PAGE_SIZE));
+            WorktreeFilesPage {
+                files,
+                next_cursor,
+                total: snapshot.files.len(),
+                version: snapshot.version,
+            }
+        },
+    )
+}
+
+/// Best fuzzy matches for `query` among `files`
+fn fuzzy_find(
+    files: &BTreeMap<String, String>,
+    query: &str,
+    limit: usize,
+) -> Vec<WorktreeFileMatch> {
+    let mut matches: Vec<WorktreeFileMatch> = files
+        .iter()
+        .filter_map(|entry| {
+            let (score, positions) = super::fuzzy::fuzzy_score(query, entry.0)?;
+            Some(WorktreeFileMatch {
+                file: to_worktree_file(entry),
+                score,
+                positions,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.file.relative_path.cmp(&b.file.relative_path))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// Fuzzy-search the worktree's file index
+pub fn search(
+    worktree_path: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<WorktreeFileMatch>, String> {
+    with_snapshot(
+        worktree_path,
+        |snapshot, live| live || snapshot.scanned_at.elapsed() < UNWATCHED_INDEX_TTL,
+        |snapshot| fuzzy_find(&snapshot.files, query, limit.max(1)),
+    )
+}
+
+/// Mark the worktree the watcher follows (if any), indexing it in the
+/// background when it becomes watched.
+///
+/// An existing snapshot may have missed changes while it wasn't watched, so
+/// it's rebuilt.
+pub fn set_live(worktree_path: Option<&str>) {
+    let mut index = INDEX.lock().unwrap();
+    if index.live.as_deref() == worktree_path {
+        return;
+    }
+    index.live = worktree_path.map(str::to_string);
+    let Some(path) = worktree_path else {
+        return;
+    };
+    if !index.indexing.insert(path.to_string()) {
+        return;
+    }
+    drop(index);
+
+    let path = path.to_string();
+    std::thread::spawn(move || {
+        let root = Path::new(&path);
+        let files = scan(root, root, true);
+        log::trace!("Indexed {} files in {path}", files.len());
+        let mut index = INDEX.lock().unwrap();
+        index.indexing.remove(&path);
+        index.insert(&path, files);
+    });
+}
+
+/// Update `files` for changed paths (relative to `root`), returning what was
+/// added and removed
+fn apply(
+    files: &mut BTreeMap<String, String>,
+    root: &Path,
+    changed: &[PathBuf],
+) -> (Vec<WorktreeFile>, Vec<String>) {
+    let mut removed = Vec::new();
+    let mut rescanned = BTreeMap::new();
+    let mut parent_scans: HashMap<PathBuf, BTreeMap<String, String>> = HashMap::new();
+    for relative in changed {
+        let full = root.join(relative);
+        let key = relative.to_string_lossy().to_string();
+
+        // Everything at or below the path is replaced by what's on disk now
+        let below: Vec<String> = files
+            .range(key.clone()..)
+            .map(|(path, _)| path)
+            .take_while(|path| path.starts_with(key.as_str()))
+            .filter(|path| {
+                let rest = &path[key.len()..];
+                rest.is_empty() || rest.starts_with(['/', '\\'])
+            })
+            .cloned()
+            .collect();
+        for path in below {
+            files.remove(&path);
+            removed.push(path);
+        }
+        if full.is_dir() {
+            rescanned.extend(scan(root, &full, true));
+        } else if full.is_file() {
+            // Look the file up in its parent's listing, so it's only added if
+            // it isn't ignored
+            let parent = full.parent().unwrap_or(root).to_path_buf();
+            let siblings = parent_scans
+                .entry(parent)
+                .or_insert_with_key(|parent| scan(root, parent, false));
+            if let Some(extension) = siblings.get(&key) {
+                rescanned.insert(key, extension.clone());
+            }
+        }
+    }
+
+    let mut added = Vec::new();
+    for (path, extension) in rescanned {
+        if let Some(i) = removed.iter().position(|p| *p == path) {
+            // Still there: neither added nor removed
+            removed.swap_remove(i);
+        } else {
+            added.push(to_worktree_file((&path, &extension)));
+        }
+        files.insert(path, extension);
+    }
+    removed.sort();
+    (added, removed)
+}
+
+/// Apply watcher-reported changes to the live snapshot of `worktree_path` and
+/// emit `worktree-files:changed`. `overflowed` means changes were dropped,
+/// so the worktree is rescanned instead.
+pub fn apply_changes(app: &AppHandle, worktree_path: &str, changed: &[PathBuf], overflowed: bool) {
+    let mut index = INDEX.lock().unwrap();
+    if !index.is_live(worktree_path) {
+        return;
+    }
+    index.next_version += 1;
+    let version = index.next_version;
+    let Some(snapshot) = index.snapshots.get_mut(worktree_path) else {
+        return;
+    };
+
+    let root = Path::new(worktree_path);
+    let event = if overflowed {
+        snapshot.files = scan(root, root, true);
+        snapshot.version = version;
+        WorktreeFilesChangedEvent {
+            worktree_path: worktree_path.to_string(),
+            version: snapshot.version,
+            added: Vec::new(),
+            removed: Vec::new(),
+            reset: true,
+        }
+    } else {
+        let (added, removed) = apply(&mut snapshot.files, root, changed);
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        snapshot.version = version;
+        WorktreeFilesChangedEvent {
+            worktree_path: worktree_path.to_string(),
+            version: snapshot.version,
+            added,
+            removed,
+            reset: false,
+        }
+    };
+    drop(index);
+
+    if let Err(e) = app.emit_all("worktree-files:changed", &event) {
+        log::error!("Failed to emit worktree-files:changed event: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> BTreeMap<String, String> {
+        paths
+            .iter()
+            .map(|p| (p.to_string(), String::new()))
+            .collect()
+    }
+
+    #[test]
+    fn test_page_cursor() {
+        let all = files(&["a", "b", "c", "d", "e"]);
+
+        let (first, cursor) = page(&all, None, 2);
+        assert_eq!(first.len(), 2);
+        assert_eq!(cursor.as_deref(), Some("b"));
+
+        let (rest, cursor) = page(&all, Some("b"), 10);
+        let paths: Vec<_> = rest.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["c", "d", "e"]);
+        assert_eq!(cursor, None);
+
+        // A cursor that was deleted meanwhile still resumes in order
+        let (rest, _) = page(&all, Some("bb"), 1);
+        assert_eq!(rest[0].relative_path, "c");
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/new")).unwrap();
+        std::fs::write(root.join("src/new/mod.rs"), "").unwrap();
+        std::fs::write(root.join("kept.rs"), "").unwrap();
+
+        let mut snapshot = files(&["kept.rs", "old/a.rs", "old/b.rs", "gone.rs"]);
+        let (added, removed) = apply(
+            &mut snapshot,
+            root,
+            &[
+                PathBuf::from("src/new"),
+                PathBuf::from("old"),
+                PathBuf::from("gone.rs"),
+                PathBuf::from("kept.rs"),
+            ],
+        );
+
+        let added: Vec<_> = added.iter().map(|f| f.relative_path.clone()).collect();
+        let expected = Path::new("src").join("new").join("mod.rs");
+        assert_eq!(added, vec![expected.to_string_lossy().to_string()]);
+        assert_eq!(removed, vec!["gone.rs", "old/a.rs", "old/b.rs"]);
+        assert!(snapshot.contains_key("kept.rs"));
+    }
+}
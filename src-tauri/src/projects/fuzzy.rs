@@ -0,0 +1,95 @@
+//! Fuzzy path matching for the @-mention picker
+//!
+//! A query matches a path when all of its characters appear in order
+//! (case-insensitively). Matches score higher when characters are consecutive,
+//! start a word or path segment, or fall in the file name.
+
+/// Whether `current` starts a word, given the character before it
+fn is_boundary(previous: char, current: char) -> bool {
+    matches!(previous, '/' | '\\' | '_' | '-' | '.' | ' ')
+        || (previous.is_lowercase() && current.is_uppercase())
+}
+
+fn chars_match(candidate: char, query: char) -> bool {
+    candidate == query || candidate.to_lowercase().eq(query.to_lowercase())
+}
+
+/// Greedily match `query` in `chars` starting at `from`
+fn match_from(
+    chars: &[char],
+    query: &[char],
+    from: usize,
+    name_start: usize,
+) -> Option<(i64, Vec<usize>)> {
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0i64;
+    let mut next = from;
+    for &q in query {
+        let found = (next..chars.len()).find(|&i| chars_match(chars[i], q))?;
+        let mut points = 1;
+        if found > 0 && positions.last() == Some(&(found - 1)) {
+            points += 8;
+        }
+        if found == 0 || is_boundary(chars[found - 1], chars[found]) {
+            points += 6;
+        }
+        if found >= name_start {
+            points += 3;
+        }
+        if chars[found] == q {
+            points += 1;
+        }
+        score += points;
+        positions.push(found);
+        next = found + 1;
+    }
+    Some((score, positions))
+}
+
+/// Score `path` against `query`, returning the score and the matched character
+/// positions, or None if it doesn't match. Whitespace in the query is ignored.
+pub fn fuzzy_score(query: &str, path: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let chars: Vec<char> = path.chars().collect();
+    let name_start = chars
+        .iter()
+        .rposition(|c| matches!(c, '/' | '\\'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    // Try the whole path, and the file name alone (greedy matching from the
+    // start can spend the query on directory names)
+    let whole = match_from(&chars, &query, 0, name_start)?;
+    let best = match match_from(&chars, &query, name_start, name_start) {
+        Some(in_name) if in_name.0 > whole.0 => in_name,
+        _ => whole,
+    };
+
+    // Prefer shorter paths among equal matches
+    Some((best.0 - chars.len() as i64 / 8, best.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        assert!(fuzzy_score("xyz", "src/main.rs").is_none());
+
+        let (_, positions) = fuzzy_score("main", "src/domain/main.rs").unwrap();
+        assert_eq!(positions, vec![11, 12, 13, 14]);
+
+        let exact = fuzzy_score("button", "src/components/Button.tsx")
+            .unwrap()
+            .0;
+        let scattered = fuzzy_score("button", "src/bundle/utils/toolbar/icon.ts")
+            .unwrap()
+            .0;
+        assert!(exact > scattered);
+
+        let camel = fuzzy_score("ub", "src/useButton.ts").unwrap().0;
+        let plain = fuzzy_score("ub", "src/pubsub.ts").unwrap().0;
+        assert!(camel > plain);
+    }
+}
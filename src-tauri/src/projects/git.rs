@@ -1,8 +1,11 @@
+use crate::command_audit::AuditedCommand;
 use crate::platform::silent_command;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
+use super::git_queue;
+use super::jj;
 use super::types::{JeanConfig, MergeType};
 
 /// Repository identifier extracted from GitHub remote URL
@@ -20,12 +23,21 @@ impl RepoIdentifier {
 }
 
 /// Extract repository owner and name from a git repository's GitHub remote
+/// (or workspace and repository slug from a Bitbucket Cloud remote)
 ///
 /// Returns an error if:
 /// - The repository has no origin remote
-/// - The remote URL is not a GitHub URL
+/// - The remote URL is neither a GitHub nor a Bitbucket URL
 pub fn get_repo_identifier(repo_path: &str) -> Result<RepoIdentifier, String> {
-    let github_url = get_github_url(repo_path)?;
+    let github_url = match get_github_url(repo_path) {
+        Ok(url) => url,
+        Err(e) => {
+            return get_origin_url(repo_path)
+                .ok()
+                .and_then(|url| super::bitbucket::parse_remote(&url))
+                .ok_or(e)
+        }
+    };
 
     // Parse owner/repo from URL: https://github.com/owner/repo
     let url_without_prefix = github_url
@@ -53,7 +65,7 @@ pub fn get_repo_identifier(repo_path: &str) -> Result<RepoIdentifier, String> {
 ///
 /// On Windows, PowerShell doesn't have a login mode concept.
 #[cfg(unix)]
-fn get_user_shell() -> (String, bool) {
+pub(crate) fn get_user_shell() -> (String, bool) {
     let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
 
     // Check if shell supports -l (login) flag
@@ -67,12 +79,27 @@ fn get_user_shell() -> (String, bool) {
 }
 
 #[cfg(windows)]
-fn get_user_shell() -> (String, bool) {
+pub(crate) fn get_user_shell() -> (String, bool) {
     // Windows PowerShell doesn't have a login mode concept
     ("powershell.exe".to_string(), false)
 }
 
-/// Check if a path is a valid git repository
+/// A command running `script` in the user's shell, as a login shell when it
+/// supports one (see `get_user_shell`)
+pub(crate) fn user_shell_command(script: &str) -> std::process::Command {
+    let (shell, supports_login) = get_user_shell();
+    log::trace!("Using shell: {shell} (login mode: {supports_login})");
+    let mut cmd = silent_command(&shell);
+    if supports_login {
+        cmd.args(["-l", "-c", script]);
+    } else {
+        cmd.args(["-c", script]);
+    }
+    cmd
+}
+
+/// Check if a path is a valid git repository (or a jj repository, whose
+/// workspaces have a `.jj` directory but no `.git`)
 pub fn validate_git_repo(path: &str) -> Result<bool, String> {
     let path = Path::new(path);
 
@@ -86,7 +113,7 @@ pub fn validate_git_repo(path: &str) -> Result<bool, String> {
 
     // Check for .git directory or file (could be a worktree)
     let git_path = path.join(".git");
-    Ok(git_path.exists())
+    Ok(git_path.exists() || jj::is_jj_repo(path))
 }
 
 /// Initialize a new git repository at the given path
@@ -108,7 +135,7 @@ pub fn init_repo(path: &str) -> Result<(), String> {
         let has_commits = silent_command("git")
             .args(["rev-parse", "HEAD"])
             .current_dir(path)
-            .output()
+            .output_audited()
             .map(|o| o.status.success())
             .unwrap_or(false);
 
@@ -122,7 +149,7 @@ pub fn init_repo(path: &str) -> Result<(), String> {
         let output = silent_command("git")
             .args(["init"])
             .current_dir(path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to run git init: {e}"))?;
 
         if !output.status.success() {
@@ -139,7 +166,7 @@ pub fn init_repo(path: &str) -> Result<(), String> {
     let add_output = silent_command("git")
         .args(["add", ".gitkeep"])
         .current_dir(path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git add: {e}"))?;
 
     if !add_output.status.success() {
@@ -151,7 +178,7 @@ pub fn init_repo(path: &str) -> Result<(), String> {
     let commit_output = silent_command("git")
         .args(["commit", "-m", "jean's init vibe commit"])
         .current_dir(path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git commit: {e}"))?;
 
     if !commit_output.status.success() {
@@ -178,14 +205,12 @@ pub fn get_repo_name(path: &str) -> Result<String, String> {
         })
 }
 
-/// Get the GitHub URL for a repository
-///
-/// Converts git remote URLs to HTTPS GitHub URLs
-pub fn get_github_url(repo_path: &str) -> Result<String, String> {
+/// URL of a repository's `origin` remote
+pub fn get_origin_url(repo_path: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["remote", "get-url", "origin"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get remote URL: {e}"))?;
 
     if !output.status.success() {
@@ -193,7 +218,14 @@ pub fn get_github_url(repo_path: &str) -> Result<String, String> {
         return Err(format!("Failed to get remote URL: {stderr}"));
     }
 
-    let remote_url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Get the GitHub URL for a repository
+///
+/// Converts git remote URLs to HTTPS GitHub URLs
+pub fn get_github_url(repo_path: &str) -> Result<String, String> {
+    let remote_url = get_origin_url(repo_path)?;
 
     // Convert SSH URL to HTTPS URL if needed
     // git@github.com:user/repo.git -> https://github.com/user/repo
@@ -219,7 +251,7 @@ pub fn get_current_branch(repo_path: &str) -> Result<String, String> {
     let output = silent_command("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git command: {e}"))?;
 
     if !output.status.success() {
@@ -240,7 +272,7 @@ pub fn branch_exists(repo_path: &str, branch_name: &str) -> bool {
             &format!("refs/heads/{branch_name}"),
         ])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
@@ -250,7 +282,7 @@ pub fn has_commits(repo_path: &str) -> bool {
     silent_command("git")
         .args(["rev-parse", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
@@ -303,7 +335,7 @@ pub fn rename_branch(repo_path: &str, new_name: &str) -> Result<String, String>
     let head_check = silent_command("git")
         .args(["symbolic-ref", "--short", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check HEAD state: {e}"))?;
 
     if !head_check.status.success() {
@@ -324,7 +356,7 @@ pub fn rename_branch(repo_path: &str, new_name: &str) -> Result<String, String>
     let branch_exists = silent_command("git")
         .args(["rev-parse", "--verify", &format!("refs/heads/{}", new_name)])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| o.status.success())
         .unwrap_or(false);
 
@@ -339,7 +371,7 @@ pub fn rename_branch(repo_path: &str, new_name: &str) -> Result<String, String>
     let output = silent_command("git")
         .args(["branch", "-m", &final_name])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to rename branch: {e}"))?;
 
     if !output.status.success() {
@@ -367,7 +399,7 @@ fn find_unique_branch_name(repo_path: &str, base_name: &str) -> Result<String, S
         let exists = silent_command("git")
             .args(["rev-parse", "--verify", &format!("refs/heads/{candidate}")])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
             .map(|o| o.status.success())
             .unwrap_or(false);
 
@@ -381,10 +413,14 @@ fn find_unique_branch_name(repo_path: &str, base_name: &str) -> Result<String, S
 
 /// Get list of local branches for a repository
 pub fn get_branches(repo_path: &str) -> Result<Vec<String>, String> {
+    if jj::is_jj_repo(Path::new(repo_path)) {
+        return jj::list_bookmarks(repo_path);
+    }
+
     let output = silent_command("git")
         .args(["branch", "--format=%(refname:short)"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git command: {e}"))?;
 
     if !output.status.success() {
@@ -401,34 +437,34 @@ pub fn get_branches(repo_path: &str) -> Result<Vec<String>, String> {
     Ok(branches)
 }
 
-/// Pull changes from remote origin for the specified base branch
-pub fn git_pull(repo_path: &str, base_branch: &str) -> Result<String, String> {
-    log::trace!("Pulling from origin/{base_branch} in {repo_path}");
+/// Pull changes from a remote for the specified base branch
+pub fn git_pull(repo_path: &str, base_branch: &str, remote: &str) -> Result<String, String> {
+    log::trace!("Pulling from {remote}/{base_branch} in {repo_path}");
 
     // Use explicit fetch + merge instead of `git pull` to avoid
     // "Cannot rebase onto multiple branches" when pull.rebase=true
     // is set in git config (common in worktree contexts)
     let fetch = silent_command("git")
-        .args(["fetch", "origin", base_branch])
+        .args(["fetch", remote, base_branch])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git fetch: {e}"))?;
 
     if !fetch.status.success() {
         let stderr = String::from_utf8_lossy(&fetch.stderr).to_string();
-        log::error!("Failed to fetch origin/{base_branch}: {stderr}");
+        log::error!("Failed to fetch {remote}/{base_branch}: {stderr}");
         return Err(stderr);
     }
 
     let merge = silent_command("git")
-        .args(["merge", &format!("origin/{base_branch}")])
+        .args(["merge", &format!("{remote}/{base_branch}")])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git merge: {e}"))?;
 
     if merge.status.success() {
         let stdout = String::from_utf8_lossy(&merge.stdout).to_string();
-        log::trace!("Successfully merged origin/{base_branch}");
+        log::trace!("Successfully merged {remote}/{base_branch}");
         Ok(stdout)
     } else {
         let stdout_str = String::from_utf8_lossy(&merge.stdout);
@@ -439,7 +475,7 @@ pub fn git_pull(repo_path: &str, base_branch: &str) -> Result<String, String> {
             let conflicts = silent_command("git")
                 .args(["diff", "--name-only", "--diff-filter=U"])
                 .current_dir(repo_path)
-                .output()
+                .output_audited()
                 .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
                 .unwrap_or_default();
 
@@ -456,19 +492,19 @@ pub fn git_pull(repo_path: &str, base_branch: &str) -> Result<String, String> {
         } else {
             stderr_str.trim().to_string()
         };
-        log::error!("Failed to merge origin/{base_branch}: {error}");
+        log::error!("Failed to merge {remote}/{base_branch}: {error}");
         Err(error)
     }
 }
 
-/// Push current branch to remote origin
-pub fn git_push(repo_path: &str) -> Result<String, String> {
-    log::trace!("Pushing to origin in {repo_path}");
+/// Push current branch to a remote (the branch's upstream if it has one)
+pub fn git_push(repo_path: &str, remote: &str) -> Result<String, String> {
+    log::trace!("Pushing to {remote} in {repo_path}");
 
     let output = silent_command("git")
         .args(["push"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git push: {e}"))?;
 
     if output.status.success() {
@@ -476,18 +512,18 @@ pub fn git_push(repo_path: &str) -> Result<String, String> {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         // Git push often outputs to stderr even on success
         let result = if stdout.is_empty() { stderr } else { stdout };
-        log::trace!("Successfully pushed to origin");
+        log::trace!("Successfully pushed to {remote}");
         Ok(result)
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         // Check if branch doesn't have upstream yet (same pattern as rebase_feature_branch)
         if stderr.contains("has no upstream branch") {
-            log::trace!("No upstream branch, retrying with -u origin HEAD");
+            log::trace!("No upstream branch, retrying with -u {remote} HEAD");
             let push_u_output = silent_command("git")
-                .args(["push", "-u", "origin", "HEAD"])
+                .args(["push", "-u", remote, "HEAD"])
                 .current_dir(repo_path)
-                .output()
+                .output_audited()
                 .map_err(|e| format!("Failed to run git push -u: {e}"))?;
 
             if push_u_output.status.success() {
@@ -503,7 +539,7 @@ pub fn git_push(repo_path: &str) -> Result<String, String> {
             }
         }
 
-        log::error!("Failed to push to origin: {stderr}");
+        log::error!("Failed to push to {remote}: {stderr}");
         Err(stderr)
     }
 }
@@ -513,12 +549,13 @@ pub fn git_push(repo_path: &str) -> Result<String, String> {
 ///
 /// Flow:
 /// 1. Query gh pr view for fork info
-/// 2. Same-repo PR: push to origin
+/// 2. Same-repo PR: push to the given remote
 /// 3. Fork PR: add fork remote if needed, fetch, push
 pub fn git_push_to_pr(
     repo_path: &str,
     pr_number: u32,
     gh_binary: &std::path::Path,
+    remote: &str,
 ) -> Result<String, String> {
     log::trace!("Pushing to PR #{pr_number} remote branch in {repo_path}");
 
@@ -532,13 +569,13 @@ pub fn git_push_to_pr(
             "headRefName,isCrossRepository,headRepositoryOwner,headRepository",
         ])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr view: {e}"))?;
 
     if !gh_output.status.success() {
         let stderr = String::from_utf8_lossy(&gh_output.stderr).to_string();
         log::warn!("gh pr view failed, falling back to regular push: {stderr}");
-        return git_push(repo_path);
+        return git_push(repo_path, remote);
     }
 
     let pr_info: serde_json::Value = serde_json::from_slice(&gh_output.stdout)
@@ -550,23 +587,23 @@ pub fn git_push_to_pr(
     let is_cross_repository = pr_info["isCrossRepository"].as_bool().unwrap_or(false);
 
     if !is_cross_repository {
-        // Same-repo PR: push to origin with --force-with-lease
-        log::trace!("Same-repo PR, pushing to origin/{head_ref_name}");
+        // Same-repo PR: push to the remote with --force-with-lease
+        log::trace!("Same-repo PR, pushing to {remote}/{head_ref_name}");
         let output = silent_command("git")
-            .args(["push", "--force-with-lease", "origin", head_ref_name])
+            .args(["push", "--force-with-lease", remote, head_ref_name])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to run git push: {e}"))?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             let result = if stdout.is_empty() { stderr } else { stdout };
-            log::trace!("Successfully pushed to origin/{head_ref_name}");
+            log::trace!("Successfully pushed to {remote}/{head_ref_name}");
             return Ok(result);
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            log::error!("Failed to push to origin/{head_ref_name}: {stderr}");
+            log::error!("Failed to push to {remote}/{head_ref_name}: {stderr}");
             return Err(stderr);
         }
     }
@@ -581,12 +618,12 @@ pub fn git_push_to_pr(
 
     log::trace!("Fork PR from {fork_owner}/{fork_repo_name}, branch {head_ref_name}");
 
-    // Determine URL scheme from origin
+    // Determine URL scheme from the remote
     let origin_url_output = silent_command("git")
-        .args(["remote", "get-url", "origin"])
+        .args(["remote", "get-url", remote])
         .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Failed to get origin URL: {e}"))?;
+        .output_audited()
+        .map_err(|e| format!("Failed to get {remote} URL: {e}"))?;
 
     let origin_url = String::from_utf8_lossy(&origin_url_output.stdout)
         .trim()
@@ -603,7 +640,7 @@ pub fn git_push_to_pr(
     let remotes_output = silent_command("git")
         .args(["remote", "-v"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to list remotes: {e}"))?;
 
     let remotes_str = String::from_utf8_lossy(&remotes_output.stdout);
@@ -620,7 +657,7 @@ pub fn git_push_to_pr(
             let add_output = silent_command("git")
                 .args(["remote", "add", fork_owner, &fork_url])
                 .current_dir(repo_path)
-                .output();
+                .output_audited();
 
             if let Err(e) = &add_output {
                 log::warn!("Failed to add fork remote: {e}");
@@ -639,7 +676,7 @@ pub fn git_push_to_pr(
     let fetch_output = silent_command("git")
         .args(["fetch", &remote_name, head_ref_name])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to fetch from fork: {e}"))?;
 
     if !fetch_output.status.success() {
@@ -652,7 +689,7 @@ pub fn git_push_to_pr(
     let push_output = silent_command("git")
         .args(["push", "--force-with-lease", &remote_name, head_ref_name])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to push to fork: {e}"))?;
 
     if push_output.status.success() {
@@ -671,11 +708,13 @@ pub fn git_push_to_pr(
 /// Fetch from remote origin (best effort, ignores errors if no remote)
 pub fn fetch_origin(repo_path: &str) -> Result<(), String> {
     log::trace!("Fetching from origin in {repo_path}");
+    let _queued = git_queue::lock(repo_path);
+    let _slot = git_queue::fetch_slot();
 
     let output = silent_command("git")
         .args(["fetch", "origin"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git fetch: {e}"))?;
 
     if !output.status.success() {
@@ -696,12 +735,69 @@ pub fn fetch_origin(repo_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Remote used when a project has no preferred remote
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// A configured git remote
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GitRemote {
+    pub name: String,
+    pub fetch_url: String,
+    pub push_url: String,
+}
+
+/// Parse `git remote -v` output (`name<TAB>url (fetch|push)` lines)
+fn parse_remotes(output: &str) -> Vec<GitRemote> {
+    let mut remotes: Vec<GitRemote> = Vec::new();
+    for line in output.lines() {
+        let Some((name, rest)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some((url, kind)) = rest.rsplit_once(' ') else {
+            continue;
+        };
+        let index = match remotes.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                remotes.push(GitRemote {
+                    name: name.to_string(),
+                    fetch_url: String::new(),
+                    push_url: String::new(),
+                });
+                remotes.len() - 1
+            }
+        };
+        match kind {
+            "(fetch)" => remotes[index].fetch_url = url.to_string(),
+            "(push)" => remotes[index].push_url = url.to_string(),
+            _ => {}
+        }
+    }
+    remotes
+}
+
+/// List the repository's remotes
+pub fn list_remotes(repo_path: &str) -> Result<Vec<GitRemote>, String> {
+    let output = silent_command("git")
+        .args(["remote", "-v"])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git remote: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to list remotes: {stderr}"));
+    }
+
+    Ok(parse_remotes(&String::from_utf8_lossy(&output.stdout)))
+}
+
 /// Get list of remote branches for a repository (strips origin/ prefix)
 pub fn get_remote_branches(repo_path: &str) -> Result<Vec<String>, String> {
     let output = silent_command("git")
         .args(["branch", "-r", "--format=%(refname:short)"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git command: {e}"))?;
 
     if !output.status.success() {
@@ -746,6 +842,10 @@ pub fn create_worktree(
     log::trace!(
         "Creating worktree at {worktree_path} with branch {new_branch_name} from {base_branch}"
     );
+    if jj::is_jj_repo(Path::new(repo_path)) {
+        return jj::add_workspace(repo_path, worktree_path, base_branch, Some(new_branch_name));
+    }
+    let _queued = git_queue::lock(repo_path);
 
     // Ensure parent directory exists
     let worktree_path_obj = Path::new(worktree_path);
@@ -765,7 +865,7 @@ pub fn create_worktree(
             base_branch,
         ])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git worktree add: {e}"))?;
 
     if !output.status.success() {
@@ -789,6 +889,10 @@ pub fn create_worktree_from_existing_branch(
     existing_branch: &str,
 ) -> Result<(), String> {
     log::trace!("Creating worktree at {worktree_path} using existing branch {existing_branch}");
+    if jj::is_jj_repo(Path::new(repo_path)) {
+        return jj::add_workspace(repo_path, worktree_path, existing_branch, None);
+    }
+    let _queued = git_queue::lock(repo_path);
 
     // Ensure parent directory exists
     let worktree_path_obj = Path::new(worktree_path);
@@ -801,7 +905,7 @@ pub fn create_worktree_from_existing_branch(
     let output = silent_command("git")
         .args(["worktree", "add", worktree_path, existing_branch])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git worktree add: {e}"))?;
 
     if !output.status.success() {
@@ -843,7 +947,7 @@ pub fn gh_pr_checkout(
     let output = silent_command(gh_binary)
         .args(&args)
         .current_dir(worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr checkout: {e}"))?;
 
     if !output.status.success() {
@@ -855,7 +959,7 @@ pub fn gh_pr_checkout(
     let branch_output = silent_command("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(worktree_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get branch name: {e}"))?;
 
     let branch_name = String::from_utf8_lossy(&branch_output.stdout)
@@ -873,13 +977,17 @@ pub fn gh_pr_checkout(
 /// * `worktree_path` - Path to the worktree to remove
 pub fn remove_worktree(repo_path: &str, worktree_path: &str) -> Result<(), String> {
     log::trace!("Removing worktree at {worktree_path}");
+    if jj::is_jj_repo(Path::new(repo_path)) {
+        return jj::forget_workspace(repo_path, worktree_path);
+    }
     log::trace!("git worktree remove {worktree_path} --force (in {repo_path})");
+    let _queued = git_queue::lock(repo_path);
 
     // git worktree remove <path>
     let output = silent_command("git")
         .args(["worktree", "remove", worktree_path, "--force"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git worktree remove: {e}"))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -905,7 +1013,7 @@ pub fn remove_worktree(repo_path: &str, worktree_path: &str) -> Result<(), Strin
             let _ = silent_command("git")
                 .args(["worktree", "prune"])
                 .current_dir(repo_path)
-                .output();
+                .output_audited();
         } else {
             return Err(format!("Failed to remove worktree: {stderr}"));
         }
@@ -915,6 +1023,72 @@ pub fn remove_worktree(repo_path: &str, worktree_path: &str) -> Result<(), Strin
     Ok(())
 }
 
+/// Move a git worktree to a new location
+///
+/// # Arguments
+/// * `repo_path` - Path to the main repository
+/// * `worktree_path` - Current path of the worktree
+/// * `new_path` - Destination path (must not exist)
+pub fn move_worktree(repo_path: &str, worktree_path: &str, new_path: &str) -> Result<(), String> {
+    log::trace!("git worktree move {worktree_path} {new_path} (in {repo_path})");
+    let _queued = git_queue::lock(repo_path);
+
+    if let Some(parent) = std::path::Path::new(new_path).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create worktree parent directory: {e}"))?;
+    }
+
+    let output = silent_command("git")
+        .args(["worktree", "move", worktree_path, new_path])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git worktree move: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to move worktree: {}", stderr.trim()));
+    }
+
+    log::trace!("Successfully moved worktree to {new_path}");
+    Ok(())
+}
+
+/// Prune administrative entries of worktrees whose directories are gone
+pub fn prune_worktrees(repo_path: &str) -> Result<(), String> {
+    log::trace!("git worktree prune (in {repo_path})");
+    let _queued = git_queue::lock(repo_path);
+
+    let output = silent_command("git")
+        .args(["worktree", "prune"])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git worktree prune: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to prune worktrees: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Reconnect a worktree directory that was moved back into place with its repository
+pub fn repair_worktree(repo_path: &str, worktree_path: &str) -> Result<(), String> {
+    log::trace!("git worktree repair {worktree_path} (in {repo_path})");
+    let _queued = git_queue::lock(repo_path);
+
+    let output = silent_command("git")
+        .args(["worktree", "repair", worktree_path])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git worktree repair: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to repair worktree: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
 /// Delete the branch associated with a worktree
 ///
 /// # Arguments
@@ -923,12 +1097,13 @@ pub fn remove_worktree(repo_path: &str, worktree_path: &str) -> Result<(), Strin
 pub fn delete_branch(repo_path: &str, branch_name: &str) -> Result<(), String> {
     log::trace!("Deleting branch {branch_name}");
     log::trace!("git branch -D {branch_name} (in {repo_path})");
+    let _queued = git_queue::lock(repo_path);
 
     // git branch -D <branch>
     let output = silent_command("git")
         .args(["branch", "-D", branch_name])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git branch -D: {e}"))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -958,7 +1133,7 @@ pub fn list_worktrees(repo_path: &str) -> Result<Vec<String>, String> {
     let output = silent_command("git")
         .args(["worktree", "list", "--porcelain"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git worktree list: {e}"))?;
 
     if !output.status.success() {
@@ -992,7 +1167,7 @@ pub fn commit_changes(repo_path: &str, message: &str, stage_all: bool) -> Result
         let add_output = silent_command("git")
             .args(["add", "-A"])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to run git add: {e}"))?;
 
         if !add_output.status.success() {
@@ -1005,7 +1180,7 @@ pub fn commit_changes(repo_path: &str, message: &str, stage_all: bool) -> Result
     let status_output = silent_command("git")
         .args(["status", "--porcelain"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check git status: {e}"))?;
 
     let status = String::from_utf8_lossy(&status_output.stdout);
@@ -1017,7 +1192,7 @@ pub fn commit_changes(repo_path: &str, message: &str, stage_all: bool) -> Result
     let diff_output = silent_command("git")
         .args(["diff", "--cached", "--quiet"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check staged changes: {e}"))?;
 
     // Exit code 0 means no staged changes, 1 means there are staged changes
@@ -1032,7 +1207,7 @@ pub fn commit_changes(repo_path: &str, message: &str, stage_all: bool) -> Result
     let commit_output = silent_command("git")
         .args(["commit", "-m", message])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git commit: {e}"))?;
 
     if !commit_output.status.success() {
@@ -1057,7 +1232,7 @@ pub fn commit_changes(repo_path: &str, message: &str, stage_all: bool) -> Result
     let hash_output = silent_command("git")
         .args(["rev-parse", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get commit hash: {e}"))?;
 
     let hash = String::from_utf8_lossy(&hash_output.stdout)
@@ -1103,13 +1278,14 @@ pub fn open_pull_request(
     body: Option<&str>,
     draft: bool,
     gh_binary: &std::path::Path,
+    remote: &str,
 ) -> Result<String, String> {
     log::trace!("Opening pull request from {repo_path}");
 
     // First check if gh is installed
     let gh_check = silent_command(gh_binary)
         .args(["--version"])
-        .output()
+        .output_audited()
         .map_err(|_| {
             format!(
                 "GitHub CLI (gh) is not installed. {}",
@@ -1128,7 +1304,7 @@ pub fn open_pull_request(
     let auth_check = silent_command(gh_binary)
         .args(["auth", "status"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to check gh auth status: {e}"))?;
 
     if !auth_check.status.success() {
@@ -1138,9 +1314,9 @@ pub fn open_pull_request(
     // Push current branch to remote first
     log::trace!("Pushing current branch to remote...");
     let push_output = silent_command("git")
-        .args(["push", "-u", "origin", "HEAD"])
+        .args(["push", "-u", remote, "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to push to remote: {e}"))?;
 
     if !push_output.status.success() {
@@ -1177,7 +1353,7 @@ pub fn open_pull_request(
     let output = silent_command(gh_binary)
         .args(&args)
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr create: {e}"))?;
 
     if !output.status.success() {
@@ -1217,7 +1393,7 @@ pub fn get_uncommitted_count(repo_path: &str) -> Result<u32, String> {
     let output = silent_command("git")
         .args(["status", "--porcelain"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to get git status: {e}"))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -1230,7 +1406,7 @@ pub fn has_upstream_branch(repo_path: &str) -> bool {
     silent_command("git")
         .args(["rev-parse", "--abbrev-ref", "@{upstream}"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
@@ -1287,31 +1463,27 @@ pub fn read_jean_config(worktree_path: &str) -> Option<JeanConfig> {
 /// - JEAN_WORKSPACE_PATH: Path to the newly created worktree
 /// - JEAN_ROOT_PATH: Path to the repository root directory
 /// - JEAN_BRANCH: Current branch name
+///
+/// `env` (the worktree's jean.json env profile) is set on top of the
+/// direnv / Nix environment.
 pub fn run_setup_script(
     worktree_path: &str,
     root_path: &str,
     branch: &str,
     script: &str,
+    env: &[(String, String)],
 ) -> Result<String, String> {
     log::trace!("Running setup script in {worktree_path}: {script}");
 
     // Use user's shell with login mode for proper PATH
-    let (shell, supports_login) = get_user_shell();
-    log::trace!("Using shell: {shell} (login mode: {supports_login})");
-
-    let mut cmd = silent_command(&shell);
-    if supports_login {
-        cmd.args(["-l", "-c", script]);
-    } else {
-        cmd.args(["-c", script]);
-    }
-
-    let output = cmd
+    let output = user_shell_command(script)
         .current_dir(worktree_path)
+        .envs(super::dev_env::env_for(worktree_path))
+        .envs(env.iter().cloned())
         .env("JEAN_WORKSPACE_PATH", worktree_path)
         .env("JEAN_ROOT_PATH", root_path)
         .env("JEAN_BRANCH", branch)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run setup script: {e}"))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -1329,10 +1501,10 @@ pub fn run_setup_script(
 
 /// Check if there are uncommitted changes (staged or unstaged)
 pub fn has_uncommitted_changes(repo_path: &str) -> bool {
-    silent_command("git")
+    git_queue::read_command()
         .args(["status", "--porcelain"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| {
             if o.status.success() {
                 !String::from_utf8_lossy(&o.stdout).trim().is_empty()
@@ -1343,12 +1515,85 @@ pub fn has_uncommitted_changes(repo_path: &str) -> bool {
         .unwrap_or(false)
 }
 
-/// Rebase the current branch onto a base branch from origin
+/// Stash a worktree's uncommitted changes, untracked files included, under
+/// `label`. Returns the stash commit, or None when there was nothing to stash.
+pub fn stash_worktree_changes(worktree_path: &str, label: &str) -> Result<Option<String>, String> {
+    if !has_uncommitted_changes(worktree_path) {
+        return Ok(None);
+    }
+    log::trace!("git stash push --include-untracked -m {label} (in {worktree_path})");
+    let _queued = git_queue::lock(worktree_path);
+
+    let output = silent_command("git")
+        .args(["stash", "push", "--include-untracked", "-m", label])
+        .current_dir(worktree_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git stash push: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to stash changes: {stderr}"));
+    }
+
+    let output = silent_command("git")
+        .args(["rev-parse", "stash@{0}"])
+        .current_dir(worktree_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git rev-parse: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to read stash: {stderr}"));
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// The `stash@{n}` entry for a stash commit in `git stash list --format='%gd %H'`
+fn find_stash_ref(stash_list: &str, stash: &str) -> Option<String> {
+    stash_list.lines().find_map(|line| {
+        let (stash_ref, sha) = line.split_once(' ')?;
+        (sha.trim() == stash).then(|| stash_ref.to_string())
+    })
+}
+
+/// Re-apply changes set aside by `stash_worktree_changes` and drop the stash.
+///
+/// Stashes are shared by all worktrees of a repository, so the stash is
+/// looked up by its commit rather than its position.
+pub fn restore_stashed_changes(worktree_path: &str, stash: &str) -> Result<(), String> {
+    log::trace!("Restoring stash {stash} (in {worktree_path})");
+    let _queued = git_queue::lock(worktree_path);
+
+    let output = silent_command("git")
+        .args(["stash", "list", "--format=%gd %H"])
+        .current_dir(worktree_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git stash list: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to list stashes: {stderr}"));
+    }
+    let stash_ref = find_stash_ref(&String::from_utf8_lossy(&output.stdout), stash)
+        .ok_or_else(|| format!("Stash {stash} no longer exists"))?;
+
+    let output = silent_command("git")
+        .args(["stash", "pop", &stash_ref])
+        .current_dir(worktree_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git stash pop: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to restore stashed changes: {stderr}"));
+    }
+    Ok(())
+}
+
+/// Rebase the current branch onto a base branch from a remote
 ///
 /// This performs:
 /// 1. Commits any uncommitted changes with the provided message
-/// 2. Fetches from origin
-/// 3. Rebases onto origin/{base_branch}
+/// 2. Fetches from the remote
+/// 3. Rebases onto {remote}/{base_branch}
 /// 4. Force pushes with lease
 ///
 /// Returns an error message if any step fails
@@ -1356,6 +1601,7 @@ pub fn rebase_onto_base(
     repo_path: &str,
     base_branch: &str,
     commit_message: Option<&str>,
+    remote: &str,
 ) -> Result<String, String> {
     log::trace!("Starting rebase onto {base_branch} in {repo_path}");
 
@@ -1368,7 +1614,7 @@ pub fn rebase_onto_base(
         let add_output = silent_command("git")
             .args(["add", "-A"])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to stage changes: {e}"))?;
 
         if !add_output.status.success() {
@@ -1380,7 +1626,7 @@ pub fn rebase_onto_base(
         let commit_output = silent_command("git")
             .args(["commit", "-m", message])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
             .map_err(|e| format!("Failed to commit changes: {e}"))?;
 
         if !commit_output.status.success() {
@@ -1392,25 +1638,25 @@ pub fn rebase_onto_base(
         }
     }
 
-    // Step 2: Fetch from origin
-    log::trace!("Fetching from origin...");
+    // Step 2: Fetch from the remote
+    log::trace!("Fetching from {remote}...");
     let fetch_output = silent_command("git")
-        .args(["fetch", "origin", base_branch])
+        .args(["fetch", remote, base_branch])
         .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Failed to fetch from origin: {e}"))?;
+        .output_audited()
+        .map_err(|e| format!("Failed to fetch from {remote}: {e}"))?;
 
     if !fetch_output.status.success() {
         let stderr = String::from_utf8_lossy(&fetch_output.stderr);
-        return Err(format!("Failed to fetch from origin: {stderr}"));
+        return Err(format!("Failed to fetch from {remote}: {stderr}"));
     }
 
-    // Step 3: Rebase onto origin/{base_branch}
-    log::trace!("Rebasing onto origin/{base_branch}...");
+    // Step 3: Rebase onto {remote}/{base_branch}
+    log::trace!("Rebasing onto {remote}/{base_branch}...");
     let rebase_output = silent_command("git")
-        .args(["rebase", &format!("origin/{base_branch}")])
+        .args(["rebase", &format!("{remote}/{base_branch}")])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to rebase: {e}"))?;
 
     if !rebase_output.status.success() {
@@ -1419,7 +1665,7 @@ pub fn rebase_onto_base(
         let _ = silent_command("git")
             .args(["rebase", "--abort"])
             .current_dir(repo_path)
-            .output();
+            .output_audited();
         return Err(format!(
             "Rebase failed (conflicts likely). Rebase has been aborted.\n{stderr}"
         ));
@@ -1430,7 +1676,7 @@ pub fn rebase_onto_base(
     let push_output = silent_command("git")
         .args(["push", "--force-with-lease"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to push: {e}"))?;
 
     if !push_output.status.success() {
@@ -1439,9 +1685,9 @@ pub fn rebase_onto_base(
         if stderr.contains("has no upstream branch") {
             // Try regular push with -u
             let push_u_output = silent_command("git")
-                .args(["push", "-u", "origin", "HEAD"])
+                .args(["push", "-u", remote, "HEAD"])
                 .current_dir(repo_path)
-                .output()
+                .output_audited()
                 .map_err(|e| format!("Failed to push: {e}"))?;
 
             if !push_u_output.status.success() {
@@ -1457,6 +1703,215 @@ pub fn rebase_onto_base(
     Ok("Rebase completed successfully".to_string())
 }
 
+// =============================================================================
+// Interactive Rebase
+// =============================================================================
+
+/// A commit of the branch, as listed for an interactive rebase
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RebaseCommit {
+    pub sha: String,
+    pub short_sha: String,
+    pub subject: String,
+    pub author: String,
+    pub timestamp: u64,
+}
+
+/// What to do with a commit in an interactive rebase
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RebaseActionKind {
+    Pick,
+    /// Meld into the previous commit, keeping both messages
+    Squash,
+    /// Meld into the previous commit, dropping this message
+    Fixup,
+    Drop,
+    /// Keep the commit with a new message
+    Reword,
+}
+
+/// One line of the rebase todo list
+#[derive(Debug, Clone, Deserialize)]
+pub struct RebaseAction {
+    pub sha: String,
+    pub action: RebaseActionKind,
+    /// New message (reword only)
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Fork point of the branch: where it leaves `{remote}/{base_branch}` (or the
+/// local base branch when there's no remote-tracking one)
+fn rebase_fork_point(repo_path: &str, base_branch: &str, remote: &str) -> Result<String, String> {
+    for base in [format!("{remote}/{base_branch}"), base_branch.to_string()] {
+        let output = git_queue::read_command()
+            .args(["merge-base", "HEAD", &base])
+            .current_dir(repo_path)
+            .output_audited()
+            .map_err(|e| format!("Failed to find merge base: {e}"))?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+    }
+    Err(format!(
+        "Failed to find where the branch leaves {base_branch}"
+    ))
+}
+
+fn parse_rebase_commits(output: &str) -> Vec<RebaseCommit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(RebaseCommit {
+                sha: fields.next()?.to_string(),
+                short_sha: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                timestamp: fields.next()?.parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Commits the branch has on top of its base, oldest first
+pub fn list_rebase_commits(
+    repo_path: &str,
+    base_branch: &str,
+    remote: &str,
+) -> Result<Vec<RebaseCommit>, String> {
+    let fork_point = rebase_fork_point(repo_path, base_branch, remote)?;
+    let output = git_queue::read_command()
+        .args([
+            "log",
+            "--reverse",
+            "--no-merges",
+            "--format=%H%x1f%h%x1f%s%x1f%an%x1f%at",
+            &format!("{fork_point}..HEAD"),
+        ])
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to list commits: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to list commits: {stderr}"));
+    }
+    Ok(parse_rebase_commits(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Rebase todo list for `actions`, with the files holding reworded messages
+/// (`exec` lines amend the picked commit with them). Every commit must appear
+/// exactly once.
+fn rebase_todo(
+    commits: &[RebaseCommit],
+    actions: &[RebaseAction],
+    message_file: impl Fn(usize) -> String,
+) -> Result<(String, Vec<(String, String)>), String> {
+    if actions.len() != commits.len()
+        || !commits
+            .iter()
+            .all(|c| actions.iter().filter(|a| a.sha == c.sha).count() == 1)
+    {
+        return Err("The rebase plan must list every commit of the branch once".to_string());
+    }
+    let mut todo = String::new();
+    let mut messages = Vec::new();
+    let mut has_target = false;
+    for action in actions {
+        let line = match action.action {
+            RebaseActionKind::Pick | RebaseActionKind::Reword => "pick",
+            RebaseActionKind::Squash | RebaseActionKind::Fixup if !has_target => {
+                return Err(format!(
+                    "Commit {} has no earlier commit to be melded into",
+                    action.sha
+                ));
+            }
+            RebaseActionKind::Squash => "squash",
+            RebaseActionKind::Fixup => "fixup",
+            RebaseActionKind::Drop => "drop",
+        };
+        todo.push_str(&format!("{line} {}\n", action.sha));
+        has_target |= action.action != RebaseActionKind::Drop;
+        if action.action == RebaseActionKind::Reword {
+            let message = action
+                .message
+                .as_deref()
+                .map(str::trim)
+                .filter(|m| !m.is_empty())
+                .ok_or_else(|| format!("Commit {} needs a new message", action.sha))?;
+            let path = message_file(messages.len());
+            todo.push_str(&format!(
+                "exec git commit --amend --allow-empty -F '{path}'\n"
+            ));
+            messages.push((path, message.to_string()));
+        }
+    }
+    Ok((todo, messages))
+}
+
+/// Rewrite the branch's commits on top of the same fork point: reorder,
+/// squash, fixup, drop or reword them. Uncommitted changes are stashed for the
+/// duration. The rebase is aborted if it fails. Returns the new HEAD.
+pub fn interactive_rebase(
+    repo_path: &str,
+    base_branch: &str,
+    remote: &str,
+    actions: &[RebaseAction],
+) -> Result<String, String> {
+    let _queued = git_queue::lock(repo_path);
+    let commits = list_rebase_commits(repo_path, base_branch, remote)?;
+    let fork_point = rebase_fork_point(repo_path, base_branch, remote)?;
+
+    let dir = std::env::temp_dir().join(format!("jean-rebase-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to prepare rebase: {e}"))?;
+    // Forward slashes so the paths survive git's shell on Windows
+    let path_in = |name: String| dir.join(name).to_string_lossy().replace('\\', "/");
+    let result = (|| {
+        let (todo, messages) =
+            rebase_todo(&commits, actions, |i| path_in(format!("message-{i}.txt")))?;
+        let todo_path = path_in("todo".to_string());
+        std::fs::write(&todo_path, todo).map_err(|e| format!("Failed to prepare rebase: {e}"))?;
+        for (path, message) in &messages {
+            std::fs::write(path, message).map_err(|e| format!("Failed to prepare rebase: {e}"))?;
+        }
+
+        log::trace!(
+            "Interactive rebase of {} commit(s) in {repo_path}",
+            commits.len()
+        );
+        let output = silent_command("git")
+            .args(["rebase", "-i", "--autostash", &fork_point])
+            // The todo list is ours; squashes keep the combined message
+            .env("GIT_SEQUENCE_EDITOR", format!("cp '{todo_path}'"))
+            .env("GIT_EDITOR", "true")
+            .current_dir(repo_path)
+            .output_audited()
+            .map_err(|e| format!("Failed to rebase: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let _ = silent_command("git")
+                .args(["rebase", "--abort"])
+                .current_dir(repo_path)
+                .output_audited();
+            return Err(format!(
+                "Rebase failed (conflicts likely). Rebase has been aborted.\n{stderr}"
+            ));
+        }
+
+        let head = silent_command("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo_path)
+            .output_audited()
+            .map_err(|e| format!("Failed to read HEAD: {e}"))?;
+        Ok(String::from_utf8_lossy(&head.stdout).trim().to_string())
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
 // =============================================================================
 // Local Merge Operations
 // =============================================================================
@@ -1519,7 +1974,7 @@ pub fn merge_branch_to_base(
     let checkout_output = silent_command("git")
         .args(["checkout", base_branch])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     match checkout_output {
         Ok(output) if !output.status.success() => {
@@ -1541,7 +1996,7 @@ pub fn merge_branch_to_base(
     let pull_output = silent_command("git")
         .args(["pull", "origin", base_branch])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     if let Ok(output) = &pull_output {
         if !output.status.success() {
@@ -1588,13 +2043,13 @@ fn perform_merge(repo_path: &str, feature_branch: &str, squash: bool) -> MergeRe
         silent_command("git")
             .args(["merge", "--squash", feature_branch])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
     } else {
         // --no-ff creates a merge commit preserving history
         silent_command("git")
             .args(["merge", "--no-ff", feature_branch, "-m", &merge_message])
             .current_dir(repo_path)
-            .output()
+            .output_audited()
     };
 
     match merge_output {
@@ -1605,7 +2060,7 @@ fn perform_merge(repo_path: &str, feature_branch: &str, squash: bool) -> MergeRe
                     let commit_output = silent_command("git")
                         .args(["commit", "-m", &merge_message])
                         .current_dir(repo_path)
-                        .output();
+                        .output_audited();
 
                     match commit_output {
                         Ok(co) if !co.status.success() => {
@@ -1656,7 +2111,7 @@ fn rebase_and_merge(
     let rebase_output = silent_command("git")
         .args(["rebase", base_branch])
         .current_dir(worktree_path)
-        .output();
+        .output_audited();
 
     match rebase_output {
         Ok(output) => {
@@ -1674,7 +2129,7 @@ fn rebase_and_merge(
                     let conflict_output = silent_command("git")
                         .args(["diff", "--name-only", "--diff-filter=U"])
                         .current_dir(worktree_path)
-                        .output();
+                        .output_audited();
 
                     let conflicting_files: Vec<String> = conflict_output
                         .map(|o| {
@@ -1690,7 +2145,7 @@ fn rebase_and_merge(
                     let diff_output = silent_command("git")
                         .args(["diff"])
                         .current_dir(worktree_path)
-                        .output();
+                        .output_audited();
 
                     let conflict_diff = diff_output
                         .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
@@ -1704,7 +2159,7 @@ fn rebase_and_merge(
                     let _ = silent_command("git")
                         .args(["rebase", "--abort"])
                         .current_dir(worktree_path)
-                        .output();
+                        .output_audited();
 
                     return MergeResult::Conflict {
                         conflicting_files,
@@ -1715,7 +2170,7 @@ fn rebase_and_merge(
                     let _ = silent_command("git")
                         .args(["rebase", "--abort"])
                         .current_dir(worktree_path)
-                        .output();
+                        .output_audited();
 
                     let error_detail = if !stderr.trim().is_empty() {
                         stderr.trim().to_string()
@@ -1744,7 +2199,7 @@ fn rebase_and_merge(
     let ff_merge = silent_command("git")
         .args(["merge", "--ff-only", feature_branch])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     match ff_merge {
         Ok(output) => {
@@ -1768,7 +2223,7 @@ fn get_head_commit_hash(repo_path: &str) -> MergeResult {
     let hash_output = silent_command("git")
         .args(["rev-parse", "HEAD"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     let commit_hash = hash_output
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
@@ -1793,7 +2248,7 @@ fn handle_merge_failure(repo_path: &str, stdout: &[u8], stderr: &[u8]) -> MergeR
         let conflict_output = silent_command("git")
             .args(["diff", "--name-only", "--diff-filter=U"])
             .current_dir(repo_path)
-            .output();
+            .output_audited();
 
         let conflicting_files: Vec<String> = conflict_output
             .map(|o| {
@@ -1809,7 +2264,7 @@ fn handle_merge_failure(repo_path: &str, stdout: &[u8], stderr: &[u8]) -> MergeR
         let diff_output = silent_command("git")
             .args(["diff"])
             .current_dir(repo_path)
-            .output();
+            .output_audited();
 
         let conflict_diff = diff_output
             .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
@@ -1823,7 +2278,7 @@ fn handle_merge_failure(repo_path: &str, stdout: &[u8], stderr: &[u8]) -> MergeR
         let _ = silent_command("git")
             .args(["merge", "--abort"])
             .current_dir(repo_path)
-            .output();
+            .output_audited();
 
         MergeResult::Conflict {
             conflicting_files,
@@ -1834,7 +2289,7 @@ fn handle_merge_failure(repo_path: &str, stdout: &[u8], stderr: &[u8]) -> MergeR
         let _ = silent_command("git")
             .args(["merge", "--abort"])
             .current_dir(repo_path)
-            .output();
+            .output_audited();
 
         // Create a human-friendly error message
         let error_detail = if !stderr_str.trim().is_empty() {
@@ -1855,6 +2310,31 @@ fn handle_merge_failure(repo_path: &str, stdout: &[u8], stderr: &[u8]) -> MergeR
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_remotes() {
+        let output = "origin\tgit@github.com:me/repo.git (fetch)\n\
+                      origin\tgit@github.com:me/repo.git (push)\n\
+                      upstream\thttps://github.com/org/repo.git (fetch)\n\
+                      upstream\tno_push (push)\n";
+        let remotes = parse_remotes(output);
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes[0].name, "origin");
+        assert_eq!(remotes[0].push_url, "git@github.com:me/repo.git");
+        assert_eq!(remotes[1].fetch_url, "https://github.com/org/repo.git");
+        assert_eq!(remotes[1].push_url, "no_push");
+    }
+
+    #[test]
+    fn test_find_stash_ref() {
+        let list = "stash@{0} 1111111111111111111111111111111111111111\n\
+                    stash@{1} 2222222222222222222222222222222222222222\n";
+        assert_eq!(
+            find_stash_ref(list, "2222222222222222222222222222222222222222"),
+            Some("stash@{1}".to_string())
+        );
+        assert_eq!(find_stash_ref(list, "3333"), None);
+    }
+
     // ========================================================================
     // get_repo_name tests
     // ========================================================================
@@ -1929,4 +2409,45 @@ mod tests {
         };
         assert_eq!(id.to_key(), "my-org-my-project");
     }
+
+    #[test]
+    fn test_rebase_todo() {
+        let commits = parse_rebase_commits("a1\x1fa1\x1fAdd login\x1fann\x1f1\nb2\x1fb2\x1fFix typo\x1fann\x1f2\nc3\x1fc3\x1fWIP\x1fann\x1f3\n");
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[1].subject, "Fix typo");
+
+        let action = |sha: &str, action, message: Option<&str>| RebaseAction {
+            sha: sha.to_string(),
+            action,
+            message: message.map(str::to_string),
+        };
+        let (todo, messages) = rebase_todo(
+            &commits,
+            &[
+                action("a1", RebaseActionKind::Reword, Some("Add login form")),
+                action("c3", RebaseActionKind::Drop, None),
+                action("b2", RebaseActionKind::Fixup, None),
+            ],
+            |i| format!("/tmp/m{i}"),
+        )
+        .unwrap();
+        assert_eq!(
+            todo,
+            "pick a1\nexec git commit --amend --allow-empty -F '/tmp/m0'\ndrop c3\nfixup b2\n"
+        );
+        assert_eq!(
+            messages,
+            vec![("/tmp/m0".to_string(), "Add login form".to_string())]
+        );
+
+        // A squash needs an earlier commit, and every commit must be listed
+        let squash_first = [
+            action("b2", RebaseActionKind::Squash, None),
+            action("a1", RebaseActionKind::Pick, None),
+            action("c3", RebaseActionKind::Pick, None),
+        ];
+        assert!(rebase_todo(&commits, &squash_first, |i| i.to_string()).is_err());
+        let missing = [action("a1", RebaseActionKind::Pick, None)];
+        assert!(rebase_todo(&commits, &missing, |i| i.to_string()).is_err());
+    }
 }
@@ -0,0 +1,188 @@
+//! Per-repository queue for git operations
+//!
+//! Worktrees of a repository share its git directory, so creating or removing
+//! a worktree, deleting a branch and fetching all take locks in the same place
+//! (`packed-refs.lock`, `config.lock`, ref locks). Running them concurrently
+//! produced sporadic "index.lock exists" / "cannot lock ref" failures.
+//!
+//! Mutating operations hold [`lock`] for the repository, which serializes them
+//! in FIFO order per repository (keyed by the shared git directory, so all
+//! worktrees of a repo queue together). Fetches additionally take a global
+//! [`fetch_slot`], limiting how many run at once across repositories.
+//!
+//! The lock is reentrant per thread, so an operation holding it can call other
+//! queued operations on the same repository.
+//!
+//! Read-only status polls don't queue; they run git with optional locks
+//! disabled instead (see [`read_command`]).
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Most fetches running at once, across all repositories
+const MAX_CONCURRENT_FETCHES: usize = 3;
+
+/// FIFO ticket lock for one repository
+#[derive(Default)]
+struct RepoQueue {
+    /// (next ticket to hand out, ticket currently served)
+    tickets: Mutex<(u64, u64)>,
+    turn: Condvar,
+}
+
+static QUEUES: Lazy<Mutex<HashMap<PathBuf, Arc<RepoQueue>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Repository path -> shared git directory
+static REPO_KEYS: Lazy<Mutex<HashMap<String, PathBuf>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static FETCHES: Lazy<(Mutex<usize>, Condvar)> = Lazy::new(|| (Mutex::new(0), Condvar::new()));
+
+thread_local! {
+    /// Repositories whose queue this thread currently holds
+    static HELD: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
+/// The git directory shared by all worktrees of the repository at `repo_path`
+fn repo_key(repo_path: &str) -> PathBuf {
+    if let Some(key) = REPO_KEYS.lock().unwrap().get(repo_path) {
+        return key.clone();
+    }
+
+    let common_dir = silent_command("git")
+        .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
+        .current_dir(repo_path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()));
+    let Some(key) = common_dir else {
+        // Not cached: the path may not be a repository yet
+        return std::fs::canonicalize(repo_path).unwrap_or_else(|_| PathBuf::from(repo_path));
+    };
+
+    REPO_KEYS
+        .lock()
+        .unwrap()
+        .insert(repo_path.to_string(), key.clone());
+    key
+}
+
+/// Holds a repository's queue until dropped
+pub struct QueueGuard {
+    /// None when the thread already held the queue (reentrant use)
+    held: Option<(PathBuf, Arc<RepoQueue>)>,
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        let Some((key, queue)) = self.held.take() else {
+            return;
+        };
+        HELD.with(|held| held.borrow_mut().remove(&key));
+        let mut tickets = queue.tickets.lock().unwrap();
+        tickets.1 += 1;
+        queue.turn.notify_all();
+    }
+}
+
+/// Wait for the repository's turn, then hold it until the guard is dropped
+pub fn lock(repo_path: &str) -> QueueGuard {
+    let key = repo_key(repo_path);
+    if HELD.with(|held| held.borrow().contains(&key)) {
+        return QueueGuard { held: None };
+    }
+
+    let queue = QUEUES
+        .lock()
+        .unwrap()
+        .entry(key.clone())
+        .or_default()
+        .clone();
+    {
+        let mut tickets = queue.tickets.lock().unwrap();
+        let ticket = tickets.0;
+        tickets.0 += 1;
+        if tickets.1 != ticket {
+            log::trace!("Waiting for queued git operations in {}", key.display());
+        }
+        while tickets.1 != ticket {
+            tickets = queue.turn.wait(tickets).unwrap();
+        }
+    }
+
+    HELD.with(|held| held.borrow_mut().insert(key.clone()));
+    QueueGuard {
+        held: Some((key, queue)),
+    }
+}
+
+/// Holds one of the global fetch slots until dropped
+pub struct FetchSlot(());
+
+impl Drop for FetchSlot {
+    fn drop(&mut self) {
+        let (running, freed) = &*FETCHES;
+        *running.lock().unwrap() -= 1;
+        freed.notify_one();
+    }
+}
+
+/// Wait until fewer than `MAX_CONCURRENT_FETCHES` fetches are running
+pub fn fetch_slot() -> FetchSlot {
+    let (running, freed) = &*FETCHES;
+    let mut running = running.lock().unwrap();
+    while *running >= MAX_CONCURRENT_FETCHES {
+        running = freed.wait(running).unwrap();
+    }
+    *running += 1;
+    FetchSlot(())
+}
+
+/// A git command for read-only queries that runs alongside queued operations.
+/// Optional locks are disabled so e.g. `git status` doesn't refresh the index
+/// (and take `index.lock`) under a running commit or rebase.
+pub fn read_command() -> Command {
+    let mut command = silent_command("git");
+    command.env("GIT_OPTIONAL_LOCKS", "0");
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_queue_serializes_and_is_reentrant() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let inside = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let path = path.clone();
+                let inside = inside.clone();
+                std::thread::spawn(move || {
+                    let _guard = lock(&path);
+                    assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                    // Nested use on the same thread doesn't deadlock
+                    drop(lock(&path));
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
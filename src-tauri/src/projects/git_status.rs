@@ -1,7 +1,10 @@
+use crate::command_audit::AuditedCommand;
 use crate::platform::silent_command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use super::git_queue;
 
 /// Information about a worktree for polling
 #[derive(Debug, Clone)]
@@ -46,11 +49,13 @@ pub struct GitBranchStatus {
 /// Fetch the latest changes from origin for a specific branch
 fn fetch_origin_branch(repo_path: &str, branch: &str) -> Result<(), String> {
     log::trace!("Fetching origin/{branch} in {repo_path}");
+    let _queued = git_queue::lock(repo_path);
+    let _slot = git_queue::fetch_slot();
 
     let output = silent_command("git")
         .args(["fetch", "origin", branch])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git fetch: {e}"))?;
 
     if !output.status.success() {
@@ -72,10 +77,10 @@ fn fetch_origin_branch(repo_path: &str, branch: &str) -> Result<(), String> {
 
 /// Get the current branch name
 fn get_current_branch(repo_path: &str) -> Result<String, String> {
-    let output = silent_command("git")
+    let output = git_queue::read_command()
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run git command: {e}"))?;
 
     if !output.status.success() {
@@ -95,10 +100,10 @@ fn get_uncommitted_diff_stats(repo_path: &str) -> (u32, u32) {
 
     // 1. Get diff stats for unstaged changes (working directory vs index)
     // git diff --numstat outputs: "added<tab>removed<tab>filename" per line
-    let unstaged_output = silent_command("git")
+    let unstaged_output = git_queue::read_command()
         .args(["diff", "--numstat"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     if let Ok(o) = unstaged_output {
         if o.status.success() {
@@ -116,10 +121,10 @@ fn get_uncommitted_diff_stats(repo_path: &str) -> (u32, u32) {
 
     // 2. Get diff stats for staged changes (index vs HEAD)
     // git diff --cached --numstat shows changes that have been `git add`ed
-    let staged_output = silent_command("git")
+    let staged_output = git_queue::read_command()
         .args(["diff", "--cached", "--numstat"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     if let Ok(o) = staged_output {
         if o.status.success() {
@@ -137,10 +142,10 @@ fn get_uncommitted_diff_stats(repo_path: &str) -> (u32, u32) {
 
     // 3. Get stats for untracked (new) files
     // List all untracked files
-    let untracked_output = silent_command("git")
+    let untracked_output = git_queue::read_command()
         .args(["ls-files", "--others", "--exclude-standard"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     if let Ok(o) = untracked_output {
         if o.status.success() {
@@ -172,10 +177,10 @@ fn get_untracked_files_raw_patch(repo_path: &str) -> String {
     let mut raw_patch = String::new();
 
     // List all untracked files
-    let output = silent_command("git")
+    let output = git_queue::read_command()
         .args(["ls-files", "--others", "--exclude-standard"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     let Ok(o) = output else {
         return raw_patch;
@@ -222,10 +227,10 @@ fn get_untracked_files_diff(repo_path: &str) -> Vec<DiffFile> {
     let mut untracked_files: Vec<DiffFile> = Vec::new();
 
     // List all untracked files
-    let output = silent_command("git")
+    let output = git_queue::read_command()
         .args(["ls-files", "--others", "--exclude-standard"])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     let Ok(o) = output else {
         return untracked_files;
@@ -240,73 +245,99 @@ fn get_untracked_files_diff(repo_path: &str) -> Vec<DiffFile> {
         if file_path.is_empty() {
             continue;
         }
+        untracked_files.push(get_untracked_file_diff(repo_path, file_path));
+    }
 
-        let full_path = std::path::Path::new(repo_path).join(file_path);
+    untracked_files
+}
 
-        // Try to read file content
-        match std::fs::read_to_string(&full_path) {
-            Ok(content) => {
-                let lines: Vec<&str> = content.lines().collect();
-                let line_count = lines.len() as u32;
-
-                // Create diff lines (all additions)
-                let diff_lines: Vec<DiffLine> = lines
-                    .iter()
-                    .enumerate()
-                    .map(|(i, line)| DiffLine {
-                        line_type: "addition".to_string(),
-                        content: (*line).to_string(),
-                        old_line_number: None,
-                        new_line_number: Some((i + 1) as u32),
-                    })
-                    .collect();
-
-                // Create a single hunk containing all lines
-                let hunk = DiffHunk {
-                    header: format!("@@ -0,0 +1,{line_count} @@"),
-                    old_start: 0,
-                    old_lines: 0,
-                    new_start: 1,
-                    new_lines: line_count,
-                    lines: diff_lines,
-                };
-
-                untracked_files.push(DiffFile {
-                    path: file_path.to_string(),
-                    old_path: None,
-                    status: "untracked".to_string(),
-                    additions: line_count,
-                    deletions: 0,
-                    is_binary: false,
-                    hunks: vec![hunk],
-                });
+/// Diff of a single untracked file: all lines as additions, or binary if it
+/// can't be read as text
+pub(crate) fn get_untracked_file_diff(repo_path: &str, file_path: &str) -> DiffFile {
+    let full_path = std::path::Path::new(repo_path).join(file_path);
+
+    // Try to read file content
+    match std::fs::read_to_string(&full_path) {
+        Ok(content) => {
+            let lines: Vec<&str> = content.lines().collect();
+            let line_count = lines.len() as u32;
+
+            // Create diff lines (all additions)
+            let diff_lines: Vec<DiffLine> = lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| DiffLine {
+                    line_type: "addition".to_string(),
+                    content: (*line).to_string(),
+                    old_line_number: None,
+                    new_line_number: Some((i + 1) as u32),
+                })
+                .collect();
+
+            // Create a single hunk containing all lines
+            let hunk = DiffHunk {
+                header: format!("@@ -0,0 +1,{line_count} @@"),
+                old_start: 0,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: line_count,
+                lines: diff_lines,
+            };
+
+            DiffFile {
+                path: file_path.to_string(),
+                old_path: None,
+                status: "untracked".to_string(),
+                additions: line_count,
+                deletions: 0,
+                is_binary: false,
+                hunks: vec![hunk],
             }
-            Err(_) => {
-                // Binary file or read error - mark as binary untracked
-                untracked_files.push(DiffFile {
-                    path: file_path.to_string(),
-                    old_path: None,
-                    status: "untracked".to_string(),
-                    additions: 0,
-                    deletions: 0,
-                    is_binary: true,
-                    hunks: Vec::new(),
-                });
+        }
+        Err(_) => {
+            // Binary file or read error - mark as binary untracked
+            DiffFile {
+                path: file_path.to_string(),
+                old_path: None,
+                status: "untracked".to_string(),
+                additions: 0,
+                deletions: 0,
+                is_binary: true,
+                hunks: Vec::new(),
             }
         }
     }
+}
 
-    untracked_files
+/// Raw unified patch of a single untracked file, matching `get_untracked_file_diff`.
+/// Empty for binary files.
+pub(crate) fn get_untracked_file_raw_patch(file: &DiffFile) -> String {
+    let Some(hunk) = file.hunks.first().filter(|_| !file.is_binary) else {
+        return String::new();
+    };
+    let file_path = &file.path;
+    let mut raw_patch = String::new();
+    raw_patch.push_str(&format!("diff --git a/{file_path} b/{file_path}\n"));
+    raw_patch.push_str("new file mode 100644\n");
+    raw_patch.push_str("--- /dev/null\n");
+    raw_patch.push_str(&format!("+++ b/{file_path}\n"));
+    raw_patch.push_str(&format!("{}\n", hunk.header));
+    for line in &hunk.lines {
+        raw_patch.push('+');
+        raw_patch.push_str(&line.content);
+        raw_patch.push('\n');
+    }
+    raw_patch
 }
 
 /// Get the number of lines added and removed compared to base branch (origin/main)
 fn get_branch_diff_stats(repo_path: &str, base_branch: &str) -> (u32, u32) {
     // git diff --numstat origin/main...HEAD shows changes in current branch vs base
     let origin_ref = format!("origin/{base_branch}");
-    let output = silent_command("git")
+    let output = git_queue::read_command()
         .args(["diff", "--numstat", &format!("{origin_ref}...HEAD")])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     match output {
         Ok(o) if o.status.success() => {
@@ -329,10 +360,10 @@ fn get_branch_diff_stats(repo_path: &str, base_branch: &str) -> (u32, u32) {
 
 /// Check if a git ref exists
 fn ref_exists(repo_path: &str, git_ref: &str) -> bool {
-    silent_command("git")
+    git_queue::read_command()
         .args(["rev-parse", "--verify", "--quiet", git_ref])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
@@ -340,10 +371,10 @@ fn ref_exists(repo_path: &str, git_ref: &str) -> bool {
 /// Count commits between two refs
 /// Returns 0 if either ref doesn't exist
 fn count_commits_between(repo_path: &str, from_ref: &str, to_ref: &str) -> u32 {
-    let output = silent_command("git")
+    let output = git_queue::read_command()
         .args(["rev-list", "--count", &format!("{from_ref}..{to_ref}")])
         .current_dir(repo_path)
-        .output();
+        .output_audited();
 
     match output {
         Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
@@ -359,7 +390,7 @@ fn count_commits_between(repo_path: &str, from_ref: &str, to_ref: &str) -> u32 {
 // ============================================================================
 
 /// A single line in a diff hunk
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffLine {
     /// Line type: "context", "addition", "deletion"
     pub line_type: String,
@@ -372,7 +403,7 @@ pub struct DiffLine {
 }
 
 /// A single hunk in a diff
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffHunk {
     /// Header line (e.g., "@@ -1,5 +1,7 @@")
     pub header: String,
@@ -451,46 +482,8 @@ fn parse_hunk_header(header: &str) -> Option<(u32, u32, u32, u32)> {
     Some((old_start, old_lines, new_start, new_lines))
 }
 
-/// Get detailed diff content for a repository
-///
-/// `diff_type` can be "uncommitted" (working directory vs HEAD) or "branch" (HEAD vs base branch)
-pub fn get_git_diff(
-    repo_path: &str,
-    diff_type: &str,
-    base_branch: Option<&str>,
-) -> Result<GitDiff, String> {
-    let base = base_branch.unwrap_or("main");
-    let range = format!("origin/{base}...HEAD");
-
-    let (base_ref, target_ref, args): (String, String, Vec<&str>) = match diff_type {
-        "uncommitted" => (
-            "HEAD".to_string(),
-            "working directory".to_string(),
-            vec!["diff", "HEAD", "--unified=3"],
-        ),
-        "branch" => {
-            let origin_ref = format!("origin/{base}");
-            (
-                origin_ref,
-                "HEAD".to_string(),
-                vec!["diff", "--unified=3", &range],
-            )
-        }
-        _ => return Err(format!("Invalid diff_type: {diff_type}")),
-    };
-
-    let output = silent_command("git")
-        .args(&args)
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Failed to run git diff: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Git diff failed: {stderr}"));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+/// Parse unified `git diff` output into per-file hunks
+pub(crate) fn parse_diff(stdout: &str) -> Vec<DiffFile> {
     let mut files: Vec<DiffFile> = Vec::new();
     let mut current_file: Option<DiffFile> = None;
     let mut current_hunk: Option<DiffHunk> = None;
@@ -619,6 +612,65 @@ pub fn get_git_diff(
         files.push(file);
     }
 
+    files
+}
+
+/// Split unified `git diff` output into one patch per file.
+/// Concatenating the pieces gives back the original output.
+pub(crate) fn split_patch_by_file(patch: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = patch
+        .match_indices("\ndiff --git ")
+        .map(|(i, _)| i + 1)
+        .collect();
+    if patch.starts_with("diff --git ") {
+        starts.insert(0, 0);
+    }
+    starts.push(patch.len());
+    starts.windows(2).map(|w| &patch[w[0]..w[1]]).collect()
+}
+
+/// Get detailed diff content for a repository
+///
+/// `diff_type` can be "uncommitted" (working directory vs HEAD) or "branch" (HEAD vs base branch)
+pub fn get_git_diff(
+    repo_path: &str,
+    diff_type: &str,
+    base_branch: Option<&str>,
+) -> Result<GitDiff, String> {
+    let base = base_branch.unwrap_or("main");
+    let range = format!("origin/{base}...HEAD");
+
+    let (base_ref, target_ref, args): (String, String, Vec<&str>) = match diff_type {
+        "uncommitted" => (
+            "HEAD".to_string(),
+            "working directory".to_string(),
+            vec!["diff", "HEAD", "--unified=3"],
+        ),
+        "branch" => {
+            let origin_ref = format!("origin/{base}");
+            (
+                origin_ref,
+                "HEAD".to_string(),
+                vec!["diff", "--unified=3", &range],
+            )
+        }
+        _ => return Err(format!("Invalid diff_type: {diff_type}")),
+    };
+
+    let output = git_queue::read_command()
+        .args(&args)
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git diff: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Git diff failed: {stderr}"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut files = parse_diff(&stdout);
+
     // Build raw patch - start with git diff output
     let mut raw_patch = stdout.to_string();
 
@@ -655,15 +707,25 @@ pub fn get_git_diff(
 
 /// Get the branch status for a worktree compared to its base branch
 ///
-/// This fetches the latest from origin and compares the current HEAD
+/// This fetches the latest from origin (unless `fetch` is false, e.g. for
+/// projects with remote polling disabled) and compares the current HEAD
 /// to origin/{base_branch} to determine ahead/behind counts.
-pub fn get_branch_status(info: &ActiveWorktreeInfo) -> Result<GitBranchStatus, String> {
+pub fn get_branch_status(
+    info: &ActiveWorktreeInfo,
+    fetch: bool,
+) -> Result<GitBranchStatus, String> {
+    if super::jj::is_jj_repo(std::path::Path::new(&info.worktree_path)) {
+        return super::jj::branch_status(info, fetch);
+    }
+
     let repo_path = &info.worktree_path;
     let base_branch = &info.base_branch;
 
     // Fetch latest from origin for the base branch
     // This is best-effort; if it fails, we'll compare with stale data
-    let _ = fetch_origin_branch(repo_path, base_branch);
+    if fetch {
+        let _ = fetch_origin_branch(repo_path, base_branch);
+    }
 
     // Get current branch name
     let current_branch = get_current_branch(repo_path)?;
@@ -696,7 +758,9 @@ pub fn get_branch_status(info: &ActiveWorktreeInfo) -> Result<GitBranchStatus, S
     let origin_current_ref = format!("origin/{current_branch}");
     let unpushed_count = if current_branch != *base_branch {
         // Fetch origin/{current_branch} so we have up-to-date remote info
-        let _ = fetch_origin_branch(repo_path, &current_branch);
+        if fetch {
+            let _ = fetch_origin_branch(repo_path, &current_branch);
+        }
         if ref_exists(repo_path, &origin_current_ref) {
             count_commits_between(repo_path, &origin_current_ref, "HEAD")
         } else {
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
+use super::branch_naming::BranchNameScheme;
 use super::git::get_repo_identifier;
+use crate::command_audit::AuditedCommand;
 use crate::gh_cli::config::resolve_gh_binary;
 use crate::platform::silent_command;
 
@@ -70,9 +72,9 @@ pub struct IssueContext {
     pub comments: Vec<GitHubComment>,
 }
 
-/// List GitHub issues for a repository
+/// List issues for a repository
 ///
-/// Uses `gh issue list` to fetch issues from the repository.
+/// Uses `gh issue list` (or `glab issue list` for GitLab repositories).
 /// - state: "open", "closed", or "all" (default: "open")
 /// - Returns up to 100 issues sorted by creation date (newest first)
 #[tauri::command]
@@ -81,13 +83,20 @@ pub async fn list_github_issues(
     project_path: String,
     state: Option<String>,
 ) -> Result<Vec<GitHubIssue>, String> {
-    log::trace!("Listing GitHub issues for {project_path} with state: {state:?}");
+    log::trace!("Listing issues for {project_path} with state: {state:?}");
 
-    let gh = resolve_gh_binary(&app);
     let state_arg = state.unwrap_or_else(|| "open".to_string());
+    super::repo_provider::for_repo(&app, &project_path).list_issues(&project_path, &state_arg)
+}
 
+/// List issues using `gh issue list`
+pub fn fetch_github_issues(
+    gh: &std::path::Path,
+    project_path: &str,
+    state_arg: &str,
+) -> Result<Vec<GitHubIssue>, String> {
     // Run gh issue list
-    let output = silent_command(&gh)
+    let output = silent_command(gh)
         .args([
             "issue",
             "list",
@@ -96,10 +105,10 @@ pub async fn list_github_issues(
             "-L",
             "100",
             "--state",
-            &state_arg,
+            state_arg,
         ])
-        .current_dir(&project_path)
-        .output()
+        .current_dir(project_path)
+        .output_audited()
         .map_err(|e| format!("Failed to run gh issue list: {e}"))?;
 
     if !output.status.success() {
@@ -152,7 +161,7 @@ pub async fn search_github_issues(
             "all",
         ])
         .current_dir(&project_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh issue list --search: {e}"))?;
 
     if !output.status.success() {
@@ -189,8 +198,17 @@ pub async fn get_github_issue(
     log::trace!("Getting GitHub issue #{issue_number} for {project_path}");
 
     let gh = resolve_gh_binary(&app);
+    fetch_github_issue(&gh, &project_path, issue_number)
+}
+
+/// Fetch an issue with its comments using `gh issue view`
+pub fn fetch_github_issue(
+    gh: &std::path::Path,
+    project_path: &str,
+    issue_number: u32,
+) -> Result<GitHubIssueDetail, String> {
     // Run gh issue view
-    let output = silent_command(&gh)
+    let output = silent_command(gh)
         .args([
             "issue",
             "view",
@@ -198,8 +216,8 @@ pub async fn get_github_issue(
             "--json",
             "number,title,body,state,labels,createdAt,author,comments",
         ])
-        .current_dir(&project_path)
-        .output()
+        .current_dir(project_path)
+        .output_audited()
         .map_err(|e| format!("Failed to run gh issue view: {e}"))?;
 
     if !output.status.success() {
@@ -222,6 +240,32 @@ pub async fn get_github_issue(
     Ok(issue)
 }
 
+/// Add labels to an issue using `gh issue edit`
+pub fn add_issue_labels(
+    gh: &std::path::Path,
+    project_path: &str,
+    issue_number: u32,
+    labels: &[String],
+) -> Result<(), String> {
+    let output = silent_command(gh)
+        .args([
+            "issue",
+            "edit",
+            &issue_number.to_string(),
+            "--add-label",
+            &labels.join(","),
+        ])
+        .current_dir(project_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run gh issue edit: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("gh issue edit failed: {stderr}"));
+    }
+    Ok(())
+}
+
 /// Generate a slug from an issue title for branch naming
 /// e.g., "Fix the login bug" -> "fix-the-login-bug"
 pub fn slugify_issue_title(title: &str) -> String {
@@ -250,10 +294,17 @@ pub fn slugify_issue_title(title: &str) -> String {
 }
 
 /// Generate a branch name from an issue
-/// e.g., Issue #123 "Fix the login bug" -> "issue-123-fix-the-login-bug"
-pub fn generate_branch_name_from_issue(issue_number: u32, title: &str) -> String {
+/// e.g., Issue #123 "Fix the login bug" -> "issue-123-fix-the-login-bug",
+/// or shaped by the project's naming scheme when it has one
+pub fn generate_branch_name_from_issue(
+    issue_number: u32,
+    title: &str,
+    scheme: Option<&BranchNameScheme>,
+) -> String {
     let slug = slugify_issue_title(title);
-    format!("issue-{issue_number}-{slug}")
+    scheme
+        .and_then(|scheme| scheme.apply(&slug, Some(issue_number)))
+        .unwrap_or_else(|| format!("issue-{issue_number}-{slug}"))
 }
 
 /// Format issue context as markdown for the context file
@@ -324,14 +375,14 @@ pub struct ContextRef {
 pub struct ContextReferences {
     pub issues: std::collections::HashMap<String, ContextRef>,
     pub prs: std::collections::HashMap<String, ContextRef>,
+    /// Issues from other trackers (Jira, Linear), keyed "{provider}:{id}"
+    #[serde(default)]
+    pub trackers: std::collections::HashMap<String, ContextRef>,
 }
 
 /// Get the directory for shared GitHub contexts
 pub fn get_github_contexts_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
     Ok(app_data_dir.join("git-context"))
 }
 
@@ -472,6 +523,87 @@ pub fn remove_pr_reference(
     Ok(orphaned)
 }
 
+/// Context file of an issue from another tracker (Jira, Linear)
+/// File format: `{provider}-issue-{id}.md`
+pub fn get_tracker_context_path(
+    app: &tauri::AppHandle,
+    provider: &str,
+    id: &str,
+) -> Result<PathBuf, String> {
+    Ok(get_github_contexts_dir(app)?.join(format!("{provider}-issue-{id}.md")))
+}
+
+/// Add a worktree reference to a tracker issue context
+pub fn add_tracker_reference(
+    app: &tauri::AppHandle,
+    provider: &str,
+    id: &str,
+    worktree_id: &str,
+) -> Result<(), String> {
+    let mut refs = load_context_references(app)?;
+    let entry = refs.trackers.entry(format!("{provider}:{id}")).or_default();
+    if !entry.worktrees.contains(&worktree_id.to_string()) {
+        entry.worktrees.push(worktree_id.to_string());
+    }
+    entry.orphaned_at = None;
+
+    save_context_references(app, &refs)
+}
+
+/// Remove a worktree reference from a tracker issue context
+/// Returns true if the context is now orphaned (no more references)
+pub fn remove_tracker_reference(
+    app: &tauri::AppHandle,
+    provider: &str,
+    id: &str,
+    worktree_id: &str,
+) -> Result<bool, String> {
+    let mut refs = load_context_references(app)?;
+    let orphaned = match refs.trackers.get_mut(&format!("{provider}:{id}")) {
+        Some(entry) => {
+            entry.worktrees.retain(|w| w != worktree_id);
+            if entry.worktrees.is_empty() && entry.orphaned_at.is_none() {
+                entry.orphaned_at = Some(
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                );
+                true
+            } else {
+                false
+            }
+        }
+        None => false,
+    };
+
+    save_context_references(app, &refs)?;
+    Ok(orphaned)
+}
+
+/// Get all tracker issues referenced by a worktree, as (provider, id) pairs
+/// for one provider (None = all providers)
+pub fn get_worktree_tracker_refs(
+    app: &tauri::AppHandle,
+    worktree_id: &str,
+    provider: Option<&str>,
+) -> Result<Vec<(String, String)>, String> {
+    let refs = load_context_references(app)?;
+    let mut keys: Vec<(String, String)> = refs
+        .trackers
+        .iter()
+        .filter(|(_, entry)| entry.worktrees.contains(&worktree_id.to_string()))
+        .filter_map(|(key, _)| key.split_once(':'))
+        .filter(|(p, _)| match provider {
+            Some(provider) => *p == provider,
+            None => true,
+        })
+        .map(|(p, id)| (p.to_string(), id.to_string()))
+        .collect();
+    keys.sort();
+    Ok(keys)
+}
+
 /// Get all issue keys referenced by a worktree
 /// Returns keys in format "{owner}-{repo}-{number}"
 pub fn get_worktree_issue_refs(
@@ -533,6 +665,13 @@ pub fn remove_all_worktree_references(
         }
     }
 
+    for entry in refs.trackers.values_mut() {
+        entry.worktrees.retain(|w| w != worktree_id);
+        if entry.worktrees.is_empty() && entry.orphaned_at.is_none() {
+            entry.orphaned_at = Some(now);
+        }
+    }
+
     save_context_references(app, &refs)?;
     Ok((orphaned_issues, orphaned_prs))
 }
@@ -633,6 +772,33 @@ pub fn cleanup_orphaned_contexts(
         refs.prs.remove(key);
     }
 
+    // Clean up orphaned tracker issues
+    let trackers_to_remove: Vec<String> = refs
+        .trackers
+        .iter()
+        .filter(|(_, entry)| {
+            entry
+                .orphaned_at
+                .is_some_and(|orphaned_at| orphaned_at + retention_secs < now)
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    for key in &trackers_to_remove {
+        // Key format: {provider}:{id}, file format: {provider}-issue-{id}.md
+        if let Some((provider, id)) = key.split_once(':') {
+            let file_path = contexts_dir.join(format!("{provider}-issue-{id}.md"));
+            if file_path.exists() {
+                if let Err(e) = std::fs::remove_file(&file_path) {
+                    log::warn!("Failed to remove orphaned {provider} context {id}: {e}");
+                } else {
+                    deleted_count += 1;
+                }
+            }
+        }
+        refs.trackers.remove(key);
+    }
+
     save_context_references(app, &refs)?;
     Ok(deleted_count)
 }
@@ -654,8 +820,11 @@ pub async fn load_issue_context(
     let repo_id = get_repo_identifier(&project_path)?;
     let repo_key = repo_id.to_key();
 
-    // Fetch issue data from GitHub
-    let issue = get_github_issue(app.clone(), project_path, issue_number).await?;
+    // Fetch issue data from GitHub, unless it was prefetched recently
+    let issue = match super::context_prefetch::cached_issue(&repo_key, issue_number) {
+        Some(issue) => issue,
+        None => get_github_issue(app.clone(), project_path, issue_number).await?,
+    };
 
     // Create issue context
     let ctx = IssueContext {
@@ -674,7 +843,7 @@ pub async fn load_issue_context(
     let context_file = contexts_dir.join(format!("{repo_key}-issue-{issue_number}.md"));
     let context_content = format_issue_context_markdown(&ctx);
 
-    std::fs::write(&context_file, context_content)
+    crate::encryption::write(&context_file, context_content)
         .map_err(|e| format!("Failed to write issue context file: {e}"))?;
 
     // Add reference tracking
@@ -719,7 +888,7 @@ pub async fn list_loaded_issue_contexts(
             let repo_key = format!("{owner}-{repo}");
             let context_file = contexts_dir.join(format!("{repo_key}-issue-{number}.md"));
 
-            if let Ok(content) = std::fs::read_to_string(&context_file) {
+            if let Ok(content) = crate::encryption::read_to_string(&context_file) {
                 // Parse title from first line: "# GitHub Issue #123: Title"
                 let title = content
                     .lines()
@@ -885,9 +1054,9 @@ pub struct LoadedPullRequestContext {
     pub repo_name: String,
 }
 
-/// List GitHub pull requests for a repository
+/// List pull requests (or GitLab merge requests) for a repository
 ///
-/// Uses `gh pr list` to fetch PRs from the repository.
+/// Uses `gh pr list` (or `glab mr list` for GitLab repositories).
 /// - state: "open", "closed", "merged", or "all" (default: "open")
 /// - Returns up to 100 PRs sorted by creation date (newest first)
 #[tauri::command]
@@ -896,13 +1065,20 @@ pub async fn list_github_prs(
     project_path: String,
     state: Option<String>,
 ) -> Result<Vec<GitHubPullRequest>, String> {
-    log::trace!("Listing GitHub PRs for {project_path} with state: {state:?}");
+    log::trace!("Listing PRs for {project_path} with state: {state:?}");
 
-    let gh = resolve_gh_binary(&app);
     let state_arg = state.unwrap_or_else(|| "open".to_string());
+    super::repo_provider::for_repo(&app, &project_path).list_prs(&project_path, &state_arg)
+}
 
+/// List PRs using `gh pr list`
+pub fn fetch_github_prs(
+    gh: &std::path::Path,
+    project_path: &str,
+    state_arg: &str,
+) -> Result<Vec<GitHubPullRequest>, String> {
     // Run gh pr list
-    let output = silent_command(&gh)
+    let output = silent_command(gh)
         .args([
             "pr",
             "list",
@@ -911,10 +1087,10 @@ pub async fn list_github_prs(
             "-L",
             "100",
             "--state",
-            &state_arg,
+            state_arg,
         ])
-        .current_dir(&project_path)
-        .output()
+        .current_dir(project_path)
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr list: {e}"))?;
 
     if !output.status.success() {
@@ -966,7 +1142,7 @@ pub async fn search_github_prs(
             "all",
         ])
         .current_dir(&project_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr list --search: {e}"))?;
 
     if !output.status.success() {
@@ -991,7 +1167,7 @@ pub async fn search_github_prs(
     Ok(prs)
 }
 
-/// Get detailed information about a specific GitHub PR
+/// Get detailed information about a specific PR or GitLab merge request
 ///
 /// Uses `gh pr view` to fetch the PR with comments and reviews.
 #[tauri::command]
@@ -1000,11 +1176,19 @@ pub async fn get_github_pr(
     project_path: String,
     pr_number: u32,
 ) -> Result<GitHubPullRequestDetail, String> {
-    log::trace!("Getting GitHub PR #{pr_number} for {project_path}");
+    log::trace!("Getting PR #{pr_number} for {project_path}");
 
-    let gh = resolve_gh_binary(&app);
+    super::repo_provider::for_repo(&app, &project_path).get_pr(&project_path, pr_number)
+}
+
+/// Fetch a PR with its comments and reviews using `gh pr view`
+pub fn fetch_github_pr(
+    gh: &std::path::Path,
+    project_path: &str,
+    pr_number: u32,
+) -> Result<GitHubPullRequestDetail, String> {
     // Run gh pr view
-    let output = silent_command(&gh)
+    let output = silent_command(gh)
         .args([
             "pr",
             "view",
@@ -1012,8 +1196,8 @@ pub async fn get_github_pr(
             "--json",
             "number,title,body,state,headRefName,baseRefName,isDraft,createdAt,author,labels,comments,reviews",
         ])
-        .current_dir(&project_path)
-        .output()
+        .current_dir(project_path)
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr view: {e}"))?;
 
     if !output.status.success() {
@@ -1130,7 +1314,7 @@ pub fn get_pr_diff(
     let output = silent_command(gh_binary)
         .args(["pr", "diff", &pr_number.to_string(), "--color", "never"])
         .current_dir(project_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr diff: {e}"))?;
 
     if !output.status.success() {
@@ -1174,13 +1358,15 @@ pub async fn load_pr_context(
     let repo_id = get_repo_identifier(&project_path)?;
     let repo_key = repo_id.to_key();
 
-    let gh = resolve_gh_binary(&app);
-
-    // Fetch PR data from GitHub
-    let pr = get_github_pr(app.clone(), project_path.clone(), pr_number).await?;
-
-    // Fetch the diff
-    let diff = get_pr_diff(&project_path, pr_number, &gh).ok();
+    // Fetch PR data and diff from GitHub, unless they were prefetched recently
+    let (pr, diff) = match super::context_prefetch::cached_pr(&repo_key, pr_number) {
+        Some(cached) => cached,
+        None => {
+            let gh = resolve_gh_binary(&app);
+            let pr = get_github_pr(app.clone(), project_path.clone(), pr_number).await?;
+            (pr, get_pr_diff(&project_path, pr_number, &gh).ok())
+        }
+    };
 
     // Create PR context
     let ctx = PullRequestContext {
@@ -1203,7 +1389,7 @@ pub async fn load_pr_context(
     let context_file = contexts_dir.join(format!("{repo_key}-pr-{pr_number}.md"));
     let context_content = format_pr_context_markdown(&ctx);
 
-    std::fs::write(&context_file, context_content)
+    crate::encryption::write(&context_file, context_content)
         .map_err(|e| format!("Failed to write PR context file: {e}"))?;
 
     // Add reference tracking
@@ -1251,7 +1437,7 @@ pub async fn list_loaded_pr_contexts(
             let repo_key = format!("{owner}-{repo}");
             let context_file = contexts_dir.join(format!("{repo_key}-pr-{number}.md"));
 
-            if let Ok(content) = std::fs::read_to_string(&context_file) {
+            if let Ok(content) = crate::encryption::read_to_string(&context_file) {
                 // Parse title from first line: "# GitHub Pull Request #123: Title"
                 let title = content
                     .lines()
@@ -1376,7 +1562,7 @@ pub async fn get_issue_context_content(
         ));
     }
 
-    std::fs::read_to_string(&context_file)
+    crate::encryption::read_to_string(&context_file)
         .map_err(|e| format!("Failed to read issue context file: {e}"))
 }
 
@@ -1406,7 +1592,7 @@ pub async fn get_pr_context_content(
         return Err(format!("PR context file not found for PR #{pr_number}"));
     }
 
-    std::fs::read_to_string(&context_file)
+    crate::encryption::read_to_string(&context_file)
         .map_err(|e| format!("Failed to read PR context file: {e}"))
 }
 
@@ -0,0 +1,369 @@
+//! GitLab support through the `glab` CLI
+//!
+//! Issues and merge requests are converted into the GitHub types the rest of
+//! the app already uses (an MR's IID is its `number`, source and target
+//! branches are its head and base refs), so the issue/PR UI and the PR status
+//! poller work unchanged. Used through `repo_provider::GitLab`.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use super::github_issues::{
+    GitHubAuthor, GitHubComment, GitHubIssue, GitHubLabel, GitHubPullRequest,
+    GitHubPullRequestDetail,
+};
+use super::pr_status::{
+    compute_display_status, CheckStatus, MergeableStatus, PrState, PrStatus, ReviewDecision,
+};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Issues and MRs listed at once, like `gh ... -L 100`
+const LIST_LIMIT: &str = "100";
+
+#[derive(Debug, Deserialize)]
+struct GlabUser {
+    username: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlabIssue {
+    iid: u32,
+    title: String,
+    description: Option<String>,
+    state: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    created_at: String,
+    author: GlabUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlabPipeline {
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlabMergeRequest {
+    iid: u32,
+    title: String,
+    description: Option<String>,
+    state: String,
+    source_branch: String,
+    target_branch: String,
+    #[serde(default)]
+    draft: bool,
+    created_at: String,
+    author: GlabUser,
+    #[serde(default)]
+    labels: Vec<String>,
+    head_pipeline: Option<GlabPipeline>,
+    #[serde(default)]
+    has_conflicts: bool,
+    detailed_merge_status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlabNote {
+    body: String,
+    author: GlabUser,
+    created_at: String,
+    /// Notes GitLab adds itself ("added 2 commits", label changes)
+    #[serde(default)]
+    system: bool,
+}
+
+fn author(user: GlabUser) -> GitHubAuthor {
+    GitHubAuthor {
+        login: user.username,
+    }
+}
+
+fn labels(names: Vec<String>) -> Vec<GitHubLabel> {
+    names
+        .into_iter()
+        .map(|name| GitHubLabel {
+            name,
+            color: String::new(),
+        })
+        .collect()
+}
+
+/// GitLab states ("opened", "merged", ...) as GitHub spells them
+fn github_state(state: &str) -> String {
+    match state {
+        "opened" | "locked" => "OPEN".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+fn glab(glab: &Path, args: &[&str], repo_path: &str) -> Result<String, String> {
+    let output = silent_command(glab)
+        .args(args)
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run glab {} {}: {e}", args[0], args[1]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("glab auth login") || stderr.contains("401") {
+            return Err("GitLab CLI not authenticated. Run 'glab auth login' first.".to_string());
+        }
+        if stderr.contains("not a git repository") {
+            return Err("Not a git repository".to_string());
+        }
+        return Err(format!("glab {} {} failed: {stderr}", args[0], args[1]));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `glab ... list` flag for a GitHub-style state filter
+fn state_flag(state: &str) -> Option<&'static str> {
+    match state {
+        "closed" => Some("--closed"),
+        "merged" => Some("--merged"),
+        "all" => Some("--all"),
+        _ => None,
+    }
+}
+
+fn parse_issues(json: &str) -> Result<Vec<GitHubIssue>, String> {
+    let issues: Vec<GlabIssue> =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse glab response: {e}"))?;
+    Ok(issues
+        .into_iter()
+        .map(|issue| GitHubIssue {
+            number: issue.iid,
+            title: issue.title,
+            body: issue.description,
+            state: github_state(&issue.state),
+            labels: labels(issue.labels),
+            created_at: issue.created_at,
+            author: author(issue.author),
+        })
+        .collect())
+}
+
+fn pull_request(mr: GlabMergeRequest) -> GitHubPullRequest {
+    GitHubPullRequest {
+        number: mr.iid,
+        title: mr.title,
+        body: mr.description,
+        state: github_state(&mr.state),
+        head_ref_name: mr.source_branch,
+        base_ref_name: mr.target_branch,
+        is_draft: mr.draft,
+        created_at: mr.created_at,
+        author: author(mr.author),
+        labels: labels(mr.labels),
+    }
+}
+
+/// List issues: state is "open", "closed" or "all"
+pub fn list_issues(
+    glab_bin: &Path,
+    repo_path: &str,
+    state: &str,
+) -> Result<Vec<GitHubIssue>, String> {
+    let mut args = vec![
+        "issue",
+        "list",
+        "--output",
+        "json",
+        "--per-page",
+        LIST_LIMIT,
+    ];
+    args.extend(state_flag(state));
+    parse_issues(&glab(glab_bin, &args, repo_path)?)
+}
+
+/// List merge requests: state is "open", "closed", "merged" or "all"
+pub fn list_merge_requests(
+    glab_bin: &Path,
+    repo_path: &str,
+    state: &str,
+) -> Result<Vec<GitHubPullRequest>, String> {
+    let mut args = vec!["mr", "list", "--output", "json", "--per-page", LIST_LIMIT];
+    args.extend(state_flag(state));
+    let json = glab(glab_bin, &args, repo_path)?;
+    let mrs: Vec<GlabMergeRequest> =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse glab response: {e}"))?;
+    Ok(mrs.into_iter().map(pull_request).collect())
+}
+
+fn view_merge_request(
+    glab_bin: &Path,
+    repo_path: &str,
+    iid: u32,
+) -> Result<GlabMergeRequest, String> {
+    let json = glab(
+        glab_bin,
+        &["mr", "view", &iid.to_string(), "--output", "json"],
+        repo_path,
+    )?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse glab response: {e}"))
+}
+
+/// A merge request with its discussion (system notes left out)
+pub fn get_merge_request(
+    glab_bin: &Path,
+    repo_path: &str,
+    iid: u32,
+) -> Result<GitHubPullRequestDetail, String> {
+    let mr = pull_request(view_merge_request(glab_bin, repo_path, iid)?);
+    let notes = glab(
+        glab_bin,
+        &[
+            "api",
+            &format!("projects/:id/merge_requests/{iid}/notes?sort=asc&per_page=100"),
+        ],
+        repo_path,
+    )?;
+    let notes: Vec<GlabNote> =
+        serde_json::from_str(&notes).map_err(|e| format!("Failed to parse glab response: {e}"))?;
+    Ok(GitHubPullRequestDetail {
+        number: mr.number,
+        title: mr.title,
+        body: mr.body,
+        state: mr.state,
+        head_ref_name: mr.head_ref_name,
+        base_ref_name: mr.base_ref_name,
+        is_draft: mr.is_draft,
+        created_at: mr.created_at,
+        author: mr.author,
+        labels: mr.labels,
+        comments: notes
+            .into_iter()
+            .filter(|n| !n.system)
+            .map(|n| GitHubComment {
+                body: n.body,
+                author: author(n.author),
+                created_at: n.created_at,
+            })
+            .collect(),
+        // GitLab approvals carry no review text
+        reviews: Vec::new(),
+    })
+}
+
+/// Check out a merge request in a worktree, returning the local branch
+pub fn checkout_merge_request(
+    glab_bin: &Path,
+    worktree_path: &str,
+    iid: u32,
+    branch_name: Option<&str>,
+) -> Result<String, String> {
+    log::trace!("Running glab mr checkout {iid} in {worktree_path}");
+    let iid_str = iid.to_string();
+    let mut args = vec!["mr", "checkout", &iid_str];
+    if let Some(name) = branch_name {
+        args.extend(["--branch", name]);
+    }
+    glab(glab_bin, &args, worktree_path)
+        .map_err(|e| format!("Failed to checkout MR !{iid}: {e}"))?;
+
+    let branch_output = silent_command("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(worktree_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to get branch name: {e}"))?;
+    Ok(String::from_utf8_lossy(&branch_output.stdout)
+        .trim()
+        .to_string())
+}
+
+fn pipeline_status(pipeline: Option<&GlabPipeline>) -> Option<CheckStatus> {
+    match pipeline?.status.as_str() {
+        "success" => Some(CheckStatus::Success),
+        "failed" => Some(CheckStatus::Failure),
+        "canceled" => Some(CheckStatus::Error),
+        "skipped" | "manual" => None,
+        _ => Some(CheckStatus::Pending),
+    }
+}
+
+fn status_from(mr: &GlabMergeRequest, pr_url: &str, worktree_id: &str) -> PrStatus {
+    let state = match mr.state.as_str() {
+        "merged" => PrState::Merged,
+        "closed" => PrState::Closed,
+        _ => PrState::Open,
+    };
+    let review_decision = match mr.detailed_merge_status.as_deref() {
+        Some("not_approved") => Some(ReviewDecision::ReviewRequired),
+        Some("requested_changes") => Some(ReviewDecision::ChangesRequested),
+        _ => None,
+    };
+    let mergeable = if mr.has_conflicts {
+        Some(MergeableStatus::Conflicting)
+    } else {
+        match mr.detailed_merge_status.as_deref() {
+            Some("mergeable") => Some(MergeableStatus::Mergeable),
+            Some("checking") | Some("unchecked") => Some(MergeableStatus::Unknown),
+            _ => None,
+        }
+    };
+    PrStatus {
+        worktree_id: worktree_id.to_string(),
+        pr_number: mr.iid,
+        pr_url: pr_url.to_string(),
+        display_status: compute_display_status(&state, mr.draft, &review_decision),
+        state,
+        is_draft: mr.draft,
+        review_decision,
+        check_status: pipeline_status(mr.head_pipeline.as_ref()),
+        mergeable,
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    }
+}
+
+/// Status of a merge request, as the PR status poller reports it
+pub fn merge_request_status(
+    glab_bin: &Path,
+    repo_path: &str,
+    iid: u32,
+    pr_url: &str,
+    worktree_id: &str,
+) -> Result<PrStatus, String> {
+    log::trace!("Fetching MR status for !{iid} in {repo_path}");
+    let mr = view_merge_request(glab_bin, repo_path, iid)?;
+    Ok(status_from(&mr, pr_url, worktree_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::pr_status::PrDisplayStatus;
+
+    #[test]
+    fn test_convert_glab_json() {
+        let issues = parse_issues(
+            r#"[{"iid":12,"title":"Crash on start","description":null,"state":"opened",
+                "labels":["bug"],"created_at":"2024-01-01T00:00:00Z","author":{"username":"ann"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(issues[0].number, 12);
+        assert_eq!(issues[0].state, "OPEN");
+        assert_eq!(issues[0].labels[0].name, "bug");
+        assert_eq!(issues[0].author.login, "ann");
+
+        let mr: GlabMergeRequest = serde_json::from_str(
+            r#"{"iid":7,"title":"Fix crash","description":"Details","state":"opened",
+                "source_branch":"fix-crash","target_branch":"main","draft":false,
+                "created_at":"2024-01-02T00:00:00Z","author":{"username":"bob"},"labels":[],
+                "head_pipeline":{"status":"failed"},"has_conflicts":false,
+                "detailed_merge_status":"not_approved"}"#,
+        )
+        .unwrap();
+        let status = status_from(&mr, "https://gitlab.com/o/r/-/merge_requests/7", "w1");
+        assert_eq!(status.check_status, Some(CheckStatus::Failure));
+        assert_eq!(status.display_status, PrDisplayStatus::Review);
+
+        let pr = pull_request(mr);
+        assert_eq!(pr.head_ref_name, "fix-crash");
+        assert_eq!(pr.base_ref_name, "main");
+    }
+}
@@ -0,0 +1,158 @@
+//! Staging and discarding single hunks
+//!
+//! Hunks come from the uncommitted diff (`git diff HEAD`, see
+//! `git_status::get_git_diff`), so each one describes HEAD → working tree. A
+//! hunk is turned back into a one-hunk patch and fed to `git apply`: onto the
+//! index to stage it, reversed onto the index and working tree to discard it.
+
+use std::path::Path;
+
+use super::git_queue;
+use super::git_status::DiffHunk;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// A one-hunk patch of `file`. Hunks starting at line 0 add or delete the
+/// whole file.
+pub fn hunk_patch(file: &str, hunk: &DiffHunk) -> String {
+    let mut patch = format!("diff --git a/{file} b/{file}\n");
+    if hunk.old_start == 0 && hunk.old_lines == 0 {
+        patch.push_str(&format!(
+            "new file mode 100644\n--- /dev/null\n+++ b/{file}\n"
+        ));
+    } else if hunk.new_start == 0 && hunk.new_lines == 0 {
+        patch.push_str(&format!(
+            "deleted file mode 100644\n--- a/{file}\n+++ /dev/null\n"
+        ));
+    } else {
+        patch.push_str(&format!("--- a/{file}\n+++ b/{file}\n"));
+    }
+    patch.push_str(&hunk.header);
+    patch.push('\n');
+    for line in &hunk.lines {
+        let prefix = match line.line_type.as_str() {
+            "addition" => '+',
+            "deletion" => '-',
+            _ => ' ',
+        };
+        patch.push(prefix);
+        patch.push_str(&line.content);
+        patch.push('\n');
+    }
+    patch
+}
+
+/// Run `git apply` with a patch, returning whether it applied
+fn git_apply(repo_path: &str, patch: &str, args: &[&str]) -> Result<bool, String> {
+    let patch_path = std::env::temp_dir().join(format!("jean-hunk-{}.patch", uuid::Uuid::new_v4()));
+    std::fs::write(&patch_path, patch).map_err(|e| format!("Failed to write patch: {e}"))?;
+    let output = silent_command("git")
+        .arg("apply")
+        .args(args)
+        .args(["--recount", "--whitespace=nowarn"])
+        .arg(&patch_path)
+        .current_dir(repo_path)
+        .output_audited();
+    let _ = std::fs::remove_file(&patch_path);
+    let output = output.map_err(|e| format!("Failed to run git apply: {e}"))?;
+    if !output.status.success() {
+        log::trace!(
+            "git apply {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.status.success())
+}
+
+fn check_file(worktree_path: &str, file: &str) -> Result<(), String> {
+    if file.is_empty() || Path::new(file).is_absolute() || file.split('/').any(|c| c == "..") {
+        return Err(format!("Invalid file path: {file}"));
+    }
+    if !Path::new(worktree_path).is_dir() {
+        return Err(format!("Worktree not found: {worktree_path}"));
+    }
+    Ok(())
+}
+
+/// Stage a hunk of `file` (a no-op when it is already staged)
+pub fn stage_hunk(worktree_path: &str, file: &str, hunk: &DiffHunk) -> Result<(), String> {
+    check_file(worktree_path, file)?;
+    let _queued = git_queue::lock(worktree_path);
+    let patch = hunk_patch(file, hunk);
+    if git_apply(worktree_path, &patch, &["--cached"])? {
+        return Ok(());
+    }
+    if git_apply(worktree_path, &patch, &["--cached", "--reverse", "--check"])? {
+        log::trace!("Hunk of {file} is already staged");
+        return Ok(());
+    }
+    Err(format!(
+        "Failed to stage hunk of {file}: it no longer matches the file, refresh the diff"
+    ))
+}
+
+/// Revert a hunk of `file` to HEAD, in the index (if staged) and the working
+/// tree
+pub fn discard_hunk(worktree_path: &str, file: &str, hunk: &DiffHunk) -> Result<(), String> {
+    check_file(worktree_path, file)?;
+    let _queued = git_queue::lock(worktree_path);
+    let patch = hunk_patch(file, hunk);
+    if !git_apply(worktree_path, &patch, &["--reverse", "--check"])? {
+        return Err(format!(
+            "Failed to discard hunk of {file}: it no longer matches the file, refresh the diff"
+        ));
+    }
+    // Unstage first; an unstaged hunk simply doesn't apply to the index
+    git_apply(worktree_path, &patch, &["--cached", "--reverse"])?;
+    if git_apply(worktree_path, &patch, &["--reverse"])? {
+        Ok(())
+    } else {
+        Err(format!("Failed to discard hunk of {file}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::git_status::DiffLine;
+
+    fn line(line_type: &str, content: &str) -> DiffLine {
+        DiffLine {
+            line_type: line_type.to_string(),
+            content: content.to_string(),
+            old_line_number: None,
+            new_line_number: None,
+        }
+    }
+
+    #[test]
+    fn test_hunk_patch() {
+        let hunk = DiffHunk {
+            header: "@@ -3,2 +3,2 @@ fn main()".to_string(),
+            old_start: 3,
+            old_lines: 2,
+            new_start: 3,
+            new_lines: 2,
+            lines: vec![
+                line("context", "    let a = 1;"),
+                line("deletion", "    let b = 2;"),
+                line("addition", "    let b = 3;"),
+            ],
+        };
+        assert_eq!(
+            hunk_patch("src/main.rs", &hunk),
+            "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n\
+             @@ -3,2 +3,2 @@ fn main()\n     let a = 1;\n-    let b = 2;\n+    let b = 3;\n"
+        );
+
+        let new_file = DiffHunk {
+            header: "@@ -0,0 +1 @@".to_string(),
+            old_start: 0,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 1,
+            lines: vec![line("addition", "hello")],
+        };
+        assert!(hunk_patch("a.txt", &new_file).contains("--- /dev/null\n+++ b/a.txt\n"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Bulk project import
+//!
+//! Finds git repositories under a directory so they can be previewed and
+//! added in one go, optionally filed into sidebar folders that mirror the
+//! directory structure.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Directories never worth descending into
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+/// Default scan depth below the root directory
+pub const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Maximum sidebar folder nesting (matches `create_folder`)
+const MAX_FOLDER_DEPTH: usize = 3;
+
+/// A repository found by a scan
+#[derive(Debug, Clone, Serialize)]
+pub struct ScannedRepo {
+    /// Absolute path to the repository
+    pub path: String,
+    /// Repository (directory) name
+    pub name: String,
+    /// Directories between the scan root and the repository
+    pub relative_dirs: Vec<String>,
+    /// Whether the repository is already a project
+    pub already_added: bool,
+}
+
+fn walk(dir: &Path, rel: &mut Vec<String>, max_depth: usize, found: &mut Vec<ScannedRepo>) {
+    // `.git` files are worktrees and submodules; only real clones count
+    if dir.join(".git").is_dir() {
+        if let Some(name) = dir.file_name().and_then(|n| n.to_str()) {
+            found.push(ScannedRepo {
+                path: dir.to_string_lossy().to_string(),
+                name: name.to_string(),
+                relative_dirs: rel[..rel.len().saturating_sub(1)].to_vec(),
+                already_added: false,
+            });
+        }
+        return;
+    }
+    if rel.len() >= max_depth {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            (!name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()))
+                .then_some((name, entry.path()))
+        })
+        .collect();
+    subdirs.sort();
+    for (name, path) in subdirs {
+        rel.push(name);
+        walk(&path, rel, max_depth, found);
+        rel.pop();
+    }
+}
+
+/// Find git repositories under `root` (the root itself included), sorted by path
+pub fn scan(root: &Path, max_depth: usize) -> Vec<ScannedRepo> {
+    let mut found = Vec::new();
+    walk(root, &mut Vec::new(), max_depth, &mut found);
+    found
+}
+
+/// Sidebar folder names for a repository's relative directories, with any
+/// directories beyond the folder nesting limit merged into the last folder
+pub fn folder_names(relative_dirs: &[String]) -> Vec<String> {
+    if relative_dirs.len() <= MAX_FOLDER_DEPTH {
+        return relative_dirs.to_vec();
+    }
+    let mut names = relative_dirs[..MAX_FOLDER_DEPTH - 1].to_vec();
+    names.push(relative_dirs[MAX_FOLDER_DEPTH - 1..].join("/"));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_finds_nested_repos() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [
+            "api/.git",
+            "clients/acme/web/.git",
+            "node_modules/pkg/.git",
+            "notes",
+        ] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        // A worktree checkout inside a repo-less directory is skipped
+        std::fs::create_dir_all(root.path().join("wt")).unwrap();
+        std::fs::write(root.path().join("wt/.git"), "gitdir: /elsewhere").unwrap();
+
+        let repos = scan(root.path(), DEFAULT_MAX_DEPTH);
+        let names: Vec<_> = repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "web"]);
+        assert_eq!(repos[1].relative_dirs, vec!["clients", "acme"]);
+    }
+
+    #[test]
+    fn test_folder_names() {
+        let dirs: Vec<String> = ["a", "b", "c", "d"].map(String::from).to_vec();
+        assert_eq!(folder_names(&dirs[..2]), vec!["a", "b"]);
+        assert_eq!(folder_names(&dirs), vec!["a", "b", "c/d"]);
+    }
+}
@@ -0,0 +1,512 @@
+//! Jira issue provider
+//!
+//! Lists Jira issues and loads them as worktree context, the same way GitHub
+//! issues are: a markdown file in the shared `git-context` directory
+//! (`jira-issue-{KEY}.md`) with reference tracking per worktree. Configured
+//! in preferences with the site URL and an API token; with an account email
+//! the token is a Jira Cloud API token (basic auth), without one it's a Data
+//! Center personal access token (bearer auth).
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::branch_naming::BranchNameScheme;
+use super::github_issues::{
+    add_tracker_reference, get_tracker_context_path, get_worktree_tracker_refs,
+    remove_tracker_reference, slugify_issue_title,
+};
+use super::storage::load_projects_data;
+use super::tickets;
+use super::types::Worktree;
+
+/// Provider name in context references and file names
+const PROVIDER: &str = "jira";
+
+/// Issues shown when no JQL is given
+const DEFAULT_JQL: &str =
+    "assignee = currentUser() AND statusCategory != Done ORDER BY updated DESC";
+
+/// Fields requested for issue lists
+const LIST_FIELDS: &str = "summary,status,issuetype,assignee,updated";
+
+/// Fields requested when loading an issue as context
+const CONTEXT_FIELDS: &str = "summary,description,status,issuetype,assignee,updated,comment";
+
+/// Jira issue in a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JiraIssue {
+    pub key: String,
+    pub title: String,
+    pub status: Option<String>,
+    pub issue_type: Option<String>,
+    pub assignee: Option<String>,
+    pub updated: Option<String>,
+    pub url: String,
+}
+
+/// Loaded Jira issue context info returned to frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedJiraContext {
+    pub key: String,
+    pub title: String,
+    pub comment_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    issues: Vec<RawIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawIssue {
+    key: String,
+    #[serde(default)]
+    fields: RawFields,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawFields {
+    summary: String,
+    description: Option<String>,
+    status: Option<Named>,
+    issuetype: Option<Named>,
+    assignee: Option<RawUser>,
+    updated: Option<String>,
+    comment: Option<RawComments>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawUser {
+    #[serde(rename = "displayName")]
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawComments {
+    #[serde(default)]
+    comments: Vec<RawComment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawComment {
+    author: Option<RawUser>,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    created: String,
+}
+
+/// Jira connection settings from preferences
+struct JiraConfig {
+    base_url: String,
+    email: Option<String>,
+    token: String,
+}
+
+impl JiraConfig {
+    async fn load(app: &AppHandle) -> Result<Self, String> {
+        let prefs = crate::load_preferences(app.clone()).await?;
+        let base_url = prefs
+            .jira_base_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| "Jira is not configured: set the Jira site URL".to_string())?;
+        let token = prefs
+            .jira_api_token
+            .filter(|t| !t.trim().is_empty())
+            .ok_or_else(|| "Jira is not configured: set a Jira API token".to_string())?;
+        Ok(Self {
+            base_url,
+            email: prefs.jira_email.filter(|e| !e.trim().is_empty()),
+            token,
+        })
+    }
+
+    /// Jira Cloud retired `/search` in favour of `/search/jql`
+    fn is_cloud(&self) -> bool {
+        self.base_url.contains(".atlassian.net")
+    }
+
+    fn issue_url(&self, key: &str) -> String {
+        format!("{}/browse/{key}", self.base_url)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, String> {
+        let client = reqwest::Client::builder()
+            .user_agent("Jean-App/1.0")
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+        let request = client
+            .get(format!("{}/rest/api/2/{path}", self.base_url))
+            .query(query)
+            .header("Accept", "application/json");
+        let request = match &self.email {
+            Some(email) => request.basic_auth(email, Some(&self.token)),
+            None => request.bearer_auth(&self.token),
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Jira: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Jira API returned status: {}", response.status()));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Jira API response: {e}"))
+    }
+}
+
+/// Check an issue key (`PROJ-123`) before it goes into URLs and file names
+fn validate_issue_key(key: &str) -> Result<(), String> {
+    if tickets::is_issue_key(key) {
+        Ok(())
+    } else {
+        Err(format!("Invalid Jira issue key: {key}"))
+    }
+}
+
+/// Generate a branch name from a Jira issue
+/// e.g., PAY-42 "Fix refunds" -> "PAY-42-fix-refunds", or shaped by the
+/// project's naming scheme with the key in front of the last component
+pub fn generate_branch_name_from_jira(
+    key: &str,
+    summary: &str,
+    scheme: Option<&BranchNameScheme>,
+) -> String {
+    let slug = slugify_issue_title(summary);
+    let name = scheme
+        .and_then(|scheme| scheme.apply(&slug, None))
+        .unwrap_or(slug);
+    if name.is_empty() {
+        return key.to_string();
+    }
+    tickets::thread_into_branch(&name, key)
+}
+
+/// Format a Jira issue as markdown for the context file
+fn format_jira_context_markdown(issue: &RawIssue, url: &str) -> String {
+    let fields = &issue.fields;
+    let mut content = String::new();
+
+    content.push_str(&format!(
+        "# Jira Issue {}: {}\n\n",
+        issue.key, fields.summary
+    ));
+
+    let mut meta = vec![format!("**Link:** {url}")];
+    if let Some(issue_type) = &fields.issuetype {
+        meta.push(format!("**Type:** {}", issue_type.name));
+    }
+    if let Some(status) = &fields.status {
+        meta.push(format!("**Status:** {}", status.name));
+    }
+    if let Some(assignee) = &fields.assignee {
+        meta.push(format!("**Assignee:** {}", assignee.display_name));
+    }
+    content.push_str(&meta.join("  \n"));
+    content.push_str("\n\n---\n\n");
+
+    content.push_str("## Description\n\n");
+    match fields.description.as_deref().filter(|d| !d.is_empty()) {
+        Some(description) => content.push_str(description),
+        None => content.push_str("*No description provided.*"),
+    }
+    content.push_str("\n\n");
+
+    let comments = fields
+        .comment
+        .as_ref()
+        .map(|c| c.comments.as_slice())
+        .unwrap_or_default();
+    if !comments.is_empty() {
+        content.push_str("## Comments\n\n");
+        for comment in comments {
+            let author = comment
+                .author
+                .as_ref()
+                .map(|a| a.display_name.as_str())
+                .unwrap_or("Unknown");
+            content.push_str(&format!("### {author} ({})\n\n", comment.created));
+            content.push_str(&comment.body);
+            content.push_str("\n\n---\n\n");
+        }
+    }
+
+    content.push_str("---\n\n");
+    content.push_str("*Investigate this issue and propose a solution.*\n");
+
+    content
+}
+
+/// List Jira issues matching a JQL query (default: open issues assigned to me)
+#[tauri::command]
+pub async fn list_jira_issues(
+    app: AppHandle,
+    jql: Option<String>,
+) -> Result<Vec<JiraIssue>, String> {
+    log::trace!("Listing Jira issues");
+    let config = JiraConfig::load(&app).await?;
+
+    let jql = jql
+        .filter(|q| !q.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_JQL.to_string());
+    let path = if config.is_cloud() {
+        "search/jql"
+    } else {
+        "search"
+    };
+    let response: SearchResponse = config
+        .get(
+            path,
+            &[("jql", &jql), ("fields", LIST_FIELDS), ("maxResults", "50")],
+        )
+        .await?;
+
+    let issues: Vec<JiraIssue> = response
+        .issues
+        .into_iter()
+        .map(|issue| JiraIssue {
+            url: config.issue_url(&issue.key),
+            key: issue.key,
+            title: issue.fields.summary,
+            status: issue.fields.status.map(|s| s.name),
+            issue_type: issue.fields.issuetype.map(|t| t.name),
+            assignee: issue.fields.assignee.map(|a| a.display_name),
+            updated: issue.fields.updated,
+        })
+        .collect();
+
+    log::trace!("Found {} Jira issues", issues.len());
+    Ok(issues)
+}
+
+/// Load/refresh Jira issue context for a worktree
+///
+/// Context is stored in shared location: `git-context/jira-issue-{KEY}.md`
+#[tauri::command]
+pub async fn load_jira_issue_context(
+    app: AppHandle,
+    worktree_id: String,
+    issue_key: String,
+) -> Result<LoadedJiraContext, String> {
+    log::trace!("Loading Jira issue {issue_key} context for worktree {worktree_id}");
+    validate_issue_key(&issue_key)?;
+    let config = JiraConfig::load(&app).await?;
+
+    let issue: RawIssue = config
+        .get(&format!("issue/{issue_key}"), &[("fields", CONTEXT_FIELDS)])
+        .await?;
+
+    let context_file = get_tracker_context_path(&app, PROVIDER, &issue.key)?;
+    if let Some(dir) = context_file.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create git-context directory: {e}"))?;
+    }
+    let context_content = format_jira_context_markdown(&issue, &config.issue_url(&issue.key));
+    crate::encryption::write(&context_file, context_content)
+        .map_err(|e| format!("Failed to write Jira issue context file: {e}"))?;
+
+    add_tracker_reference(&app, PROVIDER, &issue.key, &worktree_id)?;
+
+    let comment_count = issue.fields.comment.map_or(0, |c| c.comments.len());
+    log::trace!(
+        "Jira issue context loaded for {} ({comment_count} comments)",
+        issue.key
+    );
+
+    Ok(LoadedJiraContext {
+        key: issue.key,
+        title: issue.fields.summary,
+        comment_count,
+    })
+}
+
+/// List all loaded Jira issue contexts for a worktree
+#[tauri::command]
+pub async fn list_loaded_jira_contexts(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Vec<LoadedJiraContext>, String> {
+    log::trace!("Listing loaded Jira contexts for worktree {worktree_id}");
+
+    let mut contexts = Vec::new();
+    for (_, key) in get_worktree_tracker_refs(&app, &worktree_id, Some(PROVIDER))? {
+        let context_file = get_tracker_context_path(&app, PROVIDER, &key)?;
+        if let Ok(content) = crate::encryption::read_to_string(&context_file) {
+            // Parse title from first line: "# Jira Issue KEY: Title"
+            let title = content
+                .lines()
+                .next()
+                .and_then(|line| line.split_once(": "))
+                .map(|(_, title)| title.to_string())
+                .unwrap_or_else(|| key.clone());
+            let comment_count = content.lines().filter(|l| l.starts_with("### ")).count();
+            contexts.push(LoadedJiraContext {
+                key,
+                title,
+                comment_count,
+            });
+        }
+    }
+
+    Ok(contexts)
+}
+
+/// Remove a loaded Jira issue context for a worktree
+#[tauri::command]
+pub async fn remove_jira_issue_context(
+    app: AppHandle,
+    worktree_id: String,
+    issue_key: String,
+) -> Result<(), String> {
+    log::trace!("Removing Jira issue {issue_key} context for worktree {worktree_id}");
+    validate_issue_key(&issue_key)?;
+
+    // If orphaned, delete the shared file immediately
+    if remove_tracker_reference(&app, PROVIDER, &issue_key, &worktree_id)? {
+        let context_file = get_tracker_context_path(&app, PROVIDER, &issue_key)?;
+        if context_file.exists() {
+            std::fs::remove_file(&context_file)
+                .map_err(|e| format!("Failed to remove Jira issue context file: {e}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a worktree for a Jira issue, with a branch named after its key and
+/// the issue loaded as context
+#[tauri::command]
+pub async fn create_worktree_from_jira_issue(
+    app: AppHandle,
+    project_id: String,
+    issue_key: String,
+    base_branch: Option<String>,
+    template_id: Option<String>,
+) -> Result<Worktree, String> {
+    log::trace!("Creating worktree for Jira issue {issue_key}");
+    validate_issue_key(&issue_key)?;
+    let config = JiraConfig::load(&app).await?;
+    let issue: RawIssue = config
+        .get(&format!("issue/{issue_key}"), &[("fields", "summary")])
+        .await?;
+
+    let name = {
+        let data = load_projects_data(&app)?;
+        let project = data
+            .find_project(&project_id)
+            .ok_or_else(|| format!("Project not found: {project_id}"))?;
+        let template = match &template_id {
+            Some(id) => Some(
+                project
+                    .find_template(id)
+                    .ok_or_else(|| format!("Worktree template not found: {id}"))?,
+            ),
+            None => None,
+        };
+        let scheme = BranchNameScheme::for_project(project, template.map(|t| t.name.clone()));
+        let prefix = template
+            .and_then(|t| t.branch_prefix.clone())
+            .unwrap_or_default();
+        let branch = format!(
+            "{prefix}{}",
+            generate_branch_name_from_jira(&issue.key, &issue.fields.summary, scheme.as_ref())
+        );
+
+        // Check if this branch name already exists, if so, add a suffix
+        let mut candidate = branch.clone();
+        let mut counter = 2;
+        while data.worktree_name_exists(&project_id, &candidate) {
+            candidate = format!("{branch}-{counter}");
+            counter += 1;
+        }
+        candidate
+    };
+
+    let worktree = super::create_worktree(
+        app.clone(),
+        project_id,
+        base_branch,
+        None,
+        None,
+        Some(name),
+        template_id,
+        None,
+    )
+    .await?;
+
+    if let Err(e) = load_jira_issue_context(app, worktree.id.clone(), issue.key).await {
+        log::warn!("Failed to load Jira issue context: {e}");
+    }
+
+    Ok(worktree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_branch_name_from_jira() {
+        assert_eq!(
+            generate_branch_name_from_jira("PAY-42", "Refunds fail for EUR", None),
+            "PAY-42-refunds-fail-for-eur"
+        );
+
+        let scheme = BranchNameScheme {
+            template: "{user}/{type}/{slug}".to_string(),
+            user: Some("ada".to_string()),
+            kind: Some("fix".to_string()),
+        };
+        assert_eq!(
+            generate_branch_name_from_jira("PAY-42", "Refunds fail", Some(&scheme)),
+            "ada/fix/PAY-42-refunds-fail"
+        );
+
+        assert!(validate_issue_key("PAY-42").is_ok());
+        for bad in ["PAY", "42-1", "PAY-4a", "../PAY-1"] {
+            assert!(validate_issue_key(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_format_jira_context_markdown() {
+        let issue: RawIssue = serde_json::from_value(serde_json::json!({
+            "key": "PAY-42",
+            "fields": {
+                "summary": "Refunds fail",
+                "status": { "name": "In Progress" },
+                "description": null,
+                "comment": { "comments": [
+                    { "author": { "displayName": "Ada" }, "body": "Seen in EUR", "created": "2024-01-01" }
+                ]}
+            }
+        }))
+        .unwrap();
+
+        let content = format_jira_context_markdown(&issue, "https://x.atlassian.net/browse/PAY-42");
+        assert!(content.starts_with("# Jira Issue PAY-42: Refunds fail\n"));
+        assert!(content.contains("**Status:** In Progress"));
+        assert!(content.contains("*No description provided.*"));
+        assert!(content.contains("### Ada (2024-01-01)\n\nSeen in EUR"));
+    }
+}
@@ -0,0 +1,266 @@
+//! Jujutsu (jj) repositories
+//!
+//! A jj repository with a (usually colocated) git backend has a `.jj`
+//! directory. Its worktrees are jj workspaces (`jj workspace add`), which have
+//! no `.git` of their own, so git commands fail in them. For these repos
+//! branch listing, worktree creation/removal and branch status go through jj:
+//! bookmarks stand in for branches, the working-copy commit `@` holds the
+//! uncommitted changes and its parent `@-` plays the part of HEAD.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::git_queue;
+use super::git_status::{parse_diff, ActiveWorktreeInfo, GitBranchStatus};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Whether a directory is (a workspace of) a jj repository
+pub fn is_jj_repo(path: &Path) -> bool {
+    path.join(".jj").is_dir()
+}
+
+/// Run jj in a repository or workspace, returning its stdout
+fn jj(repo_path: &str, args: &[&str]) -> Result<String, String> {
+    let output = silent_command("jj")
+        .args(["--color", "never"])
+        .args(args)
+        .current_dir(repo_path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run jj {}: {e}", args[0]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("jj {} failed: {}", args[0], stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Revset symbol of a bookmark, quoted so names like `feature/x` parse
+fn bookmark(name: &str) -> String {
+    format!("\"{name}\"")
+}
+
+fn remote_bookmark(name: &str) -> String {
+    format!("\"{name}\"@origin")
+}
+
+/// Workspace name of a worktree: its directory name, unique per project
+fn workspace_name(worktree_path: &str) -> String {
+    Path::new(worktree_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| worktree_path.to_string())
+}
+
+/// Local bookmarks of a repository
+pub fn list_bookmarks(repo_path: &str) -> Result<Vec<String>, String> {
+    let output = jj(
+        repo_path,
+        &["bookmark", "list", "-T", r#"if(!remote, name ++ "\n")"#],
+    )
+    .map_err(|e| format!("Failed to list bookmarks: {e}"))?;
+    let mut bookmarks: Vec<String> = output
+        .lines()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    bookmarks.dedup();
+    Ok(bookmarks)
+}
+
+/// Add a workspace at `worktree_path` with a new change on top of
+/// `revision`, and point a new bookmark at it if one is given
+pub fn add_workspace(
+    repo_path: &str,
+    worktree_path: &str,
+    revision: &str,
+    new_bookmark: Option<&str>,
+) -> Result<(), String> {
+    log::trace!("Adding jj workspace at {worktree_path} on {revision}");
+    let _queued = git_queue::lock(repo_path);
+
+    if let Some(parent) = Path::new(worktree_path).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create parent directory: {e}"))?;
+    }
+    let name = workspace_name(worktree_path);
+    jj(
+        repo_path,
+        &[
+            "workspace",
+            "add",
+            "--name",
+            &name,
+            "--revision",
+            &bookmark(revision),
+            worktree_path,
+        ],
+    )
+    .map_err(|e| format!("Failed to create worktree: {e}"))?;
+
+    if let Some(new_bookmark) = new_bookmark {
+        jj(
+            worktree_path,
+            &["bookmark", "create", new_bookmark, "--revision", "@"],
+        )
+        .map_err(|e| format!("Failed to create bookmark {new_bookmark}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Forget a workspace and delete its directory
+pub fn forget_workspace(repo_path: &str, worktree_path: &str) -> Result<(), String> {
+    log::trace!("Forgetting jj workspace at {worktree_path}");
+    let _queued = git_queue::lock(repo_path);
+    if let Err(e) = jj(
+        repo_path,
+        &["workspace", "forget", &workspace_name(worktree_path)],
+    ) {
+        // Already forgotten; still clean up the directory
+        log::warn!("Failed to forget jj workspace, proceeding with cleanup: {e}");
+    }
+    if Path::new(worktree_path).exists() {
+        std::fs::remove_dir_all(worktree_path)
+            .map_err(|e| format!("Failed to remove worktree directory: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Number of commits in a revset (0 if it can't be evaluated, e.g. a
+/// missing remote bookmark)
+fn count(repo_path: &str, revset: &str) -> u32 {
+    jj(
+        repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            revset,
+            "-T",
+            r#"commit_id ++ "\n""#,
+        ],
+    )
+    .map(|out| out.lines().filter(|l| !l.is_empty()).count() as u32)
+    .unwrap_or(0)
+}
+
+fn revision_exists(repo_path: &str, revset: &str) -> bool {
+    jj(
+        repo_path,
+        &["log", "--no-graph", "-r", revset, "-T", "commit_id"],
+    )
+    .is_ok()
+}
+
+/// Lines added and removed by a `jj diff` range
+fn diff_stats(repo_path: &str, args: &[&str]) -> (u32, u32) {
+    let mut command = vec!["diff", "--git"];
+    command.extend_from_slice(args);
+    jj(repo_path, &command)
+        .map(|out| {
+            parse_diff(&out)
+                .iter()
+                .fold((0, 0), |(a, r), f| (a + f.additions, r + f.deletions))
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Bookmark of the closest ancestor of the working copy that has one (the
+/// workspace name when there is none)
+fn current_bookmark(repo_path: &str) -> String {
+    jj(
+        repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "latest(::@ & bookmarks())",
+            "-T",
+            r#"local_bookmarks.map(|b| b.name()).join("\n")"#,
+        ],
+    )
+    .ok()
+    .and_then(|out| out.lines().next().map(str::to_string))
+    .filter(|name| !name.is_empty())
+    .unwrap_or_else(|| workspace_name(repo_path))
+}
+
+/// Branch status of a jj workspace, computed like `get_branch_status` with
+/// `@-` as HEAD and `@` as the uncommitted changes
+pub fn branch_status(info: &ActiveWorktreeInfo, fetch: bool) -> Result<GitBranchStatus, String> {
+    let repo_path = info.worktree_path.as_str();
+    let base_branch = &info.base_branch;
+    if fetch {
+        let _slot = git_queue::fetch_slot();
+        if let Err(e) = jj(repo_path, &["git", "fetch", "--branch", base_branch]) {
+            log::trace!("jj git fetch of {base_branch} failed: {e}");
+        }
+    }
+
+    let current_branch = current_bookmark(repo_path);
+    let base = bookmark(base_branch);
+    let origin_base = remote_bookmark(base_branch);
+
+    let behind_count = count(repo_path, &format!("@-..{origin_base}"));
+    let ahead_count = count(repo_path, &format!("{origin_base}..@-"));
+    let (uncommitted_added, uncommitted_removed) = diff_stats(repo_path, &["-r", "@"]);
+    let fork_point = format!("heads(::@- & ::{origin_base})");
+    let (branch_diff_added, branch_diff_removed) =
+        diff_stats(repo_path, &["--from", &fork_point, "--to", "@-"]);
+    let base_branch_ahead_count = count(repo_path, &format!("{origin_base}..{base}"));
+    let base_branch_behind_count = count(repo_path, &format!("{base}..{origin_base}"));
+    let worktree_ahead_count = count(repo_path, &format!("{base}..@-"));
+
+    let origin_current = remote_bookmark(&current_branch);
+    let unpushed_count = if current_branch != *base_branch {
+        if revision_exists(repo_path, &origin_current) {
+            count(repo_path, &format!("{origin_current}..@-"))
+        } else {
+            worktree_ahead_count
+        }
+    } else {
+        base_branch_ahead_count
+    };
+
+    let checked_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(GitBranchStatus {
+        worktree_id: info.worktree_id.clone(),
+        current_branch,
+        base_branch: base_branch.clone(),
+        behind_count,
+        ahead_count,
+        has_updates: behind_count > 0,
+        checked_at,
+        uncommitted_added,
+        uncommitted_removed,
+        branch_diff_added,
+        branch_diff_removed,
+        base_branch_ahead_count,
+        base_branch_behind_count,
+        worktree_ahead_count,
+        unpushed_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_name_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_jj_repo(dir.path()));
+        std::fs::create_dir(dir.path().join(".jj")).unwrap();
+        assert!(is_jj_repo(dir.path()));
+
+        assert_eq!(
+            workspace_name("/home/ann/jean/api/fuzzy-otter"),
+            "fuzzy-otter"
+        );
+        assert_eq!(remote_bookmark("feature/x"), "\"feature/x\"@origin");
+    }
+}
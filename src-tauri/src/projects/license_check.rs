@@ -0,0 +1,530 @@
+//! License check of new dependencies
+//!
+//! Compares the lockfiles of a branch (Cargo.lock and package-lock.json, at
+//! any depth) with those at its merge-base, and checks the license of every
+//! package the branch adds against the project's `LicensePolicy`. Licenses
+//! come from package-lock.json itself and from `cargo metadata`. When the
+//! policy is violated, `create_pr_with_ai_content` is blocked, unless the
+//! policy only warns or the check is overridden with a reason.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::storage::load_projects_data;
+use super::types::{LicensePolicy, Worktree};
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// A package added by the branch
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NewDependency {
+    /// `cargo` or `npm`
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    /// SPDX expression, when the package declares one
+    pub license: Option<String>,
+    /// Lockfile the package was added to, relative to the worktree
+    pub lockfile: String,
+}
+
+/// A new dependency whose license the policy doesn't allow
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseViolation {
+    pub dependency: NewDependency,
+    pub reason: String,
+}
+
+/// Result of checking a branch's new dependencies
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseReport {
+    pub dependencies: Vec<NewDependency>,
+    pub violations: Vec<LicenseViolation>,
+}
+
+/// An SPDX expression in disjunctive normal form: any one of the alternatives
+/// is enough, and every license of an alternative applies
+type Alternatives = Vec<Vec<String>>;
+
+struct SpdxParser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> SpdxParser<'a> {
+    fn new(expression: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in expression.char_indices() {
+            if c == '(' || c == ')' || c == '/' || c.is_whitespace() {
+                if let Some(s) = start.take() {
+                    tokens.push(&expression[s..i]);
+                }
+                if !c.is_whitespace() {
+                    tokens.push(&expression[i..i + 1]);
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(s) = start {
+            tokens.push(&expression[s..]);
+        }
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn take(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Option<Alternatives> {
+        let mut alternatives = self.and_expr()?;
+        // `/` is the old Cargo spelling of OR (`MIT/Apache-2.0`)
+        while self
+            .peek()
+            .is_some_and(|t| t.eq_ignore_ascii_case("OR") || t == "/")
+        {
+            self.pos += 1;
+            alternatives.extend(self.and_expr()?);
+        }
+        Some(alternatives)
+    }
+
+    fn and_expr(&mut self) -> Option<Alternatives> {
+        let mut alternatives = self.atom()?;
+        while self.peek().is_some_and(|t| t.eq_ignore_ascii_case("AND")) {
+            self.pos += 1;
+            let right = self.atom()?;
+            alternatives = alternatives
+                .iter()
+                .flat_map(|left| {
+                    right.iter().map(move |r| {
+                        let mut all = left.clone();
+                        all.extend(r.iter().cloned());
+                        all
+                    })
+                })
+                .collect();
+        }
+        Some(alternatives)
+    }
+
+    fn atom(&mut self) -> Option<Alternatives> {
+        match self.take()? {
+            "(" => {
+                let inner = self.or_expr()?;
+                (self.take()? == ")").then_some(inner)
+            }
+            ")" | "/" => None,
+            license => {
+                // Exceptions (`Apache-2.0 WITH LLVM-exception`) don't change
+                // which license applies
+                if self.peek().is_some_and(|t| t.eq_ignore_ascii_case("WITH")) {
+                    self.pos += 1;
+                    self.take()?;
+                }
+                Some(vec![vec![license.to_string()]])
+            }
+        }
+    }
+}
+
+/// Parse an SPDX license expression. None when it isn't valid.
+fn parse_expression(expression: &str) -> Option<Alternatives> {
+    let mut parser = SpdxParser::new(expression);
+    let alternatives = parser.or_expr()?;
+    (parser.pos == parser.tokens.len()).then_some(alternatives)
+}
+
+/// Why a license isn't allowed by the policy (None when it is)
+pub fn check_license(policy: &LicensePolicy, license: Option<&str>) -> Option<String> {
+    let license = license
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case("UNKNOWN"));
+    let Some(license) = license else {
+        return policy
+            .deny_unknown
+            .then(|| "No license metadata".to_string());
+    };
+    let Some(alternatives) = parse_expression(license) else {
+        return policy
+            .deny_unknown
+            .then(|| format!("Unrecognized license expression `{license}`"));
+    };
+
+    let listed = |list: &[String], id: &str| list.iter().any(|l| l.trim().eq_ignore_ascii_case(id));
+    let acceptable = |ids: &Vec<String>| {
+        ids.iter().all(|id| {
+            !listed(&policy.deny, id) && (policy.allow.is_empty() || listed(&policy.allow, id))
+        })
+    };
+    if alternatives.iter().any(acceptable) {
+        return None;
+    }
+    let denied = alternatives
+        .iter()
+        .flatten()
+        .find(|id| listed(&policy.deny, id));
+    Some(match denied {
+        Some(id) => format!("{id} is denied"),
+        None => format!("{license} is not in the allow list"),
+    })
+}
+
+/// (name, version, license) of the packages a lockfile resolves
+type Package = (String, String, Option<String>);
+
+/// Packages of a package-lock.json (lockfile version 2 or later)
+fn npm_packages(lockfile: &str) -> Vec<Package> {
+    let Ok(lock) = serde_json::from_str::<serde_json::Value>(lockfile) else {
+        return Vec::new();
+    };
+    let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    packages
+        .iter()
+        // Entries outside node_modules ("" and workspace folders) are the
+        // project's own packages; links point at them
+        .filter(|(path, entry)| path.contains("node_modules/") && entry.get("link").is_none())
+        .filter_map(|(path, entry)| {
+            let name = path.rsplit("node_modules/").next()?.to_string();
+            let version = entry.get("version")?.as_str()?.to_string();
+            let license = entry
+                .get("license")
+                .and_then(|l| l.as_str())
+                .map(str::to_string);
+            Some((name, version, license))
+        })
+        .collect()
+}
+
+/// Third-party packages of a Cargo.lock (workspace members have no source)
+fn cargo_packages(lockfile: &str) -> Vec<Package> {
+    let mut packages = Vec::new();
+    for block in lockfile.split("[[package]]").skip(1) {
+        let value = |key: &str| {
+            block.lines().find_map(|line| {
+                let (k, v) = line.split_once('=')?;
+                (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
+            })
+        };
+        if let (Some(name), Some(version), Some(_)) =
+            (value("name"), value("version"), value("source"))
+        {
+            packages.push((name, version, None));
+        }
+    }
+    packages
+}
+
+/// Licenses of the packages of a Cargo project, from `cargo metadata`
+fn cargo_licenses(dir: &Path) -> Vec<Package> {
+    let output = silent_command("cargo")
+        .args(["metadata", "--format-version", "1", "--offline"])
+        .current_dir(dir)
+        .output_audited();
+    let metadata = match output {
+        Ok(o) if o.status.success() => {
+            serde_json::from_slice::<serde_json::Value>(&o.stdout).unwrap_or_default()
+        }
+        Ok(o) => {
+            log::warn!(
+                "cargo metadata failed in {}: {}",
+                dir.display(),
+                String::from_utf8_lossy(&o.stderr).trim()
+            );
+            return Vec::new();
+        }
+        Err(e) => {
+            log::warn!("Failed to run cargo metadata: {e}");
+            return Vec::new();
+        }
+    };
+    metadata["packages"]
+        .as_array()
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|p| {
+                    Some((
+                        p["name"].as_str()?.to_string(),
+                        p["version"].as_str()?.to_string(),
+                        p["license"].as_str().map(str::to_string),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Packages of `current` whose name doesn't appear in `base`
+fn added(base: &[Package], current: Vec<Package>) -> Vec<Package> {
+    let base_names: HashSet<&str> = base.iter().map(|(name, _, _)| name.as_str()).collect();
+    let mut seen = HashSet::new();
+    current
+        .into_iter()
+        .filter(|(name, version, _)| {
+            !base_names.contains(name.as_str()) && seen.insert((name.clone(), version.clone()))
+        })
+        .collect()
+}
+
+fn git(path: &str, args: &[&str]) -> Result<String, String> {
+    let output = silent_command("git")
+        .args(args)
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Packages added to the worktree's lockfiles since it forked from the
+/// target branch on `remote`
+pub fn new_dependencies(
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+) -> Result<Vec<NewDependency>, String> {
+    let path = worktree.path.as_str();
+    let base = git(
+        path,
+        &["merge-base", &format!("{remote}/{target_branch}"), "HEAD"],
+    )?;
+    let base = base.trim();
+    let lockfiles = git(
+        path,
+        &[
+            "ls-files",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+            "--",
+            "*Cargo.lock",
+            "*package-lock.json",
+        ],
+    )?;
+
+    let mut dependencies = Vec::new();
+    for lockfile in lockfiles.lines() {
+        let Ok(current) = std::fs::read_to_string(Path::new(path).join(lockfile)) else {
+            continue;
+        };
+        // A lockfile the base doesn't have is all new
+        let previous = git(path, &["show", &format!("{base}:{lockfile}")]).unwrap_or_default();
+        let is_cargo = lockfile.ends_with("Cargo.lock");
+        let new = if is_cargo {
+            let new = added(&cargo_packages(&previous), cargo_packages(&current));
+            if new.is_empty() {
+                new
+            } else {
+                let dir = Path::new(path).join(lockfile);
+                let licenses = cargo_licenses(dir.parent().unwrap_or(Path::new(path)));
+                new.into_iter()
+                    .map(|(name, version, _)| {
+                        let license = licenses
+                            .iter()
+                            .find(|(n, v, _)| *n == name && *v == version)
+                            .and_then(|(_, _, l)| l.clone());
+                        (name, version, license)
+                    })
+                    .collect()
+            }
+        } else {
+            added(&npm_packages(&previous), npm_packages(&current))
+        };
+        dependencies.extend(
+            new.into_iter()
+                .map(|(name, version, license)| NewDependency {
+                    ecosystem: if is_cargo { "cargo" } else { "npm" }.to_string(),
+                    name,
+                    version,
+                    license,
+                    lockfile: lockfile.to_string(),
+                }),
+        );
+    }
+    dependencies.sort_by(|a, b| (&a.lockfile, &a.name).cmp(&(&b.lockfile, &b.name)));
+    Ok(dependencies)
+}
+
+/// Check the licenses of a branch's new dependencies against a policy
+pub fn check(
+    policy: &LicensePolicy,
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+) -> Result<LicenseReport, String> {
+    let dependencies = new_dependencies(worktree, remote, target_branch)?;
+    let violations = dependencies
+        .iter()
+        .filter_map(|dependency| {
+            let reason = check_license(policy, dependency.license.as_deref())?;
+            Some(LicenseViolation {
+                dependency: dependency.clone(),
+                reason,
+            })
+        })
+        .collect();
+    Ok(LicenseReport {
+        dependencies,
+        violations,
+    })
+}
+
+fn describe(violation: &LicenseViolation) -> String {
+    let d = &violation.dependency;
+    format!(
+        "{} {} ({}): {}",
+        d.name, d.version, d.lockfile, violation.reason
+    )
+}
+
+/// Check a branch's new dependencies before creating a PR.
+///
+/// Returns the violations to warn about: all of them when the policy only
+/// warns or `override_reason` is given, none when they pass. Otherwise a
+/// violation is an error.
+pub fn enforce(
+    policy: Option<&LicensePolicy>,
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+    override_reason: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let Some(policy) = policy else {
+        return Ok(Vec::new());
+    };
+    let report = check(policy, worktree, remote, target_branch)?;
+    let violations: Vec<String> = report.violations.iter().map(describe).collect();
+    if violations.is_empty() {
+        return Ok(violations);
+    }
+    let overridden = override_reason.is_some_and(|r| !r.trim().is_empty());
+    if policy.warn_only || overridden {
+        log::warn!(
+            "License check found {} violation(s) in {}: {}",
+            violations.len(),
+            worktree.name,
+            violations.join("; ")
+        );
+        return Ok(violations);
+    }
+    Err(format!(
+        "License check failed:\n{}\nRemove these dependencies, change the project's license policy or override with a reason.",
+        violations.join("\n")
+    ))
+}
+
+/// Note appended to the body of a PR created despite license violations
+pub fn warning_note(violations: &[String]) -> String {
+    let list = violations
+        .iter()
+        .map(|v| format!("> - {v}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("\n\n---\n> Dependencies with disallowed licenses:\n{list}")
+}
+
+/// Check the licenses of a worktree's new dependencies without creating a PR
+#[tauri::command]
+pub async fn check_dependency_licenses(
+    app: AppHandle,
+    worktree_path: String,
+) -> Result<LicenseReport, String> {
+    log::trace!("Checking dependency licenses for: {worktree_path}");
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .worktrees
+        .iter()
+        .find(|w| w.path == worktree_path)
+        .ok_or_else(|| format!("Worktree not found: {worktree_path}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let policy = project
+        .license_policy
+        .as_ref()
+        .ok_or_else(|| format!("No license policy is set up for {}", project.name))?;
+    let target_branch = super::stacks::pr_base(&data, project, worktree);
+    check(policy, worktree, project.remote(), target_branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_license() {
+        let policy = LicensePolicy {
+            allow: vec!["MIT".to_string(), "Apache-2.0".to_string()],
+            deny: vec!["GPL-3.0-only".to_string()],
+            deny_unknown: true,
+            warn_only: false,
+        };
+        assert_eq!(check_license(&policy, Some("MIT")), None);
+        assert_eq!(check_license(&policy, Some("MIT/Apache-2.0")), None);
+        assert_eq!(check_license(&policy, Some("GPL-3.0-only OR MIT")), None);
+        assert_eq!(
+            check_license(&policy, Some("Apache-2.0 WITH LLVM-exception")),
+            None
+        );
+        assert_eq!(
+            check_license(&policy, Some("(MIT OR ISC) AND GPL-3.0-only")).as_deref(),
+            Some("GPL-3.0-only is denied")
+        );
+        assert_eq!(
+            check_license(&policy, Some("BSD-3-Clause")).as_deref(),
+            Some("BSD-3-Clause is not in the allow list")
+        );
+        assert!(check_license(&policy, None).is_some());
+        let lenient = LicensePolicy::default();
+        assert_eq!(check_license(&lenient, None), None);
+        assert_eq!(check_license(&lenient, Some("BSD-3-Clause")), None);
+    }
+
+    #[test]
+    fn test_added_packages() {
+        let base = r#"{"lockfileVersion":3,"packages":{
+            "":{"name":"app"},
+            "node_modules/react":{"version":"18.2.0","license":"MIT"}
+        }}"#;
+        let current = r#"{"lockfileVersion":3,"packages":{
+            "":{"name":"app"},
+            "node_modules/react":{"version":"18.3.0","license":"MIT"},
+            "node_modules/foo/node_modules/bar":{"version":"1.0.0","license":"GPL-3.0-only"},
+            "node_modules/local":{"link":true,"resolved":"packages/local"}
+        }}"#;
+        assert_eq!(
+            added(&npm_packages(base), npm_packages(current)),
+            vec![(
+                "bar".to_string(),
+                "1.0.0".to_string(),
+                Some("GPL-3.0-only".to_string())
+            )]
+        );
+
+        let lock = "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+                    [[package]]\nname = \"serde\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(
+            cargo_packages(lock),
+            vec![("serde".to_string(), "1.0.0".to_string(), None)]
+        );
+    }
+}
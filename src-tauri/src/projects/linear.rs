@@ -0,0 +1,449 @@
+//! Linear issue provider
+//!
+//! Lists the Linear issues assigned to the user and loads them as worktree
+//! context (`git-context/linear-issue-{ID}.md`, tracked per worktree like
+//! GitHub and Jira issues). When the worktree's PR opens or merges, loaded
+//! issues are moved to the team's review and done states.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use super::github_issues::{
+    add_tracker_reference, get_tracker_context_path, get_worktree_tracker_refs,
+    remove_tracker_reference,
+};
+use super::tickets;
+
+/// Provider name in context references and file names
+const PROVIDER: &str = "linear";
+
+const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+const ASSIGNED_ISSUES_QUERY: &str = r#"
+query AssignedIssues {
+  viewer {
+    assignedIssues(
+      first: 50
+      orderBy: updatedAt
+      filter: { state: { type: { nin: ["completed", "canceled"] } } }
+    ) {
+      nodes { identifier title url updatedAt priorityLabel state { name } }
+    }
+  }
+}"#;
+
+const ISSUE_QUERY: &str = r#"
+query Issue($id: String!) {
+  issue(id: $id) {
+    identifier title description url priorityLabel
+    state { name }
+    assignee { name }
+    comments(first: 50) { nodes { body createdAt user { name } } }
+  }
+}"#;
+
+const ISSUE_STATES_QUERY: &str = r#"
+query IssueStates($id: String!) {
+  issue(id: $id) {
+    id
+    state { id type }
+    team { states { nodes { id name type position } } }
+  }
+}"#;
+
+const UPDATE_STATE_MUTATION: &str = r#"
+mutation UpdateIssueState($id: String!, $stateId: String!) {
+  issueUpdate(id: $id, input: { stateId: $stateId }) { success }
+}"#;
+
+/// Linear issue in a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinearIssue {
+    pub identifier: String,
+    pub title: String,
+    pub url: String,
+    pub updated_at: Option<String>,
+    pub priority_label: Option<String>,
+    pub state: Option<LinearNamed>,
+}
+
+/// Name-only Linear object (state, user)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinearNamed {
+    pub name: String,
+}
+
+/// Loaded Linear issue context info returned to frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedLinearContext {
+    pub identifier: String,
+    pub title: String,
+    pub comment_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinearIssueDetail {
+    identifier: String,
+    title: String,
+    description: Option<String>,
+    url: String,
+    priority_label: Option<String>,
+    state: Option<LinearNamed>,
+    assignee: Option<LinearNamed>,
+    comments: Nodes<LinearComment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinearComment {
+    body: String,
+    created_at: String,
+    user: Option<LinearNamed>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WorkflowState {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    position: f64,
+}
+
+/// PR events that move Linear issues along
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrEvent {
+    Opened,
+    Merged,
+}
+
+/// Run a GraphQL request and return its `data`
+async fn graphql(app: &AppHandle, query: &str, variables: Value) -> Result<Value, String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    let api_key = prefs
+        .linear_api_key
+        .filter(|k| !k.trim().is_empty())
+        .ok_or_else(|| "Linear is not configured: set a Linear API key".to_string())?;
+
+    let client = reqwest::Client::builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let response = client
+        .post(LINEAR_API_URL)
+        .header("Authorization", api_key.trim())
+        .json(&json!({ "query": query, "variables": variables }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach Linear: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Linear API returned status: {}", response.status()));
+    }
+
+    let mut body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Linear API response: {e}"))?;
+
+    if let Some(message) = body["errors"][0]["message"].as_str() {
+        return Err(format!("Linear API error: {message}"));
+    }
+    Ok(body["data"].take())
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse Linear API response: {e}"))
+}
+
+fn validate_identifier(identifier: &str) -> Result<(), String> {
+    if tickets::is_issue_key(identifier) {
+        Ok(())
+    } else {
+        Err(format!("Invalid Linear issue identifier: {identifier}"))
+    }
+}
+
+/// Format a Linear issue as markdown for the context file
+fn format_linear_context_markdown(issue: &LinearIssueDetail) -> String {
+    let mut content = String::new();
+
+    content.push_str(&format!(
+        "# Linear Issue {}: {}\n\n",
+        issue.identifier, issue.title
+    ));
+
+    let mut meta = vec![format!("**Link:** {}", issue.url)];
+    if let Some(state) = &issue.state {
+        meta.push(format!("**Status:** {}", state.name));
+    }
+    if let Some(priority) = &issue.priority_label {
+        meta.push(format!("**Priority:** {priority}"));
+    }
+    if let Some(assignee) = &issue.assignee {
+        meta.push(format!("**Assignee:** {}", assignee.name));
+    }
+    content.push_str(&meta.join("  \n"));
+    content.push_str("\n\n---\n\n");
+
+    content.push_str("## Description\n\n");
+    match issue.description.as_deref().filter(|d| !d.is_empty()) {
+        Some(description) => content.push_str(description),
+        None => content.push_str("*No description provided.*"),
+    }
+    content.push_str("\n\n");
+
+    if !issue.comments.nodes.is_empty() {
+        content.push_str("## Comments\n\n");
+        for comment in &issue.comments.nodes {
+            let author = comment
+                .user
+                .as_ref()
+                .map(|u| u.name.as_str())
+                .unwrap_or("Unknown");
+            content.push_str(&format!("### {author} ({})\n\n", comment.created_at));
+            content.push_str(&comment.body);
+            content.push_str("\n\n---\n\n");
+        }
+    }
+
+    content.push_str("---\n\n");
+    content.push_str("*Investigate this issue and propose a solution.*\n");
+
+    content
+}
+
+/// State to move an issue to for a PR event, if any
+///
+/// Opened: the team's "In Review" state, else its last in-progress state.
+/// Merged: the team's first done state. Finished issues are left alone.
+fn target_state<'a>(
+    states: &'a [WorkflowState],
+    current: &WorkflowState,
+    event: PrEvent,
+) -> Option<&'a WorkflowState> {
+    if matches!(current.kind.as_str(), "completed" | "canceled") {
+        return None;
+    }
+    let by_position = |a: &&WorkflowState, b: &&WorkflowState| a.position.total_cmp(&b.position);
+    let target = match event {
+        PrEvent::Opened => states
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case("In Review"))
+            .or_else(|| {
+                states
+                    .iter()
+                    .filter(|s| s.kind == "started")
+                    .max_by(by_position)
+            }),
+        PrEvent::Merged => states
+            .iter()
+            .filter(|s| s.kind == "completed")
+            .min_by(by_position),
+    }?;
+    (target.id != current.id).then_some(target)
+}
+
+/// List open Linear issues assigned to the user
+#[tauri::command]
+pub async fn list_linear_issues(app: AppHandle) -> Result<Vec<LinearIssue>, String> {
+    log::trace!("Listing Linear issues");
+    let mut data = graphql(&app, ASSIGNED_ISSUES_QUERY, json!({})).await?;
+    let issues: Nodes<LinearIssue> = parse(data["viewer"]["assignedIssues"].take())?;
+    log::trace!("Found {} Linear issues", issues.nodes.len());
+    Ok(issues.nodes)
+}
+
+/// Load/refresh Linear issue context for a worktree
+///
+/// Context is stored in shared location: `git-context/linear-issue-{ID}.md`
+#[tauri::command]
+pub async fn load_linear_issue_context(
+    app: AppHandle,
+    worktree_id: String,
+    identifier: String,
+) -> Result<LoadedLinearContext, String> {
+    log::trace!("Loading Linear issue {identifier} context for worktree {worktree_id}");
+    validate_identifier(&identifier)?;
+
+    let mut data = graphql(&app, ISSUE_QUERY, json!({ "id": identifier })).await?;
+    if data["issue"].is_null() {
+        return Err(format!("Linear issue not found: {identifier}"));
+    }
+    let issue: LinearIssueDetail = parse(data["issue"].take())?;
+    validate_identifier(&issue.identifier)?;
+
+    let context_file = get_tracker_context_path(&app, PROVIDER, &issue.identifier)?;
+    if let Some(dir) = context_file.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create git-context directory: {e}"))?;
+    }
+    crate::encryption::write(&context_file, format_linear_context_markdown(&issue))
+        .map_err(|e| format!("Failed to write Linear issue context file: {e}"))?;
+
+    add_tracker_reference(&app, PROVIDER, &issue.identifier, &worktree_id)?;
+
+    Ok(LoadedLinearContext {
+        comment_count: issue.comments.nodes.len(),
+        identifier: issue.identifier,
+        title: issue.title,
+    })
+}
+
+/// List all loaded Linear issue contexts for a worktree
+#[tauri::command]
+pub async fn list_loaded_linear_contexts(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Vec<LoadedLinearContext>, String> {
+    log::trace!("Listing loaded Linear contexts for worktree {worktree_id}");
+
+    let mut contexts = Vec::new();
+    for (_, identifier) in get_worktree_tracker_refs(&app, &worktree_id, Some(PROVIDER))? {
+        let context_file = get_tracker_context_path(&app, PROVIDER, &identifier)?;
+        if let Ok(content) = crate::encryption::read_to_string(&context_file) {
+            // Parse title from first line: "# Linear Issue ID: Title"
+            let title = content
+                .lines()
+                .next()
+                .and_then(|line| line.split_once(": "))
+                .map(|(_, title)| title.to_string())
+                .unwrap_or_else(|| identifier.clone());
+            let comment_count = content.lines().filter(|l| l.starts_with("### ")).count();
+            contexts.push(LoadedLinearContext {
+                identifier,
+                title,
+                comment_count,
+            });
+        }
+    }
+
+    Ok(contexts)
+}
+
+/// Remove a loaded Linear issue context for a worktree
+#[tauri::command]
+pub async fn remove_linear_issue_context(
+    app: AppHandle,
+    worktree_id: String,
+    identifier: String,
+) -> Result<(), String> {
+    log::trace!("Removing Linear issue {identifier} context for worktree {worktree_id}");
+    validate_identifier(&identifier)?;
+
+    // If orphaned, delete the shared file immediately
+    if remove_tracker_reference(&app, PROVIDER, &identifier, &worktree_id)? {
+        let context_file = get_tracker_context_path(&app, PROVIDER, &identifier)?;
+        if context_file.exists() {
+            std::fs::remove_file(&context_file)
+                .map_err(|e| format!("Failed to remove Linear issue context file: {e}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Move the Linear issues loaded in a worktree along when its PR opens or merges
+pub async fn sync_issue_status(
+    app: AppHandle,
+    worktree_id: String,
+    event: PrEvent,
+) -> Result<(), String> {
+    let prefs = crate::load_preferences(app.clone()).await?;
+    if !prefs.linear_sync_issue_status || prefs.linear_api_key.is_none() {
+        return Ok(());
+    }
+
+    for (_, identifier) in get_worktree_tracker_refs(&app, &worktree_id, Some(PROVIDER))? {
+        let mut data = graphql(&app, ISSUE_STATES_QUERY, json!({ "id": identifier })).await?;
+        let issue = &mut data["issue"];
+        let (Some(issue_id), Ok(current), Ok(states)) = (
+            issue["id"].as_str().map(str::to_string),
+            parse::<WorkflowState>(issue["state"].take()),
+            parse::<Nodes<WorkflowState>>(issue["team"]["states"].take()),
+        ) else {
+            log::warn!("Linear issue {identifier} has no workflow states");
+            continue;
+        };
+
+        let Some(target) = target_state(&states.nodes, &current, event) else {
+            continue;
+        };
+        let mut result = graphql(
+            &app,
+            UPDATE_STATE_MUTATION,
+            json!({ "id": issue_id, "stateId": target.id }),
+        )
+        .await?;
+        if result["issueUpdate"]["success"].take() == Value::Bool(true) {
+            log::info!("Moved Linear issue {identifier} to {}", target.name);
+        } else {
+            log::warn!("Linear did not update issue {identifier}");
+        }
+    }
+    Ok(())
+}
+
+/// Fire-and-forget `sync_issue_status`
+pub fn spawn_sync_issue_status(app: &AppHandle, worktree_id: &str, event: PrEvent) {
+    let app = app.clone();
+    let worktree_id = worktree_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = sync_issue_status(app, worktree_id, event).await {
+            log::warn!("Failed to update Linear issue status: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(id: &str, name: &str, kind: &str, position: f64) -> WorkflowState {
+        WorkflowState {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            position,
+        }
+    }
+
+    #[test]
+    fn test_target_state() {
+        let states = vec![
+            state("todo", "Todo", "unstarted", 0.0),
+            state("wip", "In Progress", "started", 1.0),
+            state("qa", "QA", "started", 2.0),
+            state("done", "Done", "completed", 3.0),
+            state("shipped", "Shipped", "completed", 4.0),
+        ];
+        let target = |current: &WorkflowState, event| {
+            target_state(&states, current, event).map(|s| s.id.as_str())
+        };
+
+        // No "In Review": the last in-progress state
+        assert_eq!(target(&states[1], PrEvent::Opened), Some("qa"));
+        assert_eq!(target(&states[2], PrEvent::Opened), None);
+        assert_eq!(target(&states[1], PrEvent::Merged), Some("done"));
+        assert_eq!(target(&states[4], PrEvent::Opened), None);
+
+        let mut with_review = states.clone();
+        with_review.push(state("review", "In review", "started", 1.5));
+        assert_eq!(
+            target_state(&with_review, &states[0], PrEvent::Opened).map(|s| s.id.as_str()),
+            Some("review")
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Import of linter results as review findings
+//!
+//! Converts eslint (`--format json`), clippy (`--message-format=json`) and
+//! ruff (`--output-format=json`) output into `ReviewFinding`s and adds them to
+//! the worktree's stored review, so static-analysis and AI findings are
+//! triaged in the same review tab. Importing a tool's output again replaces
+//! the findings previously imported from that tool.
+
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use super::commands::{ReviewFinding, ReviewResponse};
+use super::storage::load_projects_data;
+
+/// Characters of a diagnostic kept in a finding's title
+const MAX_TITLE_LEN: usize = 80;
+
+/// Linters whose JSON output can be imported
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LintTool {
+    Eslint,
+    Clippy,
+    Ruff,
+}
+
+impl LintTool {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Eslint => "eslint",
+            Self::Clippy => "clippy",
+            Self::Ruff => "ruff",
+        }
+    }
+}
+
+fn title(rule: Option<&str>, message: &str) -> String {
+    let first_line = message.lines().next().unwrap_or("").trim();
+    let title = match rule {
+        Some(rule) => format!("{rule}: {first_line}"),
+        None => first_line.to_string(),
+    };
+    if title.chars().count() <= MAX_TITLE_LEN {
+        return title;
+    }
+    let cut: String = title.chars().take(MAX_TITLE_LEN - 1).collect();
+    format!("{cut}…")
+}
+
+/// Path relative to the worktree, as AI findings report them
+fn relative(file: &str, root: &str) -> String {
+    let root = format!("{}/", root.trim_end_matches('/'));
+    file.strip_prefix(&root).unwrap_or(file).to_string()
+}
+
+fn finding(
+    tool: LintTool,
+    severity: &str,
+    file: String,
+    line: Option<u32>,
+    rule: Option<&str>,
+    message: &str,
+    suggestion: Option<String>,
+) -> ReviewFinding {
+    ReviewFinding {
+        severity: severity.to_string(),
+        file,
+        line,
+        title: title(rule, message),
+        description: message.trim().to_string(),
+        suggestion,
+        source: Some(tool.name().to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EslintFile {
+    file_path: String,
+    messages: Vec<EslintMessage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EslintMessage {
+    rule_id: Option<String>,
+    /// 1 = warning, 2 = error
+    severity: u8,
+    message: String,
+    line: Option<u32>,
+}
+
+fn parse_eslint(output: &str, root: &str) -> Result<Vec<ReviewFinding>, String> {
+    let files: Vec<EslintFile> =
+        serde_json::from_str(output).map_err(|e| format!("Failed to parse eslint JSON: {e}"))?;
+    Ok(files
+        .into_iter()
+        .flat_map(|file| {
+            let path = relative(&file.file_path, root);
+            file.messages.into_iter().map(move |m| {
+                let severity = if m.severity >= 2 {
+                    "critical"
+                } else {
+                    "warning"
+                };
+                finding(
+                    LintTool::Eslint,
+                    severity,
+                    path.clone(),
+                    m.line,
+                    m.rule_id.as_deref(),
+                    &m.message,
+                    None,
+                )
+            })
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    level: String,
+    message: String,
+    code: Option<DiagnosticCode>,
+    #[serde(default)]
+    spans: Vec<DiagnosticSpan>,
+    #[serde(default)]
+    children: Vec<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: u32,
+    is_primary: bool,
+    suggested_replacement: Option<String>,
+}
+
+fn parse_clippy(output: &str) -> Result<Vec<ReviewFinding>, String> {
+    let mut findings = Vec::new();
+    for line in output.lines().filter(|l| l.trim_start().starts_with('{')) {
+        let message: CargoMessage =
+            serde_json::from_str(line).map_err(|e| format!("Failed to parse clippy JSON: {e}"))?;
+        let Some(diagnostic) = message
+            .message
+            .filter(|_| message.reason == "compiler-message")
+        else {
+            continue;
+        };
+        let severity = match diagnostic.level.as_str() {
+            "error" => "critical",
+            "warning" => "warning",
+            _ => continue,
+        };
+        // Summary lines ("N warnings emitted") have no location
+        let Some(span) = diagnostic.spans.iter().find(|s| s.is_primary) else {
+            continue;
+        };
+        let suggestion = diagnostic
+            .children
+            .iter()
+            .filter(|c| c.level == "help")
+            .map(|c| {
+                match c
+                    .spans
+                    .iter()
+                    .find_map(|s| s.suggested_replacement.as_ref())
+                {
+                    Some(replacement) => format!("{}: `{replacement}`", c.message),
+                    None => c.message.clone(),
+                }
+            })
+            .next();
+        findings.push(finding(
+            LintTool::Clippy,
+            severity,
+            span.file_name.clone(),
+            Some(span.line_start),
+            diagnostic.code.as_ref().map(|c| c.code.as_str()),
+            &diagnostic.message,
+            suggestion,
+        ));
+    }
+    Ok(findings)
+}
+
+#[derive(Deserialize)]
+struct RuffDiagnostic {
+    code: Option<String>,
+    message: String,
+    filename: String,
+    location: Option<RuffLocation>,
+    fix: Option<RuffFix>,
+}
+
+#[derive(Deserialize)]
+struct RuffLocation {
+    row: u32,
+}
+
+#[derive(Deserialize)]
+struct RuffFix {
+    message: Option<String>,
+}
+
+fn parse_ruff(output: &str, root: &str) -> Result<Vec<ReviewFinding>, String> {
+    let diagnostics: Vec<RuffDiagnostic> =
+        serde_json::from_str(output).map_err(|e| format!("Failed to parse ruff JSON: {e}"))?;
+    Ok(diagnostics
+        .into_iter()
+        .map(|d| {
+            finding(
+                LintTool::Ruff,
+                "warning",
+                relative(&d.filename, root),
+                d.location.map(|l| l.row),
+                d.code.as_deref(),
+                &d.message,
+                d.fix.and_then(|f| f.message),
+            )
+        })
+        .collect())
+}
+
+/// Convert a linter's JSON output into findings
+pub fn parse(tool: LintTool, output: &str, root: &str) -> Result<Vec<ReviewFinding>, String> {
+    match tool {
+        LintTool::Eslint => parse_eslint(output, root),
+        LintTool::Clippy => parse_clippy(output),
+        LintTool::Ruff => parse_ruff(output, root),
+    }
+}
+
+/// Add imported findings to a review, replacing earlier findings from the
+/// same tool. Without a review, a new one is started.
+pub fn merge(
+    review: Option<ReviewResponse>,
+    tool: LintTool,
+    imported: Vec<ReviewFinding>,
+) -> ReviewResponse {
+    let summary = format!("Imported {} findings from {}.", imported.len(), tool.name());
+    match review {
+        Some(mut review) => {
+            review
+                .findings
+                .retain(|f| f.source.as_deref() != Some(tool.name()));
+            review.findings.extend(imported);
+            review
+        }
+        None => {
+            let approval_status = if imported.iter().any(|f| f.severity == "critical") {
+                "changes_requested"
+            } else if imported.is_empty() {
+                "approved"
+            } else {
+                "needs_discussion"
+            };
+            ReviewResponse {
+                summary,
+                findings: imported,
+                approval_status: approval_status.to_string(),
+            }
+        }
+    }
+}
+
+/// Import a linter's JSON output into a worktree's review
+#[tauri::command]
+pub async fn import_lint_findings(
+    app: AppHandle,
+    worktree_id: String,
+    tool: LintTool,
+    output: String,
+) -> Result<ReviewResponse, String> {
+    log::trace!(
+        "Importing {} findings for worktree {worktree_id}",
+        tool.name()
+    );
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let imported = parse(tool, &output, &worktree.path)?;
+
+    let mut ui_state = crate::load_ui_state(app.clone()).await?;
+    let review = ui_state
+        .review_results
+        .get(&worktree_id)
+        .cloned()
+        .and_then(|r| serde_json::from_value(r).ok());
+    let review = merge(review, tool, imported);
+    let value =
+        serde_json::to_value(&review).map_err(|e| format!("Failed to serialize review: {e}"))?;
+    ui_state.review_results.insert(worktree_id, value);
+    crate::save_ui_state(app, ui_state).await?;
+    Ok(review)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_linters() {
+        let eslint = r#"[{"filePath":"/repo/src/app.ts","messages":[
+            {"ruleId":"no-unused-vars","severity":2,"message":"'x' is defined but never used.","line":3}
+        ]}]"#;
+        let findings = parse(LintTool::Eslint, eslint, "/repo").unwrap();
+        assert_eq!(findings[0].file, "src/app.ts");
+        assert_eq!(findings[0].severity, "critical");
+        assert_eq!(
+            findings[0].title,
+            "no-unused-vars: 'x' is defined but never used."
+        );
+
+        let clippy = r#"{"reason":"compiler-artifact"}
+{"reason":"compiler-message","message":{"level":"warning","message":"redundant clone","code":{"code":"clippy::redundant_clone"},"spans":[{"file_name":"src/main.rs","line_start":10,"is_primary":true,"suggested_replacement":null}],"children":[{"level":"help","message":"remove this","spans":[{"file_name":"src/main.rs","line_start":10,"is_primary":true,"suggested_replacement":""}],"children":[]}]}}
+{"reason":"compiler-message","message":{"level":"warning","message":"1 warning emitted","code":null,"spans":[],"children":[]}}"#;
+        let findings = parse(LintTool::Clippy, clippy, "/repo").unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, Some(10));
+        assert_eq!(findings[0].suggestion.as_deref(), Some("remove this: ``"));
+
+        let ruff = r#"[{"code":"F401","message":"`os` imported but unused","filename":"/repo/app.py","location":{"row":1,"column":8},"fix":{"message":"Remove unused import"}}]"#;
+        let findings = parse(LintTool::Ruff, ruff, "/repo").unwrap();
+        assert_eq!(findings[0].file, "app.py");
+        assert_eq!(findings[0].source.as_deref(), Some("ruff"));
+
+        // Re-importing replaces the tool's findings but keeps others
+        let review = merge(None, LintTool::Ruff, findings.clone());
+        assert_eq!(review.approval_status, "needs_discussion");
+        let review = merge(Some(review), LintTool::Ruff, Vec::new());
+        assert!(review.findings.is_empty());
+    }
+}
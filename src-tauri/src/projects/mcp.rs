@@ -0,0 +1,109 @@
+//! MCP servers for Claude sessions
+//!
+//! Servers come from the `mcp` section of the worktree's jean.json and from
+//! the project's settings, which win for servers of the same name. The merged
+//! list is written to `<app data>/mcp-configs/<worktree id>.json` and passed
+//! to the Claude CLI with `--mcp-config`; a file keeps server env values out
+//! of the process list.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+use super::git::read_jean_config;
+use super::storage::load_projects_data;
+use super::types::McpServer;
+
+/// Project servers added to (and replacing) jean.json's
+pub fn merge(
+    jean_json: BTreeMap<String, McpServer>,
+    project: &BTreeMap<String, McpServer>,
+) -> BTreeMap<String, McpServer> {
+    let mut servers = jean_json;
+    servers.extend(project.iter().map(|(name, s)| (name.clone(), s.clone())));
+    servers
+}
+
+/// Check servers entered in project settings
+pub fn validate(servers: &BTreeMap<String, McpServer>) -> Result<(), String> {
+    for (name, server) in servers {
+        if name.trim().is_empty() {
+            return Err("MCP server name cannot be empty".to_string());
+        }
+        if server.command.trim().is_empty() {
+            return Err(format!("MCP server {name} needs a command"));
+        }
+    }
+    Ok(())
+}
+
+/// `--mcp-config` JSON for a set of servers
+pub fn config_json(servers: &BTreeMap<String, McpServer>) -> serde_json::Value {
+    serde_json::json!({ "mcpServers": servers })
+}
+
+/// Write the merged MCP config of a worktree, returning its path (None when
+/// the worktree has no MCP servers)
+pub fn config_file(app: &AppHandle, worktree_id: &str) -> Result<Option<PathBuf>, String> {
+    let data = load_projects_data(app)?;
+    let Some(worktree) = data.find_worktree(worktree_id) else {
+        return Ok(None);
+    };
+    let project_servers = data
+        .find_project(&worktree.project_id)
+        .map(|p| p.mcp_servers.clone())
+        .unwrap_or_default();
+    let jean_json = read_jean_config(&worktree.path)
+        .map(|config| config.mcp)
+        .unwrap_or_default();
+    let servers = merge(jean_json, &project_servers);
+    if servers.is_empty() {
+        return Ok(None);
+    }
+
+    let dir = crate::locations::app_data_dir(app)?.join("mcp-configs");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create MCP config directory: {e}"))?;
+    let path = dir.join(format!("{worktree_id}.json"));
+    let json = serde_json::to_string_pretty(&config_json(&servers))
+        .map_err(|e| format!("Failed to serialize MCP config: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write MCP config: {e}"))?;
+    log::trace!(
+        "Wrote MCP config with {} server(s) for worktree {worktree_id}",
+        servers.len()
+    );
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(command: &str) -> McpServer {
+        McpServer {
+            command: command.to_string(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_and_config_json() {
+        let jean_json = BTreeMap::from([
+            ("db".to_string(), server("db-mcp")),
+            ("docs".to_string(), server("docs-mcp")),
+        ]);
+        let project = BTreeMap::from([("db".to_string(), server("local-db-mcp"))]);
+        let servers = merge(jean_json, &project);
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers["db"].command, "local-db-mcp");
+
+        let json = config_json(&servers);
+        assert_eq!(json["mcpServers"]["docs"]["command"], "docs-mcp");
+        assert!(json["mcpServers"]["docs"].get("env").is_none());
+
+        assert!(validate(&servers).is_ok());
+        assert!(validate(&BTreeMap::from([("x".to_string(), server(" "))])).is_err());
+    }
+}
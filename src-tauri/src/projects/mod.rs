@@ -1,11 +1,52 @@
+pub mod auto_archive;
+pub mod bitbucket;
+pub mod branch_naming;
+pub mod ci_checks;
+pub mod cleanup;
+pub mod code_search;
 mod commands;
+pub mod commit_hooks;
+pub mod compose;
+pub mod context_prefetch;
+pub mod conventional_commits;
+pub mod coverage;
+pub mod dashboard;
+pub mod dev_env;
+pub mod devcontainer;
+pub mod diff_cache;
+pub mod env_profiles;
+pub mod external_diff;
+pub mod file_listing;
+mod fuzzy;
 pub mod git;
+pub mod git_queue;
 pub mod git_status;
 pub mod github_issues;
-mod names;
+pub mod gitlab;
+pub mod hunks;
+pub mod import_scan;
+pub mod jira;
+pub mod jj;
+pub mod license_check;
+pub mod linear;
+pub mod lint_import;
+pub mod mcp;
+pub mod names;
+pub mod packages;
+pub mod pr_checklist;
 pub mod pr_status;
+pub mod repo_provider;
+pub mod review_report;
+pub mod review_sarif;
 pub mod saved_contexts;
+pub mod security_audit;
+pub mod snapshots;
+pub mod stacks;
 pub mod storage;
+pub mod test_runner;
+pub mod tickets;
+pub mod toolchain;
+pub mod trash;
 pub mod types;
 
 // Re-export commands for registration in lib.rs
@@ -0,0 +1,372 @@
+//! Monorepo package detection
+//!
+//! Reads the workspace manifests of a checkout (pnpm-workspace.yaml, the
+//! `workspaces` field of package.json, a Cargo `[workspace]` and Nx
+//! `project.json` files) and lists the packages they declare, so reviews and
+//! run scripts can be scoped to one package.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Directories never searched for packages
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build"];
+
+/// How deep to search for Nx `project.json` files and `**` globs
+const MAX_SEARCH_DEPTH: usize = 5;
+
+/// A package declared by a workspace manifest
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WorkspacePackage {
+    /// Package name from its manifest (directory name if it has none)
+    pub name: String,
+    /// Directory relative to the checkout, with `/` separators
+    pub path: String,
+    /// Manifest that declared it: `pnpm`, `npm`, `cargo` or `nx`
+    pub kind: String,
+}
+
+/// String literals in a line of TOML or YAML (single or double quoted)
+fn quoted_strings(s: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find(['"', '\'']) {
+        let quote = rest.as_bytes()[start] as char;
+        let after = &rest[start + 1..];
+        let Some(end) = after.find(quote) else { break };
+        out.push(&after[..end]);
+        rest = &after[end + 1..];
+    }
+    out
+}
+
+/// `packages:` entries of pnpm-workspace.yaml
+fn parse_pnpm_workspace(content: &str) -> Vec<String> {
+    let mut in_packages = false;
+    let mut patterns = Vec::new();
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        let item = line.trim();
+        if let (true, Some(item)) = (in_packages, item.strip_prefix('-')) {
+            let item = item.trim();
+            let pattern = quoted_strings(item).first().copied().unwrap_or(item);
+            if !pattern.is_empty() {
+                patterns.push(pattern.to_string());
+            }
+        }
+    }
+    patterns
+}
+
+/// `workspaces` of package.json (array or `{ "packages": [...] }`)
+fn parse_npm_workspaces(content: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let workspaces = &json["workspaces"];
+    let list = workspaces
+        .as_array()
+        .or_else(|| workspaces["packages"].as_array());
+    list.into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect()
+}
+
+/// `members` (and `exclude`, prefixed with `!`) of a Cargo `[workspace]`
+fn parse_cargo_workspace(content: &str) -> Vec<String> {
+    let mut in_workspace = false;
+    let mut key: Option<&str> = None;
+    let mut patterns = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.starts_with('[') && !line.contains('=') && key.is_none() {
+            in_workspace = line == "[workspace]";
+            continue;
+        }
+        if !in_workspace {
+            continue;
+        }
+        let values = match key {
+            Some(_) => line,
+            None => match line.split_once('=') {
+                Some((k, v)) if matches!(k.trim(), "members" | "exclude") => {
+                    key = Some(if k.trim() == "members" { "" } else { "!" });
+                    v
+                }
+                _ => continue,
+            },
+        };
+        let prefix = key.unwrap_or("");
+        patterns.extend(
+            quoted_strings(values)
+                .iter()
+                .map(|p| format!("{prefix}{p}")),
+        );
+        if values.contains(']') {
+            key = None;
+        }
+    }
+    patterns
+}
+
+/// Whether a path segment matches a glob segment (`*` wildcards only)
+fn segment_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| segment_matches(rest, &name[i..]))
+        }
+    }
+}
+
+fn subdirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Directories (relative to `root`) matching a workspace glob
+fn expand(root: &Path, rel: &str, segments: &[&str], depth: usize, out: &mut Vec<String>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        out.push(rel.to_string());
+        return;
+    };
+    let join = |name: &str| {
+        if rel.is_empty() {
+            name.to_string()
+        } else {
+            format!("{rel}/{name}")
+        }
+    };
+    match *segment {
+        "" | "." => expand(root, rel, rest, depth, out),
+        "**" => {
+            expand(root, rel, rest, depth, out);
+            if depth < MAX_SEARCH_DEPTH {
+                for name in subdirs(&root.join(rel)) {
+                    expand(root, &join(&name), segments, depth + 1, out);
+                }
+            }
+        }
+        segment if segment.contains('*') => {
+            for name in subdirs(&root.join(rel)) {
+                if segment_matches(segment, &name) {
+                    expand(root, &join(&name), rest, depth + 1, out);
+                }
+            }
+        }
+        segment => {
+            if root.join(rel).join(segment).is_dir() {
+                expand(root, &join(segment), rest, depth + 1, out);
+            }
+        }
+    }
+}
+
+/// Package directories for a list of globs (`!` globs exclude)
+fn resolve_globs(root: &Path, patterns: &[String]) -> Vec<String> {
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for pattern in patterns {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.as_str()),
+        };
+        let segments: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+        let target = if negated {
+            &mut excluded
+        } else {
+            &mut included
+        };
+        expand(root, "", &segments, 0, target);
+    }
+    included.retain(|dir| !excluded.contains(dir));
+    included.sort();
+    included.dedup();
+    included
+}
+
+/// Name from package.json, Cargo.toml or project.json in a package directory
+fn package_name(dir: &Path) -> Option<String> {
+    for file in ["package.json", "project.json"] {
+        if let Ok(content) = std::fs::read_to_string(dir.join(file)) {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
+                if let Some(name) = json["name"].as_str() {
+                    return Some(name.to_string());
+                }
+            }
+        }
+    }
+    let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let mut in_package = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if let (true, Some((key, value))) = (in_package, line.split_once('=')) {
+            if key.trim() == "name" {
+                return quoted_strings(value).first().map(|s| s.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Nx projects: directories with a project.json
+fn find_nx_projects(root: &Path, rel: &str, depth: usize, out: &mut Vec<String>) {
+    let dir = root.join(rel);
+    if !rel.is_empty() && dir.join("project.json").is_file() {
+        out.push(rel.to_string());
+    }
+    if depth >= MAX_SEARCH_DEPTH {
+        return;
+    }
+    for name in subdirs(&dir) {
+        let child = if rel.is_empty() {
+            name
+        } else {
+            format!("{rel}/{name}")
+        };
+        find_nx_projects(root, &child, depth + 1, out);
+    }
+}
+
+/// List the packages declared by the workspace manifests in `root`
+pub fn list(root: &Path) -> Vec<WorkspacePackage> {
+    let read = |file: &str| std::fs::read_to_string(root.join(file)).ok();
+    let mut dirs: Vec<(String, &str)> = Vec::new();
+
+    if let Some(content) = read("pnpm-workspace.yaml") {
+        let patterns = parse_pnpm_workspace(&content);
+        dirs.extend(
+            resolve_globs(root, &patterns)
+                .into_iter()
+                .map(|d| (d, "pnpm")),
+        );
+    } else if let Some(content) = read("package.json") {
+        let patterns = parse_npm_workspaces(&content);
+        dirs.extend(
+            resolve_globs(root, &patterns)
+                .into_iter()
+                .map(|d| (d, "npm")),
+        );
+    }
+    if let Some(content) = read("Cargo.toml") {
+        let patterns = parse_cargo_workspace(&content);
+        dirs.extend(
+            resolve_globs(root, &patterns)
+                .into_iter()
+                .map(|d| (d, "cargo")),
+        );
+    }
+    if root.join("nx.json").is_file() {
+        let mut nx = Vec::new();
+        find_nx_projects(root, "", 0, &mut nx);
+        dirs.extend(nx.into_iter().map(|d| (d, "nx")));
+    }
+
+    let mut packages: Vec<WorkspacePackage> = Vec::new();
+    for (path, kind) in dirs {
+        if path.is_empty() || packages.iter().any(|p| p.path == path) {
+            continue;
+        }
+        let name = package_name(&root.join(&path))
+            .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string());
+        packages.push(WorkspacePackage {
+            name,
+            path,
+            kind: kind.to_string(),
+        });
+    }
+    packages
+}
+
+/// Validate a package path and return it for use as a git pathspec
+pub fn validate_path(root: &Path, package_path: &str) -> Result<String, String> {
+    let path = package_path.trim_end_matches('/');
+    if path.is_empty()
+        || Path::new(path).is_absolute()
+        || path.split(['/', '\\']).any(|s| s == "..")
+        || !root.join(path).is_dir()
+    {
+        return Err(format!("Invalid package path: {package_path}"));
+    }
+    Ok(path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifests() {
+        let pnpm =
+            "packages:\n  - 'apps/*'\n  - \"packages/**\"\n  - '!**/test/**'\ncatalog:\n  - x\n";
+        assert_eq!(
+            parse_pnpm_workspace(pnpm),
+            vec!["apps/*", "packages/**", "!**/test/**"]
+        );
+
+        let cargo = "[workspace]\nmembers = [\n  \"crates/*\", # all crates\n  \"tools/cli\",\n]\nexclude = [\"crates/old\"]\n\n[package]\nname = \"x\"\n";
+        assert_eq!(
+            parse_cargo_workspace(cargo),
+            vec!["crates/*", "tools/cli", "!crates/old"]
+        );
+
+        assert_eq!(
+            parse_npm_workspaces(r#"{"workspaces": {"packages": ["libs/*"]}}"#),
+            vec!["libs/*"]
+        );
+    }
+
+    #[test]
+    fn test_list_cargo_workspace() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+        )
+        .unwrap();
+        for (dir, name) in [("crates/core", "app-core"), ("crates/old", "old")] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+            std::fs::write(
+                root.path().join(dir).join("Cargo.toml"),
+                format!("[package]\nname = \"{name}\"\n"),
+            )
+            .unwrap();
+        }
+
+        let packages = list(root.path());
+        assert_eq!(
+            packages,
+            vec![WorkspacePackage {
+                name: "app-core".to_string(),
+                path: "crates/core".to_string(),
+                kind: "cargo".to_string(),
+            }]
+        );
+    }
+}
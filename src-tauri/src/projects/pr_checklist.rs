@@ -0,0 +1,309 @@
+//! Pre-PR checklist
+//!
+//! Projects can require checks before `create_pr_with_ai_content` opens a PR:
+//! a test command and a lint command that must exit cleanly, an AI review of
+//! the current HEAD, no TODO/FIXME comments added by the branch, and a minimum
+//! test coverage of the changed lines. A failed checklist blocks the PR unless
+//! it is overridden with a reason, which is recorded on the worktree and noted
+//! in the PR body.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::coverage;
+use super::git::user_shell_command;
+use super::storage::{load_projects_data, update_worktree};
+use super::types::{ChecklistOverride, PrChecklist, Project, Worktree};
+use crate::background_tasks::ci_watcher::tail;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Output kept from a failed command
+const MAX_DETAILS_LEN: usize = 2000;
+
+/// Markers that fail the "no TODOs" item
+const TODO_MARKERS: [&str; 2] = ["TODO", "FIXME"];
+
+/// Result of one checklist item
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecklistItem {
+    /// `tests`, `lint`, `review`, `todos` or `coverage`
+    pub id: String,
+    pub label: String,
+    pub passed: bool,
+    /// Why the item failed (command output, offending lines)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// Result of a checklist run
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecklistResult {
+    pub items: Vec<ChecklistItem>,
+    pub passed: bool,
+}
+
+impl ChecklistResult {
+    fn failed_labels(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter(|i| !i.passed)
+            .map(|i| i.label.clone())
+            .collect()
+    }
+}
+
+fn item(id: &str, label: &str, failure: Option<String>) -> ChecklistItem {
+    ChecklistItem {
+        id: id.to_string(),
+        label: label.to_string(),
+        passed: failure.is_none(),
+        details: failure,
+    }
+}
+
+/// Run a command in the worktree, returning its output when it fails
+fn run_command(project: &Project, worktree: &Worktree, command: &str) -> Option<String> {
+    let output = user_shell_command(command)
+        .current_dir(&worktree.path)
+        .envs(super::dev_env::env_for(&worktree.path))
+        .env("JEAN_WORKSPACE_PATH", &worktree.path)
+        .env("JEAN_ROOT_PATH", &project.path)
+        .env("JEAN_BRANCH", &worktree.branch)
+        .output_audited();
+    match output {
+        Ok(o) if o.status.success() => None,
+        Ok(o) => Some(tail(
+            format!(
+                "`{command}` exited with {}\n{}{}",
+                o.status,
+                String::from_utf8_lossy(&o.stdout),
+                String::from_utf8_lossy(&o.stderr)
+            )
+            .trim(),
+            MAX_DETAILS_LEN,
+        )),
+        Err(e) => Some(format!("Failed to run `{command}`: {e}")),
+    }
+}
+
+fn git(path: &str, args: &[&str]) -> Result<String, String> {
+    let output = silent_command("git")
+        .args(args)
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Current HEAD commit of a worktree
+pub fn head_commit(path: &str) -> Option<String> {
+    git(path, &["rev-parse", "HEAD"]).ok()
+}
+
+/// Added lines of a diff that contain a TODO marker, as `file: line`
+fn added_todos(diff: &str) -> Vec<String> {
+    let mut file = "";
+    let mut todos = Vec::new();
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").unwrap_or(path);
+        } else if let Some(added) = line.strip_prefix('+') {
+            if TODO_MARKERS.iter().any(|m| added.contains(m)) {
+                todos.push(format!("{file}: {}", added.trim()));
+            }
+        }
+    }
+    todos
+}
+
+/// Changes of the branch and working tree since it forked from the target
+fn branch_diff(path: &str, remote: &str, target_branch: &str) -> Result<String, String> {
+    let base = git(
+        path,
+        &["merge-base", &format!("{remote}/{target_branch}"), "HEAD"],
+    )?;
+    git(path, &["diff", base.trim()])
+}
+
+/// Evaluate a project's checklist for a worktree, diffing against
+/// `target_branch` on `remote`
+pub fn evaluate(
+    checklist: &PrChecklist,
+    project: &Project,
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+) -> ChecklistResult {
+    let mut items = Vec::new();
+    let command = |c: &Option<String>| c.clone().filter(|c| !c.trim().is_empty());
+
+    if let Some(test_command) = command(&checklist.test_command) {
+        let failure = run_command(project, worktree, &test_command);
+        items.push(item("tests", "Tests pass", failure));
+    }
+    if let Some(lint_command) = command(&checklist.lint_command) {
+        let failure = run_command(project, worktree, &lint_command);
+        items.push(item("lint", "Lint is clean", failure));
+    }
+    if checklist.require_review {
+        let reviewed = worktree.reviewed_commit.is_some()
+            && worktree.reviewed_commit == head_commit(&worktree.path);
+        let failure = (!reviewed).then(|| "No AI review of the latest commit was run".to_string());
+        items.push(item("review", "Review performed", failure));
+    }
+    if checklist.no_todos {
+        let failure = match branch_diff(&worktree.path, remote, target_branch) {
+            Ok(diff) => {
+                let todos = added_todos(&diff);
+                (!todos.is_empty()).then(|| tail(&todos.join("\n"), MAX_DETAILS_LEN))
+            }
+            Err(e) => Some(e),
+        };
+        items.push(item("todos", "No TODOs in diff", failure));
+    }
+    if let Some(min) = checklist.min_diff_coverage {
+        let failure = match coverage::for_worktree(worktree, remote, target_branch) {
+            Ok(Some(c)) => match c.percent {
+                Some(percent) if percent < min => Some(format!(
+                    "{percent:.1}% of changed lines are covered ({} of {})",
+                    c.covered, c.total
+                )),
+                _ => None,
+            },
+            Ok(None) => Some("No coverage report found; run the tests with coverage".to_string()),
+            Err(e) => Some(e),
+        };
+        let label = format!("Diff coverage is at least {min}%");
+        items.push(item("coverage", &label, failure));
+    }
+
+    let passed = items.iter().all(|i| i.passed);
+    ChecklistResult { items, passed }
+}
+
+/// Check a project's checklist before creating a PR.
+///
+/// Passes when there is no checklist or every item passes. A failed checklist
+/// is an error, unless a reason to override it is given; the override is
+/// returned so it can be recorded.
+pub fn enforce(
+    project: &Project,
+    worktree: &Worktree,
+    remote: &str,
+    target_branch: &str,
+    override_reason: Option<&str>,
+) -> Result<Option<ChecklistOverride>, String> {
+    let Some(checklist) = &project.pr_checklist else {
+        return Ok(None);
+    };
+    let result = evaluate(checklist, project, worktree, remote, target_branch);
+    if result.passed {
+        return Ok(None);
+    }
+    let failed = result.failed_labels();
+    match override_reason.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(reason) => {
+            log::warn!(
+                "Pre-PR checklist overridden for {} ({}): {reason}",
+                worktree.name,
+                failed.join(", ")
+            );
+            Ok(Some(ChecklistOverride {
+                failed,
+                reason: reason.to_string(),
+                overridden_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            }))
+        }
+        None => Err(format!(
+            "Pre-PR checklist failed: {}. Fix the failing items or override with a reason.",
+            failed.join(", ")
+        )),
+    }
+}
+
+/// Note appended to the body of a PR created despite a failed checklist
+pub fn override_note(checklist_override: &ChecklistOverride) -> String {
+    format!(
+        "\n\n---\n> Pre-PR checklist overridden ({}): {}",
+        checklist_override.failed.join(", "),
+        checklist_override.reason
+    )
+}
+
+/// Remember the HEAD commit an AI review ran against
+pub fn record_review(app: &AppHandle, worktree_id: &str, worktree_path: &str) {
+    let commit = head_commit(worktree_path);
+    if let Err(e) = update_worktree(app, worktree_id, |w| {
+        w.reviewed_commit = commit;
+        Ok(())
+    }) {
+        log::warn!("Failed to record review: {e}");
+    }
+}
+
+/// Run a worktree's pre-PR checklist without creating a PR
+#[tauri::command]
+pub async fn run_pr_checklist(
+    app: AppHandle,
+    worktree_path: String,
+) -> Result<ChecklistResult, String> {
+    log::trace!("Running pre-PR checklist for: {worktree_path}");
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .worktrees
+        .iter()
+        .find(|w| w.path == worktree_path)
+        .ok_or_else(|| format!("Worktree not found: {worktree_path}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let checklist = project
+        .pr_checklist
+        .as_ref()
+        .ok_or_else(|| format!("No pre-PR checklist is set up for {}", project.name))?;
+
+    Ok(evaluate(
+        checklist,
+        project,
+        worktree,
+        project.remote(),
+        &project.default_branch,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_added_todos() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ // TODO: existing, not added
+-// FIXME: removed
++// TODO: handle errors
++fn main() {}
+";
+        assert_eq!(
+            added_todos(diff),
+            vec!["src/lib.rs: // TODO: handle errors"]
+        );
+        assert!(added_todos("+++ b/a.rs\n+fn ok() {}\n").is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::command_audit::AuditedCommand;
 use crate::platform::silent_command;
 use serde::{Deserialize, Serialize};
 
@@ -103,7 +104,7 @@ pub fn get_pr_status(
             "state,isDraft,reviewDecision,statusCheckRollup,mergeable",
         ])
         .current_dir(repo_path)
-        .output()
+        .output_audited()
         .map_err(|e| format!("Failed to run gh pr view: {e}"))?;
 
     if !output.status.success() {
@@ -216,7 +217,7 @@ fn compute_check_status(checks: &Option<Vec<StatusCheck>>) -> Option<CheckStatus
     }
 }
 
-fn compute_display_status(
+pub fn compute_display_status(
     state: &PrState,
     is_draft: bool,
     review_decision: &Option<ReviewDecision>,
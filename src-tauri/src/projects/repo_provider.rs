@@ -0,0 +1,217 @@
+//! Issue and PR hosting providers
+//!
+//! Issue lists, PR checkout and PR status polling go through a
+//! `RepoProvider` picked from the repository's `origin` remote: GitHub via
+//! `gh`, GitLab via `glab`, Bitbucket Cloud via its REST API. GitLab merge
+//! requests and Bitbucket pull requests are reported with the GitHub PR
+//! types, numbered by their IID and ID.
+
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+
+use super::bitbucket;
+use super::github_issues::{
+    fetch_github_issues, fetch_github_pr, fetch_github_prs, GitHubIssue, GitHubPullRequest,
+    GitHubPullRequestDetail,
+};
+use super::pr_status::{get_pr_status, PrStatus};
+use crate::gh_cli::config::resolve_gh_binary;
+
+/// Where a repository's issues and PRs live
+pub trait RepoProvider: Send {
+    /// Issues in a state ("open", "closed" or "all")
+    fn list_issues(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubIssue>, String>;
+
+    /// PRs in a state ("open", "closed", "merged" or "all")
+    fn list_prs(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubPullRequest>, String>;
+
+    /// A PR with its comments and reviews
+    fn get_pr(&self, repo_path: &str, pr_number: u32) -> Result<GitHubPullRequestDetail, String>;
+
+    /// Check out a PR in a worktree, returning the local branch name
+    fn checkout_pr(
+        &self,
+        worktree_path: &str,
+        pr_number: u32,
+        branch_name: Option<&str>,
+    ) -> Result<String, String>;
+
+    /// State, review decision, checks and mergeability of a PR
+    fn pr_status(
+        &self,
+        repo_path: &str,
+        pr_number: u32,
+        pr_url: &str,
+        worktree_id: &str,
+    ) -> Result<PrStatus, String>;
+}
+
+pub struct GitHub {
+    pub gh: PathBuf,
+}
+
+impl RepoProvider for GitHub {
+    fn list_issues(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubIssue>, String> {
+        fetch_github_issues(&self.gh, repo_path, state)
+    }
+
+    fn list_prs(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubPullRequest>, String> {
+        fetch_github_prs(&self.gh, repo_path, state)
+    }
+
+    fn get_pr(&self, repo_path: &str, pr_number: u32) -> Result<GitHubPullRequestDetail, String> {
+        fetch_github_pr(&self.gh, repo_path, pr_number)
+    }
+
+    fn checkout_pr(
+        &self,
+        worktree_path: &str,
+        pr_number: u32,
+        branch_name: Option<&str>,
+    ) -> Result<String, String> {
+        super::git::gh_pr_checkout(worktree_path, pr_number, branch_name, &self.gh)
+    }
+
+    fn pr_status(
+        &self,
+        repo_path: &str,
+        pr_number: u32,
+        pr_url: &str,
+        worktree_id: &str,
+    ) -> Result<PrStatus, String> {
+        get_pr_status(repo_path, pr_number, pr_url, worktree_id, &self.gh)
+    }
+}
+
+pub struct GitLab {
+    pub glab: PathBuf,
+}
+
+impl RepoProvider for GitLab {
+    fn list_issues(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubIssue>, String> {
+        super::gitlab::list_issues(&self.glab, repo_path, state)
+    }
+
+    fn list_prs(&self, repo_path: &str, state: &str) -> Result<Vec<GitHubPullRequest>, String> {
+        super::gitlab::list_merge_requests(&self.glab, repo_path, state)
+    }
+
+    fn get_pr(&self, repo_path: &str, pr_number: u32) -> Result<GitHubPullRequestDetail, String> {
+        super::gitlab::get_merge_request(&self.glab, repo_path, pr_number)
+    }
+
+    fn checkout_pr(
+        &self,
+        worktree_path: &str,
+        pr_number: u32,
+        branch_name: Option<&str>,
+    ) -> Result<String, String> {
+        super::gitlab::checkout_merge_request(&self.glab, worktree_path, pr_number, branch_name)
+    }
+
+    fn pr_status(
+        &self,
+        repo_path: &str,
+        pr_number: u32,
+        pr_url: &str,
+        worktree_id: &str,
+    ) -> Result<PrStatus, String> {
+        super::gitlab::merge_request_status(&self.glab, repo_path, pr_number, pr_url, worktree_id)
+    }
+}
+
+pub struct Bitbucket {
+    pub client: bitbucket::Client,
+}
+
+impl RepoProvider for Bitbucket {
+    fn list_issues(&self, _repo_path: &str, state: &str) -> Result<Vec<GitHubIssue>, String> {
+        self.client.list_issues(state)
+    }
+
+    fn list_prs(&self, _repo_path: &str, state: &str) -> Result<Vec<GitHubPullRequest>, String> {
+        self.client.list_pull_requests(state)
+    }
+
+    fn get_pr(&self, _repo_path: &str, pr_number: u32) -> Result<GitHubPullRequestDetail, String> {
+        self.client.get_pull_request(pr_number)
+    }
+
+    fn checkout_pr(
+        &self,
+        worktree_path: &str,
+        pr_number: u32,
+        branch_name: Option<&str>,
+    ) -> Result<String, String> {
+        self.client
+            .checkout_pull_request(worktree_path, pr_number, branch_name)
+    }
+
+    fn pr_status(
+        &self,
+        _repo_path: &str,
+        pr_number: u32,
+        pr_url: &str,
+        worktree_id: &str,
+    ) -> Result<PrStatus, String> {
+        self.client
+            .pull_request_status(pr_number, pr_url, worktree_id)
+    }
+}
+
+/// Host of a git remote URL, for `git@host:`, `ssh://` and `https://` remotes
+pub(crate) fn remote_host(remote_url: &str) -> Option<&str> {
+    let rest = match remote_url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => remote_url.split_once(':')?.0,
+    };
+    let host = rest.split(['/', ':']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    (!host.is_empty()).then_some(host)
+}
+
+/// Whether a remote points at GitLab (gitlab.com or a self-hosted
+/// instance with "gitlab" in its host name)
+fn is_gitlab_remote(remote_url: &str) -> bool {
+    remote_host(remote_url).is_some_and(|host| host.to_lowercase().contains("gitlab"))
+}
+
+/// The provider for a repository, from its `origin` remote. Anything that
+/// isn't recognisably GitLab or Bitbucket Cloud is treated as GitHub, as
+/// before.
+pub fn for_repo(app: &AppHandle, repo_path: &str) -> Box<dyn RepoProvider> {
+    let remote_url = super::git::get_origin_url(repo_path).unwrap_or_default();
+    if let Some(repo) = bitbucket::parse_remote(&remote_url) {
+        Box::new(Bitbucket {
+            client: bitbucket::Client::new(app, repo),
+        })
+    } else if is_gitlab_remote(&remote_url) {
+        Box::new(GitLab {
+            glab: PathBuf::from("glab"),
+        })
+    } else {
+        Box::new(GitHub {
+            gh: resolve_gh_binary(app),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_gitlab_remote() {
+        assert!(is_gitlab_remote("git@gitlab.com:group/repo.git"));
+        assert!(is_gitlab_remote(
+            "https://gitlab.example.org/group/sub/repo.git"
+        ));
+        assert!(is_gitlab_remote(
+            "ssh://git@gitlab.internal:2222/group/repo.git"
+        ));
+        assert!(!is_gitlab_remote("git@github.com:user/gitlab-mirror.git"));
+        assert!(!is_gitlab_remote("https://github.com/user/repo"));
+        assert!(!is_gitlab_remote(""));
+    }
+}
@@ -0,0 +1,165 @@
+//! Markdown reports of AI reviews
+//!
+//! Renders a worktree's stored review (summary, findings and which findings
+//! were marked fixed) as a Markdown document that can be pasted into a PR
+//! description or a team wiki.
+
+use tauri::AppHandle;
+
+use super::commands::{ReviewFinding, ReviewResponse};
+use super::storage::load_projects_data;
+use super::types::Worktree;
+
+/// Severities in report order
+const SEVERITIES: [&str; 4] = ["critical", "warning", "suggestion", "praise"];
+
+/// Key of a finding in `fixed_review_findings`, as built by the review panel
+pub fn finding_key(finding: &ReviewFinding, index: usize) -> String {
+    format!("{}:{}:{index}", finding.file, finding.line.unwrap_or(0))
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES
+        .iter()
+        .position(|s| s.eq_ignore_ascii_case(severity))
+        .unwrap_or(SEVERITIES.len())
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Render a review as Markdown
+pub fn render(worktree: &Worktree, review: &ReviewResponse, fixed: &[String]) -> String {
+    let mut findings: Vec<(usize, &ReviewFinding)> = review.findings.iter().enumerate().collect();
+    findings.sort_by_key(|(index, f)| (severity_rank(&f.severity), *index));
+    let is_fixed =
+        |index: usize, finding: &ReviewFinding| fixed.contains(&finding_key(finding, index));
+    let fixed_count = findings.iter().filter(|(i, f)| is_fixed(*i, f)).count();
+
+    let mut report = format!("# Code review: {}\n\n", worktree.branch);
+    report.push_str(&format!(
+        "**Status:** {}  \n**Findings:** {} ({fixed_count} fixed)\n\n",
+        capitalize(&review.approval_status.replace('_', " ")),
+        findings.len()
+    ));
+    report.push_str(&format!("## Summary\n\n{}\n", review.summary.trim()));
+
+    if findings.is_empty() {
+        report.push_str("\nNo findings.\n");
+        return report;
+    }
+    report.push_str("\n## Findings\n");
+    for (index, finding) in findings {
+        let location = match finding.line {
+            // The model reports line 0 when a finding isn't about one line
+            Some(line) if line > 0 => format!("{}:{line}", finding.file),
+            _ => finding.file.clone(),
+        };
+        let check = if is_fixed(index, finding) { "x" } else { " " };
+        report.push_str(&format!(
+            "\n- [{check}] **{}** ({}) `{location}`\n\n  {}\n",
+            finding.title.trim(),
+            finding.severity.to_lowercase(),
+            finding.description.trim().replace('\n', "\n  ")
+        ));
+        if let Some(suggestion) = finding
+            .suggestion
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+        {
+            report.push_str(&format!(
+                "\n  _Suggestion:_ {}\n",
+                suggestion.trim().replace('\n', "\n  ")
+            ));
+        }
+    }
+    report
+}
+
+/// A worktree's last AI review and the keys of the findings marked fixed
+pub async fn stored_review(
+    app: &AppHandle,
+    worktree_id: &str,
+) -> Result<(Worktree, ReviewResponse, Vec<String>), String> {
+    let data = load_projects_data(app)?;
+    let worktree = data
+        .find_worktree(worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+
+    let ui_state = crate::load_ui_state(app.clone()).await?;
+    let review = ui_state
+        .review_results
+        .get(worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("No review results for {}", worktree.name))?;
+    let review: ReviewResponse =
+        serde_json::from_value(review).map_err(|e| format!("Failed to parse review: {e}"))?;
+    let fixed = ui_state
+        .fixed_review_findings
+        .get(worktree_id)
+        .cloned()
+        .unwrap_or_default();
+    Ok((worktree, review, fixed))
+}
+
+/// Render a worktree's last AI review as a Markdown report
+#[tauri::command]
+pub async fn export_review_report(app: AppHandle, worktree_id: String) -> Result<String, String> {
+    log::trace!("Exporting review report for worktree {worktree_id}");
+    let (worktree, review, fixed) = stored_review(&app, &worktree_id).await?;
+    Ok(render(&worktree, &review, &fixed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let worktree: Worktree = serde_json::from_value(serde_json::json!({
+            "id": "w1",
+            "project_id": "p1",
+            "name": "login",
+            "path": "/tmp/login",
+            "branch": "feat/login",
+            "created_at": 0,
+        }))
+        .unwrap();
+        let finding = |severity: &str, line: Option<u32>, title: &str| ReviewFinding {
+            severity: severity.to_string(),
+            file: "src/auth.rs".to_string(),
+            line,
+            title: title.to_string(),
+            description: "Details".to_string(),
+            suggestion: None,
+            source: None,
+        };
+        let review = ReviewResponse {
+            summary: "Looks good overall.".to_string(),
+            findings: vec![
+                finding("suggestion", None, "Rename"),
+                finding("critical", Some(12), "SQL injection"),
+            ],
+            approval_status: "changes_requested".to_string(),
+        };
+
+        let report = render(&worktree, &review, &["src/auth.rs:12:1".to_string()]);
+        assert!(report.starts_with("# Code review: feat/login\n"));
+        assert!(report.contains("**Status:** Changes requested"));
+        assert!(report.contains("**Findings:** 2 (1 fixed)"));
+        // Critical first, and marked fixed
+        let critical = report
+            .find("- [x] **SQL injection** (critical) `src/auth.rs:12`")
+            .unwrap();
+        let suggestion = report
+            .find("- [ ] **Rename** (suggestion) `src/auth.rs`")
+            .unwrap();
+        assert!(critical < suggestion);
+    }
+}
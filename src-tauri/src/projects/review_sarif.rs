@@ -0,0 +1,172 @@
+//! SARIF export of AI reviews
+//!
+//! Serializes a stored review as a SARIF 2.1.0 log, so findings can be
+//! uploaded to GitHub code scanning or read by other SARIF tools. Each
+//! severity is a rule; findings marked fixed are reported as suppressed.
+
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use super::commands::{ReviewFinding, ReviewResponse};
+use super::review_report::{finding_key, stored_review};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rules, one per severity: (severity, SARIF level, description)
+const RULES: [(&str, &str, &str); 4] = [
+    ("critical", "error", "Critical issue found by AI review"),
+    ("warning", "warning", "Potential problem found by AI review"),
+    ("suggestion", "note", "Improvement suggested by AI review"),
+    ("praise", "none", "Good pattern noted by AI review"),
+];
+
+fn rule_index(severity: &str) -> usize {
+    RULES
+        .iter()
+        .position(|(s, _, _)| s.eq_ignore_ascii_case(severity))
+        // Unknown severities are reported as warnings
+        .unwrap_or(1)
+}
+
+/// File path relative to the repository root, with forward slashes
+fn relative_uri(file: &str, root: &str) -> String {
+    let file = file.replace('\\', "/");
+    let root = root.replace('\\', "/");
+    file.strip_prefix(&format!("{}/", root.trim_end_matches('/')))
+        .unwrap_or(&file)
+        .trim_start_matches("./")
+        .to_string()
+}
+
+fn result(finding: &ReviewFinding, index: usize, root: &str, fixed: bool) -> Value {
+    let rule = rule_index(&finding.severity);
+    let (rule_id, level, _) = RULES[rule];
+    let mut message = format!("{}\n\n{}", finding.title.trim(), finding.description.trim());
+    if let Some(suggestion) = finding
+        .suggestion
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+    {
+        message.push_str(&format!("\n\nSuggestion: {}", suggestion.trim()));
+    }
+
+    let mut location = json!({ "artifactLocation": { "uri": relative_uri(&finding.file, root) } });
+    if let Some(line) = finding.line.filter(|l| *l > 0) {
+        location["region"] = json!({ "startLine": line });
+    }
+    let mut result = json!({
+        "ruleId": rule_id,
+        "ruleIndex": rule,
+        "level": level,
+        "message": { "text": message },
+        "locations": [{ "physicalLocation": location }],
+        "partialFingerprints": { "jeanFindingKey": finding_key(finding, index) },
+    });
+    if fixed {
+        result["suppressions"] = json!([{
+            "kind": "external",
+            "justification": "Marked as fixed in Jean",
+        }]);
+    }
+    result
+}
+
+/// Serialize a review as a SARIF log. `root` is the repository path, used to
+/// make absolute file paths relative.
+pub fn to_sarif(review: &ReviewResponse, fixed: &[String], root: &str) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(id, level, description)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": level },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = review
+        .findings
+        .iter()
+        .enumerate()
+        .map(|(index, finding)| {
+            let is_fixed = fixed.contains(&finding_key(finding, index));
+            result(finding, index, root, is_fixed)
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "Jean AI review",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+            "properties": {
+                "summary": review.summary,
+                "approvalStatus": review.approval_status,
+            },
+        }]
+    })
+}
+
+/// Serialize a worktree's last AI review as a SARIF log
+#[tauri::command]
+pub async fn export_review_sarif(app: AppHandle, worktree_id: String) -> Result<String, String> {
+    log::trace!("Exporting review SARIF for worktree {worktree_id}");
+    let (worktree, review, fixed) = stored_review(&app, &worktree_id).await?;
+    serde_json::to_string_pretty(&to_sarif(&review, &fixed, &worktree.path))
+        .map_err(|e| format!("Failed to serialize SARIF: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_sarif() {
+        let review = ReviewResponse {
+            summary: "Mostly fine.".to_string(),
+            findings: vec![
+                ReviewFinding {
+                    severity: "critical".to_string(),
+                    file: "/repo/src/db.rs".to_string(),
+                    line: Some(7),
+                    title: "SQL injection".to_string(),
+                    description: "Query built from input".to_string(),
+                    suggestion: Some("Use params".to_string()),
+                    source: None,
+                },
+                ReviewFinding {
+                    severity: "suggestion".to_string(),
+                    file: "src/lib.rs".to_string(),
+                    line: Some(0),
+                    title: "Rename".to_string(),
+                    description: "Unclear name".to_string(),
+                    suggestion: None,
+                    source: None,
+                },
+            ],
+            approval_status: "changes_requested".to_string(),
+        };
+        let sarif = to_sarif(&review, &["src/lib.rs:0:1".to_string()], "/repo");
+        let results = &sarif["runs"][0]["results"];
+
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[0]["ruleIndex"], 0);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/db.rs");
+        assert_eq!(location["region"]["startLine"], 7);
+        assert!(results[0].get("suppressions").is_none());
+
+        assert_eq!(results[1]["level"], "note");
+        assert!(results[1]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+        assert_eq!(results[1]["suppressions"][0]["kind"], "external");
+    }
+}
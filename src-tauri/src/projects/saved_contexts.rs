@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use tauri::Manager;
 
 /// Attached saved context info returned to frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,10 +22,7 @@ pub async fn attach_saved_context(
 ) -> Result<AttachedSavedContext, String> {
     log::trace!("Attaching saved context '{slug}' for worktree {worktree_id}");
 
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     let saved_contexts_dir = app_data_dir.join("session-context");
     std::fs::create_dir_all(&saved_contexts_dir)
@@ -38,7 +34,7 @@ pub async fn attach_saved_context(
         return Err(format!("Source context file not found: {source_path}"));
     }
 
-    let content = std::fs::read_to_string(source)
+    let content = crate::encryption::read_to_string(source)
         .map_err(|e| format!("Failed to read source context file: {e}"))?;
 
     // Extract name from content (first line if it starts with # )
@@ -52,7 +48,7 @@ pub async fn attach_saved_context(
     let dest_file = saved_contexts_dir.join(format!("{worktree_id}-context-{slug}.md"));
 
     // Write content to destination
-    std::fs::write(&dest_file, &content)
+    crate::encryption::write(&dest_file, &content)
         .map_err(|e| format!("Failed to write attached context file: {e}"))?;
 
     // Get file metadata for size and created_at
@@ -87,10 +83,7 @@ pub async fn remove_saved_context(
 ) -> Result<(), String> {
     log::trace!("Removing saved context '{slug}' from worktree {worktree_id}");
 
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     let context_file = app_data_dir
         .join("session-context")
@@ -113,10 +106,7 @@ pub async fn list_attached_saved_contexts(
 ) -> Result<Vec<AttachedSavedContext>, String> {
     log::trace!("Listing attached saved contexts for worktree {worktree_id}");
 
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     let saved_contexts_dir = app_data_dir.join("session-context");
 
@@ -137,7 +127,7 @@ pub async fn list_attached_saved_contexts(
                 let slug = file_name[prefix.len()..file_name.len() - 3].to_string();
 
                 // Read file to extract name from first line
-                let name = if let Ok(content) = std::fs::read_to_string(entry.path()) {
+                let name = if let Ok(content) = crate::encryption::read_to_string(&entry.path()) {
                     content
                         .lines()
                         .next()
@@ -183,10 +173,7 @@ pub async fn get_saved_context_content(
     worktree_id: String,
     slug: String,
 ) -> Result<String, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(&app)?;
 
     let context_file = app_data_dir
         .join("session-context")
@@ -196,7 +183,7 @@ pub async fn get_saved_context_content(
         return Err(format!("Saved context file not found for slug '{slug}'"));
     }
 
-    std::fs::read_to_string(&context_file)
+    crate::encryption::read_to_string(&context_file)
         .map_err(|e| format!("Failed to read saved context file: {e}"))
 }
 
@@ -208,10 +195,7 @@ pub fn cleanup_saved_contexts_for_worktree(
     app: &tauri::AppHandle,
     worktree_id: &str,
 ) -> Result<(), String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
+    let app_data_dir = crate::locations::app_data_dir(app)?;
 
     let saved_contexts_dir = app_data_dir.join("session-context");
     if !saved_contexts_dir.exists() {
@@ -0,0 +1,546 @@
+//! Dependency security audits
+//!
+//! Runs cargo-audit, npm audit and pip-audit (whichever apply to the
+//! worktree) through the user's login shell and normalizes their JSON reports
+//! into one list of vulnerabilities. Optionally a "Security triage" session is
+//! created, with a prompt asking the model to judge each vulnerability's
+//! exploitability in this codebase and propose fixes. Sending the prompt is
+//! left to the frontend, as for the fix-CI session.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::git::user_shell_command;
+use super::storage::load_projects_data;
+use crate::command_audit::AuditedCommand;
+
+/// Characters of an advisory description kept as its title
+const MAX_TITLE_LEN: usize = 120;
+
+/// Characters of a tool's error output kept in the report
+const MAX_ERROR_LEN: usize = 2000;
+
+/// Severities from most to least severe
+const SEVERITIES: [&str; 5] = ["critical", "high", "medium", "low", "unknown"];
+
+/// Audit tools that can be run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTool {
+    CargoAudit,
+    NpmAudit,
+    PipAudit,
+}
+
+impl AuditTool {
+    fn name(self) -> &'static str {
+        match self {
+            Self::CargoAudit => "cargo audit",
+            Self::NpmAudit => "npm audit",
+            Self::PipAudit => "pip-audit",
+        }
+    }
+
+    fn command(self, root: &Path) -> &'static str {
+        match self {
+            Self::CargoAudit => "cargo audit --json",
+            Self::NpmAudit => "npm audit --json",
+            Self::PipAudit if root.join("requirements.txt").exists() => {
+                "pip-audit -r requirements.txt -f json"
+            }
+            Self::PipAudit => "pip-audit -f json .",
+        }
+    }
+}
+
+/// A vulnerable dependency, normalized across tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vulnerability {
+    pub tool: AuditTool,
+    pub package: String,
+    /// Installed version, or the affected range when the tool only reports that
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Advisory ID (RUSTSEC, GHSA, PYSEC, ...)
+    pub id: String,
+    /// critical, high, medium, low or unknown
+    pub severity: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// How to fix it, when the tool knows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+/// Result of auditing a worktree's dependencies
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityAudit {
+    pub worktree_id: String,
+    pub tools: Vec<AuditTool>,
+    /// Most severe first
+    pub vulnerabilities: Vec<Vulnerability>,
+    /// Tools that could not be run, with their error output
+    pub errors: Vec<String>,
+    /// Prompt for the triage session (only when triage was requested)
+    pub triage_prompt: Option<String>,
+    /// Session created for the triage
+    pub session_id: Option<String>,
+}
+
+/// Tools that apply to a worktree, from its lockfiles and manifests
+pub fn detect(root: &Path) -> Vec<AuditTool> {
+    let mut tools = Vec::new();
+    if root.join("Cargo.lock").exists() {
+        tools.push(AuditTool::CargoAudit);
+    }
+    if root.join("package-lock.json").exists() {
+        tools.push(AuditTool::NpmAudit);
+    }
+    if root.join("requirements.txt").exists() || root.join("pyproject.toml").exists() {
+        tools.push(AuditTool::PipAudit);
+    }
+    tools
+}
+
+fn normalize_severity(severity: &str) -> String {
+    match severity.to_lowercase().as_str() {
+        "critical" => "critical",
+        "high" => "high",
+        "moderate" | "medium" => "medium",
+        "low" | "info" => "low",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES
+        .iter()
+        .position(|s| *s == severity)
+        .unwrap_or(SEVERITIES.len())
+}
+
+fn title(text: &str) -> String {
+    let first_line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let first_line = first_line.trim();
+    if first_line.chars().count() <= MAX_TITLE_LEN {
+        return first_line.to_string();
+    }
+    let cut: String = first_line.chars().take(MAX_TITLE_LEN - 1).collect();
+    format!("{cut}…")
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_ERROR_LEN {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_ERROR_LEN).collect();
+    format!("{cut}\n…")
+}
+
+fn upgrade_to(versions: &[String]) -> Option<String> {
+    (!versions.is_empty()).then(|| format!("Upgrade to {}", versions.join(" or ")))
+}
+
+#[derive(Deserialize)]
+struct CargoAuditReport {
+    vulnerabilities: CargoVulnerabilities,
+}
+
+#[derive(Deserialize)]
+struct CargoVulnerabilities {
+    list: Vec<CargoVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct CargoVulnerability {
+    advisory: CargoAdvisory,
+    package: CargoPackage,
+    #[serde(default)]
+    versions: Option<CargoVersions>,
+}
+
+#[derive(Deserialize)]
+struct CargoAdvisory {
+    id: String,
+    title: String,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct CargoVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+}
+
+fn parse_cargo_audit(output: &str) -> Result<Vec<Vulnerability>, String> {
+    let report: CargoAuditReport = serde_json::from_str(output)
+        .map_err(|e| format!("Failed to parse cargo audit JSON: {e}"))?;
+    Ok(report
+        .vulnerabilities
+        .list
+        .into_iter()
+        .map(|v| Vulnerability {
+            tool: AuditTool::CargoAudit,
+            package: v.package.name,
+            version: Some(v.package.version),
+            url: Some(
+                v.advisory
+                    .url
+                    .unwrap_or_else(|| format!("https://rustsec.org/advisories/{}", v.advisory.id)),
+            ),
+            id: v.advisory.id,
+            // cargo-audit reports no severity, only an optional CVSS vector
+            severity: "unknown".to_string(),
+            title: title(&v.advisory.title),
+            fix: v
+                .versions
+                .and_then(|versions| upgrade_to(&versions.patched)),
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct NpmAuditReport {
+    #[serde(default)]
+    vulnerabilities: BTreeMap<String, NpmVulnerability>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmVulnerability {
+    name: String,
+    #[serde(default)]
+    range: Option<String>,
+    /// Advisory objects, or names of vulnerable dependencies this comes through
+    #[serde(default)]
+    via: Vec<Value>,
+    #[serde(default)]
+    fix_available: Value,
+}
+
+#[derive(Deserialize)]
+struct NpmAdvisory {
+    source: Value,
+    name: String,
+    title: String,
+    url: Option<String>,
+    severity: String,
+    range: Option<String>,
+}
+
+fn npm_fix(fix_available: &Value) -> Option<String> {
+    match fix_available {
+        Value::Bool(true) => Some("Run `npm audit fix`".to_string()),
+        Value::Object(fix) => {
+            let name = fix.get("name")?.as_str()?;
+            let version = fix.get("version")?.as_str()?;
+            let major = fix
+                .get("isSemVerMajor")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            Some(format!(
+                "Upgrade {name} to {version}{}",
+                if major { " (breaking)" } else { "" }
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn parse_npm_audit(output: &str) -> Result<Vec<Vulnerability>, String> {
+    let report: NpmAuditReport =
+        serde_json::from_str(output).map_err(|e| format!("Failed to parse npm audit JSON: {e}"))?;
+    let mut vulnerabilities = Vec::new();
+    for entry in report.vulnerabilities.into_values() {
+        // Entries that are only vulnerable through a dependency are reported
+        // under that dependency's own entry
+        for advisory in entry
+            .via
+            .iter()
+            .filter_map(|v| serde_json::from_value::<NpmAdvisory>(v.clone()).ok())
+            .filter(|a| a.name == entry.name)
+        {
+            let id = advisory
+                .url
+                .as_deref()
+                .and_then(|url| url.rsplit('/').next())
+                .map(str::to_string)
+                .unwrap_or_else(|| advisory.source.to_string());
+            vulnerabilities.push(Vulnerability {
+                tool: AuditTool::NpmAudit,
+                package: entry.name.clone(),
+                version: advisory.range.or_else(|| entry.range.clone()),
+                id,
+                severity: normalize_severity(&advisory.severity),
+                title: title(&advisory.title),
+                url: advisory.url,
+                fix: npm_fix(&entry.fix_available),
+            });
+        }
+    }
+    Ok(vulnerabilities)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PipAuditReport {
+    Report {
+        dependencies: Vec<PipDependency>,
+    },
+    /// Older pip-audit versions print the dependency list alone
+    List(Vec<PipDependency>),
+}
+
+#[derive(Deserialize)]
+struct PipDependency {
+    name: String,
+    version: Option<String>,
+    #[serde(default)]
+    vulns: Vec<PipVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct PipVulnerability {
+    id: String,
+    #[serde(default)]
+    fix_versions: Vec<String>,
+    #[serde(default)]
+    description: String,
+}
+
+fn parse_pip_audit(output: &str) -> Result<Vec<Vulnerability>, String> {
+    let report: PipAuditReport =
+        serde_json::from_str(output).map_err(|e| format!("Failed to parse pip-audit JSON: {e}"))?;
+    let dependencies = match report {
+        PipAuditReport::Report { dependencies } => dependencies,
+        PipAuditReport::List(dependencies) => dependencies,
+    };
+    Ok(dependencies
+        .into_iter()
+        .flat_map(|dep| {
+            let PipDependency {
+                name,
+                version,
+                vulns,
+            } = dep;
+            vulns.into_iter().map(move |v| Vulnerability {
+                tool: AuditTool::PipAudit,
+                package: name.clone(),
+                version: version.clone(),
+                url: Some(format!("https://osv.dev/vulnerability/{}", v.id)),
+                title: if v.description.trim().is_empty() {
+                    v.id.clone()
+                } else {
+                    title(&v.description)
+                },
+                id: v.id,
+                // pip-audit reports no severity
+                severity: "unknown".to_string(),
+                fix: upgrade_to(&v.fix_versions),
+            })
+        })
+        .collect())
+}
+
+/// Normalize an audit tool's JSON report
+pub fn parse(tool: AuditTool, output: &str) -> Result<Vec<Vulnerability>, String> {
+    match tool {
+        AuditTool::CargoAudit => parse_cargo_audit(output),
+        AuditTool::NpmAudit => parse_npm_audit(output),
+        AuditTool::PipAudit => parse_pip_audit(output),
+    }
+}
+
+/// Prompt for a session that triages the vulnerabilities
+pub fn triage_prompt(vulnerabilities: &[Vulnerability]) -> Option<String> {
+    if vulnerabilities.is_empty() {
+        return None;
+    }
+    let mut prompt = format!(
+        "A dependency audit found {} known vulnerabilities in this repository. Triage them:\n\
+         1. For each one, find out whether this codebase uses the affected package and the vulnerable functionality, and how.\n\
+         2. Explain whether and how it could be exploited here, and rate the real risk (critical, high, medium, low or not exploitable).\n\
+         3. Propose a fix: an upgrade, a replacement or a mitigation, noting breaking changes.\n\
+         Summarize the results as a table ordered by real risk. Don't change any files until I confirm which fixes to apply.\n",
+        vulnerabilities.len()
+    );
+    for v in vulnerabilities {
+        let version = v
+            .version
+            .as_deref()
+            .map(|version| format!(" {version}"))
+            .unwrap_or_default();
+        prompt.push_str(&format!(
+            "\n- **{}** `{}{version}` ({}, {}): {}",
+            v.id,
+            v.package,
+            v.severity,
+            v.tool.name(),
+            v.title
+        ));
+        if let Some(url) = &v.url {
+            prompt.push_str(&format!(" <{url}>"));
+        }
+        if let Some(fix) = &v.fix {
+            prompt.push_str(&format!(". Fix: {fix}"));
+        }
+    }
+    Some(prompt)
+}
+
+fn run_tool(tool: AuditTool, path: &str) -> Result<Vec<Vulnerability>, String> {
+    let command = tool.command(Path::new(path));
+    log::trace!("Running security audit in {path}: {command}");
+    let output = user_shell_command(command)
+        .current_dir(path)
+        .envs(super::dev_env::env_for(path))
+        .output_audited()
+        .map_err(|e| format!("Failed to run `{command}`: {e}"))?;
+    // All three tools exit non-zero when they find vulnerabilities, so only
+    // output that doesn't parse counts as a failure
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse(tool, &stdout).map_err(|e| {
+        if output.status.success() {
+            e
+        } else {
+            format!(
+                "`{command}` failed:\n{}",
+                truncate(&String::from_utf8_lossy(&output.stderr))
+            )
+        }
+    })
+}
+
+/// Audit a worktree's dependencies for known vulnerabilities. With `triage`,
+/// a session is created for triaging them and its prompt is returned.
+#[tauri::command]
+pub async fn run_security_audit(
+    app: AppHandle,
+    worktree_id: String,
+    triage: Option<bool>,
+) -> Result<SecurityAudit, String> {
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let tools = detect(Path::new(&worktree.path));
+    if tools.is_empty() {
+        return Err(format!(
+            "No Cargo.lock, package-lock.json, requirements.txt or pyproject.toml found in {}",
+            worktree.name
+        ));
+    }
+
+    let path = worktree.path.clone();
+    let audit_tools = tools.clone();
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        audit_tools
+            .into_iter()
+            .map(|tool| (tool, run_tool(tool, &path)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to run security audit: {e}"))?;
+
+    let mut vulnerabilities = Vec::new();
+    let mut errors = Vec::new();
+    for (tool, result) in results {
+        match result {
+            Ok(found) => vulnerabilities.extend(found),
+            Err(e) => {
+                log::warn!("{} failed in {}: {e}", tool.name(), worktree.name);
+                errors.push(e);
+            }
+        }
+    }
+    if errors.len() == tools.len() {
+        return Err(errors.join("\n\n"));
+    }
+    let mut seen = HashSet::new();
+    vulnerabilities.retain(|v| seen.insert((v.tool, v.package.clone(), v.id.clone())));
+    vulnerabilities.sort_by_key(|v| severity_rank(&v.severity));
+
+    let mut audit = SecurityAudit {
+        worktree_id,
+        tools,
+        vulnerabilities,
+        errors,
+        triage_prompt: None,
+        session_id: None,
+    };
+    if triage.unwrap_or(false) {
+        audit.triage_prompt = triage_prompt(&audit.vulnerabilities);
+        if audit.triage_prompt.is_some() {
+            let session = crate::chat::create_session(
+                app.clone(),
+                worktree.id.clone(),
+                worktree.path.clone(),
+                Some("Security triage".to_string()),
+            )
+            .await?;
+            audit.session_id = Some(session.id);
+        }
+    }
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audit_reports() {
+        let cargo = r#"{"database":{},"lockfile":{},"settings":{},"vulnerabilities":{"found":true,"count":1,"list":[
+            {"advisory":{"id":"RUSTSEC-2024-0001","package":"smallvec","title":"Buffer overflow in insert_many","url":null,"cvss":"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"},
+             "versions":{"patched":[">=1.6.1"],"unaffected":[]},
+             "package":{"name":"smallvec","version":"1.6.0"}}
+        ]},"warnings":{}}"#;
+        let found = parse(AuditTool::CargoAudit, cargo).unwrap();
+        assert_eq!(found[0].package, "smallvec");
+        assert_eq!(found[0].version.as_deref(), Some("1.6.0"));
+        assert_eq!(found[0].fix.as_deref(), Some("Upgrade to >=1.6.1"));
+        assert_eq!(
+            found[0].url.as_deref(),
+            Some("https://rustsec.org/advisories/RUSTSEC-2024-0001")
+        );
+
+        let npm = r#"{"auditReportVersion":2,"vulnerabilities":{
+            "lodash":{"name":"lodash","severity":"high","range":"<4.17.21","via":[
+                {"source":1106913,"name":"lodash","dependency":"lodash","title":"Command Injection in lodash","url":"https://github.com/advisories/GHSA-35jh-r3h4-6jhm","severity":"high","range":"<4.17.21"}
+            ],"fixAvailable":true},
+            "some-lib":{"name":"some-lib","severity":"high","range":"1.x","via":["lodash"],"fixAvailable":{"name":"some-lib","version":"2.0.0","isSemVerMajor":true}}
+        }}"#;
+        let found = parse(AuditTool::NpmAudit, npm).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "GHSA-35jh-r3h4-6jhm");
+        assert_eq!(found[0].severity, "high");
+        assert_eq!(found[0].fix.as_deref(), Some("Run `npm audit fix`"));
+
+        let pip = r#"{"dependencies":[
+            {"name":"flask","version":"0.5","vulns":[{"id":"PYSEC-2019-179","fix_versions":["1.0"],"aliases":["CVE-2019-1010083"],"description":"The Pallets Project Flask before 1.0 is affected by unexpected memory usage."}]},
+            {"name":"requests","version":"2.31.0","vulns":[]}
+        ],"fixes":[]}"#;
+        let found = parse(AuditTool::PipAudit, pip).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, "unknown");
+        assert_eq!(found[0].fix.as_deref(), Some("Upgrade to 1.0"));
+
+        let prompt = triage_prompt(&found).unwrap();
+        assert!(prompt.contains("**PYSEC-2019-179** `flask 0.5` (unknown, pip-audit)"));
+        assert!(triage_prompt(&[]).is_none());
+    }
+}
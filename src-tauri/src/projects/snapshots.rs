@@ -0,0 +1,341 @@
+//! Worktree snapshots (local checkpoints)
+//!
+//! A snapshot records the whole working tree of a worktree (staged, unstaged
+//! and untracked files, ignored ones excepted) as a commit on top of HEAD,
+//! without touching the index, the working tree or the branch. The tree is
+//! written through a temporary index and the commit is kept under
+//! `refs/jean/snapshots/<worktree id>/<snapshot id>`, so it lives in the
+//! repository but out of branches, `git log` and pushes.
+//!
+//! Restoring resets the current branch to the snapshot's base commit and the
+//! working tree to the snapshotted files (all of them unstaged), after taking
+//! a snapshot of the current state so the restore can itself be undone.
+//!
+//! Snapshots are taken automatically (unless the `auto_snapshots` preference
+//! is off) before merging a worktree into its base, before letting Claude fix
+//! commit hook failures and before build/yolo chat runs. Only the latest
+//! `MAX_AUTO_SNAPSHOTS` automatic ones of a worktree are kept. Snapshots are
+//! deleted with their worktree (or, for a trashed one, when it's purged).
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::git_queue;
+use super::storage::load_projects_data;
+use super::types::Worktree;
+use crate::command_audit::AuditedCommand;
+use crate::platform::silent_command;
+
+/// Namespace of the snapshot refs
+const REF_PREFIX: &str = "refs/jean/snapshots";
+
+/// Prefix of the IDs of automatic snapshots
+const AUTO_PREFIX: &str = "auto-";
+
+/// Automatic snapshots kept per worktree (manual ones are never pruned)
+const MAX_AUTO_SNAPSHOTS: usize = 20;
+
+/// A snapshot of a worktree
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Last component of the snapshot's ref
+    pub id: String,
+    pub worktree_id: String,
+    pub label: String,
+    /// Commit holding the snapshotted files
+    pub commit: String,
+    /// HEAD when the snapshot was taken
+    pub base_commit: String,
+    pub created_at: u64,
+    /// Taken automatically rather than by the user
+    pub auto: bool,
+}
+
+fn worktree_refs(worktree_id: &str) -> String {
+    format!("{REF_PREFIX}/{worktree_id}")
+}
+
+/// Run git in a worktree, returning its trimmed stdout
+fn git(repo_path: &str, args: &[&str], index: Option<&str>) -> Result<String, String> {
+    let mut command = silent_command("git");
+    command.args(args).current_dir(repo_path);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    let output = command
+        .output_audited()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args[0], stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Parse `git for-each-ref` output of `FOR_EACH_REF_FORMAT`, newest first
+fn parse_snapshots(output: &str, worktree_id: &str) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let refname = fields.next()?;
+            let commit = fields.next()?.to_string();
+            let base_commit = fields.next()?.to_string();
+            let created_at = fields.next()?.parse().ok()?;
+            let label = fields.next().unwrap_or_default().to_string();
+            let id = refname.rsplit('/').next()?.to_string();
+            Some(Snapshot {
+                auto: id.starts_with(AUTO_PREFIX),
+                id,
+                worktree_id: worktree_id.to_string(),
+                label,
+                commit,
+                base_commit,
+                created_at,
+            })
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    snapshots
+}
+
+const FOR_EACH_REF_FORMAT: &str =
+    "--format=%(refname)%00%(objectname)%00%(parent)%00%(creatordate:unix)%00%(subject)";
+
+fn list(repo_path: &str, worktree_id: &str) -> Result<Vec<Snapshot>, String> {
+    let output = git(
+        repo_path,
+        &[
+            "for-each-ref",
+            FOR_EACH_REF_FORMAT,
+            &worktree_refs(worktree_id),
+        ],
+        None,
+    )?;
+    Ok(parse_snapshots(&output, worktree_id))
+}
+
+/// Write the working tree (untracked files included) as a tree object,
+/// leaving the worktree's index alone. The temporary index starts as a copy
+/// of the worktree's, so `git add` only hashes files changed since it was
+/// last refreshed.
+fn write_worktree_tree(repo_path: &str) -> Result<String, String> {
+    let index = std::env::temp_dir().join(format!("jean-snapshot-{}.index", Uuid::new_v4()));
+    let seeded = git(repo_path, &["rev-parse", "--git-path", "index"], None)
+        .map(|path| Path::new(repo_path).join(path))
+        .is_ok_and(|worktree_index| std::fs::copy(worktree_index, &index).is_ok());
+    let index = index.to_string_lossy().to_string();
+    let result = if seeded {
+        Ok(String::new())
+    } else {
+        git(repo_path, &["read-tree", "HEAD"], Some(&index))
+    }
+    .and_then(|_| git(repo_path, &["add", "-A"], Some(&index)))
+    .and_then(|_| git(repo_path, &["write-tree"], Some(&index)));
+    let _ = std::fs::remove_file(&index);
+    result
+}
+
+/// Take a snapshot of a worktree. An automatic snapshot identical to the
+/// latest snapshot isn't taken again; the latest one is returned instead.
+pub fn take(worktree: &Worktree, label: &str, auto: bool) -> Result<Snapshot, String> {
+    let repo_path = worktree.path.as_str();
+    let head = git(repo_path, &["rev-parse", "--verify", "HEAD"], None)
+        .map_err(|_| format!("Cannot snapshot {}: it has no commits yet", worktree.name))?;
+    let tree = write_worktree_tree(repo_path)?;
+
+    let existing = list(repo_path, &worktree.id)?;
+    if auto {
+        if let Some(latest) = existing.first() {
+            let latest_tree = git(
+                repo_path,
+                &["rev-parse", &format!("{}^{{tree}}", latest.commit)],
+                None,
+            )?;
+            if latest_tree == tree && latest.base_commit == head {
+                log::trace!("Worktree {} unchanged since last snapshot", worktree.name);
+                return Ok(latest.clone());
+            }
+        }
+    }
+
+    let _lock = git_queue::lock(repo_path);
+    let message = if label.trim().is_empty() {
+        "Snapshot"
+    } else {
+        label.trim()
+    };
+    let commit = git(
+        repo_path,
+        &["commit-tree", &tree, "-p", &head, "-m", message],
+        None,
+    )?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let id = if auto {
+        format!("{AUTO_PREFIX}{millis}")
+    } else {
+        millis.to_string()
+    };
+    git(
+        repo_path,
+        &[
+            "update-ref",
+            &format!("{}/{id}", worktree_refs(&worktree.id)),
+            &commit,
+        ],
+        None,
+    )?;
+    log::info!("Took snapshot {id} of {}: {message}", worktree.name);
+
+    if auto {
+        // Older automatic snapshots beyond the limit, newest first
+        for old in existing
+            .iter()
+            .filter(|s| s.auto)
+            .skip(MAX_AUTO_SNAPSHOTS - 1)
+        {
+            let refname = format!("{}/{}", worktree_refs(&worktree.id), old.id);
+            if let Err(e) = git(repo_path, &["update-ref", "-d", &refname], None) {
+                log::warn!("Failed to prune snapshot {}: {e}", old.id);
+            }
+        }
+    }
+
+    Ok(Snapshot {
+        id,
+        worktree_id: worktree.id.clone(),
+        label: message.to_string(),
+        commit,
+        base_commit: head,
+        created_at: (millis / 1000) as u64,
+        auto,
+    })
+}
+
+/// Take an automatic snapshot of the worktree at `worktree_path` before an
+/// operation that rewrites it. Failures are logged, never returned, so they
+/// don't block the operation.
+pub async fn auto_snapshot(app: &AppHandle, worktree_path: &str, label: &str) {
+    let enabled = crate::load_preferences(app.clone())
+        .await
+        .map(|prefs| prefs.auto_snapshots)
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+    let worktree = load_projects_data(app)
+        .ok()
+        .and_then(|data| data.worktrees.into_iter().find(|w| w.path == worktree_path));
+    let Some(worktree) = worktree else {
+        return;
+    };
+    if let Err(e) = take(&worktree, label, true) {
+        log::warn!("Failed to snapshot {} ({label}): {e}", worktree.name);
+    }
+}
+
+/// Delete all snapshots of a worktree (when it's deleted for good)
+pub fn delete_all(repo_path: &str, worktree_id: &str) {
+    let snapshots = match list(repo_path, worktree_id) {
+        Ok(snapshots) => snapshots,
+        Err(e) => {
+            log::warn!("Failed to list snapshots of {worktree_id}: {e}");
+            return;
+        }
+    };
+    for snapshot in snapshots {
+        let refname = format!("{}/{}", worktree_refs(worktree_id), snapshot.id);
+        if let Err(e) = git(repo_path, &["update-ref", "-d", &refname], None) {
+            log::warn!("Failed to delete snapshot {}: {e}", snapshot.id);
+        }
+    }
+}
+
+fn find_worktree(app: &AppHandle, worktree_id: &str) -> Result<Worktree, String> {
+    let data = load_projects_data(app)?;
+    data.find_worktree(worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))
+}
+
+/// Take a snapshot of a worktree's working tree
+#[tauri::command]
+pub async fn create_snapshot(
+    app: AppHandle,
+    worktree_id: String,
+    label: String,
+) -> Result<Snapshot, String> {
+    let worktree = find_worktree(&app, &worktree_id)?;
+    take(&worktree, &label, false)
+}
+
+/// List a worktree's snapshots, newest first
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle, worktree_id: String) -> Result<Vec<Snapshot>, String> {
+    let worktree = find_worktree(&app, &worktree_id)?;
+    list(&worktree.path, &worktree.id)
+}
+
+/// Roll a worktree back to a snapshot: the current branch is reset to the
+/// snapshot's base commit and the working tree to its files. The state being
+/// replaced is snapshotted first; that snapshot is returned.
+#[tauri::command]
+pub async fn restore_snapshot(
+    app: AppHandle,
+    worktree_id: String,
+    snapshot_id: String,
+) -> Result<Snapshot, String> {
+    let worktree = find_worktree(&app, &worktree_id)?;
+    let snapshot = list(&worktree.path, &worktree.id)?
+        .into_iter()
+        .find(|s| s.id == snapshot_id)
+        .ok_or_else(|| format!("Snapshot not found: {snapshot_id}"))?;
+    log::info!(
+        "Restoring snapshot {} of {}: {}",
+        snapshot.id,
+        worktree.name,
+        snapshot.label
+    );
+
+    let backup = take(
+        &worktree,
+        &format!("Before restoring \"{}\"", snapshot.label),
+        true,
+    )?;
+
+    let _lock = git_queue::lock(&worktree.path);
+    let path = worktree.path.as_str();
+    // Drop files created since the snapshot, then check out its files and
+    // move the branch back to where it was, keeping the files unstaged
+    git(path, &["clean", "-fd"], None)?;
+    git(path, &["reset", "--hard", &snapshot.commit], None)?;
+    git(path, &["reset", "--mixed", &snapshot.base_commit], None)
+        .map_err(|e| format!("Failed to restore snapshot {}: {e}", snapshot.id))?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snapshots() {
+        let output = "refs/jean/snapshots/w1/1700000000000\0c1\0b1\01700000000\0Before refactor\n\
+                      refs/jean/snapshots/w1/auto-1700000100000\0c2\0b1\01700000100\0Before build run\n";
+        let snapshots = parse_snapshots(output, "w1");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].id, "auto-1700000100000");
+        assert!(snapshots[0].auto);
+        assert_eq!(snapshots[1].label, "Before refactor");
+        assert_eq!(snapshots[1].base_commit, "b1");
+        assert!(!snapshots[1].auto);
+        assert!(parse_snapshots("", "w1").is_empty());
+    }
+}
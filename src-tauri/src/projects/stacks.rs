@@ -0,0 +1,342 @@
+//! Stacked worktrees
+//!
+//! A worktree can be created on top of another worktree's branch instead of
+//! the default branch. The child records its parent and the parent commit it
+//! is based on, opens its PR against the parent's branch, and can be
+//! restacked: rebased onto the parent's latest commit, or, once the parent is
+//! merged, onto the grandparent (or the default branch) with
+//! `git rebase --onto`, so the parent's commits are dropped even when they
+//! were squash-merged. Children are restacked automatically when a parent's
+//! PR is seen merged; results are emitted as `worktree:restacked`.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::git;
+use super::storage::{load_projects_data, update_worktree};
+use super::types::{Project, ProjectsData, StackParent, Worktree};
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+
+/// Result of restacking one worktree
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RestackOutcome {
+    Restacked {
+        onto: String,
+        pushed: bool,
+    },
+    /// Not restacked (uncommitted changes, not stacked)
+    Skipped {
+        reason: String,
+    },
+    /// The rebase conflicted and was aborted
+    Conflict {
+        conflicting_files: Vec<String>,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// Payload of `worktree:restacked`
+#[derive(Debug, Clone, Serialize)]
+pub struct RestackResult {
+    pub worktree_id: String,
+    pub branch: String,
+    #[serde(flatten)]
+    pub outcome: RestackOutcome,
+}
+
+fn git(path: &str, args: &[&str]) -> Result<String, String> {
+    let output = silent_command("git")
+        .args(args)
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run git {}: {e}", args[0]))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn is_merged(worktree: &Worktree) -> bool {
+    worktree.cached_pr_status.as_deref() == Some("merged")
+}
+
+/// Parent record for a new worktree stacked on `parent_id`
+pub fn new_parent(
+    data: &ProjectsData,
+    project: &Project,
+    parent_id: &str,
+) -> Result<StackParent, String> {
+    let parent = data
+        .find_worktree(parent_id)
+        .ok_or_else(|| format!("Worktree not found: {parent_id}"))?;
+    if parent.project_id != project.id {
+        return Err("Worktrees can only be stacked within a project".to_string());
+    }
+    let base_commit = git(&project.path, &["rev-parse", &parent.branch])?;
+    Ok(StackParent {
+        worktree_id: parent.id.clone(),
+        branch: parent.branch.clone(),
+        base_commit,
+    })
+}
+
+/// Branch a worktree's PR targets: its parent's while the parent is unmerged,
+/// otherwise the project's default branch
+pub fn pr_base<'a>(data: &'a ProjectsData, project: &'a Project, worktree: &Worktree) -> &'a str {
+    worktree
+        .stack_parent
+        .as_ref()
+        .and_then(|p| data.find_worktree(&p.worktree_id))
+        .filter(|p| !is_merged(p))
+        .map(|p| p.branch.as_str())
+        .unwrap_or(&project.default_branch)
+}
+
+/// Where a stacked worktree goes next: the worktree it is stacked on after
+/// the restack (None = the default branch)
+fn next_parent<'a>(data: &'a ProjectsData, parent: &StackParent) -> Option<&'a Worktree> {
+    let current = data.find_worktree(&parent.worktree_id);
+    match current {
+        Some(p) if !is_merged(p) => Some(p),
+        // Merged (or deleted): move up to the grandparent, if it's still open
+        _ => current
+            .and_then(|p| p.stack_parent.as_ref())
+            .and_then(|gp| data.find_worktree(&gp.worktree_id))
+            .filter(|gp| !is_merged(gp)),
+    }
+}
+
+/// Files left conflicted by a failed rebase
+fn conflicting_files(path: &str) -> Vec<String> {
+    git(path, &["diff", "--name-only", "--diff-filter=U"])
+        .map(|out| out.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Push a restacked branch that has a PR, and point the PR at its new base
+fn update_pr(app: &AppHandle, worktree: &Worktree, base: &str) -> bool {
+    let Some(pr_number) = worktree.pr_number else {
+        return false;
+    };
+    if let Err(e) = git(&worktree.path, &["push", "--force-with-lease"]) {
+        log::warn!("Failed to push restacked {}: {e}", worktree.branch);
+        return false;
+    }
+    let gh = crate::gh_cli::config::resolve_gh_binary(app);
+    let output = silent_command(&gh)
+        .args(["pr", "edit", &pr_number.to_string(), "--base", base])
+        .current_dir(&worktree.path)
+        .output_audited();
+    match output {
+        Ok(o) if o.status.success() => {}
+        Ok(o) => log::warn!(
+            "Failed to change base of PR #{pr_number}: {}",
+            String::from_utf8_lossy(&o.stderr).trim()
+        ),
+        Err(e) => log::warn!("Failed to change base of PR #{pr_number}: {e}"),
+    }
+    true
+}
+
+fn restack_inner(app: &AppHandle, worktree_id: &str) -> Result<RestackOutcome, String> {
+    let data = load_projects_data(app)?;
+    let worktree = data
+        .find_worktree(worktree_id)
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let project = data
+        .find_project(&worktree.project_id)
+        .ok_or_else(|| format!("Project not found: {}", worktree.project_id))?;
+    let Some(parent) = &worktree.stack_parent else {
+        return Ok(RestackOutcome::Skipped {
+            reason: "Not stacked on another worktree".to_string(),
+        });
+    };
+    if git::has_uncommitted_changes(&worktree.path) {
+        return Ok(RestackOutcome::Skipped {
+            reason: "Has uncommitted changes".to_string(),
+        });
+    }
+
+    let next = next_parent(&data, parent);
+    let (onto, onto_ref) = match next {
+        Some(p) => (p.branch.clone(), p.branch.clone()),
+        None => {
+            let default_branch = &project.default_branch;
+            let remote = project.remote();
+            git(&worktree.path, &["fetch", remote, default_branch])?;
+            (default_branch.clone(), format!("{remote}/{default_branch}"))
+        }
+    };
+    let onto_commit = git(&worktree.path, &["rev-parse", &onto_ref])?;
+
+    log::trace!(
+        "Restacking {} onto {onto_ref} (from {})",
+        worktree.branch,
+        parent.base_commit
+    );
+    if let Err(e) = git(
+        &worktree.path,
+        &["rebase", "--onto", &onto_commit, &parent.base_commit],
+    ) {
+        let files = conflicting_files(&worktree.path);
+        let _ = git(&worktree.path, &["rebase", "--abort"]);
+        if files.is_empty() {
+            return Err(e);
+        }
+        return Ok(RestackOutcome::Conflict {
+            conflicting_files: files,
+        });
+    }
+
+    let new_parent = next.map(|p| StackParent {
+        worktree_id: p.id.clone(),
+        branch: p.branch.clone(),
+        base_commit: onto_commit,
+    });
+    update_worktree(app, worktree_id, |w| {
+        w.stack_parent = new_parent;
+        Ok(())
+    })?;
+
+    let pushed = update_pr(app, worktree, &onto);
+    Ok(RestackOutcome::Restacked { onto, pushed })
+}
+
+/// Restack a worktree onto its parent (or past it, once merged)
+pub fn restack(app: &AppHandle, worktree_id: &str) -> RestackResult {
+    let branch = load_projects_data(app)
+        .ok()
+        .and_then(|d| d.find_worktree(worktree_id).map(|w| w.branch.clone()))
+        .unwrap_or_default();
+    let outcome = restack_inner(app, worktree_id)
+        .unwrap_or_else(|message| RestackOutcome::Failed { message });
+    let result = RestackResult {
+        worktree_id: worktree_id.to_string(),
+        branch,
+        outcome,
+    };
+    if let Err(e) = app.emit_all("worktree:restacked", &result) {
+        log::error!("Failed to emit worktree:restacked event: {e}");
+    }
+    result
+}
+
+/// IDs of the worktrees stacked directly on a worktree
+fn children(data: &ProjectsData, worktree_id: &str) -> Vec<String> {
+    data.worktrees
+        .iter()
+        .filter(|w| w.archived_at.is_none())
+        .filter(|w| {
+            w.stack_parent
+                .as_ref()
+                .is_some_and(|p| p.worktree_id == worktree_id)
+        })
+        .map(|w| w.id.clone())
+        .collect()
+}
+
+/// Restack every worktree stacked directly on a worktree
+pub fn restack_children_of(
+    app: &AppHandle,
+    worktree_id: &str,
+) -> Result<Vec<RestackResult>, String> {
+    let data = load_projects_data(app)?;
+    Ok(children(&data, worktree_id)
+        .iter()
+        .map(|id| restack(app, id))
+        .collect())
+}
+
+/// Restack a merged worktree's children in the background
+pub fn spawn_restack_children(app: &AppHandle, worktree_id: &str) {
+    let app = app.clone();
+    let worktree_id = worktree_id.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = restack_children_of(&app, &worktree_id) {
+            log::warn!("Failed to restack children of {worktree_id}: {e}");
+        }
+    });
+}
+
+/// Restack a worktree onto its parent's latest commit, or past the parent
+/// once it's merged
+#[tauri::command]
+pub async fn restack_worktree(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<RestackResult, String> {
+    log::trace!("Restacking worktree {worktree_id}");
+    Ok(restack(&app, &worktree_id))
+}
+
+/// Restack all worktrees stacked on a worktree
+#[tauri::command]
+pub async fn restack_children(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Vec<RestackResult>, String> {
+    log::trace!("Restacking children of worktree {worktree_id}");
+    restack_children_of(&app, &worktree_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worktree(id: &str, status: Option<&str>, parent: Option<&str>) -> Worktree {
+        let mut worktree: Worktree = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "project_id": "p1",
+            "name": id,
+            "path": format!("/tmp/{id}"),
+            "branch": format!("{id}-branch"),
+            "created_at": 0,
+        }))
+        .unwrap();
+        worktree.cached_pr_status = status.map(str::to_string);
+        worktree.stack_parent = parent.map(|p| StackParent {
+            worktree_id: p.to_string(),
+            branch: format!("{p}-branch"),
+            base_commit: "abc".to_string(),
+        });
+        worktree
+    }
+
+    #[test]
+    fn test_next_parent() {
+        let data = ProjectsData {
+            projects: Vec::new(),
+            worktrees: vec![
+                worktree("a", Some("open"), None),
+                worktree("b", Some("merged"), Some("a")),
+                worktree("c", Some("open"), Some("b")),
+                worktree("d", Some("merged"), None),
+            ],
+        };
+        let parent_of = |id: &str| {
+            data.find_worktree(id)
+                .unwrap()
+                .stack_parent
+                .clone()
+                .unwrap()
+        };
+
+        // Open parent: stay on it
+        assert_eq!(next_parent(&data, &parent_of("b")).unwrap().id, "a");
+        // Merged parent: move to the grandparent
+        assert_eq!(next_parent(&data, &parent_of("c")).unwrap().id, "a");
+        // Merged parent without a parent of its own: the default branch
+        let on_d = worktree("e", None, Some("d")).stack_parent.unwrap();
+        assert!(next_parent(&data, &on_d).is_none());
+        assert_eq!(children(&data, "b"), vec!["c".to_string()]);
+    }
+}
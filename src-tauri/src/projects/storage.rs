@@ -1,35 +1,17 @@
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use once_cell::sync::Lazy;
-use tauri::{AppHandle, Manager};
+use rusqlite::{params, Connection};
+use tauri::AppHandle;
 
-use super::types::ProjectsData;
+use super::types::{Project, ProjectsData, Worktree};
+use crate::db::{
+    lock_exclusive, lock_exclusive_in, with_db, with_db_at, DB_FILE_NAME, PROJECTS_LOCK,
+};
 
-/// Global mutex to prevent concurrent read-modify-write races on projects.json.
-/// Multiple threads (e.g., fetch_worktrees_status) can call save_projects_data simultaneously,
-/// causing race conditions with the atomic write pattern (temp file + rename).
-static PROJECTS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-
-/// Get the path to the projects.json data file
-pub fn get_projects_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {e}"))?;
-
-    // Ensure the directory exists
-    std::fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {e}"))?;
-
-    Ok(app_data_dir.join("projects.json"))
-}
-
-/// Get the base directory for all worktrees (~/jean)
+/// Get the base directory for all worktrees (~/jean unless configured)
 pub fn get_worktrees_base_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
-
-    let jean_dir = home_dir.join("jean");
+    let jean_dir = crate::locations::worktrees_root()?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&jean_dir)
@@ -38,7 +20,7 @@ pub fn get_worktrees_base_dir() -> Result<PathBuf, String> {
     Ok(jean_dir)
 }
 
-/// Get the directory for a specific project's worktrees (~/jean/<project-name>)
+/// Get the directory for a specific project's worktrees (<worktrees root>/<project-name>)
 pub fn get_project_worktrees_dir(project_name: &str) -> Result<PathBuf, String> {
     let base_dir = get_worktrees_base_dir()?;
     let project_dir = base_dir.join(sanitize_directory_name(project_name));
@@ -63,56 +45,153 @@ pub fn sanitize_directory_name(name: &str) -> String {
         .collect()
 }
 
-/// Load projects data from disk (internal, no locking)
-fn load_projects_data_internal(app: &AppHandle) -> Result<ProjectsData, String> {
-    log::trace!("Loading projects data from disk");
-    let path = get_projects_path(app)?;
+/// Read all projects and worktrees from the database, in stored order
+fn read_projects_data(conn: &Connection) -> Result<ProjectsData, String> {
+    let projects = read_rows::<Project>(conn, "SELECT data FROM projects ORDER BY position")?;
+    let worktrees = read_rows::<Worktree>(conn, "SELECT data FROM worktrees ORDER BY position")?;
+    Ok(ProjectsData {
+        projects,
+        worktrees,
+    })
+}
 
-    if !path.exists() {
-        log::trace!("Projects file not found, returning empty data");
-        return Ok(ProjectsData::default());
+fn read_rows<T: serde::de::DeserializeOwned>(
+    conn: &Connection,
+    sql: &str,
+) -> Result<Vec<T>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to query projects data: {e}"))?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to query projects data: {e}"))?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        let json = row.map_err(|e| format!("Failed to read projects data row: {e}"))?;
+        let item = serde_json::from_str(&json).map_err(|e| {
+            log::error!("Failed to parse projects data row: {e}");
+            format!("Failed to parse projects data: {e}")
+        })?;
+        items.push(item);
     }
+    Ok(items)
+}
 
-    let contents = std::fs::read_to_string(&path).map_err(|e| {
-        log::error!("Failed to read projects file: {e}");
-        format!("Failed to read projects file: {e}")
-    })?;
-
-    let data: ProjectsData = serde_json::from_str(&contents).map_err(|e| {
-        log::error!("Failed to parse projects JSON: {e}");
-        format!("Failed to parse projects data: {e}")
-    })?;
-
-    let original_count = data.worktrees.len();
-
-    // Filter out worktrees where path doesn't exist on disk
-    let valid_worktrees: Vec<_> = data
-        .worktrees
-        .into_iter()
-        .filter(|w| {
-            let exists = std::path::Path::new(&w.path).exists();
-            if !exists {
-                log::warn!(
-                    "Removing orphaned worktree '{}' - path does not exist: {}",
-                    w.name,
-                    w.path
-                );
-            }
-            exists
+/// Read existing `(id -> (position, data))` rows for change detection
+fn read_existing(conn: &Connection, table: &str) -> Result<HashMap<String, (i64, String)>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT id, position, data FROM {table}"))
+        .map_err(|e| format!("Failed to query {table}: {e}"))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                (row.get::<_, i64>(1)?, row.get::<_, String>(2)?),
+            ))
         })
-        .collect();
+        .map_err(|e| format!("Failed to query {table}: {e}"))?;
+
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("Failed to read {table}: {e}"))
+}
+
+/// Write projects data in one transaction, only touching rows that changed
+fn write_projects_data(conn: &Connection, data: &ProjectsData) -> Result<(), String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {e}"))?;
+
+    let existing_projects = read_existing(&tx, "projects")?;
+    let mut changed = 0;
+    for (position, project) in data.projects.iter().enumerate() {
+        let json = serde_json::to_string(project)
+            .map_err(|e| format!("Failed to serialize project: {e}"))?;
+        let position = position as i64;
+        if existing_projects.get(&project.id) != Some(&(position, json.clone())) {
+            tx.execute(
+                "INSERT OR REPLACE INTO projects (id, position, data) VALUES (?1, ?2, ?3)",
+                params![project.id, position, json],
+            )
+            .map_err(|e| format!("Failed to save project {}: {e}", project.id))?;
+            changed += 1;
+        }
+    }
+    for id in existing_projects.keys() {
+        if data.find_project(id).is_none() {
+            tx.execute("DELETE FROM projects WHERE id = ?1", [id])
+                .map_err(|e| format!("Failed to delete project {id}: {e}"))?;
+            changed += 1;
+        }
+    }
 
-    let removed_count = original_count - valid_worktrees.len();
+    let existing_worktrees = read_existing(&tx, "worktrees")?;
+    for (position, worktree) in data.worktrees.iter().enumerate() {
+        let json = serde_json::to_string(worktree)
+            .map_err(|e| format!("Failed to serialize worktree: {e}"))?;
+        let position = position as i64;
+        if existing_worktrees.get(&worktree.id) != Some(&(position, json.clone())) {
+            tx.execute(
+                "INSERT OR REPLACE INTO worktrees (id, project_id, position, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![worktree.id, worktree.project_id, position, json],
+            )
+            .map_err(|e| format!("Failed to save worktree {}: {e}", worktree.id))?;
+            changed += 1;
+        }
+    }
+    for id in existing_worktrees.keys() {
+        if data.find_worktree(id).is_none() {
+            tx.execute("DELETE FROM worktrees WHERE id = ?1", [id])
+                .map_err(|e| format!("Failed to delete worktree {id}: {e}"))?;
+            changed += 1;
+        }
+    }
 
-    let data = ProjectsData {
-        projects: data.projects,
-        worktrees: valid_worktrees,
-    };
+    tx.commit()
+        .map_err(|e| format!("Failed to commit projects data: {e}"))?;
+
+    log::trace!("Saved projects data ({changed} row(s) changed)");
+    Ok(())
+}
 
-    // Save cleaned data if any orphans were removed
-    if removed_count > 0 {
-        log::trace!("Cleaned up {removed_count} orphaned worktree(s)");
-        save_projects_data_internal(app, &data)?;
+/// Load projects data from the database
+///
+/// Worktrees whose path no longer exists on disk are removed, so this takes
+/// the exclusive projects lock like the writers do.
+pub fn load_projects_data(app: &AppHandle) -> Result<ProjectsData, String> {
+    log::trace!("Loading projects data from database");
+    let _lock = lock_exclusive(app, PROJECTS_LOCK)?;
+    load_projects_data_unlocked(app)
+}
+
+fn load_projects_data_unlocked(app: &AppHandle) -> Result<ProjectsData, String> {
+    with_db(app, load_and_prune)
+}
+
+/// Read projects data, removing worktrees whose path no longer exists
+fn load_and_prune(conn: &Connection) -> Result<ProjectsData, String> {
+    let mut data = read_projects_data(conn)?;
+
+    let (valid_worktrees, orphans): (Vec<_>, Vec<_>) = data.worktrees.into_iter().partition(|w| {
+        let exists = Path::new(&w.path).exists();
+        if !exists {
+            log::warn!(
+                "Removing orphaned worktree '{}' - path does not exist: {}",
+                w.name,
+                w.path
+            );
+        }
+        exists
+    });
+    data.worktrees = valid_worktrees;
+
+    if !orphans.is_empty() {
+        for orphan in &orphans {
+            conn.execute("DELETE FROM worktrees WHERE id = ?1", [&orphan.id])
+                .map_err(|e| format!("Failed to delete orphaned worktree: {e}"))?;
+        }
+        log::trace!("Cleaned up {} orphaned worktree(s)", orphans.len());
     }
 
     log::trace!(
@@ -123,47 +202,89 @@ fn load_projects_data_internal(app: &AppHandle) -> Result<ProjectsData, String>
     Ok(data)
 }
 
-/// Load projects data from disk (with locking for thread safety)
-pub fn load_projects_data(app: &AppHandle) -> Result<ProjectsData, String> {
-    let _lock = PROJECTS_LOCK.lock().unwrap();
-    load_projects_data_internal(app)
+/// Atomically load, modify, and save projects data.
+///
+/// Holds the cross-instance projects lock for the whole cycle, so another app
+/// instance can't save in between and have its changes overwritten.
+pub fn with_projects_data_mut<F, T>(app: &AppHandle, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut ProjectsData) -> Result<T, String>,
+{
+    let _lock = lock_exclusive(app, PROJECTS_LOCK)?;
+    let mut data = load_projects_data_unlocked(app)?;
+    let result = f(&mut data)?;
+    with_db(app, |conn| write_projects_data(conn, &data))?;
+    Ok(result)
 }
 
-/// Save projects data to disk (internal, no locking - atomic write: temp file + rename)
-fn save_projects_data_internal(app: &AppHandle, data: &ProjectsData) -> Result<(), String> {
-    log::trace!("Saving projects data to disk");
-    let path = get_projects_path(app)?;
-
-    let json_content = serde_json::to_string_pretty(data).map_err(|e| {
-        log::error!("Failed to serialize projects data: {e}");
-        format!("Failed to serialize projects data: {e}")
-    })?;
-
-    // Write to a temporary file first, then rename (atomic operation)
-    let temp_path = path.with_extension("tmp");
-
-    std::fs::write(&temp_path, json_content).map_err(|e| {
-        log::error!("Failed to write projects file: {e}");
-        format!("Failed to write projects file: {e}")
-    })?;
+/// [`load_projects_data`] for callers without an app handle (the CLI
+/// companion), given the resolved app data directory
+pub fn load_projects_data_in(app_data_dir: &Path) -> Result<ProjectsData, String> {
+    let _lock = lock_exclusive_in(app_data_dir, PROJECTS_LOCK)?;
+    with_db_at(app_data_dir.join(DB_FILE_NAME), load_and_prune)
+}
 
-    std::fs::rename(&temp_path, &path).map_err(|e| {
-        log::error!("Failed to finalize projects file: {e}");
-        format!("Failed to finalize projects file: {e}")
-    })?;
+/// [`with_projects_data_mut`] for callers without an app handle
+pub fn with_projects_data_mut_in<F, T>(app_data_dir: &Path, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut ProjectsData) -> Result<T, String>,
+{
+    let _lock = lock_exclusive_in(app_data_dir, PROJECTS_LOCK)?;
+    with_db_at(app_data_dir.join(DB_FILE_NAME), |conn| {
+        let mut data = load_and_prune(conn)?;
+        let result = f(&mut data)?;
+        write_projects_data(conn, &data)?;
+        Ok(result)
+    })
+}
 
-    log::trace!(
-        "Saved {} projects and {} worktrees to {path:?}",
-        data.projects.len(),
-        data.worktrees.len()
-    );
-    Ok(())
+/// Atomically load, modify, and save a single worktree.
+///
+/// Use this for frequent per-worktree updates (e.g. cached status from polling)
+/// so they don't rewrite or race with the rest of the projects data.
+pub fn update_worktree<F, T>(app: &AppHandle, worktree_id: &str, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut Worktree) -> Result<T, String>,
+{
+    let _lock = lock_exclusive(app, PROJECTS_LOCK)?;
+    with_db(app, |conn| update_worktree_in(conn, worktree_id, f))
 }
 
-/// Save projects data to disk (with locking for thread safety)
-pub fn save_projects_data(app: &AppHandle, data: &ProjectsData) -> Result<(), String> {
-    let _lock = PROJECTS_LOCK.lock().unwrap();
-    save_projects_data_internal(app, data)
+fn update_worktree_in<F, T>(conn: &Connection, worktree_id: &str, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut Worktree) -> Result<T, String>,
+{
+    use rusqlite::OptionalExtension;
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {e}"))?;
+
+    let json: String = tx
+        .query_row(
+            "SELECT data FROM worktrees WHERE id = ?1",
+            [worktree_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load worktree: {e}"))?
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+
+    let mut worktree: Worktree =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse worktree: {e}"))?;
+    let result = f(&mut worktree)?;
+
+    let json = serde_json::to_string(&worktree)
+        .map_err(|e| format!("Failed to serialize worktree: {e}"))?;
+    tx.execute(
+        "UPDATE worktrees SET data = ?1 WHERE id = ?2",
+        params![json, worktree_id],
+    )
+    .map_err(|e| format!("Failed to save worktree: {e}"))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit worktree update: {e}"))?;
+
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -178,4 +299,69 @@ mod tests {
         assert_eq!(sanitize_directory_name("my_project"), "my_project");
         assert_eq!(sanitize_directory_name("MyProject123"), "MyProject123");
     }
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_connection(&conn).unwrap();
+        conn
+    }
+
+    fn test_data() -> ProjectsData {
+        serde_json::from_str(
+            r#"{
+                "projects": [
+                    {"id":"p1","name":"One","path":"/tmp/one","default_branch":"main","added_at":0},
+                    {"id":"p2","name":"Two","path":"/tmp/two","default_branch":"main","added_at":0}
+                ],
+                "worktrees": [
+                    {"id":"w1","project_id":"p1","name":"fuzzy-tiger","path":"/tmp/one-wt","branch":"fuzzy-tiger","created_at":0}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_write_and_read_projects_data_roundtrip() {
+        let conn = test_conn();
+        let data = test_data();
+        write_projects_data(&conn, &data).unwrap();
+
+        let loaded = read_projects_data(&conn).unwrap();
+        assert_eq!(loaded.projects.len(), 2);
+        assert_eq!(loaded.projects[0].id, "p1");
+        assert_eq!(loaded.projects[1].id, "p2");
+        assert_eq!(loaded.worktrees.len(), 1);
+    }
+
+    #[test]
+    fn test_write_projects_data_deletes_removed_rows() {
+        let conn = test_conn();
+        let mut data = test_data();
+        write_projects_data(&conn, &data).unwrap();
+
+        data.projects.retain(|p| p.id != "p2");
+        data.worktrees.clear();
+        write_projects_data(&conn, &data).unwrap();
+
+        let loaded = read_projects_data(&conn).unwrap();
+        assert_eq!(loaded.projects.len(), 1);
+        assert!(loaded.worktrees.is_empty());
+    }
+
+    #[test]
+    fn test_update_worktree_only_touches_one_row() {
+        let conn = test_conn();
+        write_projects_data(&conn, &test_data()).unwrap();
+
+        update_worktree_in(&conn, "w1", |w| {
+            w.cached_behind_count = Some(3);
+            Ok(())
+        })
+        .unwrap();
+
+        let loaded = read_projects_data(&conn).unwrap();
+        assert_eq!(loaded.worktrees[0].cached_behind_count, Some(3));
+        assert!(update_worktree_in(&conn, "missing", |_| Ok(())).is_err());
+    }
 }
@@ -0,0 +1,520 @@
+//! Test runs with structured results
+//!
+//! Detects a worktree's test framework (cargo, jest, vitest, pytest or go
+//! test), runs it through the user's login shell, and parses the output into
+//! per-test results. The last run of each worktree is stored in the
+//! `test_runs` table. Failures can be turned into a prompt for a session that
+//! fixes them.
+
+use std::path::Path;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::git::user_shell_command;
+use super::storage::load_projects_data;
+use crate::command_audit::AuditedCommand;
+use crate::db::with_db;
+
+/// Characters of a failure message kept per test
+const MAX_MESSAGE_LEN: usize = 4000;
+
+/// Failed tests included in a fix prompt
+const MAX_FIX_PROMPT_FAILURES: usize = 20;
+
+/// Test frameworks that can be run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFramework {
+    Cargo,
+    Jest,
+    Vitest,
+    Pytest,
+    Go,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result of one test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Failure output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Result of a test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRun {
+    pub worktree_id: String,
+    pub framework: TestFramework,
+    pub scope: Option<String>,
+    pub command: String,
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub exit_code: Option<i32>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub tests: Vec<TestCase>,
+    /// Prompt asking to fix the failed tests (only when requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_prompt: Option<String>,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_MESSAGE_LEN {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_MESSAGE_LEN).collect();
+    format!("{cut}\n…")
+}
+
+/// Quote an argument for a POSIX shell
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Detect the test framework of a checkout from its manifests
+pub fn detect(root: &Path) -> Option<TestFramework> {
+    if root.join("Cargo.toml").is_file() {
+        return Some(TestFramework::Cargo);
+    }
+    if let Ok(package_json) = std::fs::read_to_string(root.join("package.json")) {
+        let package: serde_json::Value = serde_json::from_str(&package_json).unwrap_or_default();
+        let has_dependency = |name: &str| {
+            ["dependencies", "devDependencies"]
+                .iter()
+                .any(|key| package[key].get(name).is_some())
+        };
+        if has_dependency("vitest") {
+            return Some(TestFramework::Vitest);
+        }
+        if has_dependency("jest") {
+            return Some(TestFramework::Jest);
+        }
+    }
+    if ["pytest.ini", "pyproject.toml", "setup.cfg", "tox.ini"]
+        .iter()
+        .any(|f| root.join(f).is_file())
+    {
+        return Some(TestFramework::Pytest);
+    }
+    if root.join("go.mod").is_file() {
+        return Some(TestFramework::Go);
+    }
+    None
+}
+
+/// Shell command running a framework's tests, limited to a scope (a test
+/// name or path filter)
+fn command_for(framework: TestFramework, scope: Option<&str>) -> String {
+    let scope = scope.map(shell_quote);
+    let with_scope = |base: &str| match &scope {
+        Some(scope) => format!("{base} {scope}"),
+        None => base.to_string(),
+    };
+    match framework {
+        TestFramework::Cargo => with_scope("cargo test --no-fail-fast"),
+        TestFramework::Jest => with_scope("npx jest --json"),
+        TestFramework::Vitest => with_scope("npx vitest run --reporter=json"),
+        TestFramework::Pytest => with_scope("python -m pytest -rA"),
+        TestFramework::Go => match &scope {
+            Some(scope) => format!("go test -json -run {scope} ./..."),
+            None => "go test -json ./...".to_string(),
+        },
+    }
+}
+
+static CARGO_TEST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^test (\S+) \.\.\. (ok|FAILED|ignored)").expect("valid cargo test regex")
+});
+static CARGO_FAILURE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^---- (\S+) stdout ----$").expect("valid cargo failure regex"));
+static PYTEST_RESULT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(PASSED|FAILED|ERROR|SKIPPED|XFAIL|XPASS) (\S+)(?: - (.*))?$")
+        .expect("valid pytest regex")
+});
+
+/// Attach the output of a failed cargo test to its result
+fn attach_failure(tests: &mut [TestCase], (name, message): (String, String)) {
+    if let Some(test) = tests.iter_mut().find(|t| t.name == name) {
+        test.message = Some(truncate(&message));
+    }
+}
+
+fn parse_cargo(output: &str) -> Vec<TestCase> {
+    let mut tests: Vec<TestCase> = Vec::new();
+    let mut failure: Option<(String, String)> = None;
+    for line in output.lines() {
+        if let Some(caps) = CARGO_TEST.captures(line) {
+            tests.push(TestCase {
+                name: caps[1].to_string(),
+                status: match &caps[2] {
+                    "ok" => TestStatus::Passed,
+                    "ignored" => TestStatus::Skipped,
+                    _ => TestStatus::Failed,
+                },
+                duration_ms: None,
+                message: None,
+            });
+        } else if let Some(caps) = CARGO_FAILURE.captures(line) {
+            if let Some(previous) = failure.take() {
+                attach_failure(&mut tests, previous);
+            }
+            failure = Some((caps[1].to_string(), String::new()));
+        } else if line == "failures:" || line.starts_with("test result:") {
+            if let Some(previous) = failure.take() {
+                attach_failure(&mut tests, previous);
+            }
+        } else if let Some((_, message)) = &mut failure {
+            message.push_str(line);
+            message.push('\n');
+        }
+    }
+    if let Some(previous) = failure {
+        attach_failure(&mut tests, previous);
+    }
+    tests
+}
+
+/// Jest's `--json` report, also written by vitest's json reporter
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestReport {
+    test_results: Vec<JestFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestFile {
+    assertion_results: Vec<JestAssertion>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JestAssertion {
+    full_name: String,
+    status: String,
+    duration: Option<f64>,
+    #[serde(default)]
+    failure_messages: Vec<String>,
+}
+
+fn parse_jest(output: &str) -> Result<Vec<TestCase>, String> {
+    // Reporters can print other output around the report
+    let json = match (output.find('{'), output.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &output[start..=end],
+        _ => return Err("No JSON test report in output".to_string()),
+    };
+    let report: JestReport =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse test report: {e}"))?;
+    Ok(report
+        .test_results
+        .into_iter()
+        .flat_map(|file| file.assertion_results)
+        .map(|a| TestCase {
+            name: a.full_name,
+            status: match a.status.as_str() {
+                "passed" => TestStatus::Passed,
+                "failed" => TestStatus::Failed,
+                _ => TestStatus::Skipped,
+            },
+            duration_ms: a.duration.map(|d| d as u64),
+            message: (!a.failure_messages.is_empty())
+                .then(|| truncate(&a.failure_messages.join("\n"))),
+        })
+        .collect())
+}
+
+fn parse_pytest(output: &str) -> Vec<TestCase> {
+    output
+        .lines()
+        .filter_map(|line| PYTEST_RESULT.captures(line))
+        .map(|caps| TestCase {
+            name: caps[2].to_string(),
+            status: match &caps[1] {
+                "PASSED" | "XFAIL" => TestStatus::Passed,
+                "SKIPPED" => TestStatus::Skipped,
+                _ => TestStatus::Failed,
+            },
+            duration_ms: None,
+            message: caps.get(3).map(|m| truncate(m.as_str())),
+        })
+        .collect()
+}
+
+/// An event of `go test -json`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GoEvent {
+    action: String,
+    package: Option<String>,
+    test: Option<String>,
+    elapsed: Option<f64>,
+    output: Option<String>,
+}
+
+fn parse_go(output: &str) -> Vec<TestCase> {
+    let mut tests: Vec<TestCase> = Vec::new();
+    let mut outputs: std::collections::HashMap<String, String> = Default::default();
+    for line in output.lines() {
+        let Ok(event) = serde_json::from_str::<GoEvent>(line) else {
+            continue;
+        };
+        let Some(test) = event.test else {
+            continue;
+        };
+        let name = format!("{}/{test}", event.package.unwrap_or_default());
+        let status = match event.action.as_str() {
+            "output" => {
+                outputs
+                    .entry(name)
+                    .or_default()
+                    .push_str(&event.output.unwrap_or_default());
+                continue;
+            }
+            "pass" => TestStatus::Passed,
+            "fail" => TestStatus::Failed,
+            "skip" => TestStatus::Skipped,
+            _ => continue,
+        };
+        let message = match status {
+            TestStatus::Failed => outputs.remove(&name).map(|o| truncate(&o)),
+            _ => None,
+        };
+        tests.push(TestCase {
+            name,
+            status,
+            duration_ms: event.elapsed.map(|s| (s * 1000.0) as u64),
+            message,
+        });
+    }
+    tests
+}
+
+/// Parse a framework's output into per-test results
+pub fn parse(framework: TestFramework, output: &str) -> Result<Vec<TestCase>, String> {
+    match framework {
+        TestFramework::Cargo => Ok(parse_cargo(output)),
+        TestFramework::Jest | TestFramework::Vitest => parse_jest(output),
+        TestFramework::Pytest => Ok(parse_pytest(output)),
+        TestFramework::Go => Ok(parse_go(output)),
+    }
+}
+
+/// Prompt asking a session to fix the failed tests of a run
+pub fn fix_prompt(run: &TestRun) -> Option<String> {
+    let failures: Vec<&TestCase> = run
+        .tests
+        .iter()
+        .filter(|t| t.status == TestStatus::Failed)
+        .collect();
+    if failures.is_empty() {
+        return None;
+    }
+    let mut prompt = format!(
+        "{} test(s) failed when running `{}`. Find the cause of each failure and fix it. \
+         Fix the code under test unless the test itself is wrong, and run the tests again to confirm.\n",
+        failures.len(),
+        run.command
+    );
+    for test in failures.iter().take(MAX_FIX_PROMPT_FAILURES) {
+        prompt.push_str(&format!("\n## {}\n", test.name));
+        if let Some(message) = &test.message {
+            prompt.push_str(&format!("\n```\n{message}\n```\n"));
+        }
+    }
+    if failures.len() > MAX_FIX_PROMPT_FAILURES {
+        prompt.push_str(&format!(
+            "\n…and {} more failed tests.\n",
+            failures.len() - MAX_FIX_PROMPT_FAILURES
+        ));
+    }
+    Some(prompt)
+}
+
+fn save_run(app: &AppHandle, run: &TestRun) -> Result<(), String> {
+    let data =
+        serde_json::to_string(run).map_err(|e| format!("Failed to serialize test run: {e}"))?;
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO test_runs (worktree_id, started_at, data) VALUES (?1, ?2, ?3)",
+            params![run.worktree_id, run.started_at as i64, data],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save test run: {e}"))
+    })
+}
+
+/// Run a worktree's tests and store the results
+#[tauri::command]
+pub async fn run_tests(
+    app: AppHandle,
+    worktree_id: String,
+    scope: Option<String>,
+    fix_failures: Option<bool>,
+) -> Result<TestRun, String> {
+    let data = load_projects_data(&app)?;
+    let worktree = data
+        .find_worktree(&worktree_id)
+        .cloned()
+        .ok_or_else(|| format!("Worktree not found: {worktree_id}"))?;
+    let framework = detect(Path::new(&worktree.path))
+        .ok_or_else(|| format!("No supported test framework found in {}", worktree.name))?;
+    let scope = scope.filter(|s| !s.trim().is_empty());
+    let command = command_for(framework, scope.as_deref());
+    log::trace!("Running tests in {}: {command}", worktree.path);
+
+    let started_at = now();
+    let start = Instant::now();
+    let path = worktree.path.clone();
+    let shell_command = command.clone();
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        user_shell_command(&shell_command)
+            .current_dir(&path)
+            .envs(super::dev_env::env_for(&path))
+            .output_audited()
+    })
+    .await
+    .map_err(|e| format!("Failed to run tests: {e}"))?
+    .map_err(|e| format!("Failed to run `{command}`: {e}"))?;
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // JSON reports are on stdout; cargo and pytest results can be on either
+    let tests = match framework {
+        TestFramework::Jest | TestFramework::Vitest => parse(framework, &stdout)?,
+        _ => parse(framework, &format!("{stdout}\n{stderr}"))?,
+    };
+    if tests.is_empty() && !output.status.success() {
+        return Err(format!(
+            "`{command}` failed before running tests:\n{}",
+            truncate(&stderr)
+        ));
+    }
+
+    let count = |status| tests.iter().filter(|t| t.status == status).count();
+    let mut run = TestRun {
+        worktree_id,
+        framework,
+        scope,
+        command,
+        started_at,
+        duration_ms,
+        exit_code: output.status.code(),
+        passed: count(TestStatus::Passed),
+        failed: count(TestStatus::Failed),
+        skipped: count(TestStatus::Skipped),
+        tests,
+        fix_prompt: None,
+    };
+    if fix_failures.unwrap_or(false) {
+        run.fix_prompt = fix_prompt(&run);
+    }
+    save_run(&app, &run)?;
+    Ok(run)
+}
+
+/// Results of a worktree's last test run
+#[tauri::command]
+pub async fn get_test_results(
+    app: AppHandle,
+    worktree_id: String,
+) -> Result<Option<TestRun>, String> {
+    let data: Option<String> = with_db(&app, |conn| {
+        conn.query_row(
+            "SELECT data FROM test_runs WHERE worktree_id = ?1",
+            params![worktree_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load test run: {e}"))
+    })?;
+    data.map(|d| serde_json::from_str(&d).map_err(|e| format!("Failed to parse test run: {e}")))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_outputs() {
+        let cargo = "\
+running 3 tests
+test db::tests::opens ... ok
+test db::tests::migrates ... FAILED
+test db::tests::slow ... ignored
+
+failures:
+
+---- db::tests::migrates stdout ----
+thread 'db::tests::migrates' panicked at src/db.rs:10:5:
+assertion failed
+
+failures:
+    db::tests::migrates
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+";
+        let tests = parse(TestFramework::Cargo, cargo).unwrap();
+        let statuses: Vec<TestStatus> = tests.iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses,
+            vec![TestStatus::Passed, TestStatus::Failed, TestStatus::Skipped]
+        );
+        assert!(tests[1]
+            .message
+            .as_deref()
+            .unwrap()
+            .ends_with("assertion failed"));
+
+        let jest = r#"some log
+{"testResults":[{"assertionResults":[
+  {"fullName":"login works","status":"passed","duration":12},
+  {"fullName":"login fails","status":"failed","duration":3,"failureMessages":["Expected 1"]}
+]}]}"#;
+        let tests = parse(TestFramework::Jest, jest).unwrap();
+        assert_eq!(tests[1].message.as_deref(), Some("Expected 1"));
+        assert_eq!(tests[0].duration_ms, Some(12));
+
+        let pytest = "FAILED tests/test_app.py::test_login - AssertionError: 1 != 2\nPASSED tests/test_app.py::test_home\n";
+        let tests = parse(TestFramework::Pytest, pytest).unwrap();
+        assert_eq!(tests[0].status, TestStatus::Failed);
+        assert_eq!(tests[0].message.as_deref(), Some("AssertionError: 1 != 2"));
+
+        let go = r#"{"Action":"run","Package":"app","Test":"TestA"}
+{"Action":"output","Package":"app","Test":"TestA","Output":"want 1\n"}
+{"Action":"fail","Package":"app","Test":"TestA","Elapsed":0.5}
+{"Action":"fail","Package":"app","Elapsed":0.6}"#;
+        let tests = parse(TestFramework::Go, go).unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "app/TestA");
+        assert_eq!(tests[0].duration_ms, Some(500));
+        assert_eq!(tests[0].message.as_deref(), Some("want 1"));
+    }
+}
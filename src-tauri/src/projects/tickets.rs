@@ -0,0 +1,128 @@
+//! Ticket IDs (JIRA-123, #456)
+//!
+//! Projects with a `ticket_pattern` regex get the ticket ID found in an issue
+//! title or a session's first message threaded through the branch name, and
+//! from there into AI commit messages (a `Refs:` trailer) and PR titles.
+//! Branch names drop characters git refs can't carry well (`#456` becomes
+//! `456`), so patterns meant to be read back from branches should match the
+//! bare ID.
+
+use regex::Regex;
+use tauri::AppHandle;
+
+use super::storage::load_projects_data;
+use super::types::Project;
+
+/// Compile a ticket pattern, with the error shown in project settings
+pub fn compile(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("Invalid ticket pattern: {e}"))
+}
+
+/// First ticket ID in `text`
+pub fn extract(pattern: &str, text: &str) -> Option<String> {
+    match compile(pattern) {
+        Ok(re) => re.find(text).map(|m| m.as_str().to_string()),
+        Err(e) => {
+            log::warn!("{e}");
+            None
+        }
+    }
+}
+
+/// Whether `key` is a tracker issue key (`PROJ-123`), as used by Jira and
+/// Linear; checked before keys go into URLs and file names
+pub fn is_issue_key(key: &str) -> bool {
+    key.split_once('-').is_some_and(|(project, number)| {
+        project.starts_with(|c: char| c.is_ascii_alphabetic())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+/// Ticket ID in `text` for a project with a ticket pattern
+pub fn extract_for_project(project: &Project, text: &str) -> Option<String> {
+    let pattern = project.ticket_pattern.as_deref()?;
+    extract(pattern, text)
+}
+
+/// Branch-safe form of a ticket ID (`#456` → `456`)
+fn branch_ticket(ticket: &str) -> String {
+    ticket
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+/// Put the ticket at the start of the branch name's last component, unless
+/// the branch already mentions it
+pub fn thread_into_branch(branch: &str, ticket: &str) -> String {
+    let ticket = branch_ticket(ticket);
+    if ticket.is_empty() || branch.to_lowercase().contains(&ticket.to_lowercase()) {
+        return branch.to_string();
+    }
+    match branch.rsplit_once('/') {
+        Some((dirs, name)) => format!("{dirs}/{ticket}-{name}"),
+        None => format!("{ticket}-{branch}"),
+    }
+}
+
+/// Add a `Refs:` trailer to a commit message that doesn't mention the ticket
+pub fn thread_into_commit_message(message: &str, ticket: &str) -> String {
+    if message.contains(ticket) {
+        return message.to_string();
+    }
+    format!("{}\n\nRefs: {ticket}", message.trim_end())
+}
+
+/// Prefix a PR title that doesn't mention the ticket
+pub fn thread_into_title(title: &str, ticket: &str) -> String {
+    if title.contains(ticket) {
+        return title.to_string();
+    }
+    format!("[{ticket}] {title}")
+}
+
+/// Ticket of a worktree (or base checkout), from its branch name
+pub fn ticket_for_checkout(app: &AppHandle, path: &str, branch: &str) -> Option<String> {
+    let data = load_projects_data(app).ok()?;
+    let project = match data.worktrees.iter().find(|w| w.path == path) {
+        Some(worktree) => data.find_project(&worktree.project_id)?,
+        None => data.projects.iter().find(|p| p.path == path)?,
+    };
+    extract_for_project(project, branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_ticket() {
+        let pattern = r"[A-Z][A-Z0-9]+-\d+";
+        let ticket = extract(pattern, "PAY-42: refunds fail").unwrap();
+        assert_eq!(ticket, "PAY-42");
+
+        assert_eq!(
+            thread_into_branch("ada/fix/refunds-fail", &ticket),
+            "ada/fix/PAY-42-refunds-fail"
+        );
+        assert_eq!(
+            thread_into_branch("pay-42-refunds", &ticket),
+            "pay-42-refunds"
+        );
+        assert_eq!(thread_into_branch("refunds", "#456"), "456-refunds");
+
+        assert_eq!(
+            thread_into_commit_message("fix: refunds\n", &ticket),
+            "fix: refunds\n\nRefs: PAY-42"
+        );
+        assert_eq!(
+            thread_into_title("Fix refunds", &ticket),
+            "[PAY-42] Fix refunds"
+        );
+        assert_eq!(thread_into_title("PAY-42 Fix", &ticket), "PAY-42 Fix");
+    }
+}
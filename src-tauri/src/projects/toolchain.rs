@@ -0,0 +1,319 @@
+//! mise / asdf toolchain detection
+//!
+//! Worktrees pinning runtimes in `.tool-versions` (asdf, mise) or `mise.toml`
+//! get them checked before the setup script runs: a missing Node or Python
+//! version is the most common reason setup scripts fail. Missing runtimes are
+//! installed first when the `install_missing_toolchains` preference is on;
+//! otherwise a `toolchain:missing` event lets the UI offer to install them.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::command_audit::AuditedCommand;
+use crate::http_server::EmitExt;
+use crate::platform::silent_command;
+
+/// Toolchain files, in the order mise reads them
+const CONFIG_FILES: [&str; 4] = [
+    ".tool-versions",
+    "mise.toml",
+    ".mise.toml",
+    ".config/mise.toml",
+];
+
+static AUTO_INSTALL: AtomicBool = AtomicBool::new(false);
+
+/// Apply the `install_missing_toolchains` preference
+pub fn set_auto_install(enabled: bool) {
+    AUTO_INSTALL.store(enabled, Ordering::Relaxed);
+}
+
+/// A runtime version pinned by a toolchain file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolRequirement {
+    pub tool: String,
+    pub version: String,
+}
+
+/// Toolchain detection result for a path
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolchainInfo {
+    /// Toolchain files found in the path
+    pub config_files: Vec<String>,
+    /// Version manager used to check and install ("mise" or "asdf"), None if
+    /// neither is installed
+    pub manager: Option<String>,
+    /// Pinned runtimes
+    pub tools: Vec<ToolRequirement>,
+    /// Pinned runtimes that aren't installed
+    pub missing: Vec<ToolRequirement>,
+}
+
+/// Payload of the `toolchain:missing` event
+#[derive(Debug, Clone, Serialize)]
+struct ToolchainMissingEvent {
+    path: String,
+    manager: String,
+    missing: Vec<ToolRequirement>,
+}
+
+/// `.tool-versions`: `<tool> <version> [<fallback versions>...]` per line
+fn parse_tool_versions(content: &str) -> Vec<ToolRequirement> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let tool = parts.next()?;
+            let version = parts.next()?;
+            Some(ToolRequirement {
+                tool: tool.to_string(),
+                version: version.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Double-quoted strings of a TOML value, in order
+fn quoted_strings(value: &str) -> Vec<&str> {
+    value.split('"').skip(1).step_by(2).collect()
+}
+
+/// `[tools]` section of a mise.toml. Handles the common value forms:
+/// `node = "20"`, `python = ["3.12", "3.11"]` and `go = { version = "1.22" }`.
+fn parse_mise_toml(content: &str) -> Vec<ToolRequirement> {
+    let mut in_tools = false;
+    let mut tools = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && !line.contains('=') {
+            in_tools = line.trim_start_matches('[').trim_end_matches(']').trim() == "tools";
+            continue;
+        }
+        if !in_tools || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let tool = key.trim().trim_matches('"').trim_matches('\'');
+        let value = value.trim();
+        let version = if value.starts_with('{') {
+            value
+                .split_once("version")
+                .and_then(|(_, rest)| quoted_strings(rest).first().copied())
+        } else {
+            quoted_strings(value).first().copied()
+        };
+        if let Some(version) = version.filter(|_| !tool.is_empty()) {
+            tools.push(ToolRequirement {
+                tool: tool.to_string(),
+                version: version.to_string(),
+            });
+        }
+    }
+    tools
+}
+
+/// Pinned runtimes of all toolchain files in `path` (first pin of a tool wins)
+fn read_requirements(path: &Path) -> (Vec<String>, Vec<ToolRequirement>) {
+    let mut files = Vec::new();
+    let mut tools: Vec<ToolRequirement> = Vec::new();
+    for name in CONFIG_FILES {
+        let Ok(content) = std::fs::read_to_string(path.join(name)) else {
+            continue;
+        };
+        files.push(name.to_string());
+        let parsed = if name == ".tool-versions" {
+            parse_tool_versions(&content)
+        } else {
+            parse_mise_toml(&content)
+        };
+        for requirement in parsed {
+            if !tools.iter().any(|t| t.tool == requirement.tool) {
+                tools.push(requirement);
+            }
+        }
+    }
+    (files, tools)
+}
+
+/// Preferred version manager: mise reads both formats, asdf only .tool-versions
+fn manager_for(config_files: &[String]) -> Option<&'static str> {
+    if which::which("mise").is_ok() {
+        Some("mise")
+    } else if which::which("asdf").is_ok() && config_files.iter().any(|f| f == ".tool-versions") {
+        Some("asdf")
+    } else {
+        None
+    }
+}
+
+#[derive(Deserialize)]
+struct MiseVersion {
+    #[serde(default)]
+    installed: bool,
+}
+
+/// Tools with a current version that isn't installed, per `mise ls --current --json`
+fn mise_missing(path: &str) -> Result<Vec<String>, String> {
+    let output = silent_command("mise")
+        .args(["ls", "--current", "--json"])
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run mise: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("mise ls failed: {}", stderr.trim()));
+    }
+    let versions: HashMap<String, Vec<MiseVersion>> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse mise output: {e}"))?;
+    Ok(versions
+        .into_iter()
+        .filter(|(_, versions)| versions.iter().any(|v| !v.installed))
+        .map(|(tool, _)| tool)
+        .collect())
+}
+
+/// Whether asdf has `version` of `tool` installed
+fn asdf_installed(path: &str, requirement: &ToolRequirement) -> bool {
+    silent_command("asdf")
+        .args(["where", &requirement.tool, &requirement.version])
+        .current_dir(path)
+        .output_audited()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Detect pinned runtimes in `path` and which of them are missing
+pub fn detect(path: &str) -> Result<ToolchainInfo, String> {
+    let (config_files, tools) = read_requirements(Path::new(path));
+    let manager = if config_files.is_empty() {
+        None
+    } else {
+        manager_for(&config_files)
+    };
+    let missing = match manager {
+        Some("mise") => {
+            let missing_tools = mise_missing(path)?;
+            tools
+                .iter()
+                .filter(|t| missing_tools.contains(&t.tool))
+                .cloned()
+                .collect()
+        }
+        Some(_) => tools
+            .iter()
+            .filter(|t| !asdf_installed(path, t))
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(ToolchainInfo {
+        config_files,
+        manager: manager.map(str::to_string),
+        tools,
+        missing,
+    })
+}
+
+/// Install the runtimes pinned in `path` with its version manager
+pub fn install(path: &str) -> Result<String, String> {
+    let (config_files, _) = read_requirements(Path::new(path));
+    let manager = manager_for(&config_files)
+        .ok_or_else(|| "Neither mise nor asdf is installed".to_string())?;
+    log::trace!("Installing toolchains in {path} with {manager}");
+    let output = silent_command(manager)
+        .arg("install")
+        .current_dir(path)
+        .output_audited()
+        .map_err(|e| format!("Failed to run {manager} install: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let combined = format!("{stdout}{stderr}");
+        return Err(format!("{manager} install failed: {}", combined.trim()));
+    }
+    Ok(format!("{stdout}{stderr}"))
+}
+
+/// Before a setup script runs: install missing runtimes when auto-install is
+/// on, otherwise tell the UI so it can offer to. Failures are only logged;
+/// the setup script reports its own errors.
+pub fn prepare_for_setup(app: &AppHandle, path: &str) {
+    let info = match detect(path) {
+        Ok(info) => info,
+        Err(e) => {
+            log::warn!("Failed to check toolchains in {path}: {e}");
+            return;
+        }
+    };
+    let Some(manager) = info.manager.filter(|_| !info.missing.is_empty()) else {
+        return;
+    };
+    if AUTO_INSTALL.load(Ordering::Relaxed) {
+        if let Err(e) = install(path) {
+            log::warn!("Failed to install toolchains in {path}: {e}");
+        }
+        return;
+    }
+    let event = ToolchainMissingEvent {
+        path: path.to_string(),
+        manager,
+        missing: info.missing,
+    };
+    if let Err(e) = app.emit_all("toolchain:missing", &event) {
+        log::error!("Failed to emit toolchain:missing event: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(tool: &str, version: &str) -> ToolRequirement {
+        ToolRequirement {
+            tool: tool.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tool_versions() {
+        let content = "# runtimes\nnodejs 20.11.0\npython 3.12.1 3.11.7  # fallback\n\nbroken\n";
+        assert_eq!(
+            parse_tool_versions(content),
+            vec![req("nodejs", "20.11.0"), req("python", "3.12.1")]
+        );
+    }
+
+    #[test]
+    fn test_parse_mise_toml() {
+        let content = r#"
+[env]
+NODE_ENV = "development"
+
+[tools]
+node = "20"
+python = ["3.12", "3.11"]
+go = { version = "1.22", os = ["linux"] }
+"npm:prettier" = "3"
+
+[tasks.build]
+run = "make"
+"#;
+        assert_eq!(
+            parse_mise_toml(content),
+            vec![
+                req("node", "20"),
+                req("python", "3.12"),
+                req("go", "1.22"),
+                req("npm:prettier", "3"),
+            ]
+        );
+    }
+}
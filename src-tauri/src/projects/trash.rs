@@ -0,0 +1,214 @@
+//! Trash-based soft delete for worktrees
+//!
+//! Instead of `git worktree remove` + branch delete, a permanently deleted
+//! worktree can have its directory moved to the OS trash. The branch, the git
+//! worktree entry and the worktree's sessions are kept for a restore window
+//! (`trash_retention_days`), after which `purge_expired` finishes the cleanup.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::git;
+use super::types::Worktree;
+use crate::db::with_db;
+
+/// A worktree whose directory was moved to the OS trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedWorktree {
+    pub worktree: Worktree,
+    pub project_path: String,
+    /// Unix timestamp when the directory was trashed
+    pub trashed_at: u64,
+}
+
+/// Whether a trashed worktree is past its restore window
+pub fn is_expired(trashed_at: u64, now: u64, retention_days: u32) -> bool {
+    trashed_at + retention_days as u64 * 86400 <= now
+}
+
+/// Record a trashed worktree so it can be restored or purged later
+pub fn record(app: &AppHandle, entry: &TrashedWorktree) -> Result<(), String> {
+    let json = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize trashed worktree: {e}"))?;
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO trashed_worktrees (worktree_id, trashed_at, data)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![entry.worktree.id, entry.trashed_at as i64, json],
+        )
+        .map_err(|e| format!("Failed to record trashed worktree: {e}"))?;
+        Ok(())
+    })
+}
+
+/// All trashed worktrees, most recently trashed first
+pub fn list(app: &AppHandle) -> Result<Vec<TrashedWorktree>, String> {
+    let rows: Vec<String> = with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare("SELECT data FROM trashed_worktrees ORDER BY trashed_at DESC")
+            .map_err(|e| format!("Failed to list trashed worktrees: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to list trashed worktrees: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list trashed worktrees: {e}"))
+    })?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|json| match serde_json::from_str(json) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                log::warn!("Skipping unparseable trashed worktree: {e}");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Remove a trashed worktree record, returning it
+pub fn take(app: &AppHandle, worktree_id: &str) -> Result<Option<TrashedWorktree>, String> {
+    let entry = list(app)?
+        .into_iter()
+        .find(|e| e.worktree.id == worktree_id);
+    if entry.is_some() {
+        with_db(app, |conn| {
+            conn.execute(
+                "DELETE FROM trashed_worktrees WHERE worktree_id = ?1",
+                [worktree_id],
+            )
+            .map_err(|e| format!("Failed to remove trashed worktree: {e}"))
+        })?;
+    }
+    Ok(entry)
+}
+
+/// Move a worktree directory to the OS trash.
+///
+/// The git worktree entry is left in place (git only prunes it once it's
+/// stale for months), so a directory restored from the trash can be repaired.
+pub fn move_to_trash(worktree_path: &str) -> Result<(), String> {
+    log::trace!("Moving worktree {worktree_path} to trash");
+    trash::delete(worktree_path).map_err(|e| format!("Failed to move worktree to trash: {e}"))
+}
+
+/// Put the worktree directory back from the OS trash, where the platform supports it
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn restore_from_os_trash(worktree_path: &str) -> Result<bool, String> {
+    let target = std::path::PathBuf::from(worktree_path);
+    let items = trash::os_limited::list().map_err(|e| format!("Failed to list trash: {e}"))?;
+    let Some(item) = items
+        .into_iter()
+        .filter(|item| item.original_path() == target)
+        .max_by_key(|item| item.time_deleted)
+    else {
+        return Ok(false);
+    };
+
+    trash::os_limited::restore_all([item])
+        .map_err(|e| format!("Failed to restore worktree from trash: {e}"))?;
+    Ok(true)
+}
+
+/// macOS has no API for restoring trashed items
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn restore_from_os_trash(_worktree_path: &str) -> Result<bool, String> {
+    Ok(false)
+}
+
+/// Bring a trashed worktree's directory back.
+///
+/// Restores from the OS trash when possible (keeping uncommitted changes);
+/// otherwise recreates the worktree from its branch.
+pub fn restore_directory(entry: &TrashedWorktree) -> Result<(), String> {
+    let worktree = &entry.worktree;
+    if std::path::Path::new(&worktree.path).exists() {
+        return Err(format!("{} already exists", worktree.path));
+    }
+
+    match restore_from_os_trash(&worktree.path) {
+        Ok(true) => {
+            log::trace!("Restored worktree {} from trash", worktree.path);
+            return git::repair_worktree(&entry.project_path, &worktree.path);
+        }
+        Ok(false) => {}
+        Err(e) => log::warn!("{e}, recreating worktree from its branch"),
+    }
+
+    log::trace!(
+        "Recreating worktree {} from branch {}",
+        worktree.path,
+        worktree.branch
+    );
+    git::prune_worktrees(&entry.project_path)?;
+    git::create_worktree_from_existing_branch(&entry.project_path, &worktree.path, &worktree.branch)
+}
+
+/// Finish deleting trashed worktrees past their restore window: prune the git
+/// worktree entry, delete the branch, sessions and contexts.
+/// Returns the number of worktrees purged.
+pub fn purge_expired(app: &AppHandle, retention_days: u32) -> Result<u32, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut purged = 0;
+    for entry in list(app)? {
+        if !is_expired(entry.trashed_at, now, retention_days) {
+            continue;
+        }
+        let worktree = &entry.worktree;
+        log::trace!("Purging trashed worktree {}", worktree.name);
+
+        if let Err(e) = git::prune_worktrees(&entry.project_path) {
+            log::warn!("Failed to prune worktrees: {e}");
+        }
+        if let Err(e) = git::delete_branch(&entry.project_path, &worktree.branch) {
+            log::warn!("Failed to delete branch (may already be deleted): {e}");
+        }
+        super::snapshots::delete_all(&entry.project_path, &worktree.id);
+        if let Err(e) = super::github_issues::cleanup_issue_contexts_for_worktree(app, &worktree.id)
+        {
+            log::warn!("Failed to cleanup issue contexts: {e}");
+        }
+        if let Err(e) = super::github_issues::cleanup_pr_contexts_for_worktree(app, &worktree.id) {
+            log::warn!("Failed to cleanup PR contexts: {e}");
+        }
+        crate::chat::storage::delete_index(app, &worktree.id)?;
+        crate::chat::change_audit::delete(app, &worktree.id);
+        take(app, &worktree.id)?;
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_expired() {
+        let day = 86400;
+        assert!(!is_expired(0, 6 * day, 7));
+        assert!(is_expired(0, 7 * day, 7));
+        // A zero-day window purges on the next run
+        assert!(is_expired(100, 100, 0));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Type of session (base branch or worktree)
@@ -28,6 +30,25 @@ pub enum MergeType {
 pub struct JeanConfig {
     #[serde(default)]
     pub scripts: JeanScripts,
+    #[serde(default)]
+    pub services: Option<JeanServices>,
+    /// MCP servers for Claude sessions in the repository, by name
+    #[serde(default)]
+    pub mcp: BTreeMap<String, McpServer>,
+    /// Environment variable profiles, by name (e.g. "local", "staging"); the
+    /// profile selected for a worktree is set in its terminals and scripts
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// An MCP server Claude sessions are started with (stdio transport)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpServer {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
 }
 
 /// Scripts section of jean.json
@@ -39,6 +60,21 @@ pub struct JeanScripts {
     pub run: Option<String>,
 }
 
+/// Services section of jean.json: a docker compose stack run per worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JeanServices {
+    /// Compose file relative to the worktree (None = docker compose's default lookup)
+    #[serde(default)]
+    pub compose_file: Option<String>,
+    /// Also remove the stack's volumes when the worktree is deleted
+    #[serde(default = "default_remove_volumes_on_delete")]
+    pub remove_volumes_on_delete: bool,
+}
+
+fn default_remove_volumes_on_delete() -> bool {
+    true
+}
+
 /// A git project that has been added to Jean, or a folder for organizing projects
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -64,6 +100,257 @@ pub struct Project {
     /// Path to custom avatar image (relative to app data dir, e.g., "avatars/abc123.png")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub avatar_path: Option<String>,
+    /// Background polling overrides (None = use the global intervals)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polling: Option<PollingOverrides>,
+    /// Run setup scripts, terminals and Claude sessions inside the checkout's
+    /// devcontainer (when it has a devcontainer config)
+    #[serde(default)]
+    pub use_devcontainer: bool,
+    /// Worktree templates offered when creating a worktree (bugfix, feature, spike...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<WorktreeTemplate>,
+    /// Branch naming scheme, e.g. `{user}/{type}/{slug}` (None = Jean's defaults)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_name_template: Option<String>,
+    /// Regex for ticket IDs (e.g. `[A-Z][A-Z0-9]+-\d+`) threaded through
+    /// branch names, commit messages and PR titles (None = off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_pattern: Option<String>,
+    /// Slack notifications for session and PR events (None = off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slack: Option<SlackSettings>,
+    /// Checks that must pass before a PR is created (None = off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr_checklist: Option<PrChecklist>,
+    /// Licenses allowed for dependencies added by a branch (None = off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_policy: Option<LicensePolicy>,
+    /// Remote to fetch base branches from, push to and open PRs through
+    /// (None = origin)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// MCP servers for the project's Claude sessions, by name (added to, and
+    /// overriding, those of jean.json)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp_servers: BTreeMap<String, McpServer>,
+    /// Tool permissions of the project's Claude sessions (None = Claude's
+    /// own settings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_policy: Option<ToolPolicy>,
+}
+
+impl Project {
+    pub fn find_template(&self, id: &str) -> Option<&WorktreeTemplate> {
+        self.templates.iter().find(|t| t.id == id)
+    }
+
+    /// The project's preferred remote
+    pub fn remote(&self) -> &str {
+        self.remote.as_deref().unwrap_or(super::git::DEFAULT_REMOTE)
+    }
+}
+
+/// Per-project preset for a recurring kind of worktree
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorktreeTemplate {
+    /// Unique identifier within the project
+    pub id: String,
+    /// Display name (e.g., "Bugfix")
+    pub name: String,
+    /// Branch to create from (None = project's default branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Prepended to generated worktree/branch names (e.g., "fix-")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_prefix: Option<String>,
+    /// Labels added to the GitHub issue the worktree is created from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issue_labels: Vec<String>,
+    /// Setup script run instead of jean.json's (None = jean.json's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup_script: Option<String>,
+    /// Scripts run in the new worktree after the setup script
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<String>,
+    /// Prompt to start the worktree's first session with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_prompt: Option<String>,
+}
+
+/// Per-project Slack notifications, posted through an incoming webhook or a
+/// bot token and channel
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SlackSettings {
+    /// Incoming webhook URL (takes precedence over the bot token)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Bot token (`xoxb-...`) for posting with `chat.postMessage`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_token: Option<String>,
+    /// Channel the bot posts to (ID or `#name`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Post when a session run finishes after at least `min_session_secs`
+    #[serde(default)]
+    pub on_session_finished: bool,
+    /// Post when a worktree's PR is opened
+    #[serde(default)]
+    pub on_pr_opened: bool,
+    /// Post when a PR's checks start failing
+    #[serde(default)]
+    pub on_ci_failed: bool,
+    /// Shortest session run worth a notification, in seconds (0 = every run)
+    #[serde(default)]
+    pub min_session_secs: u64,
+    /// Message templates per event (None = built-in message)
+    #[serde(default)]
+    pub templates: SlackTemplates,
+}
+
+/// Slack message templates, with `{project}`, `{worktree}`, `{branch}`,
+/// `{session}`, `{duration}`, `{pr_number}` and `{pr_url}` placeholders
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SlackTemplates {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_finished: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr_opened: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci_failed: Option<String>,
+}
+
+/// Per-project checklist evaluated before a PR is created
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PrChecklist {
+    /// Test command that must pass (e.g. `npm test`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_command: Option<String>,
+    /// Lint command that must pass (e.g. `cargo clippy -- -D warnings`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lint_command: Option<String>,
+    /// Require an AI review of the current HEAD
+    #[serde(default)]
+    pub require_review: bool,
+    /// Fail when the diff adds TODO or FIXME comments
+    #[serde(default)]
+    pub no_todos: bool,
+    /// Minimum share of changed lines covered by tests, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_diff_coverage: Option<f64>,
+}
+
+/// Per-project allow/deny list for the licenses of dependencies a branch adds
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LicensePolicy {
+    /// SPDX IDs that are allowed (empty = any license that isn't denied)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// SPDX IDs that are never allowed (e.g. `GPL-3.0-only`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Treat dependencies without license metadata as disallowed
+    #[serde(default)]
+    pub deny_unknown: bool,
+    /// Only warn when creating a PR instead of blocking it
+    #[serde(default)]
+    pub warn_only: bool,
+}
+
+/// Allow/deny/ask lists of Claude tool permission rules, e.g. `Bash`,
+/// `Bash(npm run test:*)`, `WebFetch(domain:docs.rs)` or `Edit(src/**)`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ToolPolicy {
+    /// Tools used without asking
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Tools never used, whatever the execution mode or approvals
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Tools that always need approval
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ask: Vec<String>,
+}
+
+/// A failed pre-PR checklist that was overridden
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChecklistOverride {
+    /// Labels of the failed items
+    pub failed: Vec<String>,
+    pub reason: String,
+    /// Unix timestamp of the override
+    pub overridden_at: u64,
+}
+
+/// Per-project overrides for background git/remote polling
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PollingOverrides {
+    /// Local git status interval in seconds (None = global interval)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_interval: Option<u64>,
+    /// Remote (fetch, PR status) interval in seconds (None = global interval)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_interval: Option<u64>,
+    /// Whether to fetch and poll PR status at all (off for repos without a remote)
+    #[serde(default = "default_remote_enabled")]
+    pub remote_enabled: bool,
+    /// Daily window with no scheduled polling, e.g. overnight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// A daily window in local time, as "HH:MM" (wraps past midnight when `end`
+/// is before `start`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    /// Minutes since midnight of an "HH:MM" time
+    fn minutes(time: &str) -> Option<u32> {
+        let (hours, minutes) = time.trim().split_once(':')?;
+        let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+        (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for time in [&self.start, &self.end] {
+            if Self::minutes(time).is_none() {
+                return Err(format!("Invalid quiet hours time (expected HH:MM): {time}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a time of day (minutes since midnight) falls in the window
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        let (Some(start), Some(end)) = (Self::minutes(&self.start), Self::minutes(&self.end))
+        else {
+            return false;
+        };
+        if start <= end {
+            (start..end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= start || minute_of_day < end
+        }
+    }
+}
+
+fn default_remote_enabled() -> bool {
+    true
+}
+
+impl Default for PollingOverrides {
+    fn default() -> Self {
+        Self {
+            local_interval: None,
+            remote_interval: None,
+            remote_enabled: true,
+            quiet_hours: None,
+        }
+    }
 }
 
 /// A git worktree created for a project
@@ -141,6 +428,32 @@ pub struct Worktree {
     /// Unix timestamp when worktree was archived (None = not archived)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub archived_at: Option<u64>,
+    /// HEAD commit at the last AI review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewed_commit: Option<String>,
+    /// Last time the pre-PR checklist was overridden
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checklist_override: Option<ChecklistOverride>,
+    /// Worktree this one is stacked on (None = based on the default branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_parent: Option<StackParent>,
+    /// Stash holding the uncommitted changes set aside when archiving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_stash: Option<String>,
+    /// jean.json env profile selected for the worktree (None = the "default"
+    /// profile, if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_profile: Option<String>,
+}
+
+/// The worktree a stacked worktree's branch is based on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StackParent {
+    pub worktree_id: String,
+    /// Parent's branch (kept so children can be restacked after it's deleted)
+    pub branch: String,
+    /// Parent branch commit the child is currently based on
+    pub base_commit: String,
 }
 
 /// Container for all persisted project data
@@ -360,6 +673,9 @@ pub struct WorktreeCreatingEvent {
 pub struct WorktreeCreatedEvent {
     /// The fully created worktree
     pub worktree: Worktree,
+    /// Initial prompt from the worktree template, for the UI to send
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_prompt: Option<String>,
 }
 
 /// Event emitted when worktree creation fails
@@ -470,3 +786,34 @@ pub struct WorktreeBranchExistsEvent {
     /// PR context to use when creating a new worktree with the suggested name
     pub pr_context: Option<super::github_issues::PullRequestContext>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours() {
+        let overnight = QuietHours {
+            start: "22:30".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert!(overnight.validate().is_ok());
+        assert!(overnight.contains(23 * 60));
+        assert!(overnight.contains(6 * 60 + 59));
+        assert!(!overnight.contains(7 * 60));
+        assert!(!overnight.contains(12 * 60));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(12 * 60 + 30));
+        assert!(!lunch.contains(13 * 60));
+
+        let invalid = QuietHours {
+            start: "25:00".to_string(),
+            end: "7am".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,39 @@
+//! Tauri commands for session search
+
+use tauri::AppHandle;
+
+use super::{fts_query, query_hits, SearchHit};
+
+/// Number of hits returned
+const MAX_HITS: u32 = 50;
+
+/// Search the messages of all sessions, best matches first.
+///
+/// With `project_id`, only sessions of that project's worktrees are searched.
+#[tauri::command]
+pub async fn search_sessions(
+    app: AppHandle,
+    query: String,
+    project_id: Option<String>,
+) -> Result<Vec<SearchHit>, String> {
+    log::trace!("Searching sessions for {query:?} (project: {project_id:?})");
+    let Some(fts_query) = fts_query(&query) else {
+        return Ok(Vec::new());
+    };
+    let worktree_ids = match &project_id {
+        Some(id) => {
+            let data = crate::projects::storage::load_projects_data(&app)?;
+            Some(
+                data.worktrees_for_project(id)
+                    .into_iter()
+                    .map(|w| w.id.clone())
+                    .collect::<Vec<_>>(),
+            )
+        }
+        None => None,
+    };
+
+    crate::db::with_db(&app, |conn| {
+        query_hits(conn, &fts_query, worktree_ids.as_deref(), MAX_HITS)
+    })
+}
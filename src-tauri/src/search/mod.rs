@@ -0,0 +1,284 @@
+//! Full-text search across sessions
+//!
+//! Session messages are indexed into the `search_messages` FTS5 table of the
+//! app database. Indexing is incremental: `search_sessions` remembers how many
+//! of a session's messages are indexed, and each metadata save indexes only
+//! the messages added since (sessions with a run in progress are picked up
+//! once it finishes). Sessions saved before the index existed are indexed in
+//! the background at startup.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::chat::run_log::load_session_messages_from;
+use crate::chat::storage::{list_all_session_ids, load_metadata};
+use crate::chat::types::{ChatMessage, MessageRole, RunStatus, SessionMetadata};
+use crate::db::with_db;
+
+pub mod commands;
+
+/// A message matching a search
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub worktree_id: String,
+    pub message_id: String,
+    /// Position of the message in the session's history
+    pub message_index: usize,
+    pub role: String,
+    pub timestamp: u64,
+    /// Matching excerpt with the matched terms wrapped in `**`
+    pub snippet: String,
+    /// Relevance; higher is better
+    pub score: f64,
+}
+
+/// FTS5 query matching messages that contain every word of `query`, the last
+/// one as a prefix so results follow typing. None for a blank query.
+pub fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\""))
+        .collect();
+    let (last, rest) = terms.split_last()?;
+    Some(
+        rest.iter()
+            .cloned()
+            .chain([format!("{last}*")])
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Worktree and number of indexed messages of a session
+fn indexed_state(conn: &Connection, session_id: &str) -> Result<Option<(String, usize)>, String> {
+    conn.query_row(
+        "SELECT worktree_id, indexed_messages FROM search_sessions WHERE session_id = ?1",
+        [session_id],
+        |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to read search index: {e}"))
+}
+
+/// Add a session's messages from position `start` on (replacing the whole
+/// session when `start` is 0) and record `total` as indexed
+pub fn index_messages(
+    conn: &Connection,
+    session_id: &str,
+    worktree_id: &str,
+    start: usize,
+    messages: &[ChatMessage],
+    total: usize,
+) -> Result<(), String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to update search index: {e}"))?;
+    if start == 0 {
+        tx.execute(
+            "DELETE FROM search_messages WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| format!("Failed to update search index: {e}"))?;
+    }
+    for (offset, message) in messages.iter().enumerate() {
+        let role = match message.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        };
+        tx.execute(
+            "INSERT INTO search_messages
+                (content, session_id, message_id, message_index, role, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                message.content,
+                session_id,
+                message.id,
+                (start + offset) as i64,
+                role,
+                message.timestamp as i64
+            ],
+        )
+        .map_err(|e| format!("Failed to update search index: {e}"))?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO search_sessions (session_id, worktree_id, indexed_messages)
+         VALUES (?1, ?2, ?3)",
+        params![session_id, worktree_id, total as i64],
+    )
+    .map_err(|e| format!("Failed to update search index: {e}"))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to update search index: {e}"))
+}
+
+/// Index the messages a session gained since it was last indexed
+pub fn update_session(app: &AppHandle, metadata: &SessionMetadata) -> Result<(), String> {
+    if metadata.runs.iter().any(|r| r.status == RunStatus::Running) {
+        return Ok(());
+    }
+    let indexed = with_db(app, |conn| indexed_state(conn, &metadata.id))?;
+    let mut start = indexed.as_ref().map_or(0, |(_, count)| *count);
+    let (mut messages, total) = load_session_messages_from(app, metadata, start)?;
+    if total < start {
+        // History got shorter; index it again from scratch
+        start = 0;
+        messages = load_session_messages_from(app, metadata, 0)?.0;
+    }
+    if messages.is_empty() && indexed == Some((metadata.worktree_id.clone(), total)) {
+        return Ok(());
+    }
+    with_db(app, |conn| {
+        index_messages(
+            conn,
+            &metadata.id,
+            &metadata.worktree_id,
+            start,
+            &messages,
+            total,
+        )
+    })
+}
+
+/// Index a saved session's new messages in the background
+pub fn session_saved(app: &AppHandle, metadata: &SessionMetadata) {
+    let app = app.clone();
+    let metadata = metadata.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = update_session(&app, &metadata) {
+            log::warn!("Failed to index session {}: {e}", metadata.id);
+        }
+    });
+}
+
+/// Drop a deleted session from the index
+pub fn remove_session(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    with_db(app, |conn| {
+        conn.execute(
+            "DELETE FROM search_messages WHERE session_id = ?1",
+            [session_id],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM search_sessions WHERE session_id = ?1",
+                [session_id],
+            )
+        })
+        .map_err(|e| format!("Failed to update search index: {e}"))?;
+        Ok(())
+    })
+}
+
+/// Bring every stored session up to date in the index
+pub fn index_all(app: &AppHandle) -> Result<(), String> {
+    for session_id in list_all_session_ids(app)? {
+        let Some(metadata) = load_metadata(app, &session_id)? else {
+            continue;
+        };
+        if let Err(e) = update_session(app, &metadata) {
+            log::warn!("Failed to index session {session_id}: {e}");
+        }
+    }
+    log::trace!("Session search index is up to date");
+    Ok(())
+}
+
+/// Best matches for an FTS5 query, optionally only in some worktrees
+pub fn query_hits(
+    conn: &Connection,
+    fts_query: &str,
+    worktree_ids: Option<&[String]>,
+    limit: u32,
+) -> Result<Vec<SearchHit>, String> {
+    let worktree_ids = worktree_ids
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to search sessions: {e}"))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT search_messages.session_id, search_sessions.worktree_id, message_id,
+                    message_index, role, timestamp,
+                    snippet(search_messages, 0, '**', '**', '…', 16), bm25(search_messages)
+             FROM search_messages
+             JOIN search_sessions ON search_sessions.session_id = search_messages.session_id
+             WHERE search_messages MATCH ?1
+               AND (?2 IS NULL OR search_sessions.worktree_id IN (SELECT value FROM json_each(?2)))
+             ORDER BY bm25(search_messages) LIMIT ?3",
+        )
+        .map_err(|e| format!("Failed to search sessions: {e}"))?;
+    let rows = stmt
+        .query_map(params![fts_query, worktree_ids, limit], |row| {
+            Ok(SearchHit {
+                session_id: row.get(0)?,
+                worktree_id: row.get(1)?,
+                message_id: row.get(2)?,
+                message_index: row.get::<_, i64>(3)? as usize,
+                role: row.get(4)?,
+                timestamp: row.get::<_, i64>(5)? as u64,
+                snippet: row.get(6)?,
+                // bm25 is lower for better matches
+                score: -row.get::<_, f64>(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to search sessions: {e}"))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to search sessions: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(
+            fts_query("login \"redirect bug"),
+            Some("\"login\" \"redirect\" \"bug\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn test_index_and_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_connection(&conn).unwrap();
+        let message = |id: &str, content: &str| ChatMessage {
+            id: id.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        index_messages(
+            &conn,
+            "s1",
+            "w1",
+            0,
+            &[message("m1", "Fix the login redirect")],
+            1,
+        )
+        .unwrap();
+        index_messages(
+            &conn,
+            "s1",
+            "w1",
+            1,
+            &[message("m2", "Redirects are handled in the router")],
+            2,
+        )
+        .unwrap();
+        index_messages(&conn, "s2", "w2", 0, &[message("m3", "Login page")], 1).unwrap();
+
+        let hits = query_hits(&conn, &fts_query("redirect").unwrap(), None, 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits
+            .iter()
+            .any(|h| h.message_id == "m2" && h.message_index == 1));
+
+        let only_w2 = ["w2".to_string()];
+        let hits = query_hits(&conn, &fts_query("login").unwrap(), Some(&only_w2), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "s2");
+        assert_eq!(hits[0].snippet, "**Login** page");
+    }
+}
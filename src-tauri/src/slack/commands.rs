@@ -0,0 +1,25 @@
+//! Tauri commands for Slack notifications
+
+use tauri::AppHandle;
+
+use crate::projects::storage::load_projects_data;
+
+/// Post a test message with a project's Slack settings
+#[tauri::command]
+pub async fn send_slack_test_message(app: AppHandle, project_id: String) -> Result<(), String> {
+    log::trace!("Sending Slack test message for project {project_id}");
+    let data = load_projects_data(&app)?;
+    let project = data
+        .find_project(&project_id)
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let settings = project
+        .slack
+        .as_ref()
+        .ok_or_else(|| format!("Slack is not set up for {}", project.name))?;
+
+    super::post(
+        settings,
+        &format!("Jean notifications for *{}* are working.", project.name),
+    )
+    .await
+}
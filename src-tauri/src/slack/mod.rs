@@ -0,0 +1,153 @@
+//! Slack notifications
+//!
+//! Projects with Slack settings get a message when a long session run
+//! finishes, a PR is opened or CI starts failing. Messages go to an incoming
+//! webhook, or through `chat.postMessage` with a bot token, and are built from
+//! per-project templates (see `SlackTemplates` for the placeholders).
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::notifications::{render, NotificationEvent};
+use crate::projects::types::SlackSettings;
+
+pub mod commands;
+
+const CHAT_POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+fn enabled(event: &NotificationEvent, settings: &SlackSettings) -> bool {
+    match event {
+        NotificationEvent::SessionFinished { duration_secs, .. } => {
+            settings.on_session_finished && *duration_secs >= settings.min_session_secs
+        }
+        NotificationEvent::PrOpened => settings.on_pr_opened,
+        NotificationEvent::CiFailed => settings.on_ci_failed,
+    }
+}
+
+fn template<'a>(event: &NotificationEvent, settings: &'a SlackSettings) -> &'a str {
+    let (custom, default) = match event {
+        NotificationEvent::SessionFinished { .. } => (
+            &settings.templates.session_finished,
+            ":white_check_mark: *{project}* / {worktree}: session \"{session}\" finished after {duration}",
+        ),
+        NotificationEvent::PrOpened => (
+            &settings.templates.pr_opened,
+            ":rocket: *{project}* / {worktree}: opened PR #{pr_number} {pr_url}",
+        ),
+        NotificationEvent::CiFailed => (
+            &settings.templates.ci_failed,
+            ":x: *{project}* / {worktree}: CI is failing on PR #{pr_number} {pr_url}",
+        ),
+    };
+    custom
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(default)
+}
+
+#[derive(Deserialize)]
+struct PostMessageResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Post a message with a project's Slack settings
+pub async fn post(settings: &SlackSettings, text: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .user_agent("Jean-App/1.0")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let non_empty = |v: &Option<String>| v.clone().filter(|v| !v.trim().is_empty());
+    if let Some(webhook_url) = non_empty(&settings.webhook_url) {
+        let response = client
+            .post(webhook_url)
+            .json(&json!({ "text": text }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to post to Slack: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Slack webhook returned status: {}",
+                response.status()
+            ));
+        }
+        return Ok(());
+    }
+
+    let (Some(token), Some(channel)) =
+        (non_empty(&settings.bot_token), non_empty(&settings.channel))
+    else {
+        return Err("Slack needs a webhook URL, or a bot token and a channel".to_string());
+    };
+    let response: PostMessageResponse = client
+        .post(CHAT_POST_MESSAGE_URL)
+        .bearer_auth(token.trim())
+        .json(&json!({ "channel": channel.trim(), "text": text }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to post to Slack: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Slack response: {e}"))?;
+    if !response.ok {
+        return Err(format!(
+            "Slack rejected the message: {}",
+            response.error.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+/// Post an event, if the project's settings ask for it
+pub async fn send(
+    settings: &SlackSettings,
+    event: &NotificationEvent,
+    vars: &[(&str, String)],
+) -> Result<(), String> {
+    if !enabled(event, settings) {
+        return Ok(());
+    }
+    post(settings, &render(template(event, settings), vars)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::format_duration;
+
+    #[test]
+    fn test_render_event_message() {
+        let mut settings = SlackSettings {
+            on_session_finished: true,
+            min_session_secs: 300,
+            ..Default::default()
+        };
+        let short = NotificationEvent::SessionFinished {
+            session: "Fix login".to_string(),
+            duration_secs: 60,
+        };
+        let long = NotificationEvent::SessionFinished {
+            session: "Fix login".to_string(),
+            duration_secs: 754,
+        };
+        assert!(!enabled(&short, &settings));
+        assert!(enabled(&long, &settings));
+        assert!(!enabled(&NotificationEvent::PrOpened, &settings));
+
+        settings.templates.session_finished =
+            Some("{worktree}: {session} ({duration})".to_string());
+        let vars = [
+            ("worktree", "fuzzy-tiger".to_string()),
+            ("session", "Fix login".to_string()),
+            ("duration", format_duration(754)),
+        ];
+        assert_eq!(
+            render(template(&long, &settings), &vars),
+            "fuzzy-tiger: Fix login (12m 34s)"
+        );
+        assert_eq!(format_duration(3725), "1h 2m");
+    }
+}
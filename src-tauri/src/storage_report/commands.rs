@@ -0,0 +1,285 @@
+//! Tauri commands for the disk usage report
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::worktrees::{measure, WorktreeDiskUsage, DEFAULT_STALE_DAYS};
+use super::{
+    expired_pastes, path_usage, remove_files_where, remove_unreferenced, StorageCategory, Usage,
+};
+use crate::chat::storage::{get_data_dir, get_images_dir, get_pastes_dir, get_saved_contexts_dir};
+use crate::http_server::EmitExt;
+use crate::projects::types::SessionType;
+
+/// Disk usage of one category
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryUsage {
+    pub category: StorageCategory,
+    pub bytes: u64,
+    pub files: u64,
+    pub cleanup_description: String,
+}
+
+/// Disk usage broken down by category
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageReport {
+    pub categories: Vec<CategoryUsage>,
+    pub total_bytes: u64,
+}
+
+/// Result of cleaning up one category
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageCleanupResult {
+    pub category: StorageCategory,
+    /// Number of items (worktrees, sessions or files) removed or compressed
+    pub removed: u32,
+    pub freed_bytes: u64,
+}
+
+/// Disk usage of a project's worktrees
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeDiskUsageReport {
+    pub project_id: String,
+    pub worktrees: Vec<WorktreeDiskUsage>,
+    pub total_bytes: u64,
+}
+
+/// Payload of `worktrees:cleanup-candidates`
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupCandidatesEvent {
+    pub project_id: String,
+    /// Stale worktrees, largest first
+    pub worktree_ids: Vec<String>,
+    pub reclaimable_bytes: u64,
+}
+
+fn recovery_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::locations::app_data_dir(app)?.join("recovery"))
+}
+
+fn combined_contexts_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::locations::app_data_dir(app)?.join("combined-contexts"))
+}
+
+/// Paths that make up a category
+fn category_paths(app: &AppHandle, category: StorageCategory) -> Result<Vec<PathBuf>, String> {
+    Ok(match category {
+        // Base sessions live in the project's own checkout, which isn't ours to count
+        StorageCategory::Worktrees => crate::projects::storage::load_projects_data(app)?
+            .worktrees
+            .iter()
+            .filter(|w| w.session_type != SessionType::Base)
+            .map(|w| PathBuf::from(&w.path))
+            .collect(),
+        StorageCategory::SessionTranscripts => vec![get_data_dir(app)?],
+        StorageCategory::PastedImages => vec![get_images_dir(app)?],
+        StorageCategory::PastedTexts => vec![get_pastes_dir(app)?],
+        StorageCategory::Contexts => vec![
+            crate::projects::github_issues::get_github_contexts_dir(app)?,
+            get_saved_contexts_dir(app)?,
+            combined_contexts_dir(app)?,
+        ],
+        StorageCategory::RecoveryFiles => vec![recovery_dir(app)?],
+    })
+}
+
+fn category_usage(app: &AppHandle, category: StorageCategory) -> Result<Usage, String> {
+    let mut usage = Usage::default();
+    for path in category_paths(app, category)? {
+        usage.add(path_usage(&path));
+    }
+    Ok(usage)
+}
+
+/// Which of `names` appear in any session's stored messages
+fn referenced_in_sessions(app: &AppHandle, names: &[String]) -> Result<HashSet<String>, String> {
+    crate::db::with_db(app, |conn| {
+        let mut stmt = conn
+            .prepare("SELECT EXISTS(SELECT 1 FROM session_metadata WHERE instr(data, ?1) > 0)")
+            .map_err(|e| format!("Failed to check file references: {e}"))?;
+        let mut referenced = HashSet::new();
+        for name in names {
+            let exists: bool = stmt
+                .query_row([name], |row| row.get(0))
+                .map_err(|e| format!("Failed to check file references: {e}"))?;
+            if exists {
+                referenced.insert(name.clone());
+            }
+        }
+        Ok(referenced)
+    })
+}
+
+/// Delete pasted files that are past the grace period and not referenced by any session
+fn cleanup_pastes(app: &AppHandle, dir: PathBuf) -> Result<u32, String> {
+    let candidates = expired_pastes(&dir);
+    let referenced = referenced_in_sessions(app, &candidates)?;
+    Ok(remove_unreferenced(&dir, &candidates, &referenced))
+}
+
+/// Remove run log directories of deleted sessions and compress the logs of
+/// archived sessions
+fn cleanup_transcripts(app: &AppHandle) -> Result<u32, String> {
+    let known: HashSet<String> = crate::chat::storage::list_all_session_ids(app)?
+        .iter()
+        .map(|id| crate::chat::storage::sanitize_filename(id))
+        .collect();
+
+    let mut removed = 0;
+    let entries = std::fs::read_dir(get_data_dir(app)?)
+        .map_err(|e| format!("Failed to read session data directory: {e}"))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.path().is_dir() || known.contains(&name) {
+            continue;
+        }
+        match std::fs::remove_dir_all(entry.path()) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("Failed to remove orphaned session data {name}: {e}"),
+        }
+    }
+
+    let data = crate::projects::storage::load_projects_data(app)?;
+    for worktree in &data.worktrees {
+        let index = crate::chat::storage::load_index(app, &worktree.id)?;
+        for session in &index.sessions {
+            if worktree.archived_at.is_none() && session.archived_at.is_none() {
+                continue;
+            }
+            match crate::chat::run_log::compress_session_logs(app, &session.id) {
+                Ok(saved) if saved > 0 => removed += 1,
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to compress logs for session {}: {e}", session.id),
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Delete orphaned issue/PR contexts and the combined context files, which are
+/// regenerated whenever a message is sent
+fn cleanup_contexts(app: &AppHandle) -> Result<u32, String> {
+    let mut removed = crate::projects::github_issues::cleanup_orphaned_contexts(app, 0)?;
+    removed += remove_files_where(&combined_contexts_dir(app)?, |name| name.ends_with(".md"));
+    Ok(removed)
+}
+
+/// Report disk usage per storage category
+#[tauri::command]
+pub async fn get_storage_report(app: AppHandle) -> Result<StorageReport, String> {
+    log::trace!("Building storage report");
+
+    let mut categories = Vec::with_capacity(StorageCategory::ALL.len());
+    for category in StorageCategory::ALL {
+        let usage = category_usage(&app, category)?;
+        categories.push(CategoryUsage {
+            category,
+            bytes: usage.bytes,
+            files: usage.files,
+            cleanup_description: category.cleanup_description().to_string(),
+        });
+    }
+    let total_bytes = categories.iter().map(|c| c.bytes).sum();
+
+    Ok(StorageReport {
+        categories,
+        total_bytes,
+    })
+}
+
+/// Report the disk usage of each of a project's worktrees, largest first
+///
+/// Worktrees without commits in `stale_days` (default 30) are flagged as
+/// stale, and emitted as cleanup candidates in a
+/// `worktrees:cleanup-candidates` event.
+#[tauri::command]
+pub async fn get_worktree_disk_usage(
+    app: AppHandle,
+    project_id: String,
+    stale_days: Option<u32>,
+) -> Result<WorktreeDiskUsageReport, String> {
+    log::trace!("Measuring worktree disk usage for project {project_id}");
+    let data = crate::projects::storage::load_projects_data(&app)?;
+    let stale_days = stale_days.unwrap_or(DEFAULT_STALE_DAYS);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Base sessions live in the project's own checkout, which isn't ours to clean up
+    let mut worktrees: Vec<WorktreeDiskUsage> = data
+        .worktrees_for_project(&project_id)
+        .into_iter()
+        .filter(|w| w.session_type != SessionType::Base && std::path::Path::new(&w.path).exists())
+        .map(|w| measure(&w.id, &w.name, &w.path, now, stale_days))
+        .collect();
+    worktrees.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes));
+    let total_bytes = worktrees.iter().map(|w| w.total_bytes).sum();
+
+    let stale: Vec<&WorktreeDiskUsage> = worktrees.iter().filter(|w| w.stale).collect();
+    if !stale.is_empty() {
+        let event = CleanupCandidatesEvent {
+            project_id: project_id.clone(),
+            worktree_ids: stale.iter().map(|w| w.worktree_id.clone()).collect(),
+            reclaimable_bytes: stale.iter().map(|w| w.total_bytes).sum(),
+        };
+        if let Err(e) = app.emit_all("worktrees:cleanup-candidates", &event) {
+            log::error!("Failed to emit worktrees:cleanup-candidates event: {e}");
+        }
+    }
+
+    Ok(WorktreeDiskUsageReport {
+        project_id,
+        worktrees,
+        total_bytes,
+    })
+}
+
+/// Run the cleanup action for one storage category
+#[tauri::command]
+pub async fn cleanup_storage_category(
+    app: AppHandle,
+    category: StorageCategory,
+) -> Result<StorageCleanupResult, String> {
+    // Transcripts and combined contexts are written by running sessions
+    if matches!(
+        category,
+        StorageCategory::SessionTranscripts | StorageCategory::Contexts
+    ) && crate::updater::has_running_sessions()
+    {
+        return Err("Cannot clean up this category while sessions are running".to_string());
+    }
+
+    log::trace!("Cleaning up storage category {category:?}");
+    let before = category_usage(&app, category)?;
+
+    let removed = match category {
+        StorageCategory::Worktrees => {
+            let result = crate::projects::delete_all_archives(app.clone()).await?;
+            result.deleted_worktrees + result.deleted_sessions
+        }
+        StorageCategory::SessionTranscripts => cleanup_transcripts(&app)?,
+        StorageCategory::PastedImages => cleanup_pastes(&app, get_images_dir(&app)?)?,
+        StorageCategory::PastedTexts => cleanup_pastes(&app, get_pastes_dir(&app)?)?,
+        StorageCategory::Contexts => cleanup_contexts(&app)?,
+        StorageCategory::RecoveryFiles => {
+            remove_files_where(&recovery_dir(&app)?, |name| name.ends_with(".json"))
+        }
+    };
+
+    let after = category_usage(&app, category)?;
+    let freed_bytes = before.bytes.saturating_sub(after.bytes);
+    log::trace!(
+        "Storage cleanup of {category:?} removed {removed} item(s), freed {freed_bytes} bytes"
+    );
+
+    Ok(StorageCleanupResult {
+        category,
+        removed,
+        freed_bytes,
+    })
+}
@@ -0,0 +1,185 @@
+//! Disk usage report
+//!
+//! Breaks down how much disk space Jean uses per category (worktrees, session
+//! transcripts, pasted files, contexts, recovery files) and offers a cleanup
+//! action for each one. Sizes are computed by walking the directories on
+//! demand; symlinks are not followed.
+//!
+//! A project's worktrees can also be measured one by one (see [`worktrees`]).
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+pub mod commands;
+pub mod worktrees;
+
+/// Pasted files younger than this are kept by cleanup, since they may belong
+/// to a message that is still being drafted
+const PASTE_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A storage category shown in the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    Worktrees,
+    SessionTranscripts,
+    PastedImages,
+    PastedTexts,
+    Contexts,
+    RecoveryFiles,
+}
+
+impl StorageCategory {
+    pub const ALL: [StorageCategory; 6] = [
+        StorageCategory::Worktrees,
+        StorageCategory::SessionTranscripts,
+        StorageCategory::PastedImages,
+        StorageCategory::PastedTexts,
+        StorageCategory::Contexts,
+        StorageCategory::RecoveryFiles,
+    ];
+
+    /// What the cleanup action for this category does (shown in the UI)
+    pub fn cleanup_description(self) -> &'static str {
+        match self {
+            StorageCategory::Worktrees => "Permanently delete all archived worktrees and sessions",
+            StorageCategory::SessionTranscripts => {
+                "Remove transcripts of deleted sessions and compress archived ones"
+            }
+            StorageCategory::PastedImages => "Delete images not referenced by any session",
+            StorageCategory::PastedTexts => "Delete pasted texts not referenced by any session",
+            StorageCategory::Contexts => {
+                "Delete orphaned issue/PR contexts and regenerable combined contexts"
+            }
+            StorageCategory::RecoveryFiles => "Delete all crash recovery files",
+        }
+    }
+}
+
+/// Total size and file count of a set of paths
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+impl Usage {
+    pub fn add(&mut self, other: Usage) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+    }
+}
+
+/// Recursively measure a file or directory (missing paths count as empty)
+pub fn path_usage(path: &Path) -> Usage {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Usage::default();
+    };
+
+    if metadata.is_file() {
+        return Usage {
+            bytes: metadata.len(),
+            files: 1,
+        };
+    }
+
+    let mut usage = Usage::default();
+    if metadata.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                usage.add(path_usage(&entry.path()));
+            }
+        }
+    }
+    usage
+}
+
+/// Delete the files directly in `dir` for which `should_delete(file_name)`
+/// returns true. Returns the number of files removed.
+pub fn remove_files_where<F>(dir: &Path, mut should_delete: F) -> u32
+where
+    F: FnMut(&str) -> bool,
+{
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !should_delete(&name) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("Failed to remove {path:?}: {e}"),
+        }
+    }
+    removed
+}
+
+/// Names of pasted files in `dir` that are past the grace period
+pub fn expired_pastes(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let cutoff = SystemTime::now() - PASTE_GRACE_PERIOD;
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+/// Delete the pasted files in `dir` named in `candidates` that aren't in `referenced`
+pub fn remove_unreferenced(dir: &Path, candidates: &[String], referenced: &HashSet<String>) -> u32 {
+    remove_files_where(dir, |name| {
+        candidates.iter().any(|c| c == name) && !referenced.contains(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_usage_counts_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("one.txt"), "12345").unwrap();
+        fs::write(dir.path().join("a/b/two.txt"), "123").unwrap();
+
+        assert_eq!(path_usage(dir.path()), Usage { bytes: 8, files: 2 });
+        assert_eq!(path_usage(&dir.path().join("missing")), Usage::default());
+    }
+
+    #[test]
+    fn test_remove_unreferenced_keeps_referenced_and_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["used.png", "unused.png", "fresh.png"] {
+            fs::write(dir.path().join(name), "x").unwrap();
+        }
+
+        let candidates = vec!["used.png".to_string(), "unused.png".to_string()];
+        let referenced = HashSet::from(["used.png".to_string()]);
+
+        assert_eq!(remove_unreferenced(dir.path(), &candidates, &referenced), 1);
+        assert!(dir.path().join("used.png").exists());
+        assert!(!dir.path().join("unused.png").exists());
+        assert!(dir.path().join("fresh.png").exists());
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-worktree disk usage
+//!
+//! Each worktree is measured twice: in full (dependencies, build output and
+//! other ignored files included) and through the same ignore-aware walk as the
+//! file listing, so the report can tell source size from what is regenerable.
+//! Worktrees without commits for a while are flagged as cleanup candidates.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{path_usage, Usage};
+use crate::command_audit::AuditedCommand;
+use crate::projects::file_listing::worktree_walker;
+use crate::projects::git_queue;
+
+/// Dependency and build directories measured on their own
+const DEPENDENCY_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// Days without commits after which a worktree is a cleanup candidate
+pub const DEFAULT_STALE_DAYS: u32 = 30;
+
+/// Disk usage of one worktree
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeDiskUsage {
+    pub worktree_id: String,
+    pub name: String,
+    pub path: String,
+    /// Everything under the worktree
+    pub total_bytes: u64,
+    /// Files that aren't ignored by git
+    pub source_bytes: u64,
+    /// Top-level `node_modules` and `target` directories
+    pub dependency_bytes: u64,
+    /// Unix timestamp of the last commit on the worktree's branch
+    pub last_commit_at: Option<u64>,
+    /// No commits within the stale period
+    pub stale: bool,
+}
+
+/// Size of the files the ignore-aware walker visits
+pub fn source_usage(path: &Path) -> Usage {
+    let mut usage = Usage::default();
+    for entry in worktree_walker(path).build().flatten() {
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            if let Ok(metadata) = entry.metadata() {
+                usage.add(Usage {
+                    bytes: metadata.len(),
+                    files: 1,
+                });
+            }
+        }
+    }
+    usage
+}
+
+fn last_commit_at(path: &str) -> Option<u64> {
+    let output = git_queue::read_command()
+        .args(["log", "-1", "--format=%ct", "HEAD"])
+        .current_dir(path)
+        .output_audited()
+        .ok()
+        .filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Whether a worktree last committed to at `last_commit_at` is stale at `now`
+pub fn is_stale(last_commit_at: Option<u64>, now: u64, stale_days: u32) -> bool {
+    last_commit_at.is_some_and(|at| now.saturating_sub(at) >= stale_days as u64 * 24 * 60 * 60)
+}
+
+/// Measure a worktree
+pub fn measure(
+    worktree_id: &str,
+    name: &str,
+    path: &str,
+    now: u64,
+    stale_days: u32,
+) -> WorktreeDiskUsage {
+    let root = Path::new(path);
+    let dependency_bytes = DEPENDENCY_DIRS
+        .iter()
+        .map(|dir| path_usage(&root.join(dir)).bytes)
+        .sum();
+    let last_commit_at = last_commit_at(path);
+    WorktreeDiskUsage {
+        worktree_id: worktree_id.to_string(),
+        name: name.to_string(),
+        path: path.to_string(),
+        total_bytes: path_usage(root).bytes,
+        source_bytes: source_usage(root).bytes,
+        dependency_bytes,
+        last_commit_at,
+        stale: is_stale(last_commit_at, now, stale_days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_source_usage_skips_ignored_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "node_modules\n").unwrap();
+        fs::write(dir.path().join("index.js"), "12345").unwrap();
+        fs::write(dir.path().join("node_modules/pkg/lib.js"), "1234567890").unwrap();
+
+        assert_eq!(
+            source_usage(dir.path()),
+            Usage {
+                bytes: 18,
+                files: 2
+            }
+        );
+        assert_eq!(path_usage(dir.path()).bytes, 28);
+
+        let day = 24 * 60 * 60;
+        assert!(is_stale(Some(0), 30 * day, 30));
+        assert!(!is_stale(Some(day), 30 * day, 30));
+        assert!(!is_stale(None, 30 * day, 30));
+    }
+}
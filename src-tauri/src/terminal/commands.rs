@@ -6,6 +6,7 @@ use super::pty::{
 };
 use super::registry::{get_all_terminal_ids, has_terminal};
 use crate::projects::git::read_jean_config;
+use crate::projects::packages::validate_path;
 
 /// Start a terminal
 #[tauri::command]
@@ -24,13 +25,35 @@ pub async fn start_terminal(
         return Err("Terminal already exists".to_string());
     }
 
-    spawn_terminal(&app, terminal_id, worktree_path, cols, rows, command)
+    // Projects using a devcontainer get their terminals inside it
+    let container = crate::projects::devcontainer::exec_for(&app, &worktree_path)?;
+
+    spawn_terminal(
+        &app,
+        terminal_id,
+        worktree_path,
+        cols,
+        rows,
+        command,
+        container,
+    )
 }
 
 /// Get the run script from jean.json for a worktree
+///
+/// With `package_path`, a run script in the package's own jean.json wins over
+/// the worktree's, and runs from the package directory.
 #[tauri::command]
-pub async fn get_run_script(worktree_path: String) -> Option<String> {
-    read_jean_config(&worktree_path).and_then(|config| config.scripts.run)
+pub async fn get_run_script(worktree_path: String, package_path: Option<String>) -> Option<String> {
+    let root = std::path::Path::new(&worktree_path);
+    let package_script = package_path
+        .and_then(|p| validate_path(root, &p).ok())
+        .and_then(|p| {
+            let config = read_jean_config(&root.join(&p).to_string_lossy())?;
+            Some(format!("cd \"{p}\" && {}", config.scripts.run?))
+        });
+    package_script
+        .or_else(|| read_jean_config(&worktree_path).and_then(|config| config.scripts.run))
 }
 
 /// Write data to a terminal (stdin)
@@ -8,6 +8,7 @@ use super::registry::{register_terminal, unregister_terminal};
 use super::types::{
     TerminalOutputEvent, TerminalSession, TerminalStartedEvent, TerminalStoppedEvent,
 };
+use crate::projects::devcontainer::Exec;
 
 /// Detect user's default shell (cross-platform)
 fn get_user_shell() -> String {
@@ -22,6 +23,7 @@ pub fn spawn_terminal(
     cols: u16,
     rows: u16,
     command: Option<String>,
+    container: Option<Exec>,
 ) -> Result<(), String> {
     log::trace!("Spawning terminal {terminal_id} at {worktree_path}");
     if let Some(ref cmd) = command {
@@ -44,8 +46,27 @@ pub fn spawn_terminal(
     let shell = get_user_shell();
     log::trace!("Using shell: {shell}");
 
+    // jean.json env profile selected for the worktree
+    let profile_env = crate::projects::env_profiles::env_for(app, &worktree_path);
+
     // Build command - either run a specific command or start interactive shell
-    let mut cmd = if let Some(ref run_command) = command {
+    let mut cmd = if let Some(exec) = &container {
+        // Inside the devcontainer, with the container's own shell
+        let script = match &command {
+            Some(run_command) => format!(
+                "{run_command}; echo ''; echo '[Command finished. Press Ctrl+D to close]'; cat"
+            ),
+            None => "exec \"${SHELL:-/bin/sh}\" -l".to_string(),
+        };
+        let argv = ["sh".to_string(), "-lc".to_string(), script];
+        let mut env = vec![("TERM", "xterm-256color"), ("COLORTERM", "truecolor")];
+        env.extend(profile_env.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        let (program, args) = exec.command_line(&argv, &env, true);
+        log::trace!("Using devcontainer: {program} {}", args.join(" "));
+        let mut c = CommandBuilder::new(program);
+        c.args(args);
+        c
+    } else if let Some(ref run_command) = command {
         // Run the command in shell, then keep shell open for inspection
         let mut c = CommandBuilder::new(&shell);
         #[cfg(windows)]
@@ -72,6 +93,15 @@ pub fn spawn_terminal(
     cmd.env("TERM", "xterm-256color");
     cmd.env("COLORTERM", "truecolor");
     cmd.env("JEAN_WORKTREE_PATH", &worktree_path);
+    if container.is_none() {
+        // direnv / Nix environment of the worktree
+        for (key, value) in crate::projects::dev_env::env_for(&worktree_path) {
+            cmd.env(key, value);
+        }
+    }
+    for (key, value) in &profile_env {
+        cmd.env(key, value);
+    }
 
     // Spawn the shell
     let child = pair
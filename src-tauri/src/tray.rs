@@ -0,0 +1,275 @@
+//! System tray icon
+//!
+//! Shows how many sessions are running, waiting for input or finished (marked
+//! for review) across all active worktrees. The menu lists the waiting sessions;
+//! picking one brings the main window up and emits `tray:focus-session` for the
+//! frontend to open it. It also toggles the pause of all background polling.
+//!
+//! Counts are refreshed from stored session metadata every few seconds, and
+//! the menu is only rebuilt when they change.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::menu::{
+    CheckMenuItemBuilder, Menu, MenuBuilder, MenuEvent, MenuItemBuilder, PredefinedMenuItem,
+};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager};
+
+use crate::background_tasks::BackgroundTaskManager;
+use crate::http_server::EmitExt;
+use crate::projects::storage::load_projects_data;
+
+const TRAY_ID: &str = "jean-tray";
+
+/// Seconds between session count refreshes
+const REFRESH_INTERVAL: u64 = 5;
+
+/// Menu id prefix of waiting sessions, followed by `<worktree id>:<session id>`
+const SESSION_ITEM_PREFIX: &str = "tray-session:";
+const PAUSE_ITEM: &str = "tray-pause-polling";
+const SHOW_ITEM: &str = "tray-show";
+const QUIT_ITEM: &str = "tray-quit";
+
+/// A session waiting for input
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaitingSession {
+    pub worktree_id: String,
+    pub session_id: String,
+    /// "<worktree> · <session>"
+    pub label: String,
+}
+
+/// Session counts shown in the tray
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraySummary {
+    pub running: usize,
+    pub waiting: Vec<WaitingSession>,
+    /// Sessions marked for review
+    pub finished: usize,
+}
+
+/// Payload of `tray:focus-session`
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusSession {
+    pub worktree_id: String,
+    pub session_id: String,
+}
+
+/// Last summary and pause state the menu was built for
+static LAST_STATE: Lazy<Mutex<Option<(TraySummary, bool)>>> = Lazy::new(|| Mutex::new(None));
+
+impl TraySummary {
+    /// One-line status, e.g. "2 running · 1 waiting · 3 finished"
+    pub fn status_line(&self) -> String {
+        if self.running == 0 && self.waiting.is_empty() && self.finished == 0 {
+            return "No active sessions".to_string();
+        }
+        format!(
+            "{} running · {} waiting · {} finished",
+            self.running,
+            self.waiting.len(),
+            self.finished
+        )
+    }
+
+    pub fn tooltip(&self, paused: bool) -> String {
+        let mut tooltip = format!("Jean — {}", self.status_line());
+        if paused {
+            tooltip.push_str(" (polling paused)");
+        }
+        tooltip
+    }
+}
+
+/// Worktree and session ids of a waiting session's menu item
+fn parse_session_item(id: &str) -> Option<(&str, &str)> {
+    id.strip_prefix(SESSION_ITEM_PREFIX)?.split_once(':')
+}
+
+/// Count the sessions of all active worktrees
+fn summarize(app: &AppHandle) -> Result<TraySummary, String> {
+    let data = load_projects_data(app)?;
+    let running = crate::chat::registry::get_running_sessions();
+    let mut summary = TraySummary::default();
+    for worktree in data.worktrees.iter().filter(|w| w.archived_at.is_none()) {
+        let Ok(index) = crate::chat::storage::load_index(app, &worktree.id) else {
+            continue;
+        };
+        for entry in index.sessions.iter().filter(|s| s.archived_at.is_none()) {
+            if running.contains(&entry.id) {
+                summary.running += 1;
+                continue;
+            }
+            let Ok(Some(metadata)) = crate::chat::storage::load_metadata(app, &entry.id) else {
+                continue;
+            };
+            if metadata.waiting_for_input {
+                summary.waiting.push(WaitingSession {
+                    worktree_id: worktree.id.clone(),
+                    session_id: entry.id.clone(),
+                    label: format!("{} · {}", worktree.name, entry.name),
+                });
+            } else if metadata.is_reviewing {
+                summary.finished += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn build_menu(
+    app: &AppHandle,
+    summary: &TraySummary,
+    paused: bool,
+) -> tauri::Result<Menu<tauri::Wry>> {
+    let status = MenuItemBuilder::with_id("tray-status", summary.status_line())
+        .enabled(false)
+        .build(app)?;
+    let mut menu = MenuBuilder::new(app).item(&status);
+    if !summary.waiting.is_empty() {
+        menu = menu.item(&PredefinedMenuItem::separator(app)?);
+        for session in &summary.waiting {
+            let id = format!(
+                "{SESSION_ITEM_PREFIX}{}:{}",
+                session.worktree_id, session.session_id
+            );
+            menu = menu.item(&MenuItemBuilder::with_id(id, &session.label).build(app)?);
+        }
+    }
+    let pause = CheckMenuItemBuilder::with_id(PAUSE_ITEM, "Pause All Polling")
+        .checked(paused)
+        .build(app)?;
+    menu.item(&PredefinedMenuItem::separator(app)?)
+        .item(&pause)
+        .item(&PredefinedMenuItem::separator(app)?)
+        .item(&MenuItemBuilder::with_id(SHOW_ITEM, "Show Jean").build(app)?)
+        .item(&MenuItemBuilder::with_id(QUIT_ITEM, "Quit Jean").build(app)?)
+        .build()
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn is_paused(app: &AppHandle) -> bool {
+    app.try_state::<BackgroundTaskManager>()
+        .is_some_and(|manager| manager.is_paused())
+}
+
+/// Rebuild the tray menu and tooltip if the session counts or pause state
+/// changed (or always, with `force`)
+fn refresh(app: &AppHandle, force: bool) -> Result<(), String> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let summary = summarize(app)?;
+    let paused = is_paused(app);
+    let state = (summary, paused);
+    {
+        let mut last = LAST_STATE.lock().unwrap();
+        if !force && last.as_ref() == Some(&state) {
+            return Ok(());
+        }
+        *last = Some(state.clone());
+    }
+    let (summary, paused) = state;
+    let menu =
+        build_menu(app, &summary, paused).map_err(|e| format!("Failed to build tray menu: {e}"))?;
+    tray.set_menu(Some(menu))
+        .map_err(|e| format!("Failed to set tray menu: {e}"))?;
+    tray.set_tooltip(Some(summary.tooltip(paused)))
+        .map_err(|e| format!("Failed to set tray tooltip: {e}"))
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    log::trace!("Tray menu event: {id}");
+    match id {
+        PAUSE_ITEM => {
+            if let Some(manager) = app.try_state::<BackgroundTaskManager>() {
+                manager.set_paused(!manager.is_paused());
+            }
+            if let Err(e) = refresh(app, true) {
+                log::error!("Failed to refresh tray: {e}");
+            }
+        }
+        SHOW_ITEM => show_main_window(app),
+        QUIT_ITEM => app.exit(0),
+        _ => {
+            let Some((worktree_id, session_id)) = parse_session_item(id) else {
+                return;
+            };
+            show_main_window(app);
+            let payload = FocusSession {
+                worktree_id: worktree_id.to_string(),
+                session_id: session_id.to_string(),
+            };
+            if let Err(e) = app.emit_all("tray:focus-session", &payload) {
+                log::error!("Failed to emit tray:focus-session event: {e}");
+            }
+        }
+    }
+}
+
+/// Create the tray icon and start refreshing it
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let summary = TraySummary::default();
+    let menu =
+        build_menu(app, &summary, false).map_err(|e| format!("Failed to build tray menu: {e}"))?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(summary.tooltip(false))
+        .menu(&menu)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder
+        .build(app)
+        .map_err(|e| format!("Failed to create tray icon: {e}"))?;
+
+    let app = app.clone();
+    thread::spawn(move || loop {
+        if let Err(e) = refresh(&app, false) {
+            log::warn!("Failed to refresh tray: {e}");
+        }
+        thread::sleep(Duration::from_secs(REFRESH_INTERVAL));
+    });
+    log::trace!("Tray icon initialized");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_text_and_item_ids() {
+        let mut summary = TraySummary::default();
+        assert_eq!(summary.tooltip(false), "Jean — No active sessions");
+
+        summary.running = 2;
+        summary.finished = 1;
+        summary.waiting.push(WaitingSession {
+            worktree_id: "w1".to_string(),
+            session_id: "s1".to_string(),
+            label: "login · Session 1".to_string(),
+        });
+        assert_eq!(summary.status_line(), "2 running · 1 waiting · 1 finished");
+        assert_eq!(
+            summary.tooltip(true),
+            "Jean — 2 running · 1 waiting · 1 finished (polling paused)"
+        );
+
+        assert_eq!(parse_session_item("tray-session:w1:s1"), Some(("w1", "s1")));
+        assert_eq!(parse_session_item(PAUSE_ITEM), None);
+    }
+}
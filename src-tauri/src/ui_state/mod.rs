@@ -0,0 +1,193 @@
+//! Write-behind layer for UI state
+//!
+//! The frontend saves UI state on every sidebar resize or tab switch. Instead of
+//! rewriting `ui-state.json` each time, saves are queued here and coalesced: a
+//! single background writer flushes the latest state once updates have been
+//! quiet for `DEBOUNCE`, or at most `MAX_DELAY` after the first queued update.
+//! Pending state is flushed immediately when the app loses focus or exits.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, Once};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+use crate::UIState;
+
+/// Quiet period after the last update before writing
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Upper bound on how long an update can stay unwritten during constant churn
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Latest unwritten UI state
+struct Pending {
+    path: PathBuf,
+    state: UIState,
+    first_queued: Instant,
+    last_queued: Instant,
+}
+
+static PENDING: Lazy<(Mutex<Option<Pending>>, Condvar)> =
+    Lazy::new(|| (Mutex::new(None), Condvar::new()));
+
+static WRITER_STARTED: Once = Once::new();
+
+/// When a pending update should be written
+fn deadline(first_queued: Instant, last_queued: Instant) -> Instant {
+    (last_queued + DEBOUNCE).min(first_queued + MAX_DELAY)
+}
+
+/// Atomically write UI state to disk
+pub fn write_ui_state(path: &Path, ui_state: &UIState) -> Result<(), String> {
+    let json_content = serde_json::to_string_pretty(ui_state).map_err(|e| {
+        log::error!("Failed to serialize UI state: {e}");
+        format!("Failed to serialize UI state: {e}")
+    })?;
+
+    // Writes are serialized by the writer, so a fixed temp file name is enough
+    // and a crash leaves at most one temp file behind
+    let temp_path = path.with_extension("json.tmp");
+
+    std::fs::write(&temp_path, json_content).map_err(|e| {
+        log::error!("Failed to write UI state file: {e}");
+        format!("Failed to write UI state file: {e}")
+    })?;
+
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        // Clean up temp file on rename failure
+        let _ = std::fs::remove_file(&temp_path);
+        log::error!("Failed to finalize UI state file: {e}");
+        format!("Failed to finalize UI state file: {e}")
+    })?;
+
+    log::trace!("Saved UI state to {path:?}");
+    Ok(())
+}
+
+/// Remove `ui-state.<uuid>.tmp` files left behind by interrupted saves
+pub fn cleanup_stale_temp_files(dir: &Path) -> u32 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("ui-state.") && name.ends_with(".tmp") {
+            if let Err(e) = std::fs::remove_file(entry.path()) {
+                log::warn!("Failed to remove stale UI state temp file {name}: {e}");
+            } else {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+fn writer_loop() {
+    let (lock, cvar) = &*PENDING;
+    loop {
+        let mut pending = lock.lock().unwrap();
+
+        let due = loop {
+            match pending.as_ref() {
+                Some(p) => break deadline(p.first_queued, p.last_queued),
+                None => pending = cvar.wait(pending).unwrap(),
+            }
+        };
+
+        let now = Instant::now();
+        if now < due {
+            // Woken early by another update (which may move the deadline) or timed out
+            let (guard, _) = cvar.wait_timeout(pending, due - now).unwrap();
+            drop(guard);
+            continue;
+        }
+
+        // Write while holding the lock so a concurrent flush can't be
+        // overwritten by this (older) state
+        let Some(p) = pending.take() else {
+            continue;
+        };
+        if let Err(e) = write_ui_state(&p.path, &p.state) {
+            log::error!("Deferred UI state write failed: {e}");
+        }
+    }
+}
+
+/// Queue UI state to be written by the background writer
+pub fn queue_write(path: PathBuf, state: UIState) {
+    WRITER_STARTED.call_once(|| {
+        if let Some(dir) = path.parent() {
+            let removed = cleanup_stale_temp_files(dir);
+            if removed > 0 {
+                log::trace!("Removed {removed} stale UI state temp file(s)");
+            }
+        }
+        std::thread::spawn(writer_loop);
+    });
+
+    let (lock, cvar) = &*PENDING;
+    let mut pending = lock.lock().unwrap();
+    let now = Instant::now();
+    let first_queued = pending.as_ref().map_or(now, |p| p.first_queued);
+    *pending = Some(Pending {
+        path,
+        state,
+        first_queued,
+        last_queued: now,
+    });
+    cvar.notify_one();
+}
+
+/// The queued state, if it hasn't been written yet
+pub fn pending_state() -> Option<UIState> {
+    let (lock, _) = &*PENDING;
+    lock.lock().unwrap().as_ref().map(|p| p.state.clone())
+}
+
+/// Write any pending UI state immediately (on blur and exit)
+pub fn flush() {
+    let (lock, _) = &*PENDING;
+    let mut pending = lock.lock().unwrap();
+    if let Some(p) = pending.take() {
+        log::trace!("Flushing pending UI state");
+        if let Err(e) = write_ui_state(&p.path, &p.state) {
+            log::error!("Failed to flush UI state: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_debounces_and_caps() {
+        let start = Instant::now();
+
+        // A single update waits for the debounce period
+        assert_eq!(deadline(start, start), start + DEBOUNCE);
+
+        // Constant churn can't push the write past the max delay
+        let late = start + MAX_DELAY;
+        assert_eq!(deadline(start, late), start + MAX_DELAY);
+    }
+
+    #[test]
+    fn test_write_and_cleanup_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ui-state.json");
+        std::fs::write(dir.path().join("ui-state.1234.tmp"), "{}").unwrap();
+        std::fs::write(dir.path().join("preferences.json"), "{}").unwrap();
+
+        write_ui_state(&path, &UIState::default()).unwrap();
+        assert!(path.exists());
+        assert!(!dir.path().join("ui-state.json.tmp").exists());
+
+        assert_eq!(cleanup_stale_temp_files(dir.path()), 1);
+        assert!(dir.path().join("preferences.json").exists());
+    }
+}
@@ -0,0 +1,102 @@
+//! Tauri commands for app updates
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::{
+    build_update_info, cancel_deferred_install, check_channel, defer_install, download_and_install,
+    fetch_release_notes, has_running_sessions, is_deferred_install_pending, normalize_channel,
+    InstallUpdateStatus, UpdateInfo,
+};
+use crate::http_server::EmitExt;
+
+/// Resolve the channel to use: explicit argument, then preferences, then stable
+async fn resolve_channel(app: &AppHandle, channel: Option<String>) -> String {
+    let channel = match channel {
+        Some(c) => c,
+        None => crate::load_preferences(app.clone())
+            .await
+            .map(|p| p.update_channel)
+            .unwrap_or_default(),
+    };
+    normalize_channel(&channel).to_string()
+}
+
+/// Check for an available update on the given (or preferred) channel
+#[tauri::command]
+pub async fn check_for_app_update(
+    app: AppHandle,
+    channel: Option<String>,
+) -> Result<Option<UpdateInfo>, String> {
+    let channel = resolve_channel(&app, channel).await;
+    log::trace!("Checking for updates on {channel} channel");
+
+    match check_channel(&app, &channel).await? {
+        Some(update) => Ok(Some(build_update_info(&update, &channel).await)),
+        None => Ok(None),
+    }
+}
+
+/// Get release notes for a specific version
+#[tauri::command]
+pub async fn get_release_notes(version: String) -> Result<Option<String>, String> {
+    fetch_release_notes(&version).await
+}
+
+/// Install the latest update from the given (or preferred) channel.
+///
+/// When `defer_until_idle` (or the matching preference) is set and sessions are
+/// running, the install is queued and happens once all sessions finish.
+#[tauri::command]
+pub async fn install_app_update(
+    app: AppHandle,
+    channel: Option<String>,
+    defer_until_idle: Option<bool>,
+) -> Result<InstallUpdateStatus, String> {
+    let channel = resolve_channel(&app, channel).await;
+    let defer_until_idle = match defer_until_idle {
+        Some(d) => d,
+        None => crate::load_preferences(app.clone())
+            .await
+            .map(|p| p.defer_updates_until_idle)
+            .unwrap_or(true),
+    };
+
+    let Some(update) = check_channel(&app, &channel).await? else {
+        return Ok(InstallUpdateStatus::UpToDate);
+    };
+
+    if defer_until_idle && has_running_sessions() {
+        log::trace!("Sessions running, deferring update {}", update.version);
+        defer_install(app.clone(), channel);
+        if let Err(e) = app.emit_all("updater:deferred", &update.version) {
+            log::error!("Failed to emit updater:deferred event: {e}");
+        }
+        return Ok(InstallUpdateStatus::Deferred);
+    }
+
+    download_and_install(&app, update).await?;
+    Ok(InstallUpdateStatus::Installed)
+}
+
+/// Status of the deferred update queue
+#[derive(Debug, Clone, Serialize)]
+pub struct DeferredUpdateStatus {
+    pub pending: bool,
+    pub sessions_running: bool,
+}
+
+/// Get whether a deferred update is waiting for sessions to finish
+#[tauri::command]
+pub async fn get_deferred_update_status() -> Result<DeferredUpdateStatus, String> {
+    Ok(DeferredUpdateStatus {
+        pending: is_deferred_install_pending(),
+        sessions_running: has_running_sessions(),
+    })
+}
+
+/// Cancel a deferred update install
+#[tauri::command]
+pub async fn cancel_deferred_update() -> Result<bool, String> {
+    Ok(cancel_deferred_install())
+}
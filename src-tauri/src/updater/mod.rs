@@ -0,0 +1,304 @@
+//! App update management
+//!
+//! Wraps the Tauri updater plugin with:
+//! - **Channels**: `stable` (latest GitHub release) and `beta` (prerelease manifest)
+//! - **Release notes**: surfaced before install so users know what they're getting
+//! - **Defer until idle**: an update is never installed while agent sessions are running;
+//!   instead it waits in the background and installs once all sessions finish.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{AppHandle, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::http_server::EmitExt;
+
+pub mod commands;
+
+/// Update manifest for the stable channel
+const STABLE_ENDPOINT: &str =
+    "https://github.com/coollabsio/jean/releases/latest/download/latest.json";
+
+/// Update manifest for the beta channel (published under the rolling `beta` tag)
+const BETA_ENDPOINT: &str = "https://github.com/coollabsio/jean/releases/download/beta/latest.json";
+
+/// GitHub API URL for fetching release notes by tag
+const GITHUB_RELEASES_API: &str = "https://api.github.com/repos/coollabsio/jean/releases";
+
+/// How often to check whether sessions have finished while an update is deferred
+const IDLE_CHECK_INTERVAL_SECS: u64 = 10;
+
+/// Set while a deferred install is waiting for sessions to finish
+static DEFERRED_UPDATE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Set to cancel a pending deferred install
+static DEFERRED_UPDATE_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Information about an available update
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: String,
+    /// Release notes (markdown) from the manifest or GitHub release
+    pub release_notes: Option<String>,
+    /// Publication date in RFC 3339 format
+    pub date: Option<String>,
+}
+
+/// Result of requesting an update install
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallUpdateStatus {
+    /// Update was downloaded and installed; app needs a restart
+    Installed,
+    /// Sessions are running; install will happen once they finish
+    Deferred,
+    /// No update available on the selected channel
+    UpToDate,
+}
+
+/// Progress event payload for update downloads
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgress {
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+/// Normalize a channel name, falling back to stable for unknown values
+pub fn normalize_channel(channel: &str) -> &'static str {
+    match channel {
+        "beta" => "beta",
+        _ => "stable",
+    }
+}
+
+fn endpoint_for_channel(channel: &str) -> &'static str {
+    match normalize_channel(channel) {
+        "beta" => BETA_ENDPOINT,
+        _ => STABLE_ENDPOINT,
+    }
+}
+
+/// Check the given channel for an available update
+pub async fn check_channel(app: &AppHandle, channel: &str) -> Result<Option<Update>, String> {
+    let endpoint =
+        Url::parse(endpoint_for_channel(channel)).map_err(|e| format!("Invalid endpoint: {e}"))?;
+
+    let updater = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("Failed to configure updater: {e}"))?
+        .build()
+        .map_err(|e| format!("Failed to build updater: {e}"))?;
+
+    updater
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {e}"))
+}
+
+/// Fetch release notes for a version from GitHub releases
+pub async fn fetch_release_notes(version: &str) -> Result<Option<String>, String> {
+    let tag = if version.starts_with('v') {
+        version.to_string()
+    } else {
+        format!("v{version}")
+    };
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{GITHUB_RELEASES_API}/tags/{tag}"))
+        .header("User-Agent", "Jean-App")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch release notes: {e}"))?;
+
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+
+    let release: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse release notes: {e}"))?;
+
+    Ok(release
+        .get("body")
+        .and_then(|b| b.as_str())
+        .map(|s| s.to_string())
+        .filter(|s| !s.trim().is_empty()))
+}
+
+/// Build the frontend-facing info for an update, resolving release notes
+pub async fn build_update_info(update: &Update, channel: &str) -> UpdateInfo {
+    let mut release_notes = update.body.clone().filter(|b| !b.trim().is_empty());
+    if release_notes.is_none() {
+        release_notes = fetch_release_notes(&update.version)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("{e}");
+                None
+            });
+    }
+
+    UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        channel: normalize_channel(channel).to_string(),
+        release_notes,
+        date: update.date.map(|d| d.to_string()),
+    }
+}
+
+/// Whether any agent sessions are currently running or queued to run
+pub fn has_running_sessions() -> bool {
+    crate::chat::has_running_sessions()
+}
+
+/// Download and install an update, emitting progress events
+pub async fn download_and_install(app: &AppHandle, update: Update) -> Result<(), String> {
+    let version = update.version.clone();
+    let mut downloaded: u64 = 0;
+
+    update
+        .download_and_install(
+            |chunk_length, content_length| {
+                downloaded += chunk_length as u64;
+                let progress = UpdateProgress {
+                    version: version.clone(),
+                    downloaded,
+                    total: content_length,
+                };
+                if let Err(e) = app.emit_all("updater:progress", &progress) {
+                    log::warn!("Failed to emit update progress: {e}");
+                }
+            },
+            || log::trace!("Update download finished, installing"),
+        )
+        .await
+        .map_err(|e| format!("Failed to install update: {e}"))?;
+
+    if let Err(e) = app.emit_all("updater:installed", &version) {
+        log::error!("Failed to emit updater:installed event: {e}");
+    }
+
+    Ok(())
+}
+
+/// Mark a deferred install as pending, clearing any earlier cancellation.
+/// Returns false if one is already pending.
+fn begin_deferred() -> bool {
+    if DEFERRED_UPDATE_PENDING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    DEFERRED_UPDATE_CANCELLED.store(false, Ordering::SeqCst);
+    true
+}
+
+/// Whether the pending deferred install was cancelled; if so it is no longer
+/// pending
+fn deferred_cancelled() -> bool {
+    let cancelled = DEFERRED_UPDATE_CANCELLED.load(Ordering::SeqCst);
+    if cancelled {
+        log::trace!("Deferred update cancelled");
+        DEFERRED_UPDATE_PENDING.store(false, Ordering::SeqCst);
+    }
+    cancelled
+}
+
+/// Queue an update to be installed once no sessions are running.
+///
+/// Returns false if a deferred install is already pending.
+pub fn defer_install(app: AppHandle, channel: String) -> bool {
+    if !begin_deferred() {
+        return false;
+    }
+
+    std::thread::spawn(move || {
+        loop {
+            if deferred_cancelled() {
+                return;
+            }
+            if !has_running_sessions() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_secs(IDLE_CHECK_INTERVAL_SECS));
+        }
+
+        log::trace!("Sessions idle, installing deferred update");
+        let result = tauri::async_runtime::block_on(async {
+            match check_channel(&app, &channel).await? {
+                // Cancelled while the update was being checked
+                Some(_) if deferred_cancelled() => Ok(()),
+                Some(update) => download_and_install(&app, update).await,
+                None => Ok(()),
+            }
+        });
+
+        if let Err(e) = result {
+            log::error!("Deferred update failed: {e}");
+            if let Err(emit_err) = app.emit_all("updater:error", &e) {
+                log::error!("Failed to emit updater:error event: {emit_err}");
+            }
+        }
+
+        DEFERRED_UPDATE_PENDING.store(false, Ordering::SeqCst);
+    });
+
+    true
+}
+
+/// Cancel a pending deferred install. Returns whether one was pending.
+pub fn cancel_deferred_install() -> bool {
+    let pending = DEFERRED_UPDATE_PENDING.load(Ordering::SeqCst);
+    if pending {
+        DEFERRED_UPDATE_CANCELLED.store(true, Ordering::SeqCst);
+    }
+    pending
+}
+
+/// Whether a deferred install is waiting for sessions to finish
+pub fn is_deferred_install_pending() -> bool {
+    DEFERRED_UPDATE_PENDING.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_endpoints() {
+        assert_eq!(normalize_channel("beta"), "beta");
+        assert_eq!(normalize_channel("stable"), "stable");
+        assert_eq!(normalize_channel("nightly"), "stable");
+        assert_eq!(endpoint_for_channel("beta"), BETA_ENDPOINT);
+        assert_eq!(endpoint_for_channel("stable"), STABLE_ENDPOINT);
+        assert_eq!(endpoint_for_channel(""), STABLE_ENDPOINT);
+    }
+
+    #[test]
+    fn test_deferred_install_state() {
+        assert!(!is_deferred_install_pending());
+        assert!(!cancel_deferred_install());
+
+        assert!(begin_deferred());
+        assert!(is_deferred_install_pending());
+        // Only one deferred install at a time
+        assert!(!begin_deferred());
+        assert!(!deferred_cancelled());
+
+        assert!(cancel_deferred_install());
+        assert!(deferred_cancelled());
+        assert!(!is_deferred_install_pending());
+
+        // A new deferral starts uncancelled
+        assert!(begin_deferred());
+        assert!(!deferred_cancelled());
+        assert!(cancel_deferred_install());
+        assert!(deferred_cancelled());
+    }
+}
@@ -30,6 +30,17 @@ import { useAutoArchiveOnMerge } from './hooks/useAutoArchiveOnMerge'
 import useStreamingEvents from './components/chat/hooks/useStreamingEvents'
 import { preloadAllSounds } from './lib/sounds'
 
+/** Update info returned by the backend `check_for_app_update` command. */
+interface AppUpdateInfo {
+  version: string
+  current_version: string
+  channel: string
+  release_notes: string | null
+  date: string | null
+}
+
+type InstallUpdateStatus = 'installed' | 'deferred' | 'up_to_date'
+
 /** Loading screen shown while preloading initial data (browser mode only). */
 function WebLoadingScreen() {
   return (
@@ -405,35 +416,38 @@ function App() {
       if (!isNativeApp()) return
 
       try {
-        const { check } = await import('@tauri-apps/plugin-updater')
         const { ask, message } = await import('@tauri-apps/plugin-dialog')
 
-        const update = await check()
+        const update = await invoke<AppUpdateInfo | null>(
+          'check_for_app_update'
+        )
         if (update) {
           logger.info(`Update available: ${update.version}`)
 
-          // Show confirmation dialog
+          const notes = update.release_notes
+            ? `\n\n${update.release_notes.slice(0, 1500)}`
+            : ''
+
+          // Show confirmation dialog with release notes
           const shouldUpdate = await ask(
-            `Update available: ${update.version}\n\nWould you like to install this update now?`,
+            `Update available: ${update.version}${notes}\n\nWould you like to install this update now?`,
             { title: 'Update Available', kind: 'info' }
           )
 
           if (shouldUpdate) {
             try {
-              // Download and install with progress logging
-              await update.downloadAndInstall(event => {
-                switch (event.event) {
-                  case 'Started':
-                    logger.info(`Downloading ${event.data.contentLength} bytes`)
-                    break
-                  case 'Progress':
-                    logger.info(`Downloaded: ${event.data.chunkLength} bytes`)
-                    break
-                  case 'Finished':
-                    logger.info('Download complete, installing...')
-                    break
-                }
-              })
+              // Installs now, or waits until running sessions finish
+              const status =
+                await invoke<InstallUpdateStatus>('install_app_update')
+
+              if (status === 'deferred') {
+                await message(
+                  'Sessions are still running. The update will install automatically once they finish.',
+                  { title: 'Update Deferred', kind: 'info' }
+                )
+                return
+              }
+              if (status !== 'installed') return
 
               // Ask if user wants to restart now
               const shouldRestart = await ask(
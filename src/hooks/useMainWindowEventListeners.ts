@@ -460,8 +460,9 @@ export function useMainWindowEventListeners() {
           logger.debug('Check for updates menu event received')
           if (!isNativeApp()) return
           try {
-            const { check } = await import('@tauri-apps/plugin-updater')
-            const update = await check()
+            const update = await invoke<{ version: string } | null>(
+              'check_for_app_update'
+            )
             if (update) {
               commandContext.showToast(
                 `Update available: ${update.version}`,